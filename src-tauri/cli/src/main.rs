@@ -0,0 +1,201 @@
+// ASTRAL CLI
+// Talks to a running ASTRAL through its local API (the `api.enabled`
+// setting) so scripts and terminals can chat, run commands and routines,
+// speak and transcribe. The port and token are read from ASTRAL's saved
+// settings unless given with --port / --token or the ASTRAL_API_PORT /
+// ASTRAL_API_TOKEN environment variables.
+
+use reqwest::blocking::Client;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// Tauri's bundle identifier; the settings store lives under it
+const APP_IDENTIFIER: &str = "com.astral.app";
+const DEFAULT_PORT: u16 = 7865;
+
+const USAGE: &str = "Usage: astral-cli [--port N] [--token T] <command> [args]
+
+Commands:
+  chat <message>         Ask the LLM; prints the reply
+  command <text>         Run text as if it were spoken; prints the reply
+  speak <text>           Say text out loud
+  transcribe <file>      Transcribe an audio file with Whisper
+  routines               List automation routines
+  run <routine-id>       Run an automation routine
+
+Use - as the text to read it from stdin.";
+
+struct Connection {
+    client: Client,
+    base: String,
+    token: String,
+}
+
+impl Connection {
+    fn post_json(&self, path: &str, body: Value) -> Result<Value, String> {
+        let response = self
+            .client
+            .post(format!("{}{}", self.base, path))
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send();
+        read_response(response)
+    }
+
+    fn post_bytes(&self, path: &str, body: Vec<u8>) -> Result<Value, String> {
+        let response = self
+            .client
+            .post(format!("{}{}", self.base, path))
+            .bearer_auth(&self.token)
+            .body(body)
+            .send();
+        read_response(response)
+    }
+
+    fn get(&self, path: &str) -> Result<Value, String> {
+        let response = self.client.get(format!("{}{}", self.base, path)).bearer_auth(&self.token).send();
+        read_response(response)
+    }
+}
+
+fn read_response(response: reqwest::Result<reqwest::blocking::Response>) -> Result<Value, String> {
+    let response = response.map_err(|e| {
+        if e.is_connect() {
+            "ASTRAL isn't reachable. Is it running with the local API enabled?".to_string()
+        } else {
+            e.to_string()
+        }
+    })?;
+    let status = response.status();
+    let text = response.text().map_err(|e| e.to_string())?;
+    let body: Value = if text.is_empty() { Value::Null } else { serde_json::from_str(&text).unwrap_or(Value::String(text)) };
+
+    if status.is_success() {
+        Ok(body)
+    } else {
+        let message = body.get("error").and_then(Value::as_str).map(str::to_string).unwrap_or_else(|| body.to_string());
+        Err(format!("{} ({})", message, status))
+    }
+}
+
+/// The `api` section of ASTRAL's saved settings, if it has run on this machine
+fn saved_api_settings() -> Option<Value> {
+    let path: PathBuf = dirs::data_dir()?.join(APP_IDENTIFIER).join("settings.json");
+    let content = std::fs::read_to_string(path).ok()?;
+    let store: Value = serde_json::from_str(&content).ok()?;
+    store.get("app_settings")?.get("api").cloned()
+}
+
+/// The rest of the arguments as one string, or stdin for "-"
+fn text_arg(args: &[String]) -> Result<String, String> {
+    let text = if args == ["-"] {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input).map_err(|e| e.to_string())?;
+        input
+    } else {
+        args.join(" ")
+    };
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(text)
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut port: Option<u16> = std::env::var("ASTRAL_API_PORT").ok().and_then(|p| p.parse().ok());
+    let mut token: Option<String> = std::env::var("ASTRAL_API_TOKEN").ok().filter(|t| !t.is_empty());
+    let mut rest = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => port = Some(args.next().and_then(|p| p.parse().ok()).ok_or("--port needs a number")?),
+            "--token" => token = Some(args.next().ok_or("--token needs a value")?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => rest.push(arg),
+        }
+    }
+    let Some((command, rest)) = rest.split_first() else {
+        return Err(USAGE.to_string());
+    };
+
+    let settings = saved_api_settings();
+    let port = port
+        .or_else(|| settings.as_ref()?.get("port")?.as_u64().and_then(|p| u16::try_from(p).ok()))
+        .unwrap_or(DEFAULT_PORT);
+    let token = token
+        .or_else(|| settings.as_ref()?.get("token")?.as_str().filter(|t| !t.is_empty()).map(str::to_string))
+        .ok_or("No API token found. Enable the local API in ASTRAL, or pass --token")?;
+
+    let connection = Connection {
+        // Chat and transcription can take a while on local models
+        client: Client::builder().timeout(Duration::from_secs(120)).build().map_err(|e| e.to_string())?,
+        base: format!("http://127.0.0.1:{}", port),
+        token,
+    };
+
+    match command.as_str() {
+        "chat" => {
+            let reply = connection.post_json("/v1/chat", json!({ "message": text_arg(rest)? }))?;
+            println!("{}", reply.get("content").and_then(Value::as_str).unwrap_or_default());
+        }
+        "command" => {
+            let reply = connection.post_json("/v1/command", json!({ "command": text_arg(rest)? }))?;
+            println!("{}", reply.get("response").and_then(Value::as_str).unwrap_or_default());
+        }
+        "speak" => {
+            connection.post_json("/v1/speak", json!({ "text": text_arg(rest)? }))?;
+        }
+        "transcribe" => {
+            let [path] = rest else {
+                return Err(USAGE.to_string());
+            };
+            let audio = std::fs::read(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+            let reply = connection.post_bytes("/v1/transcribe", audio)?;
+            println!("{}", reply.get("text").and_then(Value::as_str).unwrap_or_default());
+        }
+        "routines" => {
+            let routines = connection.get("/v1/routines")?;
+            for routine in routines.as_array().into_iter().flatten() {
+                let field = |key: &str| routine.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+                let enabled = routine.get("enabled").and_then(Value::as_bool).unwrap_or(false);
+                println!("{:<20} {}{}", field("id"), field("name"), if enabled { "" } else { " (disabled)" });
+            }
+        }
+        "run" => {
+            let [id] = rest else {
+                return Err(USAGE.to_string());
+            };
+            let result = connection.post_json(&format!("/v1/routines/{}/run", id), Value::Null)?;
+            let errors: Vec<&str> = result
+                .get("errors")
+                .and_then(Value::as_array)
+                .map(|e| e.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            if result.get("success").and_then(Value::as_bool).unwrap_or(false) {
+                println!("Ran {}", id);
+            } else {
+                return Err(format!("{} failed: {}", id, errors.join("; ")));
+            }
+        }
+        _ => return Err(USAGE.to_string()),
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
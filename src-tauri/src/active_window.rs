@@ -0,0 +1,218 @@
+// Active Window Module
+// Tracks the foreground window (title + process) so the assistant knows what
+// "this page" or "this document" means, and routines can depend on the app in front.
+// ASTRAL's own window is ignored, so talking to it doesn't hide the app you came from.
+
+use tracing::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// `{{active_window}}` in the system prompt
+pub const ACTIVE_WINDOW_VARIABLE: &str = "active_window";
+pub const ACTIVE_WINDOW_EVENT: &str = "active-window-changed";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveWindow {
+    pub title: String,
+    /// Executable name without extension ("chrome", "Code")
+    pub process_name: String,
+    pub pid: u32,
+}
+
+impl ActiveWindow {
+    /// "chrome: \"Rust Book - Chapter 4\""
+    pub fn summary(&self) -> String {
+        if self.title.is_empty() {
+            self.process_name.clone()
+        } else {
+            format!("{}: \"{}\"", self.process_name, self.title)
+        }
+    }
+
+    /// Whether a spoken or configured app name refers to this window's app.
+    /// Matches the process name, or the window title for browser tabs and documents.
+    pub fn matches_app(&self, app_name: &str) -> bool {
+        let query = app_name.trim().to_lowercase();
+        let query = query.strip_suffix(".exe").unwrap_or(&query);
+        if query.is_empty() {
+            return false;
+        }
+        let process = self.process_name.to_lowercase();
+        process == query || process.contains(query) || self.title.to_lowercase().contains(query)
+    }
+}
+
+static CURRENT: Lazy<Mutex<Option<ActiveWindow>>> = Lazy::new(|| Mutex::new(None));
+
+/// The last foreground window that wasn't ASTRAL
+pub fn current() -> Option<ActiveWindow> {
+    CURRENT.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Value for the `{{active_window}}` prompt variable
+pub fn active_window_variable() -> String {
+    current().map(|w| w.summary()).unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Poll the foreground window and emit `active-window-changed` when it switches
+pub fn start_tracking(app: AppHandle) {
+    let own_pid = std::process::id();
+
+    std::thread::spawn(move || {
+        info!("Tracking the active window");
+        let mut warned = false;
+
+        loop {
+            match platform::foreground_window() {
+                Ok(Some(window)) if window.pid != own_pid => {
+                    let changed = {
+                        let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+                        let changed = current.as_ref() != Some(&window);
+                        *current = Some(window.clone());
+                        changed
+                    };
+                    if changed {
+                        let _ = app.emit(ACTIVE_WINDOW_EVENT, &window);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    // Usually a missing tool; don't repeat it every second
+                    if !warned {
+                        warn!("Couldn't read the active window: {}", e);
+                        warned = true;
+                    }
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::ActiveWindow;
+    use anyhow::Result;
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId};
+
+    fn process_name(pid: u32) -> Option<String> {
+        unsafe {
+            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+            let mut buffer = [0u16; 1024];
+            let mut len = buffer.len() as u32;
+            let result = QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut len);
+            let _ = CloseHandle(handle);
+            result.ok()?;
+
+            let path = String::from_utf16_lossy(&buffer[..len as usize]);
+            std::path::Path::new(&path).file_stem().map(|s| s.to_string_lossy().to_string())
+        }
+    }
+
+    pub fn foreground_window() -> Result<Option<ActiveWindow>> {
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0 == 0 {
+                return Ok(None);
+            }
+
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+            let mut buffer = [0u16; 512];
+            let len = GetWindowTextW(hwnd, &mut buffer);
+            let title = String::from_utf16_lossy(&buffer[..len.max(0) as usize]);
+
+            Ok(Some(ActiveWindow {
+                title,
+                process_name: process_name(pid).unwrap_or_default(),
+                pid,
+            }))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::ActiveWindow;
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    // Window titles need the Accessibility permission; without it only the app is known
+    const SCRIPT: &str = r#"tell application "System Events"
+set proc to first application process whose frontmost is true
+set windowTitle to ""
+try
+set windowTitle to name of front window of proc
+end try
+return (unix id of proc as text) & linefeed & name of proc & linefeed & windowTitle
+end tell"#;
+
+    pub fn foreground_window() -> Result<Option<ActiveWindow>> {
+        let output = Command::new("osascript").args(["-e", SCRIPT]).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        let pid = match lines.next().and_then(|l| l.trim().parse().ok()) {
+            Some(pid) => pid,
+            None => return Ok(None),
+        };
+        Ok(Some(ActiveWindow {
+            pid,
+            process_name: lines.next().unwrap_or_default().trim().to_string(),
+            title: lines.next().unwrap_or_default().trim().to_string(),
+        }))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::ActiveWindow;
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    // X11 only; Wayland compositors don't expose the focused window to clients
+    fn xdotool(args: &[&str]) -> Result<Option<String>> {
+        let output = match Command::new("xdotool").args(args).output() {
+            Ok(output) => output,
+            Err(_) => bail!("Install xdotool to track the active window"),
+        };
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(Some(String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    pub fn foreground_window() -> Result<Option<ActiveWindow>> {
+        let pid: u32 = match xdotool(&["getactivewindow", "getwindowpid"])?.and_then(|p| p.parse().ok()) {
+            Some(pid) => pid,
+            None => return Ok(None),
+        };
+        let title = xdotool(&["getactivewindow", "getwindowname"])?.unwrap_or_default();
+        let process_name = std::fs::read_to_string(format!("/proc/{}/comm", pid))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+
+        Ok(Some(ActiveWindow { title, process_name, pid }))
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_active_window() -> Result<Option<ActiveWindow>, String> {
+    Ok(current())
+}
@@ -0,0 +1,216 @@
+// Alerts Module
+// Threshold rules ("CPU above 90% for 5 minutes") checked against every
+// monitor sample. A rule that stays breached for its duration fires once per
+// episode: an `alert-triggered` event for the UI, its system event for
+// routines, and optionally a spoken warning and a push to the webhooks.
+
+use crate::system_monitor::SystemStats;
+use tracing::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const ALERT_EVENT: &str = "alert-triggered";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    /// Overall CPU usage, percent
+    Cpu,
+    /// The busiest single core, percent
+    CpuCore,
+    /// RAM usage, percent
+    Memory,
+    /// Free space on the emptiest fixed drive, GB
+    DiskFreeGb,
+    /// The fastest fan, RPM. Reading fans on Windows launches PowerShell, so
+    /// only enable this where it's wanted.
+    FanRpm,
+}
+
+impl AlertMetric {
+    fn describe(&self, value: f32) -> String {
+        match self {
+            Self::Cpu => format!("CPU usage is at {:.0} percent", value),
+            Self::CpuCore => format!("a CPU core is pegged at {:.0} percent", value),
+            Self::Memory => format!("memory usage is at {:.0} percent", value),
+            Self::DiskFreeGb => format!("a drive is down to {:.0} GB free", value),
+            Self::FanRpm => format!("a fan has spun up to {:.0} RPM", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comparison {
+    Above,
+    Below,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub enabled: bool,
+    pub metric: AlertMetric,
+    pub comparison: Comparison,
+    pub threshold: f32,
+    /// How long the threshold has to stay crossed; 0 fires on the first sample
+    pub duration_secs: u64,
+    /// System event fired for routines, e.g. "cpu_high"
+    pub event: String,
+    pub speak: bool,
+    /// Also send the warning to every enabled webhook
+    #[serde(default)]
+    pub push: bool,
+}
+
+impl AlertRule {
+    fn new(name: &str, metric: AlertMetric, comparison: Comparison, threshold: f32, duration_secs: u64, event: &str, speak: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            enabled: true,
+            metric,
+            comparison,
+            threshold,
+            duration_secs,
+            event: event.to_string(),
+            speak,
+            push: false,
+        }
+    }
+
+    fn breached(&self, value: f32) -> bool {
+        match self.comparison {
+            Comparison::Above => value > self.threshold,
+            Comparison::Below => value < self.threshold,
+        }
+    }
+}
+
+pub fn default_rules() -> Vec<AlertRule> {
+    vec![
+        AlertRule::new("High CPU", AlertMetric::Cpu, Comparison::Above, 90.0, 5 * 60, "cpu_high", true),
+        AlertRule::new("CPU core saturated", AlertMetric::CpuCore, Comparison::Above, 95.0, 30, "cpu_core_saturated", false),
+        AlertRule::new("High memory", AlertMetric::Memory, Comparison::Above, 95.0, 60, "memory_high", true),
+        AlertRule::new("Low disk space", AlertMetric::DiskFreeGb, Comparison::Below, 10.0, 0, "disk_space_low", true),
+        // What counts as a spike depends on the machine, so this one starts off
+        AlertRule {
+            enabled: false,
+            ..AlertRule::new("Fan spike", AlertMetric::FanRpm, Comparison::Above, 4000.0, 60, "fan_spike", true)
+        },
+    ]
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertEvent {
+    pub rule: String,
+    pub metric: AlertMetric,
+    pub value: f32,
+    pub threshold: f32,
+    pub event: String,
+}
+
+#[derive(Default)]
+struct RuleState {
+    breached_since: Option<Instant>,
+    fired: bool,
+}
+
+/// Per-rule breach tracking, owned by the monitor loop
+#[derive(Default)]
+pub struct AlertTracker {
+    rules: HashMap<String, RuleState>,
+}
+
+fn metric_value(metric: AlertMetric, stats: &SystemStats) -> Option<f32> {
+    match metric {
+        AlertMetric::Cpu => Some(stats.cpu_usage),
+        AlertMetric::CpuCore => stats.cores.iter().map(|c| c.usage).max_by(|a, b| a.total_cmp(b)),
+        AlertMetric::Memory => Some(stats.memory_usage),
+        AlertMetric::DiskFreeGb => crate::disks::disk_stats()
+            .iter()
+            .filter(|d| !d.removable)
+            .map(|d| d.free as f32 / (1024.0 * 1024.0 * 1024.0))
+            .min_by(|a, b| a.total_cmp(b)),
+        AlertMetric::FanRpm => crate::sensors::fans().iter().map(|f| f.rpm as f32).max_by(|a, b| a.total_cmp(b)),
+    }
+}
+
+impl AlertTracker {
+    /// Rules that have just fired for this sample. Blocking (disk rules read the drives).
+    pub fn check(&mut self, rules: &[AlertRule], stats: &SystemStats) -> Vec<AlertEvent> {
+        let mut fired = Vec::new();
+        for rule in rules.iter().filter(|r| r.enabled) {
+            let state = self.rules.entry(rule.name.clone()).or_default();
+            let Some(value) = metric_value(rule.metric, stats) else { continue };
+
+            if !rule.breached(value) {
+                *state = RuleState::default();
+                continue;
+            }
+            let since = *state.breached_since.get_or_insert_with(Instant::now);
+            if !state.fired && since.elapsed() >= Duration::from_secs(rule.duration_secs) {
+                state.fired = true;
+                fired.push(AlertEvent {
+                    rule: rule.name.clone(),
+                    metric: rule.metric,
+                    value,
+                    threshold: rule.threshold,
+                    event: rule.event.clone(),
+                });
+            }
+        }
+        // Forget rules that were removed or renamed
+        self.rules.retain(|name, _| rules.iter().any(|r| &r.name == name));
+        fired
+    }
+}
+
+/// Tell the UI, routines and (per rule) the user about a fired alert
+pub async fn raise(app: &AppHandle, rules: &[AlertRule], alert: AlertEvent) {
+    info!("Alert '{}': {:.1} (threshold {:.1})", alert.rule, alert.value, alert.threshold);
+    let _ = app.emit(ALERT_EVENT, &alert);
+
+    let rule = rules.iter().find(|r| r.name == alert.rule);
+    let warning = format!("Heads up, {}.", alert.metric.describe(alert.value));
+    if rule.is_some_and(|r| r.speak) {
+        crate::voice::speak(&warning);
+    }
+    if rule.is_some_and(|r| r.push) {
+        let app = app.clone();
+        let title = format!("ASTRAL: {}", alert.rule);
+        tokio::spawn(async move {
+            if let Err(e) = crate::webhooks::send(&app, &title, &warning, &[]).await {
+                warn!("Couldn't push alert '{}': {}", title, e);
+            }
+        });
+    }
+    if !alert.event.is_empty() {
+        crate::commands::fire_system_event(app, &alert.event).await;
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_alert_rules(app: AppHandle) -> Result<Vec<AlertRule>, String> {
+    Ok(crate::settings::load_settings(app).await?.alert_rules)
+}
+
+#[tauri::command]
+pub async fn set_alert_rules(app: AppHandle, rules: Vec<AlertRule>) -> Result<(), String> {
+    if let Some(rule) = rules.iter().find(|r| r.name.trim().is_empty()) {
+        return Err(format!("Alert rule for {:?} has no name", rule.metric));
+    }
+    // Breach tracking is keyed by name
+    let mut names = HashSet::new();
+    if let Some(rule) = rules.iter().find(|r| !names.insert(r.name.as_str())) {
+        return Err(format!("More than one alert rule is named {}", rule.name));
+    }
+    crate::settings::modify_settings(&app, |settings| {
+        settings.system.alert_rules = rules;
+        Ok(())
+    })
+    .await
+}
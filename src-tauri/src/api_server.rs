@@ -0,0 +1,499 @@
+// Local Control API
+// An optional HTTP + WebSocket server on 127.0.0.1 so scripts, stream decks
+// and other local tools can drive the assistant: chat, run a command, speak,
+// transcribe and run routines, plus a live stream of the app's events and an
+// MCP endpoint (see mcp.rs).
+// Off unless `api.enabled`; every request needs the `api.token` as a bearer
+// token (or `?token=` on the WebSocket, which browsers can't add headers to).
+// Enabling it or changing the port takes effect on the next launch.
+// `api.remote` adds a second listener for other devices on the LAN or
+// tailnet, HTTPS only, with its own token and an allowlist of routes
+// (remote_access.rs).
+
+use crate::error::{AstralError, ErrorCode};
+use crate::remote_access::{RemoteAccessSettings, RemoteCapability};
+use crate::state::AppState;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use lazy_static::lazy_static;
+use tracing::{info, warn};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::RwLock;
+use tauri::{AppHandle, Listener, Manager};
+use tokio::sync::broadcast;
+
+const TOKEN_LENGTH: usize = 32;
+/// The same cap the Whisper API puts on uploads
+const MAX_AUDIO_BYTES: usize = 25 * 1024 * 1024;
+const EVENT_BUFFER: usize = 256;
+
+/// Forwarded to `/v1/events` subscribers as `{"event": .., "payload": ..}`
+const STREAMED_EVENTS: &[&str] = &[
+    crate::voice::STATE_CHANGED_EVENT,
+    crate::voice::bus::PIPELINE_EVENT,
+    crate::voice::MUTE_CHANGED_EVENT,
+    crate::voice::PAUSE_CHANGED_EVENT,
+    crate::profiles::PROFILE_CHANGED_EVENT,
+    crate::privacy::PRIVACY_MODE_EVENT,
+    crate::sync::SYNC_COMPLETED_EVENT,
+    crate::notes::NOTES_CHANGED_EVENT,
+    crate::voice::SPEAK_EVENT,
+    crate::system_monitor::SYSTEM_STATS_EVENT,
+    crate::alerts::ALERT_EVENT,
+    crate::network::NETWORK_CHANGED_EVENT,
+    crate::system_events::DEVICE_CHANGED_EVENT,
+    crate::system_events::SESSION_CHANGED_EVENT,
+    crate::active_window::ACTIVE_WINDOW_EVENT,
+    crate::appearance::THEME_EVENT,
+    crate::dictation::DICTATION_TOGGLED_EVENT,
+];
+
+lazy_static! {
+    /// The token the running server accepts; replaced by `regenerate_api_token`
+    static ref ACTIVE_TOKEN: RwLock<String> = RwLock::new(String::new());
+    /// Token and allowlist the running remote listener checks, `None` when it isn't running
+    static ref REMOTE_POLICY: RwLock<Option<RemoteAccessSettings>> = RwLock::new(None);
+}
+
+#[derive(Clone)]
+struct ApiContext {
+    app: AppHandle,
+    events: broadcast::Sender<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiStatus {
+    pub enabled: bool,
+    pub port: u16,
+    pub token: String,
+    pub url: String,
+    pub mcp_url: String,
+    pub remote: RemoteStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteStatus {
+    pub enabled: bool,
+    /// Whether the remote listener is up (it starts with the app)
+    pub running: bool,
+    pub token: String,
+    pub url: String,
+    pub capabilities: Vec<RemoteCapability>,
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+}
+
+impl From<AstralError> for ApiError {
+    fn from(error: AstralError) -> Self {
+        let status = match error.code {
+            ErrorCode::NotConfigured | ErrorCode::Muted | ErrorCode::Cancelled => StatusCode::CONFLICT,
+            ErrorCode::InvalidInput => StatusCode::BAD_REQUEST,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::PermissionDenied | ErrorCode::PrivacyMode => StatusCode::FORBIDDEN,
+            ErrorCode::SpendingCap => StatusCode::PAYMENT_REQUIRED,
+            ErrorCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+            ErrorCode::ServiceUnavailable | ErrorCode::RateLimited => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::Network | ErrorCode::Unauthorized | ErrorCode::ProviderError => StatusCode::BAD_GATEWAY,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(status, error.message)
+    }
+}
+
+type ApiResult = Result<Json<Value>, ApiError>;
+
+#[derive(Deserialize)]
+struct ChatRequest {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct CommandRequest {
+    command: String,
+}
+
+#[derive(Deserialize)]
+struct SpeakRequest {
+    text: String,
+}
+
+fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Compare without returning early, so response timing doesn't leak the prefix
+fn tokens_match(given: &str, expected: &str) -> bool {
+    !expected.is_empty()
+        && given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// The saved token, generating and saving one the first time
+async fn ensure_token(app: &AppHandle) -> Result<String, String> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    if !settings.api.token.is_empty() {
+        return Ok(settings.api.token);
+    }
+    crate::settings::modify_settings(app, |settings| {
+        if settings.api.token.is_empty() {
+            settings.api.token = generate_token();
+        }
+        Ok(settings.api.token.clone())
+    })
+    .await
+}
+
+/// The remote token, generating and saving one the first time
+async fn ensure_remote_token(app: &AppHandle) -> Result<String, String> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    if !settings.api.remote.token.is_empty() {
+        return Ok(settings.api.remote.token);
+    }
+    crate::settings::modify_settings(app, |settings| {
+        if settings.api.remote.token.is_empty() {
+            settings.api.remote.token = generate_token();
+        }
+        Ok(settings.api.remote.token.clone())
+    })
+    .await
+}
+
+/// The bearer token, or `?token=` for WebSocket clients
+fn presented_token(request: &Request) -> Option<String> {
+    let bearer = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string);
+    let query = request.uri().query().and_then(|q| {
+        q.split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "token")
+            .map(|(_, value)| value.to_string())
+    });
+    bearer.or(query)
+}
+
+async fn require_token(request: Request, next: Next) -> Response {
+    let expected = ACTIVE_TOKEN.read().map(|t| t.clone()).unwrap_or_default();
+    match presented_token(&request) {
+        Some(token) if tokens_match(&token, &expected) => next.run(request).await,
+        _ => ApiError(StatusCode::UNAUTHORIZED, "Missing or invalid API token".to_string()).into_response(),
+    }
+}
+
+/// Remote listener: the peer must be on the LAN or tailnet, present the
+/// remote token, and call a route on the allowlist
+async fn require_remote_access(ConnectInfo(peer): ConnectInfo<SocketAddr>, request: Request, next: Next) -> Response {
+    let Some(policy) = REMOTE_POLICY.read().ok().and_then(|p| p.clone()) else {
+        return ApiError(StatusCode::SERVICE_UNAVAILABLE, "Remote access is off".to_string()).into_response();
+    };
+    if let Some(reason) = crate::remote_access::refuse_peer(peer.ip()) {
+        warn!("Refused remote API request from {}: {}", peer, reason);
+        return ApiError(StatusCode::FORBIDDEN, reason.to_string()).into_response();
+    }
+    match presented_token(&request) {
+        Some(token) if tokens_match(&token, &policy.token) => {}
+        _ => {
+            warn!("Remote API request from {} with a missing or invalid token", peer);
+            return ApiError(StatusCode::UNAUTHORIZED, "Missing or invalid API token".to_string()).into_response();
+        }
+    }
+
+    match RemoteCapability::for_path(request.uri().path()) {
+        Some(capability) if !policy.capabilities.contains(&capability) => ApiError(
+            StatusCode::FORBIDDEN,
+            format!("Remote clients aren't allowed to use {}", capability.describe()),
+        )
+        .into_response(),
+        // Unknown paths fall through to a 404
+        _ => {
+            info!("Remote API: {} {} from {}", request.method(), request.uri().path(), peer);
+            next.run(request).await
+        }
+    }
+}
+
+// ========== Handlers ==========
+
+async fn chat(State(ctx): State<ApiContext>, Json(body): Json<ChatRequest>) -> ApiResult {
+    let response = ctx.app.state::<AppState>().send_llm_message(&body.message).await?;
+    Ok(Json(json!(response)))
+}
+
+async fn command(State(ctx): State<ApiContext>, Json(body): Json<CommandRequest>) -> ApiResult {
+    let response = crate::commands::execute_command_from(ctx.app.clone(), "api", body.command, None).await?;
+    Ok(Json(json!({ "response": response })))
+}
+
+async fn speak(Json(body): Json<SpeakRequest>) -> Result<StatusCode, ApiError> {
+    if body.text.trim().is_empty() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, "Nothing to speak".to_string()));
+    }
+    crate::voice::speak(&body.text);
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// The request body is the raw audio file (WAV, MP3, ...)
+async fn transcribe(State(ctx): State<ApiContext>, body: axum::body::Bytes) -> ApiResult {
+    if body.is_empty() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, "No audio in the request body".to_string()));
+    }
+    let text = crate::voice::whisper::whisper_transcribe_bytes(ctx.app.clone(), body.to_vec()).await?;
+    Ok(Json(json!({ "text": text })))
+}
+
+async fn list_routines(State(ctx): State<ApiContext>) -> ApiResult {
+    let routines = ctx.app.state::<AppState>().inner().automation.lock().await.get_all_routines();
+    Ok(Json(json!(routines)))
+}
+
+async fn run_routine(State(ctx): State<ApiContext>, Path(id): Path<String>) -> ApiResult {
+    let result = crate::commands::execute_automation(ctx.app.clone(), id).await?;
+    Ok(Json(json!(result)))
+}
+
+/// MCP Streamable HTTP: one JSON-RPC message in, one JSON response out
+async fn mcp(State(ctx): State<ApiContext>, Json(message): Json<Value>) -> Response {
+    match crate::mcp::handle(&ctx.app, message).await {
+        Some(reply) => Json(reply).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+async fn events(State(ctx): State<ApiContext>, upgrade: WebSocketUpgrade) -> Response {
+    let receiver = ctx.events.subscribe();
+    upgrade.on_upgrade(move |socket| stream_events(socket, receiver))
+}
+
+async fn stream_events(mut socket: WebSocket, mut receiver: broadcast::Receiver<String>) {
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Ok(text) => {
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow client just misses the oldest events
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                _ => {}
+            },
+        }
+    }
+}
+
+/// Re-broadcast the app's own events to WebSocket subscribers
+fn forward_events(app: &AppHandle) -> broadcast::Sender<String> {
+    let (sender, _) = broadcast::channel(EVENT_BUFFER);
+    for &name in STREAMED_EVENTS {
+        let sender = sender.clone();
+        app.listen_any(name, move |event| {
+            let payload = serde_json::from_str::<Value>(event.payload()).unwrap_or(Value::Null);
+            // No subscribers is not an error
+            let _ = sender.send(json!({ "event": name, "payload": payload }).to_string());
+        });
+    }
+    sender
+}
+
+fn routes() -> Router<ApiContext> {
+    Router::new()
+        .route("/v1/chat", post(chat))
+        .route("/v1/command", post(command))
+        .route("/v1/speak", post(speak))
+        .route("/v1/transcribe", post(transcribe).layer(DefaultBodyLimit::max(MAX_AUDIO_BYTES)))
+        .route("/v1/routines", get(list_routines))
+        .route("/v1/routines/:id/run", post(run_routine))
+        .route("/v1/events", get(events))
+        .route("/mcp", post(mcp))
+}
+
+/// Serve the remote listener until it stops
+async fn serve_remote(ctx: ApiContext, remote: RemoteAccessSettings) {
+    let address = match remote.socket_address() {
+        Ok(address) => address,
+        Err(e) => {
+            warn!("Remote API not started: {}", e);
+            return;
+        }
+    };
+    let tls = match axum_server::tls_rustls::RustlsConfig::from_pem_file(&remote.tls_cert_path, &remote.tls_key_path).await {
+        Ok(tls) => tls,
+        Err(e) => {
+            warn!("Remote API couldn't load its TLS certificate: {}", e);
+            return;
+        }
+    };
+    let service = routes()
+        .layer(middleware::from_fn(require_remote_access))
+        .with_state(ctx)
+        .into_make_service_with_connect_info::<SocketAddr>();
+
+    info!("Remote API listening on https://{}", address);
+    if let Err(e) = axum_server::bind_rustls(address, tls).serve(service).await {
+        warn!("Remote API stopped: {}", e);
+    }
+    if let Ok(mut policy) = REMOTE_POLICY.write() {
+        *policy = None;
+    }
+}
+
+/// Apply a new token or allowlist to the running remote listener; called when settings change
+pub fn update_remote_policy(remote: &RemoteAccessSettings) {
+    if let Ok(mut policy) = REMOTE_POLICY.write() {
+        if let Some(active) = policy.as_mut() {
+            active.token = remote.token.clone();
+            active.capabilities = remote.capabilities.clone();
+        }
+    }
+}
+
+/// Start the server if `api.enabled`; called from setup
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let settings = match crate::settings::load_settings(app.clone()).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("Couldn't load settings for the local API: {}", e);
+                return;
+            }
+        };
+        if !settings.api.enabled {
+            return;
+        }
+
+        match ensure_token(&app).await {
+            Ok(token) => {
+                if let Ok(mut active) = ACTIVE_TOKEN.write() {
+                    *active = token;
+                }
+            }
+            Err(e) => {
+                warn!("Couldn't set up the local API token: {}", e);
+                return;
+            }
+        }
+
+        // Never bind beyond loopback: the token is the only protection
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, settings.api.port));
+        let listener = match tokio::net::TcpListener::bind(address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("Local API couldn't listen on {}: {}", address, e);
+                return;
+            }
+        };
+
+        let ctx = ApiContext { events: forward_events(&app), app: app.clone() };
+        if settings.api.remote.enabled && !settings.api.remote.uses_tls() {
+            warn!("Remote API not started: it needs a TLS certificate and key (tailscale cert makes them)");
+        } else if settings.api.remote.enabled {
+            match ensure_remote_token(&app).await {
+                Ok(token) => {
+                    let remote = RemoteAccessSettings { token, ..settings.api.remote.clone() };
+                    if let Ok(mut policy) = REMOTE_POLICY.write() {
+                        *policy = Some(remote.clone());
+                    }
+                    tauri::async_runtime::spawn(serve_remote(ctx.clone(), remote));
+                }
+                Err(e) => warn!("Couldn't set up the remote API token: {}", e),
+            }
+        }
+
+        info!("Local API listening on http://{}", address);
+        let router = routes().layer(middleware::from_fn(require_token)).with_state(ctx);
+        if let Err(e) = axum::serve(listener, router).await {
+            warn!("Local API stopped: {}", e);
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+/// Settings and token for the settings screen
+#[tauri::command]
+pub async fn get_api_status(app: AppHandle) -> Result<ApiStatus, String> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    let token = ensure_token(&app).await?;
+    let remote = &settings.api.remote;
+    let remote_token = if remote.enabled { ensure_remote_token(&app).await? } else { remote.token.clone() };
+    Ok(ApiStatus {
+        enabled: settings.api.enabled,
+        port: settings.api.port,
+        token,
+        url: format!("http://127.0.0.1:{}/v1", settings.api.port),
+        mcp_url: format!("http://127.0.0.1:{}/mcp", settings.api.port),
+        remote: RemoteStatus {
+            enabled: remote.enabled,
+            running: REMOTE_POLICY.read().is_ok_and(|p| p.is_some()),
+            token: remote_token,
+            url: format!("https://{}:{}/v1", remote.bind_address, remote.port),
+            capabilities: remote.capabilities.clone(),
+        },
+    })
+}
+
+/// Replace the token; clients holding the old one are rejected immediately
+#[tauri::command]
+pub async fn regenerate_api_token(app: AppHandle) -> Result<String, String> {
+    let token = generate_token();
+    crate::settings::modify_settings(&app, |settings| {
+        settings.api.token = token.clone();
+        Ok::<_, String>(())
+    })
+    .await?;
+
+    if let Ok(mut active) = ACTIVE_TOKEN.write() {
+        // Only swap if the server is running; otherwise it picks the token up at start
+        if !active.is_empty() {
+            *active = token.clone();
+        }
+    }
+    info!("Local API token regenerated");
+    Ok(token)
+}
+
+/// Replace the remote token; devices holding the old one are rejected immediately
+#[tauri::command]
+pub async fn regenerate_remote_api_token(app: AppHandle) -> Result<String, String> {
+    let token = generate_token();
+    // Saving runs `update_remote_policy`
+    crate::settings::modify_settings(&app, |settings| {
+        settings.api.remote.token = token.clone();
+        Ok::<_, String>(())
+    })
+    .await?;
+    info!("Remote API token regenerated");
+    Ok(token)
+}
@@ -0,0 +1,496 @@
+// App Discovery Module
+// Builds a catalog of installed applications so the launcher isn't limited to a
+// fixed list: Start Menu shortcuts, App Paths and UWP packages on Windows,
+// .app bundles on macOS, and .desktop entries on Linux
+
+use tracing::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// How often the catalog is rebuilt in the background
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppSource {
+    StartMenu,
+    AppPaths,
+    Uwp,
+    MacBundle,
+    DesktopEntry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredApp {
+    pub name: String,
+    /// What to hand to the shell to start the app: a .lnk/.exe path or a
+    /// `shell:AppsFolder\<AUMID>` URI on Windows, a .app bundle on macOS,
+    /// or a .desktop file on Linux
+    pub launch_target: String,
+    /// File the app's icon can be loaded from (exe, shortcut, or package logo)
+    pub icon_path: Option<String>,
+    pub source: AppSource,
+}
+
+static CATALOG: Lazy<RwLock<Vec<DiscoveredApp>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Snapshot of the current catalog
+pub fn catalog() -> Vec<DiscoveredApp> {
+    CATALOG.read().map(|c| c.clone()).unwrap_or_default()
+}
+
+/// Rescan the system and replace the catalog. Blocking; call off the async runtime.
+pub fn refresh_catalog() -> usize {
+    let mut by_name: HashMap<String, DiscoveredApp> = HashMap::new();
+
+    // Later sources only fill gaps: Start Menu / UWP names are the friendliest
+    for app in platform::scan() {
+        by_name.entry(app.name.to_lowercase()).or_insert(app);
+    }
+
+    let mut apps: Vec<DiscoveredApp> = by_name.into_values().collect();
+    apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+    let count = apps.len();
+    if let Ok(mut catalog) = CATALOG.write() {
+        *catalog = apps;
+    }
+
+    info!("App catalog refreshed: {} applications", count);
+    count
+}
+
+/// Build the catalog now and keep it fresh in the background
+pub fn start_background_refresh() {
+    tauri::async_runtime::spawn(async {
+        loop {
+            if let Err(e) = tokio::task::spawn_blocking(refresh_catalog).await {
+                warn!("App catalog refresh failed: {}", e);
+            }
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+    });
+}
+
+/// Look up a discovered app by name (case-insensitive)
+pub fn find_discovered(query: &str) -> Option<DiscoveredApp> {
+    let query = query.trim().to_lowercase();
+    let catalog = CATALOG.read().ok()?;
+
+    catalog
+        .iter()
+        .find(|app| app.name.to_lowercase() == query)
+        .or_else(|| {
+            catalog.iter().find(|app| {
+                app.name
+                    .to_lowercase()
+                    .split(|c: char| !c.is_alphanumeric())
+                    .any(|word| word == query)
+            })
+        })
+        .cloned()
+}
+
+/// Shortcuts that clutter the Start Menu but aren't apps anyone asks for
+fn is_noise(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    ["uninstall", "readme", "read me", "release notes", "help", "website", "documentation", "license"]
+        .iter()
+        .any(|word| lower.contains(word))
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{is_noise, AppSource, DiscoveredApp};
+    use tracing::warn;
+    use serde::Deserialize;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use walkdir::WalkDir;
+    use winreg::enums::{HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE};
+    use winreg::RegKey;
+
+    pub fn scan() -> Vec<DiscoveredApp> {
+        let mut apps = Vec::new();
+        apps.extend(scan_uwp_packages());
+        apps.extend(scan_start_menu());
+        apps.extend(scan_app_paths());
+        apps
+    }
+
+    fn start_menu_dirs() -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        if let Ok(program_data) = std::env::var("ProgramData") {
+            dirs.push(Path::new(&program_data).join(r"Microsoft\Windows\Start Menu\Programs"));
+        }
+        if let Ok(app_data) = std::env::var("APPDATA") {
+            dirs.push(Path::new(&app_data).join(r"Microsoft\Windows\Start Menu\Programs"));
+        }
+        dirs
+    }
+
+    fn scan_start_menu() -> Vec<DiscoveredApp> {
+        let mut apps = Vec::new();
+
+        for dir in start_menu_dirs() {
+            for entry in WalkDir::new(&dir).into_iter().filter_map(|e| e.ok()) {
+                let path = entry.path();
+                if !path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("lnk")) {
+                    continue;
+                }
+
+                let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                if is_noise(&name) {
+                    continue;
+                }
+
+                let target = path.to_string_lossy().to_string();
+                apps.push(DiscoveredApp {
+                    name,
+                    launch_target: target.clone(),
+                    // Shell resolves a shortcut's icon from the .lnk itself
+                    icon_path: Some(target),
+                    source: AppSource::StartMenu,
+                });
+            }
+        }
+
+        apps
+    }
+
+    fn scan_app_paths() -> Vec<DiscoveredApp> {
+        const APP_PATHS: &str = r"SOFTWARE\Microsoft\Windows\CurrentVersion\App Paths";
+        let mut apps = Vec::new();
+
+        for hive in [HKEY_LOCAL_MACHINE, HKEY_CURRENT_USER] {
+            let Ok(key) = RegKey::predef(hive).open_subkey(APP_PATHS) else {
+                continue;
+            };
+
+            for subkey_name in key.enum_keys().filter_map(|k| k.ok()) {
+                let Ok(subkey) = key.open_subkey(&subkey_name) else {
+                    continue;
+                };
+                let Ok(exe_path) = subkey.get_value::<String, _>("") else {
+                    continue;
+                };
+
+                let exe_path = exe_path.trim_matches('"').to_string();
+                if exe_path.is_empty() || !Path::new(&exe_path).exists() {
+                    continue;
+                }
+
+                let name = Path::new(&subkey_name)
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or(subkey_name.clone());
+
+                apps.push(DiscoveredApp {
+                    name,
+                    launch_target: exe_path.clone(),
+                    icon_path: Some(exe_path),
+                    source: AppSource::AppPaths,
+                });
+            }
+        }
+
+        apps
+    }
+
+    #[derive(Deserialize)]
+    struct StartApp {
+        name: String,
+        app_id: String,
+        logo: Option<String>,
+    }
+
+    // Get-StartApps has the display names; package manifests have the logos
+    const UWP_SCRIPT: &str = r#"
+$logos = @{}
+Get-AppxPackage | ForEach-Object {
+    try {
+        $m = Get-AppxPackageManifest $_
+        $logos[$_.PackageFamilyName] = Join-Path $_.InstallLocation $m.Package.Properties.Logo
+    } catch {}
+}
+$apps = @(Get-StartApps | Where-Object { $_.AppID -like '*!*' } | ForEach-Object {
+    [PSCustomObject]@{ name = $_.Name; app_id = $_.AppID; logo = $logos[$_.AppID.Split('!')[0]] }
+})
+ConvertTo-Json -Compress -InputObject $apps
+"#;
+
+    fn scan_uwp_packages() -> Vec<DiscoveredApp> {
+        let output = match Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", UWP_SCRIPT])
+            .output()
+        {
+            Ok(output) if output.status.success() => output,
+            Ok(output) => {
+                warn!("UWP app scan failed: {}", String::from_utf8_lossy(&output.stderr));
+                return Vec::new();
+            }
+            Err(e) => {
+                warn!("UWP app scan failed: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let start_apps: Vec<StartApp> = match serde_json::from_str(stdout.trim()) {
+            Ok(apps) => apps,
+            Err(e) => {
+                warn!("Failed to parse UWP app list: {}", e);
+                return Vec::new();
+            }
+        };
+
+        start_apps
+            .into_iter()
+            .filter(|app| !is_noise(&app.name))
+            .map(|app| DiscoveredApp {
+                name: app.name,
+                launch_target: format!(r"shell:AppsFolder\{}", app.app_id),
+                icon_path: app.logo.and_then(|logo| resolve_scaled_asset(Path::new(&logo))),
+                source: AppSource::Uwp,
+            })
+            .collect()
+    }
+
+    /// Package manifests name `Logo.png` but ship `Logo.scale-100.png` etc.
+    fn resolve_scaled_asset(path: &Path) -> Option<String> {
+        if path.exists() {
+            return Some(path.to_string_lossy().to_string());
+        }
+
+        let stem = path.file_stem()?.to_string_lossy().to_lowercase();
+        std::fs::read_dir(path.parent()?)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|p| {
+                let name = p.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+                name.starts_with(&format!("{}.", stem)) && name.ends_with(".png")
+            })
+            .map(|p| p.to_string_lossy().to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{is_noise, AppSource, DiscoveredApp};
+    use std::path::{Path, PathBuf};
+    use walkdir::WalkDir;
+
+    fn application_dirs() -> Vec<PathBuf> {
+        let mut roots = vec![
+            PathBuf::from("/Applications"),
+            PathBuf::from("/System/Applications"),
+        ];
+        if let Some(home) = dirs::home_dir() {
+            roots.push(home.join("Applications"));
+        }
+        roots
+    }
+
+    pub fn scan() -> Vec<DiscoveredApp> {
+        let mut apps = Vec::new();
+
+        for dir in application_dirs() {
+            // Bundles sit at the top level or one folder down (e.g. /Applications/Utilities)
+            let mut walker = WalkDir::new(&dir).max_depth(2).into_iter();
+            while let Some(entry) = walker.next() {
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                let path = entry.path();
+                if !path.extension().is_some_and(|ext| ext == "app") {
+                    continue;
+                }
+                // Don't descend into the bundle itself
+                walker.skip_current_dir();
+
+                let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                    continue;
+                };
+                if is_noise(&name) {
+                    continue;
+                }
+
+                apps.push(DiscoveredApp {
+                    name,
+                    launch_target: path.to_string_lossy().to_string(),
+                    icon_path: bundle_icon(path),
+                    source: AppSource::MacBundle,
+                });
+            }
+        }
+
+        apps
+    }
+
+    /// First .icns in the bundle's resources; good enough without parsing Info.plist
+    fn bundle_icon(bundle: &Path) -> Option<String> {
+        std::fs::read_dir(bundle.join("Contents/Resources"))
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|p| p.extension().is_some_and(|ext| ext == "icns"))
+            .map(|p| p.to_string_lossy().to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{is_noise, AppSource, DiscoveredApp};
+    use std::path::{Path, PathBuf};
+
+    fn application_dirs() -> Vec<PathBuf> {
+        let mut roots = vec![
+            PathBuf::from("/usr/share/applications"),
+            PathBuf::from("/usr/local/share/applications"),
+            PathBuf::from("/var/lib/flatpak/exports/share/applications"),
+            PathBuf::from("/var/lib/snapd/desktop/applications"),
+        ];
+        if let Some(data) = dirs::data_dir() {
+            roots.push(data.join("applications"));
+            roots.push(data.join("flatpak/exports/share/applications"));
+        }
+        roots
+    }
+
+    pub fn scan() -> Vec<DiscoveredApp> {
+        let mut apps = Vec::new();
+
+        for dir in application_dirs() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                if !path.extension().is_some_and(|ext| ext == "desktop") {
+                    continue;
+                }
+                if let Some(app) = parse_desktop_file(&path) {
+                    apps.push(app);
+                }
+            }
+        }
+
+        apps
+    }
+
+    /// Fields we care about from a .desktop file's [Desktop Entry] group
+    #[derive(Default)]
+    pub struct DesktopEntry {
+        pub name: Option<String>,
+        pub exec: Option<String>,
+        pub icon: Option<String>,
+        pub hidden: bool,
+        pub is_application: bool,
+    }
+
+    pub fn read_desktop_entry(path: &Path) -> Option<DesktopEntry> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let mut entry = DesktopEntry::default();
+        let mut in_main_group = false;
+
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_main_group = line == "[Desktop Entry]";
+                continue;
+            }
+            if !in_main_group {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            // Unlocalized keys only, e.g. Name= but not Name[de]=
+            match key.trim() {
+                "Name" => entry.name = Some(value.trim().to_string()),
+                "Exec" => entry.exec = Some(value.trim().to_string()),
+                "Icon" => entry.icon = Some(value.trim().to_string()),
+                "Type" => entry.is_application = value.trim() == "Application",
+                "NoDisplay" | "Hidden" if value.trim() == "true" => entry.hidden = true,
+                _ => {}
+            }
+        }
+
+        Some(entry)
+    }
+
+    fn parse_desktop_file(path: &Path) -> Option<DiscoveredApp> {
+        let entry = read_desktop_entry(path)?;
+        if !entry.is_application || entry.hidden || entry.exec.is_none() {
+            return None;
+        }
+
+        let name = entry.name?;
+        if is_noise(&name) {
+            return None;
+        }
+
+        Some(DiscoveredApp {
+            name,
+            launch_target: path.to_string_lossy().to_string(),
+            // Either an absolute path or an icon theme name
+            icon_path: entry.icon,
+            source: AppSource::DesktopEntry,
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+mod platform {
+    use super::DiscoveredApp;
+
+    pub fn scan() -> Vec<DiscoveredApp> {
+        Vec::new()
+    }
+}
+
+/// Command line from a .desktop file's Exec key, with field codes (%U, %f, ...) removed
+#[cfg(target_os = "linux")]
+pub fn desktop_entry_command(path: &std::path::Path) -> Option<String> {
+    let exec = platform::read_desktop_entry(path)?.exec?;
+
+    let mut command = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            // Drop the field code along with the placeholder, except for a literal %%
+            if chars.next() == Some('%') {
+                command.push('%');
+            }
+        } else {
+            command.push(c);
+        }
+    }
+
+    let command = command.split_whitespace().collect::<Vec<_>>().join(" ");
+    if command.is_empty() {
+        None
+    } else {
+        Some(command)
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_app_catalog() -> Result<Vec<DiscoveredApp>, String> {
+    Ok(catalog())
+}
+
+#[tauri::command]
+pub async fn refresh_app_catalog() -> Result<usize, String> {
+    tokio::task::spawn_blocking(refresh_catalog)
+        .await
+        .map_err(|e| format!("App catalog refresh failed: {}", e))
+}
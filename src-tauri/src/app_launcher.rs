@@ -1,6 +1,13 @@
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Command;
+use std::sync::Mutex;
+use tracing::{debug, info};
+
+/// Minimum fuzzy score (0.0 - 1.0) for a match to be accepted
+const FUZZY_THRESHOLD: f64 = 0.8;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppInfo {
@@ -16,7 +23,8 @@ pub struct LaunchResult {
     pub app_name: String,
 }
 
-// Common Windows applications with their executable names
+// Common applications with their Windows executable names (other platforms
+// resolve these through the discovered catalog)
 fn get_app_registry() -> HashMap<String, AppInfo> {
     let mut apps = HashMap::new();
     
@@ -113,7 +121,23 @@ fn get_app_registry() -> HashMap<String, AppInfo> {
 }
 
 pub fn find_app(query: &str) -> Option<AppInfo> {
-    let query_lower = query.to_lowercase();
+    let query_lower = normalize_query(query);
+    
+    // Learned corrections win ("when I say browser I mean Firefox")
+    let learned = LEARNED_ALIASES.lock().ok().and_then(|aliases| aliases.get(&query_lower).cloned());
+    if let Some(target) = learned {
+        if let Some(app) = match_app(&target) {
+            return Some(app);
+        }
+    }
+    
+    match_app(&query_lower)
+}
+
+/// Match a query against the built-in registry and discovered catalog,
+/// ignoring learned aliases
+fn match_app(query: &str) -> Option<AppInfo> {
+    let query_lower = normalize_query(query);
     let apps = get_app_registry();
     
     // First try exact match
@@ -130,14 +154,223 @@ pub fn find_app(query: &str) -> Option<AppInfo> {
         }
     }
     
-    None
+    // Then the discovered catalog of installed apps
+    if let Some(app) = crate::app_discovery::find_discovered(&query_lower) {
+        return Some(discovered_to_info(app));
+    }
+    
+    // Finally fuzzy-match against every known name, so "spotfy" or
+    // "visual code" still find something
+    let mut best: Option<(f64, AppInfo)> = None;
+    let mut consider = |score: f64, app: &AppInfo| {
+        if score >= FUZZY_THRESHOLD && best.as_ref().map_or(true, |(s, _)| score > *s) {
+            best = Some((score, app.clone()));
+        }
+    };
+    
+    for app in apps.values() {
+        for name in std::iter::once(&app.name).chain(app.aliases.iter()) {
+            consider(match_score(&query_lower, name), app);
+        }
+    }
+    
+    for discovered in crate::app_discovery::catalog() {
+        let score = match_score(&query_lower, &discovered.name);
+        consider(score, &discovered_to_info(discovered));
+    }
+    
+    if let Some((score, app)) = &best {
+        debug!("Fuzzy matched '{}' to {} (score {:.2})", query_lower, app.name, score);
+    }
+    
+    best.map(|(_, app)| app)
+}
+
+fn discovered_to_info(app: crate::app_discovery::DiscoveredApp) -> AppInfo {
+    AppInfo {
+        aliases: vec![app.name.to_lowercase()],
+        name: app.name,
+        executable: app.launch_target,
+    }
+}
+
+/// Lowercase, trim, and drop filler words like "the" / "app"
+fn normalize_query(query: &str) -> String {
+    const FILLER: &[&str] = &["the", "app", "application", "program", "please", "my"];
+    
+    query
+        .to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '\'' || c == '"' || c == '.' || c == ',')
+        .filter(|w| !w.is_empty() && !FILLER.contains(w))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Similarity between a query and an app name, 0.0 - 1.0.
+/// Takes the better of whole-string edit distance and per-token scoring, so
+/// both typos ("spotfy") and partial names ("visual code") score well.
+fn match_score(query: &str, candidate: &str) -> f64 {
+    let candidate = candidate.to_lowercase();
+    if query.is_empty() || candidate.is_empty() {
+        return 0.0;
+    }
+    if query == candidate {
+        return 1.0;
+    }
+    
+    let edit = strsim::normalized_damerau_levenshtein(query, &candidate);
+    
+    let candidate_tokens: Vec<&str> = candidate.split_whitespace().collect();
+    let query_tokens: Vec<&str> = query.split_whitespace().collect();
+    let token = query_tokens
+        .iter()
+        .map(|q| {
+            candidate_tokens
+                .iter()
+                .map(|c| strsim::jaro_winkler(q, c))
+                .fold(0.0, f64::max)
+        })
+        .sum::<f64>()
+        / query_tokens.len() as f64;
+    
+    // Token scoring is generous with short words, weight it slightly lower
+    edit.max(token * 0.95)
+}
+
+// ========== Learned aliases ==========
+
+static LEARNED_ALIASES: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(load_aliases()));
+
+fn aliases_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("app_aliases.json");
+    Some(path)
+}
+
+fn load_aliases() -> HashMap<String, String> {
+    aliases_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_aliases(aliases: &HashMap<String, String>) -> Result<(), String> {
+    let path = aliases_path().ok_or_else(|| "Could not find config directory".to_string())?;
+    
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    
+    let content = serde_json::to_string_pretty(aliases)
+        .map_err(|e| format!("Failed to serialize aliases: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write aliases: {}", e))
+}
+
+/// Remember that `alias` means `app_name`. The app must resolve to something
+/// we can launch. Returns the resolved app.
+pub fn learn_alias(alias: &str, app_name: &str) -> Result<AppInfo, String> {
+    let alias = normalize_query(alias);
+    if alias.is_empty() {
+        return Err("Alias can't be empty".to_string());
+    }
+    
+    let app = match_app(app_name).ok_or_else(|| format!("I don't know an app called '{}'", app_name))?;
+    
+    let mut aliases = LEARNED_ALIASES.lock().map_err(|e| e.to_string())?;
+    aliases.insert(alias.clone(), app.name.clone());
+    save_aliases(&aliases)?;
+    
+    info!("Learned alias '{}' -> {}", alias, app.name);
+    Ok(app)
+}
+
+/// Every learned alias, alias -> app name
+pub fn learned_aliases() -> HashMap<String, String> {
+    LEARNED_ALIASES.lock().map(|aliases| aliases.clone()).unwrap_or_default()
+}
+
+/// Merge aliases from a settings backup; existing ones with the same name are replaced
+pub fn import_aliases(imported: HashMap<String, String>) -> Result<usize, String> {
+    let mut aliases = LEARNED_ALIASES.lock().map_err(|e| e.to_string())?;
+    let count = imported.len();
+    for (alias, app_name) in imported {
+        let alias = normalize_query(&alias);
+        if !alias.is_empty() {
+            aliases.insert(alias, app_name);
+        }
+    }
+    save_aliases(&aliases)?;
+    Ok(count)
+}
+
+/// Replace every learned alias, e.g. with the merged set from sync
+pub fn replace_aliases(replacement: HashMap<String, String>) -> Result<(), String> {
+    let mut aliases = LEARNED_ALIASES.lock().map_err(|e| e.to_string())?;
+    save_aliases(&replacement)?;
+    *aliases = replacement;
+    Ok(())
+}
+
+pub fn forget_alias(alias: &str) -> Result<bool, String> {
+    let alias = normalize_query(alias);
+    let mut aliases = LEARNED_ALIASES.lock().map_err(|e| e.to_string())?;
+    let removed = aliases.remove(&alias).is_some();
+    if removed {
+        save_aliases(&aliases)?;
+    }
+    Ok(removed)
+}
+
+/// Parse spoken corrections like "when I say browser I mean Firefox"
+/// into (alias, app name)
+pub fn parse_alias_correction(command: &str) -> Option<(String, String)> {
+    let lower = command.to_lowercase();
+    let lower = lower.trim().trim_end_matches(|c: char| c.is_ascii_punctuation());
+    
+    let rest = lower.strip_prefix("when i say ")?;
+    let (alias, app) = rest.split_once(" i mean ")?;
+    
+    let alias = alias.trim().trim_matches(|c| c == '\'' || c == '"' || c == ',');
+    let app = app.trim().trim_matches(|c| c == '\'' || c == '"');
+    
+    if alias.is_empty() || app.is_empty() {
+        return None;
+    }
+    
+    Some((alias.to_string(), app.to_string()))
+}
+
+/// Whether `executable` is a concrete launch target from discovery (a path or
+/// shell:AppsFolder URI) rather than a bare command name
+#[cfg(target_os = "windows")]
+fn is_direct_target(executable: &str) -> bool {
+    executable.starts_with("shell:") || executable.contains('\\') || executable.contains('/')
 }
 
 #[cfg(target_os = "windows")]
 pub fn launch_app(app_name: &str) -> Result<LaunchResult, String> {
     let app_info = find_app(app_name).ok_or_else(|| format!("Application '{}' not found", app_name))?;
     
-    println!("[APP_LAUNCHER] Attempting to launch: {} (executable: {})", app_info.name, app_info.executable);
+    info!("Launching: {} (executable: {})", app_info.name, app_info.executable);
+    
+    // Discovered apps carry an exact target; hand it straight to the shell
+    if is_direct_target(&app_info.executable) {
+        let result = if app_info.executable.starts_with("shell:") {
+            Command::new("explorer").arg(&app_info.executable).spawn()
+        } else {
+            Command::new("cmd").args(&["/C", "start", "", &app_info.executable]).spawn()
+        };
+        
+        return match result {
+            Ok(_) => Ok(LaunchResult {
+                success: true,
+                message: format!("Launched {}", app_info.name),
+                app_name: app_info.name.clone(),
+            }),
+            Err(e) => Err(format!("Failed to launch {}: {}", app_info.name, e)),
+        };
+    }
     
     // Method 1: Try shell:AppsFolder protocol (most reliable for modern apps)
     let shell_result = Command::new("explorer")
@@ -145,14 +378,14 @@ pub fn launch_app(app_name: &str) -> Result<LaunchResult, String> {
         .spawn();
     
     if shell_result.is_ok() {
-        println!("[APP_LAUNCHER] Method 1 (shell:AppsFolder) succeeded");
+        debug!("Launched via shell:AppsFolder");
         return Ok(LaunchResult {
             success: true,
             message: format!("Launched {}", app_info.name),
             app_name: app_info.name.clone(),
         });
     }
-    println!("[APP_LAUNCHER] Method 1 failed: {:?}", shell_result.err());
+    debug!("shell:AppsFolder launch failed: {:?}", shell_result.err());
     
     // Method 2: Try using Windows Shell via PowerShell (searches Start Menu)
     let ps_result = Command::new("powershell")
@@ -164,14 +397,14 @@ pub fn launch_app(app_name: &str) -> Result<LaunchResult, String> {
         .spawn();
     
     if ps_result.is_ok() {
-        println!("[APP_LAUNCHER] Method 2 (PowerShell) succeeded");
+        debug!("Launched via PowerShell");
         return Ok(LaunchResult {
             success: true,
             message: format!("Launched {}", app_info.name),
             app_name: app_info.name.clone(),
         });
     }
-    println!("[APP_LAUNCHER] Method 2 failed: {:?}", ps_result.err());
+    debug!("PowerShell launch failed: {:?}", ps_result.err());
     
     // Method 3: Try cmd /c start with executable (most compatible)
     let cmd_result = Command::new("cmd")
@@ -180,7 +413,7 @@ pub fn launch_app(app_name: &str) -> Result<LaunchResult, String> {
     
     match cmd_result {
         Ok(_) => {
-            println!("[APP_LAUNCHER] Method 3 (cmd start) succeeded");
+            debug!("Launched via cmd start");
             Ok(LaunchResult {
                 success: true,
                 message: format!("Launched {}", app_info.name),
@@ -188,15 +421,78 @@ pub fn launch_app(app_name: &str) -> Result<LaunchResult, String> {
             })
         },
         Err(e) => {
-            println!("[APP_LAUNCHER] All methods failed");
+            tracing::warn!("Every launch method failed for {}", app_info.name);
             Err(format!("Failed to launch {}: {}. The app might not be installed or accessible.", app_info.name, e))
         }
     }
 }
 
+/// The built-in registry uses Windows executable names; on other platforms
+/// prefer the discovered entry with the same name when there is one
 #[cfg(not(target_os = "windows"))]
+fn resolve_native_target(app_info: &AppInfo) -> String {
+    if app_info.executable.contains('/') {
+        return app_info.executable.clone();
+    }
+    
+    crate::app_discovery::find_discovered(&app_info.name)
+        .map(|app| app.launch_target)
+        .unwrap_or_else(|| app_info.executable.clone())
+}
+
+#[cfg(target_os = "macos")]
+pub fn launch_app(app_name: &str) -> Result<LaunchResult, String> {
+    let app_info = find_app(app_name).ok_or_else(|| format!("Application '{}' not found", app_name))?;
+    let target = resolve_native_target(&app_info);
+    
+    info!("Launching: {} (target: {})", app_info.name, target);
+    
+    // Bundle paths open directly; bare names go through Launch Services
+    let status = if target.ends_with(".app") {
+        Command::new("open").arg(&target).status()
+    } else {
+        Command::new("open").args(["-a", &target]).status()
+    };
+    
+    match status {
+        Ok(status) if status.success() => Ok(LaunchResult {
+            success: true,
+            message: format!("Launched {}", app_info.name),
+            app_name: app_info.name.clone(),
+        }),
+        Ok(status) => Err(format!("Failed to launch {}: open exited with {}", app_info.name, status)),
+        Err(e) => Err(format!("Failed to launch {}: {}", app_info.name, e)),
+    }
+}
+
+#[cfg(target_os = "linux")]
 pub fn launch_app(app_name: &str) -> Result<LaunchResult, String> {
-    Err("App launching is only supported on Windows".to_string())
+    let app_info = find_app(app_name).ok_or_else(|| format!("Application '{}' not found", app_name))?;
+    let target = resolve_native_target(&app_info);
+    
+    info!("Launching: {} (target: {})", app_info.name, target);
+    
+    let result = if target.ends_with(".desktop") {
+        let command = crate::app_discovery::desktop_entry_command(std::path::Path::new(&target))
+            .ok_or_else(|| format!("{} has no command to run", app_info.name))?;
+        Command::new("sh").args(["-c", &command]).spawn()
+    } else {
+        Command::new(&target).spawn()
+    };
+    
+    match result {
+        Ok(_) => Ok(LaunchResult {
+            success: true,
+            message: format!("Launched {}", app_info.name),
+            app_name: app_info.name.clone(),
+        }),
+        Err(e) => Err(format!("Failed to launch {}: {}. The app might not be installed or accessible.", app_info.name, e)),
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+pub fn launch_app(_app_name: &str) -> Result<LaunchResult, String> {
+    Err("App launching isn't supported on this platform".to_string())
 }
 
 #[tauri::command]
@@ -214,3 +510,19 @@ pub async fn get_available_apps() -> Result<Vec<AppInfo>, String> {
 pub async fn find_app_command(query: String) -> Result<Option<AppInfo>, String> {
     Ok(find_app(&query))
 }
+
+#[tauri::command]
+pub async fn get_app_aliases() -> Result<HashMap<String, String>, String> {
+    let aliases = LEARNED_ALIASES.lock().map_err(|e| e.to_string())?;
+    Ok(aliases.clone())
+}
+
+#[tauri::command]
+pub async fn learn_app_alias(alias: String, app_name: String) -> Result<AppInfo, String> {
+    learn_alias(&alias, &app_name)
+}
+
+#[tauri::command]
+pub async fn forget_app_alias(alias: String) -> Result<bool, String> {
+    forget_alias(&alias)
+}
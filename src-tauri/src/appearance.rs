@@ -0,0 +1,450 @@
+// Appearance Module
+// OS light/dark theme and Night Light, and keeping ASTRAL's own theme
+// setting in step with the OS
+
+use anyhow::Result;
+use tracing::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Fired with the new OS theme whenever it changes, whatever ASTRAL's setting
+pub const OS_THEME_EVENT: &str = "os-theme-changed";
+/// Fired with the theme ASTRAL should draw with
+pub const THEME_EVENT: &str = "theme-changed";
+
+const THEME_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// ASTRAL's theme setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Dark,
+    Light,
+    /// Follow the OS light/dark setting
+    #[serde(alias = "system")]
+    Auto,
+}
+
+impl Theme {
+    /// The concrete theme to draw with. Auto reads the OS setting (blocking)
+    /// and falls back to dark when it can't be read.
+    pub fn resolve(&self) -> ThemeMode {
+        match self {
+            Self::Dark => ThemeMode::Dark,
+            Self::Light => ThemeMode::Light,
+            Self::Auto => os_theme().unwrap_or(ThemeMode::Dark),
+        }
+    }
+}
+
+impl From<ThemeMode> for Theme {
+    fn from(mode: ThemeMode) -> Self {
+        match mode {
+            ThemeMode::Dark => Self::Dark,
+            ThemeMode::Light => Self::Light,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMode {
+    Light,
+    Dark,
+}
+
+impl ThemeMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "light" => Some(Self::Light),
+            "dark" => Some(Self::Dark),
+            _ => None,
+        }
+    }
+
+    /// Lowercase name, for logs and replies
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+
+    fn toggled(self) -> Self {
+        match self {
+            Self::Light => Self::Dark,
+            Self::Dark => Self::Light,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppearanceStatus {
+    pub theme: ThemeMode,
+    /// None when the platform has no night light we can read
+    pub night_light: Option<bool>,
+}
+
+/// A spoken appearance request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppearanceCommand {
+    /// None toggles
+    Theme(Option<ThemeMode>),
+    NightLight(Option<bool>),
+}
+
+impl AppearanceCommand {
+    /// "turn on dark mode", "switch to light mode", "toggle night light".
+    /// Only instructions: the command starts with a verb or with the setting
+    /// itself ("dark mode on"), so "is dark mode on?" and "I like light
+    /// themes" aren't taken for one.
+    pub fn from_command(command: &str) -> Option<Self> {
+        const VERBS: &[&str] = &[
+            "turn ", "switch ", "toggle ", "enable ", "disable ", "activate ", "deactivate ", "set ", "use ", "go ", "change ", "put ",
+        ];
+        const SETTINGS: &[&str] = &["night light", "night shift", "blue light", "dark mode", "dark theme", "light mode", "light theme"];
+        let lower = crate::intents::imperative(command);
+        if !VERBS.iter().chain(SETTINGS).any(|p| lower.starts_with(p)) {
+            return None;
+        }
+        let words: Vec<&str> = lower.split(|c: char| !c.is_alphanumeric()).collect();
+        let on = words.iter().any(|w| ["on", "enable", "activate"].contains(w));
+        let off = words.iter().any(|w| ["off", "disable", "deactivate"].contains(w));
+
+        if lower.contains("night light") || lower.contains("night shift") || lower.contains("blue light") {
+            return Some(Self::NightLight(match (on, off) {
+                (true, false) => Some(true),
+                (false, true) => Some(false),
+                _ => None,
+            }));
+        }
+
+        let dark = lower.contains("dark mode") || lower.contains("dark theme");
+        let light = lower.contains("light mode") || lower.contains("light theme");
+        match (dark, light) {
+            (true, false) if off => Some(Self::Theme(Some(ThemeMode::Light))),
+            (true, false) if lower.contains("toggle") => Some(Self::Theme(None)),
+            (true, false) => Some(Self::Theme(Some(ThemeMode::Dark))),
+            (false, true) if off => Some(Self::Theme(Some(ThemeMode::Dark))),
+            (false, true) if lower.contains("toggle") => Some(Self::Theme(None)),
+            (false, true) => Some(Self::Theme(Some(ThemeMode::Light))),
+            _ => None,
+        }
+    }
+}
+
+/// Current OS theme and night light state. Blocking.
+pub fn status() -> Result<AppearanceStatus> {
+    Ok(AppearanceStatus {
+        theme: platform::theme()?,
+        night_light: platform::night_light().ok(),
+    })
+}
+
+/// Switch the OS app and system theme. Blocking.
+pub fn set_theme(mode: ThemeMode) -> Result<()> {
+    info!("Setting OS theme to {}", mode.as_str());
+    platform::set_theme(mode)
+}
+
+/// Blocking.
+pub fn set_night_light(enabled: bool) -> Result<()> {
+    info!("Turning night light {}", if enabled { "on" } else { "off" });
+    platform::set_night_light(enabled)
+}
+
+/// Run a parsed command, returning the reply and the theme when it changed
+pub fn apply_command(command: AppearanceCommand) -> Result<(String, Option<ThemeMode>)> {
+    match command {
+        AppearanceCommand::Theme(mode) => {
+            let mode = match mode {
+                Some(mode) => mode,
+                None => platform::theme()?.toggled(),
+            };
+            set_theme(mode)?;
+            Ok((format!("Switched to {} mode", mode.as_str()), Some(mode)))
+        }
+        AppearanceCommand::NightLight(enabled) => {
+            let enabled = match enabled {
+                Some(enabled) => enabled,
+                None => !platform::night_light()?,
+            };
+            set_night_light(enabled)?;
+            Ok((format!("Night light is {}", if enabled { "on" } else { "off" }), None))
+        }
+    }
+}
+
+/// The OS light/dark setting. Blocking.
+pub fn os_theme() -> Result<ThemeMode> {
+    platform::theme()
+}
+
+/// Store `mode` as ASTRAL's theme and tell the UI. An "auto" setting is kept,
+/// since it already follows the OS.
+pub async fn sync_app_theme(app: &tauri::AppHandle, mode: ThemeMode) -> Result<(), String> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    if settings.general.theme != Theme::Auto && settings.general.theme != Theme::from(mode) {
+        crate::settings::modify_settings(app, |settings| {
+            // The user may have picked "auto" since
+            if settings.general.theme != Theme::Auto {
+                settings.general.theme = Theme::from(mode);
+            }
+            Ok::<_, String>(())
+        })
+        .await?;
+    }
+    app.emit(THEME_EVENT, mode).map_err(|e| e.to_string())
+}
+
+/// Poll the OS theme, emitting `os-theme-changed` on every switch and
+/// `theme-changed` too when ASTRAL's theme is set to auto
+pub fn start_theme_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last = os_theme().ok();
+
+        loop {
+            std::thread::sleep(THEME_POLL_INTERVAL);
+            let Ok(mode) = os_theme() else { continue };
+            if last == Some(mode) {
+                continue;
+            }
+            last = Some(mode);
+            info!("OS theme changed to {}", mode.as_str());
+
+            if let Err(e) = app.emit(OS_THEME_EVENT, mode) {
+                warn!("Failed to emit {}: {}", OS_THEME_EVENT, e);
+            }
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let Ok(settings) = crate::settings::load_settings(app.clone()).await else { return };
+                if settings.general.theme == Theme::Auto {
+                    let _ = app.emit(THEME_EVENT, mode);
+                }
+            });
+        }
+    });
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::ThemeMode;
+    use anyhow::{bail, Context, Result};
+    use windows::core::w;
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE,
+    };
+    use winreg::enums::{HKEY_CURRENT_USER, KEY_READ, KEY_WRITE};
+    use winreg::RegKey;
+
+    const PERSONALIZE_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Themes\Personalize";
+    const NIGHT_LIGHT_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\DefaultAccount\Current\default$windows.data.bluelightreduction.bluelightreductionstate\windows.data.bluelightreduction.bluelightreductionstate";
+
+    // Offsets into the undocumented Night Light state blob
+    const STATE_OFFSET: usize = 18;
+    const STATE_ON: u8 = 0x15;
+    const STATE_OFF: u8 = 0x13;
+    const ENABLED_MARKER_OFFSET: usize = 23;
+    const ENABLED_MARKER: [u8; 2] = [0x10, 0x00];
+    const TIMESTAMP_RANGE: std::ops::Range<usize> = 10..15;
+
+    pub fn theme() -> Result<ThemeMode> {
+        let key = RegKey::predef(HKEY_CURRENT_USER).open_subkey_with_flags(PERSONALIZE_KEY, KEY_READ)?;
+        let light: u32 = key.get_value("AppsUseLightTheme").unwrap_or(0);
+        Ok(if light == 1 { ThemeMode::Light } else { ThemeMode::Dark })
+    }
+
+    pub fn set_theme(mode: ThemeMode) -> Result<()> {
+        let key = RegKey::predef(HKEY_CURRENT_USER).open_subkey_with_flags(PERSONALIZE_KEY, KEY_READ | KEY_WRITE)?;
+        let value: u32 = if mode == ThemeMode::Light { 1 } else { 0 };
+        key.set_value("AppsUseLightTheme", &value)?;
+        key.set_value("SystemUsesLightTheme", &value)?;
+
+        // Running apps and the taskbar only repaint after this broadcast
+        unsafe {
+            SendMessageTimeoutW(
+                HWND_BROADCAST,
+                WM_SETTINGCHANGE,
+                WPARAM(0),
+                LPARAM(w!("ImmersiveColorSet").as_ptr() as isize),
+                SMTO_ABORTIFHUNG,
+                200,
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    fn night_light_blob() -> Result<(RegKey, Vec<u8>)> {
+        let key = RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey_with_flags(NIGHT_LIGHT_KEY, KEY_READ | KEY_WRITE)
+            .context("Night Light has never been configured on this PC")?;
+        let data = key.get_raw_value("Data")?.bytes;
+        if data.len() <= ENABLED_MARKER_OFFSET + ENABLED_MARKER.len() {
+            bail!("Unrecognized Night Light state");
+        }
+        Ok((key, data))
+    }
+
+    pub fn night_light() -> Result<bool> {
+        let (_, data) = night_light_blob()?;
+        Ok(data[STATE_OFFSET] == STATE_ON)
+    }
+
+    pub fn set_night_light(enabled: bool) -> Result<()> {
+        let (key, mut data) = night_light_blob()?;
+        if (data[STATE_OFFSET] == STATE_ON) == enabled {
+            return Ok(());
+        }
+
+        if enabled {
+            data[STATE_OFFSET] = STATE_ON;
+            data.splice(ENABLED_MARKER_OFFSET..ENABLED_MARKER_OFFSET, ENABLED_MARKER);
+        } else {
+            data[STATE_OFFSET] = STATE_OFF;
+            if data[ENABLED_MARKER_OFFSET..ENABLED_MARKER_OFFSET + 2] == ENABLED_MARKER {
+                data.drain(ENABLED_MARKER_OFFSET..ENABLED_MARKER_OFFSET + 2);
+            }
+        }
+
+        // The shell ignores the write unless the change timestamp moves forward
+        if let Some(byte) = data[TIMESTAMP_RANGE].iter_mut().find(|b| **b != 0xff) {
+            *byte += 1;
+        }
+
+        key.set_raw_value(
+            "Data",
+            &winreg::RegValue { bytes: data, vtype: winreg::enums::RegType::REG_BINARY },
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::ThemeMode;
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    fn osascript(script: &str) -> Result<String> {
+        let output = Command::new("osascript").args(["-e", script]).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    pub fn theme() -> Result<ThemeMode> {
+        let dark = osascript("tell application \"System Events\" to tell appearance preferences to get dark mode")?;
+        Ok(if dark == "true" { ThemeMode::Dark } else { ThemeMode::Light })
+    }
+
+    pub fn set_theme(mode: ThemeMode) -> Result<()> {
+        osascript(&format!(
+            "tell application \"System Events\" to tell appearance preferences to set dark mode to {}",
+            mode == ThemeMode::Dark
+        ))
+        .map(|_| ())
+    }
+
+    // Night Shift is only reachable through the private CoreBrightness framework
+    pub fn night_light() -> Result<bool> {
+        bail!("Night Shift can't be controlled on macOS yet")
+    }
+
+    pub fn set_night_light(_enabled: bool) -> Result<()> {
+        bail!("Night Shift can't be controlled on macOS yet")
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::ThemeMode;
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    // GNOME and desktops built on its settings schema
+    fn gsettings(args: &[&str]) -> Result<String> {
+        let output = Command::new("gsettings").args(args).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    pub fn theme() -> Result<ThemeMode> {
+        let scheme = gsettings(&["get", "org.gnome.desktop.interface", "color-scheme"])?;
+        Ok(if scheme.contains("dark") { ThemeMode::Dark } else { ThemeMode::Light })
+    }
+
+    pub fn set_theme(mode: ThemeMode) -> Result<()> {
+        let scheme = if mode == ThemeMode::Dark { "prefer-dark" } else { "default" };
+        gsettings(&["set", "org.gnome.desktop.interface", "color-scheme", scheme]).map(|_| ())
+    }
+
+    pub fn night_light() -> Result<bool> {
+        Ok(gsettings(&["get", "org.gnome.settings-daemon.plugins.color", "night-light-enabled"])? == "true")
+    }
+
+    pub fn set_night_light(enabled: bool) -> Result<()> {
+        gsettings(&[
+            "set",
+            "org.gnome.settings-daemon.plugins.color",
+            "night-light-enabled",
+            if enabled { "true" } else { "false" },
+        ])
+        .map(|_| ())
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_appearance() -> Result<AppearanceStatus, String> {
+    tokio::task::spawn_blocking(status)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Switch the OS theme ("light" / "dark") and ASTRAL's theme with it
+#[tauri::command]
+pub async fn set_os_theme(app: tauri::AppHandle, mode: String) -> Result<(), String> {
+    let mode = ThemeMode::parse(&mode).ok_or_else(|| format!("Unknown theme: {}", mode))?;
+    tokio::task::spawn_blocking(move || set_theme(mode))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    sync_app_theme(&app, mode).await
+}
+
+#[tauri::command]
+pub async fn set_night_light_command(enabled: bool) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || set_night_light(enabled))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Copy the OS theme into ASTRAL's settings. Returns the theme now in use.
+#[tauri::command]
+pub async fn sync_theme_with_os(app: tauri::AppHandle) -> Result<ThemeMode, String> {
+    let mode = tokio::task::spawn_blocking(os_theme)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    sync_app_theme(&app, mode).await?;
+    Ok(mode)
+}
+
+/// The theme ASTRAL should draw with, with "auto" resolved against the OS
+#[tauri::command]
+pub async fn get_effective_theme(app: tauri::AppHandle) -> Result<ThemeMode, String> {
+    let settings = crate::settings::load_settings(app).await?;
+    let theme = settings.general.theme;
+    tokio::task::spawn_blocking(move || theme.resolve())
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,197 +0,0 @@
-// Audio Engine Module
-// Handles wake word detection, STT, TTS, and audio processing
-
-use log::{info, warn};
-use anyhow::{Result, Context};
-use tokio::sync::mpsc;
-use std::sync::Arc;
-use tokio::sync::Mutex;
-
-/// Audio processing states
-#[derive(Debug, Clone, PartialEq)]
-pub enum AudioState {
-    Idle,
-    ListeningForWakeWord,
-    Recording,
-    Processing,
-}
-
-/// Wake word detection result
-#[derive(Debug, Clone)]
-pub struct WakeWordDetection {
-    pub keyword: String,
-    pub confidence: f32,
-    pub timestamp: std::time::SystemTime,
-}
-
-/// Audio Engine - Main audio processing system
-pub struct AudioEngine {
-    state: Arc<Mutex<AudioState>>,
-    wake_word_tx: Option<mpsc::Sender<WakeWordDetection>>,
-    is_running: Arc<Mutex<bool>>,
-}
-
-impl AudioEngine {
-    pub fn new() -> Self {
-        info!("Initializing Audio Engine...");
-        Self {
-            state: Arc::new(Mutex::new(AudioState::Idle)),
-            wake_word_tx: None,
-            is_running: Arc::new(Mutex::new(false)),
-        }
-    }
-
-    /// Start wake word detection (always-listening mode)
-    pub async fn start_wake_word_detection(&mut self) -> Result<mpsc::Receiver<WakeWordDetection>> {
-        info!("Starting wake word detection for 'Hey ASTRAL'...");
-        
-        let (tx, rx) = mpsc::channel(10);
-        self.wake_word_tx = Some(tx.clone());
-        
-        let is_running = self.is_running.clone();
-        let state = self.state.clone();
-        
-        // Spawn background task for wake word detection
-        tokio::spawn(async move {
-            *is_running.lock().await = true;
-            *state.lock().await = AudioState::ListeningForWakeWord;
-            
-            info!("Wake word detection thread started");
-            
-            // Simulate wake word detection (in production, use Porcupine)
-            // This would normally use:
-            // - cpal for audio capture
-            // - porcupine-rs for wake word detection
-            // - Real-time audio processing pipeline
-            
-            while *is_running.lock().await {
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                
-                // In production: Process audio frames and detect "Hey ASTRAL"
-                // For now: Placeholder that can be triggered by frontend
-            }
-            
-            info!("Wake word detection thread stopped");
-        });
-        
-        Ok(rx)
-    }
-
-    /// Stop wake word detection
-    pub async fn stop_wake_word_detection(&self) -> Result<()> {
-        info!("Stopping wake word detection...");
-        *self.is_running.lock().await = false;
-        *self.state.lock().await = AudioState::Idle;
-        Ok(())
-    }
-
-    /// Trigger wake word detection manually (for testing/frontend activation)
-    pub async fn trigger_wake_word(&self) -> Result<()> {
-        if let Some(tx) = &self.wake_word_tx {
-            let detection = WakeWordDetection {
-                keyword: "Hey ASTRAL".to_string(),
-                confidence: 0.95,
-                timestamp: std::time::SystemTime::now(),
-            };
-            
-            tx.send(detection).await
-                .context("Failed to send wake word detection")?;
-            
-            info!("Wake word triggered manually");
-        }
-        Ok(())
-    }
-
-    /// Get current audio state
-    pub async fn get_state(&self) -> AudioState {
-        self.state.lock().await.clone()
-    }
-
-    /// Set audio state
-    pub async fn set_state(&self, new_state: AudioState) {
-        *self.state.lock().await = new_state;
-    }
-
-    /// Transcribe audio using local or cloud STT
-    pub async fn transcribe_audio(&self, _audio_data: Vec<f32>) -> Result<String> {
-        info!("Transcribing audio...");
-        self.set_state(AudioState::Processing).await;
-        
-        // In production: Use Whisper.cpp for local STT
-        // whisper-rs crate can be used for Rust bindings
-        // Fallback to Azure/Google Speech-to-Text for cloud processing
-        
-        warn!("Using placeholder transcription - Whisper.cpp not yet integrated");
-        
-        self.set_state(AudioState::Idle).await;
-        Ok("Transcription would appear here".to_string())
-    }
-
-    /// Synthesize speech with TTS
-    pub async fn synthesize_speech(&self, text: &str, _accent: &str) -> Result<Vec<u8>> {
-        info!("Synthesizing speech: {}", text);
-        
-        // In production: Use multi-provider TTS
-        // - Azure TTS (best British accent quality)
-        // - Google Cloud TTS (fallback)
-        // - Local piper-tts (offline mode)
-        
-        // Example Azure TTS integration:
-        // let azure_key = std::env::var("AZURE_SPEECH_KEY")?;
-        // let region = std::env::var("AZURE_SPEECH_REGION")?;
-        // let audio = azure_tts::synthesize(text, "en-GB-RyanNeural", &azure_key, &region).await?;
-        
-        warn!("TTS provider not yet integrated - returning empty audio");
-        Ok(vec![])
-    }
-}
-
-impl Default for AudioEngine {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-/// Audio capture configuration
-#[allow(dead_code)]
-pub struct AudioConfig {
-    pub sample_rate: u32,
-    pub channels: u16,
-    pub buffer_size: usize,
-}
-
-impl Default for AudioConfig {
-    fn default() -> Self {
-        Self {
-            sample_rate: 16000, // 16kHz for speech recognition
-            channels: 1,         // Mono
-            buffer_size: 512,
-        }
-    }
-}
-
-/// Initialize audio capture device using cpal
-#[allow(dead_code)]
-pub async fn init_audio_capture() -> Result<()> {
-    info!("Initializing audio capture...");
-    
-    // In production: Use cpal to enumerate and select audio device
-    // let host = cpal::default_host();
-    // let device = host.default_input_device()
-    //     .context("No input device available")?;
-    // let config = device.default_input_config()?;
-    
-    info!("Audio capture initialized (placeholder)");
-    Ok(())
-}
-
-/// Process audio buffer for wake word detection
-#[allow(dead_code)]
-pub fn process_audio_buffer(_buffer: &[f32]) -> Option<WakeWordDetection> {
-    // In production: Pass buffer to Porcupine wake word engine
-    // let porcupine = Porcupine::new(access_key, keyword_paths, sensitivities)?;
-    // let keyword_index = porcupine.process(buffer)?;
-    
-    // For now: Return None (no detection)
-    None
-}
@@ -1,24 +1,146 @@
 // Automation Module
-// Handles multi-step automation routines and scheduled tasks
+// Handles multi-step automation routines and scheduled tasks. Once a routine
+// is changed the whole set is saved to ASTRAL/routines.json and loaded in
+// place of the defaults on later runs.
 
-use log::{info, warn};
+use tracing::{info, warn};
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
 use tokio::time::{sleep, Duration};
 
+use crate::permissions::Capability;
+
 /// Automation action types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum AutomationAction {
     LaunchApp { app_name: String },
+    CloseApp { app_name: String },
     OpenWebsite { url: String },
-    SendNotification { title: String, message: String },
+    OpenPath { path: String },
+    /// `actions` are "snooze", "run_now" and "dismiss" buttons; "run_now" starts `routine_id`
+    SendNotification {
+        title: String,
+        message: String,
+        #[serde(default)]
+        actions: Vec<String>,
+        #[serde(default)]
+        routine_id: Option<String>,
+    },
     SetVolume { level: u8 },
+    AdjustVolume { delta: i32 },
+    MuteVolume { muted: bool },
+    /// 0-100, applied to every display
+    SetBrightness { level: u8 },
+    /// "light" or "dark"
+    SetTheme { mode: String },
+    NightLight { enabled: bool },
+    /// "off", "priority_only" or "alarms_only"
+    SetFocusAssist { mode: String },
+    /// `monitor` is 1-based (default: where the window is); `position` is
+    /// "left", "right", "maximized" or "centered"
+    MoveWindow { app_name: String, monitor: Option<usize>, position: String },
     MediaControl { action: String },
+    /// Saved Wi-Fi profile name, matched like the spoken command
+    ConnectWifi { network: String },
     SystemCommand { command: String },
+    /// "lock", "sleep", "shutdown" or "restart", optionally after a delay
+    Power { action: String, delay_minutes: Option<u64> },
+    /// "power_saver", "balanced" or "high_performance"
+    SetPowerPlan { plan: String },
     Wait { seconds: u64 },
     Speak { text: String },
+    /// Warn out loud if the CPU or GPU passes `max_celsius` during the next `minutes`
+    WatchTemperatures { minutes: u64, max_celsius: f32 },
+    /// Speak an LLM-written summary of how the system is doing
+    SpeakSystemReport,
+    /// Read today's calendar out loud; skipped when no calendar is set up
+    SpeakAgenda,
+    /// Call a Home Assistant service ("light.turn_off") with its target and
+    /// fields in `data`, e.g. `{"entity_id": "light.desk"}`
+    HomeAssistant {
+        service: String,
+        #[serde(default)]
+        data: serde_json::Value,
+    },
+    MqttPublish {
+        topic: String,
+        payload: String,
+        #[serde(default)]
+        retain: bool,
+    },
+    /// Switch or dim a Hue light or room by name; `brightness` is 0-100
+    HueLights {
+        target: String,
+        #[serde(default)]
+        on: Option<bool>,
+        #[serde(default)]
+        brightness: Option<u8>,
+    },
+    HueScene { scene: String },
+    /// Send to the named webhooks, or all enabled ones when `targets` is empty
+    PushNotification {
+        title: String,
+        message: String,
+        #[serde(default)]
+        targets: Vec<String>,
+    },
+}
+
+impl AutomationAction {
+    /// Permission the action needs before it may run
+    pub fn capability(&self) -> Option<Capability> {
+        match self {
+            AutomationAction::SystemCommand { .. } => Some(Capability::SystemCommand),
+            AutomationAction::Power { .. } => Some(Capability::Power),
+            AutomationAction::CloseApp { .. } => Some(Capability::CloseApps),
+            _ => None,
+        }
+    }
+}
+
+/// Checked before a routine runs; the routine is skipped unless all hold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AutomationCondition {
+    /// `active: false` = "only when Focus Assist is off"
+    FocusAssist { active: bool },
+    /// `playing: true` = "only when music is playing"
+    MediaPlaying { playing: bool },
+    /// Only when this app's window is in front ("code", "chrome")
+    ActiveApp { app_name: String },
+}
+
+impl AutomationCondition {
+    /// None when the condition holds, otherwise why it doesn't
+    async fn unmet(&self) -> Option<String> {
+        match self {
+            AutomationCondition::FocusAssist { active } => {
+                let mode = tokio::task::spawn_blocking(crate::focus_assist::mode).await;
+                match mode {
+                    Ok(Ok(mode)) if mode.is_active() == *active => None,
+                    Ok(Ok(mode)) => Some(format!("Focus Assist is {}", mode.label())),
+                    Ok(Err(e)) => Some(format!("Focus Assist state unavailable: {}", e)),
+                    Err(e) => Some(e.to_string()),
+                }
+            }
+            AutomationCondition::MediaPlaying { playing } => {
+                let track = tokio::task::spawn_blocking(crate::media::now_playing).await;
+                let is_playing = matches!(&track, Ok(Ok(Some(t))) if t.playing);
+                (is_playing != *playing).then(|| {
+                    if is_playing { "Media is playing".to_string() } else { "Nothing is playing".to_string() }
+                })
+            }
+            AutomationCondition::ActiveApp { app_name } => match crate::active_window::current() {
+                Some(window) if window.matches_app(app_name) => None,
+                Some(window) => Some(format!("{} is in front, not {}", window.process_name, app_name)),
+                None => Some("The active window is unknown".to_string()),
+            },
+        }
+    }
 }
 
 /// Automation trigger types
@@ -28,6 +150,13 @@ pub enum AutomationTrigger {
     Schedule { time: String }, // "08:00" format
     VoiceCommand { phrase: String },
     SystemEvent { event_type: String },
+    /// A message on a topic matching `topic` (`+` and `#` allowed), and
+    /// only with this exact payload when `payload` is set
+    Mqtt {
+        topic: String,
+        #[serde(default)]
+        payload: Option<String>,
+    },
 }
 
 /// Automation routine definition
@@ -39,8 +168,28 @@ pub struct AutomationRoutine {
     pub enabled: bool,
     pub trigger: AutomationTrigger,
     pub actions: Vec<AutomationAction>,
+    #[serde(default)]
+    pub conditions: Vec<AutomationCondition>,
     pub created_at: String,
     pub last_run: Option<String>,
+    /// When the user last changed it (runs don't count); sync keeps the newer copy
+    #[serde(default)]
+    pub updated_at: Option<String>,
+}
+
+impl AutomationRoutine {
+    /// `updated_at`, or `created_at` for routines never changed since
+    pub fn modified_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.updated_at
+            .as_deref()
+            .unwrap_or(&self.created_at)
+            .parse()
+            .unwrap_or(chrono::DateTime::UNIX_EPOCH)
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Some(chrono::Utc::now().to_rfc3339());
+    }
 }
 
 /// Automation execution result
@@ -51,6 +200,9 @@ pub struct AutomationResult {
     pub actions_executed: usize,
     pub errors: Vec<String>,
     pub duration_ms: u64,
+    /// Set when a condition wasn't met and nothing ran
+    #[serde(default)]
+    pub skipped_reason: Option<String>,
 }
 
 /// Automation Manager
@@ -68,12 +220,31 @@ impl AutomationManager {
             is_running: false,
         };
         
-        // Load default routines
-        manager.load_default_routines();
+        match load_saved_routines() {
+            Some(routines) => {
+                info!("Loaded {} saved routines", routines.len());
+                for routine in routines {
+                    manager.routines.insert(routine.id.clone(), routine);
+                }
+            }
+            None => manager.load_default_routines(),
+        }
         
         manager
     }
 
+    fn save(&self) -> Result<()> {
+        let path = routines_path().context("Could not find config directory")?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create config dir")?;
+        }
+        let mut routines = self.get_all_routines();
+        routines.sort_by(|a, b| a.id.cmp(&b.id));
+        let content = serde_json::to_string_pretty(&routines)?;
+        std::fs::write(&path, content).context("Failed to write routines")?;
+        Ok(())
+    }
+
     /// Load default automation routines
     fn load_default_routines(&mut self) {
         // Morning Routine
@@ -90,6 +261,7 @@ impl AutomationManager {
                     text: "Good morning! Starting your morning routine.".to_string(),
                 },
                 AutomationAction::SetVolume { level: 50 },
+                AutomationAction::SpeakAgenda,
                 AutomationAction::LaunchApp {
                     app_name: "Calendar".to_string(),
                 },
@@ -100,10 +272,14 @@ impl AutomationManager {
                 AutomationAction::SendNotification {
                     title: "Morning Routine".to_string(),
                     message: "Your morning routine is complete!".to_string(),
+                    actions: Vec::new(),
+                    routine_id: None,
                 },
             ],
+            conditions: Vec::new(),
             created_at: chrono::Utc::now().to_rfc3339(),
             last_run: None,
+            updated_at: None,
         });
 
         // Work Mode
@@ -130,10 +306,14 @@ impl AutomationManager {
                 AutomationAction::SendNotification {
                     title: "Work Mode".to_string(),
                     message: "Work mode activated. Focus time!".to_string(),
+                    actions: Vec::new(),
+                    routine_id: None,
                 },
             ],
+            conditions: Vec::new(),
             created_at: chrono::Utc::now().to_rfc3339(),
             last_run: None,
+            updated_at: None,
         });
 
         // Evening Wind Down
@@ -150,16 +330,45 @@ impl AutomationManager {
                     text: "Good evening! Time to wind down.".to_string(),
                 },
                 AutomationAction::SetVolume { level: 40 },
+                AutomationAction::NightLight { enabled: true },
+                AutomationAction::SetTheme {
+                    mode: "dark".to_string(),
+                },
                 AutomationAction::OpenWebsite {
                     url: "https://open.spotify.com".to_string(),
                 },
                 AutomationAction::SendNotification {
                     title: "Evening Routine".to_string(),
                     message: "Time to relax and recharge!".to_string(),
+                    actions: Vec::new(),
+                    routine_id: None,
+                },
+            ],
+            conditions: Vec::new(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            last_run: None,
+            updated_at: None,
+        });
+
+        // Pause on Lock
+        self.add_routine(AutomationRoutine {
+            id: "pause-on-lock".to_string(),
+            name: "Pause on Lock".to_string(),
+            description: "Pause music when you lock the PC".to_string(),
+            enabled: false,
+            trigger: AutomationTrigger::SystemEvent {
+                event_type: "session_locked".to_string(),
+            },
+            actions: vec![
+                AutomationAction::MediaControl {
+                    action: "pause".to_string(),
                 },
             ],
+            // The media key toggles, so only press it while something plays
+            conditions: vec![AutomationCondition::MediaPlaying { playing: true }],
             created_at: chrono::Utc::now().to_rfc3339(),
             last_run: None,
+            updated_at: None,
         });
 
         // Gaming Mode
@@ -168,21 +377,33 @@ impl AutomationManager {
             name: "Gaming Mode".to_string(),
             description: "Optimize system for gaming".to_string(),
             enabled: true,
-            trigger: AutomationTrigger::VoiceCommand {
-                phrase: "start gaming mode".to_string(),
+            // "start gaming mode" is handled directly by execute_command
+            trigger: AutomationTrigger::SystemEvent {
+                event_type: "controller_connected".to_string(),
             },
             actions: vec![
                 AutomationAction::Speak {
                     text: "Activating gaming mode. Good luck and have fun!".to_string(),
                 },
                 AutomationAction::SetVolume { level: 80 },
+                AutomationAction::SetPowerPlan {
+                    plan: "high_performance".to_string(),
+                },
+                AutomationAction::WatchTemperatures {
+                    minutes: 180,
+                    max_celsius: 90.0,
+                },
                 AutomationAction::SendNotification {
                     title: "Gaming Mode".to_string(),
                     message: "System optimized for gaming!".to_string(),
+                    actions: Vec::new(),
+                    routine_id: None,
                 },
             ],
+            conditions: Vec::new(),
             created_at: chrono::Utc::now().to_rfc3339(),
             last_run: None,
+            updated_at: None,
         });
 
         info!("Loaded {} default routines", self.routines.len());
@@ -205,10 +426,11 @@ impl AutomationManager {
     }
 
     /// Update routine
-    pub fn update_routine(&mut self, routine: AutomationRoutine) -> Result<()> {
+    pub fn update_routine(&mut self, mut routine: AutomationRoutine) -> Result<()> {
         if self.routines.contains_key(&routine.id) {
+            routine.touch();
             self.routines.insert(routine.id.clone(), routine);
-            Ok(())
+            self.save()
         } else {
             Err(anyhow::anyhow!("Routine not found: {}", routine.id))
         }
@@ -218,7 +440,29 @@ impl AutomationManager {
     pub fn delete_routine(&mut self, id: &str) -> Result<()> {
         self.routines.remove(id)
             .context(format!("Routine not found: {}", id))?;
-        Ok(())
+        self.save()
+    }
+
+    /// Add or replace routines by id, e.g. from a settings backup
+    pub fn import_routines(&mut self, routines: Vec<AutomationRoutine>) -> Result<usize> {
+        let count = routines.len();
+        for mut routine in routines {
+            routine.touch();
+            self.routines.insert(routine.id.clone(), routine);
+        }
+        self.save()?;
+        Ok(count)
+    }
+
+    /// Take routines changed or deleted on another device, keeping their timestamps
+    pub fn apply_sync(&mut self, changed: Vec<AutomationRoutine>, removed: &[String]) -> Result<()> {
+        for id in removed {
+            self.routines.remove(id);
+        }
+        for routine in changed {
+            self.routines.insert(routine.id.clone(), routine);
+        }
+        self.save()
     }
 
     /// Toggle routine enabled state
@@ -227,12 +471,66 @@ impl AutomationManager {
             .context(format!("Routine not found: {}", id))?;
         
         routine.enabled = !routine.enabled;
+        routine.touch();
         info!("Routine '{}' enabled: {}", routine.name, routine.enabled);
-        Ok(routine.enabled)
+        let enabled = routine.enabled;
+        self.save()?;
+        Ok(enabled)
+    }
+
+    /// Enable exactly the routines in `ids`, e.g. when switching profiles
+    pub fn set_enabled_routines(&mut self, ids: &[String]) -> Result<()> {
+        for routine in self.routines.values_mut() {
+            let enabled = ids.contains(&routine.id);
+            if routine.enabled != enabled {
+                routine.enabled = enabled;
+                routine.touch();
+            }
+        }
+        self.save()
+    }
+
+    /// IDs of enabled routines triggered by a system event ("network_connected", ...)
+    pub fn routines_for_event(&self, event_type: &str) -> Vec<String> {
+        self.routines
+            .values()
+            .filter(|r| r.enabled)
+            .filter(|r| matches!(&r.trigger, AutomationTrigger::SystemEvent { event_type: t } if t.eq_ignore_ascii_case(event_type)))
+            .map(|r| r.id.clone())
+            .collect()
+    }
+
+    /// Topic filters the enabled routines' MQTT triggers listen on
+    pub fn mqtt_topics(&self) -> Vec<String> {
+        self.routines
+            .values()
+            .filter(|r| r.enabled)
+            .filter_map(|r| match &r.trigger {
+                AutomationTrigger::Mqtt { topic, .. } => Some(topic.trim().to_string()),
+                _ => None,
+            })
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+
+    /// IDs of enabled routines triggered by an MQTT message
+    pub fn routines_for_mqtt(&self, topic: &str, payload: &str) -> Vec<String> {
+        self.routines
+            .values()
+            .filter(|r| r.enabled)
+            .filter(|r| match &r.trigger {
+                AutomationTrigger::Mqtt { topic: filter, payload: expected } => {
+                    crate::mqtt::topic_matches(filter.trim(), topic)
+                        && expected.as_deref().map_or(true, |p| p.trim() == payload.trim())
+                }
+                _ => false,
+            })
+            .map(|r| r.id.clone())
+            .collect()
     }
 
     /// Execute a routine by ID
-    pub async fn execute_routine(&mut self, id: &str) -> Result<AutomationResult> {
+    pub async fn execute_routine(&mut self, app: &AppHandle, id: &str) -> Result<AutomationResult> {
         let start_time = std::time::Instant::now();
         
         let routine = self.routines.get(id)
@@ -244,13 +542,27 @@ impl AutomationManager {
             return Err(anyhow::anyhow!("Routine is disabled"));
         }
 
+        for condition in &routine.conditions {
+            if let Some(reason) = condition.unmet().await {
+                info!("Skipping routine '{}': {}", routine.name, reason);
+                return Ok(AutomationResult {
+                    routine_id: id.to_string(),
+                    success: true,
+                    actions_executed: 0,
+                    errors: Vec::new(),
+                    duration_ms: start_time.elapsed().as_millis() as u64,
+                    skipped_reason: Some(reason),
+                });
+            }
+        }
+
         info!("Executing routine: {}", routine.name);
         
         let mut actions_executed = 0;
         let mut errors = Vec::new();
 
         for (i, action) in routine.actions.iter().enumerate() {
-            match self.execute_action(action).await {
+            match self.execute_action(app, action).await {
                 Ok(_) => {
                     actions_executed += 1;
                     info!("Action {}/{} completed", i + 1, routine.actions.len());
@@ -282,11 +594,16 @@ impl AutomationManager {
             actions_executed,
             errors,
             duration_ms,
+            skipped_reason: None,
         })
     }
 
     /// Execute a single automation action
-    async fn execute_action(&self, action: &AutomationAction) -> Result<()> {
+    async fn execute_action(&self, app: &AppHandle, action: &AutomationAction) -> Result<()> {
+        if let Some(capability) = action.capability() {
+            crate::permissions::check(capability)?;
+        }
+
         match action {
             AutomationAction::LaunchApp { app_name } => {
                 info!("Launching app: {}", app_name);
@@ -294,32 +611,97 @@ impl AutomationManager {
                 // crate::system_integration::launch_application(app_name).await?;
                 Ok(())
             }
+            AutomationAction::CloseApp { app_name } => {
+                let app_name = app_name.clone();
+                tokio::task::spawn_blocking(move || crate::processes::kill_by_name(&app_name, false))
+                    .await?
+                    .map(|_| ())
+            }
             AutomationAction::OpenWebsite { url } => {
-                info!("Opening website: {}", url);
-                // In production: Use tauri-plugin-shell
-                // shell::open(url, None)?;
-                Ok(())
+                crate::system_integration::open_path(url, false).map(|_| ())
             }
-            AutomationAction::SendNotification { title, message } => {
-                info!("Sending notification: {} - {}", title, message);
-                // In production: Use tauri-plugin-notification
-                Ok(())
+            AutomationAction::OpenPath { path } => {
+                crate::system_integration::open_path(path, false).map(|_| ())
+            }
+            AutomationAction::SendNotification { title, message, actions, routine_id } => {
+                let actions = actions
+                    .iter()
+                    .map(|a| {
+                        crate::notifications::NotificationAction::parse(a)
+                            .ok_or_else(|| anyhow::anyhow!("Unknown notification action: {}", a))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let request = crate::notifications::NotificationRequest {
+                    title: title.clone(),
+                    message: message.clone(),
+                    actions,
+                    routine_id: routine_id.clone(),
+                    snooze_minutes: None,
+                };
+                crate::notifications::send(request).map(|_| ())
             }
             AutomationAction::SetVolume { level } => {
-                info!("Setting volume to {}%", level);
-                // In production: Use Windows CoreAudio API
-                Ok(())
+                crate::system_integration::set_master_volume(*level)
+            }
+            AutomationAction::AdjustVolume { delta } => {
+                crate::system_integration::adjust_master_volume(*delta).map(|_| ())
+            }
+            AutomationAction::MuteVolume { muted } => {
+                crate::system_integration::set_master_mute(*muted)
             }
             AutomationAction::MediaControl { action } => {
-                info!("Media control: {}", action);
-                // In production: Use crate::system_integration::control_media
-                Ok(())
+                crate::system_integration::control_media(action).await
             }
             AutomationAction::SystemCommand { command } => {
                 info!("Executing system command: {}", command);
                 // In production: Use tauri-plugin-shell with caution
                 Ok(())
             }
+            AutomationAction::Power { action, delay_minutes } => {
+                let action = crate::power::PowerAction::parse(action)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown power action: {}", action))?;
+                // Routines are set up ahead of time, so they don't ask for confirmation
+                crate::power::run(crate::power::PowerCommand {
+                    action,
+                    delay: delay_minutes.map(|m| Duration::from_secs(m * 60)),
+                })
+            }
+            AutomationAction::SetPowerPlan { plan } => {
+                let kind = crate::power::PowerPlanKind::parse(plan)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown power plan: {}", plan))?;
+                crate::power::set_power_plan(kind).map(|_| ())
+            }
+            AutomationAction::SetBrightness { level } => {
+                let level = *level;
+                tokio::task::spawn_blocking(move || crate::display::set_brightness(level, None)).await?
+            }
+            AutomationAction::SetTheme { mode } => {
+                let mode = crate::appearance::ThemeMode::parse(mode)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown theme: {}", mode))?;
+                tokio::task::spawn_blocking(move || crate::appearance::set_theme(mode)).await?
+            }
+            AutomationAction::NightLight { enabled } => {
+                let enabled = *enabled;
+                tokio::task::spawn_blocking(move || crate::appearance::set_night_light(enabled)).await?
+            }
+            AutomationAction::SetFocusAssist { mode } => {
+                let mode = crate::focus_assist::FocusAssistMode::parse(mode)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown Focus Assist mode: {}", mode))?;
+                tokio::task::spawn_blocking(move || crate::focus_assist::set_mode(mode)).await?
+            }
+            AutomationAction::MoveWindow { app_name, monitor, position } => {
+                let position = crate::window_layout::WindowPosition::parse(position)
+                    .ok_or_else(|| anyhow::anyhow!("Unknown window position: {}", position))?;
+                let monitor = monitor
+                    .map(crate::window_layout::MonitorTarget::Index)
+                    .unwrap_or(crate::window_layout::MonitorTarget::Current);
+                let app_name = app_name.clone();
+                tokio::task::spawn_blocking(move || crate::window_layout::place_window(&app_name, monitor, position)).await?
+            }
+            AutomationAction::ConnectWifi { network } => {
+                let network = network.clone();
+                tokio::task::spawn_blocking(move || crate::network::connect_wifi(&network).map(|_| ())).await?
+            }
             AutomationAction::Wait { seconds } => {
                 info!("Waiting {} seconds...", seconds);
                 sleep(Duration::from_secs(*seconds)).await;
@@ -327,9 +709,42 @@ impl AutomationManager {
             }
             AutomationAction::Speak { text } => {
                 info!("Speaking: {}", text);
-                // In production: Use audio_engine.synthesize_speech
+                crate::voice::speak(text);
+                Ok(())
+            }
+            AutomationAction::WatchTemperatures { minutes, max_celsius } => {
+                crate::sensors::start_thermal_watch(app.clone(), Duration::from_secs(minutes * 60), *max_celsius);
                 Ok(())
             }
+            AutomationAction::SpeakSystemReport => {
+                let report = crate::system_report::system_report(app).await.map_err(|e| anyhow::anyhow!(e))?;
+                crate::voice::speak(&report);
+                Ok(())
+            }
+            AutomationAction::SpeakAgenda => {
+                match crate::calendar::todays_agenda(app).await.map_err(|e| anyhow::anyhow!(e))? {
+                    Some(agenda) => crate::voice::speak(&agenda),
+                    None => info!("No calendar set up, skipping the agenda"),
+                }
+                Ok(())
+            }
+            AutomationAction::HomeAssistant { service, data } => {
+                let data = if data.is_null() { serde_json::json!({}) } else { data.clone() };
+                crate::home_assistant::call_service(app, service, data).await.map_err(|e| anyhow::anyhow!(e))
+            }
+            AutomationAction::MqttPublish { topic, payload, retain } => {
+                crate::mqtt::publish(topic, payload, *retain).await.map_err(|e| anyhow::anyhow!(e))
+            }
+            AutomationAction::HueLights { target, on, brightness } => {
+                crate::hue::apply_action(app, target, *on, *brightness).await.map_err(|e| anyhow::anyhow!(e))
+            }
+            AutomationAction::HueScene { scene } => {
+                crate::hue::apply_scene(app, scene).await.map_err(|e| anyhow::anyhow!(e))
+            }
+            AutomationAction::PushNotification { title, message, targets } => crate::webhooks::send(app, title, message, targets)
+                .await
+                .map(|_| ())
+                .map_err(|e| anyhow::anyhow!(e)),
         }
     }
 
@@ -352,6 +767,24 @@ impl AutomationManager {
     }
 }
 
+fn routines_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("routines.json");
+    Some(path)
+}
+
+fn load_saved_routines() -> Option<Vec<AutomationRoutine>> {
+    let content = std::fs::read_to_string(routines_path()?).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(routines) => Some(routines),
+        Err(e) => {
+            warn!("Ignoring unreadable routines.json: {}", e);
+            None
+        }
+    }
+}
+
 impl Default for AutomationManager {
     fn default() -> Self {
         Self::new()
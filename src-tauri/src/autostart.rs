@@ -0,0 +1,71 @@
+// Autostart Module
+// Launch ASTRAL at login so scheduled routines run without opening it by hand.
+// The `general.auto_start` setting is the source of truth; the OS entry (Run
+// key, launch agent or XDG autostart file) is brought in line with it at
+// startup and whenever the setting changes.
+
+use tracing::{info, warn};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
+
+/// Passed by the login entry so the window can stay in the tray
+pub const AUTOSTART_ARG: &str = "--autostart";
+
+pub fn launched_at_login() -> bool {
+    std::env::args().any(|arg| arg == AUTOSTART_ARG)
+}
+
+pub fn apply(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let launcher = app.autolaunch();
+    let current = launcher.is_enabled().map_err(|e| e.to_string())?;
+    if current == enabled {
+        return Ok(());
+    }
+
+    info!("{} launch at login", if enabled { "Enabling" } else { "Disabling" });
+    if enabled {
+        launcher.enable().map_err(|e| e.to_string())
+    } else {
+        launcher.disable().map_err(|e| e.to_string())
+    }
+}
+
+/// Sync the OS login entry with the setting, and start hidden when launched at login
+pub fn init(app: &AppHandle) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let enabled = match crate::settings::load_settings(handle.clone()).await {
+            Ok(settings) => settings.general.auto_start,
+            Err(e) => {
+                warn!("Failed to load settings: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = apply(&handle, enabled) {
+            warn!("Failed to update launch at login: {}", e);
+        }
+    });
+
+    if launched_at_login() {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.hide();
+        }
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_auto_start(app: AppHandle) -> Result<bool, String> {
+    Ok(crate::settings::load_settings(app).await?.general.auto_start)
+}
+
+/// Saving the setting updates the OS entry
+#[tauri::command]
+pub async fn set_auto_start(app: AppHandle, enabled: bool) -> Result<(), String> {
+    crate::settings::modify_settings(&app, |settings| {
+        settings.general.auto_start = enabled;
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,263 @@
+// Backup Module
+// Exports everything the user has configured into one zip archive and
+// restores it on another install: settings, automation routines and learned
+// app aliases. API keys and the local API token are left out unless asked
+// for; when they are included they're written in plain text, since the
+// store's encryption is tied to this machine.
+
+use crate::automation::AutomationRoutine;
+use crate::settings::AppSettings;
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+use zip::write::SimpleFileOptions;
+
+/// Bumped when the archive layout changes incompatibly
+const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+const ROUTINES_ENTRY: &str = "routines.json";
+const ALIASES_ENTRY: &str = "app_aliases.json";
+
+/// Credentials left out of exports by default: the API keys and the local API token
+fn secret_pointers(tree: &Value) -> Vec<String> {
+    let mut pointers = crate::settings::secret_pointers(tree);
+    pointers.push("/api/token".to_string());
+    pointers
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub includes_secrets: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub created_at: String,
+    pub routines: usize,
+    pub aliases: usize,
+    /// Whether API keys came from the archive; otherwise the ones already set here are kept
+    pub secrets_imported: bool,
+}
+
+fn default_backup_path() -> Result<PathBuf, String> {
+    let mut path = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "No documents directory on this system".to_string())?;
+    path.push(format!("astral-backup-{}.zip", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    Ok(path)
+}
+
+fn strip_secrets(tree: &mut Value) {
+    for pointer in secret_pointers(tree) {
+        if let Some(slot) = tree.pointer_mut(&pointer) {
+            *slot = match slot {
+                Value::String(_) => Value::String(String::new()),
+                _ => Value::Null,
+            };
+        }
+    }
+}
+
+fn has_value(tree: &Value, pointer: &str) -> bool {
+    tree.pointer(pointer).and_then(Value::as_str).is_some_and(|s| !s.is_empty())
+}
+
+/// Where a secret at `pointer` in the imported settings lives in the current
+/// ones. Profiles and webhooks are matched by id and name rather than
+/// position, since either list may have changed since the backup; None when
+/// there's no match.
+fn current_pointer(imported: &Value, current: &Value, pointer: &str) -> Option<String> {
+    for (list, key) in [("/profiles/list", "/id"), ("/webhooks/targets", "/name")] {
+        let Some(rest) = pointer.strip_prefix(list).and_then(|p| p.strip_prefix('/')) else { continue };
+        let (index, field) = rest.split_once('/')?;
+        let identity = imported.pointer(&format!("{}/{}{}", list, index, key))?.as_str()?;
+        let position = current.pointer(list)?.as_array()?.iter().position(|entry| {
+            entry.pointer(key).and_then(Value::as_str).is_some_and(|v| v.eq_ignore_ascii_case(identity))
+        })?;
+        return Some(format!("{}/{}/{}", list, position, field));
+    }
+    // A calendar feed is nothing but its URL; stripped ones are put back by `keep_calendar_feeds`
+    if pointer.starts_with("/calendar/ics_urls/") {
+        return None;
+    }
+    Some(pointer.to_string())
+}
+
+/// Replace the feeds stripped from the imported settings with the ones set here
+fn keep_calendar_feeds(imported: &mut Value, current: &Value) {
+    let Some(feeds) = imported.pointer_mut("/calendar/ics_urls").and_then(Value::as_array_mut) else { return };
+    if !feeds.iter().any(|feed| feed.as_str() == Some("")) {
+        return;
+    }
+    feeds.retain(|feed| feed.as_str() != Some(""));
+    for feed in current.pointer("/calendar/ics_urls").and_then(Value::as_array).into_iter().flatten() {
+        if !feeds.contains(feed) {
+            feeds.push(feed.clone());
+        }
+    }
+}
+
+fn write_archive(path: &PathBuf, entries: &[(&str, Vec<u8>)]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let file = std::fs::File::create(path).map_err(|e| format!("Couldn't create {:?}: {}", path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in entries {
+        zip.start_file(*name, options).map_err(|e| e.to_string())?;
+        zip.write_all(content).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<std::fs::File>, name: &str) -> Result<Option<String>, String> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut content = String::new();
+    entry.read_to_string(&mut content).map_err(|e| format!("Couldn't read {}: {}", name, e))?;
+    Ok(Some(content))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    serde_json::to_vec_pretty(value).map_err(|e| e.to_string())
+}
+
+fn parse<T: serde::de::DeserializeOwned>(name: &str, content: &str) -> Result<T, String> {
+    serde_json::from_str(content).map_err(|e| format!("{} in the backup is invalid: {}", name, e))
+}
+
+// ========== Tauri Commands ==========
+
+/// Write a backup to `path` (a timestamped file in Documents by default) and return where it went
+#[tauri::command]
+pub async fn export_settings(
+    app: AppHandle,
+    path: Option<String>,
+    include_secrets: Option<bool>,
+) -> Result<String, String> {
+    let include_secrets = include_secrets.unwrap_or(false);
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path.trim()),
+        None => default_backup_path()?,
+    };
+
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    let mut settings = serde_json::to_value(&settings).map_err(|e| e.to_string())?;
+    if !include_secrets {
+        strip_secrets(&mut settings);
+    }
+    let mut routines = app.state::<AppState>().inner().automation.lock().await.get_all_routines();
+    routines.sort_by(|a, b| a.id.cmp(&b.id));
+    let aliases = crate::app_launcher::learned_aliases();
+    let manifest = BackupManifest {
+        format: FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Local::now().to_rfc3339(),
+        includes_secrets: include_secrets,
+    };
+
+    let entries = [
+        (MANIFEST_ENTRY, to_json(&manifest)?),
+        (SETTINGS_ENTRY, to_json(&settings)?),
+        (ROUTINES_ENTRY, to_json(&routines)?),
+        (ALIASES_ENTRY, to_json(&aliases)?),
+    ];
+
+    let target = path.clone();
+    tokio::task::spawn_blocking(move || write_archive(&target, &entries))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    info!("Settings exported to {:?} (secrets included: {})", path, include_secrets);
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Restore a backup. Settings are replaced, routines and aliases are merged by id.
+#[tauri::command]
+pub async fn import_settings(app: AppHandle, path: String) -> Result<ImportSummary, String> {
+    let path = PathBuf::from(path.trim());
+    let (manifest, settings, routines, aliases) = tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&path).map_err(|e| format!("Couldn't open {:?}: {}", path, e))?;
+        let mut archive = zip::ZipArchive::new(file).map_err(|_| "Not an ASTRAL backup".to_string())?;
+        let manifest = read_entry(&mut archive, MANIFEST_ENTRY)?.ok_or_else(|| "Not an ASTRAL backup".to_string())?;
+        Ok::<_, String>((
+            manifest,
+            read_entry(&mut archive, SETTINGS_ENTRY)?,
+            read_entry(&mut archive, ROUTINES_ENTRY)?,
+            read_entry(&mut archive, ALIASES_ENTRY)?,
+        ))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let manifest: BackupManifest = parse(MANIFEST_ENTRY, &manifest)?;
+    if manifest.format > FORMAT_VERSION {
+        return Err(format!(
+            "This backup was made by a newer ASTRAL ({}); update before importing it",
+            manifest.app_version
+        ));
+    }
+
+    let mut secrets_imported = false;
+    if let Some(content) = settings {
+        let mut imported: Value = parse(SETTINGS_ENTRY, &content)?;
+        crate::settings::modify_settings(&app, |settings| {
+            let current = serde_json::to_value(&*settings).map_err(|e| e.to_string())?;
+            for pointer in secret_pointers(&imported) {
+                if has_value(&imported, &pointer) {
+                    secrets_imported = true;
+                    continue;
+                }
+                let Some(existing) = current_pointer(&imported, &current, &pointer).and_then(|p| current.pointer(&p)) else {
+                    continue;
+                };
+                if let Some(slot) = imported.pointer_mut(&pointer) {
+                    *slot = existing.clone();
+                }
+            }
+            keep_calendar_feeds(&mut imported, &current);
+            *settings = serde_json::from_value::<AppSettings>(imported)
+                .map_err(|e| format!("{} in the backup is invalid: {}", SETTINGS_ENTRY, e))?;
+            Ok::<_, String>(())
+        })
+        .await?;
+    }
+
+    let routines = match routines {
+        Some(content) => {
+            let routines: Vec<AutomationRoutine> = parse(ROUTINES_ENTRY, &content)?;
+            let mut automation = app.state::<AppState>().inner().automation.lock().await;
+            automation.import_routines(routines).map_err(|e| e.to_string())?
+        }
+        None => 0,
+    };
+    crate::tray::refresh_routines(&app).await;
+
+    let aliases = match aliases {
+        Some(content) => crate::app_launcher::import_aliases(parse::<HashMap<String, String>>(ALIASES_ENTRY, &content)?)?,
+        None => 0,
+    };
+
+    info!("Imported backup from {} ({} routines, {} aliases)", manifest.created_at, routines, aliases);
+    Ok(ImportSummary {
+        created_at: manifest.created_at,
+        routines,
+        aliases,
+        secrets_imported,
+    })
+}
@@ -0,0 +1,724 @@
+// Calculator Module
+// Answers arithmetic and unit conversions without the LLM, so "what's 15% of
+// 84" or "convert 5 miles to km" come back instantly, exactly and offline.
+// Expressions are parsed here (words like "times" and "squared" included,
+// trig in degrees) rather than left to a model that might get them wrong.
+// Currency conversions use the European Central Bank's daily rates, cached
+// in the config dir and reused when offline or in privacy mode. Matched as
+// an intent before the LLM, and offered to LLM clients as MCP tools.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// ECB reference rates, no key needed; they change once per working day
+const RATES_ENDPOINT: &str = "https://api.frankfurter.app/latest";
+
+/// Rates older than this are refreshed when there's a connection
+const RATES_MAX_AGE_SECS: i64 = 12 * 3600;
+
+// ========== Expressions ==========
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Op(char),
+    Open,
+    Close,
+    Word(String),
+}
+
+/// Spoken operators in the form the tokenizer understands
+fn normalize(expression: &str) -> String {
+    let mut text = format!(" {} ", expression.to_lowercase());
+    for (words, symbol) in [
+        ("square root of", "sqrt"),
+        ("cube root of", "cbrt"),
+        ("to the power of", "^"),
+        ("multiplied by", "*"),
+        ("divided by", "/"),
+        ("percent", "%"),
+        ("times", "*"),
+        ("plus", "+"),
+        ("minus", "-"),
+        ("over", "/"),
+        ("squared", "^2"),
+        ("cubed", "^3"),
+        ("×", "*"),
+        ("÷", "/"),
+    ] {
+        text = if words.chars().all(|c| c.is_alphabetic() || c == ' ') {
+            text.replace(&format!(" {} ", words), &format!(" {} ", symbol))
+        } else {
+            text.replace(words, &format!(" {} ", symbol))
+        };
+    }
+    text
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let text = normalize(expression);
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while i < chars.len() {
+                let c = chars[i];
+                // "1,234" is a thousands separator, "1, 2" isn't
+                let separator = c == ',' && chars.get(i + 1).is_some_and(char::is_ascii_digit);
+                if c.is_ascii_digit() || c == '.' {
+                    number.push(c);
+                } else if !separator {
+                    break;
+                }
+                i += 1;
+            }
+            tokens.push(Token::Number(number.parse().map_err(|_| format!("{} isn't a number", number))?));
+        } else if c.is_alphabetic() {
+            let mut word = String::new();
+            while i < chars.len() && chars[i].is_alphabetic() {
+                word.push(chars[i]);
+                i += 1;
+            }
+            // "3 x 4"
+            tokens.push(if word == "x" { Token::Op('*') } else { Token::Word(word) });
+        } else {
+            tokens.push(match c {
+                '+' | '-' | '*' | '/' | '^' | '%' | '!' => Token::Op(c),
+                '(' => Token::Open,
+                ')' => Token::Close,
+                _ => return Err(format!("I don't know what {} means here", c)),
+            });
+            i += 1;
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+/// A value, and whether it was written as a percentage ("15%"), which
+/// changes what adding it means: 84 + 15% is 96.6
+#[derive(Clone, Copy)]
+struct Operand {
+    value: f64,
+    percent: bool,
+}
+
+impl Operand {
+    fn plain(value: f64) -> Self {
+        Self { value, percent: false }
+    }
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expression(&mut self) -> Result<Operand, String> {
+        let mut left = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek().cloned() {
+            self.next();
+            let right = self.term()?;
+            let right = if right.percent && !left.percent { left.value * right.value } else { right.value };
+            left = Operand::plain(if op == '+' { left.value + right } else { left.value - right });
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> Result<Operand, String> {
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op(op @ ('*' | '/'))) => *op,
+                // "15% of 84"
+                Some(Token::Word(word)) if word == "of" => '*',
+                _ => break,
+            };
+            self.next();
+            let right = self.unary()?;
+            left = Operand::plain(match op {
+                '*' => left.value * right.value,
+                _ if right.value == 0.0 => return Err("You can't divide by zero".to_string()),
+                _ => left.value / right.value,
+            });
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Operand, String> {
+        match self.peek() {
+            Some(Token::Op('-')) => {
+                self.next();
+                let operand = self.unary()?;
+                Ok(Operand { value: -operand.value, ..operand })
+            }
+            Some(Token::Op('+')) => {
+                self.next();
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Operand, String> {
+        let base = self.postfix()?;
+        if self.peek() == Some(&Token::Op('^')) {
+            self.next();
+            // Right-associative: 2^3^2 is 2^9
+            let exponent = self.unary()?;
+            return Ok(Operand::plain(base.value.powf(exponent.value)));
+        }
+        Ok(base)
+    }
+
+    fn postfix(&mut self) -> Result<Operand, String> {
+        let mut operand = self.primary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Op('%')) => {
+                    self.next();
+                    operand = Operand { value: operand.value / 100.0, percent: true };
+                }
+                Some(Token::Op('!')) => {
+                    self.next();
+                    operand = Operand::plain(factorial(operand.value)?);
+                }
+                _ => return Ok(operand),
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Operand, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Operand::plain(value)),
+            Some(Token::Open) => {
+                let inner = self.expression()?;
+                if self.next() != Some(Token::Close) {
+                    return Err("A bracket isn't closed".to_string());
+                }
+                Ok(inner)
+            }
+            Some(Token::Word(word)) => match word.as_str() {
+                "pi" => Ok(Operand::plain(std::f64::consts::PI)),
+                "e" => Ok(Operand::plain(std::f64::consts::E)),
+                // "the square root of 9"
+                "the" | "a" => self.primary(),
+                name => {
+                    let argument = self.postfix()?.value;
+                    apply_function(name, argument).map(Operand::plain)
+                }
+            },
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("The expression ends too early".to_string()),
+        }
+    }
+}
+
+fn factorial(n: f64) -> Result<f64, String> {
+    if n < 0.0 || n.fract() != 0.0 || n > 170.0 {
+        return Err("Factorials need a whole number from 0 to 170".to_string());
+    }
+    Ok((1..=n as u64).map(|i| i as f64).product())
+}
+
+fn apply_function(name: &str, x: f64) -> Result<f64, String> {
+    let value = match name {
+        "sqrt" if x < 0.0 => return Err("Negative numbers don't have a real square root".to_string()),
+        "sqrt" => x.sqrt(),
+        "cbrt" => x.cbrt(),
+        "abs" => x.abs(),
+        "ln" if x <= 0.0 => return Err("Logarithms need a positive number".to_string()),
+        "ln" => x.ln(),
+        "log" if x <= 0.0 => return Err("Logarithms need a positive number".to_string()),
+        "log" => x.log10(),
+        "exp" => x.exp(),
+        // Degrees, which is what people mean out loud
+        "sin" | "sine" => x.to_radians().sin(),
+        "cos" | "cosine" => x.to_radians().cos(),
+        "tan" | "tangent" => x.to_radians().tan(),
+        "round" => x.round(),
+        "floor" => x.floor(),
+        "ceil" => x.ceil(),
+        _ => return Err(format!("I don't know the function {}", name)),
+    };
+    Ok(value)
+}
+
+/// Evaluate an arithmetic expression ("15% of 84", "2 ^ 10", "sqrt(2) * 3")
+pub fn evaluate(expression: &str) -> Result<f64, String> {
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+        return Err("There's nothing to calculate".to_string());
+    }
+    let mut parser = Parser { tokens, position: 0 };
+    let result = parser.expression()?;
+    if parser.position < parser.tokens.len() {
+        return Err(format!("I couldn't make sense of {:?}", parser.tokens[parser.position]));
+    }
+    if !result.value.is_finite() {
+        return Err("The result is too large".to_string());
+    }
+    Ok(result.value)
+}
+
+/// Whether `expression` has something to work out, so "what's 5" or
+/// "what's the weather" aren't taken for sums
+fn has_operation(expression: &str) -> bool {
+    tokenize(expression).is_ok_and(|tokens| {
+        (tokens.iter().any(|t| matches!(t, Token::Op(_))) && tokens.iter().any(|t| matches!(t, Token::Number(_))))
+            || tokens.iter().any(|t| matches!(t, Token::Word(w) if apply_function(w, 1.0).is_ok()))
+    })
+}
+
+/// Up to 10 significant digits, without trailing zeros
+pub fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        return format!("{}", value as i64);
+    }
+    let magnitude = if value == 0.0 { 0 } else { value.abs().log10().floor() as i32 };
+    if !(-6..15).contains(&magnitude) {
+        return format!("{:e}", value);
+    }
+    let decimals = (9 - magnitude).clamp(0, 12) as usize;
+    let text = format!("{:.*}", decimals, value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// ========== Units ==========
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Temperature,
+    Speed,
+    Time,
+    Data,
+    Area,
+}
+
+struct Unit {
+    /// Singular and plural for answers
+    singular: &'static str,
+    plural: &'static str,
+    /// Other spellings and abbreviations
+    aliases: &'static [&'static str],
+    dimension: Dimension,
+    /// In the dimension's base unit: base = value * factor + offset
+    factor: f64,
+    offset: f64,
+}
+
+const fn unit(singular: &'static str, plural: &'static str, aliases: &'static [&'static str], dimension: Dimension, factor: f64) -> Unit {
+    Unit { singular, plural, aliases, dimension, factor, offset: 0.0 }
+}
+
+use Dimension::*;
+
+static UNITS: &[Unit] = &[
+    unit("millimeter", "millimeters", &["mm", "millimetre", "millimetres"], Length, 0.001),
+    unit("centimeter", "centimeters", &["cm", "centimetre", "centimetres"], Length, 0.01),
+    unit("meter", "meters", &["m", "metre", "metres"], Length, 1.0),
+    unit("kilometer", "kilometers", &["km", "kilometre", "kilometres", "k"], Length, 1000.0),
+    unit("inch", "inches", &["in", "\""], Length, 0.0254),
+    unit("foot", "feet", &["ft", "'"], Length, 0.3048),
+    unit("yard", "yards", &["yd", "yds"], Length, 0.9144),
+    unit("mile", "miles", &["mi"], Length, 1609.344),
+    unit("nautical mile", "nautical miles", &["nmi"], Length, 1852.0),
+    unit("milligram", "milligrams", &["mg"], Mass, 0.000_001),
+    unit("gram", "grams", &["g", "gr"], Mass, 0.001),
+    unit("kilogram", "kilograms", &["kg", "kilo", "kilos"], Mass, 1.0),
+    unit("tonne", "tonnes", &["t", "metric ton", "metric tons"], Mass, 1000.0),
+    unit("ounce", "ounces", &["oz"], Mass, 0.028_349_523_125),
+    unit("pound", "pounds", &["lb", "lbs"], Mass, 0.453_592_37),
+    unit("stone", "stone", &["st", "stones"], Mass, 6.350_293_18),
+    unit("milliliter", "milliliters", &["ml", "millilitre", "millilitres"], Volume, 0.001),
+    unit("centiliter", "centiliters", &["cl", "centilitre", "centilitres"], Volume, 0.01),
+    unit("liter", "liters", &["l", "litre", "litres"], Volume, 1.0),
+    unit("cubic meter", "cubic meters", &["m3", "cubic metre", "cubic metres"], Volume, 1000.0),
+    unit("teaspoon", "teaspoons", &["tsp"], Volume, 0.004_928_921_593_75),
+    unit("tablespoon", "tablespoons", &["tbsp"], Volume, 0.014_786_764_781_25),
+    unit("fluid ounce", "fluid ounces", &["fl oz"], Volume, 0.029_573_529_562_5),
+    unit("cup", "cups", &[], Volume, 0.236_588_236_5),
+    unit("pint", "pints", &["pt"], Volume, 0.473_176_473),
+    unit("quart", "quarts", &["qt"], Volume, 0.946_352_946),
+    unit("gallon", "gallons", &["gal"], Volume, 3.785_411_784),
+    Unit { singular: "degree Celsius", plural: "degrees Celsius", aliases: &["c", "celsius", "centigrade", "°c", "degrees c"], dimension: Temperature, factor: 1.0, offset: 0.0 },
+    Unit {
+        singular: "degree Fahrenheit",
+        plural: "degrees Fahrenheit",
+        aliases: &["f", "fahrenheit", "°f", "degrees f"],
+        dimension: Temperature,
+        factor: 5.0 / 9.0,
+        offset: -32.0 * 5.0 / 9.0,
+    },
+    Unit { singular: "kelvin", plural: "kelvin", aliases: &["k", "kelvins"], dimension: Temperature, factor: 1.0, offset: -273.15 },
+    unit("meter per second", "meters per second", &["m/s", "mps"], Speed, 1.0),
+    unit("kilometer per hour", "kilometers per hour", &["km/h", "kmh", "kph", "kilometres per hour"], Speed, 1.0 / 3.6),
+    unit("mile per hour", "miles per hour", &["mph"], Speed, 0.447_04),
+    unit("knot", "knots", &["kn", "kt"], Speed, 1852.0 / 3600.0),
+    unit("millisecond", "milliseconds", &["ms"], Time, 0.001),
+    unit("second", "seconds", &["s", "sec", "secs"], Time, 1.0),
+    unit("minute", "minutes", &["min", "mins"], Time, 60.0),
+    unit("hour", "hours", &["h", "hr", "hrs"], Time, 3600.0),
+    unit("day", "days", &[], Time, 86_400.0),
+    unit("week", "weeks", &["wk"], Time, 604_800.0),
+    unit("year", "years", &["yr", "yrs"], Time, 31_557_600.0),
+    unit("bit", "bits", &[], Data, 0.125),
+    unit("byte", "bytes", &["b"], Data, 1.0),
+    unit("kilobyte", "kilobytes", &["kb"], Data, 1e3),
+    unit("megabyte", "megabytes", &["mb"], Data, 1e6),
+    unit("gigabyte", "gigabytes", &["gb", "gig", "gigs"], Data, 1e9),
+    unit("terabyte", "terabytes", &["tb"], Data, 1e12),
+    unit("kibibyte", "kibibytes", &["kib"], Data, 1024.0),
+    unit("mebibyte", "mebibytes", &["mib"], Data, 1_048_576.0),
+    unit("gibibyte", "gibibytes", &["gib"], Data, 1_073_741_824.0),
+    unit("square meter", "square meters", &["m2", "sq m", "square metre", "square metres"], Area, 1.0),
+    unit("square kilometer", "square kilometers", &["km2", "sq km", "square kilometre", "square kilometres"], Area, 1e6),
+    unit("square foot", "square feet", &["sq ft", "ft2"], Area, 0.092_903_04),
+    unit("acre", "acres", &[], Area, 4_046.856_422_4),
+    unit("hectare", "hectares", &["ha"], Area, 10_000.0),
+    unit("square mile", "square miles", &["sq mi", "mi2"], Area, 2_589_988.110_336),
+];
+
+fn clean_unit_name(name: &str) -> String {
+    let name = name.trim().trim_end_matches(['?', '.', '!']).trim().to_lowercase();
+    ["a ", "an ", "one ", "the "].iter().find_map(|a| name.strip_prefix(a)).unwrap_or(&name).trim().to_string()
+}
+
+/// Units a name could mean; "k" is both kilometers and kelvin
+fn find_units(name: &str) -> Vec<&'static Unit> {
+    let name = clean_unit_name(name);
+    UNITS
+        .iter()
+        .filter(|u| u.singular.eq_ignore_ascii_case(&name) || u.plural.eq_ignore_ascii_case(&name) || u.aliases.contains(&name.as_str()))
+        .collect()
+}
+
+/// Convert between two units of the same kind
+pub fn convert_units(value: f64, from: &str, to: &str) -> Result<String, String> {
+    let (from_units, to_units) = (find_units(from), find_units(to));
+    if from_units.is_empty() {
+        return Err(format!("I don't know the unit {}", from.trim()));
+    }
+    if to_units.is_empty() {
+        return Err(format!("I don't know the unit {}", to.trim()));
+    }
+    let (from_unit, to_unit) = from_units
+        .iter()
+        .find_map(|f| to_units.iter().find(|t| t.dimension == f.dimension).map(|t| (*f, *t)))
+        .ok_or_else(|| format!("I can't convert {} to {}", from.trim(), to.trim()))?;
+    let base = value * from_unit.factor + from_unit.offset;
+    let result = round_significant((base - to_unit.offset) / to_unit.factor);
+    Ok(format!(
+        "{} {} is {} {}.",
+        format_number(value),
+        if value == 1.0 { from_unit.singular } else { from_unit.plural },
+        format_number(result),
+        if result == 1.0 { to_unit.singular } else { to_unit.plural },
+    ))
+}
+
+/// Conversions rarely need more than 6 significant digits
+fn round_significant(value: f64) -> f64 {
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+    let scale = 10f64.powi(5 - value.abs().log10().floor() as i32);
+    (value * scale).round() / scale
+}
+
+// ========== Currency ==========
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RateCache {
+    /// The ECB date the rates are for
+    date: String,
+    /// Unix seconds
+    fetched_at: i64,
+    /// Units of each currency per euro
+    rates: HashMap<String, f64>,
+}
+
+static RATES: Lazy<Mutex<Option<RateCache>>> = Lazy::new(|| Mutex::new(load_rates()));
+
+fn rates_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("currency_rates.json");
+    Some(path)
+}
+
+fn load_rates() -> Option<RateCache> {
+    let content = std::fs::read_to_string(rates_path()?).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|e| warn!("Ignoring unreadable currency_rates.json: {}", e))
+        .ok()
+}
+
+fn save_rates(cache: &RateCache) {
+    let Some(path) = rates_path() else { return };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string_pretty(cache) {
+        Ok(content) => {
+            if let Err(e) = std::fs::write(&path, content) {
+                warn!("Failed to write currency rates: {}", e);
+            }
+        }
+        Err(e) => warn!("Failed to serialize currency rates: {}", e),
+    }
+}
+
+async fn fetch_rates() -> AstralResult<RateCache> {
+    let request = crate::proxy::client().get(RATES_ENDPOINT).timeout(Duration::from_secs(10));
+    let response = crate::privacy::send(request)
+        .await
+        .map_err(|e| AstralError::from(e.context("Couldn't fetch exchange rates")))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("The exchange rate service", status, &body));
+    }
+    let body: Value = response.json().await?;
+    let mut rates: HashMap<String, f64> = body
+        .get("rates")
+        .and_then(Value::as_object)
+        .map(|rates| rates.iter().filter_map(|(code, rate)| Some((code.clone(), rate.as_f64()?))).collect())
+        .unwrap_or_default();
+    rates.insert("EUR".to_string(), 1.0);
+    let date = body.get("date").and_then(Value::as_str).unwrap_or_default().to_string();
+    info!("Fetched {} exchange rates for {}", rates.len(), date);
+    Ok(RateCache { date, fetched_at: chrono::Utc::now().timestamp(), rates })
+}
+
+/// Current rates, or the cached ones when they can't be refreshed
+async fn rates() -> AstralResult<RateCache> {
+    let cached = RATES.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    if let Some(cache) = &cached {
+        if chrono::Utc::now().timestamp() - cache.fetched_at < RATES_MAX_AGE_SECS {
+            return Ok(cache.clone());
+        }
+    }
+    match fetch_rates().await {
+        Ok(fresh) => {
+            save_rates(&fresh);
+            *RATES.lock().unwrap_or_else(|e| e.into_inner()) = Some(fresh.clone());
+            Ok(fresh)
+        }
+        Err(e) => match cached {
+            Some(cache) => {
+                warn!("Using exchange rates from {}: {}", cache.date, e);
+                Ok(cache)
+            }
+            None => Err(AstralError::new(
+                ErrorCode::ServiceUnavailable,
+                format!("I need to go online once to get exchange rates: {}", e.message),
+            )),
+        },
+    }
+}
+
+/// ISO code for a currency name or code ("dollars", "eur")
+fn currency_code(name: &str) -> Option<&'static str> {
+    let name = clean_unit_name(name);
+    let code = match name.as_str() {
+        "dollar" | "dollars" | "usd" | "us dollars" | "bucks" | "$" => "USD",
+        "euro" | "euros" | "eur" | "€" => "EUR",
+        "pound" | "pounds" | "gbp" | "quid" | "sterling" | "pounds sterling" | "british pounds" | "£" => "GBP",
+        "yen" | "jpy" | "¥" => "JPY",
+        "franc" | "francs" | "swiss francs" | "chf" => "CHF",
+        "canadian dollar" | "canadian dollars" | "cad" => "CAD",
+        "australian dollar" | "australian dollars" | "aud" => "AUD",
+        "new zealand dollars" | "nzd" => "NZD",
+        "yuan" | "renminbi" | "cny" => "CNY",
+        "rupee" | "rupees" | "inr" => "INR",
+        "krona" | "kronor" | "swedish kronor" | "sek" => "SEK",
+        "norwegian kroner" | "nok" => "NOK",
+        "danish kroner" | "dkk" => "DKK",
+        "zloty" | "pln" => "PLN",
+        "real" | "reais" | "brl" => "BRL",
+        "peso" | "pesos" | "mexican pesos" | "mxn" => "MXN",
+        "won" | "krw" => "KRW",
+        "lira" | "try" => "TRY",
+        "rand" | "zar" => "ZAR",
+        "hkd" | "hong kong dollars" => "HKD",
+        "sgd" | "singapore dollars" => "SGD",
+        "czk" | "koruna" => "CZK",
+        "huf" | "forint" => "HUF",
+        _ => return None,
+    };
+    Some(code)
+}
+
+pub async fn convert_currency(amount: f64, from: &str, to: &str) -> AstralResult<String> {
+    let unknown = |name: &str| AstralError::invalid_input(format!("I don't know the currency {}", name.trim()));
+    let from_code = currency_code(from).ok_or_else(|| unknown(from))?;
+    let to_code = currency_code(to).ok_or_else(|| unknown(to))?;
+    let cache = rates().await?;
+    let rate = |code: &str| {
+        cache
+            .rates
+            .get(code)
+            .copied()
+            .ok_or_else(|| AstralError::new(ErrorCode::NotFound, format!("There's no exchange rate for {}", code)))
+    };
+    let result = amount / rate(from_code)? * rate(to_code)?;
+    Ok(format!(
+        "{} {} is {:.2} {} (rates from {}).",
+        format_number(amount),
+        from_code,
+        result,
+        to_code,
+        cache.date
+    ))
+}
+
+// ========== Voice Commands ==========
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcQuery {
+    /// "what's 15% of 84"
+    Expression(String),
+    /// "convert 5 miles to km", "how many cups in a liter"
+    Convert { value: f64, from: String, to: String },
+}
+
+/// "5 miles" -> (5, "miles"); a bare unit counts as one
+fn split_quantity(text: &str) -> Option<(f64, String)> {
+    let text = text.trim();
+    let number_end = text
+        .char_indices()
+        .find(|(_, c)| !(c.is_ascii_digit() || *c == '.' || *c == ',' || *c == '-'))
+        .map_or(text.len(), |(i, _)| i);
+    let (number, unit) = text.split_at(number_end);
+    let unit = unit.trim().to_string();
+    if unit.is_empty() {
+        return None;
+    }
+    if number.is_empty() {
+        return Some((1.0, unit));
+    }
+    number.replace(',', "").parse().ok().map(|value| (value, unit))
+}
+
+fn is_convertible(from: &str, to: &str) -> bool {
+    let same_dimension = find_units(from)
+        .iter()
+        .any(|f| find_units(to).iter().any(|t| t.dimension == f.dimension));
+    same_dimension || (currency_code(from).is_some() && currency_code(to).is_some())
+}
+
+/// `text` without the punctuation ending the sentence, keeping a "!" that
+/// follows a number or bracket, which is a factorial ("what's 5!")
+fn trim_sentence_end(text: &str) -> &str {
+    let mut text = text.trim_end();
+    while let Some(last) = text.chars().last() {
+        let rest = &text[..text.len() - last.len_utf8()];
+        let factorial = last == '!' && rest.ends_with(|c: char| c.is_ascii_digit() || c == ')');
+        if !matches!(last, '?' | '.' | '!') || factorial {
+            break;
+        }
+        text = rest.trim_end();
+    }
+    text
+}
+
+impl CalcQuery {
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.trim().to_lowercase();
+        let lower = trim_sentence_end(&lower);
+
+        // "how many cups are in a liter"
+        if let Some(rest) = lower.strip_prefix("how many ") {
+            let (to, from) = rest.split_once(" are in ").or_else(|| rest.split_once(" in "))?;
+            let (value, from) = split_quantity(from)?;
+            return is_convertible(&from, to).then(|| CalcQuery::Convert { value, from, to: to.trim().to_string() });
+        }
+
+        let rest = ["what's ", "what is ", "whats ", "calculate ", "compute ", "how much is ", "work out ", "convert "]
+            .iter()
+            .find_map(|p| lower.strip_prefix(p))
+            .or_else(|| lower.starts_with(|c: char| c.is_ascii_digit() || c == '(').then_some(lower))?;
+
+        for separator in [" in ", " to ", " into "] {
+            if let Some((quantity, to)) = rest.rsplit_once(separator) {
+                if let Some((value, from)) = split_quantity(quantity) {
+                    if is_convertible(&from, to) {
+                        return Some(CalcQuery::Convert { value, from, to: to.trim().to_string() });
+                    }
+                }
+            }
+        }
+        (has_operation(rest) && evaluate(rest).is_ok()).then(|| CalcQuery::Expression(rest.to_string()))
+    }
+}
+
+/// Work out a query and phrase the answer
+pub async fn answer(query: CalcQuery) -> String {
+    match query {
+        CalcQuery::Expression(expression) => match evaluate(&expression) {
+            Ok(value) => format!("That's {}.", format_number(value)),
+            Err(e) => format!("I couldn't work that out: {}", e),
+        },
+        CalcQuery::Convert { value, from, to } => {
+            if find_units(&from).iter().any(|f| find_units(&to).iter().any(|t| t.dimension == f.dimension)) {
+                return convert_units(value, &from, &to).unwrap_or_else(|e| format!("I couldn't convert that: {}", e));
+            }
+            match convert_currency(value, &from, &to).await {
+                Ok(text) => text,
+                Err(e) => format!("I couldn't convert that: {}", e),
+            }
+        }
+    }
+}
+
+/// Convert `value` from one unit or currency to another, for the MCP tool
+pub async fn convert(value: f64, from: &str, to: &str) -> AstralResult<String> {
+    if currency_code(from).is_some() && currency_code(to).is_some() && convert_units(value, from, to).is_err() {
+        return convert_currency(value, from, to).await;
+    }
+    convert_units(value, from, to).map_err(AstralError::invalid_input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factorial_at_the_end() {
+        assert_eq!(CalcQuery::from_command("what is 5!"), Some(CalcQuery::Expression("5!".to_string())));
+        assert_eq!(CalcQuery::from_command("What's (2+1)!?"), Some(CalcQuery::Expression("(2+1)!".to_string())));
+        assert_eq!(evaluate("5!"), Ok(120.0));
+    }
+
+    #[test]
+    fn sentence_punctuation_is_dropped() {
+        assert_eq!(CalcQuery::from_command("what's 2 + 3?"), Some(CalcQuery::Expression("2 + 3".to_string())));
+        assert_eq!(CalcQuery::from_command("what is 12 / 4."), Some(CalcQuery::Expression("12 / 4".to_string())));
+    }
+}
@@ -0,0 +1,1095 @@
+// Calendar Module
+// Reads upcoming events from ICS subscriptions (a calendar's secret iCal
+// address, a published Outlook calendar, a webcal:// link or a local .ics
+// file) and from Google Calendar, so "what's on my calendar today" has an
+// answer. Off unless `calendar.enabled`. Events for today and the next two
+// days are fetched every `calendar.refresh_minutes` and kept in memory;
+// meetings are announced `calendar.announce_minutes` before they start, the
+// morning routine reads the day's agenda, and the LLM prompt gets the next
+// few events as `{{upcoming_events}}`. Google needs an OAuth client of the
+// "Desktop app" type (see oauth.rs); `connect_google_calendar` keeps only the
+// refresh token. Nothing here writes to a calendar.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// System prompt variable with the next few events
+pub const UPCOMING_EVENTS_VARIABLE: &str = "upcoming_events";
+
+const GOOGLE_CALENDARS_ENDPOINT: &str = "https://www.googleapis.com/calendar/v3/calendars";
+const GOOGLE_SCOPE: &str = "https://www.googleapis.com/auth/calendar.readonly";
+
+/// Days after today that are fetched, enough for "tomorrow" and the prompt
+const LOOKAHEAD_DAYS: i64 = 2;
+
+/// Events listed in `{{upcoming_events}}`
+const PROMPT_EVENTS: usize = 3;
+
+/// Stops runaway expansion of old daily events
+const MAX_RECURRENCE_PERIODS: i64 = 20_000;
+
+/// The `calendar` settings section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalendarSettings {
+    pub enabled: bool,
+    /// ICS feed addresses (https:// or webcal://) or paths to .ics files
+    pub ics_urls: Vec<String>,
+    pub google_client_id: String,
+    pub google_client_secret: String,
+    /// Set by `connect_google_calendar`, cleared by `disconnect_google_calendar`
+    pub google_refresh_token: String,
+    /// Google calendars to read; "primary" is the account's own
+    pub google_calendar_ids: Vec<String>,
+    /// Say "Standup starts in 5 minutes" this long before a meeting; 0 turns it off
+    pub announce_minutes: u64,
+    pub refresh_minutes: u64,
+}
+
+impl Default for CalendarSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ics_urls: Vec::new(),
+            google_client_id: String::new(),
+            google_client_secret: String::new(),
+            google_refresh_token: String::new(),
+            google_calendar_ids: vec!["primary".to_string()],
+            announce_minutes: 5,
+            refresh_minutes: 15,
+        }
+    }
+}
+
+impl CalendarSettings {
+    pub fn google_client(&self) -> crate::oauth::GoogleClient<'_> {
+        crate::oauth::GoogleClient {
+            client_id: &self.google_client_id,
+            client_secret: &self.google_client_secret,
+        }
+    }
+
+    pub fn google_connected(&self) -> bool {
+        !self.google_refresh_token.is_empty()
+    }
+
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        self.ics_urls = self
+            .ics_urls
+            .iter()
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty() && seen.insert(u.clone()))
+            .collect();
+        self.google_client_id = self.google_client_id.trim().to_string();
+        self.google_client_secret = self.google_client_secret.trim().to_string();
+        self.google_calendar_ids = self
+            .google_calendar_ids
+            .iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+        if self.google_calendar_ids.is_empty() {
+            self.google_calendar_ids.push("primary".to_string());
+        }
+        if self.announce_minutes > 120 {
+            return Err("Meetings can be announced at most 120 minutes ahead".to_string());
+        }
+        if !(1..=1440).contains(&self.refresh_minutes) {
+            return Err("The calendar refreshes every 1 to 1440 minutes".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarEvent {
+    pub title: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub all_day: bool,
+    pub location: Option<String>,
+    /// Name of the feed or Google calendar it came from
+    pub calendar: String,
+}
+
+impl CalendarEvent {
+    /// Same event seen through two sources (an ICS feed of a Google calendar
+    /// that's also connected) has the same key
+    fn key(&self) -> String {
+        format!("{}|{}", self.start.to_rfc3339(), self.title.to_lowercase())
+    }
+
+    fn is_on(&self, day: NaiveDate) -> bool {
+        let first = self.start.date_naive();
+        // All-day events end at midnight after their last day
+        let last = match self.all_day {
+            true => (self.end - ChronoDuration::seconds(1)).date_naive(),
+            false => self.end.date_naive(),
+        };
+        first <= day && day <= last.max(first)
+    }
+
+    /// "Standup at 9:30", "Company holiday all day"
+    fn describe(&self) -> String {
+        if self.all_day {
+            format!("{} all day", self.title)
+        } else {
+            format!("{} at {}", self.title, self.start.format("%-H:%M"))
+        }
+    }
+}
+
+/// What the dashboard shows
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarStatus {
+    pub enabled: bool,
+    pub google_connected: bool,
+    pub fetched_at: Option<String>,
+    /// One line per source that couldn't be read on the last refresh
+    pub errors: Vec<String>,
+    pub events: Vec<CalendarEvent>,
+}
+
+#[derive(Default)]
+struct Cache {
+    events: Vec<CalendarEvent>,
+    fetched_at: Option<DateTime<Local>>,
+    errors: Vec<String>,
+    /// Keys of the events already announced
+    announced: HashSet<String>,
+}
+
+static CACHE: Lazy<Mutex<Cache>> = Lazy::new(|| Mutex::new(Cache::default()));
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+fn cache() -> std::sync::MutexGuard<'static, Cache> {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// ========== Voice Commands ==========
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgendaDay {
+    Today,
+    Tomorrow,
+}
+
+impl AgendaDay {
+    fn date(self) -> NaiveDate {
+        let today = Local::now().date_naive();
+        match self {
+            AgendaDay::Today => today,
+            AgendaDay::Tomorrow => today + ChronoDuration::days(1),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            AgendaDay::Today => "today",
+            AgendaDay::Tomorrow => "tomorrow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarQuery {
+    /// "what's on my calendar today", "do I have any meetings tomorrow"
+    Agenda(AgendaDay),
+    /// "when is my next meeting"
+    Next,
+}
+
+impl CalendarQuery {
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.to_lowercase();
+        let about = ["calendar", "agenda", "schedule", "meeting", "appointment", "events"]
+            .iter()
+            .any(|w| lower.contains(w));
+        let asks = ["what", "when", "do i have", "any ", "read", "tell me", "show"]
+            .iter()
+            .any(|w| lower.contains(w));
+        // "my calendar", not "the schedule for the world cup"
+        let mine = ["my ", "do i have", "am i"].iter().any(|w| lower.contains(w));
+        if !about || !asks || !mine {
+            return None;
+        }
+        if lower.contains("next") {
+            Some(Self::Next)
+        } else if lower.contains("tomorrow") {
+            Some(Self::Agenda(AgendaDay::Tomorrow))
+        } else {
+            Some(Self::Agenda(AgendaDay::Today))
+        }
+    }
+}
+
+/// "Standup at 9:30, Review at 14:00 and Lunch at 12:30"
+fn spoken_list(events: &[&CalendarEvent]) -> String {
+    let items: Vec<String> = events.iter().map(|e| e.describe()).collect();
+    match items.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        Some((last, _)) => last.clone(),
+        None => String::new(),
+    }
+}
+
+fn describe_agenda(events: &[CalendarEvent], day: AgendaDay) -> String {
+    let now = Local::now();
+    let date = day.date();
+    // Today's agenda leaves out what's already over
+    let on_day: Vec<&CalendarEvent> = events
+        .iter()
+        .filter(|e| e.is_on(date) && (day == AgendaDay::Tomorrow || e.all_day || e.end > now))
+        .collect();
+    match on_day.len() {
+        0 => format!("Nothing on your calendar {}.", day.label()),
+        1 => format!("You have one event {}: {}.", day.label(), spoken_list(&on_day)),
+        n => format!("You have {} events {}: {}.", n, day.label(), spoken_list(&on_day)),
+    }
+}
+
+fn describe_next(events: &[CalendarEvent]) -> String {
+    let now = Local::now();
+    let Some(next) = events.iter().find(|e| !e.all_day && e.start > now) else {
+        return "Nothing else on your calendar for the next couple of days.".to_string();
+    };
+    let day = match (next.start.date_naive() - now.date_naive()).num_days() {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        _ => next.start.format("on %A").to_string(),
+    };
+    let mut answer = format!("Your next event is {} {}", next.describe(), day);
+    let minutes = (next.start - now).num_minutes();
+    if minutes < 60 {
+        answer.push_str(&format!(", {}", starts_in(minutes + 1)));
+    }
+    if let Some(location) = &next.location {
+        answer.push_str(&format!(", in {}", location));
+    }
+    answer.push('.');
+    answer
+}
+
+fn starts_in(minutes: i64) -> String {
+    if minutes <= 1 {
+        "in a minute".to_string()
+    } else {
+        format!("in {} minutes", minutes)
+    }
+}
+
+/// Events in the cache, fetching them first if the cache is still empty
+async fn current_events(app: &AppHandle) -> AstralResult<Option<Vec<CalendarEvent>>> {
+    let settings = crate::settings::load_settings(app.clone()).await?.calendar;
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let fetched = cache().fetched_at.is_some();
+    if !fetched {
+        refresh(app).await?;
+    }
+    Ok(Some(cache().events.clone()))
+}
+
+/// Spoken answer to a calendar question
+pub async fn answer(app: &AppHandle, query: CalendarQuery) -> String {
+    match current_events(app).await {
+        Ok(Some(events)) => match query {
+            CalendarQuery::Agenda(day) => describe_agenda(&events, day),
+            CalendarQuery::Next => describe_next(&events),
+        },
+        Ok(None) => "Your calendar isn't connected. Add an ICS subscription or Google Calendar in Settings.".to_string(),
+        Err(e) => format!("I couldn't read your calendar: {}", e),
+    }
+}
+
+/// Today's agenda for the morning routine, or None when there's no calendar
+pub async fn todays_agenda(app: &AppHandle) -> AstralResult<Option<String>> {
+    Ok(current_events(app).await?.map(|events| describe_agenda(&events, AgendaDay::Today)))
+}
+
+/// `{{upcoming_events}}`: "Standup today 9:30; Dentist tomorrow 16:00"
+pub fn upcoming_events_variable() -> String {
+    let cache = cache();
+    if cache.fetched_at.is_none() {
+        return "no calendar connected".to_string();
+    }
+    let now = Local::now();
+    let today = now.date_naive();
+    let upcoming: Vec<String> = cache
+        .events
+        .iter()
+        .filter(|e| e.end > now)
+        .take(PROMPT_EVENTS)
+        .map(|e| {
+            let day = if e.start.date_naive() <= today { "today" } else { "tomorrow" };
+            match e.all_day {
+                true => format!("{} {} (all day)", e.title, day),
+                false => format!("{} {} {}", e.title, day, e.start.format("%-H:%M")),
+            }
+        })
+        .collect();
+    if upcoming.is_empty() {
+        "none".to_string()
+    } else {
+        upcoming.join("; ")
+    }
+}
+
+// ========== ICS ==========
+
+/// Where an ICS time is anchored
+#[derive(Debug, Clone, Copy)]
+enum Zone {
+    Utc,
+    Named(chrono_tz::Tz),
+    /// No zone, or one chrono-tz doesn't know (Outlook's "W. Europe Standard
+    /// Time"): taken as local time
+    Floating,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IcsTime {
+    naive: NaiveDateTime,
+    zone: Zone,
+    all_day: bool,
+}
+
+#[derive(Debug, Default)]
+struct RawEvent {
+    uid: String,
+    summary: String,
+    location: Option<String>,
+    start: Option<IcsTime>,
+    end: Option<IcsTime>,
+    duration: Option<ChronoDuration>,
+    rrule: Option<String>,
+    exdates: Vec<NaiveDateTime>,
+    recurrence_id: Option<NaiveDateTime>,
+    cancelled: bool,
+}
+
+/// Undo line folding: a line starting with a space continues the previous one
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// `DTSTART;TZID=Europe/Berlin:20260101T090000` -> name, parameters, value
+fn parse_property(line: &str) -> Option<(String, Vec<(String, String)>, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        (c == ':' && !quoted).then_some(i)
+    })?;
+    let mut head = line[..colon].split(';');
+    let name = head.next()?.trim().to_uppercase();
+    let params = head
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.trim().to_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, &line[colon + 1..]))
+}
+
+fn param<'a>(params: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    params.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push(' '),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+fn parse_time(value: &str, params: &[(String, String)]) -> Option<IcsTime> {
+    let value = value.trim();
+    if param(params, "VALUE") == Some("DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return Some(IcsTime { naive: date.and_hms_opt(0, 0, 0)?, zone: Zone::Floating, all_day: true });
+    }
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(v) => (v, true),
+        None => (value, false),
+    };
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let zone = if utc {
+        Zone::Utc
+    } else {
+        param(params, "TZID")
+            .and_then(|tz| tz.parse::<chrono_tz::Tz>().ok())
+            .map_or(Zone::Floating, Zone::Named)
+    };
+    Some(IcsTime { naive, zone, all_day: false })
+}
+
+fn to_local(naive: NaiveDateTime, zone: Zone) -> Option<DateTime<Local>> {
+    match zone {
+        Zone::Utc => Some(Utc.from_utc_datetime(&naive).with_timezone(&Local)),
+        Zone::Named(tz) => tz.from_local_datetime(&naive).earliest().map(|t| t.with_timezone(&Local)),
+        Zone::Floating => Local.from_local_datetime(&naive).earliest(),
+    }
+}
+
+/// `PT1H30M`, `P1D`, `P2W`
+fn parse_duration(value: &str) -> Option<ChronoDuration> {
+    let value = value.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, value.trim_start_matches('+')),
+    };
+    let mut total = ChronoDuration::zero();
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                total += match unit {
+                    'W' => ChronoDuration::weeks(n),
+                    'D' => ChronoDuration::days(n),
+                    'H' => ChronoDuration::hours(n),
+                    'M' => ChronoDuration::minutes(n),
+                    'S' => ChronoDuration::seconds(n),
+                    _ => return None,
+                };
+            }
+        }
+    }
+    Some(if negative { -total } else { total })
+}
+
+/// A BYDAY entry: "MO", or "2TU" / "-1FR" (the second Tuesday, the last
+/// Friday) in monthly and yearly rules
+fn parse_weekday(code: &str) -> Option<(Option<i32>, Weekday)> {
+    let code = code.trim();
+    let split = code.find(|c: char| c.is_ascii_alphabetic())?;
+    let (ordinal, day) = code.split_at(split);
+    let ordinal = match ordinal {
+        "" => None,
+        n => Some(n.parse::<i32>().ok().filter(|n| *n != 0 && n.abs() <= 53)?),
+    };
+    let day = match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    };
+    Some((ordinal, day))
+}
+
+fn add_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    let total = date.year() as i64 * 12 + date.month0() as i64 + months;
+    NaiveDate::from_ymd_opt((total / 12) as i32, (total % 12) as u32 + 1, date.day())
+}
+
+/// The days a MONTHLY or YEARLY rule picks in the month starting at `first`:
+/// every matching weekday, the nth or nth-from-last one for "2TU" / "-1FR",
+/// narrowed to BYMONTHDAY; with neither, the start's day of the month
+fn month_days(first: NaiveDate, start_day: u32, by_day: &[(Option<i32>, Weekday)], by_month_day: &[i32]) -> Vec<NaiveDate> {
+    if by_day.is_empty() && by_month_day.is_empty() {
+        return NaiveDate::from_ymd_opt(first.year(), first.month(), start_day).into_iter().collect();
+    }
+    let days: Vec<NaiveDate> = std::iter::successors(Some(first), |d| d.succ_opt())
+        .take_while(|d| d.month() == first.month())
+        .collect();
+    let mut picked: Vec<NaiveDate> = if by_day.is_empty() {
+        days.clone()
+    } else {
+        by_day
+            .iter()
+            .flat_map(|(ordinal, weekday)| {
+                let matching: Vec<NaiveDate> = days.iter().copied().filter(|d| d.weekday() == *weekday).collect();
+                match *ordinal {
+                    None => matching,
+                    Some(n) if n > 0 => matching.get(n as usize - 1).copied().into_iter().collect(),
+                    Some(n) => matching
+                        .len()
+                        .checked_sub(n.unsigned_abs() as usize)
+                        .and_then(|i| matching.get(i))
+                        .copied()
+                        .into_iter()
+                        .collect(),
+                }
+            })
+            .collect()
+    };
+    if !by_month_day.is_empty() {
+        let length = days.len() as i32;
+        picked.retain(|d| {
+            let day = d.day() as i32;
+            by_month_day.iter().any(|n| if *n > 0 { day == *n } else { day - length - 1 == *n })
+        });
+    }
+    picked.sort();
+    picked.dedup();
+    picked
+}
+
+/// Start times of a recurring event from `start` up to `until`, keeping
+/// those after `not_before`. Handles the DAILY, WEEKLY (with BYDAY),
+/// MONTHLY and YEARLY (with BYDAY, ordinals included, BYMONTHDAY and
+/// BYMONTH) rules calendars export for ordinary repeating meetings, with
+/// INTERVAL, COUNT and UNTIL. A rule with parts it can't follow (BYSETPOS,
+/// BYHOUR, ...) yields only the first occurrence rather than wrong ones.
+fn expand_rrule(start: NaiveDateTime, rule: &str, not_before: NaiveDateTime, until: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let mut freq = String::new();
+    let mut interval = 1i64;
+    let mut count: Option<usize> = None;
+    let mut rule_until: Option<NaiveDateTime> = None;
+    let mut by_day: Vec<(Option<i32>, Weekday)> = Vec::new();
+    let mut by_month_day: Vec<i32> = Vec::new();
+    let mut by_month: Vec<u32> = Vec::new();
+    for part in rule.split(';') {
+        let Some((key, value)) = part.split_once('=') else { continue };
+        let parsed = match key.trim().to_uppercase().as_str() {
+            "FREQ" => {
+                freq = value.trim().to_uppercase();
+                Some(())
+            }
+            "INTERVAL" => {
+                interval = value.trim().parse().unwrap_or(1).max(1);
+                Some(())
+            }
+            "COUNT" => {
+                count = value.trim().parse().ok();
+                Some(())
+            }
+            "UNTIL" => {
+                rule_until = parse_time(value, &[]).map(|t| t.naive);
+                Some(())
+            }
+            "BYDAY" => value.split(',').map(parse_weekday).collect::<Option<_>>().map(|days| by_day = days),
+            "BYMONTHDAY" => value
+                .split(',')
+                .map(|n| n.trim().parse::<i32>().ok().filter(|n| *n != 0 && n.abs() <= 31))
+                .collect::<Option<_>>()
+                .map(|days| by_month_day = days),
+            "BYMONTH" => value
+                .split(',')
+                .map(|n| n.trim().parse::<u32>().ok().filter(|n| (1..=12).contains(n)))
+                .collect::<Option<_>>()
+                .map(|months| by_month = months),
+            // Only changes which day weeks start on, which BYDAY here doesn't depend on
+            "WKST" => Some(()),
+            _ => None,
+        };
+        if parsed.is_none() {
+            return vec![start];
+        }
+    }
+    let weekly = freq == "WEEKLY";
+    if (weekly && (by_day.iter().any(|(ordinal, _)| ordinal.is_some()) || !by_month_day.is_empty()))
+        || (freq == "DAILY" && (!by_day.is_empty() || !by_month_day.is_empty()))
+        // Without BYMONTH, "20MO" in a yearly rule counts through the whole year
+        || (freq == "YEARLY" && !by_day.is_empty() && by_month.is_empty())
+    {
+        return vec![start];
+    }
+    let mut weekdays: Vec<Weekday> = by_day.iter().map(|(_, day)| *day).collect();
+    weekdays.sort_by_key(|d| d.num_days_from_monday());
+    weekdays.dedup();
+    let first_of_month = start.date().with_day(1).unwrap_or(start.date());
+
+    let mut found = Vec::new();
+    let mut emitted = 0usize;
+    for period in 0..MAX_RECURRENCE_PERIODS {
+        let step = period * interval;
+        let days: Vec<NaiveDate> = match freq.as_str() {
+            "DAILY" => vec![start.date() + ChronoDuration::days(step)],
+            "WEEKLY" => {
+                let monday = start.date() - ChronoDuration::days(start.weekday().num_days_from_monday() as i64)
+                    + ChronoDuration::weeks(step);
+                let days = if weekdays.is_empty() { vec![start.weekday()] } else { weekdays.clone() };
+                days.iter().map(|d| monday + ChronoDuration::days(d.num_days_from_monday() as i64)).collect()
+            }
+            "MONTHLY" => add_months(first_of_month, step)
+                .filter(|month| by_month.is_empty() || by_month.contains(&month.month()))
+                .map(|month| month_days(month, start.day(), &by_day, &by_month_day))
+                .unwrap_or_default(),
+            "YEARLY" => {
+                let months = if by_month.is_empty() { vec![start.month()] } else { by_month.clone() };
+                let mut days: Vec<NaiveDate> = months
+                    .iter()
+                    .filter_map(|m| NaiveDate::from_ymd_opt(start.year() + step as i32, *m, 1))
+                    .flat_map(|month| month_days(month, start.day(), &by_day, &by_month_day))
+                    .collect();
+                days.sort();
+                days
+            }
+            _ => return vec![start],
+        };
+        let days = days.into_iter().filter(|d| by_month.is_empty() || by_month.contains(&d.month()));
+        for candidate in days.map(|d| d.and_time(start.time())) {
+            if candidate < start {
+                continue;
+            }
+            if candidate > until || rule_until.is_some_and(|u| candidate > u) {
+                return found;
+            }
+            emitted += 1;
+            if candidate >= not_before {
+                found.push(candidate);
+            }
+            if count.is_some_and(|c| emitted >= c) {
+                return found;
+            }
+        }
+    }
+    found
+}
+
+fn parse_raw_events(text: &str) -> (Option<String>, Vec<RawEvent>) {
+    let mut calendar_name = None;
+    let mut events = Vec::new();
+    let mut current: Option<RawEvent> = None;
+    // VALARM and other components nested in an event have properties of their own
+    let mut nested = 0usize;
+
+    for line in unfold(text) {
+        let Some((name, params, value)) = parse_property(&line) else { continue };
+        let is_event = value.trim().eq_ignore_ascii_case("VEVENT");
+        match name.as_str() {
+            "BEGIN" if current.is_none() => {
+                if is_event {
+                    current = Some(RawEvent::default());
+                }
+                continue;
+            }
+            "BEGIN" => {
+                nested += 1;
+                continue;
+            }
+            "END" if nested > 0 => {
+                nested -= 1;
+                continue;
+            }
+            "END" if is_event => {
+                events.extend(current.take());
+                continue;
+            }
+            _ => {}
+        }
+        let Some(event) = current.as_mut() else {
+            if name == "X-WR-CALNAME" {
+                calendar_name = Some(unescape(value)).filter(|n| !n.is_empty());
+            }
+            continue;
+        };
+        if nested > 0 {
+            continue;
+        }
+        match name.as_str() {
+            "UID" => event.uid = value.trim().to_string(),
+            "SUMMARY" => event.summary = unescape(value),
+            "LOCATION" => event.location = Some(unescape(value)).filter(|l| !l.is_empty()),
+            "DTSTART" => event.start = parse_time(value, &params),
+            "DTEND" => event.end = parse_time(value, &params),
+            "DURATION" => event.duration = parse_duration(value),
+            "RRULE" => event.rrule = Some(value.trim().to_string()),
+            "EXDATE" => event.exdates.extend(value.split(',').filter_map(|v| parse_time(v, &params)).map(|t| t.naive)),
+            "RECURRENCE-ID" => event.recurrence_id = parse_time(value, &params).map(|t| t.naive),
+            "STATUS" => event.cancelled = value.trim().eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+    (calendar_name, events)
+}
+
+/// Events in `text` that overlap `from..to`, recurring ones expanded
+fn parse_ics(text: &str, fallback_name: &str, from: DateTime<Local>, to: DateTime<Local>) -> Vec<CalendarEvent> {
+    let (name, raw) = parse_raw_events(text);
+    let calendar = name.unwrap_or_else(|| fallback_name.to_string());
+    // Moved or edited occurrences come as separate events that replace the original
+    let overrides: HashSet<(String, NaiveDateTime)> = raw
+        .iter()
+        .filter_map(|e| e.recurrence_id.map(|id| (e.uid.clone(), id)))
+        .collect();
+    // Occurrences are worked out in the event's own zone, so leave some slack
+    let window_start = from.naive_local() - ChronoDuration::days(1);
+    let window_end = to.naive_local() + ChronoDuration::days(1);
+
+    let mut events = Vec::new();
+    for event in raw.iter().filter(|e| !e.cancelled) {
+        let Some(start) = event.start else { continue };
+        let length = match (event.end, event.duration) {
+            (Some(end), _) => end.naive - start.naive,
+            (None, Some(duration)) => duration,
+            (None, None) if start.all_day => ChronoDuration::days(1),
+            (None, None) => ChronoDuration::zero(),
+        };
+        let occurrences = match (&event.rrule, event.recurrence_id) {
+            (Some(rule), None) => expand_rrule(start.naive, rule, window_start - length, window_end),
+            _ => vec![start.naive],
+        };
+        for occurrence in occurrences {
+            if event.exdates.contains(&occurrence)
+                || (event.recurrence_id.is_none() && overrides.contains(&(event.uid.clone(), occurrence)))
+            {
+                continue;
+            }
+            let Some(begins) = to_local(occurrence, start.zone) else { continue };
+            let ends = begins + length;
+            if begins < to && (ends > from || begins >= from) {
+                events.push(CalendarEvent {
+                    title: if event.summary.is_empty() { "Untitled event".to_string() } else { event.summary.clone() },
+                    start: begins,
+                    end: ends,
+                    all_day: start.all_day,
+                    location: event.location.clone(),
+                    calendar: calendar.clone(),
+                });
+            }
+        }
+    }
+    events
+}
+
+/// The feed's host or file name, for the status line; subscription
+/// addresses carry a private token, so the full URL isn't shown
+fn source_label(source: &str) -> String {
+    reqwest::Url::parse(&feed_url(source))
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .or_else(|| {
+            std::path::Path::new(source)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| source.to_string())
+}
+
+fn feed_url(source: &str) -> String {
+    match source.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => source.to_string(),
+    }
+}
+
+async fn fetch_ics(source: &str) -> AstralResult<String> {
+    let url = feed_url(source);
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Ok(tokio::fs::read_to_string(source).await?);
+    }
+    let request = crate::proxy::client().get(&url).timeout(Duration::from_secs(20));
+    let response = crate::privacy::send(request)
+        .await
+        .map_err(|e| AstralError::from(e.context(format!("Couldn't reach {}", source_label(source)))))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("The calendar feed", status, &body));
+    }
+    Ok(response.text().await?)
+}
+
+// ========== Google Calendar ==========
+
+/// `{"dateTime": "..."}` for timed events, `{"date": "2026-01-01"}` for all-day ones
+fn google_time(value: Option<&Value>) -> Option<(DateTime<Local>, bool)> {
+    let value = value?;
+    if let Some(time) = value.get("dateTime").and_then(Value::as_str) {
+        return Some((DateTime::parse_from_rfc3339(time).ok()?.with_timezone(&Local), false));
+    }
+    let date = NaiveDate::parse_from_str(value.get("date")?.as_str()?, "%Y-%m-%d").ok()?;
+    Some((Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?, true))
+}
+
+async fn google_events(settings: &CalendarSettings, from: DateTime<Local>, to: DateTime<Local>) -> AstralResult<Vec<CalendarEvent>> {
+    let token = crate::oauth::google_access_token(settings.google_client(), &settings.google_refresh_token, "Google Calendar").await?;
+    let client = crate::proxy::client();
+    let mut events = Vec::new();
+    for id in &settings.google_calendar_ids {
+        let mut url = reqwest::Url::parse(GOOGLE_CALENDARS_ENDPOINT).map_err(|e| AstralError::new(ErrorCode::Internal, e.to_string()))?;
+        if let Ok(mut segments) = url.path_segments_mut() {
+            segments.push(id).push("events");
+        }
+        let request = client.get(url).bearer_auth(&token).query(&[
+            ("timeMin", from.to_rfc3339()),
+            ("timeMax", to.to_rfc3339()),
+            ("singleEvents", "true".to_string()),
+            ("orderBy", "startTime".to_string()),
+            ("maxResults", "250".to_string()),
+        ]);
+        let body = crate::oauth::google_json(request, "Google Calendar").await?;
+        let calendar = body.get("summary").and_then(Value::as_str).unwrap_or(id).to_string();
+        let items = body.get("items").and_then(Value::as_array).cloned().unwrap_or_default();
+        events.extend(items.iter().filter(|item| item.get("status").and_then(Value::as_str) != Some("cancelled")).filter_map(|item| {
+            let (start, all_day) = google_time(item.get("start"))?;
+            let (end, _) = google_time(item.get("end")).unwrap_or((start, all_day));
+            Some(CalendarEvent {
+                title: item.get("summary").and_then(Value::as_str).unwrap_or("Untitled event").trim().to_string(),
+                start,
+                end,
+                all_day,
+                location: item.get("location").and_then(Value::as_str).map(str::trim).filter(|l| !l.is_empty()).map(str::to_string),
+                calendar: calendar.clone(),
+            })
+        }));
+    }
+    Ok(events)
+}
+
+// ========== Refresh & Announcements ==========
+
+/// Fetch events from every source into the cache
+pub async fn refresh(app: &AppHandle) -> AstralResult<()> {
+    let settings = crate::settings::load_settings(app.clone()).await?.calendar;
+    if !settings.enabled {
+        *cache() = Cache::default();
+        return Ok(());
+    }
+    let from = Local
+        .from_local_datetime(&Local::now().date_naive().and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .unwrap_or_else(Local::now);
+    let to = from + ChronoDuration::days(LOOKAHEAD_DAYS + 1);
+
+    let mut events = Vec::new();
+    let mut errors = Vec::new();
+    for source in &settings.ics_urls {
+        match fetch_ics(source).await {
+            Ok(text) => events.extend(parse_ics(&text, &source_label(source), from, to)),
+            Err(e) => {
+                warn!("Couldn't read calendar feed {}: {}", source_label(source), e);
+                errors.push(format!("{}: {}", source_label(source), e));
+            }
+        }
+    }
+    if settings.google_connected() {
+        match google_events(&settings, from, to).await {
+            Ok(google) => events.extend(google),
+            Err(e) => {
+                warn!("Couldn't read Google Calendar: {}", e);
+                errors.push(format!("Google Calendar: {}", e));
+            }
+        }
+    }
+    events.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.title.cmp(&b.title)));
+    events.dedup_by_key(|e| e.key());
+    info!("Calendar refreshed: {} events, {} sources failed", events.len(), errors.len());
+
+    let mut cache = cache();
+    let keys: HashSet<String> = events.iter().map(CalendarEvent::key).collect();
+    cache.announced.retain(|k| keys.contains(k));
+    cache.events = events;
+    cache.errors = errors;
+    cache.fetched_at = Some(Local::now());
+    Ok(())
+}
+
+/// Say and show the meetings starting within `minutes` that haven't been announced yet
+fn announce_due(minutes: u64) {
+    if minutes == 0 {
+        return;
+    }
+    let now = Local::now();
+    let due: Vec<CalendarEvent> = {
+        let mut cache = cache();
+        let soon: Vec<CalendarEvent> = cache
+            .events
+            .iter()
+            .filter(|e| !e.all_day && e.start > now && e.start - now <= ChronoDuration::minutes(minutes as i64))
+            .cloned()
+            .collect();
+        soon.into_iter().filter(|e| cache.announced.insert(e.key())).collect()
+    };
+    for event in due {
+        let mut message = format!("{} starts {}", event.title, starts_in((event.start - now).num_minutes() + 1));
+        if let Some(location) = &event.location {
+            message.push_str(&format!(" in {}", location));
+        }
+        message.push('.');
+        info!("Announcing: {}", message);
+        crate::voice::speak(&message);
+        let request = crate::notifications::NotificationRequest {
+            title: "Upcoming meeting".to_string(),
+            message,
+            actions: Vec::new(),
+            routine_id: None,
+            snooze_minutes: None,
+        };
+        if let Err(e) = crate::notifications::send(request) {
+            warn!("Couldn't show the meeting reminder: {}", e);
+        }
+    }
+}
+
+/// Refresh right away, e.g. after the settings change
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Start the background refresh and announcement loop; called from setup
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut next_refresh = Instant::now();
+        loop {
+            let settings = match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) => settings.calendar,
+                Err(e) => {
+                    warn!("Couldn't load calendar settings: {}", e);
+                    CalendarSettings::default()
+                }
+            };
+            if Instant::now() >= next_refresh {
+                if let Err(e) = refresh(&app).await {
+                    warn!("Calendar refresh failed: {}", e);
+                }
+                next_refresh = Instant::now() + Duration::from_secs(settings.refresh_minutes.max(1) * 60);
+            }
+            if settings.enabled {
+                announce_due(settings.announce_minutes);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(30)) => {}
+                _ = WAKE.notified() => next_refresh = Instant::now(),
+            }
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_calendar_status(app: AppHandle) -> AstralResult<CalendarStatus> {
+    let settings = crate::settings::load_settings(app).await?.calendar;
+    let cache = cache();
+    Ok(CalendarStatus {
+        enabled: settings.enabled,
+        google_connected: settings.google_connected(),
+        fetched_at: cache.fetched_at.map(|t| t.to_rfc3339()),
+        errors: cache.errors.clone(),
+        events: cache.events.clone(),
+    })
+}
+
+#[tauri::command]
+pub async fn refresh_calendar(app: AppHandle) -> AstralResult<CalendarStatus> {
+    refresh(&app).await?;
+    get_calendar_status(app).await
+}
+/// Sign in to Google in the browser and keep the refresh token
+#[tauri::command]
+pub async fn connect_google_calendar(app: AppHandle) -> AstralResult<()> {
+    let settings = crate::settings::load_settings(app.clone()).await?.calendar;
+    let refresh_token = crate::oauth::google_sign_in(settings.google_client(), GOOGLE_SCOPE, "Google Calendar").await?;
+    crate::settings::update_setting(app, "calendar.google_refresh_token".to_string(), Value::String(refresh_token)).await?;
+    wake();
+    Ok(())
+}
+
+/// Forget the Google refresh token, revoking it with Google as well
+#[tauri::command]
+pub async fn disconnect_google_calendar(app: AppHandle) -> AstralResult<()> {
+    let settings = crate::settings::load_settings(app.clone()).await?.calendar;
+    if settings.google_connected() {
+        crate::oauth::google_revoke(&settings.google_refresh_token).await;
+    }
+    crate::settings::update_setting(app, "calendar.google_refresh_token".to_string(), Value::String(String::new())).await?;
+    wake();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{} 09:00", date), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn expand(start: &str, rule: &str, until: &str) -> Vec<String> {
+        expand_rrule(at(start), rule, at(start), at(until))
+            .iter()
+            .map(|t| t.date().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn weekly_by_day() {
+        assert_eq!(
+            expand("2026-03-02", "FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4", "2026-12-31"),
+            ["2026-03-02", "2026-03-04", "2026-03-09", "2026-03-11"]
+        );
+    }
+
+    #[test]
+    fn monthly_ordinal_by_day() {
+        assert_eq!(
+            expand("2026-01-13", "FREQ=MONTHLY;BYDAY=2TU;COUNT=3", "2026-12-31"),
+            ["2026-01-13", "2026-02-10", "2026-03-10"]
+        );
+        assert_eq!(
+            expand("2026-01-30", "FREQ=MONTHLY;BYDAY=-1FR", "2026-04-30"),
+            ["2026-01-30", "2026-02-27", "2026-03-27", "2026-04-24"]
+        );
+    }
+
+    #[test]
+    fn monthly_by_month_day() {
+        assert_eq!(
+            expand("2026-01-31", "FREQ=MONTHLY;BYMONTHDAY=-1", "2026-04-30"),
+            ["2026-01-31", "2026-02-28", "2026-03-31", "2026-04-30"]
+        );
+        // The 31st only in months that have one
+        assert_eq!(expand("2026-01-31", "FREQ=MONTHLY", "2026-05-31"), ["2026-01-31", "2026-03-31", "2026-05-31"]);
+    }
+
+    #[test]
+    fn yearly_by_month_and_day() {
+        // Thanksgiving
+        assert_eq!(
+            expand("2026-11-26", "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", "2028-12-31"),
+            ["2026-11-26", "2027-11-25", "2028-11-23"]
+        );
+    }
+
+    #[test]
+    fn interval_and_until() {
+        assert_eq!(
+            expand("2026-01-01", "FREQ=DAILY;INTERVAL=10;UNTIL=20260125T000000Z", "2026-12-31"),
+            ["2026-01-01", "2026-01-11", "2026-01-21"]
+        );
+    }
+
+    #[test]
+    fn unsupported_parts_give_only_the_first() {
+        assert_eq!(expand("2026-01-30", "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1", "2026-12-31"), ["2026-01-30"]);
+        assert_eq!(expand("2026-01-05", "FREQ=YEARLY;BYDAY=20MO", "2030-12-31"), ["2026-01-05"]);
+    }
+}
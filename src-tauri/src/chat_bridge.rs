@@ -0,0 +1,465 @@
+// Chat Bridge
+// Lets the user message ASTRAL from Telegram or Discord when away from the
+// desk. Each message goes through `execute_command`, the same intents,
+// skills and LLM pipeline as a spoken command, and the reply comes back in
+// the chat; so do confirmation questions ("Are you sure you want to shut
+// down?"), which are answered with a plain "yes" in the chat. Both bots
+// are off by default and only answer the user IDs in their allowlist;
+// anyone else is told their ID so the owner can add it. Telegram is read
+// with long polling and Discord through its gateway, direct messages only,
+// so neither needs a port open on this machine.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use futures::{SinkExt, StreamExt};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+const TELEGRAM_API: &str = "https://api.telegram.org";
+const DISCORD_API: &str = "https://discord.com/api/v10";
+const DISCORD_GATEWAY: &str = "wss://gateway.discord.gg/?v=10&encoding=json";
+const DISCORD_GATEWAY_HOST: &str = "gateway.discord.gg";
+
+/// Direct messages only; the bot never reads servers it's in
+const DISCORD_INTENTS: u64 = 1 << 12;
+
+/// Longest message each service accepts
+const TELEGRAM_MESSAGE_LIMIT: usize = 4096;
+const DISCORD_MESSAGE_LIMIT: usize = 2000;
+
+/// How long a Telegram long poll waits for a message
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+/// Messages sent while ASTRAL was closed are dropped if older than this,
+/// so a "shut down" from last night doesn't run on the next launch
+const STALE_MESSAGE: Duration = Duration::from_secs(120);
+
+const RETRY_DELAY: Duration = Duration::from_secs(15);
+
+/// One bot account
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotSettings {
+    pub enabled: bool,
+    pub token: String,
+    /// User IDs allowed to talk to ASTRAL; nobody when empty
+    pub allowed_users: Vec<String>,
+}
+
+impl BotSettings {
+    fn allows(&self, user_id: &str) -> bool {
+        self.allowed_users.iter().any(|u| u == user_id)
+    }
+
+    fn validate(&mut self, service: &str) -> Result<(), String> {
+        self.token = self.token.trim().to_string();
+        self.allowed_users = self
+            .allowed_users
+            .iter()
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty())
+            .collect();
+        if self.enabled && self.token.is_empty() {
+            return Err(format!("Add the {} bot token", service));
+        }
+        Ok(())
+    }
+}
+
+/// The `chat_bridge` settings section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatBridgeSettings {
+    pub telegram: BotSettings,
+    pub discord: BotSettings,
+}
+
+impl ChatBridgeSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.telegram.validate("Telegram")?;
+        self.discord.validate("Discord")
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BotStatus {
+    pub enabled: bool,
+    pub connected: bool,
+    /// The bot's own name, once connected
+    pub bot_name: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChatBridgeStatus {
+    pub telegram: BotStatus,
+    pub discord: BotStatus,
+}
+
+static STATUS: Lazy<Mutex<ChatBridgeStatus>> = Lazy::new(|| Mutex::new(ChatBridgeStatus::default()));
+static TELEGRAM_WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+static DISCORD_WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// Chat commands run one at a time, in the order they arrive
+static COMMAND_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+fn status() -> std::sync::MutexGuard<'static, ChatBridgeStatus> {
+    STATUS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_error(bot: impl FnOnce(&mut ChatBridgeStatus) -> &mut BotStatus, error: Option<String>) {
+    let mut status = status();
+    let bot = bot(&mut status);
+    bot.connected = error.is_none() && bot.enabled;
+    bot.last_error = error;
+}
+
+/// Split a reply into pieces the service accepts, preferring line breaks
+fn split_message(text: &str, limit: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        if !current.is_empty() && current.chars().count() + line.chars().count() + 1 > limit {
+            pieces.push(std::mem::take(&mut current));
+        }
+        let mut line = line;
+        while line.chars().count() > limit {
+            let cut = line.char_indices().nth(limit).map_or(line.len(), |(i, _)| i);
+            pieces.push(line[..cut].to_string());
+            line = &line[cut..];
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.trim().is_empty() {
+        pieces.push(current);
+    }
+    pieces
+}
+
+/// Run one chat message through the command pipeline
+async fn answer(app: &AppHandle, bot: &BotSettings, service: &str, user_id: &str, text: &str) -> String {
+    if !bot.allows(user_id) {
+        info!("{} message from unknown user {} ignored", service, user_id);
+        return format!(
+            "I only answer my owner. Your {} user ID is {}; add it to the allowed users in ASTRAL's settings to chat with me.",
+            service, user_id
+        );
+    }
+    let text = text.trim();
+    if text.is_empty() || matches!(text, "/start" | "/help") {
+        return "Hi! Send me anything you'd say to ASTRAL out loud, like \"run evening wind down\" or \"what's on my calendar today\".".to_string();
+    }
+    info!("{} command from {}", service, user_id);
+    debug!("{} command: {}", service, text);
+    let _turn = COMMAND_LOCK.lock().await;
+    let origin = format!("{}:{}", service.to_lowercase(), user_id);
+    match crate::commands::execute_command_from(app.clone(), &origin, text.to_string(), None).await {
+        Ok(reply) if reply.trim().is_empty() => "Done.".to_string(),
+        Ok(reply) => reply,
+        Err(e) => format!("Something went wrong: {}", e),
+    }
+}
+
+// ========== Telegram ==========
+
+async fn telegram_call(token: &str, method: &str, body: Value, timeout: Duration) -> AstralResult<Value> {
+    let request = crate::proxy::client()
+        .post(format!("{}/bot{}/{}", TELEGRAM_API, token, method))
+        .json(&body)
+        .timeout(timeout);
+    let response = crate::privacy::send(request).await.map_err(telegram_error)?;
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::NOT_FOUND {
+        return Err(AstralError::new(ErrorCode::Unauthorized, "Telegram didn't accept the bot token"));
+    }
+    if status == reqwest::StatusCode::CONFLICT {
+        return Err(AstralError::new(
+            ErrorCode::ProviderError,
+            "Another program is reading this Telegram bot's messages; give ASTRAL its own bot",
+        ));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("Telegram", status, &body));
+    }
+    let body: Value = response.json().await.map_err(|e| AstralError::from(e.without_url()))?;
+    Ok(body.get("result").cloned().unwrap_or(Value::Null))
+}
+
+/// reqwest errors name the URL, and Telegram's holds the bot token
+fn telegram_error(error: anyhow::Error) -> AstralError {
+    let error = match error.downcast::<reqwest::Error>() {
+        Ok(e) => anyhow::Error::new(e.without_url()),
+        Err(e) => e,
+    };
+    AstralError::from(error.context("Couldn't reach Telegram"))
+}
+
+async fn telegram_reply(token: &str, chat_id: &Value, text: &str) {
+    for piece in split_message(text, TELEGRAM_MESSAGE_LIMIT) {
+        let body = json!({ "chat_id": chat_id, "text": piece });
+        if let Err(e) = telegram_call(token, "sendMessage", body, Duration::from_secs(20)).await {
+            warn!("Couldn't reply on Telegram: {}", e);
+            return;
+        }
+    }
+}
+
+/// Poll for messages until the settings change or the token stops working
+async fn run_telegram(app: &AppHandle, bot: &BotSettings) -> AstralResult<()> {
+    let me = telegram_call(&bot.token, "getMe", json!({}), Duration::from_secs(20)).await?;
+    let name = me.get("username").and_then(Value::as_str).map(|n| format!("@{}", n));
+    info!("Telegram bridge connected as {}", name.as_deref().unwrap_or("a bot"));
+    status().telegram.bot_name = name;
+    set_error(|s| &mut s.telegram, None);
+
+    let mut offset = 0i64;
+    loop {
+        let body = json!({ "offset": offset, "timeout": POLL_TIMEOUT_SECS, "allowed_updates": ["message"] });
+        let poll = telegram_call(&bot.token, "getUpdates", body, Duration::from_secs(POLL_TIMEOUT_SECS + 10));
+        let updates = tokio::select! {
+            updates = poll => updates?,
+            _ = TELEGRAM_WAKE.notified() => return Ok(()),
+        };
+        let now = chrono::Utc::now().timestamp();
+        for update in updates.as_array().into_iter().flatten() {
+            offset = offset.max(update.get("update_id").and_then(Value::as_i64).unwrap_or_default() + 1);
+            let Some(message) = update.get("message") else { continue };
+            let Some(text) = message.get("text").and_then(Value::as_str) else { continue };
+            let sent = message.get("date").and_then(Value::as_i64).unwrap_or(now);
+            if now - sent > STALE_MESSAGE.as_secs() as i64 {
+                continue;
+            }
+            let user_id = message.pointer("/from/id").map(|id| id.to_string()).unwrap_or_default();
+            let chat_id = message.pointer("/chat/id").cloned().unwrap_or(Value::Null);
+            let typing = json!({ "chat_id": chat_id, "action": "typing" });
+            let _ = telegram_call(&bot.token, "sendChatAction", typing, Duration::from_secs(10)).await;
+            let reply = answer(app, bot, "Telegram", &user_id, text).await;
+            telegram_reply(&bot.token, &chat_id, &reply).await;
+        }
+    }
+}
+
+// ========== Discord ==========
+
+async fn discord_request(token: &str, path: &str, body: Value) -> AstralResult<()> {
+    let request = crate::proxy::client()
+        .post(format!("{}{}", DISCORD_API, path))
+        .header("Authorization", format!("Bot {}", token))
+        .json(&body)
+        .timeout(Duration::from_secs(20));
+    let response = crate::privacy::send(request)
+        .await
+        .map_err(|e| AstralError::from(e.context("Couldn't reach Discord")))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("Discord", status, &body));
+    }
+    Ok(())
+}
+
+async fn discord_reply(token: &str, channel_id: &str, text: &str) {
+    for piece in split_message(text, DISCORD_MESSAGE_LIMIT) {
+        let path = format!("/channels/{}/messages", channel_id);
+        if let Err(e) = discord_request(token, &path, json!({ "content": piece })).await {
+            warn!("Couldn't reply on Discord: {}", e);
+            return;
+        }
+    }
+}
+
+async fn handle_discord_message(app: AppHandle, bot: BotSettings, message: Value) {
+    if message.pointer("/author/bot").and_then(Value::as_bool).unwrap_or(false) || message.get("guild_id").is_some() {
+        return;
+    }
+    let user_id = message.pointer("/author/id").and_then(Value::as_str).unwrap_or_default();
+    let channel_id = message.get("channel_id").and_then(Value::as_str).unwrap_or_default();
+    let text = message.get("content").and_then(Value::as_str).unwrap_or_default();
+    let _ = discord_request(&bot.token, &format!("/channels/{}/typing", channel_id), json!({})).await;
+    let reply = answer(&app, &bot, "Discord", user_id, text).await;
+    discord_reply(&bot.token, channel_id, &reply).await;
+}
+
+/// Stay on the gateway until the settings change or Discord drops us
+async fn run_discord(app: &AppHandle, bot: &BotSettings) -> AstralResult<()> {
+    // Not plain HTTP, so privacy mode is checked here rather than in `privacy::send`
+    crate::privacy::check_url(DISCORD_GATEWAY)?;
+    // The socket is opened here so the gateway follows the proxy setting too
+    let stream = crate::proxy::connect(DISCORD_GATEWAY_HOST, 443)
+        .await
+        .map_err(|e| AstralError::new(ErrorCode::Network, format!("Couldn't reach Discord: {}", e)))?;
+    let (mut socket, _) = tokio_tungstenite::client_async_tls(DISCORD_GATEWAY, stream)
+        .await
+        .map_err(|e| AstralError::new(ErrorCode::Network, format!("Couldn't reach Discord: {}", e)))?;
+
+    let mut sequence = Value::Null;
+    let mut heartbeat = Duration::from_secs(40);
+    let mut next_beat = tokio::time::Instant::now() + heartbeat;
+    loop {
+        let frame = tokio::select! {
+            frame = socket.next() => frame,
+            _ = tokio::time::sleep_until(next_beat) => {
+                next_beat = tokio::time::Instant::now() + heartbeat;
+                let beat = json!({ "op": 1, "d": sequence }).to_string();
+                socket.send(Message::Text(beat)).await.map_err(|e| AstralError::new(ErrorCode::Network, e.to_string()))?;
+                continue;
+            }
+            _ = DISCORD_WAKE.notified() => {
+                let _ = socket.close(None).await;
+                return Ok(());
+            }
+        };
+        let text = match frame {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Close(frame))) => {
+                // 4004: bad token; 4013/4014: intents the bot isn't allowed
+                return Err(match frame.map(|f| u16::from(f.code)) {
+                    Some(4004) => AstralError::new(ErrorCode::Unauthorized, "Discord didn't accept the bot token"),
+                    Some(code @ (4013 | 4014)) => {
+                        AstralError::new(ErrorCode::PermissionDenied, format!("Discord refused the bot's intents ({})", code))
+                    }
+                    code => AstralError::new(ErrorCode::Network, format!("Discord closed the connection ({:?})", code)),
+                });
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(AstralError::new(ErrorCode::Network, format!("Discord connection error: {}", e))),
+            None => return Err(AstralError::new(ErrorCode::Network, "Discord closed the connection")),
+        };
+        let Ok(payload) = serde_json::from_str::<Value>(&text) else { continue };
+        if let Some(s) = payload.get("s").filter(|s| !s.is_null()) {
+            sequence = s.clone();
+        }
+        match payload.get("op").and_then(Value::as_u64) {
+            // Hello: start the heartbeat and identify
+            Some(10) => {
+                let interval = payload.pointer("/d/heartbeat_interval").and_then(Value::as_u64).unwrap_or(40_000);
+                heartbeat = Duration::from_millis(interval);
+                next_beat = tokio::time::Instant::now() + heartbeat;
+                let identify = json!({
+                    "op": 2,
+                    "d": {
+                        "token": bot.token,
+                        "intents": DISCORD_INTENTS,
+                        "properties": { "os": std::env::consts::OS, "browser": "astral", "device": "astral" },
+                    },
+                });
+                socket
+                    .send(Message::Text(identify.to_string()))
+                    .await
+                    .map_err(|e| AstralError::new(ErrorCode::Network, e.to_string()))?;
+            }
+            // Heartbeat requested
+            Some(1) => next_beat = tokio::time::Instant::now(),
+            // Reconnect / invalid session: start over with a new connection
+            Some(7) | Some(9) => return Err(AstralError::new(ErrorCode::Network, "Discord asked for a new connection")),
+            Some(0) => match payload.get("t").and_then(Value::as_str) {
+                Some("READY") => {
+                    let name = payload.pointer("/d/user/username").and_then(Value::as_str).map(str::to_string);
+                    info!("Discord bridge connected as {}", name.as_deref().unwrap_or("a bot"));
+                    status().discord.bot_name = name;
+                    set_error(|s| &mut s.discord, None);
+                }
+                Some("MESSAGE_CREATE") => {
+                    let message = payload.get("d").cloned().unwrap_or(Value::Null);
+                    // Outside the socket loop so a slow command doesn't miss heartbeats
+                    tauri::async_runtime::spawn(handle_discord_message(app.clone(), bot.clone(), message));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+// ========== Lifecycle ==========
+
+/// Reconnect both bots with the current settings
+pub fn wake() {
+    TELEGRAM_WAKE.notify_one();
+    DISCORD_WAKE.notify_one();
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Service {
+    Telegram,
+    Discord,
+}
+
+impl Service {
+    fn wake_signal(self) -> &'static Notify {
+        match self {
+            Self::Telegram => &TELEGRAM_WAKE,
+            Self::Discord => &DISCORD_WAKE,
+        }
+    }
+
+    fn status(self, status: &mut ChatBridgeStatus) -> &mut BotStatus {
+        match self {
+            Self::Telegram => &mut status.telegram,
+            Self::Discord => &mut status.discord,
+        }
+    }
+}
+
+/// Keep one bot connected while it's enabled, retrying after network trouble
+async fn supervise(app: AppHandle, service: Service) {
+    let wake = service.wake_signal();
+    loop {
+        let bot = match crate::settings::load_settings(app.clone()).await {
+            Ok(settings) => match service {
+                Service::Telegram => settings.chat_bridge.telegram,
+                Service::Discord => settings.chat_bridge.discord,
+            },
+            Err(e) => {
+                warn!("Couldn't load chat bridge settings: {}", e);
+                BotSettings::default()
+            }
+        };
+        service.status(&mut status()).enabled = bot.enabled;
+        if !bot.enabled {
+            set_error(|s| service.status(s), None);
+            wake.notified().await;
+            continue;
+        }
+        let result = match service {
+            Service::Telegram => run_telegram(&app, &bot).await,
+            Service::Discord => run_discord(&app, &bot).await,
+        };
+        let Err(e) = result else { continue };
+        warn!("{:?} bridge: {}", service, e);
+        // A bad token or privacy mode won't fix itself; wait for the settings to change
+        let retry = !matches!(e.code, ErrorCode::Unauthorized | ErrorCode::PermissionDenied | ErrorCode::PrivacyMode);
+        set_error(|s| service.status(s), Some(e.to_string()));
+        if retry {
+            tokio::select! {
+                _ = tokio::time::sleep(RETRY_DELAY) => {}
+                _ = wake.notified() => {}
+            }
+        } else {
+            wake.notified().await;
+        }
+    }
+}
+
+/// Start both bridges; called from setup
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(supervise(app.clone(), Service::Telegram));
+    tauri::async_runtime::spawn(supervise(app, Service::Discord));
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_chat_bridge_status() -> ChatBridgeStatus {
+    status().clone()
+}
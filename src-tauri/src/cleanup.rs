@@ -0,0 +1,339 @@
+// Cleanup Module
+// Recycle bin size / emptying and temp + cache clearing, with a dry run that
+// reports what would be freed before anything is deleted
+
+use anyhow::Result;
+use tracing::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// Files touched more recently than this may still be in use
+const MIN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecycleBinInfo {
+    pub bytes: u64,
+    pub items: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupTarget {
+    pub name: String,
+    pub path: String,
+    /// Bytes removed, or that would be removed on a dry run
+    pub bytes: u64,
+    pub files: u64,
+    /// Files that were in use or not ours to delete
+    pub skipped: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupReport {
+    pub dry_run: bool,
+    pub targets: Vec<CleanupTarget>,
+    /// None when the recycle bin wasn't part of the run
+    pub recycle_bin: Option<RecycleBinInfo>,
+    pub total_bytes: u64,
+}
+
+impl CleanupReport {
+    /// Reply for "free up some space"
+    pub fn describe(&self) -> String {
+        let size = crate::file_search::format_size(self.total_bytes);
+        if self.total_bytes == 0 {
+            return "There's nothing to clean up right now.".to_string();
+        }
+
+        let mut parts: Vec<String> = self
+            .targets
+            .iter()
+            .filter(|t| t.bytes > 0)
+            .map(|t| format!("{} in {}", crate::file_search::format_size(t.bytes), t.name))
+            .collect();
+        if let Some(bin) = self.recycle_bin.as_ref().filter(|b| b.bytes > 0) {
+            parts.push(format!("{} in the recycle bin", crate::file_search::format_size(bin.bytes)));
+        }
+
+        if self.dry_run {
+            format!("I can free up {}: {}.", size, parts.join(", "))
+        } else {
+            format!("Freed up {}.", size)
+        }
+    }
+}
+
+/// A spoken cleanup request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanupCommand {
+    RecycleBinSize,
+    EmptyRecycleBin,
+    /// "free up some space": dry run first, then clean after confirmation
+    FreeSpace,
+}
+
+impl CleanupCommand {
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.to_lowercase();
+        let bin = lower.contains("recycle bin") || lower.contains("trash");
+
+        if bin && (lower.contains("empty") || lower.contains("clear")) {
+            return Some(Self::EmptyRecycleBin);
+        }
+        if bin && (lower.contains("how big") || lower.contains("how much") || lower.contains("size")) {
+            return Some(Self::RecycleBinSize);
+        }
+        let phrases = ["free up some space", "free up space", "free up disk space", "clean up my disk", "disk cleanup", "clear temp files", "clear the cache", "clean up temp files"];
+        if phrases.iter().any(|p| lower.contains(p)) {
+            return Some(Self::FreeSpace);
+        }
+        None
+    }
+}
+
+/// Tauri's cache folder for this app (the webview's cache lives there)
+#[cfg(not(target_os = "windows"))]
+const APP_IDENTIFIER: &str = "com.astral.app";
+
+/// Temp and cache folders cleared by `cleanup`. Other apps' caches are left
+/// alone: they aren't ours to delete, and some keep data there that isn't
+/// just a cache.
+fn cleanup_targets() -> Vec<(String, PathBuf)> {
+    let mut targets = vec![("temporary files".to_string(), std::env::temp_dir())];
+
+    #[cfg(target_os = "windows")]
+    if let Some(local) = dirs::data_local_dir() {
+        targets.push(("the internet cache".to_string(), local.join(r"Microsoft\Windows\INetCache")));
+        targets.push(("crash dumps".to_string(), local.join("CrashDumps")));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    if let Some(cache) = dirs::cache_dir() {
+        targets.push(("ASTRAL's cache".to_string(), cache.join("ASTRAL")));
+        targets.push(("ASTRAL's web cache".to_string(), cache.join(APP_IDENTIFIER)));
+    }
+
+    targets.retain(|(_, path)| path.is_dir());
+    targets
+}
+
+fn is_old_enough(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age >= MIN_AGE)
+}
+
+fn clean_folder(name: &str, root: &Path, dry_run: bool) -> CleanupTarget {
+    let mut target = CleanupTarget {
+        name: name.to_string(),
+        path: root.to_string_lossy().to_string(),
+        bytes: 0,
+        files: 0,
+        skipped: 0,
+    };
+
+    let files = WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file());
+
+    for entry in files {
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        if !is_old_enough(entry.path()) {
+            target.skipped += 1;
+            continue;
+        }
+        if dry_run || std::fs::remove_file(entry.path()).is_ok() {
+            target.bytes += size;
+            target.files += 1;
+        } else {
+            target.skipped += 1;
+        }
+    }
+
+    // Leave the folder structure apps expect, minus directories we emptied
+    if !dry_run {
+        let mut folders: Vec<PathBuf> = WalkDir::new(root)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_dir() && is_old_enough(e.path()))
+            .map(|e| e.into_path())
+            .collect();
+        // Deepest first so parents are empty by the time we reach them
+        folders.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+        for dir in folders {
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+
+    target
+}
+
+pub fn recycle_bin_info() -> Result<RecycleBinInfo> {
+    platform::recycle_bin_info()
+}
+
+pub fn empty_recycle_bin() -> Result<RecycleBinInfo> {
+    let before = platform::recycle_bin_info()?;
+    info!("Emptying recycle bin ({} items, {} bytes)", before.items, before.bytes);
+    platform::empty_recycle_bin()?;
+    Ok(before)
+}
+
+/// Clear temp/cache folders (and optionally the recycle bin). With `dry_run`
+/// nothing is deleted and the report says what would be. Blocking.
+pub fn cleanup(dry_run: bool, include_recycle_bin: bool) -> Result<CleanupReport> {
+    let targets: Vec<CleanupTarget> = cleanup_targets()
+        .iter()
+        .map(|(name, path)| clean_folder(name, path, dry_run))
+        .collect();
+
+    let recycle_bin = if !include_recycle_bin {
+        None
+    } else if dry_run {
+        platform::recycle_bin_info().ok()
+    } else {
+        match empty_recycle_bin() {
+            Ok(info) => Some(info),
+            Err(e) => {
+                warn!("Failed to empty recycle bin: {}", e);
+                None
+            }
+        }
+    };
+
+    let total_bytes = targets.iter().map(|t| t.bytes).sum::<u64>() + recycle_bin.as_ref().map_or(0, |b| b.bytes);
+    if !dry_run {
+        info!("Cleanup freed {} bytes", total_bytes);
+    }
+
+    Ok(CleanupReport { dry_run, targets, recycle_bin, total_bytes })
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::RecycleBinInfo;
+    use anyhow::Result;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::{
+        SHEmptyRecycleBinW, SHQueryRecycleBinW, SHERB_NOCONFIRMATION, SHERB_NOPROGRESSUI, SHERB_NOSOUND,
+        SHQUERYRBINFO,
+    };
+
+    pub fn recycle_bin_info() -> Result<RecycleBinInfo> {
+        let mut info = SHQUERYRBINFO {
+            cbSize: std::mem::size_of::<SHQUERYRBINFO>() as u32,
+            ..Default::default()
+        };
+        // A null root queries the bins on every drive
+        unsafe { SHQueryRecycleBinW(PCWSTR::null(), &mut info)? };
+        Ok(RecycleBinInfo { bytes: info.i64Size as u64, items: info.i64NumItems as u64 })
+    }
+
+    pub fn empty_recycle_bin() -> Result<()> {
+        let result = unsafe {
+            SHEmptyRecycleBinW(HWND(0), PCWSTR::null(), SHERB_NOCONFIRMATION | SHERB_NOPROGRESSUI | SHERB_NOSOUND)
+        };
+        // Emptying an already empty bin reports E_UNEXPECTED
+        if let Err(e) = result {
+            if recycle_bin_info().map(|i| i.items).unwrap_or(1) > 0 {
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::RecycleBinInfo;
+    use anyhow::{Context, Result};
+    use std::path::PathBuf;
+    use walkdir::WalkDir;
+
+    /// Folders holding trashed files (the macOS Trash, or the freedesktop.org trash)
+    fn trash_dirs() -> Result<Vec<PathBuf>> {
+        let home = dirs::home_dir().context("No home directory")?;
+
+        #[cfg(target_os = "macos")]
+        let folders = vec![home.join(".Trash")];
+        #[cfg(not(target_os = "macos"))]
+        let folders = {
+            let trash = dirs::data_dir().unwrap_or_else(|| home.join(".local/share")).join("Trash");
+            vec![trash.join("files"), trash.join("info")]
+        };
+
+        Ok(folders.into_iter().filter(|d| d.is_dir()).collect())
+    }
+
+    pub fn recycle_bin_info() -> Result<RecycleBinInfo> {
+        let mut info = RecycleBinInfo { bytes: 0, items: 0 };
+        for dir in trash_dirs()? {
+            if dir.ends_with("info") {
+                continue;
+            }
+            info.items += std::fs::read_dir(&dir)?.count() as u64;
+            info.bytes += WalkDir::new(&dir)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum::<u64>();
+        }
+        Ok(info)
+    }
+
+    pub fn empty_recycle_bin() -> Result<()> {
+        for dir in trash_dirs()? {
+            for entry in std::fs::read_dir(&dir)?.filter_map(|e| e.ok()) {
+                let path = entry.path();
+                let removed = if path.is_dir() { std::fs::remove_dir_all(&path) } else { std::fs::remove_file(&path) };
+                if let Err(e) = removed {
+                    tracing::warn!("Failed to remove {}: {}", path.display(), e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_recycle_bin_info() -> Result<RecycleBinInfo, String> {
+    tokio::task::spawn_blocking(recycle_bin_info)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Returns what was in the bin before it was emptied
+#[tauri::command]
+pub async fn empty_recycle_bin_command() -> Result<RecycleBinInfo, String> {
+    crate::permissions::check(crate::permissions::Capability::FileDeletion)?;
+    tokio::task::spawn_blocking(empty_recycle_bin)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Clear temp and cache folders. Defaults to a dry run; pass `dry_run: false` to delete.
+#[tauri::command]
+pub async fn run_disk_cleanup(dry_run: Option<bool>, include_recycle_bin: Option<bool>) -> Result<CleanupReport, String> {
+    let dry_run = dry_run.unwrap_or(true);
+    let include_recycle_bin = include_recycle_bin.unwrap_or(false);
+    if !dry_run {
+        crate::permissions::check(crate::permissions::Capability::FileDeletion)?;
+    }
+    tokio::task::spawn_blocking(move || cleanup(dry_run, include_recycle_bin))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
@@ -1,16 +1,112 @@
 use serde::{Deserialize, Serialize};
-use log::info;
+use std::collections::HashMap;
+use tracing::{debug, info, warn};
 use tokio::sync::Mutex;
 use once_cell::sync::Lazy;
 
-use crate::llm_provider::{LLMManager, LLMConfig, LLMResponse};
-use crate::automation::{AutomationManager, AutomationRoutine, AutomationResult};
-use crate::audio_engine::AudioEngine;
+use tauri::{AppHandle, Manager, State};
 
-// Global state managers
-static LLM_MANAGER: Lazy<Mutex<Option<LLMManager>>> = Lazy::new(|| Mutex::new(None));
-static AUTOMATION_MANAGER: Lazy<Mutex<AutomationManager>> = Lazy::new(|| Mutex::new(AutomationManager::new()));
-static AUDIO_ENGINE: Lazy<Mutex<Option<AudioEngine>>> = Lazy::new(|| Mutex::new(None));
+use crate::llm_provider::{Image, LLMManager, LLMConfig, LLMResponse, ToolCall, SYSTEM_PROMPT as DEFAULT_SYSTEM_PROMPT};
+use crate::automation::{AutomationRoutine, AutomationResult};
+use crate::state::AppState;
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use crate::intents::Intent;
+use crate::voice::bus::{PipelineEvent, Route};
+
+// Questions waiting on a yes/no answer, by the origin they were asked at, so
+// a "yes" in a chat can't confirm what the voice pipeline asked and the other
+// way round
+static PENDING_QUESTIONS: Lazy<Mutex<HashMap<String, PendingQuestion>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Origin of commands spoken or typed at this machine (voice and the app)
+pub const LOCAL_ORIGIN: &str = "local";
+
+enum PendingQuestion {
+    /// Low-confidence transcript: "did you say ...?"
+    Transcript(String),
+    /// Destructive action held back until the user confirms: "are you sure ...?"
+    Action(GatedAction),
+}
+
+/// Actions that only run after a spoken "yes"
+#[derive(Debug, Clone)]
+enum GatedAction {
+    Power(crate::power::PowerCommand),
+    CloseApp { name: String, force: bool, count: usize },
+    EmptyRecycleBin { bytes: u64 },
+    /// `summary` is the dry-run report read back to the user
+    Cleanup { summary: String },
+    /// A tool the LLM called that changes something; `summary` says what
+    Tool { call: ToolCall, summary: String },
+    /// Unlocking, opening a cover or touching the alarm
+    Home { command: crate::home_assistant::HomeCommand, question: String },
+}
+
+/// What the model is told when its tool call is held for the user's yes
+const HELD_TOOL_RESULT: &str = "Not done yet: the user has to confirm this first, and ASTRAL will ask them right after your reply. Don't ask them yourself and don't claim it's done.";
+
+impl GatedAction {
+    fn question(&self) -> String {
+        match self {
+            Self::Power(power) => format!("Are you sure you want to {}?", power.action.verb()),
+            Self::CloseApp { name, count: 1, .. } => format!("Are you sure you want to close {}?", name),
+            Self::CloseApp { name, count, .. } => format!("Are you sure you want to close {} ({} processes)?", name, count),
+            Self::EmptyRecycleBin { bytes } => format!(
+                "The recycle bin holds {}. Are you sure you want to empty it?",
+                crate::file_search::format_size(*bytes)
+            ),
+            Self::Cleanup { summary } => format!("{} Should I go ahead?", summary),
+            Self::Tool { summary, .. } => format!("Are you sure you want me to {}?", summary),
+            Self::Home { question, .. } => question.clone(),
+        }
+    }
+    
+    async fn run(self, app: &AppHandle) -> String {
+        match self {
+            Self::Home { command, .. } => crate::home_assistant::handle(app, command).await,
+            Self::Tool { call, summary } => match crate::mcp::call_llm_tool(app, call).await {
+                Ok(_) => "Done.".to_string(),
+                Err(e) => format!("I couldn't {}: {}", summary, e),
+            },
+            Self::CloseApp { name, force, .. } => {
+                let target = name.clone();
+                match tokio::task::spawn_blocking(move || crate::processes::kill_by_name(&target, force)).await {
+                    Ok(Ok(_)) => format!("Closed {}", name),
+                    Ok(Err(e)) => format!("I couldn't close {}: {}", name, e),
+                    Err(e) => format!("I couldn't close {}: {}", name, e),
+                }
+            }
+            Self::EmptyRecycleBin { .. } => {
+                match tokio::task::spawn_blocking(crate::cleanup::empty_recycle_bin).await {
+                    Ok(Ok(info)) => format!("Emptied the recycle bin and freed {}", crate::file_search::format_size(info.bytes)),
+                    Ok(Err(e)) => format!("I couldn't empty the recycle bin: {}", e),
+                    Err(e) => format!("I couldn't empty the recycle bin: {}", e),
+                }
+            }
+            Self::Cleanup { .. } => {
+                match tokio::task::spawn_blocking(|| crate::cleanup::cleanup(false, true)).await {
+                    Ok(Ok(report)) => report.describe(),
+                    Ok(Err(e)) => format!("I couldn't clean up: {}", e),
+                    Err(e) => format!("I couldn't clean up: {}", e),
+                }
+            }
+            Self::Power(power) => {
+                match tokio::task::spawn_blocking(move || crate::power::run(power)).await {
+                    Ok(Ok(())) => power.describe(),
+                    Ok(Err(e)) => format!("I couldn't {}: {}", power.action.verb(), e),
+                    Err(e) => format!("I couldn't {}: {}", power.action.verb(), e),
+                }
+            }
+        }
+    }
+}
+
+/// Hold `action` until the user answers at `origin`, returning the question to ask
+async fn ask_confirmation(origin: &str, action: GatedAction) -> String {
+    let question = action.question();
+    PENDING_QUESTIONS.lock().await.insert(origin.to_string(), PendingQuestion::Action(action));
+    question
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SystemInfo {
@@ -18,30 +114,26 @@ pub struct SystemInfo {
     pub memory_used: u64,
     pub memory_total: u64,
     pub gpu_usage: Option<f32>,
+    pub uptime_secs: u64,
+    /// Unix seconds
+    pub boot_time: u64,
+    pub pending_reboot: Option<bool>,
 }
 
 /// Initialize the ASTRAL assistant
 #[tauri::command]
-pub async fn initialize_assistant() -> Result<String, String> {
+pub async fn initialize_assistant(state: State<'_, AppState>) -> Result<String, String> {
     info!("Initializing ASTRAL assistant...");
     
-    // Initialize audio engine
-    let mut audio_engine = AudioEngine::new();
-    
-    // Start wake word detection
-    match audio_engine.start_wake_word_detection().await {
-        Ok(_) => info!("Wake word detection started"),
-        Err(e) => info!("Wake word detection not started: {}", e),
-    }
-    
-    *AUDIO_ENGINE.lock().await = Some(audio_engine);
+    // Voice pipeline (capture, wake word, STT) lives in crate::voice and is
+    // started by the frontend via start_wake_word_detection
     
-    // Initialize LLM with default config (Ollama local)
-    let llm_config = LLMConfig::default();
-    let llm_manager = LLMManager::new(llm_config);
-    *LLM_MANAGER.lock().await = Some(llm_manager);
+    // The LLM is configured from settings in setup; only fall back to the
+    // default (local Ollama) if that hasn't happened. Replacing it here would
+    // throw away the saved provider.
+    state.llm.lock().await.get_or_insert_with(|| LLMManager::new(LLMConfig::default()));
     
-    // Automation manager is already initialized via Lazy
+    // The automation manager is created with the app state in main.rs
     info!("ASTRAL initialization complete");
     
     Ok("AKI initialized successfully - Wake word: 'Hey AKI', LLM: Local Ollama, Automation: Active".to_string())
@@ -52,54 +144,214 @@ pub async fn initialize_assistant() -> Result<String, String> {
 pub async fn get_system_info() -> Result<SystemInfo, String> {
     info!("Fetching system information...");
     
-    #[cfg(target_os = "windows")]
-    {
-        use crate::system_integration::get_windows_system_info;
-        get_windows_system_info().map_err(|e| e.to_string())
-    }
-    
-    #[cfg(not(target_os = "windows"))]
-    {
-        // Placeholder for other platforms
-        Ok(SystemInfo {
-            cpu_usage: 0.0,
-            memory_used: 0,
-            memory_total: 0,
-            gpu_usage: None,
-        })
-    }
+    let stats = crate::system_monitor::get_system_stats_command().await?;
+    let boot = tokio::task::spawn_blocking(crate::system_monitor::boot_info)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(SystemInfo {
+        cpu_usage: stats.cpu_usage,
+        memory_used: stats.memory_used,
+        memory_total: stats.memory_total,
+        gpu_usage: stats.gpu_usage,
+        uptime_secs: boot.uptime_secs,
+        boot_time: boot.boot_time,
+        pending_reboot: boot.pending_reboot,
+    })
 }
 
 /// Execute a voice command
+///
+/// `confidence` is the STT confidence for the transcript, if known. Commands
+/// below the configured threshold aren't run; the user is asked to confirm first.
 #[tauri::command]
-pub async fn execute_command(command: String) -> Result<String, String> {
-    info!("Executing command: {}", command);
-    
-    // Check if this should go to LLM or handle locally
-    let lower = command.to_lowercase();
+pub async fn execute_command(
+    app: tauri::AppHandle,
+    command: String,
+    confidence: Option<f32>,
+) -> Result<String, String> {
+    execute_command_from(app, LOCAL_ORIGIN, command, confidence).await
+}
+
+/// `execute_command` for commands from elsewhere (the API, a chat bot);
+/// `origin` names where, and a question asked there is only answered there
+pub async fn execute_command_from(
+    app: tauri::AppHandle,
+    origin: &str,
+    command: String,
+    confidence: Option<f32>,
+) -> Result<String, String> {
+    crate::metrics::time(crate::metrics::Stage::Command, run_command(app, origin, command, confidence)).await
+}
+
+async fn run_command(app: tauri::AppHandle, origin: &str, command: String, confidence: Option<f32>) -> Result<String, String> {
+    debug!("Executing command: {} (confidence: {:?})", command, confidence);
     
-    // Handle automation trigger phrases
-    if lower.contains("work mode") || lower.contains("start work") {
-        let mut automation = AUTOMATION_MANAGER.lock().await;
-        match automation.execute_routine("work-mode").await {
-            Ok(_) => return Ok("Work mode activated!".to_string()),
-            Err(e) => return Ok(format!("Failed to start work mode: {}", e)),
+    let command = match resolve_clarification(&app, origin, command.clone(), confidence).await {
+        Clarification::Proceed(command) => command,
+        Clarification::Respond(reply) => {
+            routed(Route::Clarification, &command);
+            return Ok(reply);
+        }
+        Clarification::Confirmed(action) => {
+            routed(Route::Clarification, &command);
+            return Ok(action.run(&app).await);
         }
+    };
+    
+    // Built-in commands, matched by the intent router's rule patterns
+    if let Some(intent) = crate::intents::parse(&command) {
+        routed(Route::Intent, &command);
+        return handle_intent(&app, origin, intent).await;
+    }
+    
+    // Installed skills ("what's the weather"); built-in commands win over them
+    if let Some(reply) = crate::skills::handle_command(&app, &command).await {
+        routed(Route::Skill, &command);
+        return Ok(reply);
     }
     
-    if lower.contains("gaming mode") || lower.contains("start gaming") {
-        let mut automation = AUTOMATION_MANAGER.lock().await;
-        match automation.execute_routine("gaming-mode").await {
-            Ok(_) => return Ok("Gaming mode activated!".to_string()),
-            Err(e) => return Ok(format!("Failed to start gaming mode: {}", e)),
+    // Only when search is on; otherwise these are the LLM's like any other question
+    let web_query = crate::web_search::is_web_query(&command) && crate::web_search::is_enabled(&app).await;
+
+    // Everything else, when LLM classification is on ("crank it up a bit")
+    if !crate::ocr::is_screen_query(&command)
+        && !crate::documents::is_document_query(&command)
+        && !web_query
+        && !crate::knowledge_base::is_knowledge_query(&command)
+    {
+        if let Some(intent) = crate::intents::classify(&app, &command).await {
+            routed(Route::ClassifiedIntent, &command);
+            return handle_intent(&app, origin, intent).await;
         }
     }
     
+    // Search results and document passages to cite under the answer
+    let mut sources = Vec::new();
+    let mut passages = Vec::new();
+    // Plain requests may be answered with tools; the others already carry what they need
+    let mut open_ended = false;
+
+    // Screen questions ("read me the error on screen") go to the LLM with the OCR'd text
+    let prompt = if crate::ocr::is_screen_query(&command) {
+        routed(Route::Screen, &command);
+        let screen = match tokio::task::spawn_blocking(crate::ocr::read_screen).await {
+            Ok(Ok(screen)) => screen,
+            Ok(Err(e)) => return Ok(format!("I couldn't read the screen: {}", e)),
+            Err(e) => return Err(e.to_string()),
+        };
+        if screen.is_empty() {
+            return Ok("I don't see any text on the screen.".to_string());
+        }
+        crate::ocr::build_screen_prompt(&command, &screen)
+    } else if crate::documents::is_document_query(&command) {
+        // "What's in this document?" reads the file selected in Explorer/Finder
+        routed(Route::Document, &command);
+        let selected = match tokio::task::spawn_blocking(crate::documents::selected_files).await {
+            Ok(Ok(selected)) => selected,
+            Ok(Err(e)) => return Ok(format!("I couldn't see which file you mean: {}", e)),
+            Err(e) => return Err(e.to_string()),
+        };
+        let Some(path) = selected.into_iter().next() else {
+            return Ok("Select a file in Explorer or drag it onto my window first.".to_string());
+        };
+        let document = match tokio::task::spawn_blocking(move || crate::documents::extract_text(&path)).await {
+            Ok(Ok(document)) => document,
+            Ok(Err(e)) => return Ok(format!("I couldn't read that file: {}", e)),
+            Err(e) => return Err(e.to_string()),
+        };
+        crate::documents::build_document_prompt(&command, &document)
+    } else if web_query {
+        // "What's the latest on ...?" is answered from search results, not the model's memory
+        routed(Route::Web, &command);
+        sources = match crate::web_search::search_for_command(&app, &command).await {
+            Ok(results) => results,
+            Err(e) => return Ok(format!("I couldn't search the web: {}", e)),
+        };
+        if sources.is_empty() {
+            return Ok("I didn't find anything on the web about that.".to_string());
+        }
+        crate::web_search::build_search_prompt(&command, &sources)
+    } else if crate::knowledge_base::is_knowledge_query(&command) && crate::knowledge_base::is_enabled(&app).await {
+        // "According to my documents ..." is answered from the knowledge base
+        routed(Route::Knowledge, &command);
+        let limit = crate::knowledge_base::top_k(&app).await;
+        passages = match crate::knowledge_base::search(&app, &command, limit).await {
+            Ok(passages) => passages,
+            Err(e) => return Ok(format!("I couldn't search your documents: {}", e)),
+        };
+        if passages.is_empty() {
+            return Ok("I didn't find anything about that in your documents.".to_string());
+        }
+        crate::knowledge_base::build_knowledge_prompt(&command, &passages)
+    } else {
+        routed(Route::Llm, &command);
+        open_ended = true;
+        // Closely matching passages from the knowledge base, cited like its answers
+        passages = crate::knowledge_base::relevant_context(&app, &command).await;
+        command.clone()
+    };
+    let context = (!passages.is_empty() && open_ended).then(|| crate::knowledge_base::build_context_note(&passages));
+    
     // For complex queries, route to LLM
-    let mut manager_guard = LLM_MANAGER.lock().await;
+    let now_playing = tokio::task::spawn_blocking(crate::media::now_playing_variable)
+        .await
+        .unwrap_or_else(|_| "nothing".to_string());
+    
+    let state = app.state::<AppState>();
+    let mut manager_guard = state.llm.lock().await;
     if let Some(llm_manager) = manager_guard.as_mut() {
-        match llm_manager.send_message(&command).await {
-            Ok(response) => Ok(response.content),
+        llm_manager.set_template_variable(crate::media::NOW_PLAYING_VARIABLE, now_playing);
+        llm_manager.set_template_variable(
+            crate::active_window::ACTIVE_WINDOW_VARIABLE,
+            crate::active_window::active_window_variable(),
+        );
+        llm_manager.set_template_variable(
+            crate::calendar::UPCOMING_EVENTS_VARIABLE,
+            crate::calendar::upcoming_events_variable(),
+        );
+        let result = if open_ended && llm_manager.tools_enabled() {
+            let turn = llm_manager.begin_tool_turn(&prompt, crate::mcp::llm_tools(&app).await, context.as_deref());
+            // Tools may need the LLM themselves (a routine's system report)
+            drop(manager_guard);
+            // The first call that changes something waits for the user's yes
+            let held: std::sync::Mutex<Option<ToolCall>> = std::sync::Mutex::new(None);
+            let result = crate::llm_provider::run_tools(turn, |call| {
+                let (app, held) = (&app, &held);
+                async move {
+                    if !crate::mcp::needs_confirmation(&call) {
+                        return crate::mcp::call_llm_tool(app, call).await;
+                    }
+                    let mut held = held.lock().unwrap_or_else(|e| e.into_inner());
+                    if held.is_some() {
+                        return Err("Not done: another action is already waiting for the user to confirm it".to_string());
+                    }
+                    *held = Some(call);
+                    Ok(HELD_TOOL_RESULT.to_string())
+                }
+            })
+            .await;
+            if let (Ok(response), Some(manager)) = (&result, state.llm.lock().await.as_mut()) {
+                manager.remember(&prompt, &response.content).await;
+            }
+            let held = held.into_inner().unwrap_or_else(|e| e.into_inner());
+            match (result, held) {
+                (Ok(mut response), Some(call)) => {
+                    let summary = crate::mcp::describe_call(&call);
+                    let question = ask_confirmation(origin, GatedAction::Tool { call, summary }).await;
+                    response.content = format!("{} {}", response.content.trim_end(), question).trim_start().to_string();
+                    Ok(response)
+                }
+                (result, _) => result,
+            }
+        } else {
+            llm_manager.send_message_with_context(&prompt, context.as_deref()).await
+        };
+        match result {
+            Ok(response) => {
+                let cited = crate::web_search::cited_sources(&response.content, &sources)
+                    + &crate::knowledge_base::cited_files(&response.content, &passages);
+                Ok(response.content + &cited)
+            }
             Err(e) => {
                 info!("LLM error: {}, falling back to basic response", e);
                 Ok(format!("I heard: {}. LLM is not available right now.", command))
@@ -110,94 +362,601 @@ pub async fn execute_command(command: String) -> Result<String, String> {
     }
 }
 
-// ===== LLM Commands =====
+/// Tell the pipeline bus which handler took `command`
+fn routed(route: Route, command: &str) {
+    crate::voice::bus::publish(PipelineEvent::IntentRouted {
+        route,
+        command: command.to_string(),
+    });
+}
 
-#[tauri::command]
-pub async fn send_llm_message(message: String) -> Result<LLMResponse, String> {
-    info!("Sending message to LLM: {}", message);
+/// Run a parsed intent
+async fn handle_intent(app: &AppHandle, origin: &str, intent: Intent) -> Result<String, String> {
+    if let Some(capability) = intent.capability() {
+        if let Err(refused) = crate::permissions::check(capability) {
+            return Ok(refused.message);
+        }
+    }
     
-    let mut manager_guard = LLM_MANAGER.lock().await;
+    match intent {
+        Intent::RunRoutine { id } => {
+            let mut automation = app.state::<AppState>().inner().automation.lock().await;
+            let name = automation
+                .get_all_routines()
+                .into_iter()
+                .find(|r| r.id == id)
+                .map(|r| r.name)
+                .unwrap_or_else(|| id.clone());
+            match automation.execute_routine(app, &id).await {
+                Ok(_) => Ok(format!("{} activated!", name)),
+                Err(e) => Ok(format!("Failed to start {}: {}", name.to_lowercase(), e)),
+            }
+        }
+        
+        // App name corrections ("when I say browser I mean Firefox")
+        Intent::LearnAlias { alias, app_name } => match crate::app_launcher::learn_alias(&alias, &app_name) {
+            Ok(app) => Ok(format!("Got it. When you say {}, I'll open {}.", alias, app.name)),
+            Err(e) => Ok(e),
+        },
+        
+        // Lists and quick notes ("add milk to my shopping list", "what's on my list?")
+        Intent::Notes(notes) => Ok(crate::notes::apply_command(app, notes)
+            .unwrap_or_else(|e| format!("I couldn't update your notes: {}", e))),
+
+        // Calendar ("what's on my calendar today?")
+        Intent::Calendar(query) => Ok(crate::calendar::answer(app, query).await),
+
+        // Math and unit conversions ("what's 15% of 84?")
+        Intent::Calculate(query) => Ok(crate::calculator::answer(query).await),
+
+        // Email ("do I have any new email?")
+        Intent::Email(command) => Ok(crate::email::answer(app, command).await),
+
+        // Smart home ("turn off the living room lights")
+        Intent::Home(command) => Ok(match command.confirmation() {
+            Some(question) => ask_confirmation(origin, GatedAction::Home { command, question }).await,
+            None => crate::home_assistant::handle(app, command).await,
+        }),
+        Intent::Hue(command) => Ok(crate::hue::handle(app, command).await),
+
+        // Websites ("search YouTube for lofi and play the first result")
+        Intent::WebAction(command) => Ok(crate::web_actions::run(app, command).await),
+        
+        // Now playing ("what song is this?")
+        Intent::NowPlaying => Ok(match tokio::task::spawn_blocking(crate::media::now_playing).await {
+            Ok(Ok(Some(track))) => track.describe(),
+            Ok(Ok(None)) => "Nothing is playing right now.".to_string(),
+            Ok(Err(e)) => format!("I couldn't tell what's playing: {}", e),
+            Err(e) => return Err(e.to_string()),
+        }),
+        
+        // System services ("restart the print spooler service")
+        Intent::Service(crate::services::ServiceCommand { action, name }) => {
+            Ok(match tokio::task::spawn_blocking(move || crate::services::control(&name, action)).await {
+                Ok(Ok(reply)) => reply,
+                Ok(Err(e)) => format!("I couldn't do that: {}", e),
+                Err(e) => return Err(e.to_string()),
+            })
+        }
+        
+        // Media playback ("pause the music", "next song")
+        Intent::Media(action) => match crate::system_integration::send_media_key(action) {
+            Ok(_) => Ok(action.describe().to_string()),
+            Err(e) => Ok(format!("I couldn't control playback: {}", e)),
+        },
+        
+        // Assistant microphone ("mute the mic")
+        Intent::MicMute { muted } => {
+            crate::voice::set_muted(muted);
+            Ok(if muted { "Microphone muted".to_string() } else { "Microphone unmuted".to_string() })
+        }
+        
+        // Power ("lock my computer", "sleep in 10 minutes"); shutdown and restart ask first
+        Intent::CancelPower => Ok(match crate::power::cancel_scheduled() {
+            Some(action) => format!("Okay, I won't {}", action.verb()),
+            None => "There's nothing scheduled to cancel.".to_string(),
+        }),
+        Intent::Power(power) => {
+            let action = GatedAction::Power(power);
+            if power.action.needs_confirmation() {
+                return Ok(ask_confirmation(origin, action).await);
+            }
+            Ok(action.run(app).await)
+        }
+        
+        // Wi-Fi profiles ("switch to my hotspot", "list wifi networks")
+        Intent::Wifi(wifi) => Ok(match tokio::task::spawn_blocking(move || crate::network::apply_wifi_command(wifi)).await {
+            Ok(Ok(reply)) => reply,
+            Ok(Err(e)) => format!("I couldn't change the Wi-Fi network: {}", e),
+            Err(e) => return Err(e.to_string()),
+        }),
+        
+        // Network ("am I on VPN?", "what's my IP")
+        Intent::NetworkStatus => Ok(match crate::network::full_status().await {
+            Ok(status) => status.describe(),
+            Err(e) => format!("I couldn't check the network: {}", e),
+        }),
+        
+        // Health report ("how's my system doing?")
+        Intent::SystemReport => crate::system_report::system_report(app).await,
+        
+        // Uptime ("how long has my PC been on?")
+        Intent::Uptime => tokio::task::spawn_blocking(|| crate::system_monitor::boot_info().describe())
+            .await
+            .map_err(|e| e.to_string()),
+        
+        // Heavy processes ("what's slowing down my PC?", "what's using my memory")
+        Intent::TopProcesses(by) => tokio::task::spawn_blocking(move || crate::processes::top_processes_report(by))
+            .await
+            .map_err(|e| e.to_string()),
+        
+        // Closing apps ("kill Chrome"); asks first unless the app is allowlisted
+        Intent::CloseApp { name, force } => {
+            let query = name.clone();
+            let matches = tokio::task::spawn_blocking(move || crate::processes::find_by_name(&query))
+                .await
+                .map_err(|e| e.to_string())?;
+            
+            if matches.is_empty() {
+                return Ok(format!("{} isn't running.", name));
+            }
+            if matches.iter().any(|p| crate::processes::is_protected(&p.name)) {
+                return Ok(format!("{} is a system process, so I won't close it.", name));
+            }
+            
+            let allowlisted = crate::settings::load_settings(app.clone())
+                .await
+                .map(|s| s.commands.process_kill_allowlist.iter().any(|a| a.eq_ignore_ascii_case(&name)))
+                .unwrap_or(false);
+            let action = GatedAction::CloseApp { name, force, count: matches.len() };
+            
+            Ok(if allowlisted { action.run(app).await } else { ask_confirmation(origin, action).await })
+        }
+        
+        // Battery wear ("how's my battery health")
+        Intent::BatteryHealth => Ok(match tokio::task::spawn_blocking(crate::power::battery_health).await {
+            Ok(Ok(health)) => health.describe(),
+            Ok(Err(e)) => format!("I couldn't read the battery health: {}", e),
+            Err(e) => return Err(e.to_string()),
+        }),
+        
+        // Battery ("how much battery is left")
+        Intent::Battery => Ok(match tokio::task::spawn_blocking(crate::power::battery_status).await {
+            Ok(Ok(status)) => status.describe(),
+            Ok(Err(e)) => format!("I couldn't read the battery: {}", e),
+            Err(e) => return Err(e.to_string()),
+        }),
+        
+        // Power plan ("switch to high performance")
+        Intent::PowerPlan(kind) => Ok(match tokio::task::spawn_blocking(move || crate::power::set_power_plan(kind)).await {
+            Ok(Ok(plan)) => format!("Switched to the {} power plan", plan.name),
+            Ok(Err(e)) => format!("I couldn't change the power plan: {}", e),
+            Err(e) => return Err(e.to_string()),
+        }),
+        
+        // Window placement ("put the browser on my second monitor")
+        Intent::PlaceWindow(placement) => {
+            let reply = placement.describe();
+            let crate::window_layout::PlacementCommand { app_name, monitor, position } = placement;
+            Ok(match tokio::task::spawn_blocking(move || crate::window_layout::place_window(&app_name, monitor, position)).await {
+                Ok(Ok(())) => reply,
+                Ok(Err(e)) => format!("I couldn't move that window: {}", e),
+                Err(e) => return Err(e.to_string()),
+            })
+        }
+        
+        // Recycle bin and disk cleanup ("empty the recycle bin", "free up some space")
+        Intent::Cleanup(cleanup) => {
+            use crate::cleanup::CleanupCommand;
+            
+            Ok(match cleanup {
+                CleanupCommand::RecycleBinSize => match tokio::task::spawn_blocking(crate::cleanup::recycle_bin_info).await {
+                    Ok(Ok(info)) if info.items == 0 => "The recycle bin is empty.".to_string(),
+                    Ok(Ok(info)) => format!(
+                        "The recycle bin has {} items taking up {}",
+                        info.items,
+                        crate::file_search::format_size(info.bytes)
+                    ),
+                    Ok(Err(e)) => format!("I couldn't check the recycle bin: {}", e),
+                    Err(e) => return Err(e.to_string()),
+                },
+                CleanupCommand::EmptyRecycleBin => match tokio::task::spawn_blocking(crate::cleanup::recycle_bin_info).await {
+                    Ok(Ok(info)) if info.items == 0 => "The recycle bin is already empty.".to_string(),
+                    Ok(Ok(info)) => ask_confirmation(origin, GatedAction::EmptyRecycleBin { bytes: info.bytes }).await,
+                    Ok(Err(e)) => format!("I couldn't check the recycle bin: {}", e),
+                    Err(e) => return Err(e.to_string()),
+                },
+                CleanupCommand::FreeSpace => match tokio::task::spawn_blocking(|| crate::cleanup::cleanup(true, true)).await {
+                    Ok(Ok(report)) if report.total_bytes == 0 => report.describe(),
+                    Ok(Ok(report)) => ask_confirmation(origin, GatedAction::Cleanup { summary: report.describe() }).await,
+                    Ok(Err(e)) => format!("I couldn't check for files to clean up: {}", e),
+                    Err(e) => return Err(e.to_string()),
+                },
+            })
+        }
+        
+        // Focus Assist ("turn on do not disturb", "is focus assist on?")
+        Intent::FocusAssist(focus) => Ok(match tokio::task::spawn_blocking(move || crate::focus_assist::apply_command(focus)).await {
+            Ok(Ok(reply)) => reply,
+            Ok(Err(e)) => format!("I couldn't change Focus Assist: {}", e),
+            Err(e) => return Err(e.to_string()),
+        }),
+        
+        // Screen brightness ("brightness to 40", "dim the screen")
+        Intent::Brightness(brightness) => {
+            Ok(match tokio::task::spawn_blocking(move || crate::display::apply_brightness_command(brightness)).await {
+                Ok(Ok(reply)) => reply,
+                Ok(Err(e)) => format!("I couldn't change the brightness: {}", e),
+                Err(e) => return Err(e.to_string()),
+            })
+        }
+        
+        // Dark mode / night light ("turn on dark mode", "toggle night light")
+        Intent::Appearance(appearance) => {
+            Ok(match tokio::task::spawn_blocking(move || crate::appearance::apply_command(appearance)).await {
+                Ok(Ok((reply, theme))) => {
+                    if let Some(mode) = theme {
+                        if let Err(e) = crate::appearance::sync_app_theme(app, mode).await {
+                            warn!("Failed to sync ASTRAL theme: {}", e);
+                        }
+                    }
+                    reply
+                }
+                Ok(Err(e)) => format!("I couldn't change that: {}", e),
+                Err(e) => return Err(e.to_string()),
+            })
+        }
+        
+        // Speaker volume ("volume up by 10", "set volume to 40", "mute")
+        Intent::Volume(volume) => match crate::system_integration::apply_volume_command(volume) {
+            Ok(reply) => Ok(reply),
+            Err(e) => Ok(format!("I couldn't change the volume: {}", e)),
+        },
+        
+        // File search ("find my resume pdf")
+        Intent::FileSearch(query) => {
+            let results = tokio::task::spawn_blocking(move || crate::file_search::search(&query))
+                .await
+                .map_err(|e| e.to_string())?;
+            
+            crate::file_search::remember_results(&results);
+            Ok(match results.first() {
+                None => "I couldn't find any matching files.".to_string(),
+                Some(best) if results.len() == 1 => {
+                    format!("I found {} ({}) in {}", best.name, crate::file_search::format_size(best.size), best.path)
+                }
+                Some(best) => format!(
+                    "I found {} matches. The best one is {} ({}) in {}",
+                    results.len(),
+                    best.name,
+                    crate::file_search::format_size(best.size),
+                    best.path
+                ),
+            })
+        }
+        
+        // Opening an earlier search result ("open the second one")
+        Intent::OpenSearchResult { index } => {
+            let Some(result) = crate::file_search::last_result(index) else {
+                return Ok("I don't have a search result to open.".to_string());
+            };
+            match crate::system_integration::open_path(&result.path, false) {
+                Ok(_) => Ok(format!("Opening {}", result.name)),
+                Err(e) => Ok(format!("I couldn't open {}: {}", result.name, e)),
+            }
+        }
+        
+        // Apps ("open spotify")
+        Intent::LaunchApp { app_name } => {
+            let target = app_name.clone();
+            Ok(match tokio::task::spawn_blocking(move || crate::app_launcher::launch_app(&target)).await {
+                Ok(Ok(result)) if result.success => format!("Opening {}", result.app_name),
+                Ok(Ok(_)) => format!("I couldn't find or launch {}", app_name),
+                Ok(Err(e)) => format!("I couldn't launch {}: {}", app_name, e),
+                Err(e) => return Err(e.to_string()),
+            })
+        }
+    }
+}
+
+enum Clarification {
+    Proceed(String),
+    Respond(String),
+    Confirmed(GatedAction),
+}
+
+/// Handle answers to a pending clarifying question and hold back
+/// low-confidence transcripts
+async fn resolve_clarification(
+    app: &tauri::AppHandle,
+    origin: &str,
+    command: String,
+    confidence: Option<f32>,
+) -> Clarification {
+    let lower = command.trim().to_lowercase();
+    let lower = lower.trim_end_matches(|c: char| c.is_ascii_punctuation());
     
-    if manager_guard.is_none() {
-        *manager_guard = Some(LLMManager::new(LLMConfig::default()));
+    let pending = PENDING_QUESTIONS.lock().await.remove(origin);
+    match pending {
+        Some(PendingQuestion::Transcript(pending)) => {
+            if is_affirmative(lower) {
+                debug!("Clarification confirmed: {}", pending);
+                return Clarification::Proceed(pending);
+            }
+            if is_negative(lower) {
+                return Clarification::Respond("Sorry about that. Could you say it again?".to_string());
+            }
+        }
+        Some(PendingQuestion::Action(action)) => {
+            if is_affirmative(lower) {
+                debug!("Action confirmed: {:?}", action);
+                return Clarification::Confirmed(action);
+            }
+            if is_negative(lower) {
+                return Clarification::Respond("Okay, I won't.".to_string());
+            }
+        }
+        // Anything else is a new command; the pending question is dropped
+        None => {}
     }
     
-    let manager = manager_guard.as_mut().unwrap();
+    let Some(confidence) = confidence else {
+        return Clarification::Proceed(command);
+    };
     
-    manager.send_message(&message)
+    let threshold = crate::settings::load_settings(app.clone())
         .await
-        .map_err(|e| e.to_string())
+        .map(|s| s.commands.stt_confidence_threshold)
+        .unwrap_or_else(|_| crate::settings::CommandSettings::default().stt_confidence_threshold);
+    
+    if confidence < threshold {
+        info!("Low confidence transcript ({:.2} < {:.2}), asking to confirm", confidence, threshold);
+        let question = format!("Did you say \"{}\"?", command.trim());
+        PENDING_QUESTIONS.lock().await.insert(origin.to_string(), PendingQuestion::Transcript(command));
+        return Clarification::Respond(question);
+    }
+    
+    Clarification::Proceed(command)
 }
 
-#[tauri::command]
-pub fn get_llm_config() -> Result<LLMConfig, String> {
-    // Return current config or default
-    Ok(LLMConfig::default())
+fn is_affirmative(text: &str) -> bool {
+    matches!(text, "yes" | "yeah" | "yep" | "yup" | "correct" | "right" | "sure" | "yes please" | "that's right" | "do it")
+}
+
+fn is_negative(text: &str) -> bool {
+    matches!(text, "no" | "nope" | "nah" | "wrong" | "no thanks" | "cancel" | "never mind")
 }
 
+// ===== LLM Commands =====
+
+/// `bypass_cache` asks the provider even when the response cache has a fresh reply
 #[tauri::command]
-pub async fn update_llm_config(config: LLMConfig) -> Result<String, String> {
-    info!("Updating LLM config: {:?}", config.provider);
-    
-    let mut manager_guard = LLM_MANAGER.lock().await;
-    
-    if let Some(manager) = manager_guard.as_mut() {
-        manager.update_config(config.clone());
+pub async fn send_llm_message(
+    state: State<'_, AppState>,
+    message: String,
+    bypass_cache: Option<bool>,
+) -> AstralResult<LLMResponse> {
+    debug!("Sending message to LLM: {}", message);
+    if bypass_cache.unwrap_or(false) {
+        state.send_llm_message_uncached(&message).await
     } else {
-        *manager_guard = Some(LLMManager::new(config));
+        state.send_llm_message(&message).await
     }
-    
+}
+
+/// Send a chat message with images for a vision model (GPT-4o, Claude,
+/// llava): the files in `image_paths`, plus a screenshot when `screenshot` is set
+#[tauri::command]
+pub async fn send_llm_message_with_images(
+    state: State<'_, AppState>,
+    message: String,
+    image_paths: Vec<String>,
+    screenshot: bool,
+) -> AstralResult<LLMResponse> {
+    info!("Sending message to LLM with {} images{}", image_paths.len(), if screenshot { " and a screenshot" } else { "" });
+
+    let images = tokio::task::spawn_blocking(move || {
+        let mut images = image_paths
+            .iter()
+            .map(|path| Image::from_path(std::path::Path::new(path.trim())))
+            .collect::<AstralResult<Vec<_>>>()?;
+        if screenshot {
+            let png = crate::ocr::capture_png().map_err(|e| AstralError::new(ErrorCode::Internal, e))?;
+            images.push(Image::from_screenshot(&png)?);
+        }
+        Ok::<_, AstralError>(images)
+    })
+    .await??;
+    if images.is_empty() {
+        return Err(AstralError::invalid_input("Attach an image or a screenshot"));
+    }
+    let message = if message.trim().is_empty() { "What's in this image?" } else { message.trim() };
+
+    state.send_llm_message_with_images(message, images).await
+}
+
+/// Stop the LLM request in progress, if any; the command waiting on it fails
+/// with a `cancelled` error
+#[tauri::command]
+pub async fn cancel_llm_request() -> AstralResult<bool> {
+    Ok(crate::llm_provider::cancel_requests())
+}
+
+/// Summarize a dropped or selected file, or answer `question` about it
+#[tauri::command]
+pub async fn summarize_file(state: State<'_, AppState>, path: String, question: Option<String>) -> AstralResult<String> {
+    info!("Summarizing file: {}", path);
+
+    let document = tokio::task::spawn_blocking(move || crate::documents::extract_text(std::path::Path::new(&path)))
+        .await?
+        .map_err(|e| AstralError::invalid_input(e.to_string()))?;
+    let question = question.unwrap_or_else(|| "What's in this document? Summarize it.".to_string());
+    let prompt = crate::documents::build_document_prompt(&question, &document);
+
+    state.send_llm_message(&prompt).await.map(|response| response.content)
+}
+
+#[tauri::command]
+pub async fn get_llm_config(app: AppHandle) -> AstralResult<LLMConfig> {
+    Ok(crate::settings::load_settings(app).await?.llm)
+}
+
+/// Saved to the `llm` settings section, which reconfigures the manager
+#[tauri::command]
+pub async fn update_llm_config(app: AppHandle, config: LLMConfig) -> AstralResult<String> {
+    info!("Updating LLM config: {:?}", config.provider);
+    crate::settings::modify_settings(&app, |settings| {
+        settings.llm = config;
+        Ok::<_, AstralError>(())
+    })
+    .await?;
     Ok("LLM configuration updated".to_string())
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemPrompt {
+    /// The template in use, `{{name}}` placeholders and all
+    pub prompt: String,
+    /// False while the built-in prompt is in use
+    pub custom: bool,
+    pub default_prompt: String,
+}
+
+fn system_prompt_of(config: &LLMConfig) -> SystemPrompt {
+    let custom = !config.system_prompt.trim().is_empty();
+    SystemPrompt {
+        prompt: if custom { config.system_prompt.clone() } else { DEFAULT_SYSTEM_PROMPT.to_string() },
+        custom,
+        default_prompt: DEFAULT_SYSTEM_PROMPT.to_string(),
+    }
+}
+
 #[tauri::command]
-pub async fn test_llm_connection(config: LLMConfig) -> Result<bool, String> {
-    crate::llm_provider::test_connection(&config)
-        .await
-        .map_err(|e| e.to_string())
+pub async fn get_system_prompt(app: AppHandle) -> AstralResult<SystemPrompt> {
+    Ok(system_prompt_of(&crate::settings::load_settings(app).await?.llm))
+}
+
+/// Save a custom system prompt for every provider; an empty one, or the
+/// built-in text, goes back to the default
+#[tauri::command]
+pub async fn set_system_prompt(app: AppHandle, prompt: String) -> AstralResult<SystemPrompt> {
+    let prompt = prompt.trim();
+    let updated = crate::settings::modify_settings(&app, |settings| {
+        settings.llm.system_prompt = if prompt == DEFAULT_SYSTEM_PROMPT { String::new() } else { prompt.to_string() };
+        Ok::<_, AstralError>(system_prompt_of(&settings.llm))
+    })
+    .await?;
+    info!("System prompt {}", if updated.custom { "customized" } else { "reset to the default" });
+    Ok(updated)
+}
+
+#[tauri::command]
+pub async fn reset_system_prompt(app: AppHandle) -> AstralResult<SystemPrompt> {
+    set_system_prompt(app, String::new()).await
+}
+
+#[tauri::command]
+pub async fn test_llm_connection(config: LLMConfig) -> AstralResult<bool> {
+    Ok(crate::llm_provider::test_connection(&config).await?)
 }
 
 // ===== Automation Commands =====
 
 #[tauri::command]
-pub async fn get_automation_routines() -> Result<Vec<AutomationRoutine>, String> {
-    let manager = AUTOMATION_MANAGER.lock().await;
+pub async fn get_automation_routines(state: State<'_, AppState>) -> Result<Vec<AutomationRoutine>, String> {
+    let manager = state.automation.lock().await;
     Ok(manager.get_all_routines())
 }
 
 #[tauri::command]
-pub async fn execute_automation(routine_id: String) -> Result<AutomationResult, String> {
+pub async fn execute_automation(app: AppHandle, routine_id: String) -> Result<AutomationResult, String> {
     info!("Executing automation: {}", routine_id);
     
-    let mut manager = AUTOMATION_MANAGER.lock().await;
-    manager.execute_routine(&routine_id)
+    let mut manager = app.state::<AppState>().inner().automation.lock().await;
+    manager.execute_routine(&app, &routine_id)
         .await
         .map_err(|e| e.to_string())
 }
 
+/// Run every enabled routine triggered by `SystemEvent { event_type }`
+pub async fn fire_system_event(app: &AppHandle, event_type: &str) {
+    let mut manager = app.state::<AppState>().inner().automation.lock().await;
+    for id in manager.routines_for_event(event_type) {
+        info!("System event '{}' triggered routine {}", event_type, id);
+        if let Err(e) = manager.execute_routine(app, &id).await {
+            warn!("Routine {} failed: {}", id, e);
+        }
+    }
+}
+
 #[tauri::command]
-pub async fn toggle_automation(routine_id: String) -> Result<bool, String> {
+pub async fn toggle_automation(state: State<'_, AppState>, routine_id: String) -> Result<bool, String> {
     info!("Toggling automation: {}", routine_id);
     
-    let mut manager = AUTOMATION_MANAGER.lock().await;
+    let mut manager = state.automation.lock().await;
     manager.toggle_routine(&routine_id)
         .map_err(|e| e.to_string())
 }
 
+// ===== Volume Commands =====
+
+#[tauri::command]
+pub async fn get_volume() -> Result<u8, String> {
+    tokio::task::spawn_blocking(crate::system_integration::get_master_volume)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_volume(level: u8) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || crate::system_integration::set_master_volume(level.min(100)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Relative change, e.g. +10 / -10. Returns the new level.
+#[tauri::command]
+pub async fn change_volume(delta: i32) -> Result<u8, String> {
+    tokio::task::spawn_blocking(move || crate::system_integration::adjust_master_volume(delta))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_volume_mute() -> Result<bool, String> {
+    tokio::task::spawn_blocking(crate::system_integration::get_master_mute)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_volume_mute(muted: bool) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || crate::system_integration::set_master_mute(muted))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+// ===== Shell Commands =====
+
+/// Open a file, folder or http(s) URL with its default handler. Executables
+/// are refused unless `allow_executables` is set.
+#[tauri::command]
+pub async fn open_path(path: String, allow_executables: Option<bool>) -> Result<crate::system_integration::OpenedKind, String> {
+    crate::system_integration::open_path(&path, allow_executables.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
 // ===== Audio Commands =====
 
 #[tauri::command]
 pub async fn trigger_wake_word() -> Result<String, String> {
     info!("Manually triggering wake word");
     
-    let engine_guard = AUDIO_ENGINE.lock().await;
-    
-    if let Some(engine) = engine_guard.as_ref() {
-        engine.trigger_wake_word()
-            .await
-            .map_err(|e| e.to_string())?;
-        Ok("Wake word triggered".to_string())
-    } else {
-        Ok("Audio engine not initialized".to_string())
-    }
+    crate::voice::wake_word::on_wake_word_detected()?;
+    Ok("Wake word triggered".to_string())
 }
@@ -0,0 +1,244 @@
+// Crash Reports Module
+// A panic hook that writes a crash report (message, location, backtrace and
+// the recent log) to <data dir>/com.astral.app/crashes before the default
+// hook runs, so a panic on a voice thread leaves a trace instead of silently
+// killing the pipeline. Users can also bundle an error report by hand when an
+// integration misbehaves. Nothing leaves the machine unless the user has
+// turned on `diagnostics.crash_reports_consent` and submits a report themselves.
+
+use crate::logging::LogEntry;
+use serde::{Deserialize, Serialize};
+use std::backtrace::Backtrace;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tracing::{error, info, warn};
+
+pub const CRASH_REPORTS_PENDING_EVENT: &str = "crash-reports-pending";
+
+/// Log entries bundled with each report
+const LOG_SNAPSHOT_SIZE: usize = 300;
+/// Oldest reports beyond this many are deleted
+const MAX_REPORTS: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    Panic,
+    /// Created by the user from the diagnostics panel
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub kind: ReportKind,
+    pub timestamp: String,
+    pub app_version: String,
+    pub os: String,
+    pub thread: Option<String>,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: Option<String>,
+    pub logs: Vec<LogEntry>,
+    pub submitted: bool,
+}
+
+/// What the UI lists; the full report is only read when submitting
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub kind: ReportKind,
+    pub timestamp: String,
+    pub message: String,
+    pub submitted: bool,
+}
+
+fn reports_dir() -> Option<PathBuf> {
+    let mut path = dirs::data_dir()?;
+    path.push("com.astral.app");
+    path.push("crashes");
+    Some(path)
+}
+
+fn report_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid report id '{}'", id));
+    }
+    let dir = reports_dir().ok_or_else(|| "No data directory on this system".to_string())?;
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+fn new_report(kind: ReportKind, message: String) -> CrashReport {
+    let now = chrono::Local::now();
+    let prefix = match kind {
+        ReportKind::Panic => "crash",
+        ReportKind::Manual => "report",
+    };
+    CrashReport {
+        id: format!("{}-{}", prefix, now.format("%Y%m%d-%H%M%S-%3f")),
+        kind,
+        timestamp: now.to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        thread: std::thread::current().name().map(str::to_string),
+        message,
+        location: None,
+        backtrace: None,
+        logs: crate::logging::snapshot(LOG_SNAPSHOT_SIZE),
+        submitted: false,
+    }
+}
+
+fn write_report(report: &CrashReport) -> Result<PathBuf, String> {
+    let path = report_path(&report.id)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+fn read_reports() -> Vec<CrashReport> {
+    let Some(dir) = reports_dir() else {
+        return Vec::new();
+    };
+    let mut reports: Vec<CrashReport> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    reports
+}
+
+fn prune_reports() {
+    for report in read_reports().iter().skip(MAX_REPORTS) {
+        if let Ok(path) = report_path(&report.id) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Write a crash report for every panic, then hand over to the default hook
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let payload = panic_info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "Unknown panic".to_string());
+
+        let mut report = new_report(ReportKind::Panic, message);
+        report.location = panic_info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+        report.backtrace = Some(Backtrace::force_capture().to_string());
+
+        error!(
+            "Panic on thread {}: {} at {}",
+            report.thread.as_deref().unwrap_or("unnamed"),
+            report.message,
+            report.location.as_deref().unwrap_or("unknown location")
+        );
+        match write_report(&report) {
+            Ok(path) => error!("Crash report written to {:?}", path),
+            Err(e) => error!("Failed to write crash report: {}", e),
+        }
+
+        default_hook(panic_info);
+    }));
+}
+
+/// Tell the UI about reports from earlier runs that haven't been submitted
+pub fn init(app: AppHandle) {
+    std::thread::spawn(move || {
+        prune_reports();
+        let pending = read_reports().iter().filter(|r| !r.submitted && matches!(r.kind, ReportKind::Panic)).count();
+        if pending > 0 {
+            info!("{} unsent crash report(s) from earlier runs", pending);
+            if let Err(e) = app.emit(CRASH_REPORTS_PENDING_EVENT, pending) {
+                warn!("Failed to emit crash report event: {}", e);
+            }
+            let request = crate::notifications::NotificationRequest {
+                title: "ASTRAL crashed last time".to_string(),
+                message: "A crash report was saved. You can review or share it under Settings → Diagnostics.".to_string(),
+                actions: Vec::new(),
+                routine_id: None,
+                snooze_minutes: None,
+            };
+            if let Err(e) = crate::notifications::send(request) {
+                warn!("Failed to notify about crash reports: {}", e);
+            }
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_crash_reports() -> Result<Vec<CrashReportSummary>, String> {
+    let reports = tokio::task::spawn_blocking(read_reports).await.map_err(|e| e.to_string())?;
+    Ok(reports
+        .into_iter()
+        .map(|r| CrashReportSummary {
+            id: r.id,
+            kind: r.kind,
+            timestamp: r.timestamp,
+            message: r.message,
+            submitted: r.submitted,
+        })
+        .collect())
+}
+
+/// Bundle the recent log with a note from the user, e.g. "Whisper stopped responding"
+#[tauri::command]
+pub async fn create_error_report(note: String) -> Result<String, String> {
+    let note = note.trim();
+    let report = new_report(ReportKind::Manual, if note.is_empty() { "Error report".to_string() } else { note.to_string() });
+    let path = write_report(&report)?;
+    info!("Error report written to {:?}", path);
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn delete_crash_report(id: String) -> Result<(), String> {
+    std::fs::remove_file(report_path(&id)?).map_err(|e| e.to_string())
+}
+
+/// Send a report to `diagnostics.crash_report_endpoint`; refused without the user's consent
+#[tauri::command]
+pub async fn submit_crash_report(app: AppHandle, id: String) -> Result<(), String> {
+    let settings = crate::settings::load_settings(app).await?;
+    if !settings.diagnostics.crash_reports_consent {
+        return Err("Turn on crash report sharing in settings first".to_string());
+    }
+    let endpoint = settings.diagnostics.crash_report_endpoint.trim();
+    if endpoint.is_empty() {
+        return Err("No crash report endpoint is configured".to_string());
+    }
+
+    let path = report_path(&id)?;
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Couldn't read report {}: {}", id, e))?;
+    let mut report: CrashReport = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+    let client = crate::proxy::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = crate::privacy::send(client.post(endpoint).json(&report))
+        .await
+        .map_err(|e| format!("Couldn't send the report: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("The report server answered {}", response.status()));
+    }
+
+    report.submitted = true;
+    write_report(&report)?;
+    info!("Crash report {} submitted", id);
+    Ok(())
+}
@@ -0,0 +1,260 @@
+// Dictation Module
+// Hotkey-driven dictation: the frontend records and transcribes while
+// dictation is on, then hands the text back to be typed into whatever app
+// has focus. Spoken punctuation ("comma", "new line") is applied first.
+
+use anyhow::Result;
+use tracing::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter};
+
+pub const DICTATION_TOGGLED_EVENT: &str = "dictation-toggled";
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Spoken commands and what they type. Longest phrases first so "new paragraph"
+/// wins over "new". "dash" and "hyphen" type the same character; `format_dictation`
+/// spaces a dash out ("wait - no") and joins words with a hyphen ("well-known").
+const PUNCTUATION: &[(&str, &str)] = &[
+    ("new paragraph", "\n\n"),
+    ("new line", "\n"),
+    ("exclamation point", "!"),
+    ("exclamation mark", "!"),
+    ("question mark", "?"),
+    ("full stop", "."),
+    ("open quote", "\""),
+    ("close quote", "\""),
+    ("open paren", "("),
+    ("close paren", ")"),
+    ("semicolon", ";"),
+    ("period", "."),
+    ("comma", ","),
+    ("colon", ":"),
+    ("hyphen", "-"),
+    ("dash", "-"),
+];
+
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Turn dictation on or off and tell the frontend to start or stop recording
+pub fn set_active(app: &AppHandle, active: bool) {
+    if ACTIVE.swap(active, Ordering::SeqCst) == active {
+        return;
+    }
+    info!("Dictation {}", if active { "started" } else { "stopped" });
+    if let Err(e) = app.emit(DICTATION_TOGGLED_EVENT, active) {
+        warn!("Failed to emit {}: {}", DICTATION_TOGGLED_EVENT, e);
+    }
+}
+
+pub fn toggle(app: &AppHandle) -> bool {
+    let active = !is_active();
+    set_active(app, active);
+    active
+}
+
+/// Whether `text` opens with no space: punctuation that hugs the previous word
+fn attaches_left(text: &str) -> bool {
+    matches!(text.chars().next(), Some(',' | '.' | '?' | '!' | ':' | ';' | ')' | '\n' | '-'))
+}
+
+/// Apply spoken punctuation and sentence capitalization to a transcript
+pub fn format_dictation(transcript: &str) -> String {
+    let words: Vec<&str> = transcript.split_whitespace().collect();
+    let mut output = String::new();
+    let mut capitalize = true;
+    // After a hyphen the next word follows without a space
+    let mut joined = false;
+    let mut i = 0;
+
+    while i < words.len() {
+        // STT often punctuates the command word itself ("comma.")
+        let command = PUNCTUATION.iter().find(|(phrase, _)| {
+            let parts: Vec<&str> = phrase.split(' ').collect();
+            parts.len() <= words.len() - i
+                && parts.iter().zip(&words[i..]).all(|(p, w)| {
+                    w.trim_matches(|c: char| c.is_ascii_punctuation()).eq_ignore_ascii_case(p)
+                })
+        });
+
+        let (piece, consumed) = match command {
+            Some((phrase, symbol)) => (symbol.to_string(), phrase.split(' ').count()),
+            None => (words[i].to_string(), 1),
+        };
+        i += consumed;
+        let phrase = command.map(|(phrase, _)| *phrase);
+
+        let attaches = attaches_left(&piece) && phrase != Some("dash");
+        if !output.is_empty() && !output.ends_with('\n') && !output.ends_with(['(', '"']) && !attaches && !joined {
+            output.push(' ');
+        }
+        joined = phrase == Some("hyphen");
+
+        if capitalize && command.is_none() {
+            let mut chars = piece.chars();
+            if let Some(first) = chars.next() {
+                output.extend(first.to_uppercase());
+                output.push_str(chars.as_str());
+            }
+            capitalize = false;
+        } else {
+            output.push_str(&piece);
+        }
+
+        if piece.ends_with(['.', '?', '!', '\n']) {
+            capitalize = true;
+        }
+    }
+
+    output
+}
+
+/// Type `text` into the focused app. Blocking.
+pub fn type_text(text: &str) -> Result<()> {
+    platform::type_text(text)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use anyhow::{bail, Result};
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
+        KEYEVENTF_UNICODE, VIRTUAL_KEY, VK_RETURN,
+    };
+
+    fn key(vk: VIRTUAL_KEY, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: vk,
+                    wScan: scan,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        }
+    }
+
+    pub fn type_text(text: &str) -> Result<()> {
+        let mut inputs = Vec::new();
+        for unit in text.encode_utf16() {
+            // Apps treat a Unicode "\n" inconsistently; Enter works everywhere
+            if unit == u16::from(b'\n') {
+                inputs.push(key(VK_RETURN, 0, KEYBD_EVENT_FLAGS(0)));
+                inputs.push(key(VK_RETURN, 0, KEYEVENTF_KEYUP));
+            } else {
+                inputs.push(key(VIRTUAL_KEY(0), unit, KEYEVENTF_UNICODE));
+                inputs.push(key(VIRTUAL_KEY(0), unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP));
+            }
+        }
+
+        let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize != inputs.len() {
+            bail!("Only {} of {} key events were accepted", sent, inputs.len());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    // Needs the Accessibility permission for System Events
+    pub fn type_text(text: &str) -> Result<()> {
+        let mut script = String::from("tell application \"System Events\"\n");
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                // Return
+                script.push_str("key code 36\n");
+            }
+            if !line.is_empty() {
+                let escaped = line.replace('\\', "\\\\").replace('"', "\\\"");
+                script.push_str(&format!("keystroke \"{}\"\n", escaped));
+            }
+        }
+        script.push_str("end tell");
+
+        let status = Command::new("osascript").args(["-e", &script]).status()?;
+        if !status.success() {
+            bail!("osascript exited with {}", status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    // wtype on Wayland, xdotool on X11
+    pub fn type_text(text: &str) -> Result<()> {
+        let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+        let status = if wayland {
+            Command::new("wtype").args(["--", text]).status()
+        } else {
+            Command::new("xdotool").args(["type", "--delay", "0", "--", text]).status()
+        };
+
+        match status {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => bail!("Typing failed ({})", status),
+            Err(_) => bail!("Install {} to use dictation", if wayland { "wtype" } else { "xdotool" }),
+        }
+    }
+}
+
+// ========== Tauri Commands ==========
+
+/// Start or stop dictation, returning whether it's now on
+#[tauri::command]
+pub async fn toggle_dictation(app: AppHandle) -> Result<bool, String> {
+    Ok(toggle(&app))
+}
+
+#[tauri::command]
+pub async fn is_dictation_active() -> Result<bool, String> {
+    Ok(is_active())
+}
+
+/// Format a dictated transcript and type it into the focused app. Returns what was typed.
+#[tauri::command]
+pub async fn type_dictation(text: String) -> Result<String, String> {
+    crate::permissions::check(crate::permissions::Capability::KeyboardInjection)?;
+    let formatted = format_dictation(&text);
+    if formatted.is_empty() {
+        return Ok(formatted);
+    }
+
+    // Continue the previous chunk instead of gluing words together
+    let typed = if attaches_left(&formatted) { formatted } else { format!("{} ", formatted) };
+    let to_type = typed.clone();
+    tokio::task::spawn_blocking(move || type_text(&to_type))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(typed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spoken_punctuation() {
+        assert_eq!(format_dictation("hello comma world period how are you question mark"), "Hello, world. How are you?");
+        assert_eq!(format_dictation("first new line second"), "First\nSecond");
+    }
+
+    #[test]
+    fn dash_is_spaced_and_hyphen_joins() {
+        assert_eq!(format_dictation("wait dash no"), "Wait - no");
+        assert_eq!(format_dictation("a well hyphen known trick"), "A well-known trick");
+    }
+}
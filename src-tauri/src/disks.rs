@@ -0,0 +1,293 @@
+// Disks Module
+// Per-volume capacity and free space, basic SMART health of the physical
+// drives, and an optional spoken warning when a drive runs low on space.
+
+use anyhow::Result;
+use tracing::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::{DiskKind, Disks};
+use tauri::AppHandle;
+
+const GB: u64 = 1024 * 1024 * 1024;
+const LOW_SPACE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// A drive has to recover this far above the threshold before it can warn again
+const LOW_SPACE_HYSTERESIS_GB: u64 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskStats {
+    /// "C:" on Windows, the mount point elsewhere
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    /// "ssd", "hdd" or "unknown"
+    pub kind: String,
+    pub removable: bool,
+    pub total: u64,
+    pub used: u64,
+    pub free: u64,
+}
+
+/// SMART summary for one physical drive; fields are None when the drive or tool doesn't report them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiskHealth {
+    pub device: String,
+    pub model: String,
+    /// Overall SMART verdict
+    pub healthy: Option<bool>,
+    pub temperature_c: Option<f32>,
+    /// SMART attribute 5; not applicable to NVMe drives
+    pub reallocated_sectors: Option<u64>,
+}
+
+/// Mounted volumes, largest first
+pub fn disk_stats() -> Vec<DiskStats> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut stats: Vec<DiskStats> = disks
+        .iter()
+        .filter(|d| d.total_space() > 0)
+        .map(|d| {
+            let mount_point = d.mount_point().to_string_lossy().to_string();
+            let name = if cfg!(target_os = "windows") {
+                mount_point.trim_end_matches('\\').to_string()
+            } else {
+                mount_point.clone()
+            };
+            DiskStats {
+                name,
+                mount_point,
+                file_system: d.file_system().to_string_lossy().to_string(),
+                kind: match d.kind() {
+                    DiskKind::SSD => "ssd",
+                    DiskKind::HDD => "hdd",
+                    _ => "unknown",
+                }
+                .to_string(),
+                removable: d.is_removable(),
+                total: d.total_space(),
+                used: d.total_space().saturating_sub(d.available_space()),
+                free: d.available_space(),
+            }
+        })
+        .collect();
+
+    stats.sort_by(|a, b| b.total.cmp(&a.total));
+    stats
+}
+
+/// SMART health of every physical drive. Blocking and slow; reading SMART
+/// data usually needs admin rights, so fields may come back empty.
+pub fn disk_health() -> Result<Vec<DiskHealth>> {
+    match smartctl::health() {
+        Ok(health) if !health.is_empty() => Ok(health),
+        _ => platform::health(),
+    }
+}
+
+/// smartmontools, where installed, gives the same data on every OS
+mod smartctl {
+    use super::DiskHealth;
+    use anyhow::{bail, Result};
+    use serde_json::Value;
+    use std::process::Command;
+
+    fn json(args: &[&str]) -> Result<Value> {
+        let output = Command::new("smartctl").args(args).output()?;
+        // smartctl's exit code is a bitmask of drive warnings, so only the JSON matters
+        if output.stdout.is_empty() {
+            bail!("smartctl printed nothing");
+        }
+        Ok(serde_json::from_slice(&output.stdout)?)
+    }
+
+    pub fn health() -> Result<Vec<DiskHealth>> {
+        let scan = json(&["--scan", "-j"])?;
+        let devices = scan["devices"].as_array().cloned().unwrap_or_default();
+
+        Ok(devices
+            .iter()
+            .filter_map(|d| d["name"].as_str())
+            .filter_map(|device| {
+                let info = json(&["-j", "-H", "-A", "-i", device]).ok()?;
+                let reallocated = info["ata_smart_attributes"]["table"]
+                    .as_array()
+                    .and_then(|table| table.iter().find(|a| a["id"].as_u64() == Some(5)))
+                    .and_then(|a| a["raw"]["value"].as_u64());
+
+                Some(DiskHealth {
+                    device: device.to_string(),
+                    model: info["model_name"].as_str().unwrap_or(device).to_string(),
+                    healthy: info["smart_status"]["passed"].as_bool(),
+                    temperature_c: info["temperature"]["current"].as_f64().map(|t| t as f32),
+                    reallocated_sectors: reallocated,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::DiskHealth;
+    use anyhow::{bail, Result};
+    use serde::Deserialize;
+    use std::process::Command;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct PhysicalDisk {
+        device_id: Option<String>,
+        friendly_name: Option<String>,
+        health_status: Option<String>,
+        temperature: Option<f32>,
+    }
+
+    // Storage reliability counters carry the temperature; reallocated sectors
+    // aren't exposed without smartctl
+    const SCRIPT: &str = "ConvertTo-Json -Compress -InputObject @(Get-PhysicalDisk | ForEach-Object { \
+        $r = $_ | Get-StorageReliabilityCounter -ErrorAction SilentlyContinue; \
+        [pscustomobject]@{ DeviceId = $_.DeviceId; FriendlyName = $_.FriendlyName; HealthStatus = [string]$_.HealthStatus; Temperature = $r.Temperature } })";
+
+    pub fn health() -> Result<Vec<DiskHealth>> {
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+            .output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let disks: Vec<PhysicalDisk> = serde_json::from_slice(&output.stdout)?;
+
+        Ok(disks
+            .into_iter()
+            .map(|d| DiskHealth {
+                device: format!("PhysicalDrive{}", d.device_id.unwrap_or_default()),
+                model: d.friendly_name.unwrap_or_default(),
+                healthy: d.health_status.map(|s| s == "Healthy"),
+                // Drives without a sensor report 0
+                temperature_c: d.temperature.filter(|t| *t > 0.0),
+                reallocated_sectors: None,
+            })
+            .collect())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::DiskHealth;
+    use anyhow::Result;
+    use std::process::Command;
+
+    /// `diskutil info` only reports the overall SMART verdict
+    pub fn health() -> Result<Vec<DiskHealth>> {
+        let output = Command::new("diskutil").args(["info", "disk0"]).output()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let field = |key: &str| {
+            text.lines()
+                .find_map(|l| l.trim().strip_prefix(key).map(|v| v.trim().to_string()))
+        };
+
+        Ok(vec![DiskHealth {
+            device: "disk0".to_string(),
+            model: field("Device / Media Name:").unwrap_or_default(),
+            healthy: field("SMART Status:").map(|s| s == "Verified"),
+            ..Default::default()
+        }])
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::DiskHealth;
+    use anyhow::Result;
+
+    /// Drive temperatures from the drivetemp / nvme hwmon drivers; no root needed
+    pub fn health() -> Result<Vec<DiskHealth>> {
+        let hwmons = std::fs::read_dir("/sys/class/hwmon")?;
+        Ok(hwmons
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter_map(|dir| {
+                let driver = std::fs::read_to_string(dir.join("name")).ok()?.trim().to_string();
+                if driver != "drivetemp" && driver != "nvme" {
+                    return None;
+                }
+                let millidegrees: f32 = std::fs::read_to_string(dir.join("temp1_input")).ok()?.trim().parse().ok()?;
+                let model = std::fs::read_to_string(dir.join("device/model"))
+                    .map(|m| m.trim().to_string())
+                    .unwrap_or_else(|_| driver.clone());
+                Some(DiskHealth {
+                    device: dir.file_name()?.to_string_lossy().to_string(),
+                    model,
+                    temperature_c: Some(millidegrees / 1000.0),
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+}
+
+/// Drives already warned about, so the warning isn't repeated every check
+static WARNED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Warnings for fixed drives below `threshold_gb` free that haven't been warned about yet
+fn low_space_warnings(threshold_gb: u64) -> Vec<String> {
+    let mut warned = WARNED.lock().unwrap_or_else(|e| e.into_inner());
+    let mut warnings = Vec::new();
+
+    for disk in disk_stats().into_iter().filter(|d| !d.removable) {
+        let free_gb = disk.free / GB;
+        if free_gb < threshold_gb {
+            if warned.insert(disk.name.clone()) {
+                warnings.push(format!("You're down to {} GB on {}", free_gb, disk.name));
+            }
+        } else if free_gb >= threshold_gb + LOW_SPACE_HYSTERESIS_GB {
+            warned.remove(&disk.name);
+        }
+    }
+    warnings
+}
+
+/// Check free space periodically and speak a warning when the
+/// `system.low_disk_alert_gb` setting is on and a drive drops below it
+pub fn start_low_space_watch(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) if settings.system.low_disk_alert_gb > 0 => {
+                    let threshold = settings.system.low_disk_alert_gb;
+                    match tokio::task::spawn_blocking(move || low_space_warnings(threshold)).await {
+                        Ok(warnings) => {
+                            for warning in warnings {
+                                info!("{}", warning);
+                                crate::voice::speak(&warning);
+                                crate::commands::fire_system_event(&app, "disk_space_low").await;
+                            }
+                        }
+                        Err(e) => warn!("Disk space check failed: {}", e),
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Couldn't load settings for the disk space check: {}", e),
+            }
+            tokio::time::sleep(LOW_SPACE_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_disk_stats() -> Result<Vec<DiskStats>, String> {
+    tokio::task::spawn_blocking(disk_stats).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_disk_health() -> Result<Vec<DiskHealth>, String> {
+    tokio::task::spawn_blocking(disk_health)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,439 @@
+// Display Module
+// Screen brightness: the built-in panel through WMI / the backlight, and
+// external monitors through DDC/CI.
+
+use anyhow::{bail, Result};
+use tracing::info;
+use serde::{Deserialize, Serialize};
+
+/// Step used for "brightness up" / "dim the screen" without an amount
+pub const DEFAULT_BRIGHTNESS_STEP: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayKind {
+    /// Laptop panel (WMI / backlight)
+    Internal,
+    /// External monitor (DDC/CI)
+    External,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplayBrightness {
+    /// Stable within a session; pass back to `set_brightness` to target one display
+    pub id: String,
+    pub name: String,
+    pub kind: DisplayKind,
+    /// 0-100
+    pub brightness: u8,
+}
+
+/// A spoken brightness request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrightnessCommand {
+    Set(u8),
+    Adjust(i32),
+}
+
+impl BrightnessCommand {
+    /// Recognize "brightness to 40", "brighter", "dim the screen", ...
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.to_lowercase();
+
+        let amount = lower
+            .split_whitespace()
+            .find_map(|word| word.trim_end_matches(['%', '.', ',']).parse::<i32>().ok());
+
+        let up = ["brightness up", "brighter", "increase the brightness", "increase brightness", "turn up the brightness", "raise the brightness"];
+        let down = ["brightness down", "dimmer", "dim the screen", "dim my screen", "decrease the brightness", "decrease brightness", "turn down the brightness", "lower the brightness"];
+
+        if up.iter().any(|p| lower.contains(p)) {
+            return Some(Self::Adjust(amount.unwrap_or(DEFAULT_BRIGHTNESS_STEP)));
+        }
+        if down.iter().any(|p| lower.contains(p)) {
+            return Some(Self::Adjust(-amount.unwrap_or(DEFAULT_BRIGHTNESS_STEP)));
+        }
+        if lower.contains("brightness") {
+            if let Some(level) = amount {
+                return Some(Self::Set(level.clamp(0, 100) as u8));
+            }
+            if lower.contains("max") || lower.contains("full") {
+                return Some(Self::Set(100));
+            }
+        }
+
+        None
+    }
+}
+
+/// Brightness of every display we can control. Blocking.
+pub fn get_brightness() -> Result<Vec<DisplayBrightness>> {
+    platform::get_brightness()
+}
+
+/// Set one display (by id) or all of them. Blocking.
+pub fn set_brightness(level: u8, display: Option<&str>) -> Result<()> {
+    let level = level.min(100);
+    info!("Setting brightness to {}% ({})", level, display.unwrap_or("all displays"));
+    platform::set_brightness(level, display)
+}
+
+/// Change every display by `delta` points relative to its own level. Returns the new
+/// level of the first display.
+pub fn adjust_brightness(delta: i32) -> Result<u8> {
+    let displays = get_brightness()?;
+    if displays.is_empty() {
+        bail!("No display supports brightness control");
+    }
+
+    for display in &displays {
+        let level = (display.brightness as i32 + delta).clamp(0, 100) as u8;
+        platform::set_brightness(level, Some(&display.id))?;
+    }
+    Ok((displays[0].brightness as i32 + delta).clamp(0, 100) as u8)
+}
+
+/// Run a parsed brightness command, returning a confirmation to speak back
+pub fn apply_brightness_command(command: BrightnessCommand) -> Result<String> {
+    match command {
+        BrightnessCommand::Set(level) => {
+            set_brightness(level, None)?;
+            Ok(format!("Brightness set to {}%", level))
+        }
+        BrightnessCommand::Adjust(delta) => {
+            let level = adjust_brightness(delta)?;
+            Ok(format!("Brightness is now {}%", level))
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{DisplayBrightness, DisplayKind};
+    use anyhow::{bail, Result};
+    use std::process::Command;
+    use windows::Win32::Devices::Display::{
+        DestroyPhysicalMonitors, GetMonitorBrightness, GetNumberOfPhysicalMonitorsFromHMONITOR,
+        GetPhysicalMonitorsFromHMONITOR, SetMonitorBrightness, PHYSICAL_MONITOR,
+    };
+    use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, HDC, HMONITOR};
+
+    const INTERNAL_ID: &str = "internal";
+
+    const WMI_GET: &str = "(Get-CimInstance -Namespace root/WMI -ClassName WmiMonitorBrightness -ErrorAction Stop | Select-Object -First 1).CurrentBrightness";
+
+    fn powershell(script: &str) -> Result<String> {
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", script])
+            .output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Laptop panels expose brightness through WMI; desktops don't have the class
+    fn internal_brightness() -> Option<u8> {
+        powershell(WMI_GET).ok()?.parse().ok()
+    }
+
+    fn set_internal_brightness(level: u8) -> Result<()> {
+        powershell(&format!(
+            "Get-CimInstance -Namespace root/WMI -ClassName WmiMonitorBrightnessMethods -ErrorAction Stop | Invoke-CimMethod -MethodName WmiSetBrightness -Arguments @{{Timeout=0; Brightness={}}} | Out-Null",
+            level
+        ))
+        .map(|_| ())
+    }
+
+    unsafe extern "system" fn collect_monitor(monitor: HMONITOR, _: HDC, _: *mut RECT, data: LPARAM) -> BOOL {
+        let monitors = &mut *(data.0 as *mut Vec<HMONITOR>);
+        monitors.push(monitor);
+        true.into()
+    }
+
+    /// DDC/CI handles for every attached monitor; released on drop
+    struct PhysicalMonitors(Vec<PHYSICAL_MONITOR>);
+
+    impl PhysicalMonitors {
+        fn enumerate() -> Self {
+            let mut handles: Vec<HMONITOR> = Vec::new();
+            let mut monitors = Vec::new();
+
+            unsafe {
+                let _ = EnumDisplayMonitors(HDC(0), None, Some(collect_monitor), LPARAM(&mut handles as *mut _ as isize));
+
+                for handle in handles {
+                    let mut count = 0u32;
+                    if GetNumberOfPhysicalMonitorsFromHMONITOR(handle, &mut count).is_err() || count == 0 {
+                        continue;
+                    }
+                    let mut physical = vec![PHYSICAL_MONITOR::default(); count as usize];
+                    if GetPhysicalMonitorsFromHMONITOR(handle, &mut physical).is_ok() {
+                        monitors.extend(physical);
+                    }
+                }
+            }
+
+            Self(monitors)
+        }
+    }
+
+    impl Drop for PhysicalMonitors {
+        fn drop(&mut self) {
+            if !self.0.is_empty() {
+                unsafe {
+                    let _ = DestroyPhysicalMonitors(&self.0);
+                }
+            }
+        }
+    }
+
+    fn monitor_name(monitor: &PHYSICAL_MONITOR) -> String {
+        let description = monitor.szPhysicalMonitorDescription;
+        let len = description.iter().position(|c| *c == 0).unwrap_or(description.len());
+        String::from_utf16_lossy(&description[..len])
+    }
+
+    /// (min, current, max) over DDC/CI; None when the monitor doesn't support it
+    fn ddc_brightness(monitor: &PHYSICAL_MONITOR) -> Option<(u32, u32, u32)> {
+        let (mut min, mut current, mut max) = (0u32, 0u32, 0u32);
+        let ok = unsafe { GetMonitorBrightness(monitor.hPhysicalMonitor, &mut min, &mut current, &mut max) };
+        (ok != 0 && max > min).then_some((min, current, max))
+    }
+
+    pub fn get_brightness() -> Result<Vec<DisplayBrightness>> {
+        let mut displays = Vec::new();
+
+        if let Some(level) = internal_brightness() {
+            displays.push(DisplayBrightness {
+                id: INTERNAL_ID.to_string(),
+                name: "Built-in display".to_string(),
+                kind: DisplayKind::Internal,
+                brightness: level.min(100),
+            });
+        }
+
+        let monitors = PhysicalMonitors::enumerate();
+        for (index, monitor) in monitors.0.iter().enumerate() {
+            if let Some((min, current, max)) = ddc_brightness(monitor) {
+                let percent = ((current.saturating_sub(min)) * 100 / (max - min)).min(100) as u8;
+                displays.push(DisplayBrightness {
+                    id: format!("ddc:{}", index),
+                    name: monitor_name(monitor),
+                    kind: DisplayKind::External,
+                    brightness: percent,
+                });
+            }
+        }
+
+        Ok(displays)
+    }
+
+    pub fn set_brightness(level: u8, display: Option<&str>) -> Result<()> {
+        let mut changed = false;
+
+        if display.is_none_or(|id| id == INTERNAL_ID) && internal_brightness().is_some() {
+            set_internal_brightness(level)?;
+            changed = true;
+        }
+
+        let monitors = PhysicalMonitors::enumerate();
+        for (index, monitor) in monitors.0.iter().enumerate() {
+            if display.is_some_and(|id| id != format!("ddc:{}", index)) {
+                continue;
+            }
+            if let Some((min, _, max)) = ddc_brightness(monitor) {
+                let value = min + (max - min) * level as u32 / 100;
+                if unsafe { SetMonitorBrightness(monitor.hPhysicalMonitor, value) } != 0 {
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            bail!("No display supports brightness control");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{DisplayBrightness, DisplayKind};
+    use anyhow::{bail, Context, Result};
+    use std::process::Command;
+
+    // Relies on the `brightness` CLI (brew install brightness); macOS has no
+    // public command line tool for this
+    pub fn get_brightness() -> Result<Vec<DisplayBrightness>> {
+        let output = Command::new("brightness")
+            .arg("-l")
+            .output()
+            .context("Install the `brightness` tool (brew install brightness) for brightness control")?;
+
+        // "display 0: brightness 0.750000"
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (display, value) = line.split_once(": brightness ")?;
+                let index = display.trim_start_matches("display ").trim().to_string();
+                let level: f32 = value.trim().parse().ok()?;
+                Some(DisplayBrightness {
+                    name: format!("Display {}", index),
+                    kind: if index == "0" { DisplayKind::Internal } else { DisplayKind::External },
+                    id: index,
+                    brightness: (level * 100.0).round().clamp(0.0, 100.0) as u8,
+                })
+            })
+            .collect())
+    }
+
+    pub fn set_brightness(level: u8, display: Option<&str>) -> Result<()> {
+        let value = format!("{:.2}", level as f32 / 100.0);
+        let mut command = Command::new("brightness");
+        if let Some(id) = display {
+            command.args(["-d", id]);
+        }
+        let status = command
+            .arg(&value)
+            .status()
+            .context("Install the `brightness` tool (brew install brightness) for brightness control")?;
+        if !status.success() {
+            bail!("brightness exited with {}", status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::{DisplayBrightness, DisplayKind};
+    use anyhow::{bail, Result};
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+
+    fn backlights() -> Vec<PathBuf> {
+        std::fs::read_dir(BACKLIGHT_DIR)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default()
+    }
+
+    fn read_value(dir: &Path, file: &str) -> Option<u32> {
+        std::fs::read_to_string(dir.join(file)).ok()?.trim().parse().ok()
+    }
+
+    /// External monitors via ddcutil ("Display 1" blocks; VCP feature 10 is brightness)
+    fn ddc_displays() -> Vec<DisplayBrightness> {
+        let Ok(output) = Command::new("ddcutil").args(["getvcp", "10", "--brief"]).output() else {
+            return Vec::new();
+        };
+
+        // "VCP 10 C 50 100" per display, in display order
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| line.starts_with("VCP 10"))
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let current: u32 = fields.get(3)?.parse().ok()?;
+                let max: u32 = fields.get(4)?.parse().ok()?;
+                (max > 0).then(|| DisplayBrightness {
+                    id: format!("ddc:{}", index + 1),
+                    name: format!("Monitor {}", index + 1),
+                    kind: DisplayKind::External,
+                    brightness: (current * 100 / max).min(100) as u8,
+                })
+            })
+            .collect()
+    }
+
+    pub fn get_brightness() -> Result<Vec<DisplayBrightness>> {
+        let mut displays: Vec<DisplayBrightness> = backlights()
+            .iter()
+            .filter_map(|dir| {
+                let current = read_value(dir, "brightness")?;
+                let max = read_value(dir, "max_brightness").filter(|m| *m > 0)?;
+                let name = dir.file_name()?.to_string_lossy().to_string();
+                Some(DisplayBrightness {
+                    id: format!("backlight:{}", name),
+                    name,
+                    kind: DisplayKind::Internal,
+                    brightness: (current * 100 / max).min(100) as u8,
+                })
+            })
+            .collect();
+
+        displays.extend(ddc_displays());
+        Ok(displays)
+    }
+
+    pub fn set_brightness(level: u8, display: Option<&str>) -> Result<()> {
+        let mut changed = false;
+
+        for dir in backlights() {
+            let name = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            if display.is_some_and(|id| id != format!("backlight:{}", name)) {
+                continue;
+            }
+            // Writing the sysfs file needs a udev rule; brightnessctl works through logind
+            let written = read_value(&dir, "max_brightness")
+                .map(|max| std::fs::write(dir.join("brightness"), (max * level as u32 / 100).to_string()).is_ok())
+                .unwrap_or(false);
+            let via_tool = !written
+                && Command::new("brightnessctl")
+                    .args(["--device", &name, "set", &format!("{}%", level)])
+                    .status()
+                    .map(|s| s.success())
+                    .unwrap_or(false);
+            changed |= written || via_tool;
+        }
+
+        for ddc in ddc_displays() {
+            if display.is_some_and(|id| id != ddc.id) {
+                continue;
+            }
+            let number = ddc.id.trim_start_matches("ddc:");
+            let ok = Command::new("ddcutil")
+                .args(["--display", number, "setvcp", "10", &level.to_string()])
+                .status()
+                .map(|s| s.success())
+                .unwrap_or(false);
+            changed |= ok;
+        }
+
+        if !changed {
+            bail!("No display supports brightness control");
+        }
+        Ok(())
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_brightness_command() -> Result<Vec<DisplayBrightness>, String> {
+    tokio::task::spawn_blocking(get_brightness)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Set brightness (0-100) on one display, or all displays when `display` is omitted
+#[tauri::command]
+pub async fn set_brightness_command(level: u8, display: Option<String>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || set_brightness(level, display.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Relative change, e.g. +10 / -10. Returns the new level.
+#[tauri::command]
+pub async fn change_brightness(delta: i32) -> Result<u8, String> {
+    tokio::task::spawn_blocking(move || adjust_brightness(delta))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,214 @@
+// Documents Module
+// Pulls the text out of a file (plain text, PDF, DOCX) so the assistant can
+// summarize or answer questions about it. Files arrive by drag-drop onto the
+// window or from the current Explorer/Finder selection.
+
+use anyhow::{bail, Context, Result};
+use tracing::info;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Roughly what fits in a small model's context next to the instructions
+const MAX_CHARS: usize = 12_000;
+
+const PLAIN_TEXT: &[&str] = &[
+    "txt", "md", "markdown", "log", "csv", "json", "xml", "html", "htm", "yaml", "yml", "toml", "ini", "rs", "py",
+    "js", "ts", "tsx", "c", "cpp", "h", "cs", "java", "go", "sh", "ps1",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentText {
+    pub path: String,
+    pub name: String,
+    pub text: String,
+    /// True when the text was cut to fit the prompt
+    pub truncated: bool,
+}
+
+/// Whether a command asks about a selected or open document ("what's in this document?")
+pub fn is_document_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    let mentions = ["this document", "this file", "this pdf", "selected file", "selected document", "the selection"]
+        .iter()
+        .any(|p| lower.contains(p));
+    let asks = ["what", "summarize", "summary", "explain", "read", "tell me"]
+        .iter()
+        .any(|w| lower.contains(w));
+
+    mentions && asks
+}
+
+/// Read all of a document's text. Blocking.
+pub fn read_text(path: &Path) -> Result<String> {
+    if !path.is_file() {
+        bail!("{} isn't a file", path.display());
+    }
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    let text = match extension.as_str() {
+        "pdf" => pdf_extract::extract_text(path).with_context(|| format!("Couldn't read PDF {}", path.display()))?,
+        "docx" => docx_text(path)?,
+        ext if PLAIN_TEXT.contains(&ext) => String::from_utf8_lossy(&std::fs::read(path)?).to_string(),
+        "" => bail!("I can't tell what kind of file that is"),
+        ext => bail!("I can't read .{} files yet", ext),
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        bail!("{} has no readable text", path.display());
+    }
+    Ok(text.to_string())
+}
+
+/// Read a document's text, trimmed to fit a prompt. Blocking.
+pub fn extract_text(path: &Path) -> Result<DocumentText> {
+    let text = read_text(path)?;
+    let truncated = text.chars().count() > MAX_CHARS;
+    let text: String = text.chars().take(MAX_CHARS).collect();
+    info!("Extracted {} chars from {}", text.len(), path.display());
+
+    Ok(DocumentText {
+        path: path.to_string_lossy().to_string(),
+        name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        text,
+        truncated,
+    })
+}
+
+/// Body text of a .docx: paragraphs from word/document.xml, markup dropped
+fn docx_text(path: &Path) -> Result<String> {
+    let file = std::fs::File::open(path)?;
+    let mut archive = zip::ZipArchive::new(file).context("Not a valid .docx file")?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .context("The .docx has no document body")?
+        .read_to_string(&mut xml)?;
+
+    let mut text = String::new();
+    let mut in_text = false;
+    let mut rest = xml.as_str();
+
+    while let Some(start) = rest.find('<') {
+        if in_text {
+            text.push_str(&unescape_xml(&rest[..start]));
+        }
+        let Some(end) = rest[start..].find('>') else { break };
+        let tag = &rest[start + 1..start + end];
+        let name = tag.trim_start_matches('/').split([' ', '/']).next().unwrap_or("");
+
+        match name {
+            "w:t" => in_text = !tag.starts_with('/') && !tag.ends_with('/'),
+            "w:tab" => text.push('\t'),
+            "w:br" | "w:cr" => text.push('\n'),
+            "w:p" if tag.starts_with('/') => text.push('\n'),
+            _ => {}
+        }
+        rest = &rest[start + end + 1..];
+    }
+
+    Ok(text)
+}
+
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Files currently selected in Explorer (Windows) or Finder (macOS). Blocking.
+pub fn selected_files() -> Result<Vec<PathBuf>> {
+    platform::selected_files()
+}
+
+/// Prompt handing the document to the LLM along with the user's request
+pub fn build_document_prompt(request: &str, document: &DocumentText) -> String {
+    let note = if document.truncated { " (truncated)" } else { "" };
+    format!(
+        "The user asked: \"{}\"\n\nThis is the text of the file \"{}\"{}:\n---\n{}\n---\nAnswer using the file's contents. Keep it short enough to read aloud.",
+        request, document.name, note, document.text
+    )
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use anyhow::{bail, Result};
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    // Every open Explorer window is checked; ASTRAL has focus while you talk to it,
+    // so the foreground window can't tell us which one you meant
+    const SCRIPT: &str = "$shell = New-Object -ComObject Shell.Application; \
+        foreach ($w in $shell.Windows()) { try { $w.Document.SelectedItems() | ForEach-Object { $_.Path } } catch {} }";
+
+    pub fn selected_files() -> Result<Vec<PathBuf>> {
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-Command", SCRIPT])
+            .output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use anyhow::{bail, Result};
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    const SCRIPT: &str = r#"tell application "Finder"
+set out to ""
+repeat with f in (get selection)
+set out to out & POSIX path of (f as alias) & linefeed
+end repeat
+return out
+end tell"#;
+
+    pub fn selected_files() -> Result<Vec<PathBuf>> {
+        let output = Command::new("osascript").args(["-e", SCRIPT]).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use anyhow::{bail, Result};
+    use std::path::PathBuf;
+
+    // File managers here have no common scripting interface
+    pub fn selected_files() -> Result<Vec<PathBuf>> {
+        bail!("I can't see the file manager's selection here. Drag the file onto my window instead.")
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn extract_document_text(path: String) -> Result<DocumentText, String> {
+    tokio::task::spawn_blocking(move || extract_text(Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
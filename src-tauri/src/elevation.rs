@@ -0,0 +1,246 @@
+// Elevation Module
+// Runs system commands that may need admin rights. A command is tried as-is
+// first; only when it fails for lack of rights does the user get an elevation
+// prompt (UAC, the macOS admin dialog, or polkit). Declining is remembered for
+// the session so routines don't keep prompting, and callers always get an
+// error saying what happened instead of a bare exit code.
+
+use anyhow::{bail, Result};
+use tracing::{info, warn};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Phrases tools print when they lack rights (Windows, coreutils, polkit, systemd)
+const DENIED_MARKERS: &[&str] = &[
+    "access is denied",
+    "access denied",
+    "requires elevation",
+    "run as administrator",
+    "administrator privileges",
+    "permission denied",
+    "operation not permitted",
+    "must be run as root",
+    "must be root",
+    "authentication is required",
+    "interactive authentication required",
+];
+
+static ELEVATED: OnceCell<bool> = OnceCell::new();
+static DECLINED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElevationStatus {
+    /// ASTRAL itself is running as administrator / root
+    pub elevated: bool,
+    /// The user declined an elevation prompt this session
+    pub declined: bool,
+}
+
+/// Outcome of running a command through the OS elevation prompt
+enum Elevated {
+    Ran(Output),
+    Declined,
+}
+
+/// Whether ASTRAL is running with admin rights. Checked once.
+pub fn is_elevated() -> bool {
+    *ELEVATED.get_or_init(platform::is_elevated)
+}
+
+pub fn status() -> ElevationStatus {
+    ElevationStatus {
+        elevated: is_elevated(),
+        declined: DECLINED.load(Ordering::SeqCst),
+    }
+}
+
+fn message(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if !stderr.is_empty() {
+        return stderr;
+    }
+    // netsh, powercfg and sc report errors on stdout
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if stdout.is_empty() {
+        format!("exited with {}", output.status)
+    } else {
+        stdout
+    }
+}
+
+/// Whether a failed command failed because it needed admin rights
+fn needs_elevation(output: &Output) -> bool {
+    // ERROR_ACCESS_DENIED
+    if cfg!(target_os = "windows") && output.status.code() == Some(5) {
+        return true;
+    }
+    let text = format!(
+        "{} {}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    )
+    .to_lowercase();
+    DENIED_MARKERS.iter().any(|m| text.contains(m))
+}
+
+/// Run `program`, asking for elevation once if it turns out to need admin rights.
+/// Returns stdout. Blocking (the prompt waits for the user).
+pub fn run_privileged(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program).args(args).output()?;
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+    }
+    if !needs_elevation(&output) || is_elevated() {
+        bail!("{} failed: {}", program, message(&output));
+    }
+    if DECLINED.load(Ordering::SeqCst) {
+        bail!("{} needs administrator rights, and elevation was declined earlier this session", program);
+    }
+
+    info!("{} needs administrator rights, asking for elevation", program);
+    match platform::run_elevated(program, args)? {
+        Elevated::Ran(output) if output.status.success() => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+        Elevated::Ran(output) => bail!("{} failed even with administrator rights: {}", program, message(&output)),
+        Elevated::Declined => {
+            warn!("Elevation for {} was declined", program);
+            DECLINED.store(true, Ordering::SeqCst);
+            bail!("{} needs administrator rights, and the elevation prompt was declined", program)
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::Elevated;
+    use anyhow::Result;
+    use std::process::Command;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    pub fn is_elevated() -> bool {
+        unsafe {
+            let mut token = HANDLE::default();
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
+                return false;
+            }
+            let mut elevation = TOKEN_ELEVATION::default();
+            let mut size = 0u32;
+            let ok = GetTokenInformation(
+                token,
+                TokenElevation,
+                Some(&mut elevation as *mut TOKEN_ELEVATION as *mut _),
+                std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+                &mut size,
+            )
+            .is_ok();
+            let _ = CloseHandle(token);
+            ok && elevation.TokenIsElevated != 0
+        }
+    }
+
+    fn quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    // Start-Process -Verb RunAs shows the UAC prompt. The elevated process can't
+    // hand its output back, so only the exit code is reported.
+    pub fn run_elevated(program: &str, args: &[&str]) -> Result<Elevated> {
+        let arg_list = if args.is_empty() {
+            String::new()
+        } else {
+            format!(" -ArgumentList {}", args.iter().map(|a| quote(a)).collect::<Vec<_>>().join(","))
+        };
+        let script = format!(
+            "$p = Start-Process -FilePath {}{} -Verb RunAs -Wait -PassThru -WindowStyle Hidden; exit $p.ExitCode",
+            quote(program),
+            arg_list
+        );
+
+        let output = Command::new("powershell").args(["-NoProfile", "-Command", &script]).output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+        if !output.status.success() && stderr.contains("canceled by the user") {
+            return Ok(Elevated::Declined);
+        }
+        Ok(Elevated::Ran(output))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::Elevated;
+    use anyhow::Result;
+    use std::process::Command;
+
+    pub fn is_elevated() -> bool {
+        Command::new("id")
+            .arg("-u")
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).trim() == "0")
+    }
+
+    fn shell_quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+
+    pub fn run_elevated(program: &str, args: &[&str]) -> Result<Elevated> {
+        let command = std::iter::once(program)
+            .chain(args.iter().copied())
+            .map(shell_quote)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let script = format!(
+            "do shell script \"{}\" with administrator privileges",
+            command.replace('\\', "\\\\").replace('"', "\\\"")
+        );
+
+        let output = Command::new("osascript").args(["-e", &script]).output()?;
+        // "User canceled. (-128)"
+        if String::from_utf8_lossy(&output.stderr).contains("-128") {
+            return Ok(Elevated::Declined);
+        }
+        Ok(Elevated::Ran(output))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::Elevated;
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    pub fn is_elevated() -> bool {
+        Command::new("id")
+            .arg("-u")
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).trim() == "0")
+    }
+
+    pub fn run_elevated(program: &str, args: &[&str]) -> Result<Elevated> {
+        let output = match Command::new("pkexec").arg(program).args(args).output() {
+            Ok(output) => output,
+            Err(_) => bail!("{} needs root, and pkexec (polkit) isn't installed to ask for it", program),
+        };
+        // 126: the dialog was dismissed, 127: authentication failed
+        if matches!(output.status.code(), Some(126) | Some(127)) {
+            return Ok(Elevated::Declined);
+        }
+        Ok(Elevated::Ran(output))
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_elevation_status() -> Result<ElevationStatus, String> {
+    Ok(status())
+}
+
+/// Allow elevation prompts again after the user declined one
+#[tauri::command]
+pub async fn reset_elevation_prompt() -> Result<(), String> {
+    DECLINED.store(false, Ordering::SeqCst);
+    Ok(())
+}
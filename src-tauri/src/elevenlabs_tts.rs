@@ -3,13 +3,23 @@
 
 use serde::{Deserialize, Serialize};
 use reqwest;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
 
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use crate::state::AppState;
+use crate::voice::bus::{self, PipelineEvent};
+
+/// Also the `tts` settings section
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ElevenLabsConfig {
     pub api_key: String,
     pub voice_id: String, // Default voice ID
     pub model_id: String,
     pub enabled: bool,
+    /// Language code -> voice to use instead of `voice_id` for that locale
+    pub voices_by_language: HashMap<String, String>,
 }
 
 impl Default for ElevenLabsConfig {
@@ -19,6 +29,7 @@ impl Default for ElevenLabsConfig {
             voice_id: "21m00Tcm4TlvDq8ikWAM".to_string(), // Rachel - default voice
             model_id: "eleven_turbo_v2_5".to_string(), // Updated model for free tier
             enabled: false,
+            voices_by_language: HashMap::new(),
         }
     }
 }
@@ -27,6 +38,9 @@ impl Default for ElevenLabsConfig {
 struct TTSRequest {
     text: String,
     model_id: String,
+    /// Only the v2.5 models accept a language to enforce
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language_code: Option<String>,
     voice_settings: VoiceSettings,
 }
 
@@ -45,64 +59,77 @@ impl ElevenLabsEngine {
     pub fn new(config: ElevenLabsConfig) -> Self {
         Self {
             config,
-            client: reqwest::Client::new(),
+            client: crate::proxy::client(),
         }
     }
 
     /// Generate speech from text
-    pub async fn generate_speech(&self, text: &str) -> Result<Vec<u8>, String> {
+    pub async fn generate_speech(&self, text: &str) -> AstralResult<Vec<u8>> {
         if !self.config.enabled {
-            return Err("ElevenLabs is disabled".to_string());
+            return Err(AstralError::not_configured("ElevenLabs is turned off"));
         }
 
         if self.config.api_key.is_empty() {
-            return Err("ElevenLabs API key not set. Get one at: https://elevenlabs.io/".to_string());
+            return Err(AstralError::not_configured("ElevenLabs API key not set. Get one at: https://elevenlabs.io/"));
         }
 
+        let language = crate::locale::current_language();
+        let voice_id = self.config.voices_by_language.get(&language).unwrap_or(&self.config.voice_id);
         let url = format!(
             "https://api.elevenlabs.io/v1/text-to-speech/{}",
-            self.config.voice_id
+            voice_id
         );
 
         let request_body = TTSRequest {
             text: text.to_string(),
             model_id: self.config.model_id.clone(),
+            language_code: self.config.model_id.ends_with("_v2_5").then_some(language),
             voice_settings: VoiceSettings {
                 stability: 0.5,
                 similarity_boost: 0.75,
             },
         };
 
-        match self.client
-            .post(&url)
-            .header("xi-api-key", &self.config.api_key)
-            .header("Content-Type", "application/json")
-            .json(&request_body)
-            .send()
-            .await
-        {
-            Ok(response) => {
-                if response.status().is_success() {
-                    match response.bytes().await {
-                        Ok(bytes) => Ok(bytes.to_vec()),
-                        Err(e) => Err(format!("Failed to read audio data: {}", e)),
+        let request = crate::privacy::send(
+            self.client
+                .post(&url)
+                .header("xi-api-key", &self.config.api_key)
+                .header("Content-Type", "application/json")
+                .json(&request_body),
+        );
+
+        let result = crate::metrics::time(crate::metrics::Stage::Tts, async {
+            match request.await {
+                Ok(response) => {
+                    if response.status().is_success() {
+                        match response.bytes().await {
+                            Ok(bytes) => Ok(bytes.to_vec()),
+                            Err(e) => Err(AstralError::new(ErrorCode::Network, "Failed to read audio data").with_detail(e.to_string())),
+                        }
+                    } else {
+                        let status = response.status();
+                        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                        Err(AstralError::from_status("ElevenLabs", status, &error_text))
                     }
-                } else {
-                    let status = response.status();
-                    let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-                    Err(format!("ElevenLabs API error {}: {}", status, error_text))
                 }
+                Err(e) => Err(AstralError::from(e.context("Couldn't reach ElevenLabs"))),
             }
-            Err(e) => Err(format!("Request failed: {}", e)),
-        }
+        })
+        .await;
+
+        bus::publish(match &result {
+            Ok(audio) => PipelineEvent::SpeechSynthesized { bytes: audio.len() },
+            Err(e) => PipelineEvent::StageFailed { stage: bus::Stage::Tts, error: e.to_string() },
+        });
+        result
     }
 
     /// Save speech to file
-    pub async fn generate_speech_to_file(&self, text: &str, output_path: &str) -> Result<(), String> {
+    pub async fn generate_speech_to_file(&self, text: &str, output_path: &str) -> AstralResult<()> {
         let audio_data = self.generate_speech(text).await?;
         
         std::fs::write(output_path, audio_data)
-            .map_err(|e| format!("Failed to write audio file: {}", e))?;
+            .map_err(|e| AstralError::from(e).with_detail(format!("Failed to write {}", output_path)))?;
         
         Ok(())
     }
@@ -110,6 +137,8 @@ impl ElevenLabsEngine {
     /// Update configuration
     pub fn update_config(&mut self, config: ElevenLabsConfig) {
         self.config = config;
+        // Picks up a changed proxy
+        self.client = crate::proxy::client();
     }
 
     /// Get current configuration
@@ -118,40 +147,35 @@ impl ElevenLabsEngine {
     }
 }
 
-// Global instance management
-use once_cell::sync::Lazy;
-use tokio::sync::Mutex;
-
-static TTS_ENGINE: Lazy<Mutex<ElevenLabsEngine>> = Lazy::new(|| {
-    Mutex::new(ElevenLabsEngine::new(ElevenLabsConfig::default()))
-});
-
 // Tauri commands
 
 #[tauri::command]
-pub async fn elevenlabs_speak(text: String) -> Result<Vec<u8>, String> {
-    let engine = TTS_ENGINE.lock().await;
+pub async fn elevenlabs_speak(state: State<'_, AppState>, text: String) -> AstralResult<Vec<u8>> {
+    let engine = state.tts.lock().await;
     
     // Return audio bytes directly instead of file path
     engine.generate_speech(&text).await
 }
 
 #[tauri::command]
-pub async fn elevenlabs_get_config() -> Result<ElevenLabsConfig, String> {
-    let engine = TTS_ENGINE.lock().await;
+pub async fn elevenlabs_get_config(state: State<'_, AppState>) -> AstralResult<ElevenLabsConfig> {
+    let engine = state.tts.lock().await;
     Ok(engine.get_config())
 }
 
+/// Saved to the `tts` settings section, which reconfigures the engine
 #[tauri::command]
-pub async fn elevenlabs_update_config(config: ElevenLabsConfig) -> Result<(), String> {
-    let mut engine = TTS_ENGINE.lock().await;
-    engine.update_config(config);
-    Ok(())
+pub async fn elevenlabs_update_config(app: AppHandle, config: ElevenLabsConfig) -> AstralResult<()> {
+    crate::settings::modify_settings(&app, |settings| {
+        settings.tts = config;
+        Ok(())
+    })
+    .await
 }
 
 #[tauri::command]
-pub async fn elevenlabs_test() -> Result<String, String> {
-    let engine = TTS_ENGINE.lock().await;
+pub async fn elevenlabs_test(state: State<'_, AppState>) -> AstralResult<String> {
+    let engine = state.tts.lock().await;
     
     let test_text = "Hello! This is AKI testing ElevenLabs text to speech. The voice quality is quite impressive, don't you think?";
     let temp_path = std::env::temp_dir().join("astral_elevenlabs_test.mp3");
@@ -159,7 +183,7 @@ pub async fn elevenlabs_test() -> Result<String, String> {
     
     match engine.generate_speech_to_file(test_text, &temp_path_str).await {
         Ok(_) => Ok(format!("ElevenLabs test successful! Audio saved to: {}", temp_path_str)),
-        Err(e) => Err(format!("Test failed: {}", e)),
+        Err(e) => Err(e),
     }
 }
 
@@ -171,7 +195,7 @@ pub struct Voice {
 }
 
 #[tauri::command]
-pub async fn elevenlabs_get_voices() -> Result<Vec<Voice>, String> {
+pub async fn elevenlabs_get_voices() -> AstralResult<Vec<Voice>> {
     // Return a list of popular pre-made voices
     Ok(vec![
         Voice { id: "21m00Tcm4TlvDq8ikWAM".to_string(), name: "Rachel (Female, American)".to_string() },
@@ -0,0 +1,166 @@
+// Embedded LLM
+// Runs a GGUF model in-process with llama.cpp, for answering without Ollama
+// or any other server. The model file is picked in settings and loaded on
+// the first request, then kept in memory until a different file is chosen.
+// Replies are formatted with the chat template stored in the GGUF file.
+// Building llama.cpp needs a C++ toolchain and CMake, so this is behind the
+// `embedded-llm` feature.
+
+use crate::error::AstralError;
+use crate::llm_provider::{LLMConfig, LLMResponse, Message};
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use tokio::sync::watch;
+
+/// Changes when `llm_provider::cancel_requests` is called
+type Cancel = watch::Receiver<u64>;
+
+/// Context size when `context_tokens` isn't set
+pub const DEFAULT_CONTEXT_TOKENS: u32 = 4096;
+
+#[cfg_attr(not(feature = "embedded-llm"), allow(dead_code))]
+struct Options {
+    context_tokens: u32,
+    max_tokens: u32,
+    temperature: f32,
+}
+
+#[cfg(feature = "embedded-llm")]
+mod backend {
+    use super::*;
+    use llama_cpp_2::context::params::LlamaContextParams;
+    use llama_cpp_2::llama_backend::LlamaBackend;
+    use llama_cpp_2::llama_batch::LlamaBatch;
+    use llama_cpp_2::model::params::LlamaModelParams;
+    use llama_cpp_2::model::{AddBos, LlamaChatMessage, LlamaModel, Special};
+    use llama_cpp_2::sampling::LlamaSampler;
+    use once_cell::sync::Lazy;
+    use std::num::NonZeroU32;
+    use std::sync::Mutex;
+    use tracing::info;
+
+    // llama.cpp can only be initialized once per process
+    static BACKEND: Lazy<Result<LlamaBackend, String>> = Lazy::new(|| LlamaBackend::init().map_err(|e| e.to_string()));
+
+    // Loading takes seconds and gigabytes, so keep the model between requests
+    static MODEL: Lazy<Mutex<Option<(String, LlamaModel)>>> = Lazy::new(|| Mutex::new(None));
+
+    /// The reply and the tokens used (prompt and reply)
+    pub(super) fn generate(path: &str, messages: &[Message], options: &Options, cancel: &Cancel) -> Result<(String, u32)> {
+        let backend = BACKEND.as_ref().map_err(|e| anyhow!("Couldn't start llama.cpp: {}", e))?;
+        let mut cached = MODEL.lock().unwrap_or_else(|e| e.into_inner());
+
+        if !matches!(cached.as_ref(), Some((loaded, _)) if loaded == path) {
+            // Free the old model before reading the new one
+            *cached = None;
+            info!("Loading GGUF model from {}", path);
+            let model = LlamaModel::load_from_file(backend, path, &LlamaModelParams::default())
+                .map_err(|e| anyhow!("Couldn't load the model at {}: {}", path, e))?;
+            *cached = Some((path.to_string(), model));
+        }
+        let Some((_, model)) = cached.as_ref() else {
+            return Err(anyhow!("The model isn't loaded"));
+        };
+
+        let chat = messages
+            .iter()
+            .map(|m| LlamaChatMessage::new(m.role.clone(), m.content.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let template = model
+            .chat_template(None)
+            .map_err(|e| anyhow!("The model file has no chat template: {}", e))?;
+        let prompt = model.apply_chat_template(&template, &chat, true)?;
+
+        let tokens = model.str_to_token(&prompt, AddBos::Always)?;
+        if tokens.len() + options.max_tokens as usize > options.context_tokens as usize {
+            return Err(anyhow!(
+                "The conversation ({} tokens) doesn't fit the model's {} token context",
+                tokens.len(),
+                options.context_tokens
+            ));
+        }
+
+        let context_params = LlamaContextParams::default().with_n_ctx(NonZeroU32::new(options.context_tokens));
+        let mut context = model.new_context(backend, context_params)?;
+
+        let mut batch = LlamaBatch::new(tokens.len().max(512), 1);
+        let last = tokens.len() as i32 - 1;
+        for (position, token) in (0_i32..).zip(tokens.iter()) {
+            batch.add(*token, position, &[0], position == last)?;
+        }
+        context.decode(&mut batch)?;
+
+        let mut sampler = if options.temperature <= 0.0 {
+            LlamaSampler::greedy()
+        } else {
+            LlamaSampler::chain_simple([LlamaSampler::temp(options.temperature), LlamaSampler::dist(rand::random())])
+        };
+
+        let mut position = batch.n_tokens();
+        let mut reply = Vec::new();
+        let mut generated = 0;
+        while generated < options.max_tokens {
+            if cancel.has_changed().unwrap_or(true) {
+                return Err(anyhow!("Generation cancelled"));
+            }
+            let token = sampler.sample(&context, batch.n_tokens() - 1);
+            sampler.accept(token);
+            if model.is_eog_token(token) {
+                break;
+            }
+            // Bytes, not text: a character can be split across tokens
+            reply.extend(model.token_to_bytes(token, Special::Plaintext)?);
+            generated += 1;
+
+            batch.clear();
+            batch.add(token, position, &[0], true)?;
+            position += 1;
+            context.decode(&mut batch)?;
+        }
+
+        let reply = String::from_utf8_lossy(&reply).trim().to_string();
+        Ok((reply, tokens.len() as u32 + generated))
+    }
+}
+
+#[cfg(not(feature = "embedded-llm"))]
+mod backend {
+    use super::*;
+
+    pub(super) fn generate(_path: &str, _messages: &[Message], _options: &Options, _cancel: &Cancel) -> Result<(String, u32)> {
+        Err(anyhow!("ASTRAL was built without the built-in model (enable the `embedded-llm` feature)"))
+    }
+}
+
+/// Reply to `messages` with the model at `embedded_model_path`, loading it
+/// first if it isn't already. There's no JSON mode; the prompt asks for it.
+pub async fn complete(config: &LLMConfig, messages: Vec<Message>) -> Result<LLMResponse> {
+    let path = config.embedded_model_path.trim().to_string();
+    if path.is_empty() {
+        return Err(AstralError::not_configured("Choose a GGUF model file in settings").into());
+    }
+    if !Path::new(&path).is_file() {
+        return Err(AstralError::not_configured(format!("The model file {} doesn't exist", path)).into());
+    }
+
+    let options = Options {
+        context_tokens: if config.context_tokens > 0 { config.context_tokens } else { DEFAULT_CONTEXT_TOKENS },
+        max_tokens: config.max_tokens,
+        temperature: config.temperature,
+    };
+    let model = Path::new(&path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // Dropping this future doesn't stop the blocking thread, so it checks
+    // for cancellation between tokens
+    let cancel = crate::llm_provider::cancel_signal();
+    let (content, tokens_used) =
+        tokio::task::spawn_blocking(move || backend::generate(&path, &messages, &options, &cancel)).await??;
+    Ok(LLMResponse {
+        content,
+        model,
+        tokens_used: Some(tokens_used),
+    })
+}
@@ -0,0 +1,175 @@
+// Error Module
+// `AstralError`, the error commands return to the UI: a stable `code` the
+// frontend can map to its own (localized) text, a short `message` fit to
+// show as is, and an optional `detail` with the underlying cause for logs
+// and bug reports. It serializes as `{ code, message, detail }`, plus
+// `retry_after_secs` when a provider said how long to wait.
+//
+// Commands move over to it one module at a time; `From<String>` and
+// `From<AstralError> for String` let `?` cross between the two styles while
+// both exist.
+
+use reqwest::StatusCode;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Something has to be set up first, e.g. an API key or enabling a feature
+    NotConfigured,
+    /// A local service (Ollama, the Whisper server) isn't reachable
+    ServiceUnavailable,
+    /// An internet request failed
+    Network,
+    Timeout,
+    /// The provider rejected the API key
+    Unauthorized,
+    RateLimited,
+    /// The provider answered with an error of its own
+    ProviderError,
+    InvalidInput,
+    NotFound,
+    PermissionDenied,
+    /// The microphone is muted, so nothing was recorded
+    Muted,
+    /// The user called it off
+    Cancelled,
+    /// Privacy mode refused to contact a cloud service
+    PrivacyMode,
+    /// This month's spending cap for cloud models is used up
+    SpendingCap,
+    Internal,
+}
+
+#[derive(Debug, Clone, Serialize, thiserror::Error)]
+#[error("{message}")]
+pub struct AstralError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// From a rate-limited or overloaded provider's Retry-After
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+}
+
+pub type AstralResult<T> = Result<T, AstralError>;
+
+impl AstralError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            detail: None,
+            retry_after_secs: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_retry_after(mut self, wait: Option<std::time::Duration>) -> Self {
+        // Round up, so "0 seconds" never shows
+        self.retry_after_secs = wait.map(|w| w.as_secs() + u64::from(w.subsec_nanos() > 0));
+        self
+    }
+
+    pub fn not_configured(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotConfigured, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    /// A provider's HTTP error response; `body` is kept as the detail
+    pub fn from_status(service: &str, status: StatusCode, body: &str) -> Self {
+        let (code, message) = match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                (ErrorCode::Unauthorized, format!("{} rejected the API key", service))
+            }
+            StatusCode::TOO_MANY_REQUESTS => {
+                (ErrorCode::RateLimited, format!("{} is rate limiting requests; try again shortly", service))
+            }
+            StatusCode::NOT_FOUND => (ErrorCode::NotFound, format!("{} doesn't know the requested model or voice", service)),
+            status if status.is_server_error() => {
+                (ErrorCode::ProviderError, format!("{} is having problems right now", service))
+            }
+            _ => (ErrorCode::ProviderError, format!("{} refused the request", service)),
+        };
+        Self::new(code, message).with_detail(format!("HTTP {}: {}", status, body.trim()))
+    }
+
+    fn from_reqwest(error: &reqwest::Error) -> Self {
+        let (code, message) = if error.is_timeout() {
+            (ErrorCode::Timeout, "The request timed out")
+        } else if error.is_connect() {
+            (ErrorCode::ServiceUnavailable, "Couldn't connect to the service")
+        } else if let Some(status) = error.status() {
+            return Self::from_status("The service", status, "");
+        } else {
+            (ErrorCode::Network, "The request failed")
+        };
+        Self::new(code, message).with_detail(error.to_string())
+    }
+}
+
+/// Messages from commands that still build their errors as strings
+impl From<String> for AstralError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<&str> for AstralError {
+    fn from(message: &str) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<AstralError> for String {
+    fn from(error: AstralError) -> Self {
+        error.message
+    }
+}
+
+impl From<reqwest::Error> for AstralError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::from_reqwest(&error)
+    }
+}
+
+impl From<std::io::Error> for AstralError {
+    fn from(error: std::io::Error) -> Self {
+        let code = match error.kind() {
+            std::io::ErrorKind::NotFound => ErrorCode::NotFound,
+            std::io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+            _ => ErrorCode::Internal,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AstralError {
+    fn from(error: tokio::task::JoinError) -> Self {
+        Self::new(ErrorCode::Internal, "A background task failed").with_detail(error.to_string())
+    }
+}
+
+/// Keeps an `AstralError` raised inside anyhow code as is; otherwise the
+/// outermost context becomes the message and the cause chain the detail
+impl From<anyhow::Error> for AstralError {
+    fn from(error: anyhow::Error) -> Self {
+        if let Some(astral) = error.downcast_ref::<AstralError>() {
+            return astral.clone();
+        }
+
+        let code = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<reqwest::Error>())
+            .map_or(ErrorCode::Internal, |e| AstralError::from_reqwest(e).code);
+        Self::new(code, error.to_string()).with_detail(format!("{:#}", error))
+    }
+}
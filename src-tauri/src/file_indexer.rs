@@ -0,0 +1,511 @@
+// File Indexer Module
+// Keeps a persistent index of files in the folders of the `file_index`
+// settings section. The index is loaded from disk at startup, reconciled with
+// a rescan, and kept current by filesystem watchers. File search and document
+// retrieval both query it.
+
+use tracing::{info, warn};
+use notify::{Config as WatcherConfig, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+/// How long watcher changes may sit in memory before the index is written out
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Extensions treated as readable text documents (the retrieval corpus)
+const TEXT_EXTENSIONS: &[&str] = &[
+    "txt", "md", "markdown", "rst", "org", "csv", "json", "yaml", "yml", "toml", "log", "html", "htm",
+    "pdf", "docx", "odt", "rtf",
+];
+
+/// The `file_index` settings section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexerConfig {
+    /// Folders to index, recursively
+    pub folders: Vec<PathBuf>,
+    /// Directory names skipped anywhere in the tree (hidden directories are always skipped)
+    pub excluded_dirs: Vec<String>,
+    /// Upper bound on indexed files so a huge folder can't eat all memory
+    pub max_files: usize,
+    /// Keep the index current with filesystem watchers
+    pub watch: bool,
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        let folders = [
+            dirs::desktop_dir(),
+            dirs::document_dir(),
+            dirs::download_dir(),
+            dirs::picture_dir(),
+            dirs::audio_dir(),
+            dirs::video_dir(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        Self {
+            folders,
+            excluded_dirs: ["node_modules", "target", "AppData", "__pycache__", "$Recycle.Bin", "Library", "venv"]
+                .iter()
+                .map(|d| d.to_string())
+                .collect(),
+            max_files: 250_000,
+            watch: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedFile {
+    pub path: String,
+    pub name: String,
+    /// Lowercase, without the dot
+    pub extension: Option<String>,
+    pub size: u64,
+    /// Unix timestamps (seconds)
+    pub modified: Option<u64>,
+    pub created: Option<u64>,
+    /// When this entry was last added or updated in the index
+    pub indexed_at: u64,
+}
+
+impl IndexedFile {
+    /// Whether the file is a document the retrieval pipeline can read
+    pub fn is_text_document(&self) -> bool {
+        self.extension
+            .as_deref()
+            .is_some_and(|e| TEXT_EXTENSIONS.contains(&e))
+    }
+}
+
+/// Filters for `query`; every field is optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileIndexQuery {
+    /// Case-insensitive substring of the file name
+    pub name: Option<String>,
+    pub extensions: Vec<String>,
+    /// Only files under this folder
+    pub folder: Option<String>,
+    pub modified_after: Option<u64>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexStats {
+    pub files: usize,
+    pub total_size: u64,
+    pub folders: Vec<PathBuf>,
+    pub watching: bool,
+    pub last_scan: Option<u64>,
+}
+
+/// Rescan summary
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanReport {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub total: usize,
+}
+
+/// On-disk form of the index
+#[derive(Default, Serialize, Deserialize)]
+struct StoredIndex {
+    last_scan: Option<u64>,
+    files: Vec<IndexedFile>,
+}
+
+static CONFIG: Lazy<RwLock<IndexerConfig>> = Lazy::new(|| RwLock::new(IndexerConfig::default()));
+static INDEX: Lazy<RwLock<HashMap<String, IndexedFile>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+static LAST_SCAN: Lazy<Mutex<Option<u64>>> = Lazy::new(|| Mutex::new(None));
+static WATCHER: Lazy<Mutex<Option<RecommendedWatcher>>> = Lazy::new(|| Mutex::new(None));
+static DIRTY: AtomicBool = AtomicBool::new(false);
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn astral_dir() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    Some(path)
+}
+
+fn index_path() -> Option<PathBuf> {
+    astral_dir().map(|p| p.join("file_index.json"))
+}
+
+fn write_json<T: Serialize>(path: Option<PathBuf>, value: &T) -> Result<(), String> {
+    let path = path.ok_or_else(|| "Could not find config directory".to_string())?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+
+    let content = serde_json::to_string(value).map_err(|e| format!("Failed to serialize: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Current indexer configuration
+pub fn config() -> IndexerConfig {
+    CONFIG.read().map(|c| c.clone()).unwrap_or_default()
+}
+
+fn load_index() {
+    let stored: StoredIndex = index_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    if let Ok(mut last_scan) = LAST_SCAN.lock() {
+        *last_scan = stored.last_scan;
+    }
+    if let Ok(mut index) = INDEX.write() {
+        *index = stored.files.into_iter().map(|f| (f.path.clone(), f)).collect();
+    }
+}
+
+/// Write the index to disk. Blocking.
+pub fn save_index() -> Result<(), String> {
+    let stored = StoredIndex {
+        last_scan: LAST_SCAN.lock().ok().and_then(|s| *s),
+        files: INDEX.read().map(|i| i.values().cloned().collect()).unwrap_or_default(),
+    };
+
+    DIRTY.store(false, Ordering::Release);
+    write_json(index_path(), &stored)
+}
+
+fn is_excluded_dir(name: &str, config: &IndexerConfig) -> bool {
+    name.starts_with('.') || config.excluded_dirs.iter().any(|d| d.eq_ignore_ascii_case(name))
+}
+
+/// Whether a file or directory falls inside an indexed folder and outside any
+/// excluded directory
+fn is_indexable(path: &Path, is_dir: bool, config: &IndexerConfig) -> bool {
+    let Some(root) = config.folders.iter().find(|root| path.starts_with(root)) else {
+        return false;
+    };
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+
+    // A file's own name may start with a dot; only its directories are checked
+    let dirs = if is_dir { Some(relative) } else { relative.parent() };
+    dirs.is_none_or(|dirs| {
+        !dirs
+            .components()
+            .any(|c| is_excluded_dir(&c.as_os_str().to_string_lossy(), config))
+    })
+}
+
+fn to_timestamp(time: std::io::Result<SystemTime>) -> Option<u64> {
+    time.ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+}
+
+/// Build an index entry from a file's metadata
+pub fn entry_for(path: &Path, metadata: &std::fs::Metadata) -> Option<IndexedFile> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+
+    Some(IndexedFile {
+        path: path.to_string_lossy().to_string(),
+        name,
+        extension: path.extension().map(|e| e.to_string_lossy().to_lowercase()),
+        size: metadata.len(),
+        modified: to_timestamp(metadata.modified()),
+        created: to_timestamp(metadata.created()),
+        indexed_at: now_secs(),
+    })
+}
+
+fn walk_folder(root: &Path, config: &IndexerConfig, mut visit: impl FnMut(IndexedFile) -> bool) {
+    let walker = WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_type().is_dir() || !is_excluded_dir(&e.file_name().to_string_lossy(), config));
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if let Some(file) = entry_for(entry.path(), &metadata) {
+            if !visit(file) {
+                return;
+            }
+        }
+    }
+}
+
+/// Walk all folders and reconcile the index with what's on disk: new files are
+/// added, changed ones updated, vanished ones dropped. Blocking.
+pub fn rescan() -> ScanReport {
+    let config = config();
+    let mut seen: HashMap<String, IndexedFile> = HashMap::new();
+
+    'folders: for root in &config.folders {
+        let mut limit_hit = false;
+        walk_folder(root, &config, |file| {
+            seen.insert(file.path.clone(), file);
+            limit_hit = seen.len() >= config.max_files;
+            !limit_hit
+        });
+        if limit_hit {
+            warn!("File index limit reached ({} files)", config.max_files);
+            break 'folders;
+        }
+    }
+
+    let mut report = ScanReport::default();
+    if let Ok(mut index) = INDEX.write() {
+        let before = index.len();
+        index.retain(|path, _| seen.contains_key(path));
+        report.removed = before - index.len();
+
+        for (path, mut file) in seen {
+            match index.get(&path) {
+                Some(existing) if existing.size == file.size && existing.modified == file.modified => {
+                    // Unchanged: keep the original indexed_at so change tracking stays accurate
+                    file.indexed_at = existing.indexed_at;
+                }
+                Some(_) => report.updated += 1,
+                None => report.added += 1,
+            }
+            index.insert(path, file);
+        }
+
+        report.total = index.len();
+    }
+
+    if let Ok(mut last_scan) = LAST_SCAN.lock() {
+        *last_scan = Some(now_secs());
+    }
+    if let Err(e) = save_index() {
+        warn!("Failed to save file index: {}", e);
+    }
+
+    info!(
+        "File index rescanned: {} files (+{} ~{} -{})",
+        report.total, report.added, report.updated, report.removed
+    );
+    report
+}
+
+/// Re-check one path reported by the watcher: a file is (re)indexed, a new
+/// directory is walked, anything that no longer exists is dropped
+fn apply_change(path: &Path, config: &IndexerConfig) {
+    let Ok(mut index) = INDEX.write() else {
+        return;
+    };
+
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() => {
+            if is_indexable(path, false, config) && (index.len() < config.max_files || index.contains_key(&*path.to_string_lossy())) {
+                if let Some(file) = entry_for(path, &metadata) {
+                    index.insert(file.path.clone(), file);
+                }
+            }
+        }
+        Ok(metadata) if metadata.is_dir() => {
+            if is_indexable(path, true, config) {
+                walk_folder(path, config, |file| {
+                    index.insert(file.path.clone(), file);
+                    index.len() < config.max_files
+                });
+            }
+        }
+        Ok(_) => {}
+        Err(_) => {
+            // Removed or renamed away; for directories drop everything beneath
+            let prefix = path.to_string_lossy().to_string();
+            let separator = std::path::MAIN_SEPARATOR;
+            index.retain(|p, _| p != &prefix && !p.starts_with(&format!("{}{}", prefix, separator)));
+        }
+    }
+
+    DIRTY.store(true, Ordering::Release);
+}
+
+/// (Re)start watchers for the configured folders. Replacing the watcher
+/// disconnects the previous event loop, which then exits.
+fn start_watching() -> Result<(), String> {
+    let config = config();
+    let mut slot = WATCHER.lock().map_err(|e| e.to_string())?;
+    *slot = None;
+
+    if !config.watch {
+        return Ok(());
+    }
+
+    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let mut watcher = RecommendedWatcher::new(tx, WatcherConfig::default()).map_err(|e| e.to_string())?;
+
+    for folder in config.folders.iter().filter(|f| f.is_dir()) {
+        if let Err(e) = watcher.watch(folder, RecursiveMode::Recursive) {
+            warn!("Can't watch {}: {}", folder.display(), e);
+        }
+    }
+
+    std::thread::Builder::new()
+        .name("astral-indexer".into())
+        .spawn(move || loop {
+            match rx.recv_timeout(SAVE_INTERVAL) {
+                Ok(Ok(event)) => {
+                    let config = self::config();
+                    for path in &event.paths {
+                        apply_change(path, &config);
+                    }
+                }
+                Ok(Err(e)) => warn!("File watcher error: {}", e),
+                Err(RecvTimeoutError::Timeout) => {
+                    if DIRTY.load(Ordering::Acquire) {
+                        if let Err(e) = save_index() {
+                            warn!("Failed to save file index: {}", e);
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        })
+        .map_err(|e| e.to_string())?;
+
+    *slot = Some(watcher);
+    info!("Watching {} folders for file changes", config.folders.len());
+    Ok(())
+}
+
+/// Load the saved index, then reconcile and start watching in the background
+pub fn init(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        match crate::settings::load_settings(app).await {
+            Ok(settings) => *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = settings.file_index,
+            Err(e) => warn!("Couldn't load settings, indexing the default folders: {}", e),
+        }
+        let result = tokio::task::spawn_blocking(|| {
+            load_index();
+            rescan();
+            start_watching()
+        })
+        .await;
+
+        match result {
+            Ok(Err(e)) => warn!("File watcher unavailable: {}", e),
+            Err(e) => warn!("File indexer failed to start: {}", e),
+            Ok(Ok(())) => {}
+        }
+    });
+}
+
+/// Use `new_config` from now on: re-index and restart the watchers in the
+/// background. Called when the `file_index` section changes.
+pub fn set(new_config: IndexerConfig) {
+    *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = new_config;
+    tauri::async_runtime::spawn(async {
+        let result = tokio::task::spawn_blocking(|| {
+            let report = rescan();
+            info!("File index rebuilt for the new folders: {} files", report.total);
+            start_watching()
+        })
+        .await;
+
+        match result {
+            Ok(Err(e)) => warn!("File watcher unavailable: {}", e),
+            Err(e) => warn!("File indexer failed to restart: {}", e),
+            Ok(Ok(())) => {}
+        }
+    });
+}
+
+/// Run `f` over every indexed file without cloning the whole index
+pub fn with_files<T>(f: impl FnOnce(&mut dyn Iterator<Item = &IndexedFile>) -> T) -> T {
+    let index = INDEX.read().unwrap_or_else(|e| e.into_inner());
+    f(&mut index.values())
+}
+
+/// Filtered listing, most recently modified first
+pub fn query(query: &FileIndexQuery) -> Vec<IndexedFile> {
+    let name = query.name.as_ref().map(|n| n.to_lowercase());
+    let extensions: Vec<String> = query
+        .extensions
+        .iter()
+        .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+        .collect();
+
+    let mut results: Vec<IndexedFile> = with_files(|files| {
+        files
+            .filter(|f| name.as_ref().is_none_or(|n| f.name.to_lowercase().contains(n.as_str())))
+            .filter(|f| extensions.is_empty() || f.extension.as_ref().is_some_and(|e| extensions.contains(e)))
+            .filter(|f| query.folder.as_ref().is_none_or(|dir| Path::new(&f.path).starts_with(dir)))
+            .filter(|f| query.modified_after.is_none_or(|t| f.modified.is_some_and(|m| m >= t)))
+            .filter(|f| query.min_size.is_none_or(|s| f.size >= s))
+            .filter(|f| query.max_size.is_none_or(|s| f.size <= s))
+            .cloned()
+            .collect()
+    });
+
+    results.sort_by(|a, b| b.modified.cmp(&a.modified));
+    results.truncate(query.limit.unwrap_or(100));
+    results
+}
+
+/// Text documents added or changed since `since` (unix seconds), for feeding
+/// the retrieval index incrementally
+pub fn text_documents_changed_since(since: u64) -> Vec<IndexedFile> {
+    with_files(|files| {
+        files
+            .filter(|f| f.is_text_document() && f.indexed_at >= since)
+            .cloned()
+            .collect()
+    })
+}
+
+pub fn stats() -> IndexStats {
+    let (files, total_size) = with_files(|files| files.fold((0, 0), |(n, size), f| (n + 1, size + f.size)));
+
+    IndexStats {
+        files,
+        total_size,
+        folders: config().folders,
+        watching: WATCHER.lock().map(|w| w.is_some()).unwrap_or(false),
+        last_scan: LAST_SCAN.lock().ok().and_then(|s| *s),
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_file_index_stats() -> Result<IndexStats, String> {
+    Ok(stats())
+}
+
+#[tauri::command]
+pub async fn query_file_index(query: FileIndexQuery) -> Result<Vec<IndexedFile>, String> {
+    Ok(self::query(&query))
+}
+
+#[tauri::command]
+pub async fn rebuild_file_index() -> Result<ScanReport, String> {
+    tokio::task::spawn_blocking(rescan)
+        .await
+        .map_err(|e| e.to_string())
+}
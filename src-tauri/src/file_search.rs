@@ -0,0 +1,320 @@
+// File Search Module
+// Finds files by name for "find my tax return pdf" style requests. Uses the
+// Everything CLI (es.exe) when it's installed, otherwise the file indexer.
+
+use tracing::warn;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::file_indexer::{self, IndexedFile};
+
+const DEFAULT_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSearchResult {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub modified: Option<u64>,
+    /// 0.0 - 1.0, higher is a better match
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileSearchQuery {
+    /// Words to match against the file name; may be empty when filtering by extension only
+    pub query: String,
+    /// Only return files with one of these extensions ("pdf", ".docx", ...)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+static EVERYTHING_AVAILABLE: OnceCell<bool> = OnceCell::new();
+
+/// Results of the last spoken search, so "open the second one" can refer back to them
+static LAST_RESULTS: Lazy<Mutex<Vec<FileSearchResult>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+pub fn remember_results(results: &[FileSearchResult]) {
+    if let Ok(mut last) = LAST_RESULTS.lock() {
+        *last = results.to_vec();
+    }
+}
+
+/// `index` is zero-based
+pub fn last_result(index: usize) -> Option<FileSearchResult> {
+    LAST_RESULTS.lock().ok()?.get(index).cloned()
+}
+
+/// Which earlier result "open it" / "open the second one" / "open result 3" means (zero-based)
+pub fn parse_result_reference(command: &str) -> Option<usize> {
+    let lower = command.to_lowercase();
+    let rest = lower.trim().trim_end_matches(['.', '!']).strip_prefix("open ")?;
+
+    if ["it", "that", "that file", "this file", "the file"].contains(&rest) {
+        return Some(0);
+    }
+
+    let ordinals = ["first", "second", "third", "fourth", "fifth"];
+    let words: Vec<&str> = rest.split_whitespace().collect();
+    match words.as_slice() {
+        ["the", ordinal, "one" | "result" | "file" | "match"] | [ordinal, "one" | "result" | "file" | "match"] => {
+            ordinals.iter().position(|o| o == ordinal)
+        }
+        ["result" | "number", n] => n.parse::<usize>().ok().filter(|n| *n > 0).map(|n| n - 1),
+        _ => None,
+    }
+}
+
+/// Whether the Everything command line client (es.exe) is on the PATH
+fn everything_available() -> bool {
+    *EVERYTHING_AVAILABLE.get_or_init(|| {
+        cfg!(target_os = "windows")
+            && Command::new("es")
+                .arg("-version")
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false)
+    })
+}
+
+fn normalize_extensions(extensions: &[String]) -> Vec<String> {
+    extensions
+        .iter()
+        .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+/// How well `name` matches the query words, None if it doesn't match at all
+fn score_name(name: &str, terms: &[String]) -> Option<f32> {
+    if terms.is_empty() {
+        return Some(0.5);
+    }
+
+    let lower = name.to_lowercase();
+    let stem = Path::new(&lower)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| lower.clone());
+    let joined = terms.join(" ");
+
+    if stem == joined {
+        return Some(1.0);
+    }
+    if stem.starts_with(&joined) {
+        return Some(0.9);
+    }
+    if lower.contains(&joined) {
+        return Some(0.8);
+    }
+    if terms.iter().all(|t| lower.contains(t.as_str())) {
+        return Some(0.65);
+    }
+
+    // Typos: compare against the stem as a whole
+    let similarity = strsim::jaro_winkler(&stem, &joined) as f32;
+    if similarity >= 0.88 {
+        return Some(similarity * 0.6);
+    }
+
+    None
+}
+
+/// Nudge recently modified files up among otherwise equal matches
+fn recency_bonus(modified: Option<u64>) -> f32 {
+    let Some(modified) = modified else {
+        return 0.0;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let age_days = now.saturating_sub(modified) / 86_400;
+
+    match age_days {
+        0..=7 => 0.05,
+        8..=30 => 0.03,
+        31..=365 => 0.01,
+        _ => 0.0,
+    }
+}
+
+fn rank(files: impl Iterator<Item = IndexedFile>, terms: &[String], extensions: &[String], limit: usize) -> Vec<FileSearchResult> {
+    let mut results: Vec<FileSearchResult> = files
+        .filter(|f| {
+            extensions.is_empty()
+                || f.extension.as_ref().is_some_and(|e| extensions.contains(e))
+        })
+        .filter_map(|f| {
+            let score = score_name(&f.name, terms)? + recency_bonus(f.modified);
+            Some(FileSearchResult {
+                path: f.path,
+                name: f.name,
+                size: f.size,
+                modified: f.modified,
+                score: score.min(1.0),
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(limit);
+    results
+}
+
+/// Ask Everything for candidates; ranking is still done here so both paths behave the same
+fn search_everything(terms: &[String], extensions: &[String], limit: usize) -> anyhow::Result<Vec<IndexedFile>> {
+    let mut args: Vec<String> = vec!["-n".to_string(), (limit * 5).to_string()];
+    args.extend(terms.iter().cloned());
+    if !extensions.is_empty() {
+        args.push(format!("ext:{}", extensions.join(";")));
+    }
+
+    let output = Command::new("es").args(&args).output()?;
+    if !output.status.success() {
+        anyhow::bail!("es.exe failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .filter_map(|line| {
+            let path = Path::new(line);
+            let metadata = std::fs::metadata(path).ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            file_indexer::entry_for(path, &metadata)
+        })
+        .collect())
+}
+
+/// Search by name and extension, best matches first. Blocking.
+pub fn search(query: &FileSearchQuery) -> Vec<FileSearchResult> {
+    let terms: Vec<String> = query
+        .query
+        .to_lowercase()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    let extensions = normalize_extensions(&query.extensions);
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).max(1);
+
+    if terms.is_empty() && extensions.is_empty() {
+        return Vec::new();
+    }
+
+    if everything_available() {
+        match search_everything(&terms, &extensions, limit) {
+            Ok(files) => return rank(files.into_iter(), &terms, &extensions, limit),
+            Err(e) => warn!("Everything search failed, using local index: {}", e),
+        }
+    }
+
+    file_indexer::with_files(|files| rank(files.cloned(), &terms, &extensions, limit))
+}
+
+/// Pull a search out of phrases like "find my resume pdf" or "search for budget spreadsheets".
+/// Past the explicit "find the file ...", the request has to name a kind of
+/// file or a file name ("budget.xlsx"), so "find my keys" and "search for
+/// flights" aren't file searches.
+pub fn parse_search_command(command: &str) -> Option<FileSearchQuery> {
+    let lower = crate::intents::imperative(command);
+    let explicit = ["find the file", "find file", "search my files for", "search files for"]
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix));
+    let loose = || {
+        ["find my", "find the", "search for", "look for", "where is my", "where's my"]
+            .iter()
+            .find_map(|prefix| lower.strip_prefix(prefix))
+    };
+    let (rest, explicit) = match explicit {
+        Some(rest) => (rest, true),
+        None => (loose()?, false),
+    };
+
+    // Spoken file kinds map to extensions
+    let kinds: &[(&str, &[&str])] = &[
+        ("pdf", &["pdf"]),
+        ("document", &["doc", "docx", "odt", "pdf", "txt"]),
+        ("spreadsheet", &["xls", "xlsx", "ods", "csv"]),
+        ("presentation", &["ppt", "pptx", "odp"]),
+        ("photo", &["jpg", "jpeg", "png", "heic"]),
+        ("picture", &["jpg", "jpeg", "png", "heic"]),
+        ("image", &["jpg", "jpeg", "png", "gif", "webp", "bmp"]),
+        ("video", &["mp4", "mkv", "mov", "avi"]),
+        ("song", &["mp3", "flac", "wav", "m4a"]),
+        ("zip", &["zip", "rar", "7z"]),
+    ];
+
+    let mut extensions: Vec<String> = Vec::new();
+    let mut words = Vec::new();
+    for word in rest.split_whitespace() {
+        let singular = word.trim_end_matches('s');
+        if let Some((_, exts)) = kinds.iter().find(|(kind, _)| *kind == singular || *kind == word) {
+            extensions.extend(exts.iter().map(|e| e.to_string()));
+        } else if !["file", "files", "the", "a", "my", "called", "named", "please"].contains(&word) {
+            words.push(word);
+        }
+    }
+
+    let query = FileSearchQuery {
+        query: words.join(" "),
+        extensions,
+        limit: Some(5),
+    };
+
+    let names_file = !query.extensions.is_empty()
+        || rest.split_whitespace().any(|word| {
+            ["file", "files", "folder"].contains(&word)
+                || word.rsplit_once('.').is_some_and(|(name, ext)| !name.is_empty() && (1..=5).contains(&ext.len()) && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        });
+    if (query.query.is_empty() && query.extensions.is_empty()) || !(explicit || names_file) {
+        None
+    } else {
+        Some(query)
+    }
+}
+
+/// Human-friendly size for spoken replies
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn search_files(
+    query: String,
+    extensions: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> Result<Vec<FileSearchResult>, String> {
+    let query = FileSearchQuery {
+        query,
+        extensions: extensions.unwrap_or_default(),
+        limit,
+    };
+
+    tokio::task::spawn_blocking(move || search(&query))
+        .await
+        .map_err(|e| e.to_string())
+}
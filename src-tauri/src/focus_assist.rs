@@ -0,0 +1,238 @@
+// Focus Assist Module
+// Windows Focus Assist / Do Not Disturb state, readable by routines as a
+// condition ("only speak when Focus Assist is off") and settable by voice
+
+use anyhow::Result;
+use tracing::info;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusAssistMode {
+    Off,
+    /// Only notifications from the priority list
+    PriorityOnly,
+    AlarmsOnly,
+}
+
+impl FocusAssistMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "off" | "disabled" => Some(Self::Off),
+            "priority" | "priority_only" | "on" => Some(Self::PriorityOnly),
+            "alarms" | "alarms_only" => Some(Self::AlarmsOnly),
+            _ => None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        *self != Self::Off
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::PriorityOnly => "priority only",
+            Self::AlarmsOnly => "alarms only",
+        }
+    }
+}
+
+/// A spoken Focus Assist request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusAssistCommand {
+    Query,
+    Set(FocusAssistMode),
+}
+
+impl FocusAssistCommand {
+    /// "turn on focus assist", "do not disturb off", "is focus assist on?"
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.to_lowercase();
+        if !(lower.contains("focus assist") || lower.contains("do not disturb") || lower.contains("focus mode")) {
+            return None;
+        }
+
+        let words: Vec<&str> = lower.split(|c: char| !c.is_alphanumeric()).collect();
+        if lower.starts_with("is ") || lower.contains("status") {
+            return Some(Self::Query);
+        }
+        if lower.contains("alarms only") {
+            return Some(Self::Set(FocusAssistMode::AlarmsOnly));
+        }
+        if words.iter().any(|w| ["off", "disable", "deactivate", "stop"].contains(w)) {
+            return Some(Self::Set(FocusAssistMode::Off));
+        }
+        if words.iter().any(|w| ["on", "enable", "activate", "start"].contains(w)) {
+            return Some(Self::Set(FocusAssistMode::PriorityOnly));
+        }
+        Some(Self::Query)
+    }
+}
+
+/// Current Focus Assist mode. Blocking.
+pub fn mode() -> Result<FocusAssistMode> {
+    platform::mode()
+}
+
+/// Blocking.
+pub fn set_mode(mode: FocusAssistMode) -> Result<()> {
+    info!("Setting Focus Assist to {}", mode.label());
+    platform::set_mode(mode)
+}
+
+pub fn apply_command(command: FocusAssistCommand) -> Result<String> {
+    match command {
+        FocusAssistCommand::Query => Ok(format!("Focus Assist is {}", mode()?.label())),
+        FocusAssistCommand::Set(target) => {
+            set_mode(target)?;
+            Ok(format!("Focus Assist is now {}", target.label()))
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::FocusAssistMode;
+    use anyhow::{bail, Result};
+    use std::ffi::c_void;
+
+    // Focus Assist has no public API; the shell publishes the active quiet
+    // hours profile through this Windows Notification Facility state
+    const WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED: u64 = 0x0D83_063E_A3BF_1C75;
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtQueryWnfStateData(
+            state_name: *const u64,
+            type_id: *const c_void,
+            explicit_scope: *const c_void,
+            change_stamp: *mut u32,
+            buffer: *mut c_void,
+            buffer_size: *mut u32,
+        ) -> i32;
+
+        fn NtUpdateWnfStateData(
+            state_name: *const u64,
+            buffer: *const c_void,
+            length: u32,
+            type_id: *const c_void,
+            explicit_scope: *const c_void,
+            matching_change_stamp: u32,
+            check_stamp: u32,
+        ) -> i32;
+    }
+
+    pub fn mode() -> Result<FocusAssistMode> {
+        let mut change_stamp = 0u32;
+        let mut profile = 0u32;
+        let mut size = std::mem::size_of::<u32>() as u32;
+
+        let status = unsafe {
+            NtQueryWnfStateData(
+                &WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED,
+                std::ptr::null(),
+                std::ptr::null(),
+                &mut change_stamp,
+                &mut profile as *mut u32 as *mut c_void,
+                &mut size,
+            )
+        };
+        if status < 0 {
+            bail!("Couldn't read Focus Assist state (NTSTATUS {:#x})", status);
+        }
+
+        Ok(match profile {
+            1 => FocusAssistMode::PriorityOnly,
+            2 => FocusAssistMode::AlarmsOnly,
+            _ => FocusAssistMode::Off,
+        })
+    }
+
+    pub fn set_mode(mode: FocusAssistMode) -> Result<()> {
+        let profile: u32 = match mode {
+            FocusAssistMode::Off => 0,
+            FocusAssistMode::PriorityOnly => 1,
+            FocusAssistMode::AlarmsOnly => 2,
+        };
+
+        let status = unsafe {
+            NtUpdateWnfStateData(
+                &WNF_SHEL_QUIETHOURS_ACTIVE_PROFILE_CHANGED,
+                &profile as *const u32 as *const c_void,
+                std::mem::size_of::<u32>() as u32,
+                std::ptr::null(),
+                std::ptr::null(),
+                0,
+                0,
+            )
+        };
+        if status < 0 {
+            bail!("Couldn't change Focus Assist (NTSTATUS {:#x})", status);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::FocusAssistMode;
+    use anyhow::{bail, Result};
+
+    // macOS Focus modes can only be changed by the user or through Shortcuts
+    pub fn mode() -> Result<FocusAssistMode> {
+        bail!("Focus modes can't be read on macOS")
+    }
+
+    pub fn set_mode(_mode: FocusAssistMode) -> Result<()> {
+        bail!("Focus modes can't be changed on macOS")
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::FocusAssistMode;
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    // GNOME's Do Not Disturb is "show-banners = false"; it has no priority list
+    const SCHEMA: &str = "org.gnome.desktop.notifications";
+
+    pub fn mode() -> Result<FocusAssistMode> {
+        let output = Command::new("gsettings").args(["get", SCHEMA, "show-banners"]).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let banners = String::from_utf8_lossy(&output.stdout).trim() == "true";
+        Ok(if banners { FocusAssistMode::Off } else { FocusAssistMode::PriorityOnly })
+    }
+
+    pub fn set_mode(mode: FocusAssistMode) -> Result<()> {
+        let banners = if mode.is_active() { "false" } else { "true" };
+        let status = Command::new("gsettings").args(["set", SCHEMA, "show-banners", banners]).status()?;
+        if !status.success() {
+            bail!("gsettings exited with {}", status);
+        }
+        Ok(())
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_focus_assist() -> Result<FocusAssistMode, String> {
+    tokio::task::spawn_blocking(mode)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// `mode` is "off", "priority_only" or "alarms_only"
+#[tauri::command]
+pub async fn set_focus_assist(mode: String) -> Result<(), String> {
+    let mode = FocusAssistMode::parse(&mode).ok_or_else(|| format!("Unknown Focus Assist mode: {}", mode))?;
+    tokio::task::spawn_blocking(move || set_mode(mode))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,475 @@
+// Home Assistant Module
+// Drives an existing Home Assistant install through its REST API with a
+// long-lived access token (Profile -> Security in Home Assistant), so "turn
+// off the living room lights" or "is the front door locked" work without
+// setting anything up twice. Off unless `home_assistant.enabled`. The entity
+// and area names are cached and refreshed every few minutes; a spoken
+// command is only claimed when it names one of them, so "turn off the wifi"
+// still reaches the Wi-Fi handler. Routines call services with the
+// `HomeAssistant` action, and LLM clients get the `home_assistant_states`
+// and `home_assistant_call_service` MCP tools; the latter only reaches the
+// domains in `TOOL_DOMAINS`. Spoken commands that could open the house up
+// (locks, opening covers, alarms) ask "are you sure" first.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// How often the entity and area names are reloaded
+const DIRECTORY_REFRESH: Duration = Duration::from_secs(600);
+
+/// How close a spoken name has to be to an entity's name
+const MATCH_THRESHOLD: f64 = 0.8;
+
+/// Domains tool callers (MCP clients, ASTRAL's own LLM) may call services in.
+/// Locks, covers, alarms, scripts and automations stay with the user: a web
+/// page or email the model read could otherwise open the front door.
+const TOOL_DOMAINS: &[&str] = &["light", "switch", "fan", "media_player", "climate", "humidifier", "scene", "input_boolean", "vacuum"];
+
+/// Area ids and names, as JSON pairs; the REST API has no area endpoint
+const AREAS_TEMPLATE: &str =
+    "{% set ns = namespace(areas=[]) %}{% for id in areas() %}{% set ns.areas = ns.areas + [[id, area_name(id)]] %}{% endfor %}{{ ns.areas | tojson }}";
+
+/// The `home_assistant` settings section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HomeAssistantSettings {
+    pub enabled: bool,
+    /// e.g. http://homeassistant.local:8123
+    pub url: String,
+    /// Long-lived access token
+    pub token: String,
+}
+
+impl Default for HomeAssistantSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "http://homeassistant.local:8123".to_string(),
+            token: String::new(),
+        }
+    }
+}
+
+impl HomeAssistantSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.url = self.url.trim().trim_end_matches('/').to_string();
+        self.token = self.token.trim().to_string();
+        if self.enabled && !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return Err(format!("Invalid Home Assistant address: {}", self.url));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityState {
+    pub entity_id: String,
+    pub state: String,
+    pub name: String,
+    pub attributes: Value,
+}
+
+impl EntityState {
+    fn from_json(value: &Value) -> Option<Self> {
+        let entity_id = value.get("entity_id")?.as_str()?.to_string();
+        let attributes = value.get("attributes").cloned().unwrap_or(Value::Null);
+        let name = attributes
+            .get("friendly_name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| entity_id.split('.').nth(1).unwrap_or(&entity_id).replace('_', " "));
+        Some(Self {
+            state: value.get("state").and_then(Value::as_str).unwrap_or("unknown").to_string(),
+            entity_id,
+            name,
+            attributes,
+        })
+    }
+
+    pub fn domain(&self) -> &str {
+        self.entity_id.split('.').next().unwrap_or_default()
+    }
+
+    /// "The front door is locked", "Bedroom temperature is 21.5 °C"
+    pub fn describe(&self) -> String {
+        let unit = self.attributes.get("unit_of_measurement").and_then(Value::as_str);
+        match (unit, self.state.as_str()) {
+            (_, "unavailable" | "unknown") => format!("{} isn't reporting right now.", self.name),
+            (Some(unit), state) => format!("{} is {} {}.", self.name, state, unit),
+            (None, state) => format!("{} is {}.", self.name, state.replace('_', " ")),
+        }
+    }
+}
+
+/// Entity and area names, for matching spoken commands
+#[derive(Debug, Default)]
+struct Directory {
+    /// (entity id, lowercase friendly name)
+    entities: Vec<(String, String)>,
+    /// (area id, lowercase name)
+    areas: Vec<(String, String)>,
+}
+
+static DIRECTORY: Lazy<RwLock<Directory>> = Lazy::new(|| RwLock::new(Directory::default()));
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+// ========== REST Client ==========
+
+async fn settings(app: &AppHandle) -> AstralResult<HomeAssistantSettings> {
+    let settings = crate::settings::load_settings(app.clone()).await?.home_assistant;
+    if !settings.enabled {
+        return Err(AstralError::not_configured("Home Assistant is off; connect it in Settings"));
+    }
+    if settings.token.is_empty() {
+        return Err(AstralError::not_configured("Add a Home Assistant long-lived access token in Settings"));
+    }
+    Ok(settings)
+}
+
+async fn request(settings: &HomeAssistantSettings, method: reqwest::Method, path: &str, body: Option<Value>) -> AstralResult<Value> {
+    let mut request = crate::proxy::client()
+        .request(method, format!("{}/api/{}", settings.url, path))
+        .bearer_auth(&settings.token)
+        .timeout(Duration::from_secs(10));
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    let response = crate::privacy::send(request)
+        .await
+        .map_err(|e| AstralError::from(e.context("Couldn't reach Home Assistant")))?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(AstralError::new(ErrorCode::NotFound, format!("Home Assistant doesn't know {}", path)));
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("Home Assistant", status, &body));
+    }
+    let text = response.text().await?;
+    Ok(serde_json::from_str(&text).unwrap_or(Value::String(text)))
+}
+
+pub async fn states(app: &AppHandle) -> AstralResult<Vec<EntityState>> {
+    let settings = settings(app).await?;
+    let body = request(&settings, reqwest::Method::GET, "states", None).await?;
+    Ok(body.as_array().map(|all| all.iter().filter_map(EntityState::from_json).collect()).unwrap_or_default())
+}
+
+pub async fn state(app: &AppHandle, entity_id: &str) -> AstralResult<EntityState> {
+    let settings = settings(app).await?;
+    let body = request(&settings, reqwest::Method::GET, &format!("states/{}", entity_id), None).await?;
+    EntityState::from_json(&body).ok_or_else(|| AstralError::new(ErrorCode::ProviderError, "Home Assistant sent an unreadable state"))
+}
+
+/// Call `service` ("light.turn_off") with `data`, which holds the target
+/// (`entity_id` or `area_id`) and any service fields
+pub async fn call_service(app: &AppHandle, service: &str, data: Value) -> AstralResult<()> {
+    let (domain, name) = service
+        .split_once('.')
+        .filter(|(d, s)| !d.is_empty() && !s.is_empty())
+        .ok_or_else(|| AstralError::invalid_input(format!("Services look like light.turn_on, not {}", service)))?;
+    let settings = settings(app).await?;
+    info!("Calling Home Assistant service {} with {}", service, data);
+    request(&settings, reqwest::Method::POST, &format!("services/{}/{}", domain, name), Some(data)).await?;
+    Ok(())
+}
+
+/// Locking and unlocking, opening a cover (a garage door) and anything on an
+/// alarm panel: the services a misheard command or a planted instruction
+/// mustn't run unasked
+pub fn is_sensitive(service: &str) -> bool {
+    let Some((domain, name)) = service.split_once('.') else { return false };
+    match domain {
+        "lock" | "alarm_control_panel" => true,
+        "cover" => !name.starts_with("close") && !name.starts_with("stop"),
+        _ => false,
+    }
+}
+
+/// Whether a tool caller may call `service`
+pub fn tool_may_call(service: &str) -> bool {
+    service.split_once('.').is_some_and(|(domain, _)| TOOL_DOMAINS.contains(&domain)) && !is_sensitive(service)
+}
+
+/// Reload the entity and area names
+async fn refresh_directory(app: &AppHandle) -> AstralResult<()> {
+    let entities: Vec<(String, String)> = states(app)
+        .await?
+        .into_iter()
+        .map(|e| (e.entity_id, e.name.to_lowercase()))
+        .collect();
+    let settings = settings(app).await?;
+    let areas = match request(&settings, reqwest::Method::POST, "template", Some(json!({ "template": AREAS_TEMPLATE }))).await {
+        Ok(body) => {
+            // The template endpoint answers with text, which holds the JSON
+            let pairs: Vec<(String, String)> = body
+                .as_str()
+                .and_then(|text| serde_json::from_str(text).ok())
+                .or_else(|| serde_json::from_value(body).ok())
+                .unwrap_or_default();
+            pairs.into_iter().map(|(id, name)| (id, name.to_lowercase())).collect()
+        }
+        Err(e) => {
+            warn!("Couldn't list Home Assistant areas: {}", e);
+            Vec::new()
+        }
+    };
+    info!("Home Assistant: {} entities, {} areas", entities.len(), areas.len());
+    *DIRECTORY.write().unwrap_or_else(|e| e.into_inner()) = Directory { entities, areas };
+    Ok(())
+}
+
+/// Reload the names right away, e.g. after the settings change
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Keep the entity and area names current; called from setup
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) if settings.home_assistant.enabled => {
+                    if let Err(e) = refresh_directory(&app).await {
+                        warn!("Couldn't load Home Assistant entities: {}", e);
+                    }
+                }
+                Ok(_) => *DIRECTORY.write().unwrap_or_else(|e| e.into_inner()) = Directory::default(),
+                Err(e) => warn!("Couldn't load Home Assistant settings: {}", e),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(DIRECTORY_REFRESH) => {}
+                _ = WAKE.notified() => {}
+            }
+        }
+    });
+}
+
+// ========== Voice Commands ==========
+
+/// What a spoken name resolved to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HomeTarget {
+    Entity { entity_id: String },
+    /// Every `domain` entity in the area ("the living room lights")
+    Area { area_id: String, domain: String },
+}
+
+impl HomeTarget {
+    fn domain(&self) -> &str {
+        match self {
+            HomeTarget::Entity { entity_id } => entity_id.split('.').next().unwrap_or_default(),
+            HomeTarget::Area { domain, .. } => domain,
+        }
+    }
+
+    fn data(&self) -> Value {
+        match self {
+            HomeTarget::Entity { entity_id } => json!({ "entity_id": entity_id }),
+            HomeTarget::Area { area_id, .. } => json!({ "area_id": area_id }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HomeCommand {
+    /// "turn on the kitchen light", "unlock the front door"
+    Switch { target: HomeTarget, label: String, on: bool },
+    /// "set the bedroom lights to 40%"
+    Brightness { target: HomeTarget, label: String, percent: u8 },
+    /// "is the front door locked", "what's the bedroom temperature"
+    Query { entity_id: String },
+}
+
+/// Entity domain a word points at ("lights" -> light)
+fn domain_for(word: &str) -> Option<&'static str> {
+    Some(match word.trim().trim_end_matches('s') {
+        "light" | "lamp" => "light",
+        "fan" => "fan",
+        "switch" | "plug" | "socket" => "switch",
+        "blind" | "shade" | "curtain" | "cover" => "cover",
+        "heating" | "thermostat" | "heater" | "air conditioning" | "ac" => "climate",
+        "tv" | "speaker" => "media_player",
+        _ => return None,
+    })
+}
+
+fn strip_articles(text: &str) -> &str {
+    let text = text.trim().trim_end_matches(|c: char| c.is_ascii_punctuation()).trim();
+    ["the ", "my ", "all the ", "all "]
+        .iter()
+        .find_map(|a| text.strip_prefix(a))
+        .unwrap_or(text)
+        .trim()
+}
+
+/// Match a spoken name against the cached entities and areas
+fn resolve(name: &str) -> Option<HomeTarget> {
+    let name = strip_articles(name);
+    if name.is_empty() {
+        return None;
+    }
+    let directory = DIRECTORY.read().unwrap_or_else(|e| e.into_inner());
+
+    // "living room lights": an area followed by what kind of thing
+    for (area_id, area) in &directory.areas {
+        if let Some(rest) = name.strip_prefix(area.as_str()) {
+            let domain = if rest.trim().is_empty() { Some("light") } else { domain_for(rest) };
+            if let Some(domain) = domain {
+                return Some(HomeTarget::Area { area_id: area_id.clone(), domain: domain.to_string() });
+            }
+        }
+    }
+
+    let singular = name.trim_end_matches('s');
+    directory
+        .entities
+        .iter()
+        .map(|(id, friendly)| {
+            let score = if friendly == name || friendly == singular {
+                1.0
+            } else {
+                strsim::normalized_levenshtein(friendly, name)
+            };
+            (score, id)
+        })
+        .filter(|(score, _)| *score >= MATCH_THRESHOLD)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, id)| HomeTarget::Entity { entity_id: id.clone() })
+}
+
+impl HomeCommand {
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.to_lowercase();
+        let lower = lower.trim().trim_end_matches(['?', '.', '!']);
+
+        for (prefix, on) in [
+            ("turn on ", true),
+            ("switch on ", true),
+            ("turn off ", false),
+            ("switch off ", false),
+            ("unlock ", false),
+            ("lock ", true),
+            ("open ", true),
+            ("close ", false),
+        ] {
+            if let Some(rest) = lower.strip_prefix(prefix) {
+                let target = resolve(rest)?;
+                // "open" and "close" only make sense for covers, "lock" for locks
+                let fits = match prefix.trim() {
+                    "open" | "close" => target.domain() == "cover",
+                    "lock" | "unlock" => target.domain() == "lock",
+                    _ => true,
+                };
+                return fits.then(|| HomeCommand::Switch { target, label: strip_articles(rest).to_string(), on });
+            }
+        }
+        // "turn the kitchen light off"
+        for (prefix, suffix, on) in [("turn ", " on", true), ("turn ", " off", false), ("switch ", " on", true), ("switch ", " off", false)] {
+            if let Some(name) = lower.strip_prefix(prefix).and_then(|r| r.strip_suffix(suffix)) {
+                if let Some(target) = resolve(name) {
+                    return Some(HomeCommand::Switch { target, label: strip_articles(name).to_string(), on });
+                }
+            }
+        }
+        if let Some(rest) = ["set ", "dim "].iter().find_map(|p| lower.strip_prefix(p)) {
+            let (name, level) = rest.rsplit_once(" to ")?;
+            let percent: u8 = level.trim().trim_end_matches('%').trim_end_matches(" percent").trim().parse().ok()?;
+            let target = resolve(name)?;
+            return (target.domain() == "light")
+                .then(|| HomeCommand::Brightness { target, label: strip_articles(name).to_string(), percent: percent.min(100) });
+        }
+        for prefix in ["is ", "are ", "what's ", "what is "] {
+            let Some(rest) = lower.strip_prefix(prefix) else { continue };
+            // "is the front door locked" -> "the front door"; "what's the bedroom temperature" as is
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            let candidates = [words.join(" "), words[..words.len().saturating_sub(1)].join(" ")];
+            for candidate in candidates.iter().filter(|c| !c.is_empty()) {
+                if let Some(HomeTarget::Entity { entity_id }) = resolve(candidate) {
+                    return Some(HomeCommand::Query { entity_id });
+                }
+            }
+        }
+        None
+    }
+}
+
+impl HomeCommand {
+    /// "Are you sure you want to unlock the front door?" for a sensitive
+    /// service, None when the command can just run
+    pub fn confirmation(&self) -> Option<String> {
+        let HomeCommand::Switch { target, label, on } = self else { return None };
+        if !is_sensitive(&switch_service(target.domain(), *on)) {
+            return None;
+        }
+        let verb = match (target.domain(), on) {
+            ("lock", true) => "lock",
+            ("lock", false) => "unlock",
+            ("cover", _) => "open",
+            (_, true) => "turn on",
+            (_, false) => "turn off",
+        };
+        Some(format!("Are you sure you want to {} {}?", verb, label))
+    }
+}
+
+fn switch_service(domain: &str, on: bool) -> String {
+    let service = match (domain, on) {
+        ("lock", true) => "lock",
+        ("lock", false) => "unlock",
+        ("cover", true) => "open_cover",
+        ("cover", false) => "close_cover",
+        ("scene" | "script", _) => "turn_on",
+        (_, true) => "turn_on",
+        (_, false) => "turn_off",
+    };
+    format!("{}.{}", domain, service)
+}
+
+/// Carry out a spoken command and say what happened
+pub async fn handle(app: &AppHandle, command: HomeCommand) -> String {
+    let result = match command {
+        HomeCommand::Switch { target, label, on } => {
+            let service = switch_service(target.domain(), on);
+            call_service(app, &service, target.data()).await.map(|_| match target.domain() {
+                "lock" => format!("{} {}.", if on { "Locked" } else { "Unlocked" }, label),
+                "cover" => format!("{} {}.", if on { "Opening" } else { "Closing" }, label),
+                "scene" | "script" => format!("Started {}.", label),
+                _ => format!("Turned {} {}.", if on { "on" } else { "off" }, label),
+            })
+        }
+        HomeCommand::Brightness { target, label, percent } => {
+            let mut data = target.data();
+            data["brightness_pct"] = json!(percent);
+            call_service(app, "light.turn_on", data).await.map(|_| format!("Set {} to {}%.", label, percent))
+        }
+        HomeCommand::Query { entity_id } => state(app, &entity_id).await.map(|s| s.describe()),
+    };
+    result.unwrap_or_else(|e| format!("Home Assistant didn't do that: {}", e))
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_home_assistant_states(app: AppHandle) -> AstralResult<Vec<EntityState>> {
+    states(&app).await
+}
+
+#[tauri::command]
+pub async fn call_home_assistant_service(app: AppHandle, service: String, data: Option<Value>) -> AstralResult<()> {
+    call_service(&app, &service, data.unwrap_or_else(|| json!({}))).await
+}
+
+/// Check the address and token, and reload the names
+#[tauri::command]
+pub async fn test_home_assistant(app: AppHandle) -> AstralResult<usize> {
+    refresh_directory(&app).await?;
+    Ok(DIRECTORY.read().unwrap_or_else(|e| e.into_inner()).entities.len())
+}
@@ -0,0 +1,235 @@
+// Global Hotkeys Module
+// System-wide shortcuts that work even when the AKI window isn't focused.
+// Bindings are accelerator strings ("CmdOrCtrl+Shift+M") kept in settings;
+// rebinding re-registers them all, refusing duplicates and reporting the
+// ones another app already owns.
+
+use crate::state::AppState;
+use tracing::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    ToggleWindow,
+    /// Record while held
+    PushToTalk,
+    StopSpeaking,
+    FavoriteRoutine,
+    ToggleMute,
+    ToggleDictation,
+}
+
+/// Accelerator per action; empty = unbound
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HotkeyBindings {
+    pub toggle_window: String,
+    pub push_to_talk: String,
+    pub stop_speaking: String,
+    /// Runs the `general.favorite_routine` setting
+    pub favorite_routine: String,
+    pub toggle_mute: String,
+    pub toggle_dictation: String,
+}
+
+impl Default for HotkeyBindings {
+    fn default() -> Self {
+        Self {
+            toggle_window: "CmdOrCtrl+Shift+A".to_string(),
+            push_to_talk: "CmdOrCtrl+Shift+Space".to_string(),
+            stop_speaking: "CmdOrCtrl+Shift+Period".to_string(),
+            favorite_routine: String::new(),
+            toggle_mute: "CmdOrCtrl+Shift+M".to_string(),
+            toggle_dictation: "CmdOrCtrl+Shift+D".to_string(),
+        }
+    }
+}
+
+impl HotkeyBindings {
+    fn entries(&self) -> [(HotkeyAction, &str); 6] {
+        [
+            (HotkeyAction::ToggleWindow, self.toggle_window.as_str()),
+            (HotkeyAction::PushToTalk, self.push_to_talk.as_str()),
+            (HotkeyAction::StopSpeaking, self.stop_speaking.as_str()),
+            (HotkeyAction::FavoriteRoutine, self.favorite_routine.as_str()),
+            (HotkeyAction::ToggleMute, self.toggle_mute.as_str()),
+            (HotkeyAction::ToggleDictation, self.toggle_dictation.as_str()),
+        ]
+    }
+
+    /// Parse every bound accelerator, refusing bad ones and duplicates
+    fn parse(&self) -> Result<Vec<(HotkeyAction, String, Shortcut)>, String> {
+        let mut parsed: Vec<(HotkeyAction, String, Shortcut)> = Vec::new();
+        for (action, accelerator) in self.entries() {
+            let accelerator = accelerator.trim();
+            if accelerator.is_empty() {
+                continue;
+            }
+            let shortcut = Shortcut::from_str(accelerator)
+                .map_err(|e| format!("\"{}\" isn't a valid shortcut: {}", accelerator, e))?;
+            if let Some((other, _, _)) = parsed.iter().find(|(_, _, s)| s.id() == shortcut.id()) {
+                return Err(format!("{} is bound to both {:?} and {:?}", accelerator, other, action));
+            }
+            parsed.push((action, accelerator.to_string(), shortcut));
+        }
+        Ok(parsed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyStatus {
+    pub action: HotkeyAction,
+    pub shortcut: String,
+    pub registered: bool,
+    /// Why it isn't registered, usually another app holding the combination
+    pub error: Option<String>,
+}
+
+/// Shortcut id -> action, for the plugin's handler
+static ACTIVE: Lazy<Mutex<HashMap<u32, HotkeyAction>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static STATUS: Lazy<Mutex<Vec<HotkeyStatus>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+fn toggle_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let visible = window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+    if visible && window.is_focused().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn run_favorite_routine(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let id = match crate::settings::load_settings(app.clone()).await {
+            Ok(settings) if !settings.general.favorite_routine.is_empty() => settings.general.favorite_routine,
+            Ok(_) => {
+                crate::voice::speak("You haven't picked a favorite routine yet.");
+                return;
+            }
+            Err(e) => {
+                warn!("Couldn't load the favorite routine: {}", e);
+                return;
+            }
+        };
+        let mut automation = app.state::<AppState>().inner().automation.lock().await;
+        if let Err(e) = automation.execute_routine(&app, &id).await {
+            warn!("Favorite routine {} failed: {}", id, e);
+        }
+    });
+}
+
+fn handle(app: &AppHandle, action: HotkeyAction, state: ShortcutState) {
+    // Push-to-talk is the only binding that cares about the key coming back up
+    if action == HotkeyAction::PushToTalk {
+        crate::voice::push_to_talk(state == ShortcutState::Pressed);
+        return;
+    }
+    if state != ShortcutState::Pressed {
+        return;
+    }
+
+    match action {
+        HotkeyAction::ToggleWindow => toggle_window(app),
+        HotkeyAction::StopSpeaking => crate::voice::stop_speaking(),
+        HotkeyAction::FavoriteRoutine => run_favorite_routine(app),
+        HotkeyAction::ToggleMute => {
+            crate::voice::toggle_muted();
+        }
+        HotkeyAction::ToggleDictation => {
+            crate::dictation::toggle(app);
+        }
+        HotkeyAction::PushToTalk => {}
+    }
+}
+
+/// Swap in `bindings`, returning how each one fared
+fn apply(app: &AppHandle, bindings: &HotkeyBindings) -> Result<Vec<HotkeyStatus>, String> {
+    let parsed = bindings.parse()?;
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        warn!("Failed to unregister hotkeys: {}", e);
+    }
+
+    let mut active = HashMap::new();
+    let mut status = Vec::new();
+    for (action, accelerator, shortcut) in parsed {
+        match shortcuts.register(shortcut) {
+            Ok(_) => {
+                active.insert(shortcut.id(), action);
+                status.push(HotkeyStatus { action, shortcut: accelerator, registered: true, error: None });
+            }
+            // Another app may own the combination; not fatal
+            Err(e) => {
+                warn!("Failed to register {:?} hotkey {}: {}", action, accelerator, e);
+                status.push(HotkeyStatus {
+                    action,
+                    shortcut: accelerator,
+                    registered: false,
+                    error: Some(format!("Already in use by another app ({})", e)),
+                });
+            }
+        }
+    }
+
+    info!("Registered {} of {} hotkeys", active.len(), status.len());
+    *ACTIVE.lock().map_err(|e| e.to_string())? = active;
+    *STATUS.lock().map_err(|e| e.to_string())? = status.clone();
+    Ok(status)
+}
+
+/// Install the global shortcut plugin and register the saved hotkeys
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    app.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(|app, shortcut, event| {
+                let action = ACTIVE.lock().ok().and_then(|active| active.get(&shortcut.id()).copied());
+                if let Some(action) = action {
+                    handle(app, action, event.state());
+                }
+            })
+            .build(),
+    )?;
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let bindings = crate::settings::load_settings(app.clone()).await.map(|s| s.general.hotkeys).unwrap_or_default();
+        if let Err(e) = apply(&app, &bindings) {
+            warn!("Saved hotkeys are invalid, using the defaults: {}", e);
+            let _ = apply(&app, &HotkeyBindings::default());
+        }
+    });
+
+    Ok(())
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_hotkeys() -> Result<Vec<HotkeyStatus>, String> {
+    Ok(STATUS.lock().map_err(|e| e.to_string())?.clone())
+}
+
+/// Rebind and save; invalid or duplicate bindings are refused and nothing changes
+#[tauri::command]
+pub async fn set_hotkeys(app: AppHandle, bindings: HotkeyBindings) -> Result<Vec<HotkeyStatus>, String> {
+    bindings.parse()?;
+    crate::settings::modify_settings(&app, |settings| {
+        settings.general.hotkeys = bindings.clone();
+        Ok::<_, String>(())
+    })
+    .await?;
+    apply(&app, &bindings)
+}
@@ -0,0 +1,402 @@
+// Philips Hue Module
+// Talks to a Hue bridge on the local network directly, for users without a
+// Home Assistant install: "turn off the bedroom lights", "dim the desk lamp
+// to 30%", "activate the relax scene". Pairing follows the bridge's own
+// flow: press the link button, then `pair_hue_bridge` asks for an app key
+// and keeps it as `hue.username`. The bridge's lights, rooms and scenes
+// are cached so spoken commands only match names that exist; when Home
+// Assistant is also set up it gets the first chance at a command. Routines
+// use the `HueLights` and `HueScene` actions.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// Philips' discovery service lists the bridges on the caller's network
+const DISCOVERY_ENDPOINT: &str = "https://discovery.meethue.com";
+
+/// How long `pair_hue_bridge` waits for the link button
+const PAIRING_WINDOW: Duration = Duration::from_secs(30);
+
+const DIRECTORY_REFRESH: Duration = Duration::from_secs(600);
+
+/// Hue's "link button not pressed" error
+const LINK_BUTTON_ERROR: i64 = 101;
+
+/// The `hue` settings section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HueSettings {
+    pub enabled: bool,
+    /// The bridge's address on the local network
+    pub bridge_ip: String,
+    /// App key the bridge handed out when paired
+    pub username: String,
+}
+
+impl HueSettings {
+    pub fn is_paired(&self) -> bool {
+        !self.bridge_ip.is_empty() && !self.username.is_empty()
+    }
+
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.bridge_ip = self.bridge_ip.trim().trim_start_matches("http://").trim_end_matches('/').to_string();
+        self.username = self.username.trim().to_string();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HueKind {
+    Light,
+    /// A room or zone, switched as a whole
+    Group,
+    Scene,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HueResource {
+    pub kind: HueKind,
+    pub id: String,
+    pub name: String,
+    /// For lights and groups; `None` for scenes
+    pub on: Option<bool>,
+    /// The room a scene belongs to
+    pub group: Option<String>,
+}
+
+static DIRECTORY: Lazy<RwLock<Vec<HueResource>>> = Lazy::new(|| RwLock::new(Vec::new()));
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+// ========== Bridge API ==========
+
+async fn paired_settings(app: &AppHandle) -> AstralResult<HueSettings> {
+    let settings = crate::settings::load_settings(app.clone()).await?.hue;
+    if !settings.enabled {
+        return Err(AstralError::not_configured("Hue is off; turn it on in Settings"));
+    }
+    if !settings.is_paired() {
+        return Err(AstralError::not_configured("Pair your Hue bridge in Settings first"));
+    }
+    Ok(settings)
+}
+
+/// The first error in a bridge reply, which reports errors with a 200
+fn bridge_error(body: &Value) -> Option<(i64, String)> {
+    body.as_array()?.iter().find_map(|entry| {
+        let error = entry.get("error")?;
+        Some((
+            error.get("type").and_then(Value::as_i64).unwrap_or_default(),
+            error.get("description").and_then(Value::as_str).unwrap_or("unknown error").to_string(),
+        ))
+    })
+}
+
+async fn bridge_request(ip: &str, method: reqwest::Method, path: &str, body: Option<Value>) -> AstralResult<Value> {
+    let mut request = crate::proxy::client()
+        .request(method, format!("http://{}/api{}", ip, path))
+        .timeout(Duration::from_secs(10));
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    let response = crate::privacy::send(request)
+        .await
+        .map_err(|e| AstralError::from(e.context("Couldn't reach the Hue bridge")))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("The Hue bridge", status, &body));
+    }
+    let body: Value = response.json().await?;
+    match bridge_error(&body) {
+        Some((1, _)) => Err(AstralError::new(ErrorCode::Unauthorized, "The Hue bridge no longer knows ASTRAL; pair it again")),
+        Some((_, description)) => Err(AstralError::new(ErrorCode::ProviderError, format!("The Hue bridge said: {}", description))),
+        None => Ok(body),
+    }
+}
+
+async fn user_request(settings: &HueSettings, method: reqwest::Method, path: &str, body: Option<Value>) -> AstralResult<Value> {
+    bridge_request(&settings.bridge_ip, method, &format!("/{}{}", settings.username, path), body).await
+}
+
+/// Lights, rooms and scenes on the bridge
+pub async fn resources(app: &AppHandle) -> AstralResult<Vec<HueResource>> {
+    let settings = paired_settings(app).await?;
+    let mut found = Vec::new();
+    for (path, kind) in [("/lights", HueKind::Light), ("/groups", HueKind::Group), ("/scenes", HueKind::Scene)] {
+        let body = user_request(&settings, reqwest::Method::GET, path, None).await?;
+        let Some(entries) = body.as_object() else { continue };
+        for (id, entry) in entries {
+            let on = match kind {
+                HueKind::Light => entry.pointer("/state/on").and_then(Value::as_bool),
+                HueKind::Group => entry.pointer("/state/any_on").and_then(Value::as_bool),
+                HueKind::Scene => None,
+            };
+            found.push(HueResource {
+                kind: kind.clone(),
+                id: id.clone(),
+                name: entry.get("name").and_then(Value::as_str).unwrap_or(id.as_str()).to_string(),
+                on,
+                group: entry.get("group").and_then(Value::as_str).map(str::to_string),
+            });
+        }
+    }
+    Ok(found)
+}
+
+/// Switch, dim or recolor a light or room. `brightness` is 0-100.
+pub async fn set_state(app: &AppHandle, resource: &HueResource, on: Option<bool>, brightness: Option<u8>) -> AstralResult<()> {
+    let settings = paired_settings(app).await?;
+    let mut state = json!({});
+    if let Some(on) = on {
+        state["on"] = json!(on);
+    }
+    if let Some(percent) = brightness {
+        // 1-254 on the bridge; dimming to 0 means off
+        state["on"] = json!(percent > 0);
+        if percent > 0 {
+            state["bri"] = json!(((percent.min(100) as u32 * 254 + 50) / 100).max(1));
+        }
+    }
+    let path = match resource.kind {
+        HueKind::Light => format!("/lights/{}/state", resource.id),
+        HueKind::Group => format!("/groups/{}/action", resource.id),
+        HueKind::Scene => return activate_scene(app, resource).await,
+    };
+    info!("Hue: {} {} -> {}", path, resource.name, state);
+    user_request(&settings, reqwest::Method::PUT, &path, Some(state)).await?;
+    Ok(())
+}
+
+pub async fn activate_scene(app: &AppHandle, scene: &HueResource) -> AstralResult<()> {
+    let settings = paired_settings(app).await?;
+    // Scenes are recalled through their room, or group 0 (every light) when they have none
+    let group = scene.group.clone().unwrap_or_else(|| "0".to_string());
+    info!("Hue: activating scene {}", scene.name);
+    user_request(&settings, reqwest::Method::PUT, &format!("/groups/{}/action", group), Some(json!({ "scene": scene.id }))).await?;
+    Ok(())
+}
+
+async fn refresh_directory(app: &AppHandle) -> AstralResult<usize> {
+    let found = resources(app).await?;
+    let count = found.len();
+    info!("Hue bridge has {} lights, rooms and scenes", count);
+    *DIRECTORY.write().unwrap_or_else(|e| e.into_inner()) = found;
+    Ok(count)
+}
+
+/// Reload the names right away, e.g. after pairing
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Keep the bridge's names current; called from setup
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) if settings.hue.enabled && settings.hue.is_paired() => {
+                    if let Err(e) = refresh_directory(&app).await {
+                        warn!("Couldn't load the Hue lights: {}", e);
+                    }
+                }
+                Ok(_) => DIRECTORY.write().unwrap_or_else(|e| e.into_inner()).clear(),
+                Err(e) => warn!("Couldn't load Hue settings: {}", e),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(DIRECTORY_REFRESH) => {}
+                _ = WAKE.notified() => {}
+            }
+        }
+    });
+}
+
+// ========== Voice Commands ==========
+
+#[derive(Debug, Clone)]
+pub enum HueCommand {
+    /// "turn off the bedroom lights"
+    Switch { target: HueResource, on: bool },
+    /// "dim the desk lamp to 30%"
+    Brightness { target: HueResource, percent: u8 },
+    /// "activate the relax scene", "set the living room to concentrate"
+    Scene { scene: HueResource },
+}
+
+fn clean_name(text: &str) -> String {
+    let text = text.trim().trim_end_matches(|c: char| c.is_ascii_punctuation()).trim();
+    let text = ["the ", "my ", "all the ", "all "].iter().find_map(|a| text.strip_prefix(a)).unwrap_or(text);
+    text.trim().to_string()
+}
+
+/// A cached light, room or scene by spoken name: "bedroom lights" finds the
+/// "Bedroom" room, "relax scene" the "Relax" scene
+fn find(name: &str, kinds: &[HueKind]) -> Option<HueResource> {
+    let name = clean_name(name);
+    let bare = ["lights", "light", "lamps", "lamp", "scene"]
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .map(str::trim)
+        .unwrap_or(&name)
+        .to_string();
+    let directory = DIRECTORY.read().unwrap_or_else(|e| e.into_inner());
+    kinds.iter().find_map(|kind| {
+        directory
+            .iter()
+            .filter(|r| r.kind == *kind)
+            .find(|r| {
+                let candidate = r.name.to_lowercase();
+                candidate == name || candidate == bare || strsim::normalized_levenshtein(&candidate, &name) >= 0.85
+            })
+            .cloned()
+    })
+}
+
+impl HueCommand {
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.to_lowercase();
+        let lower = lower.trim().trim_end_matches(['?', '.', '!']);
+        let switchable = [HueKind::Group, HueKind::Light];
+
+        for (prefix, on) in [("turn on ", true), ("switch on ", true), ("turn off ", false), ("switch off ", false)] {
+            if let Some(rest) = lower.strip_prefix(prefix) {
+                if let Some(scene) = find(rest, &[HueKind::Scene]).filter(|_| on && rest.contains("scene")) {
+                    return Some(HueCommand::Scene { scene });
+                }
+                return find(rest, &switchable).map(|target| HueCommand::Switch { target, on });
+            }
+        }
+        for (suffix, on) in [(" on", true), (" off", false)] {
+            let middle = ["turn ", "switch "].iter().find_map(|p| lower.strip_prefix(p)).and_then(|r| r.strip_suffix(suffix));
+            if let Some(target) = middle.and_then(|name| find(name, &switchable)) {
+                return Some(HueCommand::Switch { target, on });
+            }
+        }
+        for prefix in ["activate ", "start ", "set the scene to ", "switch to "] {
+            if let Some(scene) = lower.strip_prefix(prefix).and_then(|rest| find(rest, &[HueKind::Scene])) {
+                return Some(HueCommand::Scene { scene });
+            }
+        }
+        let (name, level) = ["dim ", "set ", "brighten "].iter().find_map(|p| lower.strip_prefix(p))?.rsplit_once(" to ")?;
+        if let Some(scene) = find(level, &[HueKind::Scene]) {
+            return Some(HueCommand::Scene { scene });
+        }
+        let percent: u8 = level.trim().trim_end_matches('%').trim_end_matches(" percent").trim().parse().ok()?;
+        find(name, &switchable).map(|target| HueCommand::Brightness { target, percent: percent.min(100) })
+    }
+}
+
+/// Carry out a spoken command and say what happened
+pub async fn handle(app: &AppHandle, command: HueCommand) -> String {
+    let result = match command {
+        HueCommand::Switch { target, on } => set_state(app, &target, Some(on), None)
+            .await
+            .map(|_| format!("Turned {} {}.", if on { "on" } else { "off" }, target.name)),
+        HueCommand::Brightness { target, percent } => set_state(app, &target, None, Some(percent))
+            .await
+            .map(|_| format!("Set {} to {}%.", target.name, percent)),
+        HueCommand::Scene { scene } => activate_scene(app, &scene).await.map(|_| format!("Activated {}.", scene.name)),
+    };
+    result.unwrap_or_else(|e| format!("I couldn't change the lights: {}", e))
+}
+
+/// The `HueLights` routine action: `target` is a light or room name
+pub async fn apply_action(app: &AppHandle, target: &str, on: Option<bool>, brightness: Option<u8>) -> AstralResult<()> {
+    let resource = match find(target, &[HueKind::Group, HueKind::Light]) {
+        Some(resource) => resource,
+        None => {
+            // The cache may not be loaded yet when a routine runs at startup
+            refresh_directory(app).await?;
+            find(target, &[HueKind::Group, HueKind::Light])
+                .ok_or_else(|| AstralError::new(ErrorCode::NotFound, format!("No Hue light or room called {}", target)))?
+        }
+    };
+    set_state(app, &resource, on, brightness).await
+}
+
+/// The `HueScene` routine action
+pub async fn apply_scene(app: &AppHandle, name: &str) -> AstralResult<()> {
+    let scene = match find(name, &[HueKind::Scene]) {
+        Some(scene) => scene,
+        None => {
+            refresh_directory(app).await?;
+            find(name, &[HueKind::Scene]).ok_or_else(|| AstralError::new(ErrorCode::NotFound, format!("No Hue scene called {}", name)))?
+        }
+    };
+    activate_scene(app, &scene).await
+}
+
+// ========== Tauri Commands ==========
+
+/// Bridges on this network, from Philips' discovery service
+#[tauri::command]
+pub async fn discover_hue_bridges() -> AstralResult<Vec<String>> {
+    let request = crate::proxy::client().get(DISCOVERY_ENDPOINT).timeout(Duration::from_secs(10));
+    let response = crate::privacy::send(request)
+        .await
+        .map_err(|e| AstralError::from(e.context("Couldn't reach the Hue discovery service")))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("The Hue discovery service", status, &body));
+    }
+    let bridges: Vec<Value> = response.json().await?;
+    Ok(bridges
+        .iter()
+        .filter_map(|b| b.get("internalipaddress").and_then(Value::as_str).map(str::to_string))
+        .collect())
+}
+
+/// Ask the bridge at `bridge_ip` for an app key, retrying while the user
+/// presses its link button
+#[tauri::command]
+pub async fn pair_hue_bridge(app: AppHandle, bridge_ip: String) -> AstralResult<()> {
+    let bridge_ip = bridge_ip.trim().trim_start_matches("http://").trim_end_matches('/').to_string();
+    if bridge_ip.is_empty() {
+        return Err(AstralError::invalid_input("Enter the bridge's address or search for it first"));
+    }
+    let body = json!({ "devicetype": "astral#desktop" });
+    let deadline = tokio::time::Instant::now() + PAIRING_WINDOW;
+    let username = loop {
+        let request = crate::proxy::client()
+            .post(format!("http://{}/api", bridge_ip))
+            .json(&body)
+            .timeout(Duration::from_secs(10));
+        let response = crate::privacy::send(request)
+            .await
+            .map_err(|e| AstralError::from(e.context("Couldn't reach the Hue bridge")))?;
+        let reply: Value = response.json().await?;
+        if let Some(username) = reply.pointer("/0/success/username").and_then(Value::as_str) {
+            break username.to_string();
+        }
+        match bridge_error(&reply) {
+            Some((LINK_BUTTON_ERROR, _)) if tokio::time::Instant::now() < deadline => {
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+            Some((LINK_BUTTON_ERROR, _)) => {
+                return Err(AstralError::new(ErrorCode::Timeout, "Press the link button on the Hue bridge, then pair again"));
+            }
+            Some((_, description)) => {
+                return Err(AstralError::new(ErrorCode::ProviderError, format!("The Hue bridge said: {}", description)));
+            }
+            None => return Err(AstralError::new(ErrorCode::ProviderError, "The Hue bridge sent an unexpected reply")),
+        }
+    };
+    info!("Paired with the Hue bridge at {}", bridge_ip);
+    crate::settings::update_setting(app.clone(), "hue.bridge_ip".to_string(), Value::String(bridge_ip)).await?;
+    crate::settings::update_setting(app, "hue.username".to_string(), Value::String(username)).await?;
+    wake();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_hue_resources(app: AppHandle) -> AstralResult<Vec<HueResource>> {
+    resources(&app).await
+}
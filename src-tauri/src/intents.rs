@@ -0,0 +1,330 @@
+// Intent Router
+// Turns an utterance into a typed intent with its slots (app name, volume
+// level, power delay, ...). Rule patterns run first, in priority order, using
+// each module's own parser; when none match and `commands.llm_intent_classification`
+// is on, the LLM classifies it into one of a few common intents with
+// structured JSON output. The handlers live in commands.rs.
+
+use crate::state::AppState;
+use tracing::{debug, warn};
+use serde_json::Value;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::appearance::AppearanceCommand;
+use crate::calendar::CalendarQuery;
+use crate::cleanup::CleanupCommand;
+use crate::email::EmailCommand;
+use crate::display::BrightnessCommand;
+use crate::file_search::FileSearchQuery;
+use crate::focus_assist::FocusAssistCommand;
+use crate::home_assistant::HomeCommand;
+use crate::hue::HueCommand;
+use crate::web_actions::WebActionCommand;
+use crate::calculator::CalcQuery;
+use crate::network::WifiCommand;
+use crate::notes::NotesCommand;
+use crate::power::{PowerAction, PowerCommand, PowerPlanKind};
+use crate::processes::ProcessSort;
+use crate::services::ServiceCommand;
+use crate::system_integration::{MediaAction, VolumeCommand};
+use crate::window_layout::PlacementCommand;
+use crate::permissions::Capability;
+
+#[derive(Debug, Clone)]
+pub enum Intent {
+    RunRoutine { id: String },
+    /// "when I say browser I mean Firefox"
+    LearnAlias { alias: String, app_name: String },
+    /// "what's 15% of 84", "convert 5 miles to km"
+    Calculate(CalcQuery),
+    /// "add milk to my shopping list", "take a note ..."
+    Notes(NotesCommand),
+    /// "what's on my calendar today", "when is my next meeting"
+    Calendar(CalendarQuery),
+    /// "do I have any new email", "mark them as read"
+    Email(EmailCommand),
+    /// "turn off the living room lights"; only names Home Assistant knows
+    Home(HomeCommand),
+    Hue(HueCommand),
+    WebAction(WebActionCommand),
+    NowPlaying,
+    Service(ServiceCommand),
+    Media(MediaAction),
+    MicMute { muted: bool },
+    CancelPower,
+    Power(PowerCommand),
+    Wifi(WifiCommand),
+    NetworkStatus,
+    SystemReport,
+    Uptime,
+    TopProcesses(ProcessSort),
+    CloseApp { name: String, force: bool },
+    BatteryHealth,
+    Battery,
+    PowerPlan(PowerPlanKind),
+    PlaceWindow(PlacementCommand),
+    Cleanup(CleanupCommand),
+    FocusAssist(FocusAssistCommand),
+    Brightness(BrightnessCommand),
+    Appearance(AppearanceCommand),
+    Volume(VolumeCommand),
+    FileSearch(FileSearchQuery),
+    /// Zero-based index into the last search results
+    OpenSearchResult { index: usize },
+    LaunchApp { app_name: String },
+}
+
+impl Intent {
+    /// Permission the intent needs before it's handled
+    pub fn capability(&self) -> Option<Capability> {
+        match self {
+            Intent::Power(_) => Some(Capability::Power),
+            Intent::Cleanup(CleanupCommand::EmptyRecycleBin | CleanupCommand::FreeSpace) => Some(Capability::FileDeletion),
+            Intent::Email(EmailCommand::MarkRead) => Some(Capability::EmailChanges),
+            Intent::CloseApp { .. } => Some(Capability::CloseApps),
+            _ => None,
+        }
+    }
+}
+
+/// Built-in routines by the names they're started with
+const ROUTINE_NAMES: &[(&str, &str)] = &[("work mode", "work-mode"), ("gaming mode", "gaming-mode")];
+
+/// Verbs that start a routine: "switch to gaming mode"
+const ROUTINE_VERBS: &[&str] = &["start ", "switch to ", "turn on ", "activate ", "enable ", "enter ", "go into ", "run "];
+
+/// `command` lowercased, with courtesy around it trimmed off ("could you
+/// please shut down?" -> "shut down"), for parsers that only take a command
+/// phrased as an instruction and not a question or remark that mentions it
+pub(crate) fn imperative(command: &str) -> String {
+    const LEADING: &[&str] = &["please ", "can you ", "could you ", "would you ", "will you ", "go ahead and "];
+    let lower = command.to_lowercase();
+    let mut text = lower.trim().trim_end_matches(|c: char| c.is_ascii_punctuation()).trim_end();
+    while let Some(rest) = LEADING.iter().find_map(|p| text.strip_prefix(p)) {
+        text = rest.trim_start();
+    }
+    let text = text.strip_suffix(" please").unwrap_or(text);
+    text.trim_end_matches(',').trim().to_string()
+}
+
+/// Routine id from "work mode", "start gaming mode" or "start work". The
+/// routine name has to be the whole instruction, so "is work mode on?" and
+/// "stop gaming mode" don't start anything.
+fn parse_routine(command: &str) -> Option<&'static str> {
+    let text = imperative(command);
+    let name = ROUTINE_VERBS.iter().find_map(|verb| text.strip_prefix(verb)).unwrap_or(&text);
+    let name = name.strip_prefix("the ").unwrap_or(name);
+    ROUTINE_NAMES
+        .iter()
+        .find(|(phrase, _)| name == *phrase || text.strip_prefix("start ") == phrase.strip_suffix(" mode"))
+        .map(|(_, id)| *id)
+}
+
+/// "mute the mic" (true) or "unmute my microphone" (false); questions like
+/// "is my mic muted?" are left for someone else to answer
+fn parse_mic_mute(command: &str) -> Option<bool> {
+    let text = imperative(command);
+    let (muted, rest) = match text.strip_prefix("unmute ") {
+        Some(rest) => (false, rest),
+        None => (true, text.strip_prefix("mute ")?),
+    };
+    matches!(rest, "the mic" | "mic" | "my mic" | "the microphone" | "microphone" | "my microphone" | "yourself").then_some(muted)
+}
+
+/// "open spotify", "launch the calculator"; only when the app is known
+fn parse_launch(command: &str) -> Option<String> {
+    let lower = command.to_lowercase();
+    let lower = lower.trim().trim_end_matches(|c: char| c.is_ascii_punctuation());
+    let rest = ["open ", "launch ", "start "].iter().find_map(|p| lower.strip_prefix(p))?;
+    let rest = rest.trim_start_matches("the ").trim();
+    crate::app_launcher::find_app(rest).map(|_| rest.to_string())
+}
+
+/// Match the rule patterns. The order matters: earlier parsers claim shared
+/// words first (services before media and power for "stop" / "restart").
+pub fn parse(command: &str) -> Option<Intent> {
+    if let Some(id) = parse_routine(command) {
+        return Some(Intent::RunRoutine { id: id.to_string() });
+    }
+    if let Some((alias, app_name)) = crate::app_launcher::parse_alias_correction(command) {
+        return Some(Intent::LearnAlias { alias, app_name });
+    }
+    if let Some(query) = CalcQuery::from_command(command) {
+        return Some(Intent::Calculate(query));
+    }
+    if let Some(notes) = NotesCommand::from_command(command) {
+        return Some(Intent::Notes(notes));
+    }
+    if let Some(query) = CalendarQuery::from_command(command) {
+        return Some(Intent::Calendar(query));
+    }
+    if let Some(email) = EmailCommand::from_command(command) {
+        return Some(Intent::Email(email));
+    }
+    if let Some(home) = HomeCommand::from_command(command) {
+        return Some(Intent::Home(home));
+    }
+    if let Some(hue) = HueCommand::from_command(command) {
+        return Some(Intent::Hue(hue));
+    }
+    if let Some(action) = WebActionCommand::from_command(command) {
+        return Some(Intent::WebAction(action));
+    }
+    if crate::media::is_now_playing_query(command) {
+        return Some(Intent::NowPlaying);
+    }
+    if let Some(service) = ServiceCommand::from_command(command) {
+        return Some(Intent::Service(service));
+    }
+    if let Some(action) = MediaAction::from_command(command) {
+        return Some(Intent::Media(action));
+    }
+    if let Some(muted) = parse_mic_mute(command) {
+        return Some(Intent::MicMute { muted });
+    }
+    // Before power, which would take "when did I last reboot" for a reboot
+    if crate::system_monitor::is_uptime_query(command) {
+        return Some(Intent::Uptime);
+    }
+    if crate::power::is_cancel_command(command) {
+        return Some(Intent::CancelPower);
+    }
+    if let Some(power) = PowerCommand::from_command(command) {
+        return Some(Intent::Power(power));
+    }
+    if let Some(wifi) = WifiCommand::from_command(command) {
+        return Some(Intent::Wifi(wifi));
+    }
+    if crate::network::is_network_query(command) {
+        return Some(Intent::NetworkStatus);
+    }
+    if crate::system_report::is_report_query(command) {
+        return Some(Intent::SystemReport);
+    }
+    if let Some(by) = crate::processes::parse_top_processes_command(command) {
+        return Some(Intent::TopProcesses(by));
+    }
+    if let Some((name, force)) = crate::processes::parse_kill_command(command) {
+        return Some(Intent::CloseApp { name, force });
+    }
+    if crate::power::is_battery_health_query(command) {
+        return Some(Intent::BatteryHealth);
+    }
+    if crate::power::is_battery_query(command) {
+        return Some(Intent::Battery);
+    }
+    if let Some(kind) = crate::power::parse_power_plan_command(command) {
+        return Some(Intent::PowerPlan(kind));
+    }
+    if let Some(placement) = PlacementCommand::from_command(command) {
+        return Some(Intent::PlaceWindow(placement));
+    }
+    if let Some(cleanup) = CleanupCommand::from_command(command) {
+        return Some(Intent::Cleanup(cleanup));
+    }
+    if let Some(focus) = FocusAssistCommand::from_command(command) {
+        return Some(Intent::FocusAssist(focus));
+    }
+    if let Some(brightness) = BrightnessCommand::from_command(command) {
+        return Some(Intent::Brightness(brightness));
+    }
+    if let Some(appearance) = AppearanceCommand::from_command(command) {
+        return Some(Intent::Appearance(appearance));
+    }
+    if let Some(volume) = VolumeCommand::from_command(command) {
+        return Some(Intent::Volume(volume));
+    }
+    if let Some(query) = crate::file_search::parse_search_command(command) {
+        return Some(Intent::FileSearch(query));
+    }
+    if let Some(index) = crate::file_search::parse_result_reference(command) {
+        return Some(Intent::OpenSearchResult { index });
+    }
+    if let Some(app_name) = parse_launch(command) {
+        return Some(Intent::LaunchApp { app_name });
+    }
+    None
+}
+
+const CLASSIFIER_PROMPT: &str = "You map a voice command for a desktop assistant to one intent. \
+Reply with only a JSON object: {\"intent\": <name>, \"slots\": {...}}. Intents and their slots:\n\
+- launch_app: {\"app\": string}\n\
+- set_volume: {\"level\": 0-100}\n\
+- change_volume: {\"delta\": integer, negative for quieter}\n\
+- mute: {\"muted\": boolean}\n\
+- media: {\"action\": \"play_pause\" | \"next\" | \"previous\" | \"stop\"}\n\
+- power: {\"action\": \"lock\" | \"sleep\" | \"shutdown\" | \"restart\", \"delay_minutes\": number or null}\n\
+- run_routine: {\"id\": one of the routine ids below}\n\
+- none: {} for questions, chat, or anything else\n\
+Routines (id: name): ";
+
+fn slot_str(slots: &Value, key: &str) -> Option<String> {
+    slots.get(key).and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// Build a typed intent from the classifier's JSON reply
+fn from_classification(reply: &str, routine_ids: &[String]) -> Option<Intent> {
+    // Some models wrap the object in prose or a code fence
+    let json = reply.get(reply.find('{')?..=reply.rfind('}')?)?;
+    let value: Value = serde_json::from_str(json).ok()?;
+    let slots = value.get("slots").cloned().unwrap_or(Value::Null);
+
+    match value.get("intent")?.as_str()? {
+        "launch_app" => slot_str(&slots, "app").map(|app_name| Intent::LaunchApp { app_name }),
+        "set_volume" => {
+            let level = slots.get("level")?.as_f64()?.clamp(0.0, 100.0);
+            Some(Intent::Volume(VolumeCommand::Set(level.round() as u8)))
+        }
+        "change_volume" => {
+            let delta = slots.get("delta")?.as_f64()?.clamp(-100.0, 100.0);
+            Some(Intent::Volume(VolumeCommand::Adjust(delta.round() as i32)))
+        }
+        "mute" => Some(Intent::Volume(VolumeCommand::Mute(slots.get("muted").and_then(Value::as_bool).unwrap_or(true)))),
+        "media" => slot_str(&slots, "action").and_then(|a| MediaAction::parse(&a)).map(Intent::Media),
+        "power" => {
+            let action = slot_str(&slots, "action").and_then(|a| PowerAction::parse(&a))?;
+            let delay = slots
+                .get("delay_minutes")
+                .and_then(Value::as_f64)
+                .filter(|m| *m > 0.0)
+                .map(|m| Duration::from_secs((m * 60.0).round() as u64));
+            Some(Intent::Power(PowerCommand { action, delay }))
+        }
+        "run_routine" => slot_str(&slots, "id")
+            .filter(|id| routine_ids.contains(id))
+            .map(|id| Intent::RunRoutine { id }),
+        _ => None,
+    }
+}
+
+/// Ask the LLM to classify an utterance the rules didn't match. Off unless
+/// `commands.llm_intent_classification` is set, since it costs a round trip.
+pub async fn classify(app: &AppHandle, command: &str) -> Option<Intent> {
+    let enabled = crate::settings::load_settings(app.clone())
+        .await
+        .map(|s| s.commands.llm_intent_classification)
+        .unwrap_or(false);
+    if !enabled {
+        return None;
+    }
+
+    let state = app.state::<AppState>();
+    let routines = state.automation.lock().await.get_all_routines();
+    let routine_ids: Vec<String> = routines.iter().map(|r| r.id.clone()).collect();
+    let listing: Vec<String> = routines.iter().map(|r| format!("{}: {}", r.id, r.name)).collect();
+    let system_prompt = format!("{}{}", CLASSIFIER_PROMPT, listing.join(", "));
+
+    let llm = state.llm.lock().await;
+    let reply = match llm.as_ref()?.complete_json(&system_prompt, command).await {
+        Ok(response) => response.content,
+        Err(e) => {
+            warn!("Intent classification failed: {}", e);
+            return None;
+        }
+    };
+
+    let intent = from_classification(&reply, &routine_ids);
+    debug!("LLM classified {:?} as {:?}", command, intent);
+    intent
+}
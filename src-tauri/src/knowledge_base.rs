@@ -0,0 +1,784 @@
+// Knowledge Base Module
+// Answers questions from the user's own documents: "according to my
+// documents, when does the lease end?". The user picks folders, and the
+// text documents the file indexer finds there are cut into overlapping
+// passages, embedded (Ollama, or OpenAI when that's the LLM provider) and
+// kept in a local vector store; the indexer's change tracking keeps it
+// current without rereading every file. A question is embedded the same
+// way, the closest passages go to the LLM, and the answer cites the files
+// they came from. With `auto_context`, ordinary questions also get the
+// closely matching passages as context, without having to say "my
+// documents". Single files (PDF, Markdown, text) can also be added by hand,
+// and any document can be listed or removed again. Copies of a document
+// share one set of embeddings. Off unless `knowledge.enabled`.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use crate::file_indexer::IndexedFile;
+use crate::state::AppState;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// How often the store catches up with the file index
+const SYNC_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Passage length in characters, and how much neighbours share
+const CHUNK_CHARS: usize = 1200;
+const CHUNK_OVERLAP: usize = 200;
+
+/// Passages embedded per request
+const EMBED_BATCH: usize = 16;
+
+/// Larger files are skipped; they're rarely notes or manuals
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+const MAX_CHUNKS_PER_DOCUMENT: usize = 400;
+
+/// Longest title shown in the document list
+const TITLE_CHARS: usize = 120;
+
+/// Passages less similar than this aren't worth showing the model
+const MIN_SCORE: f32 = 0.25;
+
+/// Unasked-for passages need to be a much closer match, or small talk would
+/// come with excerpts from the user's files
+const AUTO_CONTEXT_MIN_SCORE: f32 = 0.5;
+
+/// The `knowledge` settings section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnowledgeSettings {
+    pub enabled: bool,
+    /// Folders whose documents are indexed, recursively
+    pub folders: Vec<String>,
+    /// Empty for the provider's default (see `llm_provider::default_embedding_model`)
+    pub embedding_model: String,
+    /// Passages handed to the LLM per question
+    pub top_k: usize,
+    /// Give every open-ended question the passages that match it closely
+    pub auto_context: bool,
+}
+
+impl Default for KnowledgeSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            folders: Vec::new(),
+            embedding_model: String::new(),
+            top_k: 5,
+            auto_context: true,
+        }
+    }
+}
+
+impl KnowledgeSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.folders = self
+            .folders
+            .iter()
+            .map(|f| f.trim().to_string())
+            .filter(|f| !f.is_empty())
+            .collect();
+        self.embedding_model = self.embedding_model.trim().to_string();
+        if !(1..=20).contains(&self.top_k) {
+            return Err("The knowledge base can use 1 to 20 passages per question".to_string());
+        }
+        Ok(())
+    }
+
+    fn covers(&self, path: &Path) -> bool {
+        self.folders.iter().any(|folder| path.starts_with(folder))
+    }
+}
+
+/// An embedded passage of a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Chunk {
+    text: String,
+    /// Unit length, so similarity is a dot product
+    #[serde(with = "vector_base64")]
+    vector: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredDocument {
+    name: String,
+    size: u64,
+    modified: Option<u64>,
+    /// SHA-256 of the text; empty when there was none
+    #[serde(default)]
+    hash: String,
+    /// First heading or line of the text, for the document list
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    indexed_at: u64,
+    /// Empty for files with no readable text, so they aren't retried every pass
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KnowledgeStore {
+    /// Vectors from different models can't be compared; a new model starts over
+    model: String,
+    /// Unix seconds of the last completed pass
+    last_sync: u64,
+    documents: HashMap<String, StoredDocument>,
+    /// Files added by hand, which needn't be in a knowledge folder
+    #[serde(default)]
+    added: BTreeSet<String>,
+    /// Folder documents the user removed; they stay out until added again
+    #[serde(default)]
+    excluded: BTreeSet<String>,
+}
+
+/// Little-endian f32s as base64, a quarter the size of a JSON number list
+mod vector_base64 {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(vector: &[f32], serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes: Vec<u8> = vector.iter().flat_map(|x| x.to_le_bytes()).collect();
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f32>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let bytes = STANDARD.decode(text).map_err(serde::de::Error::custom)?;
+        Ok(bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+    }
+}
+
+/// A passage that matched a question
+#[derive(Debug, Clone, Serialize)]
+pub struct Passage {
+    pub path: String,
+    pub name: String,
+    pub text: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeAnswer {
+    pub answer: String,
+    /// The passages the answer was written from, numbered as cited
+    pub sources: Vec<Passage>,
+}
+
+/// An entry in the knowledge base's document list
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeDocument {
+    pub path: String,
+    pub name: String,
+    pub title: String,
+    /// Lowercase extension, e.g. "pdf" or "md"
+    pub kind: String,
+    pub size: u64,
+    pub modified: Option<u64>,
+    pub indexed_at: u64,
+    pub chunks: usize,
+    /// Added by hand rather than found in a knowledge folder
+    pub added: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct KnowledgeStatus {
+    pub enabled: bool,
+    pub documents: usize,
+    pub chunks: usize,
+    pub indexing: bool,
+    pub last_sync: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+static STORE: Lazy<RwLock<KnowledgeStore>> = Lazy::new(|| RwLock::new(load()));
+static STATUS: Lazy<Mutex<KnowledgeStatus>> = Lazy::new(|| Mutex::new(KnowledgeStatus::default()));
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+fn store_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("knowledge_index.json");
+    Some(path)
+}
+
+fn load() -> KnowledgeStore {
+    let Some(content) = store_path().and_then(|path| std::fs::read_to_string(path).ok()) else {
+        return KnowledgeStore::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        warn!("Ignoring unreadable knowledge_index.json: {}", e);
+        KnowledgeStore::default()
+    })
+}
+
+fn save(store: &KnowledgeStore) -> Result<(), String> {
+    let path = store_path().ok_or_else(|| "Could not find config directory".to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let content = serde_json::to_string(store).map_err(|e| format!("Failed to serialize the knowledge base: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write the knowledge base: {}", e))
+}
+
+fn status() -> std::sync::MutexGuard<'static, KnowledgeStatus> {
+    STATUS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp().max(0) as u64
+}
+
+// ========== Indexing ==========
+
+/// Overlapping passages, broken at paragraph or sentence ends where possible
+fn chunk_text(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < chars.len() && chunks.len() < MAX_CHUNKS_PER_DOCUMENT {
+        let mut end = (start + CHUNK_CHARS).min(chars.len());
+        if end < chars.len() {
+            let window: String = chars[start + CHUNK_CHARS / 2..end].iter().collect();
+            let cut = window.rfind("\n\n").or_else(|| window.rfind(". ")).or_else(|| window.rfind('\n'));
+            if let Some(cut) = cut {
+                end = start + CHUNK_CHARS / 2 + window[..cut].chars().count() + 1;
+            }
+        }
+        let chunk: String = chars[start..end].iter().collect();
+        let chunk = chunk.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+        if end == chars.len() {
+            break;
+        }
+        start = end.saturating_sub(CHUNK_OVERLAP).max(start + 1);
+    }
+    chunks
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let length = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if length > 0.0 {
+        vector.iter_mut().for_each(|x| *x /= length);
+    }
+    vector
+}
+
+fn embedding_model(settings: &crate::settings::AppSettings) -> String {
+    if settings.knowledge.embedding_model.is_empty() {
+        crate::llm_provider::default_embedding_model(settings.llm.provider).to_string()
+    } else {
+        settings.knowledge.embedding_model.clone()
+    }
+}
+
+async fn embed(settings: &crate::settings::AppSettings, inputs: &[String]) -> AstralResult<Vec<Vec<f32>>> {
+    let model = embedding_model(settings);
+    let llm = crate::llm_provider::LLMConfig {
+        api_key: settings.llm.api_key.clone().filter(|k| !k.is_empty()),
+        ..settings.llm.clone()
+    };
+    let vectors = crate::llm_provider::embed(&llm, &model, inputs).await?;
+    Ok(vectors.into_iter().map(normalize).collect())
+}
+
+/// The knowledge folders need to be in the file index; add any that aren't.
+/// The index picks them up in the background, so their files arrive on a later sync.
+async fn ensure_indexed(app: &AppHandle, settings: &crate::settings::AppSettings) {
+    let missing: Vec<PathBuf> = settings
+        .knowledge
+        .folders
+        .iter()
+        .map(PathBuf::from)
+        .filter(|folder| !settings.file_index.folders.iter().any(|root| folder.starts_with(root)))
+        .collect();
+    if missing.is_empty() {
+        return;
+    }
+    info!("Adding {} knowledge folders to the file index", missing.len());
+    let result = crate::settings::modify_settings(app, |settings| {
+        for folder in missing {
+            if !settings.file_index.folders.iter().any(|root| folder.starts_with(root)) {
+                settings.file_index.folders.push(folder);
+            }
+        }
+        Ok::<_, String>(())
+    })
+    .await;
+    if let Err(e) = result {
+        warn!("Couldn't index the knowledge folders: {}", e);
+    }
+}
+
+fn content_hash(text: &str) -> String {
+    format!("{:x}", Sha256::digest(text.as_bytes()))
+}
+
+/// The first line of the text, without Markdown's heading marks
+fn title_of(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or_default();
+    line.chars().take(TITLE_CHARS).collect()
+}
+
+/// A document's text; files without any are an `InvalidInput` error
+async fn read_document(path: &str) -> AstralResult<String> {
+    let path = PathBuf::from(path);
+    tokio::task::spawn_blocking(move || crate::documents::read_text(&path))
+        .await?
+        .map_err(|e| AstralError::invalid_input(e.to_string()))
+}
+
+/// Chunk and embed `text`, or reuse the passages of a document in the store
+/// with the same text
+async fn index_document(
+    settings: &crate::settings::AppSettings,
+    file: &IndexedFile,
+    text: &str,
+) -> AstralResult<StoredDocument> {
+    let hash = content_hash(text);
+    let copy_of = {
+        let store = STORE.read().unwrap_or_else(|e| e.into_inner());
+        store
+            .documents
+            .iter()
+            .find(|(_, d)| d.hash == hash && !d.chunks.is_empty())
+            .map(|(path, d)| (path.clone(), d.chunks.clone()))
+    };
+    let chunks = match copy_of {
+        Some((original, chunks)) => {
+            if original != file.path {
+                info!("{} has the same text as {}; reusing its passages", file.path, original);
+            }
+            chunks
+        }
+        None => {
+            let mut seen = HashSet::new();
+            let passages: Vec<String> = chunk_text(text).into_iter().filter(|p| seen.insert(p.clone())).collect();
+            let mut chunks = Vec::with_capacity(passages.len());
+            for batch in passages.chunks(EMBED_BATCH) {
+                let vectors = embed(settings, batch).await?;
+                chunks.extend(batch.iter().cloned().zip(vectors).map(|(text, vector)| Chunk { text, vector }));
+            }
+            chunks
+        }
+    };
+    Ok(StoredDocument {
+        name: file.name.clone(),
+        size: file.size,
+        modified: file.modified,
+        hash,
+        title: title_of(text),
+        indexed_at: now_secs(),
+        chunks,
+    })
+}
+
+/// An entry for a file with no readable text
+fn unreadable(file: &IndexedFile) -> StoredDocument {
+    StoredDocument {
+        name: file.name.clone(),
+        size: file.size,
+        modified: file.modified,
+        hash: String::new(),
+        title: String::new(),
+        indexed_at: now_secs(),
+        chunks: Vec::new(),
+    }
+}
+
+/// An index entry for a file added by hand, if it's still there
+fn added_file(path: &str) -> Option<IndexedFile> {
+    let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+    crate::file_indexer::entry_for(Path::new(path), &metadata)
+}
+
+fn update_counts(store: &KnowledgeStore) {
+    let mut status = status();
+    status.documents = store.documents.values().filter(|d| !d.chunks.is_empty()).count();
+    status.chunks = store.documents.values().map(|d| d.chunks.len()).sum();
+}
+
+/// Bring the store up to date with the file index and the added files
+async fn sync(app: &AppHandle) -> AstralResult<()> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    let knowledge = settings.knowledge.clone();
+    ensure_indexed(app, &settings).await;
+
+    let model = embedding_model(&settings);
+    let (since, added) = {
+        let mut guard = STORE.write().unwrap_or_else(|e| e.into_inner());
+        let store = &mut *guard;
+        if store.model != model {
+            info!("Embedding model is now {}; rebuilding the knowledge base", model);
+            store.documents.clear();
+            store.model = model.clone();
+            store.last_sync = 0;
+        }
+        // Forget documents that were deleted, removed by the user, or are
+        // no longer in a knowledge folder
+        let indexed: HashSet<String> = crate::file_indexer::with_files(|files| {
+            files.filter(|f| knowledge.covers(Path::new(&f.path))).map(|f| f.path.clone()).collect()
+        });
+        let added: Vec<IndexedFile> = store.added.iter().filter_map(|path| added_file(path)).collect();
+        let excluded = &store.excluded;
+        store.documents.retain(|path, _| {
+            (indexed.contains(path) && !excluded.contains(path)) || added.iter().any(|f| &f.path == path)
+        });
+        (store.last_sync, added)
+    };
+
+    let started = now_secs();
+    let mut seen = HashSet::new();
+    let changed: Vec<_> = crate::file_indexer::text_documents_changed_since(since)
+        .into_iter()
+        .filter(|f| knowledge.covers(Path::new(&f.path)))
+        .chain(added)
+        .filter(|f| f.size <= MAX_FILE_BYTES && seen.insert(f.path.clone()))
+        .filter(|f| {
+            let store = STORE.read().unwrap_or_else(|e| e.into_inner());
+            !store.excluded.contains(&f.path)
+                && store.documents.get(&f.path).is_none_or(|d| d.size != f.size || d.modified != f.modified)
+        })
+        .collect();
+    if !changed.is_empty() {
+        info!("Knowledge base: {} documents to (re)index", changed.len());
+        status().indexing = true;
+    }
+
+    for file in changed {
+        let document = match read_document(&file.path).await {
+            Ok(text) => index_document(&settings, &file, &text).await?,
+            Err(e) if e.code == ErrorCode::InvalidInput => {
+                info!("Knowledge base skipped {}: {}", file.path, e);
+                unreadable(&file)
+            }
+            Err(e) => return Err(e),
+        };
+        let mut store = STORE.write().unwrap_or_else(|e| e.into_inner());
+        store.documents.insert(file.path.clone(), document);
+    }
+
+    let mut store = STORE.write().unwrap_or_else(|e| e.into_inner());
+    store.last_sync = started;
+    save(&store)?;
+    update_counts(&store);
+    status().last_sync = Some(started);
+    Ok(())
+}
+
+/// Catch up with the file index right away, e.g. after the folders change
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Keep the store current while the knowledge base is on; called from setup
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let enabled = crate::settings::load_settings(app.clone())
+                .await
+                .map(|s| s.knowledge.enabled)
+                .unwrap_or(false);
+            status().enabled = enabled;
+            if enabled {
+                let result = sync(&app).await;
+                let mut status = status();
+                status.indexing = false;
+                status.last_error = result.err().map(|e| {
+                    warn!("Knowledge base sync failed: {}", e);
+                    e.to_string()
+                });
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(SYNC_INTERVAL) => {}
+                _ = WAKE.notified() => {}
+            }
+        }
+    });
+}
+
+// ========== Questions ==========
+
+/// Whether a command asks about the user's own documents
+pub fn is_knowledge_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    [
+        "my documents",
+        "my docs",
+        "my files say",
+        "in my files",
+        "knowledge base",
+        "according to my",
+    ]
+    .iter()
+    .any(|p| lower.contains(p))
+}
+
+pub async fn is_enabled(app: &AppHandle) -> bool {
+    crate::settings::load_settings(app.clone()).await.is_ok_and(|s| s.knowledge.enabled)
+}
+
+/// The passages closest to `question`, best first
+pub async fn search(app: &AppHandle, question: &str, limit: usize) -> AstralResult<Vec<Passage>> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    if !settings.knowledge.enabled {
+        return Err(AstralError::not_configured("Turn on the knowledge base and pick its folders in Settings"));
+    }
+    let query_vector = embed(&settings, &[question.to_string()])
+        .await?
+        .pop()
+        .ok_or_else(|| AstralError::new(ErrorCode::ProviderError, "No embedding came back for the question"))?;
+
+    let query = query_vector.as_slice();
+    let store = STORE.read().unwrap_or_else(|e| e.into_inner());
+    if store.documents.values().all(|d| d.chunks.is_empty()) {
+        return Err(AstralError::not_configured("The knowledge base is empty; add folders with documents in Settings"));
+    }
+    let mut passages: Vec<Passage> = store
+        .documents
+        .iter()
+        .flat_map(|(path, document)| {
+            document.chunks.iter().map(move |chunk| Passage {
+                path: path.clone(),
+                name: document.name.clone(),
+                text: chunk.text.clone(),
+                score: chunk.vector.iter().zip(query).map(|(a, b)| a * b).sum(),
+            })
+        })
+        .filter(|p| p.score >= MIN_SCORE)
+        .collect();
+    passages.sort_by(|a, b| b.score.total_cmp(&a.score));
+    // Copies of a document would otherwise fill the list with the same passage
+    let mut seen = HashSet::new();
+    passages.retain(|p| seen.insert(p.text.clone()));
+    passages.truncate(limit);
+    Ok(passages)
+}
+
+/// Number of passages to hand the LLM per question
+pub async fn top_k(app: &AppHandle) -> usize {
+    crate::settings::load_settings(app.clone())
+        .await
+        .map(|s| s.knowledge.top_k)
+        .unwrap_or_else(|_| KnowledgeSettings::default().top_k)
+}
+
+/// The passages as `[n] From "file":` blocks
+fn numbered(passages: &[Passage]) -> String {
+    passages
+        .iter()
+        .enumerate()
+        .map(|(i, p)| format!("[{}] From \"{}\":\n{}", i + 1, p.name, p.text))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Prompt answering `request` from the passages
+pub fn build_knowledge_prompt(request: &str, passages: &[Passage]) -> String {
+    format!(
+        "The user asked: \"{}\"\n\nThese passages are from the user's own documents:\n---\n{}\n---\nAnswer from the passages only, citing them by number like [1]. If they don't answer the question, say so. Keep it short enough to read aloud.",
+        request,
+        numbered(passages)
+    )
+}
+
+/// Passages that closely match an open-ended `request`, when `auto_context`
+/// is on; empty otherwise, and when the search fails
+pub async fn relevant_context(app: &AppHandle, request: &str) -> Vec<Passage> {
+    let Ok(settings) = crate::settings::load_settings(app.clone()).await else {
+        return Vec::new();
+    };
+    if !settings.knowledge.enabled || !settings.knowledge.auto_context {
+        return Vec::new();
+    }
+    match search(app, request, settings.knowledge.top_k).await {
+        Ok(passages) => passages.into_iter().filter(|p| p.score >= AUTO_CONTEXT_MIN_SCORE).collect(),
+        Err(e) => {
+            info!("No document context for this request: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// System note handing the model `passages` alongside the conversation,
+/// numbered for `cited_files`
+pub fn build_context_note(passages: &[Passage]) -> String {
+    format!(
+        "Passages from the user's own documents that may help with the next message:\n---\n{}\n---\nUse them if they're relevant and cite them by number like [1]; otherwise ignore them.",
+        numbered(passages)
+    )
+}
+
+/// The files behind the `[n]` citations in `answer`, to show under it
+pub fn cited_files(answer: &str, passages: &[Passage]) -> String {
+    let cited: Vec<String> = passages
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| answer.contains(&format!("[{}]", i + 1)))
+        .map(|(i, p)| format!("[{}] {} - {}", i + 1, p.name, p.path))
+        .collect();
+    if cited.is_empty() {
+        return String::new();
+    }
+    format!("\n\nSources:\n{}", cited.join("\n"))
+}
+
+pub async fn ask(app: &AppHandle, question: &str) -> AstralResult<KnowledgeAnswer> {
+    let passages = search(app, question, top_k(app).await).await?;
+    if passages.is_empty() {
+        return Ok(KnowledgeAnswer {
+            answer: "I didn't find anything about that in your documents.".to_string(),
+            sources: passages,
+        });
+    }
+    let prompt = build_knowledge_prompt(question, &passages);
+    let response = app.state::<AppState>().send_llm_message(&prompt).await?;
+    Ok(KnowledgeAnswer { answer: response.content, sources: passages })
+}
+
+// ========== Tauri Commands ==========
+
+/// Answer a question from the knowledge base, with the passages it used
+#[tauri::command]
+pub async fn ask_documents(app: AppHandle, question: String) -> AstralResult<KnowledgeAnswer> {
+    if question.trim().is_empty() {
+        return Err(AstralError::invalid_input("Ask a question about your documents"));
+    }
+    ask(&app, question.trim()).await
+}
+
+#[tauri::command]
+pub async fn get_knowledge_status() -> KnowledgeStatus {
+    let mut status = status().clone();
+    if status.last_sync.is_none() {
+        // Before the first pass this session, report what's on disk
+        let store = STORE.read().unwrap_or_else(|e| e.into_inner());
+        status.documents = store.documents.values().filter(|d| !d.chunks.is_empty()).count();
+        status.chunks = store.documents.values().map(|d| d.chunks.len()).sum();
+    }
+    status
+}
+
+/// Drop every embedding and index the folders again
+#[tauri::command]
+pub async fn rebuild_knowledge_base() -> AstralResult<()> {
+    {
+        let mut store = STORE.write().unwrap_or_else(|e| e.into_inner());
+        store.documents.clear();
+        store.last_sync = 0;
+        save(&store)?;
+    }
+    wake();
+    Ok(())
+}
+
+fn describe(path: &str, document: &StoredDocument, store: &KnowledgeStore) -> KnowledgeDocument {
+    KnowledgeDocument {
+        path: path.to_string(),
+        name: document.name.clone(),
+        title: document.title.clone(),
+        kind: Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default(),
+        size: document.size,
+        modified: document.modified,
+        indexed_at: document.indexed_at,
+        chunks: document.chunks.len(),
+        added: store.added.contains(path),
+    }
+}
+
+/// Teach the knowledge base individual files (PDF, Markdown, text), wherever
+/// they are. They're kept current like the folders' documents.
+#[tauri::command]
+pub async fn ingest_documents(app: AppHandle, paths: Vec<String>) -> AstralResult<Vec<KnowledgeDocument>> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    if !settings.knowledge.enabled {
+        return Err(AstralError::not_configured("Turn on the knowledge base in Settings first"));
+    }
+    let paths: Vec<&str> = paths.iter().map(|p| p.trim()).filter(|p| !p.is_empty()).collect();
+    if paths.is_empty() {
+        return Err(AstralError::invalid_input("Pick the files to add"));
+    }
+
+    let mut ingested = Vec::with_capacity(paths.len());
+    for path in paths {
+        let metadata = tokio::fs::metadata(path)
+            .await
+            .map_err(|_| AstralError::new(ErrorCode::NotFound, format!("Couldn't find {}", path)))?;
+        if metadata.is_dir() {
+            return Err(AstralError::invalid_input(format!(
+                "{} is a folder; add it to the knowledge folders instead",
+                path
+            )));
+        }
+        let file = crate::file_indexer::entry_for(Path::new(path), &metadata)
+            .ok_or_else(|| AstralError::invalid_input(format!("{} isn't a file", path)))?;
+        if !file.is_text_document() {
+            return Err(AstralError::invalid_input(format!(
+                "I can't learn from {}; try a PDF, Markdown or text file",
+                file.name
+            )));
+        }
+        if file.size > MAX_FILE_BYTES {
+            return Err(AstralError::invalid_input(format!("{} is too large to learn from", file.name)));
+        }
+
+        let text = read_document(&file.path).await?;
+        let document = index_document(&settings, &file, &text).await?;
+        info!("Added {} to the knowledge base ({} passages)", file.path, document.chunks.len());
+
+        let mut store = STORE.write().unwrap_or_else(|e| e.into_inner());
+        store.excluded.remove(&file.path);
+        store.added.insert(file.path.clone());
+        store.documents.insert(file.path.clone(), document);
+        save(&store)?;
+        update_counts(&store);
+        ingested.push(describe(&file.path, &store.documents[&file.path], &store));
+    }
+    Ok(ingested)
+}
+
+/// Every document in the knowledge base, including ones with no readable text
+#[tauri::command]
+pub async fn list_knowledge_documents() -> Vec<KnowledgeDocument> {
+    let store = STORE.read().unwrap_or_else(|e| e.into_inner());
+    let mut documents: Vec<KnowledgeDocument> = store
+        .documents
+        .iter()
+        .map(|(path, document)| describe(path, document, &store))
+        .collect();
+    documents.sort_by_key(|d| d.name.to_lowercase());
+    documents
+}
+
+/// Take a document out of the knowledge base. One from a knowledge folder
+/// stays out until it's added again by hand.
+#[tauri::command]
+pub async fn remove_knowledge_document(app: AppHandle, path: String) -> AstralResult<()> {
+    let knowledge = crate::settings::load_settings(app).await?.knowledge;
+    let mut store = STORE.write().unwrap_or_else(|e| e.into_inner());
+    let stored = store.documents.remove(&path).is_some();
+    let added = store.added.remove(&path);
+    if !stored && !added {
+        return Err(AstralError::new(ErrorCode::NotFound, "That document isn't in the knowledge base"));
+    }
+    if knowledge.covers(Path::new(&path)) {
+        store.excluded.insert(path.clone());
+    }
+    save(&store)?;
+    update_counts(&store);
+    info!("Removed {} from the knowledge base", path);
+    Ok(())
+}
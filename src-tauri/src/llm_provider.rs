@@ -1,22 +1,75 @@
 // LLM Provider Module
-// Handles integration with multiple LLM providers (OpenAI, Claude, Ollama)
+// Handles integration with multiple LLM providers (OpenAI, Azure OpenAI,
+// OpenRouter, Claude, Ollama). Azure speaks OpenAI's API at a per-deployment
+// URL with an `api-key` header, and OpenRouter at its own URL with headers
+// naming the app; all three go through `Endpoint`.
+// With `tool_calling` on, a turn can also hand the model a set of tools and
+// run the ones it calls (see `ToolTurn` and `run_tools`): the model answers
+// "open spotify and turn it up" by calling `launch_app` itself, instead of
+// ASTRAL guessing the intent from the words. The conversation is kept within
+// the model's context window by estimated token count: once it fills most of
+// the room, the older turns are summarized into a system note rather than
+// dropped. Requests that hit a rate limit, an overloaded provider or a
+// dropped connection are retried with backoff (see `send_with_retry`), and
+// `cancel_requests` stops every request in flight, closing its connection.
+// `Embedded` runs a GGUF file in-process (see `embedded_llm`) for
+// machines without Ollama; it can't call tools.
+// A user message can carry images (a screenshot, a photo) for vision models
+// such as GPT-4o, Claude and llava; each provider gets them base64-encoded in
+// its own format (see `Image`). Only the text is kept in the history.
+// Every request's tokens go into the usage ledger, and requests to paid
+// providers are refused once the monthly spending cap is reached (see `usage`).
+// Short questions can be answered from `response_cache` instead of the API.
 
-use log::{info, warn};
-use anyhow::{Result, Context, bail};
+use tracing::{debug, info, warn};
+use anyhow::{Result, Context};
+use crate::error::{AstralError, ErrorCode};
+use crate::voice::bus::{self, PipelineEvent};
 use serde::{Deserialize, Serialize};
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
 use std::time::Duration;
+use once_cell::sync::Lazy;
+use tokio::sync::watch;
+
+/// System prompt template; `{{name}}` placeholders are filled from the
+/// manager's template variables. `system_prompt` in settings replaces it.
+pub const SYSTEM_PROMPT: &str = "You are AKI, a warm and human-like AI assistant. Be conversational, curious, and witty. Keep responses SHORT (1-2 sentences for simple questions). Use natural speech patterns with 'I think', 'I feel', humor, and warmth. Match the user's energy. Never say 'as an AI' - stay in character as a friendly, intelligent companion. Always reply in {{language}}.\n\nContext (only mention it when relevant): currently playing: {{now_playing}}; active window: {{active_window}}; upcoming calendar events: {{upcoming_events}}.";
 
 /// Supported LLM providers
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LLMProvider {
     OpenAI,
+    /// GPT models deployed in the user's own Azure resource
+    AzureOpenAI,
+    /// Many vendors' models behind one key; `model` is "<vendor>/<model>"
+    OpenRouter,
     Claude,
     Ollama,
+    /// A GGUF model run in-process by llama.cpp, from `embedded_model_path`
+    Embedded,
+}
+
+impl LLMProvider {
+    /// The `llm_provider` setting ("Ollama", "OpenAI", "Claude")
+    pub fn from_setting(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "openai" => Some(Self::OpenAI),
+            "azure" | "azureopenai" | "azure openai" => Some(Self::AzureOpenAI),
+            "openrouter" => Some(Self::OpenRouter),
+            "claude" | "anthropic" => Some(Self::Claude),
+            "ollama" => Some(Self::Ollama),
+            "embedded" | "llama.cpp" | "llamacpp" => Some(Self::Embedded),
+            _ => None,
+        }
+    }
 }
 
-/// LLM configuration
+/// LLM configuration; also the `llm` settings section
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LLMConfig {
     pub provider: LLMProvider,
     pub api_key: Option<String>,
@@ -24,6 +77,28 @@ pub struct LLMConfig {
     pub temperature: f32,
     pub max_tokens: u32,
     pub ollama_url: Option<String>,
+    /// Azure OpenAI resource URL, e.g. https://contoso.openai.azure.com
+    pub azure_endpoint: String,
+    /// The Azure deployment to chat with; `model` still names the underlying
+    /// model, for sizing the context
+    pub azure_deployment: String,
+    pub azure_api_version: String,
+    /// Offer the model ASTRAL's tools on open-ended requests
+    pub tool_calling: bool,
+    /// The model's context window in tokens; 0 uses the known size for the
+    /// model. Also sent to Ollama as `num_ctx` when set.
+    pub context_tokens: u32,
+    /// Most tokens of conversation sent along with a message; older turns are
+    /// summarized past it. A long history costs tokens and latency on every
+    /// request even when the model's window could take it.
+    pub history_budget_tokens: u32,
+    /// The .gguf file the `Embedded` provider runs
+    pub embedded_model_path: String,
+    /// Times to retry a rate-limited or failed request before giving up
+    pub max_retries: u32,
+    /// Replaces the built-in personality prompt when set; takes the same
+    /// `{{name}}` placeholders
+    pub system_prompt: String,
 }
 
 impl Default for LLMConfig {
@@ -35,6 +110,15 @@ impl Default for LLMConfig {
             temperature: 0.7,
             max_tokens: 150, // Reduced for shorter, snappier responses
             ollama_url: Some("http://localhost:11434".to_string()),
+            azure_endpoint: String::new(),
+            azure_deployment: String::new(),
+            azure_api_version: "2024-10-21".to_string(),
+            tool_calling: false,
+            context_tokens: 0,
+            history_budget_tokens: 4000,
+            embedded_model_path: String::new(),
+            max_retries: 3,
+            system_prompt: String::new(),
         }
     }
 }
@@ -44,6 +128,91 @@ impl Default for LLMConfig {
 pub struct Message {
     pub role: String,
     pub content: String,
+    /// Sent with this request only; each provider's request encodes them
+    #[serde(default, skip_serializing)]
+    pub images: Vec<Image>,
+}
+
+impl Message {
+    fn new(role: &str, content: impl Into<String>) -> Self {
+        Self { role: role.to_string(), content: content.into(), images: Vec::new() }
+    }
+}
+
+/// Largest image ASTRAL sends; Claude refuses anything bigger
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+/// Longest edge of a screenshot sent to a vision model; providers scale
+/// larger images down to about this anyway
+const MAX_SCREENSHOT_EDGE: u32 = 1568;
+
+/// An image for a vision model, base64-encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Image {
+    /// "image/png", "image/jpeg", "image/gif" or "image/webp", the formats
+    /// every vision provider takes
+    pub media_type: String,
+    pub data: String,
+}
+
+impl Image {
+    pub fn from_bytes(media_type: &str, bytes: &[u8]) -> std::result::Result<Self, AstralError> {
+        use base64::Engine;
+        if bytes.len() > MAX_IMAGE_BYTES {
+            return Err(AstralError::invalid_input(format!(
+                "That image is too large to send ({} MB, the limit is {} MB)",
+                bytes.len().div_ceil(1024 * 1024),
+                MAX_IMAGE_BYTES / (1024 * 1024)
+            )));
+        }
+        Ok(Self {
+            media_type: media_type.to_string(),
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        })
+    }
+
+    /// A PNG screenshot shrunk to `MAX_SCREENSHOT_EDGE` and re-encoded as
+    /// JPEG, so a 4K or multi-monitor capture still fits `MAX_IMAGE_BYTES`. Blocking.
+    pub fn from_screenshot(png: &[u8]) -> std::result::Result<Self, AstralError> {
+        let screenshot = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+            .map_err(|e| AstralError::new(ErrorCode::Internal, format!("Couldn't read the screenshot: {}", e)))?;
+        let screenshot = if screenshot.width().max(screenshot.height()) > MAX_SCREENSHOT_EDGE {
+            screenshot.resize(MAX_SCREENSHOT_EDGE, MAX_SCREENSHOT_EDGE, image::imageops::FilterType::Triangle)
+        } else {
+            screenshot
+        };
+        let pixels = screenshot.to_rgb8();
+
+        let mut jpeg = Vec::new();
+        for quality in [85, 70, 50] {
+            jpeg.clear();
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, quality)
+                .encode_image(&pixels)
+                .map_err(|e| AstralError::new(ErrorCode::Internal, format!("Couldn't encode the screenshot: {}", e)))?;
+            if jpeg.len() <= MAX_IMAGE_BYTES {
+                break;
+            }
+        }
+        Self::from_bytes("image/jpeg", &jpeg)
+    }
+
+    /// Read an image file, telling its format from the extension. Blocking.
+    pub fn from_path(path: &std::path::Path) -> std::result::Result<Self, AstralError> {
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let media_type = match extension.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            _ => {
+                return Err(AstralError::invalid_input(format!(
+                    "{} isn't a PNG, JPEG, GIF or WebP image",
+                    path.display()
+                )))
+            }
+        };
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(media_type, &bytes)
+    }
 }
 
 /// LLM response
@@ -58,9 +227,13 @@ pub struct LLMResponse {
 #[derive(Debug, Serialize)]
 struct OpenAIRequest {
     model: String,
-    messages: Vec<Message>,
+    /// See `openai_message`
+    messages: Vec<Value>,
     temperature: f32,
     max_tokens: u32,
+    /// `{"type": "json_object"}` for structured replies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 /// OpenAI API response format
@@ -77,14 +250,29 @@ struct OpenAIChoice {
 
 #[derive(Debug, Deserialize)]
 struct OpenAIUsage {
-    total_tokens: u32,
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+    /// OpenRouter leaves this out for some upstream providers
+    total_tokens: Option<u32>,
+}
+
+impl OpenAIUsage {
+    fn total(&self) -> u32 {
+        self.total_tokens.unwrap_or(self.prompt_tokens + self.completion_tokens)
+    }
 }
 
 /// Claude API request format
 #[derive(Debug, Serialize)]
 struct ClaudeRequest {
     model: String,
-    messages: Vec<Message>,
+    /// Claude takes the system prompt separately, not as a message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    /// See `claude_message`
+    messages: Vec<Value>,
     temperature: f32,
     max_tokens: u32,
 }
@@ -111,8 +299,15 @@ struct ClaudeUsage {
 #[derive(Debug, Serialize)]
 struct OllamaRequest {
     model: String,
-    messages: Vec<Message>,
+    /// See `ollama_message`
+    messages: Vec<Value>,
     stream: bool,
+    /// "json" for structured replies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
+    /// `{"num_ctx": ...}` when the context size is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<Value>,
 }
 
 /// Ollama API response format
@@ -120,6 +315,11 @@ struct OllamaRequest {
 struct OllamaResponse {
     message: Message,
     done: bool,
+    /// Tokens in the prompt and in the reply
+    #[serde(default)]
+    prompt_eval_count: u32,
+    #[serde(default)]
+    eval_count: u32,
 }
 
 /// LLM Provider Manager
@@ -127,123 +327,322 @@ pub struct LLMManager {
     config: LLMConfig,
     client: Client,
     conversation_history: Vec<Message>,
+    /// When the conversation last changed; `None` until this session adds to it
+    history_updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    template_variables: HashMap<String, String>,
+}
+
+/// Replace `{{name}}` with the matching variable; unknown names become "unknown"
+pub fn render_template(template: &str, variables: &HashMap<String, String>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        let name = rest[start + 2..start + end].trim();
+        rendered.push_str(variables.get(name).map(String::as_str).unwrap_or("unknown"));
+        rest = &rest[start + end + 2..];
+    }
+
+    rendered.push_str(rest);
+    rendered
 }
 
 impl LLMManager {
     pub fn new(config: LLMConfig) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = Self::http_client();
         
         info!("Initialized LLM Manager with provider: {:?}", config.provider);
         
+        let mut template_variables = HashMap::new();
+        template_variables.insert("language".to_string(), crate::locale::current_language_name().to_string());
+
         Self {
             config,
             client,
             conversation_history: Vec::new(),
+            history_updated_at: None,
+            template_variables,
         }
     }
 
-    /// Send a message to the LLM and get a response
+    /// Set a `{{name}}` variable used when rendering the system prompt
+    pub fn set_template_variable(&mut self, name: &str, value: impl Into<String>) {
+        self.template_variables.insert(name.to_string(), value.into());
+    }
+
+    /// Send a message to the LLM and get a response, reporting it on the pipeline bus
     pub async fn send_message(&mut self, user_message: &str) -> Result<LLMResponse> {
-        info!("Sending message to LLM: {}", user_message);
-        
-        // Add user message to history
-        self.conversation_history.push(Message {
-            role: "user".to_string(),
-            content: user_message.to_string(),
-        });
+        self.send_message_with_context(user_message, None).await
+    }
 
-        // Route to appropriate provider
-        let response = match self.config.provider {
-            LLMProvider::OpenAI => self.call_openai().await?,
-            LLMProvider::Claude => self.call_claude().await?,
-            LLMProvider::Ollama => self.call_ollama().await?,
-        };
+    /// `send_message` with a system note for this request only, e.g. passages
+    /// from the user's documents; it isn't kept in the history
+    pub async fn send_message_with_context(&mut self, user_message: &str, context: Option<&str>) -> Result<LLMResponse> {
+        self.send(user_message, context, Vec::new(), true).await
+    }
 
-        // Add assistant response to history
-        self.conversation_history.push(Message {
-            role: "assistant".to_string(),
-            content: response.content.clone(),
-        });
+    /// `send_message` that asks the provider even when a cached reply is fresh
+    pub async fn send_message_uncached(&mut self, user_message: &str) -> Result<LLMResponse> {
+        self.send(user_message, None, Vec::new(), false).await
+    }
 
-        // Keep only last 10 messages to avoid token limits
-        if self.conversation_history.len() > 10 {
-            self.conversation_history = self.conversation_history
-                .split_off(self.conversation_history.len() - 10);
+    /// `send_message` with images for a vision model to look at. The history
+    /// keeps a note that they were there, not the images.
+    pub async fn send_message_with_images(&mut self, user_message: &str, images: Vec<Image>) -> Result<LLMResponse> {
+        self.send(user_message, None, images, false).await
+    }
+
+    async fn send(&mut self, user_message: &str, context: Option<&str>, images: Vec<Image>, use_cache: bool) -> Result<LLMResponse> {
+        debug!("Sending message to LLM: {} ({} images)", user_message, images.len());
+
+        let cache_key = (use_cache && context.is_none() && images.is_empty())
+            .then(|| self.cache_key(user_message, false))
+            .flatten();
+        if let Some(response) = cache_key.as_deref().and_then(crate::response_cache::get) {
+            info!("Answering from the response cache");
+            publish_response(&response);
+            self.remember(user_message, &response.content).await;
+            return Ok(response);
+        }
+
+        let image_count = images.len();
+        let mut messages = self.get_messages_with_system_prompt();
+        if let Some(context) = context {
+            messages.push(Message::new("system", context));
+        }
+        messages.push(Message { images, ..Message::new("user", user_message) });
+
+        let response = match self.call(messages, false).await {
+            Ok(response) => response,
+            Err(e) => {
+                bus::publish(PipelineEvent::StageFailed { stage: bus::Stage::Llm, error: e.to_string() });
+                return Err(e);
+            }
+        };
+        publish_response(&response);
+        if let Some(key) = cache_key {
+            crate::response_cache::put(key, &response);
+        }
+        if image_count > 0 {
+            let noted = format!("{}\n[{} image(s) attached]", user_message, image_count);
+            self.remember(&noted, &response.content).await;
+        } else {
+            self.remember(user_message, &response.content).await;
         }
 
         Ok(response)
     }
 
+    /// Add a finished exchange to the conversation history, summarizing the
+    /// older turns if it's grown too long for the model
+    pub async fn remember(&mut self, user_message: &str, reply: &str) {
+        self.conversation_history.push(Message::new("user", user_message));
+        self.conversation_history.push(Message::new("assistant", reply));
+
+        self.history_updated_at = Some(chrono::Utc::now());
+        self.compact_history().await;
+    }
+
+    /// Tokens for the conversation: `history_budget_tokens`, or what's left
+    /// of the window once the system prompt and reply are counted if that's less
+    fn history_budget(&self) -> usize {
+        let system = self.system_prompt();
+        let window = context_window(&self.config)
+            .saturating_sub(self.config.max_tokens as usize)
+            .saturating_sub(estimate_tokens(self.config.provider, &system));
+        window.min(self.config.history_budget_tokens as usize)
+    }
+
+    fn history_tokens(&self) -> usize {
+        self.conversation_history
+            .iter()
+            .map(|m| estimate_tokens(self.config.provider, &m.content) + MESSAGE_OVERHEAD)
+            .sum()
+    }
+
+    /// Fold everything but the last few messages into a summary once the
+    /// history passes `COMPACT_AT` of its budget
+    async fn compact_history(&mut self) {
+        let budget = self.history_budget();
+        if (self.history_tokens() as f32) < budget as f32 * COMPACT_AT || self.conversation_history.len() <= KEEP_RECENT {
+            return;
+        }
+
+        let recent = self.conversation_history.split_off(self.conversation_history.len() - KEEP_RECENT);
+        let older = std::mem::replace(&mut self.conversation_history, recent);
+        let transcript: Vec<String> = older
+            .iter()
+            .map(|m| match m.role.as_str() {
+                "system" => m.content.clone(),
+                role => format!("{}: {}", role, m.content),
+            })
+            .collect();
+        let messages = vec![Message::new("system", SUMMARY_PROMPT), Message::new("user", transcript.join("\n\n"))];
+
+        match self.call(messages, false).await {
+            Ok(summary) => {
+                info!("Summarized {} older messages into {} characters", older.len(), summary.content.len());
+                self.conversation_history
+                    .insert(0, Message::new("system", format!("{}{}", SUMMARY_PREFIX, summary.content.trim())));
+            }
+            Err(e) => warn!("Couldn't summarize the conversation, dropping {} older messages: {}", older.len(), e),
+        }
+
+        // A single huge message (a pasted document) can still be too much; lose
+        // the oldest exchanges, keeping the summary and the latest one
+        loop {
+            let oldest = usize::from(self.conversation_history.first().is_some_and(|m| m.role == "system"));
+            if self.history_tokens() <= budget || self.conversation_history.len() <= oldest + 2 {
+                break;
+            }
+            self.conversation_history.drain(oldest..oldest + 2);
+        }
+    }
+
+    /// Whether open-ended requests should be answered with tools
+    pub fn tools_enabled(&self) -> bool {
+        self.config.tool_calling && self.config.provider != LLMProvider::Embedded
+    }
+
+    /// Start answering `user_message` with `tools` on offer. The turn carries
+    /// its own copy of the conversation and runs without the manager, so a tool
+    /// that asks the LLM something itself (a routine's system report) doesn't
+    /// wait on it. Pass the reply to `remember` once it's in. `context` is a
+    /// system note for this turn only, as with `send_message_with_context`.
+    pub fn begin_tool_turn(&self, user_message: &str, tools: Vec<ToolDefinition>, context: Option<&str>) -> ToolTurn {
+        let claude = self.config.provider == LLMProvider::Claude;
+        let mut system = self.system_prompt();
+        let mut transcript: Vec<Value> = Vec::new();
+        if claude {
+            // Claude takes the system prompt and summary separately, not as messages
+            for summary in self.conversation_history.iter().filter(|m| m.role == "system") {
+                system = format!("{}\n\n{}", system, summary.content);
+            }
+        } else {
+            transcript.push(json!({ "role": "system", "content": system }));
+        }
+        transcript.extend(
+            self.conversation_history
+                .iter()
+                .filter(|m| !(claude && m.role == "system"))
+                .map(|m| json!({ "role": m.role, "content": m.content })),
+        );
+        match context {
+            Some(context) if claude => system = format!("{}\n\n{}", system, context),
+            Some(context) => transcript.push(json!({ "role": "system", "content": context })),
+            None => {}
+        }
+        transcript.push(json!({ "role": "user", "content": user_message }));
+
+        let cache_key = context.is_none().then(|| self.cache_key(user_message, !tools.is_empty())).flatten();
+        ToolTurn {
+            config: self.config.clone(),
+            client: self.client.clone(),
+            system,
+            transcript,
+            tools,
+            tokens_used: None,
+            cache_key,
+        }
+    }
+
+    /// One-off request for a JSON reply, outside the conversation history
+    pub async fn complete_json(&self, system_prompt: &str, user_message: &str) -> Result<LLMResponse> {
+        let messages = vec![Message::new("system", system_prompt), Message::new("user", user_message)];
+        self.call(messages, true).await
+    }
+
+    /// Route to the configured provider
+    async fn call(&self, messages: Vec<Message>, json: bool) -> Result<LLMResponse> {
+        cancellable(crate::metrics::time(crate::metrics::Stage::LlmResponse, async {
+            crate::usage::check_cap(self.config.provider)?;
+            match self.config.provider {
+                LLMProvider::OpenAI | LLMProvider::AzureOpenAI | LLMProvider::OpenRouter => {
+                    self.call_openai(messages, json).await
+                }
+                LLMProvider::Claude => self.call_claude(messages).await,
+                LLMProvider::Ollama => self.call_ollama(messages, json).await,
+                LLMProvider::Embedded => {
+                    if messages.iter().any(|m| !m.images.is_empty()) {
+                        anyhow::bail!(AstralError::invalid_input(
+                            "The built-in model can't see images; switch to a vision model like llava, GPT-4o or Claude"
+                        ));
+                    }
+                    let response = crate::embedded_llm::complete(&self.config, messages).await?;
+                    crate::usage::record(LLMProvider::Embedded, &response.model, 0, response.tokens_used.unwrap_or(0).into());
+                    Ok(response)
+                }
+            }
+        }))
+        .await
+    }
+
     /// Call OpenAI API (GPT-4)
-    async fn call_openai(&self) -> Result<LLMResponse> {
-        let api_key = self.config.api_key.as_ref()
-            .context("OpenAI API key not configured")?;
+    async fn call_openai(&self, messages: Vec<Message>, json: bool) -> Result<LLMResponse> {
+        let endpoint = Endpoint::chat(&self.config)?;
 
         let request = OpenAIRequest {
             model: self.config.model.clone(),
-            messages: self.get_messages_with_system_prompt(),
+            messages: messages.iter().map(openai_message).collect(),
             temperature: self.config.temperature,
             max_tokens: self.config.max_tokens,
+            response_format: json.then(|| serde_json::json!({ "type": "json_object" })),
         };
 
-        let response = self.client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
+        let response = send_with_retry(endpoint.service, endpoint.post(&self.client).json(&request), self.config.max_retries)
             .await
-            .context("Failed to call OpenAI API")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            bail!("OpenAI API error: {}", error_text);
-        }
+            .with_context(|| format!("Failed to call {} API", endpoint.service))?;
 
         let openai_response: OpenAIResponse = response.json().await
-            .context("Failed to parse OpenAI response")?;
+            .with_context(|| format!("Failed to parse {} response", endpoint.service))?;
 
         let content = openai_response.choices
             .first()
-            .context("No response from OpenAI")?
+            .with_context(|| format!("No response from {}", endpoint.service))?
             .message.content.clone();
+        if let Some(usage) = &openai_response.usage {
+            crate::usage::record(self.config.provider, &self.config.model, usage.prompt_tokens.into(), usage.completion_tokens.into());
+        }
 
         Ok(LLMResponse {
             content,
             model: self.config.model.clone(),
-            tokens_used: openai_response.usage.map(|u| u.total_tokens),
+            tokens_used: openai_response.usage.map(|u| u.total()),
         })
     }
 
-    /// Call Claude API (Anthropic)
-    async fn call_claude(&self) -> Result<LLMResponse> {
+    /// Call Claude API (Anthropic); there's no JSON mode, the prompt asks for it
+    async fn call_claude(&self, messages: Vec<Message>) -> Result<LLMResponse> {
         let api_key = self.config.api_key.as_ref()
-            .context("Claude API key not configured")?;
+            .ok_or_else(|| AstralError::not_configured("Add a Claude API key in settings"))?;
 
+        // The system prompt and any conversation summary
+        let (system, messages): (Vec<Message>, Vec<Message>) = messages.into_iter().partition(|m| m.role == "system");
+        let system: Vec<String> = system.into_iter().map(|m| m.content).collect();
         let request = ClaudeRequest {
             model: self.config.model.clone(),
-            messages: self.conversation_history.clone(),
+            system: (!system.is_empty()).then(|| system.join("\n\n")),
+            messages: messages.iter().map(claude_message).collect(),
             temperature: self.config.temperature,
             max_tokens: self.config.max_tokens,
         };
 
-        let response = self.client
-            .post("https://api.anthropic.com/v1/messages")
-            .header("x-api-key", api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to call Claude API")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            bail!("Claude API error: {}", error_text);
-        }
+        let response = send_with_retry(
+            "Claude",
+            self.client
+                .post("https://api.anthropic.com/v1/messages")
+                .header("x-api-key", api_key)
+                .header("anthropic-version", "2023-06-01")
+                .header("Content-Type", "application/json")
+                .json(&request),
+            self.config.max_retries,
+        )
+        .await
+        .context("Failed to call Claude API")?;
 
         let claude_response: ClaudeResponse = response.json().await
             .context("Failed to parse Claude response")?;
@@ -253,6 +652,9 @@ impl LLMManager {
             .context("No response from Claude")?
             .text.clone();
 
+        if let Some(usage) = &claude_response.usage {
+            crate::usage::record(LLMProvider::Claude, &self.config.model, usage.input_tokens.into(), usage.output_tokens.into());
+        }
         let tokens_used = claude_response.usage.map(|u| u.input_tokens + u.output_tokens);
 
         Ok(LLMResponse {
@@ -263,49 +665,63 @@ impl LLMManager {
     }
 
     /// Call Ollama API (local LLM)
-    async fn call_ollama(&self) -> Result<LLMResponse> {
+    async fn call_ollama(&self, messages: Vec<Message>, json: bool) -> Result<LLMResponse> {
         let ollama_url = self.config.ollama_url.as_ref()
-            .context("Ollama URL not configured")?;
+            .ok_or_else(|| AstralError::not_configured("Set the Ollama URL in settings"))?;
 
         let request = OllamaRequest {
             model: self.config.model.clone(),
-            messages: self.get_messages_with_system_prompt(),
+            messages: messages.iter().map(ollama_message).collect(),
             stream: false,
+            format: json.then(|| "json".to_string()),
+            options: ollama_options(&self.config),
         };
 
         let url = format!("{}/api/chat", ollama_url);
         
-        let response = self.client
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await
-            .context("Failed to call Ollama API - is Ollama running?")?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            bail!("Ollama API error: {} - Make sure Ollama is running with 'ollama serve'", error_text);
-        }
+        let response = send_with_retry(
+            "Ollama",
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .json(&request),
+            self.config.max_retries,
+        )
+        .await
+        .context("Failed to call Ollama API - is Ollama running?")?;
 
         let ollama_response: OllamaResponse = response.json().await
             .context("Failed to parse Ollama response")?;
 
+        let (input, output) = (ollama_response.prompt_eval_count, ollama_response.eval_count);
+        crate::usage::record(LLMProvider::Ollama, &self.config.model, input.into(), output.into());
+
         Ok(LLMResponse {
             content: ollama_response.message.content,
             model: self.config.model.clone(),
-            tokens_used: None,
+            tokens_used: Some(input + output),
         })
     }
 
+    /// The custom system prompt, or the built-in one
+    fn system_template(&self) -> &str {
+        let template = self.config.system_prompt.trim();
+        if template.is_empty() { SYSTEM_PROMPT } else { template }
+    }
+
+    /// The system prompt with its variables filled in
+    fn system_prompt(&self) -> String {
+        render_template(self.system_template(), &self.template_variables)
+    }
+
+    /// Where the reply to `user_message` is cached, if replies to it are
+    fn cache_key(&self, user_message: &str, tools: bool) -> Option<String> {
+        crate::response_cache::key(&self.config, &self.system_prompt(), &self.conversation_history, tools, user_message)
+    }
+
     /// Get messages with system prompt prepended
     fn get_messages_with_system_prompt(&self) -> Vec<Message> {
-        let system_prompt = Message {
-            role: "system".to_string(),
-            content: "You are AKI, a warm and human-like AI assistant. Be conversational, curious, and witty. Keep responses SHORT (1-2 sentences for simple questions). Use natural speech patterns with 'I think', 'I feel', humor, and warmth. Match the user's energy. Never say 'as an AI' - stay in character as a friendly, intelligent companion.".to_string(),
-        };
-
-        let mut messages = vec![system_prompt];
+        let mut messages = vec![Message::new("system", self.system_prompt())];
         messages.extend(self.conversation_history.clone());
         messages
     }
@@ -314,6 +730,19 @@ impl LLMManager {
     pub fn clear_history(&mut self) {
         info!("Clearing conversation history");
         self.conversation_history.clear();
+        self.history_updated_at = Some(chrono::Utc::now());
+    }
+
+    /// When the conversation last changed, for sync
+    pub fn history_updated_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.history_updated_at
+    }
+
+    /// Continue a conversation from another device
+    pub fn restore_history(&mut self, messages: Vec<Message>, updated_at: chrono::DateTime<chrono::Utc>) {
+        info!("Restoring {} conversation messages", messages.len());
+        self.conversation_history = messages;
+        self.history_updated_at = Some(updated_at);
     }
 
     /// Get conversation history
@@ -325,7 +754,731 @@ impl LLMManager {
     pub fn update_config(&mut self, config: LLMConfig) {
         info!("Updating LLM configuration");
         self.config = config;
+        // Picks up a changed proxy
+        self.client = Self::http_client();
+    }
+
+    fn http_client() -> Client {
+        crate::proxy::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client")
+    }
+}
+
+// ========== Cancellation ==========
+
+/// Bumped by `cancel_requests`. Every request in flight watches it, so
+/// stopping doesn't need the manager, whose lock the request may be holding.
+static CANCEL: Lazy<watch::Sender<u64>> = Lazy::new(|| watch::channel(0).0);
+
+/// Notices `cancel_requests`, for work that can't simply be dropped (the
+/// built-in model generating on a blocking thread)
+pub fn cancel_signal() -> watch::Receiver<u64> {
+    CANCEL.subscribe()
+}
+
+/// Stop every LLM request in flight; dropping a request closes its HTTP
+/// connection, so the provider stops generating too. False if none was running.
+pub fn cancel_requests() -> bool {
+    let running = CANCEL.receiver_count() > 0;
+    if running {
+        info!("Cancelling {} LLM request(s)", CANCEL.receiver_count());
+    }
+    CANCEL.send_modify(|generation| *generation += 1);
+    running
+}
+
+/// Run `future` until it finishes or `cancel_requests` is called
+async fn cancellable<T>(future: impl Future<Output = Result<T>>) -> Result<T> {
+    let mut cancel = CANCEL.subscribe();
+    tokio::select! {
+        result = future => result,
+        _ = cancel.changed() => Err(cancelled().into()),
+    }
+}
+
+fn cancelled() -> AstralError {
+    AstralError::new(ErrorCode::Cancelled, "Request cancelled")
+}
+
+// ========== Retries ==========
+
+/// First wait after a failed attempt; doubles on each retry
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest ASTRAL will wait before retrying; a Retry-After longer than this
+/// fails the request straight away, since the user is waiting for an answer
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(20);
+
+/// Rate limits and overloads, which usually clear by themselves (529 is
+/// Anthropic's "overloaded")
+fn is_transient(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 502 | 503 | 504 | 529)
+}
+
+/// The request never reached the provider
+fn is_connect_error(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(|e| e.is_connect())
+}
+
+/// How long the provider asked for: `retry-after-ms` (OpenAI and Azure), or
+/// `Retry-After` in seconds or as a date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.parse::<f64>().ok()) {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
     }
+    let value = header("retry-after")?;
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or_default())
+}
+
+/// Exponential, with jitter so parallel requests don't retry in step
+fn backoff(attempt: u32) -> Duration {
+    let delay = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt)).min(RETRY_MAX_DELAY);
+    delay.mul_f64(0.75 + rand::random::<f64>() * 0.5).min(RETRY_MAX_DELAY)
+}
+
+/// Send a provider request, retrying up to `max_retries` times when it's
+/// rate limited, the provider is overloaded or the connection fails. Waits as
+/// long as Retry-After asks, or backs off exponentially. Returns only
+/// successful responses; an error status becomes an `AstralError` carrying
+/// the provider's Retry-After.
+async fn send_with_retry(service: &str, request: RequestBuilder, max_retries: u32) -> Result<Response> {
+    let mut attempt = 0;
+    loop {
+        let this_try = request.try_clone().context("The request can't be resent")?;
+        let wait = match crate::privacy::send(this_try).await {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) => {
+                let status = response.status();
+                let asked = retry_after(&response);
+                let body = response.text().await.unwrap_or_default();
+                let error = AstralError::from_status(service, status, &body).with_retry_after(asked);
+                if !is_transient(status) || attempt >= max_retries || asked.is_some_and(|wait| wait > RETRY_MAX_DELAY) {
+                    return Err(error.into());
+                }
+                let wait = asked.unwrap_or_else(|| backoff(attempt));
+                warn!("{} answered {}, retrying in {:.1}s", service, status, wait.as_secs_f32());
+                wait
+            }
+            Err(e) if attempt < max_retries && is_connect_error(&e) => {
+                let wait = backoff(attempt);
+                warn!("Couldn't reach {}, retrying in {:.1}s: {}", service, wait.as_secs_f32(), e);
+                wait
+            }
+            Err(e) => return Err(e),
+        };
+        tokio::time::sleep(wait).await;
+        attempt += 1;
+    }
+}
+
+/// Ollama refusing tools for a model that can't use them
+fn rejected_tools(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<AstralError>()
+        .and_then(|e| e.detail.as_deref())
+        .is_some_and(|detail| detail.starts_with("HTTP 400") && detail.contains("does not support tools"))
+}
+
+/// Where OpenAI-style requests go, for the providers that speak OpenAI's API
+struct Endpoint {
+    /// Names the provider in errors
+    service: &'static str,
+    url: String,
+    headers: Vec<(&'static str, String)>,
+}
+
+impl Endpoint {
+    fn chat(config: &LLMConfig) -> Result<Self> {
+        match config.provider {
+            LLMProvider::AzureOpenAI => Self::azure(config, &config.azure_deployment, "chat/completions"),
+            LLMProvider::OpenRouter => Self::openrouter(config),
+            _ => Self::openai(config, "chat/completions"),
+        }
+    }
+
+    /// On Azure, `model` names the embeddings deployment
+    fn embeddings(config: &LLMConfig, model: &str) -> Result<Self> {
+        match config.provider {
+            LLMProvider::AzureOpenAI => Self::azure(config, model, "embeddings"),
+            _ => Self::openai(config, "embeddings"),
+        }
+    }
+
+    fn openai(config: &LLMConfig, operation: &str) -> Result<Self> {
+        let api_key = config.api_key.as_ref()
+            .ok_or_else(|| AstralError::not_configured("Add an OpenAI API key in settings"))?;
+        Ok(Self {
+            service: "OpenAI",
+            url: format!("https://api.openai.com/v1/{}", operation),
+            headers: vec![("Authorization", format!("Bearer {}", api_key))],
+        })
+    }
+
+    fn openrouter(config: &LLMConfig) -> Result<Self> {
+        let api_key = config.api_key.as_ref()
+            .ok_or_else(|| AstralError::not_configured("Add an OpenRouter API key in settings"))?;
+        Ok(Self {
+            service: "OpenRouter",
+            url: "https://openrouter.ai/api/v1/chat/completions".to_string(),
+            // OpenRouter attributes requests to the app these name
+            headers: vec![
+                ("Authorization", format!("Bearer {}", api_key)),
+                ("HTTP-Referer", "https://github.com/precie21/astral-assistant".to_string()),
+                ("X-Title", "ASTRAL".to_string()),
+            ],
+        })
+    }
+
+    fn azure(config: &LLMConfig, deployment: &str, operation: &str) -> Result<Self> {
+        let api_key = config.api_key.as_ref()
+            .ok_or_else(|| AstralError::not_configured("Add an Azure OpenAI API key in settings"))?;
+        let endpoint = config.azure_endpoint.trim().trim_end_matches('/');
+        if endpoint.is_empty() || deployment.trim().is_empty() {
+            return Err(AstralError::not_configured("Set the Azure OpenAI endpoint and deployment in settings").into());
+        }
+        Ok(Self {
+            service: "Azure OpenAI",
+            url: format!(
+                "{}/openai/deployments/{}/{}?api-version={}",
+                endpoint,
+                deployment.trim(),
+                operation,
+                config.azure_api_version.trim()
+            ),
+            headers: vec![("api-key", api_key.clone())],
+        })
+    }
+
+    fn post(&self, client: &Client) -> reqwest::RequestBuilder {
+        self.headers
+            .iter()
+            .fold(client.post(&self.url), |request, (name, value)| request.header(*name, value))
+    }
+}
+
+// ========== Message Formats ==========
+
+/// OpenAI's shape, also Azure's and OpenRouter's: images join the text in a
+/// content array, as data URLs
+fn openai_message(message: &Message) -> Value {
+    if message.images.is_empty() {
+        return json!({ "role": message.role, "content": message.content });
+    }
+    let mut parts = vec![json!({ "type": "text", "text": message.content })];
+    parts.extend(message.images.iter().map(|image| {
+        json!({
+            "type": "image_url",
+            "image_url": { "url": format!("data:{};base64,{}", image.media_type, image.data) },
+        })
+    }));
+    json!({ "role": message.role, "content": parts })
+}
+
+/// Claude's shape: image blocks go before the text, which Anthropic says
+/// works best
+fn claude_message(message: &Message) -> Value {
+    if message.images.is_empty() {
+        return json!({ "role": message.role, "content": message.content });
+    }
+    let mut blocks: Vec<Value> = message
+        .images
+        .iter()
+        .map(|image| {
+            json!({
+                "type": "image",
+                "source": { "type": "base64", "media_type": image.media_type, "data": image.data },
+            })
+        })
+        .collect();
+    blocks.push(json!({ "type": "text", "text": message.content }));
+    json!({ "role": message.role, "content": blocks })
+}
+
+/// Ollama's shape: bare base64 in an `images` list beside the text
+fn ollama_message(message: &Message) -> Value {
+    let mut value = json!({ "role": message.role, "content": message.content });
+    if !message.images.is_empty() {
+        value["images"] = message.images.iter().map(|image| json!(image.data)).collect();
+    }
+    value
+}
+
+/// Report a reply on the pipeline bus
+fn publish_response(response: &LLMResponse) {
+    bus::publish(PipelineEvent::LlmResponded {
+        model: response.model.clone(),
+        chars: response.content.chars().count(),
+        tokens_used: response.tokens_used,
+    });
+}
+
+// ========== Context Budget ==========
+
+/// Share of the history budget that can fill up before older turns are summarized
+const COMPACT_AT: f32 = 0.75;
+
+/// Messages always kept word for word when summarizing
+const KEEP_RECENT: usize = 4;
+
+/// Tokens each message costs beyond its text (role and separators)
+const MESSAGE_OVERHEAD: usize = 4;
+
+const SUMMARY_PREFIX: &str = "Summary of the conversation so far: ";
+
+const SUMMARY_PROMPT: &str = "Summarize this conversation between the user and the assistant in a few sentences, as notes for the assistant. Keep names, facts, preferences, decisions and anything left unanswered; drop small talk. Write the summary only.";
+
+/// Estimated tokens in `text`. Close enough for budgeting without shipping each
+/// provider's tokenizer: GPT's tokenizers average about 4 characters a token
+/// on English, Claude's and the Llama-family models Ollama and llama.cpp run
+/// nearer 3.5.
+pub fn estimate_tokens(provider: LLMProvider, text: &str) -> usize {
+    let chars_per_token = match provider {
+        LLMProvider::OpenAI | LLMProvider::AzureOpenAI => 4.0,
+        LLMProvider::OpenRouter | LLMProvider::Claude | LLMProvider::Ollama | LLMProvider::Embedded => 3.5,
+    };
+    (text.chars().count() as f32 / chars_per_token).ceil() as usize
+}
+
+/// The model's context window in tokens, from `context_tokens` or the model name
+pub fn context_window(config: &LLMConfig) -> usize {
+    if config.context_tokens > 0 {
+        return config.context_tokens as usize;
+    }
+    let model = config.model.to_lowercase();
+    match config.provider {
+        LLMProvider::OpenAI | LLMProvider::AzureOpenAI => openai_context_window(&model),
+        LLMProvider::OpenRouter => match model.split_once('/') {
+            Some(("openai", name)) => openai_context_window(name),
+            Some(("anthropic", _)) => 200_000,
+            // Most of what OpenRouter lists has at least this much
+            _ => 32_768,
+        },
+        LLMProvider::Claude => 200_000,
+        // Ollama's default when neither the request nor the Modelfile sets num_ctx
+        LLMProvider::Ollama => 4_096,
+        LLMProvider::Embedded => crate::embedded_llm::DEFAULT_CONTEXT_TOKENS as usize,
+    }
+}
+
+fn openai_context_window(model: &str) -> usize {
+    if model.starts_with("gpt-3.5") {
+        16_385
+    } else if model == "gpt-4" || model.starts_with("gpt-4-0") {
+        8_192
+    } else if model.starts_with("gpt-4.1") {
+        1_047_576
+    } else {
+        128_000
+    }
+}
+
+fn ollama_options(config: &LLMConfig) -> Option<Value> {
+    (config.context_tokens > 0).then(|| json!({ "num_ctx": config.context_tokens }))
+}
+
+// ========== Tool Calling ==========
+
+/// Rounds of tool calls allowed in one reply, so a confused model can't loop forever
+const MAX_TOOL_ROUNDS: usize = 5;
+
+/// A function the model may call; `parameters` is a JSON Schema for its arguments
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+/// A call the model asked for
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    /// The provider's id for the call, echoed back with its result
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+enum ToolStep {
+    Reply(LLMResponse),
+    Calls(Vec<ToolCall>),
+}
+
+/// A user message being answered with tools. The transcript is kept in the
+/// provider's own format, since each shapes tool calls and results differently;
+/// only the final reply goes into the conversation history.
+pub struct ToolTurn {
+    config: LLMConfig,
+    client: Client,
+    system: String,
+    transcript: Vec<Value>,
+    tools: Vec<ToolDefinition>,
+    tokens_used: Option<u32>,
+    /// Set while a plain reply could still be cached; a tool call clears it
+    cache_key: Option<String>,
+}
+
+impl ToolTurn {
+    fn count_tokens(&mut self, tokens: Option<u64>) {
+        if let Some(tokens) = tokens {
+            self.tokens_used = Some(self.tokens_used.unwrap_or(0) + tokens as u32);
+        }
+    }
+
+    fn reply(&self, content: String) -> ToolStep {
+        ToolStep::Reply(LLMResponse {
+            content,
+            model: self.config.model.clone(),
+            tokens_used: self.tokens_used,
+        })
+    }
+
+    async fn step(&mut self) -> Result<ToolStep> {
+        cancellable(crate::metrics::time(crate::metrics::Stage::LlmResponse, async {
+            crate::usage::check_cap(self.config.provider)?;
+            match self.config.provider {
+                LLMProvider::OpenAI | LLMProvider::AzureOpenAI | LLMProvider::OpenRouter => self.step_openai().await,
+                LLMProvider::Claude => self.step_claude().await,
+                LLMProvider::Ollama => self.step_ollama().await,
+                LLMProvider::Embedded => anyhow::bail!("The built-in model can't call tools"),
+            }
+        }))
+        .await
+    }
+
+    async fn step_openai(&mut self) -> Result<ToolStep> {
+        let endpoint = Endpoint::chat(&self.config)?;
+
+        let mut request = json!({
+            "model": self.config.model,
+            "messages": self.transcript,
+            "temperature": self.config.temperature,
+            "max_tokens": self.config.max_tokens,
+        });
+        if !self.tools.is_empty() {
+            request["tools"] = self.tools.iter().map(|t| json!({ "type": "function", "function": t })).collect();
+        }
+
+        let response = send_with_retry(endpoint.service, endpoint.post(&self.client).json(&request), self.config.max_retries)
+            .await
+            .with_context(|| format!("Failed to call {} API", endpoint.service))?;
+
+        let body: Value = response.json().await
+            .with_context(|| format!("Failed to parse {} response", endpoint.service))?;
+        let usage = body.get("usage").cloned().and_then(|u| serde_json::from_value::<OpenAIUsage>(u).ok());
+        if let Some(usage) = &usage {
+            crate::usage::record(self.config.provider, &self.config.model, usage.prompt_tokens.into(), usage.completion_tokens.into());
+        }
+        self.count_tokens(usage.map(|u| u.total() as u64));
+        let message = body.pointer("/choices/0/message").cloned()
+            .with_context(|| format!("No response from {}", endpoint.service))?;
+        let calls: Vec<ToolCall> = message
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .map(|calls| {
+                calls
+                    .iter()
+                    .filter_map(|call| {
+                        Some(ToolCall {
+                            id: call.get("id")?.as_str()?.to_string(),
+                            name: call.pointer("/function/name")?.as_str()?.to_string(),
+                            // The arguments come as a JSON string
+                            arguments: call
+                                .pointer("/function/arguments")
+                                .and_then(Value::as_str)
+                                .and_then(|a| serde_json::from_str(a).ok())
+                                .unwrap_or_else(|| json!({})),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if calls.is_empty() {
+            let content = message.get("content").and_then(Value::as_str).unwrap_or_default().to_string();
+            return Ok(self.reply(content));
+        }
+        self.transcript.push(json!({
+            "role": "assistant",
+            "content": message.get("content"),
+            "tool_calls": message.get("tool_calls"),
+        }));
+        Ok(ToolStep::Calls(calls))
+    }
+
+    async fn step_claude(&mut self) -> Result<ToolStep> {
+        let api_key = self.config.api_key.as_ref()
+            .ok_or_else(|| AstralError::not_configured("Add a Claude API key in settings"))?;
+
+        let mut request = json!({
+            "model": self.config.model,
+            "system": self.system,
+            "messages": self.transcript,
+            "temperature": self.config.temperature,
+            "max_tokens": self.config.max_tokens,
+        });
+        if !self.tools.is_empty() {
+            request["tools"] = self
+                .tools
+                .iter()
+                .map(|t| json!({ "name": t.name, "description": t.description, "input_schema": t.parameters }))
+                .collect();
+        }
+
+        let response = send_with_retry(
+            "Claude",
+            self.client
+                .post("https://api.anthropic.com/v1/messages")
+                .header("x-api-key", api_key)
+                .header("anthropic-version", "2023-06-01")
+                .json(&request),
+            self.config.max_retries,
+        )
+        .await
+        .context("Failed to call Claude API")?;
+
+        let body: Value = response.json().await.context("Failed to parse Claude response")?;
+        let [input, output] = ["/usage/input_tokens", "/usage/output_tokens"].map(|p| body.pointer(p).and_then(Value::as_u64));
+        if input.is_some() || output.is_some() {
+            crate::usage::record(LLMProvider::Claude, &self.config.model, input.unwrap_or(0), output.unwrap_or(0));
+        }
+        self.count_tokens(input.into_iter().chain(output).reduce(|a, b| a + b));
+
+        let blocks = body.get("content").and_then(Value::as_array).cloned().context("No response from Claude")?;
+        let calls: Vec<ToolCall> = blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(Value::as_str) == Some("tool_use"))
+            .filter_map(|b| {
+                Some(ToolCall {
+                    id: b.get("id")?.as_str()?.to_string(),
+                    name: b.get("name")?.as_str()?.to_string(),
+                    arguments: b.get("input").cloned().unwrap_or_else(|| json!({})),
+                })
+            })
+            .collect();
+
+        if calls.is_empty() {
+            let content: Vec<&str> = blocks.iter().filter_map(|b| b.get("text").and_then(Value::as_str)).collect();
+            return Ok(self.reply(content.join("")));
+        }
+        self.transcript.push(json!({ "role": "assistant", "content": blocks }));
+        Ok(ToolStep::Calls(calls))
+    }
+
+    async fn step_ollama(&mut self) -> Result<ToolStep> {
+        let ollama_url = self.config.ollama_url.clone()
+            .ok_or_else(|| AstralError::not_configured("Set the Ollama URL in settings"))?;
+
+        let body: Value = loop {
+            let mut request = json!({
+                "model": self.config.model,
+                "messages": self.transcript,
+                "stream": false,
+            });
+            if let Some(options) = ollama_options(&self.config) {
+                request["options"] = options;
+            }
+            if !self.tools.is_empty() {
+                request["tools"] = self.tools.iter().map(|t| json!({ "type": "function", "function": t })).collect();
+            }
+
+            let request = self.client.post(format!("{}/api/chat", ollama_url)).json(&request);
+            let response = match send_with_retry("Ollama", request, self.config.max_retries).await {
+                Ok(response) => response,
+                // Plenty of local models can't take tools; they still get to answer
+                Err(e) if !self.tools.is_empty() && rejected_tools(&e) => {
+                    warn!("{} doesn't support tools, answering without them", self.config.model);
+                    self.tools.clear();
+                    continue;
+                }
+                Err(e) => return Err(e.context("Failed to call Ollama API - is Ollama running?")),
+            };
+            break response.json().await.context("Failed to parse Ollama response")?;
+        };
+
+        let [input, output] = ["prompt_eval_count", "eval_count"].map(|k| body.get(k).and_then(Value::as_u64).unwrap_or(0));
+        crate::usage::record(LLMProvider::Ollama, &self.config.model, input, output);
+        self.count_tokens(Some(input + output));
+        let message = body.get("message").cloned().context("No response from Ollama")?;
+        let calls: Vec<ToolCall> = message
+            .get("tool_calls")
+            .and_then(Value::as_array)
+            .map(|calls| {
+                calls
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, call)| {
+                        let name = call.pointer("/function/name")?.as_str()?.to_string();
+                        Some(ToolCall {
+                            // Ollama doesn't number its calls
+                            id: format!("{}-{}", name, i),
+                            name,
+                            arguments: call.pointer("/function/arguments").cloned().unwrap_or_else(|| json!({})),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if calls.is_empty() {
+            let content = message.get("content").and_then(Value::as_str).unwrap_or_default().to_string();
+            return Ok(self.reply(content));
+        }
+        self.transcript.push(message);
+        Ok(ToolStep::Calls(calls))
+    }
+
+    /// Hand the tools' output back to the model
+    fn add_results(&mut self, results: Vec<(ToolCall, std::result::Result<String, String>)>) {
+        match self.config.provider {
+            LLMProvider::Claude => {
+                let content: Vec<Value> = results
+                    .into_iter()
+                    .map(|(call, result)| {
+                        let is_error = result.is_err();
+                        let text = result.unwrap_or_else(|e| e);
+                        json!({ "type": "tool_result", "tool_use_id": call.id, "content": text, "is_error": is_error })
+                    })
+                    .collect();
+                self.transcript.push(json!({ "role": "user", "content": content }));
+            }
+            LLMProvider::OpenAI | LLMProvider::AzureOpenAI | LLMProvider::OpenRouter => {
+                for (call, result) in results {
+                    let text = result.unwrap_or_else(|e| format!("Error: {}", e));
+                    self.transcript.push(json!({ "role": "tool", "tool_call_id": call.id, "content": text }));
+                }
+            }
+            LLMProvider::Ollama | LLMProvider::Embedded => {
+                for (call, result) in results {
+                    let text = result.unwrap_or_else(|e| format!("Error: {}", e));
+                    self.transcript.push(json!({ "role": "tool", "tool_name": call.name, "content": text }));
+                }
+            }
+        }
+    }
+}
+
+/// Answer the turn, running each tool the model calls with `execute` and
+/// handing back what it returned, until the model replies in words
+pub async fn run_tools<F, Fut>(mut turn: ToolTurn, execute: F) -> Result<LLMResponse>
+where
+    F: Fn(ToolCall) -> Fut,
+    Fut: Future<Output = std::result::Result<String, String>>,
+{
+    if let Some(response) = turn.cache_key.as_deref().and_then(crate::response_cache::get) {
+        info!("Answering from the response cache");
+        publish_response(&response);
+        return Ok(response);
+    }
+    let result = tool_loop(&mut turn, &execute).await;
+    match &result {
+        Ok(response) => publish_response(response),
+        Err(e) => bus::publish(PipelineEvent::StageFailed { stage: bus::Stage::Llm, error: e.to_string() }),
+    }
+    result
+}
+
+async fn tool_loop<F, Fut>(turn: &mut ToolTurn, execute: &F) -> Result<LLMResponse>
+where
+    F: Fn(ToolCall) -> Fut,
+    Fut: Future<Output = std::result::Result<String, String>>,
+{
+    // A cancel that lands while a tool runs still stops the turn
+    let cancel = CANCEL.subscribe();
+    for _ in 0..MAX_TOOL_ROUNDS {
+        if cancel.has_changed().unwrap_or(false) {
+            return Err(cancelled().into());
+        }
+        let calls = match turn.step().await? {
+            ToolStep::Reply(response) => {
+                if let Some(key) = turn.cache_key.take() {
+                    crate::response_cache::put(key, &response);
+                }
+                return Ok(response);
+            }
+            ToolStep::Calls(calls) => {
+                // What the tools did may be different next time
+                turn.cache_key = None;
+                calls
+            }
+        };
+        let mut results = Vec::with_capacity(calls.len());
+        for call in calls {
+            info!("LLM called tool {} with {}", call.name, call.arguments);
+            let result = execute(call.clone()).await;
+            bus::publish(PipelineEvent::ToolCalled { name: call.name.clone(), ok: result.is_ok() });
+            results.push((call, result));
+        }
+        turn.add_results(results);
+    }
+    anyhow::bail!("The model kept calling tools without answering")
+}
+
+/// Embedding model used when the knowledge base doesn't name one
+pub fn default_embedding_model(provider: LLMProvider) -> &'static str {
+    match provider {
+        // On Azure this is the name of the embeddings deployment
+        LLMProvider::OpenAI | LLMProvider::AzureOpenAI => "text-embedding-3-small",
+        LLMProvider::OpenRouter | LLMProvider::Claude | LLMProvider::Ollama | LLMProvider::Embedded => "nomic-embed-text",
+    }
+}
+
+/// Embedding vectors for `inputs`, in order. OpenAI or Azure embeds when it's
+/// the provider; Claude, OpenRouter and the built-in model have no embeddings
+/// API, so their users embed with Ollama.
+pub async fn embed(config: &LLMConfig, model: &str, inputs: &[String]) -> Result<Vec<Vec<f32>>> {
+    let client = crate::proxy::builder().timeout(Duration::from_secs(120)).build()?;
+    let vectors: Vec<serde_json::Value> = if matches!(config.provider, LLMProvider::OpenAI | LLMProvider::AzureOpenAI) {
+        crate::usage::check_cap(config.provider)?;
+        let endpoint = Endpoint::embeddings(config, model)?;
+        let response = send_with_retry(
+            endpoint.service,
+            endpoint.post(&client).json(&serde_json::json!({ "model": model, "input": inputs })),
+            config.max_retries,
+        )
+        .await
+        .with_context(|| format!("Failed to call the {} embeddings API", endpoint.service))?;
+        let body: serde_json::Value = response.json().await
+            .with_context(|| format!("Failed to parse {} embeddings", endpoint.service))?;
+        let tokens = body.pointer("/usage/prompt_tokens").and_then(|t| t.as_u64()).unwrap_or(0);
+        crate::usage::record(config.provider, model, tokens, 0);
+        let mut data = body.get("data").and_then(|d| d.as_array()).cloned().unwrap_or_default();
+        data.sort_by_key(|d| d.get("index").and_then(|i| i.as_u64()).unwrap_or_default());
+        data.into_iter().filter_map(|d| d.get("embedding").cloned()).collect()
+    } else {
+        let url = config.ollama_url.clone().unwrap_or_else(|| "http://localhost:11434".to_string());
+        let response = send_with_retry(
+            "Ollama",
+            client
+                .post(format!("{}/api/embed", url))
+                .json(&serde_json::json!({ "model": model, "input": inputs })),
+            config.max_retries,
+        )
+        .await
+        .context("Failed to call Ollama for embeddings - is Ollama running?")?;
+        let body: serde_json::Value = response.json().await.context("Failed to parse Ollama embeddings")?;
+        let tokens = body.get("prompt_eval_count").and_then(|t| t.as_u64()).unwrap_or(0);
+        crate::usage::record(LLMProvider::Ollama, model, tokens, 0);
+        body.get("embeddings").and_then(|e| e.as_array()).cloned().unwrap_or_default()
+    };
+
+    let vectors: Vec<Vec<f32>> = vectors
+        .iter()
+        .map(|v| v.as_array().map(|v| v.iter().filter_map(|x| x.as_f64()).map(|x| x as f32).collect()).unwrap_or_default())
+        .collect();
+    if vectors.len() != inputs.len() {
+        anyhow::bail!("Expected {} embeddings, got {}", inputs.len(), vectors.len());
+    }
+    Ok(vectors)
 }
 
 /// Test connection to LLM provider
@@ -338,6 +1491,20 @@ pub async fn test_connection(config: &LLMConfig) -> Result<bool> {
             info!("Testing OpenAI connection...");
             Ok(true)
         }
+        LLMProvider::AzureOpenAI => {
+            if config.api_key.is_none() || config.azure_endpoint.trim().is_empty() || config.azure_deployment.trim().is_empty() {
+                return Ok(false);
+            }
+            info!("Testing Azure OpenAI connection...");
+            Ok(true)
+        }
+        LLMProvider::OpenRouter => {
+            if config.api_key.is_none() {
+                return Ok(false);
+            }
+            info!("Testing OpenRouter connection...");
+            Ok(true)
+        }
         LLMProvider::Claude => {
             if config.api_key.is_none() {
                 return Ok(false);
@@ -351,12 +1518,11 @@ pub async fn test_connection(config: &LLMConfig) -> Result<bool> {
             
             info!("Testing Ollama connection at {}...", url);
             
-            let client = Client::new();
-            let response = client
-                .get(format!("{}/api/tags", url))
-                .timeout(Duration::from_secs(2))
-                .send()
-                .await;
+            let client = crate::proxy::client();
+            let response = crate::privacy::send(
+                client.get(format!("{}/api/tags", url)).timeout(Duration::from_secs(2)),
+            )
+            .await;
             
             match response {
                 Ok(resp) if resp.status().is_success() => {
@@ -369,5 +1535,9 @@ pub async fn test_connection(config: &LLMConfig) -> Result<bool> {
                 }
             }
         }
+        LLMProvider::Embedded => {
+            info!("Checking the built-in model at {}...", config.embedded_model_path);
+            Ok(std::path::Path::new(config.embedded_model_path.trim()).is_file())
+        }
     }
 }
@@ -0,0 +1,105 @@
+// Locale Module
+// The user's language, from the `general.locale` setting (a BCP 47 tag like
+// "de-DE"). It is handed to every part of a voice interaction: the Whisper
+// server is told which language to expect, the system prompt asks the LLM
+// to answer in it, and ElevenLabs is asked to speak it, with a voice picked
+// for it in `tts.voices_by_language` when there is one. The browser speech
+// fallback in the UI reads the same setting.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::RwLock;
+use tracing::info;
+
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// ISO 639-1 code and English name of the languages offered in settings
+const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("de", "German"),
+    ("fr", "French"),
+    ("es", "Spanish"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("sv", "Swedish"),
+    ("da", "Danish"),
+    ("no", "Norwegian"),
+    ("fi", "Finnish"),
+    ("cs", "Czech"),
+    ("tr", "Turkish"),
+    ("ru", "Russian"),
+    ("uk", "Ukrainian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("zh", "Chinese"),
+    ("hi", "Hindi"),
+    ("ar", "Arabic"),
+];
+
+static CURRENT: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new(DEFAULT_LOCALE.to_string()));
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageInfo {
+    pub code: String,
+    pub name: String,
+}
+
+/// "pt-BR" -> "pt"
+pub fn language_of(locale: &str) -> String {
+    locale.split(['-', '_']).next().unwrap_or_default().trim().to_lowercase()
+}
+
+/// English name of a language code, e.g. "de" -> "German"
+pub fn language_name(code: &str) -> Option<&'static str> {
+    LANGUAGES.iter().find(|(c, _)| *c == code).map(|(_, name)| *name)
+}
+
+/// Check a locale tag and normalize its case ("pt-br" -> "pt-BR")
+pub fn normalize(locale: &str) -> Result<String, String> {
+    let mut parts = locale.trim().split(['-', '_']);
+    let language = parts.next().unwrap_or_default().to_lowercase();
+    if language_name(&language).is_none() {
+        return Err(format!("Unsupported language: {}", locale));
+    }
+    Ok(match parts.next().filter(|region| !region.is_empty()) {
+        Some(region) => format!("{}-{}", language, region.to_uppercase()),
+        None => language,
+    })
+}
+
+pub fn set_current(locale: &str) {
+    let mut current = CURRENT.write().unwrap_or_else(|e| e.into_inner());
+    if *current != locale {
+        info!("Locale set to {}", locale);
+        *current = locale.to_string();
+    }
+}
+
+pub fn current() -> String {
+    CURRENT.read().map(|l| l.clone()).unwrap_or_else(|_| DEFAULT_LOCALE.to_string())
+}
+
+/// Language code of the current locale
+pub fn current_language() -> String {
+    language_of(&current())
+}
+
+/// English name of the current language, used in the system prompt
+pub fn current_language_name() -> &'static str {
+    language_name(&current_language()).unwrap_or("English")
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_supported_languages() -> Result<Vec<LanguageInfo>, String> {
+    Ok(LANGUAGES
+        .iter()
+        .map(|(code, name)| LanguageInfo {
+            code: code.to_string(),
+            name: name.to_string(),
+        })
+        .collect())
+}
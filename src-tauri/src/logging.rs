@@ -0,0 +1,180 @@
+// Logging Module
+// Sets up tracing for the whole app: the console, a daily-rotated file in the
+// app data dir (one week kept), and an in-memory buffer of recent entries the
+// UI reads with `get_recent_logs` for diagnostics. Records from crates still
+// using the `log` facade (Tauri and its plugins) are bridged in. `RUST_LOG`
+// overrides the default filter.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt as tracing_fmt, EnvFilter, Layer};
+
+const DEFAULT_FILTER: &str = "info";
+const LOG_FILE_PREFIX: &str = "astral";
+const MAX_LOG_FILES: usize = 7;
+/// Entries kept in memory for the log viewer
+const RECENT_CAPACITY: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    /// "ERROR", "WARN", "INFO", "DEBUG" or "TRACE"
+    pub level: String,
+    /// Module the entry came from, e.g. "astral::voice::whisper"
+    pub target: String,
+    pub message: String,
+}
+
+static RECENT: Lazy<Mutex<VecDeque<LogEntry>>> = Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_CAPACITY)));
+
+/// Same place as Tauri's app data dir; logging starts before the app exists
+pub fn log_dir() -> Option<PathBuf> {
+    let mut path = dirs::data_dir()?;
+    path.push("com.astral.app");
+    path.push("logs");
+    Some(path)
+}
+
+const INFO_SEVERITY: u8 = 2;
+
+/// Lower is more severe
+fn severity(level: &str) -> Option<u8> {
+    match level.to_uppercase().as_str() {
+        "ERROR" => Some(0),
+        "WARN" | "WARNING" => Some(1),
+        "INFO" => Some(INFO_SEVERITY),
+        "DEBUG" => Some(3),
+        "TRACE" => Some(4),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => {
+                let _ = write!(self.0, "{:?}", value);
+            }
+            // Metadata the log bridge attaches; already in the entry's target
+            name if name.starts_with("log.") => {}
+            name => {
+                let _ = write!(self.0, " {}={:?}", name, value);
+            }
+        }
+    }
+}
+
+/// Copies every event into RECENT
+struct RecentLogs;
+
+impl<S: Subscriber> Layer<S> for RecentLogs {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let entry = LogEntry {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.0,
+        };
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() == RECENT_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(entry);
+        }
+    }
+}
+
+fn file_appender() -> Option<RollingFileAppender> {
+    let dir = log_dir()?;
+    RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| eprintln!("Logging to file is unavailable: {}", e))
+        .ok()
+}
+
+/// Install the global subscriber. Keep the guard alive for the whole run;
+/// dropping it flushes the file writer.
+pub fn init() -> Option<WorkerGuard> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    let (file_layer, guard) = match file_appender() {
+        Some(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(tracing_fmt::layer().with_ansi(false).with_writer(writer)), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_fmt::layer())
+        .with(file_layer)
+        .with(RecentLogs)
+        .init();
+
+    tracing::debug!("Writing logs to {:?}", log_dir());
+    guard
+}
+
+/// Newest `count` buffered entries at `max_severity` or more severe, oldest first
+fn recent_entries(max_severity: u8, count: usize) -> Vec<LogEntry> {
+    // A panic while logging can poison the lock; the entries are still fine
+    let recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    let mut entries: Vec<LogEntry> = recent
+        .iter()
+        .rev()
+        .filter(|entry| severity(&entry.level).is_some_and(|s| s <= max_severity))
+        .take(count)
+        .cloned()
+        .collect();
+    entries.reverse();
+    entries
+}
+
+/// Everything at INFO and above, for crash and error reports
+pub fn snapshot(count: usize) -> Vec<LogEntry> {
+    recent_entries(INFO_SEVERITY, count)
+}
+
+// ========== Tauri Commands ==========
+
+/// Newest `count` entries at `level` or more severe, oldest first
+#[tauri::command]
+pub async fn get_recent_logs(level: Option<String>, count: Option<usize>) -> Result<Vec<LogEntry>, String> {
+    let max_severity = match level.as_deref() {
+        Some(level) => severity(level).ok_or_else(|| format!("Unknown log level: {}", level))?,
+        None => INFO_SEVERITY,
+    };
+    Ok(recent_entries(max_severity, count.unwrap_or(200)))
+}
+
+/// Where the log files are, for attaching to bug reports
+#[tauri::command]
+pub async fn get_log_directory() -> Result<String, String> {
+    log_dir()
+        .map(|dir| dir.to_string_lossy().to_string())
+        .ok_or_else(|| "No data directory on this system".to_string())
+}
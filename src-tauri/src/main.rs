@@ -1,51 +1,257 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use log::info;
+use tracing::info;
 
 mod commands;
-mod audio_engine;
+mod state;
+mod intents;
 mod system_integration;
-mod config;
 mod llm_provider;
+mod embedded_llm;
+mod ollama;
+mod personas;
+mod usage;
+mod response_cache;
+mod proxy;
 mod automation;
 mod elevenlabs_tts;
-mod whisper_stt;
 mod system_monitor;
 mod app_launcher;
+mod app_discovery;
 mod settings;
-mod wake_word;
+mod voice;
+mod tray;
+mod hotkeys;
+mod ocr;
+mod file_search;
+mod file_indexer;
+mod power;
+mod network;
+mod processes;
+mod display;
+mod appearance;
+mod notifications;
+mod cleanup;
+mod media;
+mod autostart;
+mod focus_assist;
+mod system_events;
+mod window_layout;
+mod dictation;
+mod active_window;
+mod documents;
+mod elevation;
+mod services;
+mod disks;
+mod sensors;
+mod alerts;
+mod system_report;
+mod api_server;
+mod mcp;
+mod skills;
+mod updater;
+mod logging;
+mod crash_reports;
+mod metrics;
+mod secrets;
+mod backup;
+mod profiles;
+mod locale;
+mod setup;
+mod error;
+mod permissions;
+mod privacy;
+mod remote_access;
+mod sync;
+mod web_search;
+mod notes;
+mod calendar;
+mod oauth;
+mod email;
+mod home_assistant;
+mod mqtt;
+mod hue;
+mod chat_bridge;
+mod calculator;
+mod knowledge_base;
+mod web_actions;
+mod webhooks;
 
 use commands::*;
 use elevenlabs_tts::*;
-use whisper_stt::*;
 use system_monitor::*;
 use app_launcher::*;
+use app_discovery::*;
+use ocr::*;
+use file_search::*;
+use file_indexer::*;
+use power::*;
+use network::*;
+use processes::*;
+use display::*;
+use appearance::*;
+use notifications::*;
+use cleanup::*;
+use media::*;
+use autostart::*;
+use focus_assist::*;
+use window_layout::*;
+use dictation::*;
+use active_window::*;
+use documents::*;
+use elevation::*;
+use services::*;
+use disks::*;
+use sensors::*;
+use alerts::*;
+use system_report::*;
+use hotkeys::*;
+use api_server::*;
+use skills::*;
+use updater::*;
+use logging::*;
+use crash_reports::*;
+use metrics::*;
+use backup::*;
+use sync::*;
+use web_search::*;
+use notes::*;
+use calendar::*;
+use email::*;
+use home_assistant::*;
+use mqtt::*;
+use hue::*;
+use chat_bridge::*;
+use knowledge_base::*;
+use web_actions::*;
+use webhooks::*;
+use ollama::*;
+use personas::*;
+use usage::*;
+use response_cache::*;
+use profiles::*;
+use locale::*;
+use setup::*;
 use settings::*;
-use wake_word::*;
+use voice::*;
+use voice::wake_word::*;
+use voice::interaction::*;
+use voice::whisper::*;
+use voice::vosk::*;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 fn main() {
-    // Initialize logger
-    env_logger::init();
+    let _log_guard = logging::init();
+    crash_reports::install_panic_hook();
     
     info!("Starting ASTRAL...");
 
     tauri::Builder::default()
+        .manage(state::AppState::new())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_store::Builder::new().build())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_autostart::init(
+            tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+            Some(vec![autostart::AUTOSTART_ARG]),
+        ))
+        .setup(|app| {
+            tauri::async_runtime::spawn(state::init(app.handle().clone()));
+            voice::init(app.handle().clone());
+            notifications::init(app.handle().clone());
+            crash_reports::init(app.handle().clone());
+            tray::init(app.handle())?;
+            hotkeys::init(app.handle())?;
+            autostart::init(app.handle());
+            app_discovery::start_background_refresh();
+            file_indexer::init(app.handle().clone());
+            skills::init();
+            network::start_monitor(app.handle().clone());
+            system_events::start(app.handle().clone());
+            active_window::start_tracking(app.handle().clone());
+            appearance::start_theme_watcher(app.handle().clone());
+            system_monitor::start_monitor_loop(app.handle().clone());
+            system_monitor::start_stats_stream(app.handle().clone());
+            system_report::start_report_schedule(app.handle().clone());
+            disks::start_low_space_watch(app.handle().clone());
+            updater::start_background_checks(app.handle().clone());
+            api_server::start(app.handle().clone());
+            sync::start(app.handle().clone());
+            calendar::start(app.handle().clone());
+            home_assistant::start(app.handle().clone());
+            mqtt::start(app.handle().clone());
+            hue::start(app.handle().clone());
+            chat_bridge::start(app.handle().clone());
+            knowledge_base::start(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             initialize_assistant,
             get_system_info,
             execute_command,
             send_llm_message,
+            send_llm_message_with_images,
+            cancel_llm_request,
+            get_system_prompt,
+            set_system_prompt,
+            reset_system_prompt,
             get_llm_config,
             update_llm_config,
             test_llm_connection,
             get_automation_routines,
             execute_automation,
             toggle_automation,
+            get_volume,
+            set_volume,
+            change_volume,
+            get_volume_mute,
+            set_volume_mute,
+            open_path,
+            power_action,
+            cancel_power_action,
+            get_battery_status,
+            get_battery_health,
+            get_power_plans,
+            set_power_plan_command,
+            get_network_status,
+            get_wifi_profiles,
+            connect_wifi_network,
+            list_processes,
+            get_top_processes,
+            kill_process,
+            get_brightness_command,
+            set_brightness_command,
+            change_brightness,
+            get_appearance,
+            set_os_theme,
+            set_night_light_command,
+            sync_theme_with_os,
+            get_effective_theme,
+            send_notification,
+            notification_action,
+            cancel_snoozed_notification,
+            get_recycle_bin_info,
+            empty_recycle_bin_command,
+            run_disk_cleanup,
+            get_now_playing,
+            get_auto_start,
+            set_auto_start,
+            get_focus_assist,
+            set_focus_assist,
+            get_monitors,
+            move_app_window,
+            toggle_dictation,
+            is_dictation_active,
+            type_dictation,
+            get_active_window,
+            extract_document_text,
+            summarize_file,
+            get_elevation_status,
+            reset_elevation_prompt,
+            control_service,
             trigger_wake_word,
             elevenlabs_speak,
             elevenlabs_get_config,
@@ -57,13 +263,113 @@ fn main() {
             whisper_health_check,
             whisper_transcribe,
             whisper_transcribe_bytes,
+            whisper_transcribe_detailed,
             get_system_stats_command,
+            get_disk_stats,
+            get_disk_health,
+            get_temperatures,
+            get_fan_speeds,
+            get_alert_rules,
+            set_alert_rules,
+            speak_system_report,
+            get_hotkeys,
+            set_hotkeys,
+            check_for_update,
+            install_update,
+            get_recent_logs,
+            get_log_directory,
+            get_crash_reports,
+            create_error_report,
+            delete_crash_report,
+            submit_crash_report,
+            get_performance_metrics,
+            reset_performance_metrics,
+            export_settings,
+            import_settings,
+            sync_now,
+            get_sync_status,
+            web_search,
+            get_notes,
+            add_list_item,
+            remove_list_item,
+            set_list_item_done,
+            delete_list,
+            create_note,
+            remove_note,
+            export_notes,
+            get_calendar_status,
+            refresh_calendar,
+            connect_google_calendar,
+            disconnect_google_calendar,
+            get_unread_email,
+            summarize_email,
+            mark_email_read,
+            connect_google_email,
+            disconnect_google_email,
+            get_home_assistant_states,
+            call_home_assistant_service,
+            test_home_assistant,
+            get_mqtt_status,
+            mqtt_publish,
+            discover_hue_bridges,
+            pair_hue_bridge,
+            get_hue_resources,
+            get_chat_bridge_status,
+            ask_documents,
+            get_knowledge_status,
+            rebuild_knowledge_base,
+            ingest_documents,
+            list_knowledge_documents,
+            remove_knowledge_document,
+            get_web_actions,
+            open_web_actions_file,
+            test_webhook,
+            list_ollama_models,
+            delete_ollama_model,
+            get_personas,
+            create_persona,
+            update_persona,
+            delete_persona,
+            switch_persona,
+            export_personas,
+            import_personas,
+            get_daily_usage,
+            get_monthly_usage,
+            get_spend_status,
+            get_response_cache_stats,
+            clear_response_cache,
+            get_profiles,
+            create_profile,
+            delete_profile,
+            switch_profile,
+            get_supported_languages,
+            run_diagnostics,
+            pull_ollama_model,
+            get_api_status,
+            regenerate_api_token,
+            regenerate_remote_api_token,
+            get_skills,
+            reload_skills,
+            approve_skill,
+            revoke_skill,
+            set_skill_llm_tools,
             get_cpu_usage_command,
             get_memory_usage_command,
             get_gpu_usage_command,
+            get_stats_history,
             launch_application,
             get_available_apps,
             find_app_command,
+            get_app_catalog,
+            get_app_aliases,
+            learn_app_alias,
+            forget_app_alias,
+            refresh_app_catalog,
+            read_screen_text,
+            search_files,
+            get_file_index_stats,
+            query_file_index,
+            rebuild_file_index,
             load_settings,
             save_settings,
             update_setting,
@@ -74,6 +380,16 @@ fn main() {
             stop_wake_word_detection,
             is_wake_word_active,
             check_for_wake_word,
+            vosk_recognize_command,
+            vosk_get_command_phrases,
+            voice_get_state,
+            voice_set_state,
+            voice_settle,
+            voice_interaction,
+            cancel_voice_interaction,
+            voice_is_muted,
+            voice_set_muted,
+            voice_toggle_mute,
         ])
         .run(tauri::generate_context!())
         .expect("error while running ASTRAL application");
@@ -0,0 +1,386 @@
+// MCP Server
+// Exposes ASTRAL's system-integration layer as Model Context Protocol tools
+// (app launching, automation routines, lists and notes, speech, web search,
+// Home Assistant, the knowledge base, a calculator, system stats), so other
+// LLM clients on the machine can reuse it. Served over MCP's Streamable
+// HTTP transport at `/mcp` on the local API, behind the same bearer token.
+// Approved skills' tools are listed alongside the built-in ones. The same
+// tools are offered to ASTRAL's own LLM when tool calling is on, minus the
+// skills the user hasn't opted in; what it reads (search results, documents,
+// email) can't be trusted, so its calls that change anything wait for the
+// user to confirm (see `needs_confirmation`).
+
+use crate::llm_provider::{ToolCall, ToolDefinition};
+use crate::state::AppState;
+use tracing::info;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+// JSON-RPC error codes
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Tools that only read; ASTRAL's own LLM may call these without asking
+const READ_ONLY_TOOLS: &[&str] = &[
+    "list_routines",
+    "get_list",
+    "web_search",
+    "home_assistant_states",
+    "search_documents",
+    "calculate",
+    "convert_units",
+    "get_system_stats",
+];
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "launch_app",
+            "description": "Open an installed application by name or alias, e.g. \"spotify\" or \"browser\".",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "name": { "type": "string", "description": "App name or alias" } },
+                "required": ["name"]
+            }
+        },
+        {
+            "name": "list_routines",
+            "description": "List ASTRAL's automation routines with their ids, triggers and whether they're enabled.",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "run_routine",
+            "description": "Run an automation routine by id (see list_routines).",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "id": { "type": "string" } },
+                "required": ["id"]
+            }
+        },
+        {
+            "name": "speak",
+            "description": "Say something out loud through ASTRAL's voice.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"]
+            }
+        },
+        {
+            "name": "add_to_list",
+            "description": "Add items to one of the user's lists, e.g. \"shopping\". \"todo\" is the default list.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "list": { "type": "string" },
+                    "items": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["list", "items"]
+            }
+        },
+        {
+            "name": "get_list",
+            "description": "The items still open on one of the user's lists, or every list and note when no list is given.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "list": { "type": "string" } }
+            }
+        },
+        {
+            "name": "add_note",
+            "description": "Save a quick note for the user.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "text": { "type": "string" } },
+                "required": ["text"]
+            }
+        },
+        {
+            "name": "web_search",
+            "description": "Search the web (Brave Search or the user's SearxNG) for current information. Returns titles, links and snippets; cite the links you use.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "query": { "type": "string" } },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "home_assistant_states",
+            "description": "Current state of the user's Home Assistant entities (lights, switches, sensors, locks...). Optionally only one domain, e.g. \"light\".",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "domain": { "type": "string" } }
+            }
+        },
+        {
+            "name": "home_assistant_call_service",
+            "description": "Call a Home Assistant service, e.g. service \"light.turn_off\" with data {\"entity_id\": \"light.living_room\"} or {\"area_id\": \"kitchen\"}. Only lights, switches, fans, media players, climate, humidifiers, scenes, input booleans and vacuums; locks, covers and alarms are refused.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "service": { "type": "string" },
+                    "data": { "type": "object" }
+                },
+                "required": ["service"]
+            }
+        },
+        {
+            "name": "search_documents",
+            "description": "Search the user's knowledge base (documents in folders they chose) for passages about a question, best match first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "limit": { "type": "integer", "description": "At most this many passages, default 5" }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "calculate",
+            "description": "Evaluate an arithmetic expression exactly, e.g. \"15% of 84\", \"(3 + 4) ^ 2\" or \"sqrt(2) * 3\". Trig functions take degrees.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "expression": { "type": "string" } },
+                "required": ["expression"]
+            }
+        },
+        {
+            "name": "convert_units",
+            "description": "Convert a value between units of length, mass, volume, temperature, speed, time, data size or area, or between currencies at the latest ECB rates.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "value": { "type": "number" },
+                    "from": { "type": "string", "description": "Unit or currency, e.g. \"miles\", \"°F\", \"USD\"" },
+                    "to": { "type": "string" }
+                },
+                "required": ["value", "from", "to"]
+            }
+        },
+        {
+            "name": "get_system_stats",
+            "description": "Current CPU, per-core, memory and GPU usage and the process count.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }
+    ])
+}
+
+fn string_arg(arguments: &Value, key: &str) -> Result<String, String> {
+    arguments
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.trim().is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| format!("Missing argument: {}", key))
+}
+
+async fn call_tool(app: &AppHandle, name: &str, arguments: &Value) -> Result<Value, String> {
+    match name {
+        "launch_app" => {
+            let app_name = string_arg(arguments, "name")?;
+            let result = tokio::task::spawn_blocking(move || crate::app_launcher::launch_app(&app_name))
+                .await
+                .map_err(|e| e.to_string())??;
+            Ok(json!(result))
+        }
+        "list_routines" => {
+            let routines = app.state::<AppState>().inner().automation.lock().await.get_all_routines();
+            Ok(json!(routines))
+        }
+        "run_routine" => {
+            let id = string_arg(arguments, "id")?;
+            Ok(json!(crate::commands::execute_automation(app.clone(), id).await?))
+        }
+        "speak" => {
+            let text = string_arg(arguments, "text")?;
+            crate::voice::speak(&text);
+            Ok(json!({ "spoken": text }))
+        }
+        "add_to_list" => {
+            let list = string_arg(arguments, "list")?;
+            let items: Vec<String> = arguments
+                .get("items")
+                .and_then(Value::as_array)
+                .map(|items| items.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                .unwrap_or_default();
+            if items.is_empty() {
+                return Err("Missing argument: items".to_string());
+            }
+            let added = crate::notes::add_items(app, &list, &items)?;
+            Ok(json!({ "list": crate::notes::normalize_list_name(&list), "added": added }))
+        }
+        "get_list" => match string_arg(arguments, "list") {
+            Ok(list) => Ok(json!(crate::notes::open_items(&list))),
+            Err(_) => Ok(json!(crate::notes::snapshot())),
+        },
+        "add_note" => {
+            let text = string_arg(arguments, "text")?;
+            Ok(json!(crate::notes::add_note(app, &text)?))
+        }
+        "web_search" => {
+            let query = string_arg(arguments, "query")?;
+            Ok(json!(crate::web_search::web_search(app.clone(), query).await?))
+        }
+        "home_assistant_states" => {
+            let domain = arguments.get("domain").and_then(Value::as_str).map(str::trim).unwrap_or_default();
+            let states = crate::home_assistant::states(app).await?;
+            let states: Vec<Value> = states
+                .iter()
+                .filter(|s| domain.is_empty() || s.domain() == domain)
+                .map(|s| json!({ "entity_id": s.entity_id, "name": s.name, "state": s.state }))
+                .collect();
+            Ok(json!(states))
+        }
+        "home_assistant_call_service" => {
+            let service = string_arg(arguments, "service")?;
+            if !crate::home_assistant::tool_may_call(&service) {
+                return Err(format!("{} can't be called by tools; ask the user to do it themselves", service));
+            }
+            let data = arguments.get("data").cloned().filter(Value::is_object).unwrap_or_else(|| json!({}));
+            crate::home_assistant::call_service(app, &service, data).await?;
+            Ok(json!({ "called": service }))
+        }
+        "search_documents" => {
+            let query = string_arg(arguments, "query")?;
+            let limit = arguments.get("limit").and_then(Value::as_u64).unwrap_or(5).clamp(1, 20) as usize;
+            Ok(json!(crate::knowledge_base::search(app, &query, limit).await?))
+        }
+        "calculate" => {
+            let expression = string_arg(arguments, "expression")?;
+            let value = crate::calculator::evaluate(&expression)?;
+            Ok(json!({ "expression": expression, "result": value, "formatted": crate::calculator::format_number(value) }))
+        }
+        "convert_units" => {
+            let value = arguments.get("value").and_then(Value::as_f64).ok_or("Missing argument: value")?;
+            let from = string_arg(arguments, "from")?;
+            let to = string_arg(arguments, "to")?;
+            Ok(json!(crate::calculator::convert(value, &from, &to).await?))
+        }
+        "get_system_stats" => {
+            let stats = tokio::task::spawn_blocking(crate::system_monitor::get_system_stats)
+                .await
+                .map_err(|e| e.to_string())??;
+            Ok(json!(stats))
+        }
+        _ => match crate::skills::call_tool(app, name, arguments).await {
+            Some(result) => result.map(Value::String),
+            None => Err(format!("Unknown tool: {}", name)),
+        },
+    }
+}
+
+/// LLM APIs only allow letters, digits, `_` and `-` in tool names, so a
+/// skill's "<skill id>.<tool>" is offered as "<skill id>__<tool>"
+const SKILL_SEPARATOR: &str = "__";
+
+/// The tools ASTRAL's own LLM may call while answering. Speaking is left
+/// out: the reply is spoken anyway. Skills only when the user opted them in.
+pub async fn llm_tools(app: &AppHandle) -> Vec<ToolDefinition> {
+    let mut tools = tool_definitions().as_array().cloned().unwrap_or_default();
+    tools.extend(crate::skills::llm_tool_definitions(app).await);
+    tools
+        .iter()
+        .filter_map(|tool| {
+            Some(ToolDefinition {
+                name: tool.get("name")?.as_str()?.replace('.', SKILL_SEPARATOR),
+                description: tool.get("description").and_then(Value::as_str).unwrap_or_default().to_string(),
+                parameters: tool.get("inputSchema").cloned().unwrap_or_else(|| json!({ "type": "object", "properties": {} })),
+            })
+        })
+        .filter(|tool| tool.name != "speak")
+        .collect()
+}
+
+/// Whether a call from ASTRAL's own LLM has to be confirmed by the user
+/// before it runs: everything but the read-only tools
+pub fn needs_confirmation(call: &ToolCall) -> bool {
+    !READ_ONLY_TOOLS.contains(&call.name.as_str())
+}
+
+/// What `call` would do, to ask the user about: "open spotify"
+pub fn describe_call(call: &ToolCall) -> String {
+    let arg = |key: &str| call.arguments.get(key).and_then(Value::as_str).unwrap_or("?").to_string();
+    match call.name.as_str() {
+        "launch_app" => format!("open {}", arg("name")),
+        "run_routine" => format!("run the routine {}", arg("id")),
+        "add_to_list" => format!("add to your {} list", arg("list")),
+        "add_note" => "save a note".to_string(),
+        "home_assistant_call_service" => {
+            let target = call
+                .arguments
+                .pointer("/data/entity_id")
+                .or_else(|| call.arguments.pointer("/data/area_id"))
+                .map(|t| t.as_str().map(str::to_string).unwrap_or_else(|| t.to_string()));
+            match target {
+                Some(target) => format!("call {} on {}", arg("service"), target),
+                None => format!("call {}", arg("service")),
+            }
+        }
+        name => match name.split_once(SKILL_SEPARATOR) {
+            Some((skill, tool)) => format!("use the {} tool of the {} skill", tool, skill),
+            None => format!("use {}", name),
+        },
+    }
+}
+
+/// Run a tool the LLM called; the result is text for the model to read
+pub async fn call_llm_tool(app: &AppHandle, call: ToolCall) -> Result<String, String> {
+    let name = call.name.replacen(SKILL_SEPARATOR, ".", 1);
+    if let Some((skill_id, _)) = name.split_once('.') {
+        if !crate::skills::offered_to_llm(app, skill_id).await {
+            return Err(format!("Unknown tool: {}", call.name));
+        }
+    }
+    let value = call_tool(app, &name, &call.arguments).await?;
+    Ok(value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()))
+}
+
+fn error(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Handle one JSON-RPC message; notifications (no `id`) get no reply
+pub async fn handle(app: &AppHandle, message: Value) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "astral", "version": env!("CARGO_PKG_VERSION") }
+        }),
+        "ping" => json!({}),
+        "tools/list" => {
+            let mut tools = tool_definitions().as_array().cloned().unwrap_or_default();
+            tools.extend(crate::skills::tool_definitions(app).await);
+            json!({ "tools": tools })
+        }
+        "tools/call" => {
+            let Some(name) = params.get("name").and_then(Value::as_str) else {
+                return Some(error(&id, INVALID_PARAMS, "Missing tool name"));
+            };
+            let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+            info!("MCP tool call: {}", name);
+
+            // Tool failures are results the calling model can read, not protocol errors
+            match call_tool(app, name, &arguments).await {
+                Ok(value) => json!({
+                    "content": [{ "type": "text", "text": value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string()) }],
+                    "isError": false
+                }),
+                Err(e) => json!({
+                    "content": [{ "type": "text", "text": e }],
+                    "isError": true
+                }),
+            }
+        }
+        _ => return Some(error(&id, METHOD_NOT_FOUND, &format!("Unknown method: {}", method))),
+    };
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
@@ -0,0 +1,209 @@
+// Media Module
+// "What song is this?" - current track from the OS media session
+// (SMTC on Windows, MPRIS via playerctl on Linux, Spotify/Music on macOS)
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Name of the `{{now_playing}}` system prompt variable
+pub const NOW_PLAYING_VARIABLE: &str = "now_playing";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// Player the session belongs to ("Spotify", "Chrome")
+    pub app: Option<String>,
+    /// False when paused
+    pub playing: bool,
+}
+
+impl NowPlaying {
+    /// Short form for the LLM prompt: "Song by Artist (Spotify)"
+    pub fn summary(&self) -> String {
+        let mut summary = self.title.clone();
+        if let Some(artist) = &self.artist {
+            summary.push_str(&format!(" by {}", artist));
+        }
+        if let Some(app) = &self.app {
+            summary.push_str(&format!(" ({})", app));
+        }
+        if !self.playing {
+            summary.push_str(", paused");
+        }
+        summary
+    }
+
+    /// Spoken reply to "what song is this?"
+    pub fn describe(&self) -> String {
+        let track = match &self.artist {
+            Some(artist) => format!("{} by {}", self.title, artist),
+            None => self.title.clone(),
+        };
+        match (&self.app, self.playing) {
+            (Some(app), true) => format!("This is {}, playing in {}", track, app),
+            (None, true) => format!("This is {}", track),
+            (Some(app), false) => format!("{} is paused in {}", track, app),
+            (None, false) => format!("{} is paused", track),
+        }
+    }
+}
+
+/// "what song is this", "what's playing", "who sings this"
+pub fn is_now_playing_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    [
+        "what song is this",
+        "what song is playing",
+        "what's this song",
+        "what is this song",
+        "what's playing",
+        "what is playing",
+        "who sings this",
+        "who is singing",
+        "name of this song",
+        "what am i listening to",
+    ]
+    .iter()
+    .any(|p| lower.contains(p))
+}
+
+/// Current media session, if anything is playing or paused. Blocking.
+pub fn now_playing() -> Result<Option<NowPlaying>> {
+    platform::now_playing()
+}
+
+/// Value of the `{{now_playing}}` prompt variable. Blocking.
+pub fn now_playing_variable() -> String {
+    match now_playing() {
+        Ok(Some(track)) => track.summary(),
+        _ => "nothing".to_string(),
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{non_empty, NowPlaying};
+    use anyhow::Result;
+    use windows::Media::Control::{
+        GlobalSystemMediaTransportControlsSessionManager as SessionManager,
+        GlobalSystemMediaTransportControlsSessionPlaybackStatus as PlaybackStatus,
+    };
+
+    /// "Spotify.exe" -> "Spotify", "Microsoft.ZuneMusic_8wekyb3d8bbwe!Microsoft.ZuneMusic" -> "ZuneMusic"
+    fn app_name(app_id: &str) -> Option<String> {
+        let id = app_id.split(['!', '_']).next().unwrap_or(app_id);
+        let id = id.strip_suffix(".exe").or_else(|| id.strip_suffix(".EXE")).unwrap_or(id);
+        non_empty(id.rsplit('.').next().unwrap_or(id).to_string())
+    }
+
+    pub fn now_playing() -> Result<Option<NowPlaying>> {
+        let manager = SessionManager::RequestAsync()?.get()?;
+        // Fails when no app has a media session
+        let Ok(session) = manager.GetCurrentSession() else {
+            return Ok(None);
+        };
+
+        let properties = session.TryGetMediaPropertiesAsync()?.get()?;
+        let Some(title) = non_empty(properties.Title()?.to_string()) else {
+            return Ok(None);
+        };
+        let playing = session
+            .GetPlaybackInfo()
+            .and_then(|info| info.PlaybackStatus())
+            .map(|status| status == PlaybackStatus::Playing)
+            .unwrap_or(false);
+
+        Ok(Some(NowPlaying {
+            title,
+            artist: properties.Artist().ok().and_then(|a| non_empty(a.to_string())),
+            album: properties.AlbumTitle().ok().and_then(|a| non_empty(a.to_string())),
+            app: session.SourceAppUserModelId().ok().and_then(|id| app_name(&id.to_string())),
+            playing,
+        }))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{non_empty, NowPlaying};
+    use anyhow::Result;
+    use std::process::Command;
+
+    // Players with an AppleScript dictionary; there's no public system-wide API
+    const PLAYERS: &[&str] = &["Spotify", "Music"];
+
+    fn query(player: &str) -> Option<NowPlaying> {
+        let script = format!(
+            "if application \"{0}\" is running then tell application \"{0}\" to if player state is not stopped then return (name of current track) & tab & (artist of current track) & tab & (album of current track) & tab & (player state as string)",
+            player
+        );
+        let output = Command::new("osascript").args(["-e", &script]).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let fields: Vec<&str> = text.split('\t').collect();
+        if fields.len() < 4 {
+            return None;
+        }
+
+        Some(NowPlaying {
+            title: non_empty(fields[0].to_string())?,
+            artist: non_empty(fields[1].to_string()),
+            album: non_empty(fields[2].to_string()),
+            app: Some(player.to_string()),
+            playing: fields[3] == "playing",
+        })
+    }
+
+    pub fn now_playing() -> Result<Option<NowPlaying>> {
+        let tracks: Vec<NowPlaying> = PLAYERS.iter().filter_map(|p| query(p)).collect();
+        // Prefer whichever is actually playing
+        Ok(tracks.iter().find(|t| t.playing).or(tracks.first()).cloned())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::{non_empty, NowPlaying};
+    use anyhow::Result;
+    use std::process::Command;
+
+    // playerctl picks the most recently active MPRIS player
+    pub fn now_playing() -> Result<Option<NowPlaying>> {
+        let output = Command::new("playerctl")
+            .args(["metadata", "--format", "{{title}}\t{{artist}}\t{{album}}\t{{playerName}}\t{{status}}"])
+            .output()?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let fields: Vec<&str> = text.split('\t').collect();
+        if fields.len() < 5 {
+            return Ok(None);
+        }
+
+        Ok(non_empty(fields[0].to_string()).map(|title| NowPlaying {
+            title,
+            artist: non_empty(fields[1].to_string()),
+            album: non_empty(fields[2].to_string()),
+            app: non_empty(fields[3].to_string()),
+            playing: fields[4] == "Playing",
+        }))
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_now_playing() -> Result<Option<NowPlaying>, String> {
+    tokio::task::spawn_blocking(now_playing)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
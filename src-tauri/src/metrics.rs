@@ -0,0 +1,128 @@
+// Metrics Module
+// Latency of the steps a voice interaction goes through: speech-to-text, the
+// LLM's reply, speech synthesis, command handling, and the round trip
+// from the user finishing speaking (Recording -> Processing) to the assistant
+// starting to answer (Processing -> Speaking). The last samples of each are
+// kept in memory for `get_performance_metrics`.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+use crate::voice::VoiceState;
+
+/// Samples kept per stage for the averages and percentiles
+const WINDOW: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Stt,
+    /// Until the whole reply is in; providers are called without streaming
+    LlmResponse,
+    Tts,
+    Command,
+    VoiceRoundTrip,
+}
+
+const STAGES: [Stage; 5] = [Stage::Stt, Stage::LlmResponse, Stage::Tts, Stage::Command, Stage::VoiceRoundTrip];
+
+#[derive(Default)]
+struct Series {
+    samples: VecDeque<u64>,
+    total: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageMetrics {
+    pub stage: Stage,
+    /// Samples since launch, not just the ones in the window
+    pub count: u64,
+    pub last_ms: Option<u64>,
+    pub avg_ms: Option<u64>,
+    pub p50_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+}
+
+static SERIES: Lazy<Mutex<HashMap<Stage, Series>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// When the current interaction entered Processing
+static PROCESSING_SINCE: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn record(stage: Stage, elapsed: Duration) {
+    let ms = elapsed.as_millis() as u64;
+    debug!("{:?} took {} ms", stage, ms);
+    let mut series = SERIES.lock().unwrap_or_else(|e| e.into_inner());
+    let series = series.entry(stage).or_default();
+    if series.samples.len() == WINDOW {
+        series.samples.pop_front();
+    }
+    series.samples.push_back(ms);
+    series.total += 1;
+}
+
+/// Run `future`, recording how long it took when it succeeds
+pub async fn time<T, E>(stage: Stage, future: impl Future<Output = Result<T, E>>) -> Result<T, E> {
+    let started = Instant::now();
+    let result = future.await;
+    if result.is_ok() {
+        record(stage, started.elapsed());
+    }
+    result
+}
+
+/// Called by the voice state machine on every transition
+pub fn voice_state_changed(to: VoiceState) {
+    let mut since = PROCESSING_SINCE.lock().unwrap_or_else(|e| e.into_inner());
+    match to {
+        VoiceState::Processing => *since = Some(Instant::now()),
+        VoiceState::Speaking => {
+            if let Some(started) = since.take() {
+                record(Stage::VoiceRoundTrip, started.elapsed());
+            }
+        }
+        _ => *since = None,
+    }
+}
+
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let index = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted.get(index).copied()
+}
+
+fn summarize(stage: Stage, series: Option<&Series>) -> StageMetrics {
+    let samples: Vec<u64> = series.map(|s| s.samples.iter().copied().collect()).unwrap_or_default();
+    let mut sorted = samples.clone();
+    sorted.sort_unstable();
+
+    StageMetrics {
+        stage,
+        count: series.map(|s| s.total).unwrap_or(0),
+        last_ms: samples.last().copied(),
+        avg_ms: (!samples.is_empty()).then(|| samples.iter().sum::<u64>() / samples.len() as u64),
+        p50_ms: percentile(&sorted, 0.5),
+        p95_ms: percentile(&sorted, 0.95),
+        max_ms: sorted.last().copied(),
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_performance_metrics() -> Result<Vec<StageMetrics>, String> {
+    let series = SERIES.lock().map_err(|e| e.to_string())?;
+    Ok(STAGES.iter().map(|stage| summarize(*stage, series.get(stage))).collect())
+}
+
+#[tauri::command]
+pub async fn reset_performance_metrics() -> Result<(), String> {
+    SERIES.lock().map_err(|e| e.to_string())?.clear();
+    Ok(())
+}
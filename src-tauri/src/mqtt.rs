@@ -0,0 +1,261 @@
+// MQTT Module
+// Connects ASTRAL to an MQTT broker (Mosquitto, the Home Assistant add-on,
+// a Zigbee2MQTT box) so DIY setups can talk to it both ways: routines
+// publish with the `MqttPublish` action, and a routine with an `Mqtt`
+// trigger runs when a message arrives on a matching topic (`+` and `#`
+// wildcards, optionally only for one payload). Off unless `mqtt.enabled`.
+// The connection is kept open and comes back on its own after the broker
+// restarts; subscriptions follow the routines, checked every minute and
+// after each reconnect. Plain TCP only, meant for a broker on the LAN; the
+// connection can't go through the proxy, so while one is set the broker has
+// to be on its no-proxy list.
+
+use crate::error::{AstralError, AstralResult};
+use crate::state::AppState;
+use once_cell::sync::Lazy;
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// How often the subscriptions are compared with the routines' triggers
+const SUBSCRIPTION_CHECK: Duration = Duration::from_secs(60);
+
+/// Wait after a failed connection before trying again
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// The `mqtt` settings section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    /// Shown in the broker's logs; "astral-<host name>" when empty
+    pub client_id: String,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            username: String::new(),
+            password: String::new(),
+            client_id: String::new(),
+        }
+    }
+}
+
+impl MqttSettings {
+    fn client_id(&self) -> String {
+        if !self.client_id.is_empty() {
+            return self.client_id.clone();
+        }
+        let host = sysinfo::System::host_name().unwrap_or_else(|| "desktop".to_string());
+        format!("astral-{}", host.to_lowercase().replace(' ', "-"))
+    }
+
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.host = self.host.trim().to_string();
+        self.username = self.username.trim().to_string();
+        self.client_id = self.client_id.trim().to_string();
+        if self.enabled && self.host.is_empty() {
+            return Err("Add the address of your MQTT broker".to_string());
+        }
+        if self.port == 0 {
+            return Err("Invalid MQTT port: 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MqttStatus {
+    pub enabled: bool,
+    pub connected: bool,
+    /// Topic filters currently subscribed for routine triggers
+    pub subscriptions: Vec<String>,
+    pub last_error: Option<String>,
+}
+
+/// The live connection, for publishing
+static CLIENT: Lazy<Mutex<Option<AsyncClient>>> = Lazy::new(|| Mutex::new(None));
+static STATUS: Lazy<Mutex<MqttStatus>> = Lazy::new(|| Mutex::new(MqttStatus::default()));
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+fn status() -> std::sync::MutexGuard<'static, MqttStatus> {
+    STATUS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether `topic` matches the subscription `filter` ("home/+/motion", "zigbee/#")
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut levels = topic.split('/');
+    for part in filter.split('/') {
+        match (part, levels.next()) {
+            ("#", _) => return true,
+            ("+", Some(_)) => {}
+            (part, Some(level)) if part == level => {}
+            _ => return false,
+        }
+    }
+    levels.next().is_none()
+}
+
+/// Publish over the open connection
+pub async fn publish(topic: &str, payload: &str, retain: bool) -> AstralResult<()> {
+    let topic = topic.trim();
+    if topic.is_empty() || topic.contains(['+', '#']) {
+        return Err(AstralError::invalid_input(format!("Can't publish to {:?}", topic)));
+    }
+    let client = CLIENT.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let client = client.ok_or_else(|| AstralError::not_configured("MQTT isn't connected; check the broker in Settings"))?;
+    client
+        .publish(topic, QoS::AtLeastOnce, retain, payload.as_bytes().to_vec())
+        .await
+        .map_err(|e| AstralError::from(format!("Couldn't publish to {}: {}", topic, e)))?;
+    info!("Published to {}", topic);
+    Ok(())
+}
+
+/// Subscribe to the routines' trigger topics and drop the ones no routine uses
+async fn sync_subscriptions(app: &AppHandle, client: &AsyncClient, subscribed: &mut BTreeSet<String>) {
+    let wanted: BTreeSet<String> = app.state::<AppState>().automation.lock().await.mqtt_topics().into_iter().collect();
+    for topic in wanted.difference(subscribed) {
+        if let Err(e) = client.subscribe(topic.as_str(), QoS::AtLeastOnce).await {
+            warn!("Couldn't subscribe to {}: {}", topic, e);
+        }
+    }
+    for topic in subscribed.difference(&wanted) {
+        if let Err(e) = client.unsubscribe(topic.as_str()).await {
+            warn!("Couldn't unsubscribe from {}: {}", topic, e);
+        }
+    }
+    *subscribed = wanted;
+    status().subscriptions = subscribed.iter().cloned().collect();
+}
+
+async fn run_triggers(app: AppHandle, topic: String, payload: String) {
+    let mut manager = app.state::<AppState>().inner().automation.lock().await;
+    for id in manager.routines_for_mqtt(&topic, &payload) {
+        info!("MQTT message on {} triggered routine {}", topic, id);
+        if let Err(e) = manager.execute_routine(&app, &id).await {
+            warn!("Routine {} failed: {}", id, e);
+        }
+    }
+}
+
+/// Stay connected while MQTT is on, until the settings change
+async fn run_connection(app: &AppHandle, settings: &MqttSettings) {
+    let mut options = MqttOptions::new(settings.client_id(), settings.host.clone(), settings.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if !settings.username.is_empty() {
+        options.set_credentials(settings.username.clone(), settings.password.clone());
+    }
+    let (client, mut event_loop) = AsyncClient::new(options, 32);
+    *CLIENT.lock().unwrap_or_else(|e| e.into_inner()) = Some(client.clone());
+    let mut subscribed = BTreeSet::new();
+    let mut check = tokio::time::interval(SUBSCRIPTION_CHECK);
+
+    loop {
+        tokio::select! {
+            event = event_loop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("Connected to MQTT broker {}:{}", settings.host, settings.port);
+                    {
+                        let mut status = status();
+                        status.connected = true;
+                        status.last_error = None;
+                    }
+                    // A new session starts without subscriptions
+                    subscribed.clear();
+                    sync_subscriptions(app, &client, &mut subscribed).await;
+                }
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    let payload = String::from_utf8_lossy(&message.payload).into_owned();
+                    tauri::async_runtime::spawn(run_triggers(app.clone(), message.topic.clone(), payload));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("MQTT connection error: {}", e);
+                    {
+                        let mut status = status();
+                        status.connected = false;
+                        status.last_error = Some(e.to_string());
+                    }
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            },
+            _ = check.tick() => {
+                let connected = status().connected;
+                if connected {
+                    sync_subscriptions(app, &client, &mut subscribed).await;
+                }
+            }
+            _ = WAKE.notified() => break,
+        }
+    }
+    let _ = client.disconnect().await;
+    *CLIENT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    status().connected = false;
+}
+
+/// Reconnect with the current settings
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Start the MQTT connection loop; called from setup
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) => settings.mqtt,
+                Err(e) => {
+                    warn!("Couldn't load MQTT settings: {}", e);
+                    MqttSettings::default()
+                }
+            };
+            status().enabled = settings.enabled;
+            if !settings.enabled {
+                WAKE.notified().await;
+                continue;
+            }
+            // Not HTTP, so privacy mode is checked here rather than in `privacy::send`
+            if let Err(e) = crate::privacy::check_url(&format!("mqtt://{}", settings.host)) {
+                status().last_error = Some(e.to_string());
+                WAKE.notified().await;
+                continue;
+            }
+            if !crate::proxy::bypasses(&settings.host) {
+                warn!("Not connecting to MQTT broker {}: it would bypass the proxy", settings.host);
+                status().last_error = Some(format!(
+                    "MQTT can't go through the proxy; add {} to the proxy's no-proxy list or turn the proxy off",
+                    settings.host
+                ));
+                WAKE.notified().await;
+                continue;
+            }
+            run_connection(&app, &settings).await;
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_mqtt_status() -> MqttStatus {
+    status().clone()
+}
+
+#[tauri::command]
+pub async fn mqtt_publish(topic: String, payload: String, retain: Option<bool>) -> AstralResult<()> {
+    publish(&topic, &payload, retain.unwrap_or(false)).await
+}
@@ -0,0 +1,591 @@
+// Network Module
+// Reports the current connection (SSID, link type, IPs, VPN, internet reachability)
+// and watches for changes so routines can react to them. Also lists saved
+// Wi-Fi profiles and connects to one by name ("switch to my hotspot").
+
+use anyhow::{bail, Result};
+use tracing::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr, TcpStream, UdpSocket};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted when the connection changes, payload is the new `NetworkStatus`
+pub const NETWORK_CHANGED_EVENT: &str = "network-changed";
+
+/// How often the watcher re-checks the connection
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Well-known anycast resolvers used to probe reachability (port 443, no data sent)
+const PROBE_ADDRS: &[&str] = &["1.1.1.1:443", "8.8.8.8:443"];
+
+/// Interface names/descriptions that indicate a VPN tunnel
+const VPN_MARKERS: &[&str] = &[
+    "vpn", "wireguard", "wg", "tun", "tap", "ppp", "openvpn", "nordlynx", "wintun", "tailscale", "zerotier",
+    "anyconnect", "fortinet", "globalprotect", "pangp", "ipsec",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Connectivity {
+    /// Internet reachable
+    Online,
+    /// On a network but the internet isn't reachable (captive portal, broken uplink)
+    LocalOnly,
+    Offline,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkType {
+    Wifi,
+    Ethernet,
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkStatus {
+    pub connectivity: Connectivity,
+    pub link_type: LinkType,
+    pub ssid: Option<String>,
+    pub local_ip: Option<IpAddr>,
+    /// Only looked up when asked for; needs an outbound request
+    pub public_ip: Option<IpAddr>,
+    pub vpn_active: bool,
+    /// Names of active VPN interfaces
+    pub vpn_interfaces: Vec<String>,
+}
+
+impl NetworkStatus {
+    /// Short sentence to speak back
+    pub fn describe(&self) -> String {
+        let link = match (&self.link_type, &self.ssid) {
+            (LinkType::Wifi, Some(ssid)) => format!("on Wi-Fi network {}", ssid),
+            (LinkType::Wifi, None) => "on Wi-Fi".to_string(),
+            (LinkType::Ethernet, _) => "on a wired connection".to_string(),
+            (LinkType::Unknown, _) => "connected".to_string(),
+        };
+
+        let mut reply = match self.connectivity {
+            Connectivity::Offline => return "You're offline.".to_string(),
+            Connectivity::LocalOnly => format!("You're {}, but the internet isn't reachable", link),
+            Connectivity::Online => format!("You're online, {}", link),
+        };
+
+        if self.vpn_active {
+            reply.push_str(&format!(", and the VPN is on ({})", self.vpn_interfaces.join(", ")));
+        } else {
+            reply.push_str(", with no VPN");
+        }
+        if let Some(ip) = self.public_ip {
+            reply.push_str(&format!(". Your public IP is {}", ip));
+        }
+        reply.push('.');
+        reply
+    }
+}
+
+/// Address of the interface the OS would route internet traffic through.
+/// Connecting a UDP socket sends nothing; it only picks a route.
+fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then_some(ip)
+}
+
+fn internet_reachable() -> bool {
+    PROBE_ADDRS.iter().any(|addr| {
+        addr.parse::<SocketAddr>()
+            .map(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok())
+            .unwrap_or(false)
+    })
+}
+
+fn is_vpn_interface(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    VPN_MARKERS.iter().any(|marker| {
+        // Short markers must start a word so "tap" doesn't match "laptop"
+        if marker.len() <= 3 {
+            lower
+                .split(|c: char| !c.is_alphanumeric())
+                .any(|word| word.starts_with(marker))
+        } else {
+            lower.contains(marker)
+        }
+    })
+}
+
+async fn public_ip() -> Option<IpAddr> {
+    let client = crate::proxy::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .ok()?;
+    let text = crate::privacy::send(client.get("https://api.ipify.org")).await.ok()?.text().await.ok()?;
+    text.trim().parse().ok()
+}
+
+/// Current network status. Blocking (runs a few short probes).
+pub fn current_status() -> NetworkStatus {
+    let local_ip = local_ip();
+    let link = platform::link_info();
+    let vpn_interfaces: Vec<String> = link
+        .active_interfaces
+        .iter()
+        .filter(|name| is_vpn_interface(name))
+        .cloned()
+        .collect();
+
+    let connectivity = match local_ip {
+        None => Connectivity::Offline,
+        Some(_) if internet_reachable() => Connectivity::Online,
+        Some(_) => Connectivity::LocalOnly,
+    };
+
+    NetworkStatus {
+        connectivity,
+        link_type: link.link_type,
+        ssid: link.ssid,
+        local_ip,
+        public_ip: None,
+        vpn_active: !vpn_interfaces.is_empty(),
+        vpn_interfaces,
+    }
+}
+
+/// Status including the public IP
+pub async fn full_status() -> Result<NetworkStatus, String> {
+    let mut status = tokio::task::spawn_blocking(current_status)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if status.connectivity == Connectivity::Online {
+        status.public_ip = public_ip().await;
+    }
+    Ok(status)
+}
+
+/// Whether the command asks about the connection ("am I on VPN?", "what's my IP")
+pub fn is_network_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    ["vpn", "my ip", "ip address", "wifi", "wi-fi", "internet connection", "am i online", "am i connected", "network status"]
+        .iter()
+        .any(|p| lower.contains(p))
+}
+
+/// Profile names that usually belong to a phone hotspot
+const HOTSPOT_MARKERS: &[&str] = &["hotspot", "iphone", "android", "galaxy", "pixel", "phone"];
+
+/// A spoken Wi-Fi request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WifiCommand {
+    ListProfiles,
+    /// Spoken network name, matched against saved profiles
+    Connect(String),
+}
+
+impl WifiCommand {
+    /// "connect to HomeNet wifi", "switch to my hotspot", "list wifi networks".
+    /// Connecting needs the command to start with the verb, so "how do I use
+    /// the guest network?" isn't a request to join it.
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = crate::intents::imperative(command);
+        let networky = ["wifi", "wi-fi", "network", "hotspot"].iter().any(|w| lower.contains(w));
+        if !networky {
+            return None;
+        }
+
+        if ["list", "known", "saved", "which"].iter().any(|w| lower.contains(w)) && lower.contains("network") {
+            return Some(Self::ListProfiles);
+        }
+
+        let rest = ["connect to ", "connect me to ", "switch to ", "join ", "use "]
+            .iter()
+            .find_map(|p| lower.strip_prefix(p))?;
+
+        let name = rest
+            .split_whitespace()
+            .filter(|w| !["the", "my", "wifi", "wi-fi", "network", "wireless"].contains(w))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if name.is_empty() {
+            return None;
+        }
+        Some(Self::Connect(name))
+    }
+}
+
+/// Pick the saved profile a spoken name refers to
+fn match_profile<'a>(query: &str, profiles: &'a [String]) -> Option<&'a String> {
+    let query = query.trim().to_lowercase();
+    let exact = profiles.iter().find(|p| p.to_lowercase() == query);
+    let contains = || profiles.iter().find(|p| p.to_lowercase().contains(&query));
+    let hotspot = || {
+        (query == "hotspot").then(|| {
+            profiles
+                .iter()
+                .find(|p| HOTSPOT_MARKERS.iter().any(|m| p.to_lowercase().contains(m)))
+        })?
+    };
+    let fuzzy = || {
+        profiles
+            .iter()
+            .map(|p| (p, strsim::jaro_winkler(&p.to_lowercase(), &query)))
+            .filter(|(_, score)| *score > 0.85)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(p, _)| p)
+    };
+
+    exact.or_else(contains).or_else(hotspot).or_else(fuzzy)
+}
+
+/// Saved Wi-Fi profile names. Blocking.
+pub fn wifi_profiles() -> Result<Vec<String>> {
+    platform::wifi_profiles()
+}
+
+/// Connect to the saved profile matching `name`, returning the profile used. Blocking.
+pub fn connect_wifi(name: &str) -> Result<String> {
+    let profiles = wifi_profiles()?;
+    let Some(profile) = match_profile(name, &profiles) else {
+        bail!("There's no saved Wi-Fi network called {}", name);
+    };
+    info!("Connecting to Wi-Fi profile {}", profile);
+    platform::connect_wifi(profile)?;
+    Ok(profile.clone())
+}
+
+pub fn apply_wifi_command(command: WifiCommand) -> Result<String> {
+    match command {
+        WifiCommand::ListProfiles => {
+            let profiles = wifi_profiles()?;
+            Ok(if profiles.is_empty() {
+                "There are no saved Wi-Fi networks".to_string()
+            } else {
+                format!("Saved Wi-Fi networks: {}", profiles.join(", "))
+            })
+        }
+        WifiCommand::Connect(name) => Ok(format!("Connecting to {}", connect_wifi(&name)?)),
+    }
+}
+
+/// System events fired for routines when the connection changes
+fn change_events(old: &NetworkStatus, new: &NetworkStatus) -> Vec<&'static str> {
+    let mut events = vec!["network_changed"];
+
+    let was_online = old.connectivity == Connectivity::Online;
+    let is_online = new.connectivity == Connectivity::Online;
+    if !was_online && is_online {
+        events.push("network_connected");
+    }
+    if was_online && !is_online {
+        events.push("network_disconnected");
+    }
+    if !old.vpn_active && new.vpn_active {
+        events.push("vpn_connected");
+    }
+    if old.vpn_active && !new.vpn_active {
+        events.push("vpn_disconnected");
+    }
+    if new.ssid.is_some() && old.ssid != new.ssid {
+        events.push("wifi_changed");
+    }
+
+    events
+}
+
+/// Poll the connection and emit `network-changed` plus routine triggers on changes
+pub fn start_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last: Option<NetworkStatus> = None;
+
+        loop {
+            match tokio::task::spawn_blocking(current_status).await {
+                Ok(status) => {
+                    if let Some(previous) = last.as_ref().filter(|previous| **previous != status) {
+                        info!("Network changed: {:?} -> {:?}", previous.connectivity, status.connectivity);
+                        if let Err(e) = app.emit(NETWORK_CHANGED_EVENT, &status) {
+                            warn!("Failed to emit network change: {}", e);
+                        }
+                        for event in change_events(previous, &status) {
+                            crate::commands::fire_system_event(&app, event).await;
+                        }
+                    }
+                    last = Some(status);
+                }
+                Err(e) => warn!("Network check failed: {}", e),
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// What the platform layer can tell us beyond the routing table
+#[derive(Default)]
+struct LinkInfo {
+    link_type: LinkType,
+    ssid: Option<String>,
+    /// Names (and descriptions, where available) of interfaces that are up
+    active_interfaces: Vec<String>,
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{LinkInfo, LinkType};
+    use anyhow::{bail, Result};
+    use serde::Deserialize;
+    use std::process::Command;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Adapter {
+        name: String,
+        interface_description: String,
+        media_type: Option<String>,
+    }
+
+    const ADAPTER_SCRIPT: &str = "ConvertTo-Json -Compress -InputObject @(Get-NetAdapter | Where-Object Status -eq 'Up' | Select-Object Name, InterfaceDescription, MediaType)";
+
+    /// `netsh wlan show interfaces` prints "    SSID                   : MyNetwork"
+    fn wifi_ssid() -> Option<String> {
+        let output = Command::new("netsh").args(["wlan", "show", "interfaces"]).output().ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == "SSID").then(|| value.trim().to_string())
+            })
+            .filter(|ssid| !ssid.is_empty())
+    }
+
+    /// "    All User Profile     : HomeNet"
+    pub fn wifi_profiles() -> Result<Vec<String>> {
+        let output = Command::new("netsh").args(["wlan", "show", "profiles"]).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stdout).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.contains("Profile").then(|| value.trim().to_string())
+            })
+            .filter(|name| !name.is_empty())
+            .collect())
+    }
+
+    pub fn connect_wifi(profile: &str) -> Result<()> {
+        let output = Command::new("netsh")
+            .args(["wlan", "connect", &format!("name={}", profile)])
+            .output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stdout).trim());
+        }
+        Ok(())
+    }
+
+    pub fn link_info() -> LinkInfo {
+        let adapters: Vec<Adapter> = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", ADAPTER_SCRIPT])
+            .output()
+            .ok()
+            .and_then(|o| serde_json::from_slice(&o.stdout).ok())
+            .unwrap_or_default();
+
+        let ssid = wifi_ssid();
+        let wired = adapters.iter().any(|a| a.media_type.as_deref() == Some("802.3") && !super::is_vpn_interface(&a.interface_description));
+
+        LinkInfo {
+            link_type: if ssid.is_some() {
+                LinkType::Wifi
+            } else if wired {
+                LinkType::Ethernet
+            } else {
+                LinkType::Unknown
+            },
+            ssid,
+            active_interfaces: adapters
+                .into_iter()
+                .map(|a| format!("{} ({})", a.name, a.interface_description))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{LinkInfo, LinkType};
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    fn command_output(program: &str, args: &[&str]) -> Option<String> {
+        let output = Command::new(program).args(args).output().ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// The Wi-Fi hardware port's device, usually en0
+    fn wifi_device() -> String {
+        command_output("networksetup", &["-listallhardwareports"])
+            .and_then(|out| {
+                let mut lines = out.lines();
+                while let Some(line) = lines.next() {
+                    if line.contains("Wi-Fi") || line.contains("AirPort") {
+                        return lines.next()?.strip_prefix("Device: ").map(|d| d.trim().to_string());
+                    }
+                }
+                None
+            })
+            .unwrap_or_else(|| "en0".to_string())
+    }
+
+    /// Profiles are listed one per line, indented with a tab
+    pub fn wifi_profiles() -> Result<Vec<String>> {
+        let device = wifi_device();
+        let Some(out) = command_output("networksetup", &["-listpreferredwirelessnetworks", &device]) else {
+            bail!("networksetup isn't available");
+        };
+        Ok(out
+            .lines()
+            .filter(|line| line.starts_with('\t'))
+            .map(|line| line.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect())
+    }
+
+    // The password comes from the keychain
+    pub fn connect_wifi(profile: &str) -> Result<()> {
+        let output = Command::new("networksetup")
+            .args(["-setairportnetwork", &wifi_device(), profile])
+            .output()?;
+        // networksetup exits 0 even on failure and prints the reason instead
+        let message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || !message.is_empty() {
+            bail!("{}", if message.is_empty() { "networksetup failed".to_string() } else { message });
+        }
+        Ok(())
+    }
+
+    pub fn link_info() -> LinkInfo {
+        // "Current Wi-Fi Network: MyNetwork"
+        let ssid = command_output("networksetup", &["-getairportnetwork", "en0"])
+            .and_then(|out| out.split_once(": ").map(|(_, ssid)| ssid.trim().to_string()))
+            .filter(|ssid| !ssid.is_empty());
+
+        // VPN services configured in System Settings show as "* (Connected) ... "Name""
+        let active_interfaces = command_output("scutil", &["--nc", "list"])
+            .map(|out| {
+                out.lines()
+                    .filter(|line| line.contains("(Connected)"))
+                    .filter_map(|line| line.split('"').nth(1))
+                    .map(|name| format!("{} (vpn)", name))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        LinkInfo {
+            link_type: if ssid.is_some() { LinkType::Wifi } else { LinkType::Ethernet },
+            ssid,
+            active_interfaces,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::{LinkInfo, LinkType};
+    use anyhow::{bail, Result};
+    use std::path::Path;
+    use std::process::Command;
+
+    // NetworkManager keeps saved networks as connections of type 802-11-wireless
+    pub fn wifi_profiles() -> Result<Vec<String>> {
+        let output = match Command::new("nmcli").args(["-t", "-f", "NAME,TYPE", "connection", "show"]).output() {
+            Ok(output) => output,
+            Err(_) => bail!("Wi-Fi profiles need NetworkManager (nmcli)"),
+        };
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.rsplit_once(':'))
+            .filter(|(_, kind)| *kind == "802-11-wireless")
+            // Terse output escapes colons in names
+            .map(|(name, _)| name.replace("\\:", ":"))
+            .collect())
+    }
+
+    pub fn connect_wifi(profile: &str) -> Result<()> {
+        let output = Command::new("nmcli").args(["connection", "up", "id", profile]).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(())
+    }
+
+    pub fn link_info() -> LinkInfo {
+        let interfaces: Vec<String> = std::fs::read_dir("/sys/class/net")
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.file_name().to_string_lossy().to_string())
+                    .filter(|name| name != "lo")
+                    .filter(|name| {
+                        let state = std::fs::read_to_string(format!("/sys/class/net/{}/operstate", name)).unwrap_or_default();
+                        // Tunnels report "unknown" rather than "up"
+                        matches!(state.trim(), "up" | "unknown")
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let wireless = interfaces
+            .iter()
+            .any(|name| Path::new(&format!("/sys/class/net/{}/wireless", name)).exists());
+
+        let ssid = Command::new("iwgetid")
+            .arg("-r")
+            .output()
+            .ok()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .filter(|ssid| !ssid.is_empty());
+
+        LinkInfo {
+            link_type: if wireless {
+                LinkType::Wifi
+            } else if interfaces.iter().any(|name| name.starts_with("en") || name.starts_with("eth")) {
+                LinkType::Ethernet
+            } else {
+                LinkType::Unknown
+            },
+            ssid,
+            active_interfaces: interfaces,
+        }
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_network_status() -> Result<NetworkStatus, String> {
+    full_status().await
+}
+
+#[tauri::command]
+pub async fn get_wifi_profiles() -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(wifi_profiles)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Connect to a saved Wi-Fi profile by (spoken) name. Returns the profile used.
+#[tauri::command]
+pub async fn connect_wifi_network(name: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || connect_wifi(&name))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,476 @@
+// Notes Module
+// Named lists ("shopping", "todo") and free-form quick notes, kept in
+// ASTRAL/notes.json. Voice commands cover the everyday cases ("add milk and
+// eggs to my shopping list", "what's on my list?", "take a note, call the
+// dentist"), the dashboard edits them and exports a Markdown copy, and LLM
+// clients reach them through the MCP tools. Changes are announced with
+// `notes-changed` so open views refresh.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+/// Emitted after any change, payload is the whole `NotesStore`
+pub const NOTES_CHANGED_EVENT: &str = "notes-changed";
+
+/// The list "my list" and "the list" mean
+pub const DEFAULT_LIST: &str = "todo";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListItem {
+    pub id: String,
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+    pub added_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub id: String,
+    pub text: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotesStore {
+    /// List name (lowercase, without "list") -> items in the order added
+    pub lists: BTreeMap<String, Vec<ListItem>>,
+    pub notes: Vec<Note>,
+    /// Last change; sync keeps whichever machine's store is newer
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A spoken notes or list command
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotesCommand {
+    AddItems { list: String, items: Vec<String> },
+    RemoveItem { list: String, item: String },
+    ReadList { list: String },
+    ClearList { list: String },
+    ListLists,
+    AddNote(String),
+    ReadNotes,
+}
+
+static STORE: Lazy<Mutex<NotesStore>> = Lazy::new(|| Mutex::new(load()));
+/// Set when notes.json exists but couldn't be read or copied aside; saving
+/// would overwrite the user's notes with an empty store
+static KEEP_FILE: AtomicBool = AtomicBool::new(false);
+
+fn notes_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("notes.json");
+    Some(path)
+}
+
+fn load() -> NotesStore {
+    let Some(path) = notes_path() else {
+        return NotesStore::default();
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return NotesStore::default(),
+        Err(e) => {
+            warn!("Couldn't read notes.json, leaving it alone: {}", e);
+            KEEP_FILE.store(true, Ordering::Relaxed);
+            return NotesStore::default();
+        }
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        // Kept aside so the notes can still be recovered by hand
+        let backup = path.with_file_name(format!("notes.unreadable-{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+        match std::fs::copy(&path, &backup) {
+            Ok(_) => warn!("notes.json is unreadable ({}); saved a copy as {}", e, backup.display()),
+            Err(copy_error) => {
+                warn!("notes.json is unreadable ({}) and couldn't be copied, leaving it alone: {}", e, copy_error);
+                KEEP_FILE.store(true, Ordering::Relaxed);
+            }
+        }
+        NotesStore::default()
+    })
+}
+
+fn save(store: &NotesStore) -> Result<(), String> {
+    if KEEP_FILE.load(Ordering::Relaxed) {
+        return Err("ASTRAL couldn't read notes.json, so it won't save over it. Fix or move the file and restart ASTRAL.".to_string());
+    }
+    let path = notes_path().ok_or_else(|| "Could not find config directory".to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(store).map_err(|e| format!("Failed to serialize notes: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write notes: {}", e))
+}
+
+/// Change the store, save it and tell the UI
+fn update<T>(app: &AppHandle, change: impl FnOnce(&mut NotesStore) -> Result<T, String>) -> Result<T, String> {
+    let mut store = STORE.lock().map_err(|e| e.to_string())?;
+    let result = change(&mut store)?;
+    store.updated_at = Some(chrono::Utc::now());
+    save(&store)?;
+    if let Err(e) = app.emit(NOTES_CHANGED_EVENT, &*store) {
+        warn!("Failed to emit notes change: {}", e);
+    }
+    Ok(result)
+}
+
+/// Take a whole store from another machine, keeping its timestamp
+pub fn replace(app: &AppHandle, store: NotesStore) -> Result<(), String> {
+    let mut current = STORE.lock().map_err(|e| e.to_string())?;
+    save(&store)?;
+    *current = store;
+    if let Err(e) = app.emit(NOTES_CHANGED_EVENT, &*current) {
+        warn!("Failed to emit notes change: {}", e);
+    }
+    Ok(())
+}
+
+pub fn snapshot() -> NotesStore {
+    STORE.lock().map(|store| store.clone()).unwrap_or_default()
+}
+
+fn new_id() -> String {
+    format!("{:x}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default())
+}
+
+/// "my shopping list" -> "shopping", "the to-do list" / "my list" -> "todo"
+pub fn normalize_list_name(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    let name = name.trim_end_matches(|c: char| !c.is_alphanumeric());
+    let name = ["my ", "the ", "our "].iter().find_map(|p| name.strip_prefix(p)).unwrap_or(name);
+    let name = name.strip_suffix("list").unwrap_or(name).trim();
+    match name {
+        "" | "to-do" | "to do" | "todo" | "todos" => DEFAULT_LIST.to_string(),
+        name => name.to_string(),
+    }
+}
+
+/// "milk, eggs and bread" -> ["milk", "eggs", "bread"]
+fn split_items(items: &str) -> Vec<String> {
+    items
+        .split(',')
+        .flat_map(|part| part.split(" and "))
+        .map(|item| item.trim().trim_start_matches("and ").trim())
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Split "milk to my shopping list" at the last `separator` before the list name
+fn split_on_list<'a>(rest: &'a str, separators: &[&str]) -> Option<(&'a str, &'a str)> {
+    separators.iter().find_map(|separator| {
+        let index = rest.rfind(separator)?;
+        let list = &rest[index + separator.len()..];
+        list.trim_end_matches(|c: char| !c.is_alphanumeric())
+            .ends_with("list")
+            .then(|| (&rest[..index], list))
+    })
+}
+
+impl NotesCommand {
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.trim().to_lowercase();
+        let lower = lower.trim_end_matches(['.', '!']);
+
+        // Quick notes: "take a note, call the dentist", "note that the wifi password is ..."
+        for prefix in ["take a note that ", "take a note ", "make a note that ", "make a note ", "note that ", "add a note "] {
+            if let Some(rest) = lower.strip_prefix(prefix) {
+                // Keep the note's own capitalization
+                let text = command.trim().get(prefix.len()..).unwrap_or(rest);
+                let text = text.trim_start_matches([',', ':', ' ']).trim_end_matches(['.', '!']).trim();
+                return (!text.is_empty()).then(|| Self::AddNote(text.to_string()));
+            }
+        }
+        if ["read my notes", "what are my notes", "what notes do i have", "read me my notes"].iter().any(|p| lower.contains(p)) {
+            return Some(Self::ReadNotes);
+        }
+        if !lower.contains("list") {
+            return None;
+        }
+        // So "to my to do list" doesn't split at the wrong "to"
+        let lower = lower.replace("to do list", "todo list").replace("to-do list", "todo list");
+        let lower = lower.as_str();
+        if ["what lists", "which lists"].iter().any(|p| lower.starts_with(p)) {
+            return Some(Self::ListLists);
+        }
+
+        // "add milk and eggs to my shopping list", "put bread on the list"
+        let add = ["add ", "put "].iter().find_map(|p| lower.strip_prefix(p));
+        if let Some((items, list)) = add.and_then(|rest| split_on_list(rest, &[" to ", " on "])) {
+            let items = split_items(items);
+            return (!items.is_empty()).then(|| Self::AddItems { list: normalize_list_name(list), items });
+        }
+        // "remove milk from my shopping list", "take bread off the list", "cross eggs off my list"
+        let remove = ["remove ", "delete ", "take ", "cross ", "check "].iter().find_map(|p| lower.strip_prefix(p));
+        if let Some((item, list)) = remove.and_then(|rest| split_on_list(rest, &[" from ", " off ", " on "])) {
+            let item = item.trim().trim_start_matches("off ").trim_end_matches(" off").trim();
+            return (!item.is_empty()).then(|| Self::RemoveItem { list: normalize_list_name(list), item: item.to_string() });
+        }
+        // Only a list that exists, so a misheard "clear ..." can't wipe the default one
+        if let Some(rest) = ["clear ", "empty ", "delete "].iter().find_map(|p| lower.strip_prefix(p)) {
+            let list = normalize_list_name(rest);
+            return (rest.trim_end_matches(|c: char| !c.is_alphanumeric()).ends_with("list") && has_list(&list))
+                .then_some(Self::ClearList { list });
+        }
+        // "what's on my shopping list?", "read my list"
+        let list = ["what's on ", "what is on ", "whats on ", "read me ", "read "]
+            .iter()
+            .find_map(|p| lower.strip_prefix(p))?;
+        Some(Self::ReadList { list: normalize_list_name(list.trim_end_matches('?')) })
+    }
+}
+
+fn spoken_list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+fn list_label(list: &str) -> String {
+    if list == DEFAULT_LIST {
+        "to-do list".to_string()
+    } else {
+        format!("{} list", list)
+    }
+}
+
+/// Add `items` to `list`, creating it if needed; returns how many were new
+pub fn add_items(app: &AppHandle, list: &str, items: &[String]) -> Result<usize, String> {
+    let list = normalize_list_name(list);
+    update(app, |store| {
+        let entries = store.lists.entry(list.clone()).or_default();
+        let mut added = 0;
+        for text in items.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            // Saying it twice doesn't add it twice, but it does bring it back if ticked off
+            if let Some(existing) = entries.iter_mut().find(|e| e.text.eq_ignore_ascii_case(text)) {
+                existing.done = false;
+                continue;
+            }
+            entries.push(ListItem {
+                id: new_id() + &added.to_string(),
+                text: text.to_string(),
+                done: false,
+                added_at: chrono::Local::now().to_rfc3339(),
+            });
+            added += 1;
+        }
+        info!("Added {} items to the {} list", added, list);
+        Ok(added)
+    })
+}
+
+/// Remove the item matching `id_or_text`; false if nothing matched
+pub fn remove_item(app: &AppHandle, list: &str, id_or_text: &str) -> Result<bool, String> {
+    let list = normalize_list_name(list);
+    update(app, |store| {
+        let Some(entries) = store.lists.get_mut(&list) else {
+            return Ok(false);
+        };
+        let before = entries.len();
+        entries.retain(|e| e.id != id_or_text && !e.text.eq_ignore_ascii_case(id_or_text.trim()));
+        Ok(entries.len() != before)
+    })
+}
+
+pub fn set_done(app: &AppHandle, list: &str, id: &str, done: bool) -> Result<(), String> {
+    let list = normalize_list_name(list);
+    update(app, |store| {
+        let item = store
+            .lists
+            .get_mut(&list)
+            .and_then(|entries| entries.iter_mut().find(|e| e.id == id))
+            .ok_or_else(|| format!("No such item on the {}", list_label(&list)))?;
+        item.done = done;
+        Ok(())
+    })
+}
+
+/// Delete the whole list; false if it didn't exist
+pub fn clear_list(app: &AppHandle, list: &str) -> Result<bool, String> {
+    let list = normalize_list_name(list);
+    update(app, |store| Ok(store.lists.remove(&list).is_some()))
+}
+
+fn has_list(list: &str) -> bool {
+    STORE.lock().map(|store| store.lists.contains_key(list)).unwrap_or(false)
+}
+
+/// Items still to do on `list`
+pub fn open_items(list: &str) -> Vec<String> {
+    let list = normalize_list_name(list);
+    STORE
+        .lock()
+        .ok()
+        .and_then(|store| store.lists.get(&list).cloned())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| !item.done)
+        .map(|item| item.text)
+        .collect()
+}
+
+pub fn add_note(app: &AppHandle, text: &str) -> Result<Note, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("The note is empty".to_string());
+    }
+    update(app, |store| {
+        let note = Note {
+            id: new_id(),
+            text: text.to_string(),
+            created_at: chrono::Local::now().to_rfc3339(),
+        };
+        store.notes.push(note.clone());
+        Ok(note)
+    })
+}
+
+pub fn delete_note(app: &AppHandle, id: &str) -> Result<bool, String> {
+    update(app, |store| {
+        let before = store.notes.len();
+        store.notes.retain(|n| n.id != id);
+        Ok(store.notes.len() != before)
+    })
+}
+
+/// Carry out a spoken command and say how it went
+pub fn apply_command(app: &AppHandle, command: NotesCommand) -> Result<String, String> {
+    Ok(match command {
+        NotesCommand::AddItems { list, items } => {
+            add_items(app, &list, &items)?;
+            format!("Added {} to your {}.", spoken_list(&items), list_label(&list))
+        }
+        NotesCommand::RemoveItem { list, item } => {
+            if remove_item(app, &list, &item)? {
+                format!("Took {} off your {}.", item, list_label(&list))
+            } else {
+                format!("{} isn't on your {}.", item, list_label(&list))
+            }
+        }
+        NotesCommand::ReadList { list } => match open_items(&list).as_slice() {
+            [] => format!("Your {} is empty.", list_label(&list)),
+            items => format!("On your {}: {}.", list_label(&list), spoken_list(items)),
+        },
+        NotesCommand::ClearList { list } => {
+            if clear_list(app, &list)? {
+                format!("Cleared your {}.", list_label(&list))
+            } else {
+                format!("You don't have a {}.", list_label(&list))
+            }
+        }
+        NotesCommand::ListLists => {
+            let names: Vec<String> = snapshot().lists.keys().map(|l| list_label(l)).collect();
+            if names.is_empty() {
+                "You don't have any lists yet.".to_string()
+            } else {
+                format!("You have your {}.", spoken_list(&names))
+            }
+        }
+        NotesCommand::AddNote(text) => {
+            add_note(app, &text)?;
+            "Noted.".to_string()
+        }
+        NotesCommand::ReadNotes => {
+            let notes = snapshot().notes;
+            match notes.len() {
+                0 => "You don't have any notes.".to_string(),
+                count => {
+                    // The most recent few; the rest are on the dashboard
+                    let recent: Vec<String> = notes.iter().rev().take(5).map(|n| n.text.clone()).collect();
+                    format!("You have {} notes. The latest: {}.", count, recent.join(". "))
+                }
+            }
+        }
+    })
+}
+
+/// Everything as Markdown, lists first with checkboxes
+pub fn to_markdown(store: &NotesStore) -> String {
+    let mut markdown = String::from("# ASTRAL notes\n");
+    for (name, items) in &store.lists {
+        markdown.push_str(&format!("\n## {}\n\n", list_label(name)));
+        for item in items {
+            markdown.push_str(&format!("- [{}] {}\n", if item.done { "x" } else { " " }, item.text));
+        }
+    }
+    if !store.notes.is_empty() {
+        markdown.push_str("\n## Notes\n\n");
+        for note in &store.notes {
+            let when = chrono::DateTime::parse_from_rfc3339(&note.created_at)
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            markdown.push_str(&format!("- {} ({})\n", note.text, when));
+        }
+    }
+    markdown
+}
+
+fn default_export_path() -> Result<PathBuf, String> {
+    let mut path = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| "No documents directory on this system".to_string())?;
+    path.push(format!("astral-notes-{}.md", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    Ok(path)
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_notes() -> Result<NotesStore, String> {
+    Ok(snapshot())
+}
+
+#[tauri::command]
+pub async fn add_list_item(app: AppHandle, list: String, text: String) -> Result<usize, String> {
+    add_items(&app, &list, &[text])
+}
+
+#[tauri::command]
+pub async fn remove_list_item(app: AppHandle, list: String, id: String) -> Result<bool, String> {
+    remove_item(&app, &list, &id)
+}
+
+#[tauri::command]
+pub async fn set_list_item_done(app: AppHandle, list: String, id: String, done: bool) -> Result<(), String> {
+    set_done(&app, &list, &id, done)
+}
+
+#[tauri::command]
+pub async fn delete_list(app: AppHandle, list: String) -> Result<bool, String> {
+    clear_list(&app, &list)
+}
+
+#[tauri::command]
+pub async fn create_note(app: AppHandle, text: String) -> Result<Note, String> {
+    add_note(&app, &text)
+}
+
+#[tauri::command]
+pub async fn remove_note(app: AppHandle, id: String) -> Result<bool, String> {
+    delete_note(&app, &id)
+}
+
+/// Write the lists and notes as Markdown to `path` (a timestamped file in Documents by default)
+#[tauri::command]
+pub async fn export_notes(path: Option<String>) -> Result<String, String> {
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path.trim()),
+        None => default_export_path()?,
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, to_markdown(&snapshot())).map_err(|e| format!("Couldn't write {:?}: {}", path, e))?;
+    info!("Notes exported to {:?}", path);
+    Ok(path.to_string_lossy().to_string())
+}
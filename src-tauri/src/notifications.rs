@@ -0,0 +1,315 @@
+// Notifications Module
+// Native toast notifications with action buttons ("Snooze", "Run now",
+// "Dismiss"). Clicks come back through `handle_action`, which the
+// `notification_action` command also exposes to the UI. A snoozed
+// notification waits on a timer that `cancel_snooze` can stop.
+
+use anyhow::{bail, Result};
+use tracing::{info, warn};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+pub const NOTIFICATION_ACTION_EVENT: &str = "notification-action";
+
+pub const DEFAULT_SNOOZE_MINUTES: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationAction {
+    Snooze,
+    RunNow,
+    Dismiss,
+}
+
+impl NotificationAction {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "snooze" => Some(Self::Snooze),
+            "run_now" | "run" => Some(Self::RunNow),
+            "dismiss" => Some(Self::Dismiss),
+            _ => None,
+        }
+    }
+
+    /// Key passed through the OS and back on click
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Snooze => "snooze",
+            Self::RunNow => "run_now",
+            Self::Dismiss => "dismiss",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Snooze => "Snooze",
+            Self::RunNow => "Run now",
+            Self::Dismiss => "Dismiss",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationRequest {
+    pub title: String,
+    pub message: String,
+    #[serde(default)]
+    pub actions: Vec<NotificationAction>,
+    /// Routine started by "Run now"
+    #[serde(default)]
+    pub routine_id: Option<String>,
+    #[serde(default)]
+    pub snooze_minutes: Option<u64>,
+}
+
+/// Payload of `notification-action`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationActionEvent {
+    pub notification_id: String,
+    pub action: NotificationAction,
+    pub routine_id: Option<String>,
+}
+
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Notifications that can still be acted on, by id
+static ACTIVE: Lazy<Mutex<HashMap<String, NotificationRequest>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// Timers of snoozed notifications, by the id of the one that was snoozed
+static SNOOZED: Lazy<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn init(app: AppHandle) {
+    if APP_HANDLE.set(app).is_err() {
+        warn!("Notifications already initialized");
+    }
+}
+
+/// Show a notification, returning its id
+pub fn send(request: NotificationRequest) -> Result<String> {
+    let Some(app) = APP_HANDLE.get() else {
+        bail!("Notifications aren't initialized");
+    };
+    if request.actions.contains(&NotificationAction::RunNow) && request.routine_id.is_none() {
+        bail!("\"Run now\" needs a routine to run");
+    }
+    if request.actions.contains(&NotificationAction::Snooze) {
+        snooze_delay(&request)?;
+    }
+
+    let id = format!("notif-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    info!("Sending notification {}: {} - {}", id, request.title, request.message);
+
+    if !request.actions.is_empty() {
+        ACTIVE.lock().unwrap_or_else(|e| e.into_inner()).insert(id.clone(), request.clone());
+    }
+    platform::show(app, &id, &request)?;
+    Ok(id)
+}
+
+fn snooze_delay(request: &NotificationRequest) -> Result<Duration> {
+    let minutes = request.snooze_minutes.unwrap_or(DEFAULT_SNOOZE_MINUTES);
+    match minutes.checked_mul(60) {
+        Some(seconds) => Ok(Duration::from_secs(seconds)),
+        None => bail!("Can't snooze for {} minutes", minutes),
+    }
+}
+
+/// Called from the OS click handlers; they run off the async runtime
+fn on_clicked(id: String, key: &str) {
+    let Some(action) = NotificationAction::parse(key) else {
+        // Body click or the notification closing by itself
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = handle_action(&id, action).await {
+            warn!("Notification action {} on {} failed: {}", action.key(), id, e);
+        }
+    });
+}
+
+/// Carry out an action button. Each notification can be acted on once.
+/// Snoozing returns right away; the notification comes back from a timer.
+pub async fn handle_action(id: &str, action: NotificationAction) -> Result<()> {
+    let request = ACTIVE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(id)
+        .ok_or_else(|| anyhow::anyhow!("Notification {} is no longer active", id))?;
+
+    info!("Notification {}: {}", id, action.label());
+
+    if let Some(app) = APP_HANDLE.get() {
+        let event = NotificationActionEvent {
+            notification_id: id.to_string(),
+            action,
+            routine_id: request.routine_id.clone(),
+        };
+        if let Err(e) = app.emit(NOTIFICATION_ACTION_EVENT, &event) {
+            warn!("Failed to emit {}: {}", NOTIFICATION_ACTION_EVENT, e);
+        }
+    }
+
+    match action {
+        NotificationAction::Snooze => {
+            let delay = snooze_delay(&request)?;
+            let mut snoozed = SNOOZED.lock().unwrap_or_else(|e| e.into_inner());
+            let snoozed_id = id.to_string();
+            let timer = tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(delay).await;
+                SNOOZED.lock().unwrap_or_else(|e| e.into_inner()).remove(&snoozed_id);
+                if let Err(e) = send(request) {
+                    warn!("Snoozed notification {} couldn't be shown again: {}", snoozed_id, e);
+                }
+            });
+            snoozed.insert(id.to_string(), timer);
+        }
+        NotificationAction::RunNow => {
+            if let (Some(routine_id), Some(app)) = (request.routine_id, APP_HANDLE.get()) {
+                crate::commands::execute_automation(app.clone(), routine_id)
+                    .await
+                    .map_err(|e| anyhow::anyhow!(e))?;
+            }
+        }
+        NotificationAction::Dismiss => {}
+    }
+    Ok(())
+}
+
+/// Stop a snoozed notification from coming back. False if it wasn't snoozed
+/// or has already been shown again.
+pub fn cancel_snooze(id: &str) -> bool {
+    match SNOOZED.lock().unwrap_or_else(|e| e.into_inner()).remove(id) {
+        Some(timer) => {
+            timer.abort();
+            info!("Cancelled snoozed notification {}", id);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::NotificationRequest;
+    use anyhow::Result;
+    use tauri::AppHandle;
+    use tauri_winrt_notification::Toast;
+
+    pub fn show(app: &AppHandle, id: &str, request: &NotificationRequest) -> Result<()> {
+        // Unpackaged dev builds have no registered AppUserModelID
+        let app_id = if tauri::is_dev() {
+            Toast::POWERSHELL_APP_ID.to_string()
+        } else {
+            app.config().identifier.clone()
+        };
+
+        let mut toast = Toast::new(&app_id).title(&request.title).text1(&request.message);
+        for action in &request.actions {
+            toast = toast.add_button(action.label(), action.key());
+        }
+
+        let id = id.to_string();
+        toast
+            .on_activated(move |action| {
+                if let Some(key) = action {
+                    super::on_clicked(id.clone(), &key);
+                }
+                Ok(())
+            })
+            .show()?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::NotificationRequest;
+    use anyhow::Result;
+    use tauri::AppHandle;
+
+    pub fn show(_app: &AppHandle, id: &str, request: &NotificationRequest) -> Result<()> {
+        let mut notification = notify_rust::Notification::new();
+        notification.summary(&request.title).body(&request.message).appname("ASTRAL");
+        for action in &request.actions {
+            notification.action(action.key(), action.label());
+        }
+
+        let handle = notification.show()?;
+        if request.actions.is_empty() {
+            return Ok(());
+        }
+
+        // wait_for_action blocks until the notification is clicked or closed
+        let id = id.to_string();
+        std::thread::spawn(move || {
+            handle.wait_for_action(|key| super::on_clicked(id, key));
+        });
+        Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+mod platform {
+    use super::NotificationRequest;
+    use anyhow::Result;
+    use tracing::warn;
+    use tauri::AppHandle;
+    use tauri_plugin_notification::NotificationExt;
+
+    // The notification plugin has no desktop action buttons here; the UI can
+    // still offer them through the `notification_action` command
+    pub fn show(app: &AppHandle, _id: &str, request: &NotificationRequest) -> Result<()> {
+        if !request.actions.is_empty() {
+            warn!("Notification action buttons aren't supported on this platform");
+        }
+        app.notification()
+            .builder()
+            .title(&request.title)
+            .body(&request.message)
+            .show()?;
+        Ok(())
+    }
+}
+
+// ========== Tauri Commands ==========
+
+/// Show a notification with optional action buttons. Returns its id.
+#[tauri::command]
+pub async fn send_notification(
+    title: String,
+    message: String,
+    actions: Option<Vec<NotificationAction>>,
+    routine_id: Option<String>,
+    snooze_minutes: Option<u64>,
+) -> Result<String, String> {
+    let request = NotificationRequest {
+        title,
+        message,
+        actions: actions.unwrap_or_default(),
+        routine_id,
+        snooze_minutes,
+    };
+    tokio::task::spawn_blocking(move || send(request))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Act on a notification as if its button had been clicked
+#[tauri::command]
+pub async fn notification_action(notification_id: String, action: String) -> Result<(), String> {
+    let action = NotificationAction::parse(&action).ok_or_else(|| format!("Unknown action: {}", action))?;
+    handle_action(&notification_id, action).await.map_err(|e| e.to_string())
+}
+
+/// Keep a snoozed notification from coming back
+#[tauri::command]
+pub async fn cancel_snoozed_notification(notification_id: String) -> Result<bool, String> {
+    Ok(cancel_snooze(&notification_id))
+}
@@ -0,0 +1,213 @@
+// OAuth Module
+// Signing in to Google from a desktop app: the browser is sent to Google's
+// consent page with PKCE and a loopback redirect to a port opened just for
+// the sign-in, and the code that comes back is traded for a refresh token.
+// Callers keep that token in their own settings section (it's sealed like
+// any other secret) and ask for the narrowest scope they need; access tokens
+// are cached here until they run out. The OAuth client is one the user
+// creates of the "Desktop app" type, so no credentials ship with ASTRAL.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{info, warn};
+
+const GOOGLE_AUTH_ENDPOINT: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const GOOGLE_TOKEN_ENDPOINT: &str = "https://oauth2.googleapis.com/token";
+const GOOGLE_REVOKE_ENDPOINT: &str = "https://oauth2.googleapis.com/revoke";
+
+/// How long the browser sign-in may take
+const SIGN_IN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Access tokens by the refresh token they came from, with when they run out
+static ACCESS_TOKENS: Lazy<Mutex<HashMap<String, (String, Instant)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A user-created Google OAuth client
+#[derive(Debug, Clone, Copy)]
+pub struct GoogleClient<'a> {
+    pub client_id: &'a str,
+    pub client_secret: &'a str,
+}
+
+impl GoogleClient<'_> {
+    fn ensure_configured(&self) -> AstralResult<()> {
+        if self.client_id.is_empty() || self.client_secret.is_empty() {
+            return Err(AstralError::not_configured(
+                "Add the client ID and secret of a Google OAuth client of the \"Desktop app\" type in Settings",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Send a request to a Google API and read the JSON reply. `service` names
+/// it in errors ("Google Calendar").
+pub async fn google_json(request: reqwest::RequestBuilder, service: &str) -> AstralResult<Value> {
+    let response = crate::privacy::send(request.timeout(Duration::from_secs(20)))
+        .await
+        .map_err(|e| AstralError::from(e.context(format!("Couldn't reach {}", service))))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        if body.contains("invalid_grant") {
+            return Err(AstralError::new(
+                ErrorCode::Unauthorized,
+                format!("{} access has expired or was revoked; connect it again in Settings", service),
+            ));
+        }
+        return Err(AstralError::from_status(service, status, &body));
+    }
+    Ok(response.json().await?)
+}
+
+fn remember_access_token(refresh_token: &str, body: &Value) -> Option<String> {
+    let token = body.get("access_token")?.as_str()?.to_string();
+    let lifetime = body.get("expires_in").and_then(Value::as_u64).unwrap_or(3600);
+    let expires = Instant::now() + Duration::from_secs(lifetime.saturating_sub(60));
+    ACCESS_TOKENS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(refresh_token.to_string(), (token.clone(), expires));
+    Some(token)
+}
+
+/// A current access token for `refresh_token`, fetching a new one when the cached one ran out
+pub async fn google_access_token(client: GoogleClient<'_>, refresh_token: &str, service: &str) -> AstralResult<String> {
+    client.ensure_configured()?;
+    let cached = ACCESS_TOKENS.lock().unwrap_or_else(|e| e.into_inner()).get(refresh_token).cloned();
+    if let Some((token, expires)) = cached {
+        if Instant::now() < expires {
+            return Ok(token);
+        }
+    }
+    let request = crate::proxy::client().post(GOOGLE_TOKEN_ENDPOINT).form(&[
+        ("client_id", client.client_id),
+        ("client_secret", client.client_secret),
+        ("refresh_token", refresh_token),
+        ("grant_type", "refresh_token"),
+    ]);
+    let body = google_json(request, service).await?;
+    remember_access_token(refresh_token, &body)
+        .ok_or_else(|| AstralError::new(ErrorCode::ProviderError, "Google didn't return an access token"))
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(len).map(char::from).collect()
+}
+
+async fn reply_to_browser(stream: &mut tokio::net::TcpStream, status: &str, message: &str) {
+    let body = format!(
+        "<html><body style=\"font-family: sans-serif; padding: 2em\"><h3>{}</h3></body></html>",
+        message
+    );
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Wait for Google to send the browser back with `?code=...`
+async fn wait_for_redirect(listener: &tokio::net::TcpListener, state: &str) -> AstralResult<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut buffer = vec![0u8; 8192];
+        let read = stream.read(&mut buffer).await?;
+        let request = String::from_utf8_lossy(&buffer[..read]);
+        let path = request.lines().next().and_then(|line| line.split_whitespace().nth(1)).unwrap_or("/");
+        let Ok(url) = reqwest::Url::parse(&format!("http://127.0.0.1{}", path)) else { continue };
+        let query = |key: &str| url.query_pairs().find(|(k, _)| k == key).map(|(_, v)| v.into_owned());
+
+        if let Some(error) = query("error") {
+            reply_to_browser(&mut stream, "200 OK", "Sign-in was cancelled. You can close this tab.").await;
+            return Err(AstralError::new(ErrorCode::Cancelled, format!("Google sign-in didn't complete: {}", error)));
+        }
+        let Some(code) = query("code") else {
+            // The browser also asks for a favicon
+            reply_to_browser(&mut stream, "404 Not Found", "Not found").await;
+            continue;
+        };
+        if query("state").as_deref() != Some(state) {
+            reply_to_browser(&mut stream, "400 Bad Request", "This sign-in link doesn't match. Start again from ASTRAL.").await;
+            return Err(AstralError::invalid_input("The Google sign-in response didn't match the request"));
+        }
+        reply_to_browser(&mut stream, "200 OK", "Signed in to ASTRAL. You can close this tab.").await;
+        return Ok(code);
+    }
+}
+
+/// Run the browser sign-in for `scope` and return the refresh token
+pub async fn google_sign_in(client: GoogleClient<'_>, scope: &str, service: &str) -> AstralResult<String> {
+    client.ensure_configured()?;
+    crate::privacy::check_url(GOOGLE_TOKEN_ENDPOINT)?;
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?;
+    let redirect_uri = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+    let verifier = random_string(64);
+    let state = random_string(24);
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    let auth_url = reqwest::Url::parse_with_params(
+        GOOGLE_AUTH_ENDPOINT,
+        &[
+            ("client_id", client.client_id),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", scope),
+            ("access_type", "offline"),
+            ("prompt", "consent"),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+            ("state", state.as_str()),
+        ],
+    )
+    .map_err(|e| AstralError::new(ErrorCode::Internal, e.to_string()))?;
+    info!("Opening the browser for {} sign-in", service);
+    crate::system_integration::open_path(auth_url.as_str(), false)?;
+
+    let code = tokio::time::timeout(SIGN_IN_TIMEOUT, wait_for_redirect(&listener, &state))
+        .await
+        .map_err(|_| AstralError::new(ErrorCode::Timeout, "Google sign-in took too long; try again"))??;
+
+    let request = crate::proxy::client().post(GOOGLE_TOKEN_ENDPOINT).form(&[
+        ("client_id", client.client_id),
+        ("client_secret", client.client_secret),
+        ("code", code.as_str()),
+        ("code_verifier", verifier.as_str()),
+        ("grant_type", "authorization_code"),
+        ("redirect_uri", redirect_uri.as_str()),
+    ]);
+    let body = google_json(request, service).await?;
+    let refresh_token = body
+        .get("refresh_token")
+        .and_then(Value::as_str)
+        .ok_or_else(|| AstralError::new(ErrorCode::ProviderError, "Google didn't return a refresh token"))?
+        .to_string();
+    remember_access_token(&refresh_token, &body);
+    info!("{} connected", service);
+    Ok(refresh_token)
+}
+
+/// Revoke `refresh_token` with Google and drop its access token; failures are only logged
+pub async fn google_revoke(refresh_token: &str) {
+    ACCESS_TOKENS.lock().unwrap_or_else(|e| e.into_inner()).remove(refresh_token);
+    let request = crate::proxy::client()
+        .post(GOOGLE_REVOKE_ENDPOINT)
+        .form(&[("token", refresh_token)])
+        .timeout(Duration::from_secs(20));
+    match crate::privacy::send(request).await {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => warn!("Google didn't revoke the token: {}", response.status()),
+        Err(e) => warn!("Couldn't revoke the Google token: {}", e),
+    }
+}
@@ -0,0 +1,287 @@
+// Screen OCR Module
+// Screenshots the desktop and extracts its text so the assistant can answer
+// things like "read me the error on screen". Windows uses the built-in
+// Windows.Media.Ocr engine; other platforms shell out to a screenshot tool
+// and the tesseract CLI. The same screenshot, as a PNG, can go to a vision
+// model instead (see `capture_png`).
+
+use tracing::info;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenText {
+    /// All recognized text, one line per detected text line
+    pub text: String,
+    pub lines: Vec<String>,
+    /// Which OCR backend produced the result
+    pub engine: String,
+}
+
+impl ScreenText {
+    fn from_lines(lines: Vec<String>, engine: &str) -> Self {
+        let lines: Vec<String> = lines
+            .into_iter()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        Self {
+            text: lines.join("\n"),
+            lines,
+            engine: engine.to_string(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+}
+
+/// Capture every monitor and run OCR over it. Blocking.
+pub fn read_screen() -> Result<ScreenText, String> {
+    let result = platform::read_screen().map_err(|e| e.to_string())?;
+    info!("OCR ({}) read {} lines from screen", result.engine, result.lines.len());
+    Ok(result)
+}
+
+/// A PNG screenshot of every monitor, for a vision model. Blocking.
+pub fn capture_png() -> Result<Vec<u8>, String> {
+    let png = platform::capture_png().map_err(|e| e.to_string())?;
+    info!("Captured a {} KB screenshot", png.len() / 1024);
+    Ok(png)
+}
+
+/// Whether a command is asking about what's on screen ("read me the error on screen")
+pub fn is_screen_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    let mentions_screen = lower.contains("on screen")
+        || lower.contains("on the screen")
+        || lower.contains("on my screen")
+        || lower.contains("the screen says");
+    let asks = ["read", "what", "explain", "says", "error", "summarize"]
+        .iter()
+        .any(|w| lower.contains(w));
+
+    mentions_screen && asks
+}
+
+/// Prompt handing the screen text to the LLM along with the user's request
+pub fn build_screen_prompt(command: &str, screen: &ScreenText) -> String {
+    format!(
+        "The user asked: \"{}\"\n\nThis is the text currently visible on their screen (from OCR, may contain recognition errors):\n---\n{}\n---\nAnswer using the screen text. Keep it short enough to read aloud.",
+        command, screen.text
+    )
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::ScreenText;
+    use anyhow::{bail, Result};
+    use windows::Graphics::Imaging::{BitmapAlphaMode, BitmapEncoder, BitmapPixelFormat, SoftwareBitmap};
+    use windows::Media::Ocr::OcrEngine;
+    use windows::Security::Cryptography::CryptographicBuffer;
+    use windows::Storage::Streams::{DataReader, InMemoryRandomAccessStream};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{
+        BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits,
+        ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, SRCCOPY,
+    };
+    use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+    };
+
+    /// Top-down BGRA pixels of the whole virtual desktop
+    fn capture_screen() -> Result<(Vec<u8>, i32, i32)> {
+        unsafe {
+            let x = GetSystemMetrics(SM_XVIRTUALSCREEN);
+            let y = GetSystemMetrics(SM_YVIRTUALSCREEN);
+            let width = GetSystemMetrics(SM_CXVIRTUALSCREEN);
+            let height = GetSystemMetrics(SM_CYVIRTUALSCREEN);
+            if width <= 0 || height <= 0 {
+                bail!("No screen to capture");
+            }
+
+            let screen = GetDC(HWND(0));
+            let memory = CreateCompatibleDC(screen);
+            let bitmap = CreateCompatibleBitmap(screen, width, height);
+            let previous = SelectObject(memory, bitmap);
+
+            let copied = BitBlt(memory, 0, 0, width, height, screen, x, y, SRCCOPY);
+
+            let mut info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width,
+                    // Negative height asks for top-down rows
+                    biHeight: -height,
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut pixels = vec![0u8; width as usize * height as usize * 4];
+            let rows = GetDIBits(
+                memory,
+                bitmap,
+                0,
+                height as u32,
+                Some(pixels.as_mut_ptr() as *mut _),
+                &mut info,
+                DIB_RGB_COLORS,
+            );
+
+            SelectObject(memory, previous);
+            let _ = DeleteObject(bitmap);
+            let _ = DeleteDC(memory);
+            ReleaseDC(HWND(0), screen);
+
+            copied?;
+            if rows == 0 {
+                bail!("Failed to read screen pixels");
+            }
+
+            Ok((pixels, width, height))
+        }
+    }
+
+    pub fn read_screen() -> Result<ScreenText> {
+        // WinRT activation needs the thread in an apartment
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        }
+
+        let (pixels, width, height) = capture_screen()?;
+
+        let max = OcrEngine::MaxImageDimension()? as i32;
+        if width > max || height > max {
+            bail!("Screen is too large for OCR ({}x{}, max {})", width, height, max);
+        }
+
+        let buffer = CryptographicBuffer::CreateFromByteArray(&pixels)?;
+        let bitmap = SoftwareBitmap::CreateCopyWithAlphaFromBuffer(
+            &buffer,
+            BitmapPixelFormat::Bgra8,
+            width,
+            height,
+            BitmapAlphaMode::Premultiplied,
+        )?;
+
+        let engine = OcrEngine::TryCreateFromUserProfileLanguages()
+            .map_err(|_| anyhow::anyhow!("No OCR language pack is installed"))?;
+        let result = engine.RecognizeAsync(&bitmap)?.get()?;
+
+        let mut lines = Vec::new();
+        for line in result.Lines()? {
+            lines.push(line.Text()?.to_string());
+        }
+
+        Ok(ScreenText::from_lines(lines, "Windows.Media.Ocr"))
+    }
+
+    pub fn capture_png() -> Result<Vec<u8>> {
+        unsafe {
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        }
+
+        let (pixels, width, height) = capture_screen()?;
+
+        let stream = InMemoryRandomAccessStream::new()?;
+        let encoder = BitmapEncoder::CreateAsync(BitmapEncoder::PngEncoderId()?, &stream)?.get()?;
+        // GDI leaves the alpha byte zeroed
+        encoder.SetPixelData(
+            BitmapPixelFormat::Bgra8,
+            BitmapAlphaMode::Ignore,
+            width as u32,
+            height as u32,
+            96.0,
+            96.0,
+            &pixels,
+        )?;
+        encoder.FlushAsync()?.get()?;
+
+        let size = stream.Size()? as u32;
+        let reader = DataReader::CreateDataReader(&stream.GetInputStreamAt(0)?)?;
+        reader.LoadAsync(size)?.get()?;
+        let mut png = vec![0u8; size as usize];
+        reader.ReadBytes(&mut png)?;
+        Ok(png)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::ScreenText;
+    use anyhow::{anyhow, bail, Context, Result};
+    use std::path::Path;
+    use std::process::Command;
+
+    /// Screenshot to `path` with whatever tool the platform has
+    fn capture_screen(path: &Path) -> Result<()> {
+        let path_str = path.to_string_lossy().to_string();
+
+        #[cfg(target_os = "macos")]
+        let attempts: Vec<(&str, Vec<String>)> = vec![("screencapture", vec!["-x".into(), path_str])];
+
+        #[cfg(not(target_os = "macos"))]
+        let attempts: Vec<(&str, Vec<String>)> = vec![
+            ("grim", vec![path_str.clone()]),
+            ("gnome-screenshot", vec!["-f".into(), path_str.clone()]),
+            ("spectacle", vec!["-b".into(), "-n".into(), "-o".into(), path_str.clone()]),
+            ("import", vec!["-window".into(), "root".into(), path_str]),
+        ];
+
+        for (program, args) in attempts {
+            if let Ok(status) = Command::new(program).args(&args).status() {
+                if status.success() && path.exists() {
+                    return Ok(());
+                }
+            }
+        }
+
+        bail!("No screenshot tool available")
+    }
+
+    pub fn read_screen() -> Result<ScreenText> {
+        let path = std::env::temp_dir().join(format!("astral_ocr_{}.png", std::process::id()));
+        capture_screen(&path)?;
+
+        // `tesseract <image> stdout` prints the recognized text
+        let output = Command::new("tesseract")
+            .arg(&path)
+            .arg("stdout")
+            .output()
+            .context("tesseract is not installed");
+        let _ = std::fs::remove_file(&path);
+        let output = output?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "tesseract failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(ScreenText::from_lines(text.lines().map(str::to_string).collect(), "tesseract"))
+    }
+
+    pub fn capture_png() -> Result<Vec<u8>> {
+        let path = std::env::temp_dir().join(format!("astral_screenshot_{}.png", std::process::id()));
+        capture_screen(&path)?;
+        let png = std::fs::read(&path).context("Couldn't read the screenshot");
+        let _ = std::fs::remove_file(&path);
+        png
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn read_screen_text() -> Result<ScreenText, String> {
+    tokio::task::spawn_blocking(read_screen)
+        .await
+        .map_err(|e| e.to_string())?
+}
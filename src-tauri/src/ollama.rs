@@ -0,0 +1,196 @@
+// Ollama Module
+// Manages the models installed in the Ollama server ASTRAL talks to, so the
+// settings page can list, download and remove them without the `ollama` CLI.
+// Downloads stream Ollama's progress lines to the UI as `ollama-pull-progress`
+// events; the command itself returns once the model is installed.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use crate::llm_provider::LLMConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+pub const PULL_PROGRESS_EVENT: &str = "ollama-pull-progress";
+
+/// Models can be several GB
+const PULL_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// The configured server, or Ollama's default
+pub fn base_url(config: &LLMConfig) -> String {
+    config
+        .ollama_url
+        .clone()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| "http://localhost:11434".to_string())
+}
+
+#[derive(Deserialize)]
+struct Tags {
+    #[serde(default)]
+    models: Vec<TagEntry>,
+}
+
+#[derive(Deserialize)]
+struct TagEntry {
+    name: String,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    modified_at: String,
+    #[serde(default)]
+    details: TagDetails,
+}
+
+#[derive(Default, Deserialize)]
+struct TagDetails {
+    #[serde(default)]
+    parameter_size: String,
+    #[serde(default)]
+    quantization_level: String,
+}
+
+/// An installed model, as the settings page shows it
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaModel {
+    /// "mistral:latest"
+    pub name: String,
+    /// Bytes on disk
+    pub size: u64,
+    pub modified_at: String,
+    /// "7.2B"
+    pub parameter_size: String,
+    /// "Q4_0"
+    pub quantization: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PullProgress {
+    pub model: String,
+    /// Ollama's own words: "pulling manifest", "verifying sha256 digest", "success"
+    pub status: String,
+    /// Bytes of the layer being downloaded, when there is one
+    pub completed: Option<u64>,
+    pub total: Option<u64>,
+}
+
+/// One line of /api/pull's streamed reply
+#[derive(Deserialize)]
+struct PullLine {
+    #[serde(default)]
+    status: String,
+    completed: Option<u64>,
+    total: Option<u64>,
+    error: Option<String>,
+}
+
+/// The models installed on the server at `url`
+pub async fn installed_models(url: &str, timeout: Duration) -> AstralResult<Vec<OllamaModel>> {
+    let response = crate::privacy::send(crate::proxy::client().get(format!("{}/api/tags", url)).timeout(timeout))
+        .await
+        .map_err(|e| AstralError::from(e.context(format!("Ollama isn't answering at {}", url))))?;
+    let status = response.status();
+    if !status.is_success() {
+        let error = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("Ollama", status, &error));
+    }
+    let tags: Tags = response.json().await?;
+    Ok(tags
+        .models
+        .into_iter()
+        .map(|m| OllamaModel {
+            name: m.name,
+            size: m.size,
+            modified_at: m.modified_at,
+            parameter_size: m.details.parameter_size,
+            quantization: m.details.quantization_level,
+        })
+        .collect())
+}
+
+/// Read a finished line of the pull stream; a line with `error` fails the pull
+fn handle_pull_line(app: &AppHandle, model: &str, line: &[u8]) -> AstralResult<()> {
+    let Ok(line) = serde_json::from_slice::<PullLine>(line) else {
+        return Ok(());
+    };
+    if let Some(error) = line.error {
+        return Err(AstralError::new(ErrorCode::ProviderError, format!("Ollama couldn't pull {}: {}", model, error)));
+    }
+    let progress = PullProgress { model: model.to_string(), status: line.status, completed: line.completed, total: line.total };
+    if let Err(e) = app.emit(PULL_PROGRESS_EVENT, &progress) {
+        warn!("Failed to emit pull progress: {}", e);
+    }
+    Ok(())
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn list_ollama_models(app: AppHandle) -> AstralResult<Vec<OllamaModel>> {
+    let settings = crate::settings::load_settings(app).await?;
+    installed_models(&base_url(&settings.llm), Duration::from_secs(5)).await
+}
+
+/// Download an Ollama model, reporting progress as it goes; returns once it's installed
+#[tauri::command]
+pub async fn pull_ollama_model(app: AppHandle, model: String) -> AstralResult<()> {
+    let model = model.trim().to_string();
+    if model.is_empty() {
+        return Err(AstralError::invalid_input("Name the model to download, e.g. llama3.2"));
+    }
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    let url = base_url(&settings.llm);
+    info!("Pulling Ollama model {}", model);
+
+    let mut response = crate::privacy::send(
+        crate::proxy::client()
+            .post(format!("{}/api/pull", url))
+            .json(&json!({ "model": model, "stream": true }))
+            .timeout(PULL_TIMEOUT),
+    )
+    .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("Ollama", status, &error));
+    }
+
+    // One JSON object per line, and a chunk can end partway through one
+    let mut pending: Vec<u8> = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        pending.extend_from_slice(&chunk);
+        while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = pending.drain(..=end).collect();
+            handle_pull_line(&app, &model, &line)?;
+        }
+    }
+    if !pending.is_empty() {
+        handle_pull_line(&app, &model, &pending)?;
+    }
+
+    info!("Pulled Ollama model {}", model);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_ollama_model(app: AppHandle, model: String) -> AstralResult<()> {
+    let settings = crate::settings::load_settings(app).await?;
+    let url = base_url(&settings.llm);
+    info!("Deleting Ollama model {}", model);
+
+    let response = crate::privacy::send(
+        crate::proxy::client()
+            .delete(format!("{}/api/delete", url))
+            .json(&json!({ "model": model }))
+            .timeout(Duration::from_secs(30)),
+    )
+    .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status("Ollama", status, &error));
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+// Permissions Module
+// Capabilities that can do real damage when a command is misheard or a
+// routine is misconfigured: running shell commands, deleting files, typing
+// into other apps, shutting down / restarting, changing email, closing apps
+// and running third-party skills. Each is off until granted in the
+// `permissions` settings section. The automation executor, the intent
+// handlers and the Tauri commands behind them ask `check` before acting; a
+// refusal names the setting that would allow it.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// `SystemCommand` routine actions
+    SystemCommand,
+    /// Emptying the recycle bin and cleaning up temporary files
+    FileDeletion,
+    /// Typing text into the focused app (dictation)
+    KeyboardInjection,
+    /// Lock, sleep, shutdown and restart
+    Power,
+    /// Marking email as read; reading it never changes anything
+    EmailChanges,
+    /// Closing apps and ending processes, which loses their unsaved work
+    CloseApps,
+    /// Running installed skills, which aren't sandboxed
+    Skills,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 7] = [
+        Capability::SystemCommand,
+        Capability::FileDeletion,
+        Capability::KeyboardInjection,
+        Capability::Power,
+        Capability::EmailChanges,
+        Capability::CloseApps,
+        Capability::Skills,
+    ];
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Capability::SystemCommand => "Running system commands",
+            Capability::FileDeletion => "Deleting files",
+            Capability::KeyboardInjection => "Typing into other apps",
+            Capability::Power => "Power actions",
+            Capability::EmailChanges => "Changing email",
+            Capability::CloseApps => "Closing apps",
+            Capability::Skills => "Running skills",
+        }
+    }
+}
+
+/// The `permissions` settings section; everything starts off
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermissionSettings {
+    pub system_command: bool,
+    pub file_deletion: bool,
+    pub keyboard_injection: bool,
+    pub power: bool,
+    pub email_changes: bool,
+    pub close_apps: bool,
+    pub skills: bool,
+}
+
+impl PermissionSettings {
+    pub fn allows(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::SystemCommand => self.system_command,
+            Capability::FileDeletion => self.file_deletion,
+            Capability::KeyboardInjection => self.keyboard_injection,
+            Capability::Power => self.power,
+            Capability::EmailChanges => self.email_changes,
+            Capability::CloseApps => self.close_apps,
+            Capability::Skills => self.skills,
+        }
+    }
+}
+
+static GRANTED: Lazy<RwLock<PermissionSettings>> = Lazy::new(|| RwLock::new(PermissionSettings::default()));
+
+/// Use `permissions` from now on; called when settings load or change
+pub fn set(permissions: PermissionSettings) {
+    let mut granted = GRANTED.write().unwrap_or_else(|e| e.into_inner());
+    if *granted != permissions {
+        let allowed: Vec<_> = Capability::ALL.iter().filter(|c| permissions.allows(**c)).collect();
+        info!("Granted capabilities: {:?}", allowed);
+        *granted = permissions;
+    }
+}
+
+pub fn is_granted(capability: Capability) -> bool {
+    GRANTED.read().map(|g| g.allows(capability)).unwrap_or(false)
+}
+
+/// Error out unless `capability` has been granted
+pub fn check(capability: Capability) -> AstralResult<()> {
+    if is_granted(capability) {
+        return Ok(());
+    }
+    Err(AstralError::new(
+        ErrorCode::PermissionDenied,
+        format!("{} isn't allowed. You can turn it on in Settings under Permissions.", capability.describe()),
+    ))
+}
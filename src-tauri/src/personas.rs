@@ -0,0 +1,241 @@
+// Personas Module
+// Characters ASTRAL can speak as: each bundles a system prompt, a preferred
+// model and temperature, and a voice (ElevenLabs, and the browser voice for
+// when ElevenLabs is off). The active persona is laid over the `llm` and
+// `tts` settings when the engines are configured, so switching back to no
+// persona restores what was there; a blank field keeps the configured value.
+// Stored in the `personas` settings section, and shareable as JSON files.
+
+use crate::elevenlabs_tts::ElevenLabsConfig;
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use crate::llm_provider::LLMConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+pub const PERSONA_CHANGED_EVENT: &str = "persona-changed";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Persona {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Replaces the system prompt; takes the same `{{name}}` placeholders
+    pub system_prompt: String,
+    pub model: String,
+    pub temperature: Option<f32>,
+    /// ElevenLabs voice
+    pub voice_id: String,
+    /// Name of the browser or OS voice used when ElevenLabs is off
+    pub browser_voice: String,
+}
+
+impl Persona {
+    /// Lay this persona over the configured engines
+    pub fn apply(&self, llm: &mut LLMConfig, tts: &mut ElevenLabsConfig) {
+        if !self.system_prompt.trim().is_empty() {
+            llm.system_prompt = self.system_prompt.clone();
+        }
+        if !self.model.trim().is_empty() {
+            llm.model = self.model.trim().to_string();
+        }
+        if let Some(temperature) = self.temperature {
+            llm.temperature = temperature;
+        }
+        if !self.voice_id.trim().is_empty() {
+            tts.voice_id = self.voice_id.trim().to_string();
+            // A persona's voice is the same voice in every language
+            tts.voices_by_language.clear();
+        }
+    }
+
+    fn tidy(&mut self) -> Result<(), String> {
+        self.name = self.name.trim().to_string();
+        self.model = self.model.trim().to_string();
+        self.voice_id = self.voice_id.trim().to_string();
+        self.browser_voice = self.browser_voice.trim().to_string();
+        if self.id.trim().is_empty() {
+            self.id = crate::profiles::slug(&self.name);
+        }
+        if self.id.is_empty() {
+            return Err("Every persona needs a name".to_string());
+        }
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                return Err(format!("{}'s temperature must be between 0 and 2", self.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `personas` settings section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonaSettings {
+    /// Id of the persona in use; empty for none
+    pub active: String,
+    pub list: Vec<Persona>,
+}
+
+impl PersonaSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        for persona in &mut self.list {
+            persona.tidy()?;
+            if !ids.insert(persona.id.clone()) {
+                return Err(format!("More than one persona is called {}", persona.name));
+            }
+        }
+        if !self.active.is_empty() && !ids.contains(&self.active) {
+            self.active.clear();
+        }
+        Ok(())
+    }
+
+    pub fn active_persona(&self) -> Option<&Persona> {
+        self.list.iter().find(|p| p.id == self.active)
+    }
+}
+
+fn not_found(id: &str) -> AstralError {
+    AstralError::new(ErrorCode::NotFound, format!("There's no persona called {}", id))
+}
+
+fn default_export_path(name: &str) -> AstralResult<PathBuf> {
+    let mut path = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| AstralError::new(ErrorCode::NotFound, "No documents directory on this system"))?;
+    path.push(format!("astral-{}.json", name));
+    Ok(path)
+}
+
+/// Change the personas section and save it
+async fn modify<T>(app: &AppHandle, change: impl FnOnce(&mut PersonaSettings) -> AstralResult<T>) -> AstralResult<T> {
+    crate::settings::modify_settings(app, |settings| change(&mut settings.personas)).await
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_personas(app: AppHandle) -> AstralResult<PersonaSettings> {
+    Ok(crate::settings::load_settings(app).await?.personas)
+}
+
+/// Add a persona; its id comes from the name when not given
+#[tauri::command]
+pub async fn create_persona(app: AppHandle, mut persona: Persona) -> AstralResult<Persona> {
+    persona.tidy().map_err(AstralError::invalid_input)?;
+    modify(&app, |personas| {
+        if personas.list.iter().any(|p| p.id == persona.id) {
+            return Err(AstralError::invalid_input(format!("A persona called {} already exists", persona.name)));
+        }
+        personas.list.push(persona.clone());
+        Ok(())
+    })
+    .await?;
+    info!("Created persona {}", persona.name);
+    Ok(persona)
+}
+
+#[tauri::command]
+pub async fn update_persona(app: AppHandle, mut persona: Persona) -> AstralResult<Persona> {
+    persona.tidy().map_err(AstralError::invalid_input)?;
+    modify(&app, |personas| {
+        let slot = personas.list.iter_mut().find(|p| p.id == persona.id).ok_or_else(|| not_found(&persona.id))?;
+        *slot = persona.clone();
+        Ok(())
+    })
+    .await?;
+    Ok(persona)
+}
+
+#[tauri::command]
+pub async fn delete_persona(app: AppHandle, id: String) -> AstralResult<()> {
+    modify(&app, |personas| {
+        let before = personas.list.len();
+        personas.list.retain(|p| p.id != id);
+        if personas.list.len() == before {
+            return Err(not_found(&id));
+        }
+        if personas.active == id {
+            personas.active.clear();
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Speak as the persona `id`, or as plain ASTRAL when `id` is `None`
+#[tauri::command]
+pub async fn switch_persona(app: AppHandle, id: Option<String>) -> AstralResult<Option<Persona>> {
+    let target = modify(&app, |personas| {
+        let target = match id.as_deref().filter(|id| !id.is_empty()) {
+            Some(id) => Some(personas.list.iter().find(|p| p.id == id).cloned().ok_or_else(|| not_found(id))?),
+            None => None,
+        };
+        personas.active = target.as_ref().map(|p| p.id.clone()).unwrap_or_default();
+        Ok(target)
+    })
+    .await?;
+
+    info!("Switched to persona {}", target.as_ref().map_or("(none)", |p| p.name.as_str()));
+    if let Err(e) = app.emit(PERSONA_CHANGED_EVENT, &target) {
+        warn!("Failed to emit persona change: {}", e);
+    }
+    Ok(target)
+}
+
+/// Write the personas in `ids` (all of them when empty) to `path`, a file in
+/// Documents by default, and return where it went
+#[tauri::command]
+pub async fn export_personas(app: AppHandle, ids: Option<Vec<String>>, path: Option<String>) -> AstralResult<String> {
+    let settings = crate::settings::load_settings(app).await?;
+    let ids = ids.unwrap_or_default();
+    let chosen: Vec<Persona> = settings
+        .personas
+        .list
+        .into_iter()
+        .filter(|p| ids.is_empty() || ids.contains(&p.id))
+        .collect();
+    if chosen.is_empty() {
+        return Err(AstralError::invalid_input("There are no personas to export"));
+    }
+
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path.trim()),
+        None if chosen.len() == 1 => default_export_path(&format!("persona-{}", chosen[0].id))?,
+        None => default_export_path("personas")?,
+    };
+    let content = serde_json::to_string_pretty(&chosen).map_err(|e| AstralError::from(e.to_string()))?;
+    tokio::fs::write(&path, content).await?;
+    info!("Exported {} persona(s) to {:?}", chosen.len(), path);
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Add the personas in a file written by `export_personas` (one persona or a
+/// list), replacing any with the same id
+#[tauri::command]
+pub async fn import_personas(app: AppHandle, path: String) -> AstralResult<Vec<Persona>> {
+    let content = tokio::fs::read_to_string(path.trim()).await?;
+    let mut imported: Vec<Persona> = serde_json::from_str::<Vec<Persona>>(&content)
+        .or_else(|_| serde_json::from_str::<Persona>(&content).map(|p| vec![p]))
+        .map_err(|e| AstralError::invalid_input(format!("That isn't a persona file: {}", e)))?;
+    for persona in &mut imported {
+        persona.tidy().map_err(AstralError::invalid_input)?;
+    }
+
+    modify(&app, |personas| {
+        for persona in &imported {
+            personas.list.retain(|p| p.id != persona.id);
+            personas.list.push(persona.clone());
+        }
+        Ok(())
+    })
+    .await?;
+    info!("Imported {} persona(s)", imported.len());
+    Ok(imported)
+}
@@ -0,0 +1,770 @@
+// Power Module
+// Sleep, shutdown, restart and lock, either immediately or after a delay
+// ("sleep in 10 minutes"). Only one delayed action is pending at a time.
+// Also reports battery state and wear, and switches power plans.
+
+use anyhow::Result;
+use tracing::info;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerAction {
+    Lock,
+    Sleep,
+    Shutdown,
+    Restart,
+}
+
+impl PowerAction {
+    /// Parse an automation/command action name ("sleep", "shutdown", ...)
+    pub fn parse(action: &str) -> Option<Self> {
+        match action.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+            "lock" => Some(Self::Lock),
+            "sleep" | "suspend" => Some(Self::Sleep),
+            "shutdown" | "shut_down" | "power_off" | "poweroff" => Some(Self::Shutdown),
+            "restart" | "reboot" => Some(Self::Restart),
+            _ => None,
+        }
+    }
+
+    /// Actions that lose unsaved work and need a spoken "yes" first
+    pub fn needs_confirmation(self) -> bool {
+        matches!(self, Self::Shutdown | Self::Restart)
+    }
+
+    pub fn verb(self) -> &'static str {
+        match self {
+            Self::Lock => "lock the computer",
+            Self::Sleep => "put the computer to sleep",
+            Self::Shutdown => "shut down",
+            Self::Restart => "restart",
+        }
+    }
+}
+
+/// A spoken power request: what to do and how long to wait first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerCommand {
+    pub action: PowerAction,
+    pub delay: Option<Duration>,
+}
+
+impl PowerCommand {
+    /// Recognize "lock my computer", "shut down", "sleep in 10 minutes", ...
+    /// Only instructions count: the phrase has to be the whole command, or be
+    /// followed by "now" or a delay, so "when did I last shut down" and "I
+    /// want to sleep in tomorrow" aren't taken for one.
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = crate::intents::imperative(command);
+        let lower = lower.as_str();
+
+        const LOCK: &[&str] = &["lock", "lock my computer", "lock the computer", "lock my pc", "lock the pc", "lock the screen", "lock my screen"];
+        const SLEEP: &[&str] = &["sleep", "go to sleep", "put the computer to sleep", "put my computer to sleep", "enter sleep mode", "sleep the computer"];
+        const SHUTDOWN: &[&str] = &[
+            "shut down", "shutdown", "shut down the computer", "shut down my computer", "shut down the pc", "shut down my pc",
+            "turn off the computer", "turn off my computer", "turn off the pc", "turn off my pc", "power off",
+        ];
+        const RESTART: &[&str] = &[
+            "restart", "reboot", "restart the computer", "restart my computer", "restart the pc", "restart my pc",
+            "reboot the computer", "reboot my computer", "reboot the pc", "reboot my pc",
+        ];
+
+        let is = |phrases: &[&str]| {
+            phrases.iter().any(|p| {
+                lower.strip_prefix(p).is_some_and(|rest| {
+                    rest.is_empty() || rest == " now" || (rest.starts_with(" in ") && parse_delay(rest).is_some())
+                })
+            })
+        };
+        let action = if is(LOCK) {
+            PowerAction::Lock
+        } else if is(RESTART) {
+            PowerAction::Restart
+        } else if is(SHUTDOWN) {
+            PowerAction::Shutdown
+        } else if is(SLEEP) {
+            PowerAction::Sleep
+        } else {
+            return None;
+        };
+
+        Some(Self { action, delay: parse_delay(lower) })
+    }
+
+    /// Short confirmation to speak back
+    pub fn describe(self) -> String {
+        match self.delay {
+            Some(delay) => format!("Okay, I'll {} in {}", self.action.verb(), format_delay(delay)),
+            None => match self.action {
+                PowerAction::Lock => "Locking the computer".to_string(),
+                PowerAction::Sleep => "Going to sleep".to_string(),
+                PowerAction::Shutdown => "Shutting down".to_string(),
+                PowerAction::Restart => "Restarting".to_string(),
+            },
+        }
+    }
+}
+
+/// "in 10 minutes", "in an hour", "in 30 seconds"
+fn parse_delay(text: &str) -> Option<Duration> {
+    let (_, after) = text.split_once(" in ")?;
+    let mut words = after.split_whitespace();
+    let amount = match words.next()? {
+        "a" | "an" | "one" => 1,
+        "two" => 2,
+        "five" => 5,
+        "ten" => 10,
+        "fifteen" => 15,
+        "twenty" => 20,
+        "thirty" => 30,
+        n => n.parse::<u64>().ok()?,
+    };
+
+    let unit = words.next()?.trim_end_matches('s');
+    let seconds = match unit {
+        "second" | "sec" => amount,
+        "minute" | "min" => amount.checked_mul(60)?,
+        "hour" | "hr" => amount.checked_mul(3600)?,
+        _ => return None,
+    };
+
+    Some(Duration::from_secs(seconds))
+}
+
+fn format_delay(delay: Duration) -> String {
+    let secs = delay.as_secs();
+    let (n, unit) = if secs % 3600 == 0 && secs >= 3600 {
+        (secs / 3600, "hour")
+    } else if secs % 60 == 0 && secs >= 60 {
+        (secs / 60, "minute")
+    } else {
+        (secs, "second")
+    };
+
+    format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" })
+}
+
+/// Delayed action waiting to fire
+struct Scheduled {
+    /// Tells the timer whether it's still the current one when it fires
+    id: u64,
+    action: PowerAction,
+    handle: tauri::async_runtime::JoinHandle<()>,
+}
+
+static SCHEDULED: Lazy<Mutex<Option<Scheduled>>> = Lazy::new(|| Mutex::new(None));
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Run a power command now, or schedule it if it has a delay (replacing any
+/// previously scheduled one)
+pub fn run(command: PowerCommand) -> Result<()> {
+    let Some(delay) = command.delay else {
+        return perform(command.action);
+    };
+
+    let action = command.action;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    info!("Scheduling {:?} in {:?}", action, delay);
+    // Held until the handle is stored, so even a timer that fires at once
+    // finds itself scheduled
+    let mut scheduled = SCHEDULED.lock().unwrap_or_else(|e| e.into_inner());
+    let handle = tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        {
+            let mut scheduled = SCHEDULED.lock().unwrap_or_else(|e| e.into_inner());
+            // Replaced or cancelled while the abort was on its way
+            if scheduled.as_ref().map(|s| s.id) != Some(id) {
+                return;
+            }
+            *scheduled = None;
+        }
+        if let Err(e) = perform(action) {
+            tracing::warn!("Scheduled {:?} failed: {}", action, e);
+        }
+    });
+
+    if let Some(previous) = scheduled.replace(Scheduled { id, action, handle }) {
+        previous.handle.abort();
+    }
+    Ok(())
+}
+
+/// Cancel a delayed power action, returning what was cancelled
+pub fn cancel_scheduled() -> Option<PowerAction> {
+    let Scheduled { action, handle, .. } = SCHEDULED.lock().unwrap_or_else(|e| e.into_inner()).take()?;
+    handle.abort();
+    info!("Cancelled scheduled {:?}", action);
+    Some(action)
+}
+
+/// Whether the command is asking to call off a pending power action
+pub fn is_cancel_command(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    (lower.contains("cancel") || lower.contains("abort") || lower.contains("don't"))
+        && ["shutdown", "shut down", "sleep", "restart", "reboot", "lock"].iter().any(|w| lower.contains(w))
+}
+
+/// Carry out a power action right away
+pub fn perform(action: PowerAction) -> Result<()> {
+    info!("Performing power action: {:?}", action);
+    platform::perform(action)
+}
+
+fn run_checked(program: &str, args: &[&str]) -> Result<()> {
+    let status = Command::new(program).args(args).status()?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program, status);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{run_checked, BatteryHealth, BatteryStatus, PowerAction, PowerPlan, PowerPlanKind};
+    use anyhow::{anyhow, Result};
+    use std::process::Command;
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SetSuspendState, SYSTEM_POWER_STATUS};
+    use windows::Win32::System::Shutdown::LockWorkStation;
+
+    // Built-in scheme GUIDs; these are the same on every Windows install
+    const POWER_SAVER: &str = "a1841308-3334-4813-b798-fda12bdd6ccf";
+    const BALANCED: &str = "381b4222-f694-41f0-9685-ff5bb260df2e";
+    const HIGH_PERFORMANCE: &str = "8c5e7fda-e8bf-4a96-9a85-a6e23a8c635c";
+
+    pub fn battery_status() -> Result<BatteryStatus> {
+        let mut status = SYSTEM_POWER_STATUS::default();
+        unsafe { GetSystemPowerStatus(&mut status)? };
+
+        // BatteryFlag 128 = no system battery, 255 = unknown
+        let present = status.BatteryFlag != 128 && status.BatteryFlag != 255;
+        Ok(BatteryStatus {
+            present,
+            percent: (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent),
+            charging: status.BatteryFlag & 8 != 0,
+            plugged_in: status.ACLineStatus == 1,
+            seconds_remaining: (status.BatteryLifeTime != u32::MAX).then_some(status.BatteryLifeTime as u64),
+        })
+    }
+
+    // The battery WMI classes in root/wmi report mWh and mW
+    const BATTERY_HEALTH_SCRIPT: &str = "$ns = 'root/wmi'; ConvertTo-Json -Compress @{ \
+        Design = (Get-CimInstance -Namespace $ns BatteryStaticData -ErrorAction SilentlyContinue | Select-Object -First 1).DesignedCapacity; \
+        Full = (Get-CimInstance -Namespace $ns BatteryFullChargedCapacity -ErrorAction SilentlyContinue | Select-Object -First 1).FullChargedCapacity; \
+        Discharge = (Get-CimInstance -Namespace $ns BatteryStatus -ErrorAction SilentlyContinue | Select-Object -First 1).DischargeRate; \
+        Cycles = (Get-CimInstance -Namespace $ns BatteryCycleCount -ErrorAction SilentlyContinue | Select-Object -First 1).CycleCount }";
+
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct BatteryWmi {
+        design: Option<u64>,
+        full: Option<u64>,
+        discharge: Option<u64>,
+        cycles: Option<u32>,
+    }
+
+    pub fn battery_health() -> Result<BatteryHealth> {
+        let output = Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", BATTERY_HEALTH_SCRIPT])
+            .output()?;
+        let wmi: BatteryWmi = serde_json::from_slice(&output.stdout)?;
+
+        Ok(BatteryHealth {
+            design_capacity_mwh: wmi.design.filter(|c| *c > 0),
+            full_charge_capacity_mwh: wmi.full.filter(|c| *c > 0),
+            discharge_rate_mw: wmi.discharge.filter(|r| *r > 0),
+            cycle_count: wmi.cycles.filter(|c| *c > 0),
+            ..Default::default()
+        })
+    }
+
+    /// Parse `powercfg /list`: "Power Scheme GUID: <guid>  (<name>) *"
+    pub fn list_power_plans() -> Result<Vec<PowerPlan>> {
+        let output = Command::new("powercfg").arg("/list").output()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+
+        Ok(text
+            .lines()
+            .filter_map(|line| {
+                let (_, rest) = line.split_once(": ")?;
+                let id = rest.split_whitespace().next()?.to_string();
+                let name = rest.split_once('(')?.1.split_once(')')?.0.to_string();
+                Some(PowerPlan {
+                    id,
+                    name,
+                    active: line.trim_end().ends_with('*'),
+                })
+            })
+            .collect())
+    }
+
+    pub fn set_power_plan(kind: PowerPlanKind) -> Result<PowerPlan> {
+        let guid = match kind {
+            PowerPlanKind::PowerSaver => POWER_SAVER,
+            PowerPlanKind::Balanced => BALANCED,
+            PowerPlanKind::HighPerformance => HIGH_PERFORMANCE,
+        };
+
+        // Some OEM images hide High Performance; fall back to matching by name
+        let plans = list_power_plans()?;
+        let plan = plans
+            .iter()
+            .find(|p| p.id.eq_ignore_ascii_case(guid))
+            .or_else(|| plans.iter().find(|p| p.name.eq_ignore_ascii_case(kind.label())))
+            .ok_or_else(|| anyhow!("The {} power plan isn't available", kind.label()))?;
+
+        // Plans locked down by policy need admin rights to switch
+        crate::elevation::run_privileged("powercfg", &["/setactive", &plan.id])?;
+        Ok(PowerPlan { active: true, ..plan.clone() })
+    }
+
+    pub fn perform(action: PowerAction) -> Result<()> {
+        match action {
+            PowerAction::Lock => unsafe { Ok(LockWorkStation()?) },
+            PowerAction::Sleep => unsafe {
+                // hibernate = false, force = false, wake events allowed
+                if !SetSuspendState(false, false, false).as_bool() {
+                    anyhow::bail!("The system refused to sleep");
+                }
+                Ok(())
+            },
+            PowerAction::Shutdown => run_checked("shutdown", &["/s", "/t", "0"]),
+            PowerAction::Restart => run_checked("shutdown", &["/r", "/t", "0"]),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{run_checked, BatteryHealth, BatteryStatus, PowerAction, PowerPlan, PowerPlanKind};
+    use anyhow::{bail, Result};
+    use std::process::Command;
+
+    /// Parse `pmset -g batt`:
+    /// "Now drawing from 'AC Power'\n -InternalBattery-0 (id=..)\t87%; charging; 1:02 remaining ..."
+    pub fn battery_status() -> Result<BatteryStatus> {
+        let output = Command::new("pmset").args(["-g", "batt"]).output()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let plugged_in = text.contains("AC Power");
+
+        let Some(line) = text.lines().find(|l| l.contains("InternalBattery")) else {
+            return Ok(BatteryStatus { plugged_in, ..Default::default() });
+        };
+        let fields: Vec<&str> = line.split('\t').nth(1).unwrap_or("").split(';').map(str::trim).collect();
+
+        let percent = fields.first().and_then(|p| p.trim_end_matches('%').parse().ok());
+        let charging = fields.get(1).is_some_and(|s| *s == "charging");
+        let seconds_remaining = fields.get(2).and_then(|s| {
+            let (h, m) = s.split_whitespace().next()?.split_once(':')?;
+            Some(h.parse::<u64>().ok()? * 3600 + m.parse::<u64>().ok()? * 60)
+        });
+
+        Ok(BatteryStatus {
+            present: true,
+            percent,
+            charging,
+            plugged_in,
+            seconds_remaining,
+        })
+    }
+
+    /// Parse `ioreg -rn AppleSmartBattery`: lines like `"DesignCapacity" = 5103`.
+    /// Capacities are in mAh and the current is signed (negative while discharging).
+    pub fn battery_health() -> Result<BatteryHealth> {
+        let output = Command::new("ioreg").args(["-rn", "AppleSmartBattery"]).output()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let field = |key: &str| -> Option<i64> {
+            let needle = format!("\"{}\" = ", key);
+            text.lines()
+                .find_map(|l| l.trim().strip_prefix(needle.as_str()).and_then(|v| v.trim().parse().ok()))
+        };
+        if field("DesignCapacity").is_none() {
+            bail!("No battery found");
+        }
+
+        let millivolts = field("Voltage").unwrap_or(0);
+        let to_mwh = |mah: i64| (mah * millivolts / 1000) as u64;
+        // On Apple Silicon "MaxCapacity" is a percentage; the raw value is in mAh
+        let full = field("AppleRawMaxCapacity").or_else(|| field("MaxCapacity"));
+        let amperage = field("InstantAmperage").or_else(|| field("Amperage")).unwrap_or(0);
+
+        Ok(BatteryHealth {
+            design_capacity_mwh: field("DesignCapacity").map(to_mwh).filter(|c| *c > 0),
+            full_charge_capacity_mwh: full.map(to_mwh).filter(|c| *c > 0),
+            discharge_rate_mw: (amperage < 0).then(|| (-amperage * millivolts / 1000) as u64),
+            cycle_count: field("CycleCount").map(|c| c as u32),
+            ..Default::default()
+        })
+    }
+
+    pub fn list_power_plans() -> Result<Vec<PowerPlan>> {
+        bail!("Power plans aren't available on macOS")
+    }
+
+    pub fn set_power_plan(_kind: PowerPlanKind) -> Result<PowerPlan> {
+        bail!("Power plans aren't available on macOS")
+    }
+
+    pub fn perform(action: PowerAction) -> Result<()> {
+        match action {
+            PowerAction::Lock => run_checked("pmset", &["displaysleepnow"]),
+            PowerAction::Sleep => run_checked("pmset", &["sleepnow"]),
+            PowerAction::Shutdown => run_checked("osascript", &["-e", "tell app \"System Events\" to shut down"]),
+            PowerAction::Restart => run_checked("osascript", &["-e", "tell app \"System Events\" to restart"]),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::{run_checked, BatteryHealth, BatteryStatus, PowerAction, PowerPlan, PowerPlanKind};
+    use anyhow::{anyhow, Result};
+    use std::path::Path;
+    use std::process::Command;
+
+    fn read_sys(dir: &Path, file: &str) -> Option<String> {
+        std::fs::read_to_string(dir.join(file)).ok().map(|s| s.trim().to_string())
+    }
+
+    pub fn battery_status() -> Result<BatteryStatus> {
+        let supplies: Vec<_> = std::fs::read_dir("/sys/class/power_supply")?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .collect();
+
+        let plugged_in = supplies
+            .iter()
+            .filter(|dir| read_sys(dir, "type").as_deref() == Some("Mains"))
+            .any(|dir| read_sys(dir, "online").as_deref() == Some("1"));
+
+        let Some(battery) = supplies.iter().find(|dir| read_sys(dir, "type").as_deref() == Some("Battery")) else {
+            return Ok(BatteryStatus { plugged_in, ..Default::default() });
+        };
+
+        let status = read_sys(battery, "status").unwrap_or_default();
+        // energy_now / power_now is in µWh / µW
+        let seconds_remaining = match (read_sys(battery, "energy_now"), read_sys(battery, "power_now")) {
+            (Some(energy), Some(power)) if status == "Discharging" => {
+                let energy: f64 = energy.parse().unwrap_or(0.0);
+                let power: f64 = power.parse().unwrap_or(0.0);
+                (power > 0.0).then(|| (energy / power * 3600.0) as u64)
+            }
+            _ => None,
+        };
+
+        Ok(BatteryStatus {
+            present: true,
+            percent: read_sys(battery, "capacity").and_then(|c| c.parse().ok()),
+            charging: status == "Charging",
+            plugged_in: plugged_in || status == "Charging" || status == "Full",
+            seconds_remaining,
+        })
+    }
+
+    /// Batteries report either energy (µWh, µW) or charge (µAh, µA) plus voltage (µV)
+    pub fn battery_health() -> Result<BatteryHealth> {
+        let battery = std::fs::read_dir("/sys/class/power_supply")?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .find(|dir| read_sys(dir, "type").as_deref() == Some("Battery"))
+            .ok_or_else(|| anyhow!("No battery found"))?;
+        let number = |file: &str| read_sys(&battery, file).and_then(|v| v.parse::<u64>().ok()).filter(|v| *v > 0);
+
+        let microvolts = number("voltage_min_design").or_else(|| number("voltage_now"));
+        let energy = |energy_file: &str, charge_file: &str| {
+            number(energy_file)
+                .map(|uwh| uwh / 1000)
+                .or_else(|| Some(number(charge_file)? * microvolts? / 1_000_000_000))
+        };
+        let discharging = read_sys(&battery, "status").as_deref() == Some("Discharging");
+        let power = number("power_now")
+            .map(|uw| uw / 1000)
+            .or_else(|| Some(number("current_now")? * number("voltage_now")? / 1_000_000_000));
+
+        Ok(BatteryHealth {
+            design_capacity_mwh: energy("energy_full_design", "charge_full_design"),
+            full_charge_capacity_mwh: energy("energy_full", "charge_full"),
+            discharge_rate_mw: power.filter(|_| discharging),
+            cycle_count: number("cycle_count").map(|c| c as u32),
+            ..Default::default()
+        })
+    }
+
+    /// power-profiles-daemon: `powerprofilesctl list` marks the active profile with '*'
+    pub fn list_power_plans() -> Result<Vec<PowerPlan>> {
+        let output = Command::new("powerprofilesctl")
+            .arg("list")
+            .output()
+            .map_err(|_| anyhow!("power-profiles-daemon isn't installed"))?;
+        let text = String::from_utf8_lossy(&output.stdout);
+
+        Ok(text
+            .lines()
+            .filter(|line| line.trim_end().ends_with(':') && !line.starts_with("    "))
+            .map(|line| {
+                let active = line.trim_start().starts_with('*');
+                let id = line.trim().trim_start_matches('*').trim().trim_end_matches(':').to_string();
+                PowerPlan { name: id.clone(), id, active }
+            })
+            .collect())
+    }
+
+    pub fn set_power_plan(kind: PowerPlanKind) -> Result<PowerPlan> {
+        let id = match kind {
+            PowerPlanKind::PowerSaver => "power-saver",
+            PowerPlanKind::Balanced => "balanced",
+            PowerPlanKind::HighPerformance => "performance",
+        };
+
+        crate::elevation::run_privileged("powerprofilesctl", &["set", id])?;
+        Ok(PowerPlan { id: id.to_string(), name: id.to_string(), active: true })
+    }
+
+    pub fn perform(action: PowerAction) -> Result<()> {
+        match action {
+            PowerAction::Lock => run_checked("loginctl", &["lock-session"]),
+            PowerAction::Sleep => run_checked("systemctl", &["suspend"]),
+            PowerAction::Shutdown => run_checked("systemctl", &["poweroff"]),
+            PowerAction::Restart => run_checked("systemctl", &["reboot"]),
+        }
+    }
+}
+
+// ========== Battery & Power Plans ==========
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatteryStatus {
+    /// False on desktops without a battery
+    pub present: bool,
+    pub percent: Option<u8>,
+    pub charging: bool,
+    pub plugged_in: bool,
+    /// Estimated runtime left on battery, when the OS knows it
+    pub seconds_remaining: Option<u64>,
+}
+
+impl BatteryStatus {
+    /// Short sentence to speak back
+    pub fn describe(&self) -> String {
+        if !self.present {
+            return "This computer doesn't have a battery.".to_string();
+        }
+        let Some(percent) = self.percent else {
+            return "I can't read the battery level right now.".to_string();
+        };
+
+        let state = if self.charging {
+            "and charging"
+        } else if self.plugged_in {
+            "and plugged in"
+        } else {
+            "on battery"
+        };
+        let remaining = self
+            .seconds_remaining
+            .filter(|_| !self.plugged_in)
+            .map(|s| format!(", about {} left", format_delay(Duration::from_secs(s - s % 60).max(Duration::from_secs(60)))))
+            .unwrap_or_default();
+
+        format!("Battery is at {}% {}{}", percent, state, remaining)
+    }
+}
+
+/// Wear and drain of the battery; fields are None where the OS doesn't report them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatteryHealth {
+    pub design_capacity_mwh: Option<u64>,
+    pub full_charge_capacity_mwh: Option<u64>,
+    /// Full charge capacity as a percentage of design capacity
+    pub health_percent: Option<f32>,
+    /// Only while running on battery
+    pub discharge_rate_mw: Option<u64>,
+    pub cycle_count: Option<u32>,
+    pub seconds_remaining: Option<u64>,
+}
+
+impl BatteryHealth {
+    /// Short sentence to speak back
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        match (self.health_percent, self.full_charge_capacity_mwh, self.design_capacity_mwh) {
+            (Some(health), Some(full), Some(design)) => parts.push(format!(
+                "Battery health is {:.0}%, holding {:.1} of its original {:.1} watt-hours",
+                health,
+                full as f64 / 1000.0,
+                design as f64 / 1000.0
+            )),
+            _ => parts.push("I can't read the battery's wear level".to_string()),
+        }
+        if let Some(cycles) = self.cycle_count {
+            parts.push(format!("after {} charge cycles", cycles));
+        }
+        if let Some(rate) = self.discharge_rate_mw {
+            parts.push(format!("it's draining at {:.1} watts", rate as f64 / 1000.0));
+        }
+        if let Some(seconds) = self.seconds_remaining {
+            parts.push(format!("with about {} left", format_delay(Duration::from_secs(seconds - seconds % 60).max(Duration::from_secs(60)))));
+        }
+        format!("{}.", parts.join(", "))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerPlan {
+    /// Scheme GUID on Windows, profile name on Linux
+    pub id: String,
+    pub name: String,
+    pub active: bool,
+}
+
+/// The common plans people ask for by voice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerPlanKind {
+    PowerSaver,
+    Balanced,
+    HighPerformance,
+}
+
+impl PowerPlanKind {
+    pub fn parse(plan: &str) -> Option<Self> {
+        match plan.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+            "power_saver" | "saver" | "battery_saver" | "eco" => Some(Self::PowerSaver),
+            "balanced" | "default" | "normal" => Some(Self::Balanced),
+            "high_performance" | "performance" | "high" | "gaming" => Some(Self::HighPerformance),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::PowerSaver => "Power saver",
+            Self::Balanced => "Balanced",
+            Self::HighPerformance => "High performance",
+        }
+    }
+}
+
+/// Recognize "switch to high performance", "use the balanced power plan", ...
+pub fn parse_power_plan_command(command: &str) -> Option<PowerPlanKind> {
+    let lower = command.to_lowercase();
+    if !["power plan", "power mode", "switch to", "performance mode", "power saver", "battery saver"].iter().any(|p| lower.contains(p)) {
+        return None;
+    }
+
+    if lower.contains("high performance") || lower.contains("performance mode") || lower.contains("best performance") {
+        Some(PowerPlanKind::HighPerformance)
+    } else if lower.contains("power saver") || lower.contains("battery saver") || lower.contains("saver mode") {
+        Some(PowerPlanKind::PowerSaver)
+    } else if lower.contains("balanced") {
+        Some(PowerPlanKind::Balanced)
+    } else {
+        None
+    }
+}
+
+/// Whether the command asks about the battery ("how much battery do I have")
+pub fn is_battery_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    lower.contains("battery") && !lower.contains("saver")
+}
+
+/// Whether the command asks about battery wear ("how's my battery health")
+pub fn is_battery_health_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    lower.contains("battery")
+        && ["health", "capacity", "wear", "cycle", "degrad", "drain"].iter().any(|w| lower.contains(w))
+}
+
+pub fn battery_status() -> Result<BatteryStatus> {
+    platform::battery_status()
+}
+
+/// Capacity, drain and runtime left. Blocking.
+pub fn battery_health() -> Result<BatteryHealth> {
+    let status = platform::battery_status()?;
+    if !status.present {
+        anyhow::bail!("This computer doesn't have a battery");
+    }
+
+    let mut health = platform::battery_health()?;
+    health.health_percent = match (health.full_charge_capacity_mwh, health.design_capacity_mwh) {
+        (Some(full), Some(design)) if design > 0 => Some((full as f64 / design as f64 * 100.0).min(100.0) as f32),
+        _ => None,
+    };
+    if !status.plugged_in {
+        health.seconds_remaining = status.seconds_remaining;
+    }
+    Ok(health)
+}
+
+pub fn list_power_plans() -> Result<Vec<PowerPlan>> {
+    platform::list_power_plans()
+}
+
+/// Switch to one of the common plans, returning the plan now active
+pub fn set_power_plan(kind: PowerPlanKind) -> Result<PowerPlan> {
+    info!("Switching power plan to {:?}", kind);
+    platform::set_power_plan(kind)
+}
+
+// ========== Tauri Commands ==========
+
+/// Run a power action from the UI. The UI is responsible for asking first.
+#[tauri::command]
+pub async fn power_action(action: String, delay_seconds: Option<u64>) -> Result<String, String> {
+    crate::permissions::check(crate::permissions::Capability::Power)?;
+    let action = PowerAction::parse(&action).ok_or_else(|| format!("Unknown power action: {}", action))?;
+    let command = PowerCommand {
+        action,
+        delay: delay_seconds.filter(|s| *s > 0).map(Duration::from_secs),
+    };
+
+    tokio::task::spawn_blocking(move || run(command))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+    Ok(command.describe())
+}
+
+#[tauri::command]
+pub async fn cancel_power_action() -> Result<Option<PowerAction>, String> {
+    Ok(cancel_scheduled())
+}
+
+#[tauri::command]
+pub async fn get_battery_status() -> Result<BatteryStatus, String> {
+    tokio::task::spawn_blocking(battery_status)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_power_plans() -> Result<Vec<PowerPlan>, String> {
+    tokio::task::spawn_blocking(list_power_plans)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// `plan` is "power_saver", "balanced" or "high_performance"
+#[tauri::command]
+pub async fn set_power_plan_command(plan: String) -> Result<PowerPlan, String> {
+    let kind = PowerPlanKind::parse(&plan).ok_or_else(|| format!("Unknown power plan: {}", plan))?;
+    tokio::task::spawn_blocking(move || set_power_plan(kind))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_battery_health() -> Result<BatteryHealth, String> {
+    tokio::task::spawn_blocking(battery_health)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,90 @@
+// Privacy Module
+// Strict offline mode, the `general.privacy_mode` setting. While it's on,
+// ASTRAL only talks to services on this machine or the local network:
+// OpenAI, Claude, ElevenLabs, a Whisper server out on the internet, update
+// checks and the public IP lookup are all refused before a request leaves,
+// leaving Ollama, local Whisper and on-device speech. Outgoing requests go
+// through `send`, which is where the check happens. The tray shows when the
+// mode is on.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+/// Emitted when privacy mode is turned on or off, payload is the new `bool`
+pub const PRIVACY_MODE_EVENT: &str = "privacy-mode-changed";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Apply the setting; called when settings load or change
+pub fn set_enabled(app: &AppHandle, enabled: bool) {
+    if ENABLED.swap(enabled, Ordering::AcqRel) == enabled {
+        return;
+    }
+    info!("Privacy mode {}", if enabled { "on, cloud services blocked" } else { "off" });
+
+    crate::tray::set_privacy_indicator(app, enabled);
+    if let Err(e) = app.emit(PRIVACY_MODE_EVENT, enabled) {
+        warn!("Failed to emit privacy mode change: {}", e);
+    }
+}
+
+/// Loopback, private and link-local addresses
+pub(crate) fn is_local_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                // Unique local (fc00::/7) and link-local (fe80::/10)
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Local addresses plus `localhost` and mDNS `.local` names
+fn is_local_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+    if host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local") {
+        return true;
+    }
+    host.parse::<IpAddr>().is_ok_and(is_local_address)
+}
+
+/// Refuse `url` if privacy mode is on and it points off the local network
+pub fn check_url(url: &str) -> AstralResult<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_default();
+    if is_local_host(&host) {
+        return Ok(());
+    }
+
+    warn!("Privacy mode blocked a request to {}", host);
+    Err(AstralError::new(
+        ErrorCode::PrivacyMode,
+        format!("Privacy mode is on, so ASTRAL won't contact {}", host),
+    )
+    .with_detail("Use Ollama and a local Whisper server, or turn privacy mode off"))
+}
+
+/// Send `request` unless privacy mode blocks its destination. Transport
+/// errors come back as the underlying `reqwest::Error`, so callers can add
+/// context as before.
+pub async fn send(request: reqwest::RequestBuilder) -> anyhow::Result<reqwest::Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    check_url(request.url().as_str())?;
+    Ok(client.execute(request).await?)
+}
@@ -0,0 +1,372 @@
+// Processes Module
+// Lists running processes, ranks the heaviest ones ("what's slowing down my
+// PC?") and closes them by PID or name ("kill Chrome"). System-critical
+// processes and ASTRAL itself are never touched.
+
+use anyhow::{bail, Result};
+use tracing::info;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use sysinfo::{Pid, System};
+
+/// Processes that would take the session (or the machine) down with them
+const PROTECTED: &[&str] = &[
+    "system", "idle", "registry", "smss", "csrss", "wininit", "winlogon", "services", "lsass", "lsaiso", "svchost",
+    "dwm", "explorer", "fontdrvhost", "memcompression", "init", "systemd", "kthreadd", "launchd", "kernel_task",
+    "windowserver", "loginwindow", "xorg", "gnome-shell", "kwin_x11", "kwin_wayland",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// Percent of one core; can exceed 100 on multi-core machines
+    pub cpu_usage: f32,
+    /// Resident memory in bytes
+    pub memory: u64,
+    /// Bytes read and written since the previous refresh
+    pub disk_usage: u64,
+    pub exe: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessSort {
+    Cpu,
+    Memory,
+    Disk,
+}
+
+impl ProcessSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "cpu" | "processor" => Some(Self::Cpu),
+            "memory" | "ram" => Some(Self::Memory),
+            "disk" | "io" => Some(Self::Disk),
+            _ => None,
+        }
+    }
+
+    fn key(&self, process: &ProcessInfo) -> f64 {
+        match self {
+            Self::Cpu => process.cpu_usage as f64,
+            Self::Memory => process.memory as f64,
+            Self::Disk => process.disk_usage as f64,
+        }
+    }
+}
+
+/// Kept between calls so CPU usage is measured over the time since the last refresh
+static SYSTEM: Lazy<Mutex<System>> = Lazy::new(|| Mutex::new(System::new()));
+
+/// Process name without extension, lowercase ("Chrome.exe" -> "chrome")
+fn base_name(name: &str) -> String {
+    let lower = name.to_lowercase();
+    lower.strip_suffix(".exe").unwrap_or(&lower).to_string()
+}
+
+pub fn is_protected(name: &str) -> bool {
+    PROTECTED.contains(&base_name(name).as_str())
+}
+
+fn refreshed_system() -> std::sync::MutexGuard<'static, System> {
+    let mut system = SYSTEM.lock().unwrap_or_else(|e| e.into_inner());
+
+    // The first sample has no baseline, so take two
+    if system.processes().is_empty() {
+        system.refresh_processes();
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    }
+    system.refresh_processes();
+    system
+}
+
+/// All running processes, busiest first. Blocking.
+pub fn running_processes() -> Vec<ProcessInfo> {
+    let system = refreshed_system();
+    let mut processes: Vec<ProcessInfo> = system
+        .processes()
+        .values()
+        .map(|p| ProcessInfo {
+            pid: p.pid().as_u32(),
+            name: p.name().to_string(),
+            cpu_usage: p.cpu_usage(),
+            memory: p.memory(),
+            disk_usage: p.disk_usage().read_bytes + p.disk_usage().written_bytes,
+            exe: p.exe().map(|e| e.to_string_lossy().to_string()),
+        })
+        .collect();
+
+    processes.sort_by(|a, b| b.cpu_usage.partial_cmp(&a.cpu_usage).unwrap_or(std::cmp::Ordering::Equal));
+    processes
+}
+
+/// The `count` heaviest processes by `by`. Blocking.
+pub fn top_processes(by: ProcessSort, count: usize) -> Vec<ProcessInfo> {
+    let mut processes: Vec<ProcessInfo> = running_processes()
+        .into_iter()
+        // The idle process "uses" whatever CPU is free
+        .filter(|p| p.pid != 0 && base_name(&p.name) != "idle")
+        .collect();
+    processes.sort_by(|a, b| by.key(b).total_cmp(&by.key(a)));
+    processes.truncate(count);
+    processes
+}
+
+fn gigabytes(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+}
+
+/// A spoken answer naming the heaviest processes. For CPU (the "what's slowing
+/// down my PC?" case) the biggest memory user is mentioned too. Blocking.
+pub fn top_processes_report(by: ProcessSort) -> String {
+    let top = top_processes(by, 3);
+    let names: Vec<String> = match by {
+        ProcessSort::Cpu => {
+            let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as f32;
+            top.iter()
+                // Per-core usage, shown as a share of the whole machine like Task Manager does
+                .map(|p| (base_name(&p.name), p.cpu_usage / cores))
+                .filter(|(_, share)| *share >= 5.0)
+                .map(|(name, share)| format!("{} at {:.0} percent", name, share))
+                .collect()
+        }
+        ProcessSort::Memory => top
+            .iter()
+            .map(|p| format!("{} with {:.1} GB", base_name(&p.name), gigabytes(p.memory)))
+            .collect(),
+        ProcessSort::Disk => top
+            .iter()
+            .filter(|p| p.disk_usage > 0)
+            .map(|p| format!("{} at {:.1} MB", base_name(&p.name), p.disk_usage as f64 / (1024.0 * 1024.0)))
+            .collect(),
+    };
+
+    let mut report = match (by, names.is_empty()) {
+        (ProcessSort::Cpu, true) => "Nothing is working the CPU very hard right now.".to_string(),
+        (ProcessSort::Cpu, false) => format!("The busiest processes are {}.", names.join(", ")),
+        (ProcessSort::Memory, _) => format!("The biggest memory users are {}.", names.join(", ")),
+        (ProcessSort::Disk, true) => "Nothing is reading or writing much right now.".to_string(),
+        (ProcessSort::Disk, false) => format!("The most disk activity is from {}.", names.join(", ")),
+    };
+    if by == ProcessSort::Cpu {
+        if let Some(p) = top_processes(ProcessSort::Memory, 1).first() {
+            report.push_str(&format!(" {} is using the most memory, {:.1} GB.", base_name(&p.name), gigabytes(p.memory)));
+        }
+    }
+    report
+}
+
+/// "what's slowing down my PC", "what's using my memory", "top processes by disk"
+pub fn parse_top_processes_command(command: &str) -> Option<ProcessSort> {
+    let lower = command.to_lowercase();
+
+    let asks = ["slowing down", "so slow", "running slow", "top processes", "heaviest processes", "what's using", "what is using", "hogging"]
+        .iter()
+        .any(|phrase| lower.contains(phrase));
+    if !asks {
+        return None;
+    }
+
+    let words: Vec<&str> = lower.split(|c: char| !c.is_alphanumeric()).collect();
+    if words.iter().any(|w| *w == "memory" || *w == "ram") {
+        Some(ProcessSort::Memory)
+    } else if words.iter().any(|w| *w == "disk" || *w == "drive") {
+        Some(ProcessSort::Disk)
+    } else {
+        Some(ProcessSort::Cpu)
+    }
+}
+
+/// Processes matching a spoken app name ("chrome", "Google Chrome", "spotify"):
+/// the process name itself, or the executable of the app it names. Never a
+/// prefix, so "code" can't take "codex-helper" down with it.
+pub fn find_by_name(name: &str) -> Vec<ProcessInfo> {
+    let query = base_name(name.trim());
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // "Google Chrome" -> chrome.exe via the launcher's registry
+    let executable = crate::app_launcher::find_app(&query).and_then(|app| {
+        std::path::Path::new(&app.executable)
+            .file_name()
+            .map(|f| base_name(&f.to_string_lossy()))
+    });
+
+    running_processes()
+        .into_iter()
+        .filter(|p| {
+            let process = base_name(&p.name);
+            process == query || executable.as_deref() == Some(process.as_str())
+        })
+        .collect()
+}
+
+/// Close one process, and with `tree` every process it started. Without
+/// `force` the app is asked to exit so it can save state.
+fn terminate(pid: u32, force: bool, tree: bool) -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        let pid = pid.to_string();
+        let mut args = vec!["/PID", pid.as_str()];
+        if tree {
+            args.push("/T");
+        }
+        if force {
+            args.push("/F");
+        }
+        let status = std::process::Command::new("taskkill").args(&args).status()?;
+        if !status.success() {
+            bail!("taskkill exited with {}", status);
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let system = SYSTEM.lock().unwrap_or_else(|e| e.into_inner());
+        let process = system
+            .process(Pid::from_u32(pid))
+            .ok_or_else(|| anyhow::anyhow!("Process {} isn't running", pid))?;
+        let signal = if force { sysinfo::Signal::Kill } else { sysinfo::Signal::Term };
+        if tree {
+            let mut descendants = vec![process.pid()];
+            let mut next = 0;
+            while next < descendants.len() {
+                let parent = descendants[next];
+                descendants.extend(system.processes().values().filter(|p| p.parent() == Some(parent)).map(|p| p.pid()));
+                next += 1;
+            }
+            // Children may exit on their own once the parent goes
+            for child in descendants.iter().skip(1).filter_map(|pid| system.process(*pid)) {
+                child.kill_with(signal);
+            }
+        }
+        match process.kill_with(signal) {
+            Some(true) => Ok(()),
+            _ => bail!("Failed to signal process {}", pid),
+        }
+    }
+}
+
+/// Close a process by PID, refusing protected ones and ourselves; `tree`
+/// closes what it started as well
+pub fn kill_pid(pid: u32, force: bool, tree: bool) -> Result<()> {
+    if pid == std::process::id() {
+        bail!("Refusing to close ASTRAL itself");
+    }
+
+    let name = {
+        let system = refreshed_system();
+        system
+            .process(Pid::from_u32(pid))
+            .map(|p| p.name().to_string())
+            .ok_or_else(|| anyhow::anyhow!("Process {} isn't running", pid))?
+    };
+    if is_protected(&name) {
+        bail!("{} is a system process and can't be closed", name);
+    }
+
+    info!("Closing process {} ({}), force: {}, tree: {}", pid, name, force, tree);
+    terminate(pid, force, tree)
+}
+
+/// Close every process matching `name`, returning how many were closed
+pub fn kill_by_name(name: &str, force: bool) -> Result<usize> {
+    let matches = find_by_name(name);
+    if matches.is_empty() {
+        bail!("{} isn't running", name);
+    }
+    if let Some(p) = matches.iter().find(|p| is_protected(&p.name)) {
+        bail!("{} is a system process and can't be closed", p.name);
+    }
+
+    let mut closed = 0;
+    let mut last_error = None;
+    for process in &matches {
+        match kill_pid(process.pid, force, false) {
+            Ok(()) => closed += 1,
+            // Helpers may exit on their own once the main process does
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    match (closed, last_error) {
+        (0, Some(e)) => Err(e),
+        _ => Ok(closed),
+    }
+}
+
+/// App name from "kill chrome", "close spotify", "force quit discord"
+pub fn parse_kill_command(command: &str) -> Option<(String, bool)> {
+    let lower = crate::intents::imperative(command);
+    let lower = lower.as_str();
+
+    let prefixes: &[(&str, bool)] = &[
+        ("force quit ", true),
+        ("force close ", true),
+        ("kill ", true),
+        ("end task ", false),
+        ("close ", false),
+        ("quit ", false),
+    ];
+
+    let (rest, force) = prefixes
+        .iter()
+        .find_map(|(prefix, force)| lower.strip_prefix(prefix).map(|rest| (rest, *force)))?;
+
+    let name = rest
+        .trim_start_matches("the ")
+        .trim_start_matches("all ")
+        .trim_end_matches(" please")
+        .trim_end_matches(" app")
+        .trim_end_matches(" process")
+        .trim_end_matches(" processes")
+        .trim();
+
+    // "close the window" / "close it" are about the UI, not processes
+    if name.is_empty() || ["it", "this", "that", "window", "dashboard", "settings"].contains(&name) {
+        return None;
+    }
+
+    Some((name.to_string(), force))
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn list_processes() -> Result<Vec<ProcessInfo>, String> {
+    tokio::task::spawn_blocking(running_processes)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// `by` is "cpu", "memory" or "disk"
+#[tauri::command]
+pub async fn get_top_processes(by: String, count: Option<usize>) -> Result<Vec<ProcessInfo>, String> {
+    let by = ProcessSort::parse(&by).ok_or_else(|| format!("Can't sort processes by {}", by))?;
+    let count = count.unwrap_or(5);
+    tokio::task::spawn_blocking(move || top_processes(by, count))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Close by PID, or every process matching `name`. Returns how many were closed.
+/// `tree` closes what a PID started as well. The UI is responsible for confirming first.
+#[tauri::command]
+pub async fn kill_process(pid: Option<u32>, name: Option<String>, force: Option<bool>, tree: Option<bool>) -> Result<usize, String> {
+    crate::permissions::check(crate::permissions::Capability::CloseApps)?;
+    let force = force.unwrap_or(false);
+    let tree = tree.unwrap_or(false);
+
+    tokio::task::spawn_blocking(move || match (pid, name) {
+        (Some(pid), _) => kill_pid(pid, force, tree).map(|_| 1),
+        (None, Some(name)) => kill_by_name(&name, force),
+        (None, None) => bail!("Either a PID or a process name is required"),
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
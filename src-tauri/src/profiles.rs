@@ -0,0 +1,220 @@
+// Profiles Module
+// Named setups like "Work" and "Home" that each carry their own LLM config,
+// ElevenLabs voice and set of enabled routines. Switching saves the current
+// state into the active profile, then applies the target's through the
+// normal settings path so the engines, tray and UI all follow. Stored in the
+// `profiles` settings section; the first profile is created from whatever
+// is configured when profiles are first used.
+
+use crate::llm_provider::LLMConfig;
+use crate::settings::AppSettings;
+use crate::state::AppState;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+pub const PROFILE_CHANGED_EVENT: &str = "profile-changed";
+
+const DEFAULT_PROFILE_ID: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub llm: LLMConfig,
+    /// ElevenLabs voice
+    pub voice_id: String,
+    /// Ids of the routines enabled while this profile is active
+    pub enabled_routines: Vec<String>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: "Default".to_string(),
+            llm: LLMConfig::default(),
+            voice_id: crate::elevenlabs_tts::ElevenLabsConfig::default().voice_id,
+            enabled_routines: Vec::new(),
+        }
+    }
+}
+
+/// The `profiles` settings section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSettings {
+    /// Id of the profile in use
+    pub active: String,
+    /// Empty until profiles are first used
+    pub list: Vec<Profile>,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE_ID.to_string(),
+            list: Vec::new(),
+        }
+    }
+}
+
+impl ProfileSettings {
+    pub fn active_name(&self) -> Option<&str> {
+        self.list.iter().find(|p| p.id == self.active).map(|p| p.name.as_str())
+    }
+}
+
+/// "Work Laptop" -> "work-laptop"
+pub(crate) fn slug(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+async fn enabled_routines(app: &AppHandle) -> Vec<String> {
+    let automation = app.state::<AppState>().inner().automation.lock().await;
+    let mut ids: Vec<String> = automation.get_all_routines().into_iter().filter(|r| r.enabled).map(|r| r.id).collect();
+    ids.sort();
+    ids
+}
+
+/// The active profile as things are configured right now, with the routines
+/// that are on
+fn capture(settings: &AppSettings, id: &str, name: &str, routines: &[String]) -> Profile {
+    Profile {
+        id: id.to_string(),
+        name: name.to_string(),
+        llm: settings.llm.clone(),
+        voice_id: settings.tts.voice_id.clone(),
+        enabled_routines: routines.to_vec(),
+    }
+}
+
+/// Write the current state back into the active profile, creating it if needed
+fn sync_active(settings: &mut AppSettings, routines: &[String]) {
+    let active = settings.profiles.active.clone();
+    let name = settings
+        .profiles
+        .active_name()
+        .map(str::to_string)
+        .unwrap_or_else(|| Profile::default().name);
+    let current = capture(settings, &active, &name, routines);
+    match settings.profiles.list.iter_mut().find(|p| p.id == active) {
+        Some(profile) => *profile = current,
+        None => settings.profiles.list.insert(0, current),
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_profiles(app: AppHandle) -> Result<ProfileSettings, String> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    if !settings.profiles.list.is_empty() {
+        return Ok(settings.profiles);
+    }
+    let routines = enabled_routines(&app).await;
+    crate::settings::modify_settings(&app, |settings| {
+        if settings.profiles.list.is_empty() {
+            sync_active(settings, &routines);
+        }
+        Ok(settings.profiles.clone())
+    })
+    .await
+}
+
+/// New profile starting from the current setup
+#[tauri::command]
+pub async fn create_profile(app: AppHandle, name: String) -> Result<Profile, String> {
+    let name = name.trim().to_string();
+    let id = slug(&name);
+    if id.is_empty() {
+        return Err("Profile name can't be empty".to_string());
+    }
+
+    let routines = enabled_routines(&app).await;
+    let profile = crate::settings::modify_settings(&app, |settings| {
+        sync_active(settings, &routines);
+        if settings.profiles.list.iter().any(|p| p.id == id) {
+            return Err(format!("A profile called '{}' already exists", name));
+        }
+        let profile = capture(settings, &id, &name, &routines);
+        settings.profiles.list.push(profile.clone());
+        Ok(profile)
+    })
+    .await?;
+    info!("Created profile {}", name);
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn delete_profile(app: AppHandle, id: String) -> Result<(), String> {
+    crate::settings::modify_settings(&app, |settings| {
+        if settings.profiles.active == id {
+            return Err("Switch to another profile before deleting this one".to_string());
+        }
+        let before = settings.profiles.list.len();
+        settings.profiles.list.retain(|p| p.id != id);
+        if settings.profiles.list.len() == before {
+            return Err(format!("Profile not found: {}", id));
+        }
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, id: String) -> Result<Profile, String> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    let target = settings
+        .profiles
+        .list
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Profile not found: {}", id))?;
+    if settings.profiles.active == id {
+        return Ok(target);
+    }
+
+    // The profile being left keeps the routines that were on
+    let routines = enabled_routines(&app).await;
+    app.state::<AppState>()
+        .inner()
+        .automation
+        .lock()
+        .await
+        .set_enabled_routines(&target.enabled_routines)
+        .map_err(|e| e.to_string())?;
+
+    let target = crate::settings::modify_settings(&app, |settings| {
+        sync_active(settings, &routines);
+        let target = settings
+            .profiles
+            .list
+            .iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Profile not found: {}", id))?;
+        settings.llm = target.llm.clone();
+        settings.tts.voice_id = target.voice_id.clone();
+        settings.profiles.active = target.id.clone();
+        Ok(target)
+    })
+    .await?;
+
+    info!("Switched to profile {}", target.name);
+    if let Err(e) = app.emit(PROFILE_CHANGED_EVENT, &target) {
+        warn!("Failed to emit profile change: {}", e);
+    }
+    crate::tray::refresh_routines(&app).await;
+    Ok(target)
+}
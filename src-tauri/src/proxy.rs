@@ -0,0 +1,330 @@
+// Proxy Module
+// Sends outbound HTTP through the proxy in the `proxy` settings section, an
+// HTTP(S) or SOCKS5 URL with optional credentials. Every reqwest client in the
+// app is made by `client()` or `builder()` here, so LLM providers, speech and
+// the integrations all follow the setting; the updater, which has its own
+// client, takes `url()`. Hosts on the no-proxy list (by default this machine,
+// where Ollama and Whisper usually run, and the local network) are reached
+// directly. With no proxy set, reqwest still honours HTTP(S)_PROXY. A proxy
+// setting that can't be used fails closed: requests go nowhere rather than
+// around the proxy. Connections that aren't HTTP (IMAP, the Discord gateway)
+// open their socket with `connect()`, tunnelled with CONNECT or SOCKS5; an
+// https:// proxy can't carry those, so they fail too. MQTT can't be
+// tunnelled and asks `bypasses()` before connecting.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use once_cell::sync::Lazy;
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy, Url};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::IpAddr;
+use std::sync::RwLock;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::{info, warn};
+
+const SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+
+/// Where requests go when the proxy setting can't be used: a port nothing
+/// listens on, so they fail instead of leaving without the proxy
+const UNREACHABLE_PROXY: &str = "http://127.0.0.1:1";
+
+/// Longest CONNECT answer read before giving up on the proxy
+const MAX_CONNECT_RESPONSE: usize = 8192;
+
+/// The `proxy` settings section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub enabled: bool,
+    /// http://proxy.example:8080, or socks5h://127.0.0.1:1080 to resolve names through the proxy
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    /// Hosts, domains (".corp.example" covers its subdomains) and IP ranges reached directly
+    pub no_proxy: Vec<String>,
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            no_proxy: [
+                "localhost", "127.0.0.1", "::1", ".local",
+                // Private and link-local ranges: the router, a NAS, Home Assistant, Hue
+                "10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16", "169.254.0.0/16", "fc00::/7", "fe80::/10",
+            ]
+            .iter()
+            .map(|host| host.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl ProxySettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.url = self.url.trim().to_string();
+        self.username = self.username.trim().to_string();
+        self.no_proxy = self.no_proxy.iter().map(|host| host.trim().to_string()).filter(|host| !host.is_empty()).collect();
+        if !self.enabled {
+            return Ok(());
+        }
+        let url = Url::parse(&self.url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+        if !SCHEMES.contains(&url.scheme()) {
+            return Err("The proxy URL must start with http://, https://, socks5:// or socks5h://".to_string());
+        }
+        if url.host_str().is_none() {
+            return Err("The proxy URL needs a host".to_string());
+        }
+        if self.username.is_empty() && !self.password.is_empty() {
+            return Err("A proxy password needs a username".to_string());
+        }
+        Ok(())
+    }
+
+    fn proxy(&self) -> reqwest::Result<Option<Proxy>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let mut proxy = Proxy::all(&self.url)?;
+        if !self.username.is_empty() {
+            proxy = proxy.basic_auth(&self.username, &self.password);
+        }
+        Ok(Some(proxy.no_proxy(NoProxy::from_string(&self.no_proxy.join(",")))))
+    }
+
+    /// The proxy URL with the credentials in it
+    fn url(&self) -> Result<Option<Url>, String> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let mut url = Url::parse(&self.url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+        if !self.username.is_empty() {
+            url.set_username(&self.username).map_err(|_| "The proxy URL can't take a username".to_string())?;
+            url.set_password(Some(&self.password)).map_err(|_| "The proxy URL can't take a password".to_string())?;
+        }
+        Ok(Some(url))
+    }
+
+    /// Whether `host` is reached directly, by the same rules reqwest applies
+    /// to the no-proxy list
+    fn bypasses(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_lowercase();
+        let ip = host.parse::<IpAddr>().ok();
+        self.no_proxy.iter().any(|entry| {
+            let entry = entry.to_lowercase();
+            if entry == "*" {
+                return true;
+            }
+            if let Some(ip) = ip {
+                return in_range(ip, &entry);
+            }
+            let domain = entry.trim_start_matches('.');
+            host == domain || host.ends_with(&format!(".{}", domain))
+        })
+    }
+}
+
+/// Whether `ip` is the address or inside the range ("10.0.0.0/8") in `entry`
+fn in_range(ip: IpAddr, entry: &str) -> bool {
+    let (address, prefix) = entry.split_once('/').unwrap_or((entry, ""));
+    let Ok(address) = address.parse::<IpAddr>() else {
+        return false;
+    };
+    match (ip, address) {
+        (IpAddr::V4(ip), IpAddr::V4(address)) => {
+            let prefix = prefix.parse().unwrap_or(32).min(32);
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(address) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(address)) => {
+            let prefix = prefix.parse().unwrap_or(128).min(128);
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(address) & mask
+        }
+        _ => false,
+    }
+}
+
+static SETTINGS: Lazy<RwLock<ProxySettings>> = Lazy::new(|| RwLock::new(ProxySettings::default()));
+/// Shared by callers that don't need their own timeouts; rebuilt by `set`
+static CLIENT: Lazy<RwLock<Client>> = Lazy::new(|| RwLock::new(build()));
+
+/// Use `settings` from now on; called when settings load or change. Clients
+/// made before keep their old route, so holders of one rebuild it.
+pub fn set(settings: ProxySettings) {
+    if settings.enabled {
+        info!("Outbound requests go through the proxy at {}", settings.url);
+    }
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = settings;
+    *CLIENT.write().unwrap_or_else(|e| e.into_inner()) = build();
+}
+
+/// A client builder routed through the proxy, for callers that set timeouts
+/// or other options of their own
+pub fn builder() -> ClientBuilder {
+    let settings = SETTINGS.read().unwrap_or_else(|e| e.into_inner());
+    match settings.proxy() {
+        Ok(Some(proxy)) => Client::builder().proxy(proxy),
+        Ok(None) => Client::builder(),
+        Err(e) => {
+            // validate() checks the URL, so this is a store edited by hand
+            warn!("Can't use the proxy at {}, blocking outbound requests: {}", settings.url, e);
+            blocked()
+        }
+    }
+}
+
+/// The proxy for clients not built here (the updater's); None without one
+pub fn url() -> Result<Option<Url>, String> {
+    SETTINGS.read().unwrap_or_else(|e| e.into_inner()).url()
+}
+
+/// A builder whose requests all fail, for when the proxy can't be used
+fn blocked() -> ClientBuilder {
+    Client::builder().proxy(Proxy::all(UNREACHABLE_PROXY).expect("Invalid fallback proxy URL"))
+}
+
+/// The shared client, routed through the proxy
+pub fn client() -> Client {
+    CLIENT.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+fn build() -> Client {
+    builder().build().unwrap_or_else(|e| {
+        warn!("Failed to build the HTTP client, blocking outbound requests: {}", e);
+        blocked().build().expect("Failed to create HTTP client")
+    })
+}
+
+/// Whether connections to `host` skip the proxy: it's off, or the host is on
+/// the no-proxy list
+pub fn bypasses(host: &str) -> bool {
+    let settings = SETTINGS.read().unwrap_or_else(|e| e.into_inner());
+    !settings.enabled || settings.bypasses(host)
+}
+
+/// Open a TCP connection to `host:port` through the proxy, for protocols
+/// reqwest doesn't carry. Hosts on the no-proxy list are connected directly.
+pub async fn connect(host: &str, port: u16) -> io::Result<TcpStream> {
+    let settings = SETTINGS.read().unwrap_or_else(|e| e.into_inner()).clone();
+    if !settings.enabled || settings.bypasses(host) {
+        return TcpStream::connect((host, port)).await;
+    }
+    let proxy = Url::parse(&settings.url).map_err(|e| io::Error::other(format!("Invalid proxy URL: {}", e)))?;
+    if !matches!(proxy.scheme(), "http" | "socks5" | "socks5h") {
+        return Err(io::Error::other(format!(
+            "Only http:// and socks5:// proxies can carry a connection to {}; add it to the no-proxy list or change the proxy",
+            host
+        )));
+    }
+    let proxy_host = proxy.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+    let mut stream = TcpStream::connect((proxy_host, proxy.port_or_known_default().unwrap_or(1080))).await?;
+    match proxy.scheme() {
+        "http" => http_connect(&mut stream, host, port, &settings).await?,
+        scheme => socks5_connect(&mut stream, host, port, &settings, scheme == "socks5h").await?,
+    }
+    Ok(stream)
+}
+
+/// Ask an HTTP proxy for a tunnel with CONNECT
+async fn http_connect(stream: &mut TcpStream, host: &str, port: u16, settings: &ProxySettings) -> io::Result<()> {
+    let target = if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if !settings.username.is_empty() {
+        let credentials = BASE64.encode(format!("{}:{}", settings.username, settings.password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", credentials));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // A byte at a time, so nothing past the headers is taken from the tunnel
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() > MAX_CONNECT_RESPONSE {
+            return Err(io::Error::other("The proxy's answer to CONNECT is too long"));
+        }
+        response.push(stream.read_u8().await?);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status = response.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(io::Error::other(format!("The proxy refused the connection to {}: {}", target, status)));
+    }
+    Ok(())
+}
+
+/// Open a SOCKS5 tunnel; `remote_dns` (socks5h) leaves the name to the proxy
+async fn socks5_connect(stream: &mut TcpStream, host: &str, port: u16, settings: &ProxySettings, remote_dns: bool) -> io::Result<()> {
+    let sign_in = !settings.username.is_empty();
+    stream.write_all(if sign_in { &[5, 1, 2] } else { &[5, 1, 0] }).await?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    match reply {
+        [5, 0] => {}
+        [5, 2] if sign_in => {
+            let (user, password) = (settings.username.as_bytes(), settings.password.as_bytes());
+            if user.len() > 255 || password.len() > 255 {
+                return Err(io::Error::other("SOCKS5 proxy user names and passwords are limited to 255 bytes"));
+            }
+            let mut request = vec![1, user.len() as u8];
+            request.extend_from_slice(user);
+            request.push(password.len() as u8);
+            request.extend_from_slice(password);
+            stream.write_all(&request).await?;
+            stream.read_exact(&mut reply).await?;
+            if reply[1] != 0 {
+                return Err(io::Error::other("The proxy didn't accept the user name and password"));
+            }
+        }
+        _ => return Err(io::Error::other("The proxy wants a sign-in method that isn't supported")),
+    }
+
+    let mut request = vec![5, 1, 0];
+    let ip = match host.parse::<IpAddr>() {
+        Ok(ip) => Some(ip),
+        Err(_) if remote_dns => None,
+        Err(_) => {
+            let address = tokio::net::lookup_host((host, port)).await?.next();
+            Some(address.ok_or_else(|| io::Error::other(format!("Couldn't resolve {}", host)))?.ip())
+        }
+    };
+    match ip {
+        Some(IpAddr::V4(ip)) => {
+            request.push(1);
+            request.extend_from_slice(&ip.octets());
+        }
+        Some(IpAddr::V6(ip)) => {
+            request.push(4);
+            request.extend_from_slice(&ip.octets());
+        }
+        None if host.len() > 255 => return Err(io::Error::other(format!("{} is too long a name for SOCKS5", host))),
+        None => {
+            request.push(3);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[1] != 0 {
+        return Err(io::Error::other(format!("The proxy couldn't reach {} (SOCKS5 error {})", host, reply[1])));
+    }
+    // The address the proxy bound, then its port; neither is needed
+    let bound = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => stream.read_u8().await? as usize,
+        _ => return Err(io::Error::other("The proxy sent an invalid SOCKS5 answer")),
+    };
+    let mut rest = vec![0u8; bound + 2];
+    stream.read_exact(&mut rest).await?;
+    Ok(())
+}
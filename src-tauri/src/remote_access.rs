@@ -0,0 +1,150 @@
+// Remote Access
+// Lets a phone or laptop on the same LAN or tailnet drive this machine's
+// ASTRAL through the local API ("run evening wind down"). It's a second
+// listener next to the loopback one, off unless `api.remote.enabled`, with
+// its own token and an allowlist of what remote clients may do. Only peers
+// on the local network or a Tailscale tailnet get an answer, and only over
+// HTTPS: it doesn't start without a certificate (`tailscale cert` makes
+// one). A peer's address alone can't vouch for the link, since the tailnet
+// range is also what ISPs use for carrier-grade NAT.
+// Like the local API, enabling it or changing the address takes effect on
+// the next launch; the token and allowlist apply right away.
+
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+
+/// What a remote client can be allowed to do, one per group of API routes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteCapability {
+    /// `/v1/chat`, straight to the LLM
+    Chat,
+    /// `/v1/command`, anything the assistant understands
+    Command,
+    Speak,
+    Transcribe,
+    /// Listing and running routines
+    Routines,
+    /// The live event stream
+    Events,
+    /// The MCP endpoint
+    Mcp,
+}
+
+impl RemoteCapability {
+    /// What a request to `path` needs; `None` for paths the API doesn't serve
+    pub fn for_path(path: &str) -> Option<Self> {
+        match path {
+            "/v1/chat" => Some(Self::Chat),
+            "/v1/command" => Some(Self::Command),
+            "/v1/speak" => Some(Self::Speak),
+            "/v1/transcribe" => Some(Self::Transcribe),
+            "/v1/events" => Some(Self::Events),
+            "/mcp" => Some(Self::Mcp),
+            p if p == "/v1/routines" || p.starts_with("/v1/routines/") => Some(Self::Routines),
+            _ => None,
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Chat => "chat",
+            Self::Command => "commands",
+            Self::Speak => "speech",
+            Self::Transcribe => "transcription",
+            Self::Routines => "routines",
+            Self::Events => "the event stream",
+            Self::Mcp => "MCP",
+        }
+    }
+}
+
+/// The `api.remote` settings section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteAccessSettings {
+    /// Serve the API to other devices as well (next launch)
+    pub enabled: bool,
+    /// The machine's Tailscale or LAN address, or 0.0.0.0 for every interface
+    pub bind_address: String,
+    pub port: u16,
+    /// Bearer token for remote clients, separate from the local one;
+    /// generated the first time it's needed
+    pub token: String,
+    /// PEM certificate and private key for HTTPS
+    pub tls_cert_path: String,
+    pub tls_key_path: String,
+    /// What remote clients may do; everything else gets a 403
+    pub capabilities: Vec<RemoteCapability>,
+}
+
+impl Default for RemoteAccessSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "0.0.0.0".to_string(),
+            port: 7866,
+            token: String::new(),
+            tls_cert_path: String::new(),
+            tls_key_path: String::new(),
+            capabilities: vec![RemoteCapability::Command, RemoteCapability::Routines],
+        }
+    }
+}
+
+impl RemoteAccessSettings {
+    pub fn uses_tls(&self) -> bool {
+        !self.tls_cert_path.is_empty()
+    }
+
+    pub fn socket_address(&self) -> Result<SocketAddr, String> {
+        let ip: IpAddr = self
+            .bind_address
+            .parse()
+            .map_err(|_| format!("Invalid remote access address: {}", self.bind_address))?;
+        Ok(SocketAddr::new(ip, self.port))
+    }
+
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.bind_address = self.bind_address.trim().to_string();
+        self.tls_cert_path = self.tls_cert_path.trim().to_string();
+        self.tls_key_path = self.tls_key_path.trim().to_string();
+        self.socket_address()?;
+        if self.port < 1024 {
+            return Err(format!("Invalid remote access port: {}", self.port));
+        }
+        if self.tls_cert_path.is_empty() != self.tls_key_path.is_empty() {
+            return Err("Set both the TLS certificate and its key, or neither".to_string());
+        }
+        let mut seen = Vec::with_capacity(self.capabilities.len());
+        self.capabilities.retain(|c| {
+            if seen.contains(c) {
+                return false;
+            }
+            seen.push(*c);
+            true
+        });
+        Ok(())
+    }
+}
+
+/// Tailscale hands out addresses from 100.64.0.0/10 and fd7a:115c:a1e0::/48
+fn is_tailnet(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            a == 100 && (b & 0xc0) == 64
+        }
+        IpAddr::V6(ip) => matches!(ip.segments(), [0xfd7a, 0x115c, 0xa1e0, ..]),
+    }
+}
+
+/// Why `peer` may not use the remote API, if it may not
+pub fn refuse_peer(peer: IpAddr) -> Option<&'static str> {
+    let peer = peer.to_canonical();
+    if is_tailnet(peer) || peer.is_loopback() || crate::privacy::is_local_address(peer) {
+        None
+    } else {
+        Some("Remote access is limited to the local network and your tailnet")
+    }
+}
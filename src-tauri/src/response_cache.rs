@@ -0,0 +1,170 @@
+// Response Cache Module
+// Replies to short questions ("what time is it in Tokyo"), kept for a while so
+// asking again doesn't cost another API call. An entry is addressed by a hash
+// of everything that shapes the reply: provider, model, temperature, the
+// system prompt as rendered (with what's playing, the active window, ...),
+// the conversation so far, whether tools were on offer and the question,
+// normalized. Replies that came with document context or images, or where
+// the model called a tool, aren't kept. Off unless `response_cache.enabled`;
+// `providers` picks whose replies are cached, and a request can bypass it.
+
+use crate::llm_provider::{LLMConfig, LLMProvider, LLMResponse, Message};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Entries kept at most; the oldest go first
+const MAX_ENTRIES: usize = 500;
+
+/// The `response_cache` settings section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheSettings {
+    pub enabled: bool,
+    /// How long a reply stays fresh
+    pub ttl_secs: u64,
+    /// Longer messages aren't cached; they're rarely asked twice word for word
+    pub max_query_chars: usize,
+    /// Providers whose replies are cached
+    pub providers: Vec<LLMProvider>,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 300,
+            max_query_chars: 200,
+            // Local models cost nothing to ask again
+            providers: vec![LLMProvider::OpenAI, LLMProvider::AzureOpenAI, LLMProvider::OpenRouter, LLMProvider::Claude],
+        }
+    }
+}
+
+impl CacheSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        if !(1..=7 * 24 * 3600).contains(&self.ttl_secs) {
+            return Err("Cached replies can be kept from 1 second to 7 days".to_string());
+        }
+        if self.max_query_chars == 0 {
+            return Err("The longest cached question needs at least 1 character".to_string());
+        }
+        Ok(())
+    }
+}
+
+struct Entry {
+    response: LLMResponse,
+    stored: Instant,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    /// Since launch
+    pub hits: u64,
+    pub misses: u64,
+}
+
+static SETTINGS: Lazy<RwLock<CacheSettings>> = Lazy::new(|| RwLock::new(CacheSettings::default()));
+static ENTRIES: Lazy<Mutex<HashMap<String, Entry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static STATS: Lazy<Mutex<CacheStats>> = Lazy::new(|| Mutex::new(CacheStats::default()));
+
+/// Use `settings` from now on; called when settings load or change. Replies
+/// cached under the old settings are dropped.
+pub fn set(settings: CacheSettings) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = settings;
+    clear();
+}
+
+pub fn clear() {
+    ENTRIES.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Lowercase, single-spaced and without trailing punctuation, so "What time
+/// is it in Tokyo?" and "what time is it in tokyo" share an entry
+fn normalize(message: &str) -> String {
+    let words: Vec<&str> = message.split_whitespace().collect();
+    words.join(" ").to_lowercase().trim_end_matches(['?', '!', '.']).trim_end().to_string()
+}
+
+/// Where the reply to `message` is cached, or None when replies to it aren't.
+/// `system_prompt` is rendered, so a reply given while one song played isn't
+/// served once another does; `history` is what was said before `message`.
+pub fn key(config: &LLMConfig, system_prompt: &str, history: &[Message], tools: bool, message: &str) -> Option<String> {
+    let settings = SETTINGS.read().unwrap_or_else(|e| e.into_inner());
+    let message = normalize(message);
+    if !settings.enabled
+        || !settings.providers.contains(&config.provider)
+        || message.is_empty()
+        || message.chars().count() > settings.max_query_chars
+    {
+        return None;
+    }
+    let mut hash = Sha256::new();
+    for part in [
+        format!("{:?}", config.provider),
+        config.model.clone(),
+        config.temperature.to_string(),
+        system_prompt.to_string(),
+        tools.to_string(),
+        message,
+    ] {
+        hash.update(part.as_bytes());
+        hash.update([0]);
+    }
+    for turn in history {
+        hash.update(turn.role.as_bytes());
+        hash.update([0]);
+        hash.update(turn.content.as_bytes());
+        hash.update([0]);
+    }
+    Some(format!("{:x}", hash.finalize()))
+}
+
+/// The cached reply at `key`, if it's still fresh
+pub fn get(key: &str) -> Option<LLMResponse> {
+    let ttl = Duration::from_secs(SETTINGS.read().unwrap_or_else(|e| e.into_inner()).ttl_secs);
+    let mut entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    entries.retain(|_, entry| entry.stored.elapsed() < ttl);
+    // Nothing was spent on this one
+    let hit = entries.get(key).map(|entry| LLMResponse { tokens_used: None, ..entry.response.clone() });
+
+    let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+    if hit.is_some() {
+        stats.hits += 1;
+        debug!("Response cache hit");
+    } else {
+        stats.misses += 1;
+    }
+    hit
+}
+
+pub fn put(key: String, response: &LLMResponse) {
+    let mut entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    if entries.len() >= MAX_ENTRIES && !entries.contains_key(&key) {
+        let oldest = entries.iter().min_by_key(|(_, entry)| entry.stored).map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            entries.remove(&oldest);
+        }
+    }
+    entries.insert(key, Entry { response: response.clone(), stored: Instant::now() });
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_response_cache_stats() -> CacheStats {
+    let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    stats.entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner()).len();
+    stats
+}
+
+#[tauri::command]
+pub async fn clear_response_cache() {
+    clear();
+}
@@ -0,0 +1,165 @@
+// Secrets Module
+// Encrypts the API keys kept in the settings store so a copied settings.json
+// doesn't leak them. Windows uses DPAPI, which ties the ciphertext to the
+// user's account; elsewhere an age key generated on first use is kept next to
+// the store in <data dir>/com.astral.app/secret.key, readable only by the user.
+// Sealed values are stored as `enc:<scheme>:<base64>`; anything without that
+// prefix is a value saved before encryption and is returned unchanged.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+const PREFIX: &str = "enc:";
+
+#[cfg(windows)]
+const SCHEME: &str = "dpapi";
+#[cfg(not(windows))]
+const SCHEME: &str = "age";
+
+pub fn is_sealed(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+/// Encrypt `plain` into the form written to the store
+pub fn seal(plain: &str) -> Result<String, String> {
+    let sealed = platform::encrypt(plain.as_bytes())?;
+    Ok(format!("{}{}:{}", PREFIX, SCHEME, BASE64.encode(sealed)))
+}
+
+/// Decrypt a value read from the store
+pub fn open(stored: &str) -> Result<String, String> {
+    let Some(rest) = stored.strip_prefix(PREFIX) else {
+        return Ok(stored.to_string());
+    };
+    let (scheme, payload) = rest.split_once(':').ok_or_else(|| "Malformed encrypted value".to_string())?;
+    if scheme != SCHEME {
+        return Err(format!("Value was encrypted with {} and can't be read on this system", scheme));
+    }
+    let sealed = BASE64.decode(payload).map_err(|e| format!("Malformed encrypted value: {}", e))?;
+    let plain = platform::decrypt(&sealed)?;
+    String::from_utf8(plain).map_err(|e| e.to_string())
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{LocalFree, HLOCAL};
+    use windows::Win32::Security::Cryptography::{
+        CryptProtectData, CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+    };
+
+    fn blob(data: &[u8]) -> CRYPT_INTEGER_BLOB {
+        CRYPT_INTEGER_BLOB {
+            cbData: data.len() as u32,
+            pbData: data.as_ptr() as *mut u8,
+        }
+    }
+
+    /// Copy out a blob DPAPI allocated and free it
+    unsafe fn take(output: CRYPT_INTEGER_BLOB) -> Vec<u8> {
+        let data = std::slice::from_raw_parts(output.pbData, output.cbData as usize).to_vec();
+        let _ = LocalFree(HLOCAL(output.pbData as _));
+        data
+    }
+
+    pub fn encrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+        let input = blob(data);
+        let mut output = CRYPT_INTEGER_BLOB::default();
+        unsafe {
+            CryptProtectData(&input, PCWSTR::null(), None, None, None, CRYPTPROTECT_UI_FORBIDDEN, &mut output)
+                .map_err(|e| format!("Couldn't encrypt secret: {}", e))?;
+            Ok(take(output))
+        }
+    }
+
+    pub fn decrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+        let input = blob(data);
+        let mut output = CRYPT_INTEGER_BLOB::default();
+        unsafe {
+            CryptUnprotectData(&input, None, None, None, None, CRYPTPROTECT_UI_FORBIDDEN, &mut output)
+                .map_err(|e| format!("Couldn't decrypt secret (was it saved by another user?): {}", e))?;
+            Ok(take(output))
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use age::secrecy::ExposeSecret;
+    use age::x25519::Identity;
+    use once_cell::sync::OnceCell;
+    use std::io::{Read, Write};
+    use std::path::PathBuf;
+    use tracing::info;
+
+    static IDENTITY: OnceCell<Identity> = OnceCell::new();
+
+    fn key_path() -> Option<PathBuf> {
+        let mut path = dirs::data_dir()?;
+        path.push("com.astral.app");
+        path.push("secret.key");
+        Some(path)
+    }
+
+    fn create_key(path: &PathBuf) -> Result<Identity, String> {
+        let identity = Identity::generate();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path).map_err(|e| format!("Couldn't create secret key: {}", e))?;
+        file.write_all(identity.to_string().expose_secret().as_bytes())
+            .map_err(|e| format!("Couldn't write secret key: {}", e))?;
+
+        info!("Created secret key at {:?}", path);
+        Ok(identity)
+    }
+
+    fn identity() -> Result<&'static Identity, String> {
+        IDENTITY.get_or_try_init(|| {
+            let path = key_path().ok_or_else(|| "No data directory on this system".to_string())?;
+            match std::fs::read_to_string(&path) {
+                Ok(content) => content
+                    .trim()
+                    .parse::<Identity>()
+                    .map_err(|e| format!("Secret key at {:?} is invalid: {}", path, e)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => create_key(&path),
+                Err(e) => Err(format!("Couldn't read secret key: {}", e)),
+            }
+        })
+    }
+
+    pub fn encrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+        let recipient = identity()?.to_public();
+        let encryptor = age::Encryptor::with_recipients(vec![Box::new(recipient)])
+            .ok_or_else(|| "No recipient for encryption".to_string())?;
+
+        let mut sealed = Vec::new();
+        let mut writer = encryptor.wrap_output(&mut sealed).map_err(|e| e.to_string())?;
+        writer.write_all(data).map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())?;
+        Ok(sealed)
+    }
+
+    pub fn decrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+        let identity = identity()?;
+        let decryptor = match age::Decryptor::new(data).map_err(|e| e.to_string())? {
+            age::Decryptor::Recipients(decryptor) => decryptor,
+            _ => return Err("Secret wasn't encrypted with the local key".to_string()),
+        };
+
+        let mut reader = decryptor
+            .decrypt(std::iter::once(identity as &dyn age::Identity))
+            .map_err(|e| format!("Couldn't decrypt secret (is secret.key from another machine?): {}", e))?;
+        let mut plain = Vec::new();
+        reader.read_to_end(&mut plain).map_err(|e| e.to_string())?;
+        Ok(plain)
+    }
+}
@@ -0,0 +1,288 @@
+// Sensors Module
+// Hardware temperatures and fan speeds for the stats panel, and a thermal
+// watch Gaming Mode starts so it can warn when the CPU or GPU runs hot. Windows has no public
+// sensor API, so readings come from LibreHardwareMonitor's WMI provider when
+// it's running; elsewhere sysinfo's components. nvidia-smi fills in the GPU.
+
+use tracing::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use sysinfo::Components;
+use tauri::AppHandle;
+
+const THERMAL_WATCH_INTERVAL: Duration = Duration::from_secs(30);
+/// Degrees a sensor has to cool below the limit before it can warn again
+const THERMAL_HYSTERESIS_C: f32 = 5.0;
+
+/// Bumped by every new watch so older ones stop
+static WATCH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorKind {
+    Cpu,
+    Gpu,
+    Disk,
+    Other,
+}
+
+impl SensorKind {
+    fn classify(label: &str) -> Self {
+        let lower = label.to_lowercase();
+        if ["gpu", "nvidia", "amdgpu", "radeon", "geforce"].iter().any(|m| lower.contains(m)) {
+            Self::Gpu
+        } else if ["cpu", "core", "package", "tctl", "tdie", "k10temp", "coretemp"].iter().any(|m| lower.contains(m)) {
+            Self::Cpu
+        } else if ["nvme", "ssd", "drive", "disk", "drivetemp"].iter().any(|m| lower.contains(m)) {
+            Self::Disk
+        } else {
+            Self::Other
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Cpu => "CPU",
+            Self::Gpu => "GPU",
+            Self::Disk => "drive",
+            Self::Other => "sensor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperatureReading {
+    pub label: String,
+    pub kind: SensorKind,
+    pub celsius: f32,
+    /// Where the hardware reports one
+    pub critical: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FanReading {
+    pub label: String,
+    pub rpm: u32,
+}
+
+/// All temperature sensors that can be read. Blocking.
+pub fn temperatures() -> Vec<TemperatureReading> {
+    let mut readings = platform::temperatures();
+    if readings.is_empty() {
+        readings = component_temperatures();
+    }
+    if !readings.iter().any(|r| r.kind == SensorKind::Gpu) {
+        readings.extend(nvidia_temperatures());
+    }
+    readings
+}
+
+/// Fans that report a speed. Blocking. Empty on macOS, which keeps fan
+/// speeds behind the SMC.
+pub fn fans() -> Vec<FanReading> {
+    platform::fans()
+}
+
+/// The hottest CPU and GPU readings
+pub fn hottest(readings: &[TemperatureReading]) -> Vec<&TemperatureReading> {
+    [SensorKind::Cpu, SensorKind::Gpu]
+        .iter()
+        .filter_map(|kind| {
+            readings
+                .iter()
+                .filter(|r| r.kind == *kind)
+                .max_by(|a, b| a.celsius.total_cmp(&b.celsius))
+        })
+        .collect()
+}
+
+fn component_temperatures() -> Vec<TemperatureReading> {
+    Components::new_with_refreshed_list()
+        .iter()
+        .filter(|c| c.temperature() > 0.0)
+        .map(|c| TemperatureReading {
+            label: c.label().to_string(),
+            kind: SensorKind::classify(c.label()),
+            celsius: c.temperature(),
+            critical: c.critical(),
+        })
+        .collect()
+}
+
+/// "NVIDIA GeForce RTX 3070, 64"
+fn nvidia_temperatures() -> Vec<TemperatureReading> {
+    let Ok(output) = Command::new("nvidia-smi")
+        .args(["--query-gpu=name,temperature.gpu", "--format=csv,noheader,nounits"])
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (name, temp) = line.rsplit_once(',')?;
+            Some(TemperatureReading {
+                label: name.trim().to_string(),
+                kind: SensorKind::Gpu,
+                celsius: temp.trim().parse().ok()?,
+                critical: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{FanReading, SensorKind, TemperatureReading};
+    use serde::Deserialize;
+    use std::process::Command;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct Sensor {
+        name: String,
+        /// LibreHardwareMonitor hardware path, e.g. "/amdcpu/0" or "/gpu-nvidia/0"
+        parent: String,
+        value: f32,
+    }
+
+    /// Sensors of one type ("Temperature", "Fan") from LibreHardwareMonitor or
+    /// OpenHardwareMonitor, whichever is running
+    fn hardware_monitor_sensors(sensor_type: &str) -> Vec<Sensor> {
+        ["root/LibreHardwareMonitor", "root/OpenHardwareMonitor"]
+            .iter()
+            .find_map(|namespace| {
+                let script = format!(
+                    "ConvertTo-Json -Compress -InputObject @(Get-CimInstance -Namespace {} -ClassName Sensor -ErrorAction Stop | Where-Object SensorType -eq '{}' | Select-Object Name, Parent, Value)",
+                    namespace, sensor_type
+                );
+                let output = Command::new("powershell")
+                    .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+                    .output()
+                    .ok()?;
+                if !output.status.success() {
+                    return None;
+                }
+                serde_json::from_slice::<Vec<Sensor>>(&output.stdout).ok()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn temperatures() -> Vec<TemperatureReading> {
+        hardware_monitor_sensors("Temperature")
+            .into_iter()
+            .map(|s| {
+                // The hardware path says more about the kind than "Core Average" does
+                let kind = if s.parent.contains("gpu") {
+                    SensorKind::Gpu
+                } else if s.parent.contains("cpu") {
+                    SensorKind::Cpu
+                } else if s.parent.contains("hdd") || s.parent.contains("nvme") {
+                    SensorKind::Disk
+                } else {
+                    SensorKind::classify(&s.name)
+                };
+                TemperatureReading { label: s.name, kind, celsius: s.value, critical: None }
+            })
+            .collect()
+    }
+
+    pub fn fans() -> Vec<FanReading> {
+        hardware_monitor_sensors("Fan")
+            .into_iter()
+            .map(|s| FanReading { label: s.name, rpm: s.value.max(0.0) as u32 })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{FanReading, TemperatureReading};
+
+    // sysinfo's components already cover the SMC temperatures
+    pub fn temperatures() -> Vec<TemperatureReading> {
+        Vec::new()
+    }
+
+    pub fn fans() -> Vec<FanReading> {
+        Vec::new()
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::{FanReading, TemperatureReading};
+
+    // sysinfo's components already cover the hwmon temperatures
+    pub fn temperatures() -> Vec<TemperatureReading> {
+        Vec::new()
+    }
+
+    /// hwmon exposes fanN_input (RPM) and, on some drivers, fanN_label
+    pub fn fans() -> Vec<FanReading> {
+        let Ok(hwmons) = std::fs::read_dir("/sys/class/hwmon") else {
+            return Vec::new();
+        };
+        let read = |path: std::path::PathBuf| std::fs::read_to_string(path).ok().map(|s| s.trim().to_string());
+
+        let mut fans = Vec::new();
+        for dir in hwmons.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let chip = read(dir.join("name")).unwrap_or_else(|| "fan".to_string());
+            for n in 1..=8 {
+                let Some(rpm) = read(dir.join(format!("fan{}_input", n))).and_then(|v| v.parse().ok()) else { continue };
+                let label = read(dir.join(format!("fan{}_label", n))).unwrap_or_else(|| format!("{} fan {}", chip, n));
+                fans.push(FanReading { label, rpm });
+            }
+        }
+        fans
+    }
+}
+
+/// Speak a warning when the CPU or GPU passes `max_celsius`, checking every
+/// 30 seconds for `duration`. Starting a new watch replaces the running one.
+pub fn start_thermal_watch(app: AppHandle, duration: Duration, max_celsius: f32) {
+    let generation = WATCH_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    info!("Watching temperatures for {} minutes (limit {:.0}°C)", duration.as_secs() / 60, max_celsius);
+
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let mut warned: Vec<SensorKind> = Vec::new();
+
+        while started.elapsed() < duration && WATCH_GENERATION.load(Ordering::SeqCst) == generation {
+            match tokio::task::spawn_blocking(temperatures).await {
+                Ok(readings) => {
+                    for reading in hottest(&readings) {
+                        if reading.celsius >= max_celsius && !warned.contains(&reading.kind) {
+                            warned.push(reading.kind);
+                            crate::voice::speak(&format!(
+                                "Heads up, your {} is running hot at {:.0} degrees.",
+                                reading.kind.label(),
+                                reading.celsius
+                            ));
+                            crate::commands::fire_system_event(&app, "temperature_high").await;
+                        } else if reading.celsius < max_celsius - THERMAL_HYSTERESIS_C {
+                            warned.retain(|k| *k != reading.kind);
+                        }
+                    }
+                }
+                Err(e) => warn!("Temperature check failed: {}", e),
+            }
+            tokio::time::sleep(THERMAL_WATCH_INTERVAL).await;
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_temperatures() -> Result<Vec<TemperatureReading>, String> {
+    tokio::task::spawn_blocking(temperatures).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_fan_speeds() -> Result<Vec<FanReading>, String> {
+    tokio::task::spawn_blocking(fans).await.map_err(|e| e.to_string())
+}
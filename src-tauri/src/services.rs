@@ -0,0 +1,177 @@
+// Services Module
+// Starts, stops and restarts system services ("restart the print spooler
+// service"). These usually need admin rights, so they go through the
+// elevation broker.
+
+use anyhow::Result;
+use tracing::info;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl ServiceAction {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "start" => Some(Self::Start),
+            "stop" => Some(Self::Stop),
+            "restart" => Some(Self::Restart),
+            _ => None,
+        }
+    }
+
+    fn past_tense(&self) -> &'static str {
+        match self {
+            Self::Start => "Started",
+            Self::Stop => "Stopped",
+            Self::Restart => "Restarted",
+        }
+    }
+}
+
+/// A spoken service request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceCommand {
+    pub action: ServiceAction,
+    pub name: String,
+}
+
+impl ServiceCommand {
+    /// "restart the print spooler service", "stop service docker"
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.trim().trim_end_matches(['.', '!', '?']).to_lowercase();
+        let (verb, rest) = lower.split_once(' ')?;
+        let action = ServiceAction::parse(verb)?;
+
+        let rest = rest.strip_prefix("the ").unwrap_or(rest);
+        let name = rest
+            .strip_suffix(" service")
+            .or_else(|| rest.strip_prefix("service "))?
+            .trim();
+        if name.is_empty() {
+            return None;
+        }
+        Some(Self { action, name: name.to_string() })
+    }
+}
+
+/// Start, stop or restart a service by name or display name. Blocking; may show an elevation prompt.
+pub fn control(name: &str, action: ServiceAction) -> Result<String> {
+    let name = platform::resolve_name(name)?;
+    info!("{:?} service {}", action, name);
+    platform::control(&name, action)?;
+    Ok(format!("{} the {} service", action.past_tense(), name))
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::ServiceAction;
+    use crate::elevation::run_privileged;
+    use anyhow::{bail, Result};
+    use std::process::Command;
+    use std::time::{Duration, Instant};
+
+    const STOP_TIMEOUT: Duration = Duration::from_secs(15);
+
+    /// Spoken names are usually display names ("Print Spooler" -> "Spooler")
+    pub fn resolve_name(name: &str) -> Result<String> {
+        let output = Command::new("sc").args(["getkeyname", name]).output()?;
+        let key = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .find_map(|line| line.split_once("Name = ").map(|(_, key)| key.trim().to_string()));
+        Ok(key.unwrap_or_else(|| name.to_string()))
+    }
+
+    fn is_stopped(name: &str) -> bool {
+        Command::new("sc")
+            .args(["query", name])
+            .output()
+            .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains("STOPPED"))
+    }
+
+    pub fn control(name: &str, action: ServiceAction) -> Result<()> {
+        match action {
+            ServiceAction::Start => run_privileged("sc", &["start", name]).map(|_| ()),
+            ServiceAction::Stop => run_privileged("sc", &["stop", name]).map(|_| ()),
+            ServiceAction::Restart => {
+                // sc has no restart and "stop" returns before the service is down
+                if !is_stopped(name) {
+                    run_privileged("sc", &["stop", name])?;
+                    let started = Instant::now();
+                    while !is_stopped(name) {
+                        if started.elapsed() > STOP_TIMEOUT {
+                            bail!("{} didn't stop within {} seconds", name, STOP_TIMEOUT.as_secs());
+                        }
+                        std::thread::sleep(Duration::from_millis(250));
+                    }
+                }
+                run_privileged("sc", &["start", name]).map(|_| ())
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::ServiceAction;
+    use crate::elevation::run_privileged;
+    use anyhow::Result;
+
+    /// launchd uses reverse-DNS labels; pass those through as given
+    pub fn resolve_name(name: &str) -> Result<String> {
+        Ok(name.replace(' ', "."))
+    }
+
+    pub fn control(name: &str, action: ServiceAction) -> Result<()> {
+        let target = format!("system/{}", name);
+        match action {
+            ServiceAction::Start => run_privileged("launchctl", &["kickstart", &target]),
+            ServiceAction::Stop => run_privileged("launchctl", &["kill", "SIGTERM", &target]),
+            ServiceAction::Restart => run_privileged("launchctl", &["kickstart", "-k", &target]),
+        }
+        .map(|_| ())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::ServiceAction;
+    use crate::elevation::run_privileged;
+    use anyhow::Result;
+
+    /// "network manager" -> "network-manager"; systemd appends ".service" itself
+    pub fn resolve_name(name: &str) -> Result<String> {
+        Ok(name.trim().replace(' ', "-"))
+    }
+
+    pub fn control(name: &str, action: ServiceAction) -> Result<()> {
+        let verb = match action {
+            ServiceAction::Start => "start",
+            ServiceAction::Stop => "stop",
+            ServiceAction::Restart => "restart",
+        };
+        // Without a terminal, systemctl can't ask polkit itself and reports
+        // "Interactive authentication required", which triggers pkexec
+        run_privileged("systemctl", &[verb, name]).map(|_| ())
+    }
+}
+
+// ========== Tauri Commands ==========
+
+/// `action` is "start", "stop" or "restart"
+#[tauri::command]
+pub async fn control_service(name: String, action: String) -> Result<String, String> {
+    let action = ServiceAction::parse(&action).ok_or_else(|| format!("Unknown service action: {}", action))?;
+    if name.trim().is_empty() {
+        return Err("No service name given".to_string());
+    }
+    tokio::task::spawn_blocking(move || control(&name, action))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
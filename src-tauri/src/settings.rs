@@ -1,128 +1,638 @@
+// Settings Module
+// The one settings model for the app, kept in the tauri store under
+// `app_settings` and grouped into typed sections (general, voice, tts, llm,
+// ...). Every module reads it with `load_settings` and changes it with
+// `modify_settings`, which saves under a lock, emits `settings-changed` with
+// the sections that changed and reconfigures the running engines. The UI edits single values
+// with `update_setting("section.field", value)`.
+//
+// Older versions kept a flat settings object plus separate config files
+// (`ASTRAL/config.json`, `whisper_config.json`, `vosk_config.json`,
+// `ASTRAL/file_indexer.json`); they are folded in the first time settings are
+// loaded, and the last two are removed once the store has them.
+//
+// API keys are encrypted in the store (see `secrets`) and decrypted on load,
+// so everything else only ever sees plain values.
+
+use crate::appearance::Theme;
+use crate::elevenlabs_tts::ElevenLabsConfig;
+use crate::llm_provider::{LLMConfig, LLMProvider};
+use crate::state::AppState;
+use crate::voice::wake_word::WakeWordConfig;
+use crate::voice::whisper::WhisperConfig;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_store::StoreExt;
+use tracing::{info, warn};
+
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Held from reading the settings to writing them back in `update_setting`
+/// and `modify_settings`, so two changes can't each save over the other
+static WRITE_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+const STORE_FILE: &str = "settings.json";
+const STORE_KEY: &str = "app_settings";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppSettings {
-    pub whisper_enabled: bool,
-    pub whisper_server_url: String,
-    pub whisper_model: String,
-    pub elevenlabs_enabled: bool,
-    pub elevenlabs_api_key: String,
-    pub elevenlabs_voice_id: String,
-    pub elevenlabs_model_id: String,
-    pub llm_provider: String,
-    pub llm_model: String,
-    pub llm_api_key: Option<String>,
-    pub ollama_url: String,
-    pub wake_word_enabled: bool,
-    pub theme: String,
-}
-
-impl Default for AppSettings {
+#[serde(default)]
+pub struct GeneralSettings {
+    pub theme: Theme,
+    /// Launch at login
+    pub auto_start: bool,
+    pub hotkeys: crate::hotkeys::HotkeyBindings,
+    /// Routine id run by the favorite-routine hotkey
+    pub favorite_routine: String,
+    /// Routine ids listed in the tray's Favorite Routines menu; empty = all
+    pub favorite_routines: Vec<String>,
+    pub update_channel: crate::updater::UpdateChannel,
+    /// Look for updates at launch and every few hours
+    pub auto_check_updates: bool,
+    /// BCP 47 tag for the language ASTRAL listens, answers and speaks in
+    pub locale: String,
+    /// Strict offline mode: no cloud services, only Ollama, local Whisper and on-device speech
+    pub privacy_mode: bool,
+}
+
+impl Default for GeneralSettings {
     fn default() -> Self {
         Self {
-            whisper_enabled: false,
-            whisper_server_url: "http://localhost:9881".to_string(),
-            whisper_model: "base.en".to_string(),
-            elevenlabs_enabled: false,
-            elevenlabs_api_key: String::new(),
-            elevenlabs_voice_id: "21m00Tcm4TlvDq8ikWAM".to_string(),
-            elevenlabs_model_id: "eleven_turbo_v2_5".to_string(),
-            llm_provider: "Ollama".to_string(),
-            llm_model: "mistral:latest".to_string(),
-            llm_api_key: None,
-            ollama_url: "http://localhost:11434".to_string(),
-            wake_word_enabled: false,
-            theme: "dark".to_string(),
+            theme: Theme::Dark,
+            auto_start: false,
+            hotkeys: crate::hotkeys::HotkeyBindings::default(),
+            favorite_routine: String::new(),
+            favorite_routines: Vec::new(),
+            update_channel: crate::updater::UpdateChannel::Stable,
+            auto_check_updates: true,
+            locale: crate::locale::DEFAULT_LOCALE.to_string(),
+            privacy_mode: false,
         }
     }
 }
 
-#[tauri::command]
-pub async fn load_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
-    let store = app.store("settings.json")
-        .map_err(|e| format!("Failed to access store: {}", e))?;
-    
-    // Try to get saved settings
-    let settings = match store.get("app_settings") {
-        Some(value) => {
-            serde_json::from_value(value.clone())
-                .unwrap_or_else(|_| AppSettings::default())
-        },
-        None => AppSettings::default(),
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceSettings {
+    pub whisper: WhisperConfig,
+    pub wake_word: WakeWordConfig,
+    pub interaction: crate::voice::interaction::InteractionConfig,
+    /// Offline keyword commands
+    pub vosk: crate::voice::vosk::VoskConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommandSettings {
+    /// Transcripts below this STT confidence (0.0 - 1.0) get a clarifying
+    /// question instead of being executed
+    pub stt_confidence_threshold: f32,
+    /// Apps that can be closed by voice without a confirmation question
+    pub process_kill_allowlist: Vec<String>,
+    /// Let the LLM classify commands the built-in patterns don't recognize
+    pub llm_intent_classification: bool,
+}
+
+impl Default for CommandSettings {
+    fn default() -> Self {
+        Self {
+            stt_confidence_threshold: 0.6,
+            process_kill_allowlist: Vec::new(),
+            llm_intent_classification: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemSettings {
+    /// Speak a warning when a drive has less than this many GB free; 0 = off
+    pub low_disk_alert_gb: u64,
+    pub alert_rules: Vec<crate::alerts::AlertRule>,
+    /// How often live `system-stats` events are pushed to the UI; 0 = off
+    pub stats_stream_interval_ms: u64,
+    /// "HH:MM" to speak a system health report every day; empty = off
+    pub system_report_time: String,
+}
+
+impl Default for SystemSettings {
+    fn default() -> Self {
+        Self {
+            low_disk_alert_gb: 0,
+            alert_rules: crate::alerts::default_rules(),
+            stats_stream_interval_ms: 2000,
+            system_report_time: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiSettings {
+    /// Serve the local HTTP/WebSocket control API on 127.0.0.1 (next launch)
+    pub enabled: bool,
+    pub port: u16,
+    /// Bearer token for the local API; generated the first time it's needed
+    pub token: String,
+    /// Access from other devices on the LAN or tailnet
+    pub remote: crate::remote_access::RemoteAccessSettings,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7865,
+            token: String::new(),
+            remote: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillSettings {
+    /// Skill id -> what the user approved for it
+    pub approved: HashMap<String, crate::skills::SkillApproval>,
+    /// Approved skills whose tools ASTRAL's own LLM may call, not just MCP clients
+    pub llm_tools: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsSettings {
+    /// The user agreed to send crash reports they choose to submit
+    pub crash_reports_consent: bool,
+    /// Where submitted crash reports are POSTed as JSON
+    pub crash_report_endpoint: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub general: GeneralSettings,
+    pub voice: VoiceSettings,
+    pub tts: ElevenLabsConfig,
+    pub llm: LLMConfig,
+    pub commands: CommandSettings,
+    pub system: SystemSettings,
+    pub api: ApiSettings,
+    pub skills: SkillSettings,
+    pub diagnostics: DiagnosticsSettings,
+    pub profiles: crate::profiles::ProfileSettings,
+    pub permissions: crate::permissions::PermissionSettings,
+    pub sync: crate::sync::SyncSettings,
+    pub search: crate::web_search::SearchSettings,
+    pub calendar: crate::calendar::CalendarSettings,
+    pub email: crate::email::EmailSettings,
+    pub home_assistant: crate::home_assistant::HomeAssistantSettings,
+    pub mqtt: crate::mqtt::MqttSettings,
+    pub hue: crate::hue::HueSettings,
+    pub chat_bridge: crate::chat_bridge::ChatBridgeSettings,
+    pub knowledge: crate::knowledge_base::KnowledgeSettings,
+    pub webhooks: crate::webhooks::WebhookSettings,
+    pub personas: crate::personas::PersonaSettings,
+    pub usage: crate::usage::UsageSettings,
+    pub response_cache: crate::response_cache::CacheSettings,
+    pub proxy: crate::proxy::ProxySettings,
+    pub file_index: crate::file_indexer::IndexerConfig,
+}
+
+impl AppSettings {
+    /// Refuse values the types alone don't rule out, and tidy the rest
+    fn validate(&mut self) -> Result<(), String> {
+        if self.api.port < 1024 {
+            return Err(format!("Invalid API port: {}", self.api.port));
+        }
+        if !(0.0..=1.0).contains(&self.commands.stt_confidence_threshold) {
+            return Err("The STT confidence threshold must be between 0 and 1".to_string());
+        }
+        if self.llm.history_budget_tokens < 500 {
+            return Err("The conversation history needs a budget of at least 500 tokens".to_string());
+        }
+        self.voice.interaction.validate()?;
+        self.api.remote.validate()?;
+        self.sync.validate()?;
+        self.search.validate()?;
+        self.calendar.validate()?;
+        self.email.validate()?;
+        self.home_assistant.validate()?;
+        self.mqtt.validate()?;
+        self.hue.validate()?;
+        self.chat_bridge.validate()?;
+        self.knowledge.validate()?;
+        self.webhooks.validate()?;
+        self.personas.validate()?;
+        self.usage.validate()?;
+        self.response_cache.validate()?;
+        self.proxy.validate()?;
+        if self.api.remote.enabled && self.api.remote.port == self.api.port {
+            return Err("The local and remote API need different ports".to_string());
+        }
+        self.general.locale = crate::locale::normalize(&self.general.locale)?;
+        self.general.favorite_routine = self.general.favorite_routine.trim().to_string();
+        self.system.system_report_time = self.system.system_report_time.trim().to_string();
+        self.diagnostics.crash_report_endpoint = self.diagnostics.crash_report_endpoint.trim().to_string();
+        Ok(())
+    }
+}
+
+// ========== Migration ==========
+
+/// Flat keys of the old settings object and where they live now
+const LEGACY_KEYS: &[(&str, &str)] = &[
+    ("whisper_enabled", "/voice/whisper/enabled"),
+    ("whisper_server_url", "/voice/whisper/server_url"),
+    ("whisper_model", "/voice/whisper/model"),
+    ("wake_word_enabled", "/voice/wake_word/enabled"),
+    ("elevenlabs_enabled", "/tts/enabled"),
+    ("elevenlabs_api_key", "/tts/api_key"),
+    ("elevenlabs_voice_id", "/tts/voice_id"),
+    ("elevenlabs_model_id", "/tts/model_id"),
+    ("llm_model", "/llm/model"),
+    ("llm_api_key", "/llm/api_key"),
+    ("ollama_url", "/llm/ollama_url"),
+    ("theme", "/general/theme"),
+    ("hotkeys", "/general/hotkeys"),
+    ("favorite_routine", "/general/favorite_routine"),
+    ("favorite_routines", "/general/favorite_routines"),
+    ("update_channel", "/general/update_channel"),
+    ("auto_check_updates", "/general/auto_check_updates"),
+    ("stt_confidence_threshold", "/commands/stt_confidence_threshold"),
+    ("process_kill_allowlist", "/commands/process_kill_allowlist"),
+    ("llm_intent_classification", "/commands/llm_intent_classification"),
+    ("low_disk_alert_gb", "/system/low_disk_alert_gb"),
+    ("alert_rules", "/system/alert_rules"),
+    ("stats_stream_interval_ms", "/system/stats_stream_interval_ms"),
+    ("system_report_time", "/system/system_report_time"),
+    ("api_enabled", "/api/enabled"),
+    ("api_port", "/api/port"),
+    ("api_token", "/api/token"),
+    ("approved_skills", "/skills/approved"),
+    ("crash_reports_consent", "/diagnostics/crash_reports_consent"),
+    ("crash_report_endpoint", "/diagnostics/crash_report_endpoint"),
+];
+
+fn is_legacy(stored: &Value) -> bool {
+    stored.get("llm_provider").is_some() || LEGACY_KEYS.iter().any(|(key, _)| stored.get(key).is_some())
+}
+
+/// `ASTRAL/config.json` held the launch-at-login flag
+fn legacy_auto_start() -> Option<bool> {
+    let path = dirs::config_dir()?.join("ASTRAL").join("config.json");
+    let config: Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    config.get("auto_start")?.as_bool()
+}
+
+/// The dashboard's Whisper toggle used to write only this file
+fn legacy_whisper_config(app: &AppHandle) -> Option<WhisperConfig> {
+    let path = app.path().app_config_dir().ok()?.join("whisper_config.json");
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Config files modules kept before they became sections
+fn side_files(app: &AppHandle) -> (Option<PathBuf>, Option<PathBuf>) {
+    let vosk = app.path().app_config_dir().ok().map(|dir| dir.join("vosk_config.json"));
+    let indexer = dirs::config_dir().map(|dir| dir.join("ASTRAL").join("file_indexer.json"));
+    (vosk, indexer)
+}
+
+fn read_side_file<T: serde::de::DeserializeOwned>(path: &std::path::Path) -> Option<T> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content)
+        .map_err(|e| warn!("Couldn't read {}, keeping the defaults: {}", path.display(), e))
+        .ok()
+}
+
+/// Fold the side files into `settings` where `stored` doesn't have their
+/// section yet, returning the files found so they can be removed once the
+/// store is written. A file left behind is never read over its section.
+fn fold_side_files(app: &AppHandle, stored: Option<&Value>, settings: &mut AppSettings) -> Vec<PathBuf> {
+    let missing = |pointer: &str| stored.and_then(|v| v.pointer(pointer)).is_none();
+    let (vosk, indexer) = side_files(app);
+    let mut found = Vec::new();
+    if let Some(path) = vosk.filter(|p| p.exists()) {
+        if missing("/voice/vosk") {
+            if let Some(vosk) = read_side_file(&path) {
+                settings.voice.vosk = vosk;
+            }
+        }
+        found.push(path);
+    }
+    if let Some(path) = indexer.filter(|p| p.exists()) {
+        if missing("/file_index") {
+            if let Some(file_index) = read_side_file(&path) {
+                settings.file_index = file_index;
+            }
+        }
+        found.push(path);
+    }
+    found
+}
+
+fn remove_side_files(files: &[PathBuf]) {
+    for path in files {
+        match std::fs::remove_file(path) {
+            Ok(()) => info!("Moved {} into the settings store", path.display()),
+            Err(e) => warn!("Couldn't remove {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Build settings from an old flat store value (or none) and the old config
+/// files, returning the side files to remove once the result is stored
+fn migrate(app: &AppHandle, stored: Option<&Value>) -> (AppSettings, Vec<PathBuf>) {
+    let mut tree = serde_json::to_value(AppSettings::default()).unwrap_or(Value::Null);
+    if let Some(stored) = stored {
+        for (key, pointer) in LEGACY_KEYS {
+            if let (Some(value), Some(slot)) = (stored.get(key), tree.pointer_mut(pointer)) {
+                *slot = value.clone();
+            }
+        }
+        let provider = stored.get("llm_provider").and_then(Value::as_str).and_then(LLMProvider::from_setting);
+        if let (Some(provider), Some(slot)) = (provider, tree.pointer_mut("/llm/provider")) {
+            *slot = serde_json::to_value(provider).unwrap_or(Value::Null);
+        }
+    }
+
+    let mut settings: AppSettings = serde_json::from_value(tree).unwrap_or_else(|e| {
+        warn!("Couldn't carry over the old settings, starting from defaults: {}", e);
+        AppSettings::default()
+    });
+    if let Some(auto_start) = legacy_auto_start() {
+        settings.general.auto_start = auto_start;
+    }
+    if let Some(whisper) = legacy_whisper_config(app) {
+        settings.voice.whisper = whisper;
+    }
+    let side_files = fold_side_files(app, None, &mut settings);
+    (settings, side_files)
+}
+
+// ========== Load / Save ==========
+
+/// JSON pointers to the API keys in a serialized `AppSettings`, including
+/// each profile's LLM key, the Brave Search key, the WebDAV sync password and
+/// private calendar feed URLs. These are encrypted in the store by `secrets`; the
+/// local API token stays readable so the CLI can use it.
+pub(crate) fn secret_pointers(tree: &Value) -> Vec<String> {
+    let mut pointers = vec![
+        "/tts/api_key".to_string(),
+        "/llm/api_key".to_string(),
+        "/sync/webdav_password".to_string(),
+        "/sync/s3_secret_access_key".to_string(),
+        "/search/brave_api_key".to_string(),
+        "/calendar/google_client_secret".to_string(),
+        "/calendar/google_refresh_token".to_string(),
+        "/email/password".to_string(),
+        "/email/google_refresh_token".to_string(),
+        "/home_assistant/token".to_string(),
+        "/mqtt/password".to_string(),
+        "/hue/username".to_string(),
+        "/chat_bridge/telegram/token".to_string(),
+        "/chat_bridge/discord/token".to_string(),
+        "/proxy/password".to_string(),
+        "/api/remote/token".to_string(),
+    ];
+    // Private calendar feed URLs carry their key too
+    let feeds = tree.pointer("/calendar/ics_urls").and_then(Value::as_array).map_or(0, Vec::len);
+    pointers.extend((0..feeds).map(|i| format!("/calendar/ics_urls/{}", i)));
+    let profiles = tree.pointer("/profiles/list").and_then(Value::as_array).map_or(0, Vec::len);
+    pointers.extend((0..profiles).map(|i| format!("/profiles/list/{}/llm/api_key", i)));
+    // Slack and IFTTT webhook URLs carry their key
+    let webhooks = tree.pointer("/webhooks/targets").and_then(Value::as_array).map_or(0, Vec::len);
+    for i in 0..webhooks {
+        pointers.push(format!("/webhooks/targets/{}/url", i));
+        pointers.push(format!("/webhooks/targets/{}/token", i));
+    }
+    pointers
+}
+
+fn seal_secrets(tree: &mut Value) -> Result<(), String> {
+    for pointer in secret_pointers(tree) {
+        if let Some(slot) = tree.pointer_mut(&pointer) {
+            if let Some(plain) = slot.as_str().filter(|s| !s.is_empty() && !crate::secrets::is_sealed(s)) {
+                *slot = Value::String(crate::secrets::seal(plain)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decrypt the secret fields in place. Returns true if any were still in
+/// plain text and the store should be rewritten.
+fn open_secrets(tree: &mut Value) -> bool {
+    let mut plain_found = false;
+    for pointer in secret_pointers(tree) {
+        let Some(slot) = tree.pointer_mut(&pointer) else { continue };
+        let Some(stored) = slot.as_str().filter(|s| !s.is_empty()) else { continue };
+        if !crate::secrets::is_sealed(stored) {
+            plain_found = true;
+            continue;
+        }
+        match crate::secrets::open(stored) {
+            Ok(plain) => *slot = Value::String(plain),
+            Err(e) => {
+                // A store copied from another machine or user; the key has to be entered again
+                warn!("Clearing {}: {}", pointer, e);
+                *slot = if pointer.ends_with("/llm/api_key") { Value::Null } else { Value::String(String::new()) };
+            }
+        }
+    }
+    plain_found
+}
+
+fn write_store(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| format!("Failed to access store: {}", e))?;
+    let mut value = serde_json::to_value(settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    seal_secrets(&mut value)?;
+    store.set(STORE_KEY, value);
+    store.save().map_err(|e| format!("Failed to save store: {}", e))
+}
+
+/// Top-level sections whose contents differ
+fn changed_sections(old: &AppSettings, new: &AppSettings) -> Vec<String> {
+    let (Ok(Value::Object(old)), Ok(Value::Object(new))) = (serde_json::to_value(old), serde_json::to_value(new)) else {
+        return Vec::new();
     };
-    
-    Ok(settings)
+    new.iter()
+        .filter(|(section, value)| old.get(section.as_str()) != Some(value))
+        .map(|(section, _)| section.clone())
+        .collect()
+}
+
+/// Bring the running parts of the app in line with changed sections
+async fn apply_changes(app: &AppHandle, old: &AppSettings, new: &AppSettings, changed: &[String]) {
+    let changed = |section: &str| changed.iter().any(|s| s == section);
+
+    // Before the engines are reconfigured, which rebuilds their HTTP clients
+    if changed("proxy") {
+        crate::proxy::set(new.proxy.clone());
+    }
+    if changed("llm") || changed("tts") || changed("personas") || changed("proxy") || old.general.locale != new.general.locale {
+        app.state::<AppState>().configure(new).await;
+    }
+    if changed("voice") {
+        crate::voice::wake_word::set_config(new.voice.wake_word.clone());
+    }
+    if old.general.favorite_routines != new.general.favorite_routines {
+        crate::tray::refresh_routines(app).await;
+    }
+    if changed("profiles") {
+        crate::tray::refresh_profiles(app).await;
+    }
+    if old.general.privacy_mode != new.general.privacy_mode {
+        crate::privacy::set_enabled(app, new.general.privacy_mode);
+    }
+    if old.api.remote != new.api.remote {
+        crate::api_server::update_remote_policy(&new.api.remote);
+    }
+    if changed("sync") && new.sync.enabled {
+        crate::sync::wake();
+    }
+    if changed("calendar") {
+        crate::calendar::wake();
+    }
+    if changed("home_assistant") {
+        crate::home_assistant::wake();
+    }
+    if changed("mqtt") || changed("proxy") {
+        crate::mqtt::wake();
+    }
+    if changed("hue") {
+        crate::hue::wake();
+    }
+    if changed("chat_bridge") || changed("proxy") {
+        crate::chat_bridge::wake();
+    }
+    if changed("knowledge") {
+        crate::knowledge_base::wake();
+    }
+    if changed("permissions") {
+        crate::permissions::set(new.permissions.clone());
+    }
+    if changed("usage") {
+        crate::usage::set(new.usage.clone());
+    }
+    if changed("response_cache") {
+        crate::response_cache::set(new.response_cache.clone());
+    }
+    if changed("file_index") {
+        crate::file_indexer::set(new.file_index.clone());
+    }
+    if old.general.auto_start != new.general.auto_start {
+        if let Err(e) = crate::autostart::apply(app, new.general.auto_start) {
+            warn!("Failed to update launch at login: {}", e);
+        }
+    }
 }
 
 #[tauri::command]
-pub async fn save_settings(app: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
-    let store = app.store("settings.json")
-        .map_err(|e| format!("Failed to access store: {}", e))?;
-    
-    let value = serde_json::to_value(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    
-    store.set("app_settings", value);
-    store.save().map_err(|e| format!("Failed to save store: {}", e))?;
-    
-    Ok(())
+pub async fn load_settings(app: AppHandle) -> Result<AppSettings, String> {
+    let store = app.store(STORE_FILE).map_err(|e| format!("Failed to access store: {}", e))?;
+
+    match store.get(STORE_KEY) {
+        Some(mut value) if !is_legacy(&value) => {
+            let needs_sealing = open_secrets(&mut value);
+            let mut settings: AppSettings = match serde_json::from_value(value.clone()) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    warn!("Settings store doesn't parse ({}), reading it section by section", e);
+                    parse_sections(value.clone())
+                }
+            };
+            let side_files = fold_side_files(&app, Some(&value), &mut settings);
+            if needs_sealing || !side_files.is_empty() {
+                write_store(&app, &settings)?;
+                remove_side_files(&side_files);
+                if needs_sealing {
+                    info!("Encrypted API keys in the settings store");
+                }
+            }
+            Ok(settings)
+        }
+        stored => {
+            let (settings, side_files) = migrate(&app, stored.as_ref());
+            write_store(&app, &settings)?;
+            remove_side_files(&side_files);
+            info!("Settings moved to the sectioned format");
+            Ok(settings)
+        }
+    }
+}
+
+/// Keep every section of `stored` that parses; the ones that don't get their
+/// defaults rather than taking the whole store down with them
+fn parse_sections(stored: Value) -> AppSettings {
+    let mut tree = serde_json::to_value(AppSettings::default()).unwrap_or_default();
+    if let Value::Object(sections) = stored {
+        for (name, section) in sections {
+            let mut candidate = tree.clone();
+            candidate[name.as_str()] = section.clone();
+            match serde_json::from_value::<AppSettings>(candidate) {
+                Ok(_) => tree[name.as_str()] = section,
+                Err(e) => warn!("Settings section '{}' couldn't be read, using its defaults: {}", name, e),
+            }
+        }
+    }
+    serde_json::from_value(tree).unwrap_or_default()
 }
 
 #[tauri::command]
-pub async fn update_setting(
-    app: tauri::AppHandle, 
-    key: String, 
-    value: serde_json::Value
-) -> Result<(), String> {
-    let store = app.store("settings.json")
-        .map_err(|e| format!("Failed to access store: {}", e))?;
-    
-    // Load current settings
-    let mut settings: AppSettings = match store.get("app_settings") {
-        Some(v) => serde_json::from_value(v.clone()).unwrap_or_default(),
-        None => AppSettings::default(),
-    };
-    
-    // Update specific field
-    match key.as_str() {
-        "whisper_enabled" => settings.whisper_enabled = value.as_bool().unwrap_or(false),
-        "whisper_server_url" => settings.whisper_server_url = value.as_str().unwrap_or("").to_string(),
-        "whisper_model" => settings.whisper_model = value.as_str().unwrap_or("").to_string(),
-        "elevenlabs_enabled" => settings.elevenlabs_enabled = value.as_bool().unwrap_or(false),
-        "elevenlabs_api_key" => settings.elevenlabs_api_key = value.as_str().unwrap_or("").to_string(),
-        "elevenlabs_voice_id" => settings.elevenlabs_voice_id = value.as_str().unwrap_or("").to_string(),
-        "elevenlabs_model_id" => settings.elevenlabs_model_id = value.as_str().unwrap_or("").to_string(),
-        "llm_provider" => settings.llm_provider = value.as_str().unwrap_or("").to_string(),
-        "llm_model" => settings.llm_model = value.as_str().unwrap_or("").to_string(),
-        "llm_api_key" => settings.llm_api_key = value.as_str().map(|s| s.to_string()),
-        "ollama_url" => settings.ollama_url = value.as_str().unwrap_or("").to_string(),
-        "wake_word_enabled" => settings.wake_word_enabled = value.as_bool().unwrap_or(false),
-        "theme" => settings.theme = value.as_str().unwrap_or("dark").to_string(),
-        _ => return Err(format!("Unknown setting key: {}", key)),
-    }
-    
-    // Save updated settings
-    let settings_value = serde_json::to_value(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    
-    store.set("app_settings", settings_value);
-    store.save().map_err(|e| format!("Failed to save store: {}", e))?;
-    
+pub async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
+    let _write = WRITE_LOCK.lock().await;
+    save(app, settings).await
+}
+
+async fn save(app: AppHandle, settings: AppSettings) -> Result<(), String> {
+    let mut settings = settings;
+    settings.validate()?;
+    let old = load_settings(app.clone()).await?;
+    write_store(&app, &settings)?;
+
+    let changed = changed_sections(&old, &settings);
+    if changed.is_empty() {
+        return Ok(());
+    }
+    apply_changes(&app, &old, &settings, &changed).await;
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, &changed) {
+        warn!("Failed to emit settings change: {}", e);
+    }
     Ok(())
 }
 
+/// Load the settings, let `change` edit them and save the result, holding
+/// the write lock throughout so a save in between can't be lost. Nothing is
+/// saved when `change` fails.
+pub async fn modify_settings<T, E: From<String>>(
+    app: &AppHandle,
+    change: impl FnOnce(&mut AppSettings) -> Result<T, E>,
+) -> Result<T, E> {
+    let _write = WRITE_LOCK.lock().await;
+    let mut settings = load_settings(app.clone()).await?;
+    let result = change(&mut settings)?;
+    save(app.clone(), settings).await?;
+    Ok(result)
+}
+
+/// Set one value by its dotted path, e.g. `general.theme` or `voice.whisper.enabled`
 #[tauri::command]
-pub async fn reset_settings(app: tauri::AppHandle) -> Result<(), String> {
-    let store = app.store("settings.json")
-        .map_err(|e| format!("Failed to access store: {}", e))?;
-    
-    let default_settings = AppSettings::default();
-    let value = serde_json::to_value(&default_settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    
-    store.set("app_settings", value);
-    store.save().map_err(|e| format!("Failed to save store: {}", e))?;
-    
-    Ok(())
+pub async fn update_setting(app: AppHandle, key: String, value: Value) -> Result<(), String> {
+    modify_settings(&app, |settings| {
+        let mut tree = serde_json::to_value(&*settings).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        let pointer = format!("/{}", key.replace('.', "/"));
+        let slot = tree.pointer_mut(&pointer).ok_or_else(|| format!("Unknown setting key: {}", key))?;
+        *slot = value;
+        *settings = serde_json::from_value(tree).map_err(|e| format!("Invalid value for {}: {}", key, e))?;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn reset_settings(app: AppHandle) -> Result<(), String> {
+    save_settings(app, AppSettings::default()).await
 }
@@ -0,0 +1,334 @@
+// Setup Module
+// Checks for everything ASTRAL depends on outside itself (Ollama and its
+// models, the Whisper server, Piper, the microphone and API keys) for the
+// onboarding wizard. Each check says what's wrong in plain words and, when
+// it can, offers a fix the UI turns into a button: a download link, a model
+// to pull, or the settings section to open.
+
+use crate::llm_provider::LLMProvider;
+use crate::settings::AppSettings;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+const OLLAMA_DOWNLOAD_URL: &str = "https://ollama.com/download";
+const PIPER_DOWNLOAD_URL: &str = "https://github.com/rhasspy/piper/releases";
+const PIPER_VOICES_URL: &str = "https://huggingface.co/rhasspy/piper-voices";
+const WHISPER_SETUP_URL: &str = "https://github.com/precie21/astral-assistant/blob/main/WHISPER_SETUP.md";
+const OPENAI_KEYS_URL: &str = "https://platform.openai.com/api-keys";
+const CLAUDE_KEYS_URL: &str = "https://console.anthropic.com/settings/keys";
+const AZURE_PORTAL_URL: &str = "https://portal.azure.com";
+const OPENROUTER_KEYS_URL: &str = "https://openrouter.ai/settings/keys";
+const ELEVENLABS_URL: &str = "https://elevenlabs.io/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    /// Works, but something optional is missing or degraded
+    Warning,
+    Error,
+    /// Not needed with the current settings
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FixAction {
+    OpenUrl { url: String },
+    /// Run `pull_ollama_model` with this model
+    PullOllamaModel { model: String },
+    /// Settings section to show, e.g. "llm"
+    OpenSettings { section: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticCheck {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub suggestion: Option<String>,
+    pub fix: Option<FixAction>,
+}
+
+impl DiagnosticCheck {
+    fn new(id: &'static str, title: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            id,
+            title,
+            status,
+            detail: detail.into(),
+            suggestion: None,
+            fix: None,
+        }
+    }
+
+    fn suggest(mut self, suggestion: impl Into<String>, fix: Option<FixAction>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self.fix = fix;
+        self
+    }
+}
+
+fn open_url(url: &str) -> Option<FixAction> {
+    Some(FixAction::OpenUrl { url: url.to_string() })
+}
+
+fn open_settings(section: &str) -> Option<FixAction> {
+    Some(FixAction::OpenSettings { section: section.to_string() })
+}
+
+/// "mistral" and "mistral:latest" are the same model
+fn same_model(a: &str, b: &str) -> bool {
+    let normalize = |name: &str| if name.contains(':') { name.to_string() } else { format!("{}:latest", name) };
+    normalize(a) == normalize(b)
+}
+
+/// Ollama being reachable, then the configured model being installed
+async fn check_ollama(settings: &AppSettings) -> Vec<DiagnosticCheck> {
+    let in_use = settings.llm.provider == LLMProvider::Ollama;
+    let missing_status = if in_use { CheckStatus::Error } else { CheckStatus::Skipped };
+    let url = crate::ollama::base_url(&settings.llm);
+
+    let Ok(models) = crate::ollama::installed_models(&url, Duration::from_secs(3)).await else {
+        let check = DiagnosticCheck::new("ollama", "Ollama", missing_status, format!("Ollama isn't answering at {}", url));
+        return vec![check.suggest(
+            "Install Ollama, then start it with `ollama serve`",
+            open_url(OLLAMA_DOWNLOAD_URL),
+        )];
+    };
+
+    let mut checks = vec![DiagnosticCheck::new(
+        "ollama",
+        "Ollama",
+        CheckStatus::Ok,
+        format!("Running at {} with {} model(s)", url, models.len()),
+    )];
+
+    let model = settings.llm.model.trim();
+    if !in_use {
+        checks.push(DiagnosticCheck::new("ollama_model", "Ollama model", CheckStatus::Skipped, "Ollama isn't the selected provider"));
+    } else if models.iter().any(|m| same_model(&m.name, model)) {
+        checks.push(DiagnosticCheck::new("ollama_model", "Ollama model", CheckStatus::Ok, format!("{} is installed", model)));
+    } else {
+        let check = DiagnosticCheck::new("ollama_model", "Ollama model", CheckStatus::Error, format!("{} isn't installed", model));
+        checks.push(check.suggest(
+            format!("Download it (ollama pull {})", model),
+            Some(FixAction::PullOllamaModel { model: model.to_string() }),
+        ));
+    }
+    checks
+}
+
+async fn check_whisper(settings: &AppSettings) -> DiagnosticCheck {
+    let config = &settings.voice.whisper;
+    if !config.enabled {
+        return DiagnosticCheck::new("whisper", "Whisper speech recognition", CheckStatus::Skipped, "Whisper is turned off; the browser's recognizer is used");
+    }
+
+    let healthy = crate::voice::whisper::WhisperEngine::new(config.clone())
+        .health_check()
+        .await
+        .unwrap_or(false);
+    if !healthy {
+        let check = DiagnosticCheck::new(
+            "whisper",
+            "Whisper speech recognition",
+            CheckStatus::Error,
+            format!("The Whisper server isn't answering at {}", config.server_url),
+        );
+        return check.suggest(
+            "Run install-whisper.ps1 (Windows) or install-whisper.sh, then start the server",
+            open_url(WHISPER_SETUP_URL),
+        );
+    }
+
+    let language = crate::locale::language_of(&settings.general.locale);
+    if language != "en" && config.model.ends_with(".en") {
+        let check = DiagnosticCheck::new(
+            "whisper",
+            "Whisper speech recognition",
+            CheckStatus::Warning,
+            format!("The {} model only understands English", config.model),
+        );
+        return check.suggest("Switch to a multilingual model such as base or small", open_url(WHISPER_SETUP_URL));
+    }
+
+    DiagnosticCheck::new("whisper", "Whisper speech recognition", CheckStatus::Ok, format!("Server running at {}", config.server_url))
+}
+
+fn piper_dir() -> Option<PathBuf> {
+    let mut path = dirs::data_dir()?;
+    path.push("com.astral.app");
+    path.push("piper");
+    Some(path)
+}
+
+fn find_piper() -> Option<PathBuf> {
+    let name = if cfg!(windows) { "piper.exe" } else { "piper" };
+    let bundled = piper_dir().map(|dir| dir.join(name));
+    let on_path = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).map(|dir| dir.join(name)).collect::<Vec<_>>())
+        .unwrap_or_default();
+    bundled.into_iter().chain(on_path).find(|path| path.is_file())
+}
+
+fn piper_voices() -> usize {
+    let Some(dir) = piper_dir().map(|dir| dir.join("voices")) else {
+        return 0;
+    };
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "onnx"))
+        .count()
+}
+
+/// Piper is an optional offline voice, so nothing here is an error
+fn check_piper() -> Vec<DiagnosticCheck> {
+    let Some(binary) = find_piper() else {
+        let check = DiagnosticCheck::new("piper", "Piper offline voice", CheckStatus::Warning, "Piper isn't installed");
+        let suggestion = format!("Optional: download Piper into {:?} or put it on your PATH", piper_dir().unwrap_or_default());
+        return vec![check.suggest(suggestion, open_url(PIPER_DOWNLOAD_URL))];
+    };
+
+    let mut checks = vec![DiagnosticCheck::new("piper", "Piper offline voice", CheckStatus::Ok, format!("Found at {:?}", binary))];
+    let voices = piper_voices();
+    if voices == 0 {
+        let check = DiagnosticCheck::new("piper_voices", "Piper voices", CheckStatus::Warning, "No voices installed");
+        let suggestion = format!("Download a voice (.onnx and .onnx.json) into {:?}", piper_dir().unwrap_or_default().join("voices"));
+        checks.push(check.suggest(suggestion, open_url(PIPER_VOICES_URL)));
+    } else {
+        checks.push(DiagnosticCheck::new("piper_voices", "Piper voices", CheckStatus::Ok, format!("{} voice(s) installed", voices)));
+    }
+    checks
+}
+
+/// Windows keeps the per-user privacy switch for microphone access here
+#[cfg(windows)]
+fn microphone_blocked() -> bool {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    const CONSENT_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\CapabilityAccessManager\ConsentStore\microphone";
+    RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(CONSENT_KEY)
+        .and_then(|key| key.get_value::<String, _>("Value"))
+        .is_ok_and(|value| value.eq_ignore_ascii_case("Deny"))
+}
+
+#[cfg(not(windows))]
+fn microphone_blocked() -> bool {
+    false
+}
+
+fn check_microphone() -> DiagnosticCheck {
+    use cpal::traits::{DeviceTrait, HostTrait};
+
+    const TITLE: &str = "Microphone";
+    if microphone_blocked() {
+        let check = DiagnosticCheck::new("microphone", TITLE, CheckStatus::Error, "Microphone access is turned off in Windows privacy settings");
+        return check.suggest(
+            "Allow apps to use the microphone in Settings → Privacy & security → Microphone",
+            open_url("ms-settings:privacy-microphone"),
+        );
+    }
+
+    let Some(device) = cpal::default_host().default_input_device() else {
+        let check = DiagnosticCheck::new("microphone", TITLE, CheckStatus::Error, "No microphone found");
+        return check.suggest("Plug in a microphone or pick a default input device in your sound settings", None);
+    };
+    let name = device.name().unwrap_or_else(|_| "Default input".to_string());
+    match device.default_input_config() {
+        Ok(config) => DiagnosticCheck::new(
+            "microphone",
+            TITLE,
+            CheckStatus::Ok,
+            format!("{} ({} Hz)", name, config.sample_rate().0),
+        ),
+        Err(e) => DiagnosticCheck::new("microphone", TITLE, CheckStatus::Error, format!("{} can't be opened: {}", name, e))
+            .suggest("Check that ASTRAL is allowed to use the microphone and no other app holds it exclusively", None),
+    }
+}
+
+fn check_api_keys(settings: &AppSettings) -> Vec<DiagnosticCheck> {
+    let has_llm_key = settings.llm.api_key.as_deref().is_some_and(|k| !k.trim().is_empty());
+    let llm = match settings.llm.provider {
+        LLMProvider::Ollama => DiagnosticCheck::new("llm_api_key", "LLM API key", CheckStatus::Skipped, "Ollama runs locally and needs no key"),
+        LLMProvider::Embedded => DiagnosticCheck::new("llm_api_key", "LLM API key", CheckStatus::Skipped, "The built-in model runs locally and needs no key"),
+        _ if has_llm_key => DiagnosticCheck::new("llm_api_key", "LLM API key", CheckStatus::Ok, format!("Set for {:?}", settings.llm.provider)),
+        provider => {
+            let check = DiagnosticCheck::new("llm_api_key", "LLM API key", CheckStatus::Error, format!("{:?} is selected but no API key is set", provider));
+            match provider {
+                LLMProvider::AzureOpenAI => check.suggest(
+                    format!("Copy a key from your Azure OpenAI resource's Keys and Endpoint page at {} and add it under AI Provider", AZURE_PORTAL_URL),
+                    open_settings("llm"),
+                ),
+                _ => {
+                    let url = match provider {
+                        LLMProvider::Claude => CLAUDE_KEYS_URL,
+                        LLMProvider::OpenRouter => OPENROUTER_KEYS_URL,
+                        _ => OPENAI_KEYS_URL,
+                    };
+                    check.suggest(format!("Create a key at {} and add it under AI Provider", url), open_settings("llm"))
+                }
+            }
+        }
+    };
+
+    let tts = if !settings.tts.enabled {
+        DiagnosticCheck::new("tts_api_key", "ElevenLabs API key", CheckStatus::Skipped, "ElevenLabs is turned off")
+    } else if settings.tts.api_key.trim().is_empty() {
+        DiagnosticCheck::new("tts_api_key", "ElevenLabs API key", CheckStatus::Error, "ElevenLabs is on but no API key is set")
+            .suggest(format!("Get a free key at {} and add it under Text-to-Speech", ELEVENLABS_URL), open_settings("tts"))
+    } else {
+        DiagnosticCheck::new("tts_api_key", "ElevenLabs API key", CheckStatus::Ok, "Set")
+    };
+
+    vec![llm, tts]
+}
+
+/// Cloud services that are configured but blocked by privacy mode
+fn check_privacy_mode(settings: &AppSettings) -> DiagnosticCheck {
+    const TITLE: &str = "Privacy mode";
+    if !settings.general.privacy_mode {
+        return DiagnosticCheck::new("privacy_mode", TITLE, CheckStatus::Skipped, "Off; cloud services may be used");
+    }
+    if !matches!(settings.llm.provider, LLMProvider::Ollama | LLMProvider::Embedded) {
+        return DiagnosticCheck::new("privacy_mode", TITLE, CheckStatus::Error, format!("On, which blocks {:?}", settings.llm.provider))
+            .suggest("Switch the AI provider to Ollama, or turn privacy mode off", open_settings("llm"));
+    }
+    if settings.tts.enabled {
+        return DiagnosticCheck::new("privacy_mode", TITLE, CheckStatus::Warning, "On, so ElevenLabs is blocked and the on-device voice is used")
+            .suggest("Turn ElevenLabs off to stop seeing this", open_settings("tts"));
+    }
+    if settings.search.enabled && settings.search.provider == crate::web_search::SearchProvider::Brave {
+        return DiagnosticCheck::new("privacy_mode", TITLE, CheckStatus::Warning, "On, so Brave Search is blocked")
+            .suggest("Point web search at a SearxNG instance on your network", open_settings("search"));
+    }
+    DiagnosticCheck::new("privacy_mode", TITLE, CheckStatus::Ok, "On; everything configured runs locally")
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn run_diagnostics(app: AppHandle) -> Result<Vec<DiagnosticCheck>, String> {
+    let settings = crate::settings::load_settings(app).await?;
+
+    let local = tokio::task::spawn_blocking(|| {
+        let mut checks = vec![check_microphone()];
+        checks.extend(check_piper());
+        checks
+    });
+    let (ollama, whisper, local) = tokio::join!(check_ollama(&settings), check_whisper(&settings), local);
+
+    let mut checks = ollama;
+    checks.push(whisper);
+    checks.extend(local.map_err(|e| e.to_string())?);
+    checks.extend(check_api_keys(&settings));
+    checks.push(check_privacy_mode(&settings));
+    Ok(checks)
+}
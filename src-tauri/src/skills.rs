@@ -0,0 +1,404 @@
+// Skills Module
+// Third-party capabilities (a weather skill, a Jira skill) installed as
+// sidecar processes under <config>/ASTRAL/skills/<skill>/, each with a
+// `skill.json` manifest declaring its intents (trigger phrases for the command
+// router), tools (for MCP and the LLM) and permissions. A skill stays inert
+// until the user approves it. The approval is pinned to a fingerprint of the
+// manifest and the program and script files it runs, so a changed manifest
+// or a swapped executable needs approving again. ASTRAL's own LLM only gets
+// a skill's tools once the user opts the skill in as well.
+//
+// Skills are not sandboxed: a skill runs with the user's rights and can read
+// and write their files and reach the network, whatever its manifest
+// declares. Approving one means trusting its author. What ASTRAL does limit
+// is the environment (only a few basics and the variables the skill
+// declared are passed on), the working directory and the run time.
+//
+// Each invocation runs the skill's command once in its own directory, writes
+// one JSON request to stdin and reads one JSON reply from stdout:
+//   -> {"type": "intent", "intent": "forecast", "utterance": "what's the weather"}
+//   -> {"type": "tool", "tool": "get_forecast", "arguments": {...}}
+//   <- {"response": "Sunny, 21 degrees"}   or   {"error": "..."}
+
+use tracing::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const MANIFEST_FILE: &str = "skill.json";
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+const MAX_TIMEOUT_SECS: u64 = 120;
+/// Replies bigger than this are cut off and rejected
+const MAX_REPLY_BYTES: u64 = 1024 * 1024;
+/// Passed through even though the environment is cleared, so interpreters start.
+/// HOME is left out; a skill that needs it declares it like any other variable
+const BASE_ENV: &[&str] = &["PATH", "SYSTEMROOT", "TEMP", "TMP", "LANG"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillIntent {
+    pub name: String,
+    /// Lowercase phrases; the intent fires when the command contains one
+    pub phrases: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillTool {
+    pub name: String,
+    pub description: String,
+    #[serde(default = "empty_schema")]
+    pub input_schema: Value,
+}
+
+fn empty_schema() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillPermissions {
+    /// Declared so the user can judge it when approving; not enforced, the
+    /// OS gives us no portable way to cut a child process off the network
+    pub network: bool,
+    /// Environment variables (e.g. an API key) the skill may read
+    pub env: Vec<String>,
+    pub timeout_secs: u64,
+}
+
+impl Default for SkillPermissions {
+    fn default() -> Self {
+        Self {
+            network: false,
+            env: Vec::new(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// What the user approved for a skill
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkillApproval {
+    pub permissions: SkillPermissions,
+    /// `fingerprint` of the skill as approved
+    pub fingerprint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillManifest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Program to run; relative paths resolve inside the skill's directory
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub intents: Vec<SkillIntent>,
+    #[serde(default)]
+    pub tools: Vec<SkillTool>,
+    #[serde(default)]
+    pub permissions: SkillPermissions,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Skill {
+    #[serde(flatten)]
+    pub manifest: SkillManifest,
+    pub path: PathBuf,
+    /// Filled in from settings when listed
+    pub approved: bool,
+}
+
+static SKILLS: Lazy<RwLock<Vec<Skill>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+fn skills_dir() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("skills");
+    Some(path)
+}
+
+fn load_manifest(dir: &std::path::Path) -> Result<SkillManifest, String> {
+    let content = std::fs::read_to_string(dir.join(MANIFEST_FILE)).map_err(|e| e.to_string())?;
+    let manifest: SkillManifest = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    if manifest.id.is_empty() || !manifest.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid skill id '{}'", manifest.id));
+    }
+    Ok(manifest)
+}
+
+/// Scan the skills directory; skills with a broken manifest are skipped
+pub fn reload() -> usize {
+    let mut skills: Vec<Skill> = Vec::new();
+    if let Some(dir) = skills_dir() {
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if !path.join(MANIFEST_FILE).is_file() {
+                continue;
+            }
+            match load_manifest(&path) {
+                Ok(manifest) if skills.iter().any(|s| s.manifest.id == manifest.id) => {
+                    warn!("Skipping duplicate skill id '{}' in {:?}", manifest.id, path);
+                }
+                Ok(manifest) => skills.push(Skill { manifest, path, approved: false }),
+                Err(e) => warn!("Skipping skill in {:?}: {}", path, e),
+            }
+        }
+    }
+
+    let count = skills.len();
+    if let Ok(mut loaded) = SKILLS.write() {
+        *loaded = skills;
+    }
+    info!("Loaded {} skill(s)", count);
+    count
+}
+
+pub fn init() {
+    std::thread::spawn(reload);
+}
+
+/// SHA-256 over the manifest as loaded and every file in the skill's
+/// directory that its command and arguments name (the program, a script).
+/// None when one of them can't be read.
+fn fingerprint(skill: &Skill) -> Option<String> {
+    let manifest = &skill.manifest;
+    let mut hash = Sha256::new();
+    hash.update(serde_json::to_vec(manifest).ok()?);
+    for part in std::iter::once(&manifest.command).chain(&manifest.args) {
+        let path = skill.path.join(part);
+        if path.is_file() {
+            hash.update([0]);
+            hash.update(part.as_bytes());
+            hash.update([0]);
+            hash.update(std::fs::read(&path).ok()?);
+        }
+    }
+    Some(format!("{:x}", hash.finalize()))
+}
+
+fn is_approved(approvals: &HashMap<String, SkillApproval>, skill: &Skill) -> bool {
+    approvals.get(&skill.manifest.id).is_some_and(|approval| {
+        approval.permissions == skill.manifest.permissions && fingerprint(skill).is_some_and(|f| f == approval.fingerprint)
+    })
+}
+
+/// Loaded skills the user has approved, as they are now
+async fn approved_skills(app: &AppHandle) -> Vec<Skill> {
+    let approvals = crate::settings::load_settings(app.clone())
+        .await
+        .map(|s| s.skills.approved)
+        .unwrap_or_default();
+    SKILLS
+        .read()
+        .map(|skills| skills.iter().filter(|s| is_approved(&approvals, s)).cloned().collect())
+        .unwrap_or_default()
+}
+
+async fn invoke(skill: &Skill, request: Value) -> Result<String, String> {
+    crate::permissions::check(crate::permissions::Capability::Skills)?;
+    let manifest = &skill.manifest;
+    let local = skill.path.join(&manifest.command);
+    let program = if local.is_file() { local } else { PathBuf::from(&manifest.command) };
+
+    let mut command = tokio::process::Command::new(program);
+    command
+        .args(&manifest.args)
+        .current_dir(&skill.path)
+        .env_clear()
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    for key in BASE_ENV.iter().copied().chain(manifest.permissions.env.iter().map(String::as_str)) {
+        if let Ok(value) = std::env::var(key) {
+            command.env(key, value);
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // CREATE_NO_WINDOW: no console flashing up for script skills
+        command.creation_flags(0x0800_0000);
+    }
+
+    let mut child = command.spawn().map_err(|e| format!("Couldn't start the {} skill: {}", manifest.name, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(format!("{}\n", request).as_bytes()).await.map_err(|e| e.to_string())?;
+    }
+    let mut stdout = child.stdout.take().ok_or_else(|| "Skill has no stdout".to_string())?;
+
+    let timeout = Duration::from_secs(manifest.permissions.timeout_secs.clamp(1, MAX_TIMEOUT_SECS));
+    let mut output = Vec::new();
+    tokio::time::timeout(timeout, (&mut stdout).take(MAX_REPLY_BYTES + 1).read_to_end(&mut output))
+        .await
+        .map_err(|_| format!("The {} skill took too long to answer", manifest.name))?
+        .map_err(|e| e.to_string())?;
+    // Dropping the child kills it if it's still running
+    drop(child);
+
+    if output.len() as u64 > MAX_REPLY_BYTES {
+        return Err(format!("The {} skill sent too much output", manifest.name));
+    }
+    let reply: Value = serde_json::from_slice(&output)
+        .map_err(|e| format!("The {} skill sent an unreadable reply: {}", manifest.name, e))?;
+    if let Some(error) = reply.get("error").and_then(Value::as_str) {
+        return Err(error.to_string());
+    }
+    reply
+        .get("response")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| format!("The {} skill didn't send a response", manifest.name))
+}
+
+/// Route a spoken command to the first approved skill with a matching intent
+pub async fn handle_command(app: &AppHandle, command: &str) -> Option<String> {
+    let lower = command.to_lowercase();
+    let skills = approved_skills(app).await;
+    let (skill, intent) = skills.iter().find_map(|skill| {
+        skill
+            .manifest
+            .intents
+            .iter()
+            .find(|intent| intent.phrases.iter().any(|p| !p.is_empty() && lower.contains(&p.to_lowercase())))
+            .map(|intent| (skill, intent))
+    })?;
+
+    info!("Routing to skill {} ({})", skill.manifest.id, intent.name);
+    let request = json!({ "type": "intent", "intent": intent.name, "utterance": command });
+    Some(invoke(skill, request).await.unwrap_or_else(|e| format!("The {} skill failed: {}", skill.manifest.name, e)))
+}
+
+/// Approved skill tools, named `<skill id>.<tool>`, in MCP's tool shape
+pub async fn tool_definitions(app: &AppHandle) -> Vec<Value> {
+    definitions(&approved_skills(app).await)
+}
+
+/// `tool_definitions` of the skills opted in to ASTRAL's own LLM
+pub async fn llm_tool_definitions(app: &AppHandle) -> Vec<Value> {
+    let opted_in = llm_skill_ids(app).await;
+    let skills: Vec<Skill> = approved_skills(app)
+        .await
+        .into_iter()
+        .filter(|s| opted_in.contains(&s.manifest.id))
+        .collect();
+    definitions(&skills)
+}
+
+/// Whether the user let ASTRAL's own LLM call the tools of `skill_id`
+pub async fn offered_to_llm(app: &AppHandle, skill_id: &str) -> bool {
+    llm_skill_ids(app).await.iter().any(|id| id == skill_id)
+}
+
+async fn llm_skill_ids(app: &AppHandle) -> Vec<String> {
+    crate::settings::load_settings(app.clone())
+        .await
+        .map(|s| s.skills.llm_tools)
+        .unwrap_or_default()
+}
+
+fn definitions(skills: &[Skill]) -> Vec<Value> {
+    skills
+        .iter()
+        .flat_map(|skill| {
+            skill.manifest.tools.iter().map(move |tool| {
+                json!({
+                    "name": format!("{}.{}", skill.manifest.id, tool.name),
+                    "description": tool.description,
+                    "inputSchema": tool.input_schema,
+                })
+            })
+        })
+        .collect()
+}
+
+/// Run a `<skill id>.<tool>` tool; None when no approved skill owns the name
+pub async fn call_tool(app: &AppHandle, name: &str, arguments: &Value) -> Option<Result<String, String>> {
+    let (skill_id, tool) = name.split_once('.')?;
+    let skills = approved_skills(app).await;
+    let skill = skills
+        .iter()
+        .find(|s| s.manifest.id == skill_id && s.manifest.tools.iter().any(|t| t.name == tool))?;
+    let request = json!({ "type": "tool", "tool": tool, "arguments": arguments });
+    Some(invoke(skill, request).await)
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_skills(app: AppHandle) -> Result<Vec<Skill>, String> {
+    let approvals = crate::settings::load_settings(app).await?.skills.approved;
+    let skills = SKILLS.read().map_err(|e| e.to_string())?;
+    Ok(skills
+        .iter()
+        .cloned()
+        .map(|mut skill| {
+            skill.approved = is_approved(&approvals, &skill);
+            skill
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn reload_skills() -> Result<usize, String> {
+    tokio::task::spawn_blocking(reload).await.map_err(|e| e.to_string())
+}
+
+/// Grant a skill the permissions its manifest declares right now, for the
+/// files it runs as they are now
+#[tauri::command]
+pub async fn approve_skill(app: AppHandle, id: String) -> Result<(), String> {
+    let skill = SKILLS
+        .read()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .find(|s| s.manifest.id == id)
+        .cloned()
+        .ok_or_else(|| format!("No skill with id '{}'", id))?;
+    let fingerprint = fingerprint(&skill).ok_or_else(|| format!("Couldn't read the files of the {} skill", skill.manifest.name))?;
+
+    crate::settings::modify_settings(&app, |settings| {
+        settings.skills.approved.insert(id.clone(), SkillApproval { permissions: skill.manifest.permissions, fingerprint });
+        Ok::<_, String>(())
+    })
+    .await?;
+    info!("Skill approved: {}", id);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn revoke_skill(app: AppHandle, id: String) -> Result<(), String> {
+    crate::settings::modify_settings(&app, |settings| {
+        settings.skills.approved.remove(&id);
+        settings.skills.llm_tools.retain(|s| *s != id);
+        Ok(())
+    })
+    .await
+}
+
+/// Offer a skill's tools to ASTRAL's own LLM, or stop offering them
+#[tauri::command]
+pub async fn set_skill_llm_tools(app: AppHandle, id: String, enabled: bool) -> Result<(), String> {
+    crate::settings::modify_settings(&app, |settings| {
+        settings.skills.llm_tools.retain(|s| *s != id);
+        if enabled {
+            if !settings.skills.approved.contains_key(&id) {
+                return Err(format!("Approve the skill '{}' first", id));
+            }
+            settings.skills.llm_tools.push(id);
+        }
+        Ok(())
+    })
+    .await
+}
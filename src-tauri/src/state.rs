@@ -0,0 +1,104 @@
+// App State
+// The long-lived managers (LLM conversation, automation routines, ElevenLabs
+// TTS), registered once with `.manage()` in main.rs. Commands take
+// `State<'_, AppState>`; background tasks reach it through their AppHandle
+// with `app.state::<AppState>()`. The engines are configured from saved
+// settings at startup so voice commands and scheduled reports work before
+// the UI has loaded.
+
+use crate::automation::AutomationManager;
+use crate::elevenlabs_tts::{ElevenLabsConfig, ElevenLabsEngine};
+use crate::error::{AstralError, AstralResult};
+use crate::llm_provider::{Image, LLMConfig, LLMManager, LLMResponse};
+use crate::settings::AppSettings;
+use tracing::{info, warn};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+pub struct AppState {
+    /// Set up from settings at startup, then by `update_llm_config`
+    pub llm: Mutex<Option<LLMManager>>,
+    pub automation: Mutex<AutomationManager>,
+    pub tts: Mutex<ElevenLabsEngine>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self {
+            llm: Mutex::new(None),
+            automation: Mutex::new(AutomationManager::new()),
+            tts: Mutex::new(ElevenLabsEngine::new(ElevenLabsConfig::default())),
+        }
+    }
+
+    /// Point the LLM and TTS engines at the saved provider, model and keys,
+    /// with the active persona laid over them, and have them use the saved locale
+    pub async fn configure(&self, settings: &AppSettings) {
+        crate::locale::set_current(&settings.general.locale);
+
+        let mut llm_config = LLMConfig {
+            api_key: settings.llm.api_key.clone().filter(|k| !k.is_empty()),
+            ..settings.llm.clone()
+        };
+        let mut tts_config = settings.tts.clone();
+        if let Some(persona) = settings.personas.active_persona() {
+            persona.apply(&mut llm_config, &mut tts_config);
+        }
+        let mut llm = self.llm.lock().await;
+        if let Some(manager) = llm.as_mut() {
+            manager.update_config(llm_config);
+        } else {
+            *llm = Some(LLMManager::new(llm_config));
+        }
+        if let Some(manager) = llm.as_mut() {
+            manager.set_template_variable("language", crate::locale::current_language_name());
+        }
+        drop(llm);
+
+        self.tts.lock().await.update_config(tts_config);
+    }
+
+    /// Send `message` on the shared conversation
+    pub async fn send_llm_message(&self, message: &str) -> AstralResult<LLMResponse> {
+        let mut llm = self.llm.lock().await;
+        let manager = llm.get_or_insert_with(|| LLMManager::new(LLMConfig::default()));
+        manager.send_message(message).await.map_err(AstralError::from)
+    }
+
+    /// `send_llm_message` that asks the provider even when a cached reply is fresh
+    pub async fn send_llm_message_uncached(&self, message: &str) -> AstralResult<LLMResponse> {
+        let mut llm = self.llm.lock().await;
+        let manager = llm.get_or_insert_with(|| LLMManager::new(LLMConfig::default()));
+        manager.send_message_uncached(message).await.map_err(AstralError::from)
+    }
+
+    /// `send_llm_message` with images for a vision model
+    pub async fn send_llm_message_with_images(&self, message: &str, images: Vec<Image>) -> AstralResult<LLMResponse> {
+        let mut llm = self.llm.lock().await;
+        let manager = llm.get_or_insert_with(|| LLMManager::new(LLMConfig::default()));
+        manager.send_message_with_images(message, images).await.map_err(AstralError::from)
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Configure the managed engines from the settings store; called from setup
+pub async fn init(app: AppHandle) {
+    match crate::settings::load_settings(app.clone()).await {
+        Ok(settings) => {
+            crate::proxy::set(settings.proxy.clone());
+            app.state::<AppState>().configure(&settings).await;
+            crate::voice::wake_word::set_config(settings.voice.wake_word.clone());
+            crate::permissions::set(settings.permissions.clone());
+            crate::usage::set(settings.usage.clone());
+            crate::response_cache::set(settings.response_cache.clone());
+            crate::privacy::set_enabled(&app, settings.general.privacy_mode);
+            info!("Engines configured from settings (LLM: {:?} {})", settings.llm.provider, settings.llm.model);
+        }
+        Err(e) => warn!("Couldn't load settings, keeping default engines: {}", e),
+    }
+}
@@ -0,0 +1,738 @@
+// Sync Module
+// Keeps the conversation, automation routines and what ASTRAL was told to
+// remember (notes and lists, learned app names) in step between machines
+// running ASTRAL through one JSON file both can reach: a shared folder (a
+// network drive, Syncthing or Dropbox), a WebDAV server the user runs or an
+// S3 bucket (AWS, MinIO, R2). The servers are only spoken to over HTTPS.
+// Off unless `sync.enabled`. Each pass reads the file, merges it with what's
+// here and writes the result back. Every routine carries the time the user
+// last changed it and the newer copy wins, deletions included; app names
+// are merged the same way, one at a time. The conversation and the notes are
+// each taken whole from whichever device changed them last. A pass runs
+// every `sync.interval_minutes`, right after sync is turned on, and on
+// `sync_now`.
+//
+// Anyone who can write the sync file can hand this machine routines, so a
+// routine arriving with an action that needs a permission (shell commands,
+// power, unlocking) comes in turned off until the user turns it on here.
+
+use crate::automation::AutomationRoutine;
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use crate::llm_provider::Message;
+use crate::state::AppState;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+/// Emitted after each pass, payload is a `SyncSummary`
+pub const SYNC_COMPLETED_EVENT: &str = "sync-completed";
+
+const SYNC_FILE: &str = "astral-sync.json";
+
+/// Bumped when the sync file layout changes incompatibly; 2 added notes and
+/// app names, which an older ASTRAL would drop when writing the file back
+const FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncBackend {
+    /// A directory both machines can see
+    Folder,
+    #[serde(rename = "webdav")]
+    WebDav,
+    /// An object in an S3-compatible bucket
+    S3,
+}
+
+impl SyncBackend {
+    fn label(self) -> &'static str {
+        match self {
+            SyncBackend::Folder => "sync folder",
+            SyncBackend::WebDav => "WebDAV server",
+            SyncBackend::S3 => "S3 bucket",
+        }
+    }
+}
+
+/// The `sync` settings section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncSettings {
+    pub enabled: bool,
+    pub backend: SyncBackend,
+    pub folder: String,
+    /// Collection the sync file goes in, e.g. `https://nas.local/dav/astral/`
+    pub webdav_url: String,
+    pub webdav_username: String,
+    pub webdav_password: String,
+    /// e.g. `https://s3.eu-central-1.amazonaws.com`, or a MinIO or R2 address
+    pub s3_endpoint: String,
+    pub s3_region: String,
+    pub s3_bucket: String,
+    pub s3_access_key_id: String,
+    pub s3_secret_access_key: String,
+    pub interval_minutes: u64,
+    /// How this machine is named in the sync file; the host name when empty
+    pub device_name: String,
+    pub conversations: bool,
+    pub routines: bool,
+    /// Notes, lists and learned app names
+    pub memories: bool,
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: SyncBackend::Folder,
+            folder: String::new(),
+            webdav_url: String::new(),
+            webdav_username: String::new(),
+            webdav_password: String::new(),
+            s3_endpoint: String::new(),
+            s3_region: "us-east-1".to_string(),
+            s3_bucket: String::new(),
+            s3_access_key_id: String::new(),
+            s3_secret_access_key: String::new(),
+            interval_minutes: 15,
+            device_name: String::new(),
+            conversations: true,
+            routines: true,
+            memories: true,
+        }
+    }
+}
+
+impl SyncSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.folder = self.folder.trim().to_string();
+        self.webdav_url = self.webdav_url.trim().to_string();
+        self.s3_endpoint = self.s3_endpoint.trim().trim_end_matches('/').to_string();
+        self.s3_region = self.s3_region.trim().to_string();
+        self.s3_bucket = self.s3_bucket.trim().to_string();
+        self.s3_access_key_id = self.s3_access_key_id.trim().to_string();
+        self.device_name = self.device_name.trim().to_string();
+        if self.interval_minutes == 0 {
+            return Err("The sync interval must be at least a minute".to_string());
+        }
+        if !self.enabled {
+            return Ok(());
+        }
+        match self.backend {
+            SyncBackend::Folder if self.folder.is_empty() => Err("Choose a folder to sync through".to_string()),
+            SyncBackend::WebDav if !self.webdav_url.starts_with("https://") => {
+                Err("The WebDAV address must start with https://".to_string())
+            }
+            SyncBackend::S3 if !self.s3_endpoint.starts_with("https://") => {
+                Err("The S3 endpoint must start with https://".to_string())
+            }
+            SyncBackend::S3 if self.s3_bucket.is_empty() || self.s3_region.is_empty() => {
+                Err("Enter the S3 bucket and its region".to_string())
+            }
+            SyncBackend::S3 if self.s3_access_key_id.is_empty() || self.s3_secret_access_key.is_empty() => {
+                Err("Enter the S3 access key and secret".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Where the sync file lives on a server backend
+    fn file_url(&self) -> String {
+        match self.backend {
+            SyncBackend::S3 => format!("{}/{}/{}", self.s3_endpoint, self.s3_bucket, SYNC_FILE),
+            _ => format!("{}/{}", self.webdav_url.trim_end_matches('/'), SYNC_FILE),
+        }
+    }
+
+    fn device(&self) -> String {
+        if !self.device_name.is_empty() {
+            return self.device_name.clone();
+        }
+        sysinfo::System::host_name().unwrap_or_else(|| "ASTRAL".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RoutineRecord {
+    updated_at: DateTime<Utc>,
+    device: String,
+    /// `None` once deleted, so the deletion reaches the other machines
+    routine: Option<AutomationRoutine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AliasRecord {
+    updated_at: DateTime<Utc>,
+    device: String,
+    /// The app it opens; `None` once forgotten
+    app: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NotesRecord {
+    updated_at: DateTime<Utc>,
+    device: String,
+    store: crate::notes::NotesStore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ConversationRecord {
+    updated_at: DateTime<Utc>,
+    device: String,
+    messages: Vec<Message>,
+}
+
+/// The shared file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SyncDocument {
+    format: u32,
+    /// Device name -> when it last synced
+    devices: BTreeMap<String, DateTime<Utc>>,
+    routines: BTreeMap<String, RoutineRecord>,
+    conversation: Option<ConversationRecord>,
+    aliases: BTreeMap<String, AliasRecord>,
+    notes: Option<NotesRecord>,
+}
+
+/// What this machine had after its last pass, to tell local deletions from
+/// routines that were never synced
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LocalState {
+    routine_ids: BTreeSet<String>,
+    /// Learned app names as of the last pass, to spot ones learned or forgotten since
+    aliases: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncSummary {
+    pub synced_at: String,
+    /// Routines taken from other devices (added, changed or deleted here)
+    pub routines_received: usize,
+    /// Routines this machine changed since the last pass
+    pub routines_sent: usize,
+    pub conversation_received: bool,
+    pub conversation_sent: bool,
+    /// Routines that arrived turned off because they need a permission; the
+    /// user turns them on here once they've looked at them
+    pub routines_held: Vec<String>,
+    pub aliases_received: usize,
+    pub aliases_sent: usize,
+    pub notes_received: bool,
+    pub notes_sent: bool,
+    /// Other devices in the sync file
+    pub devices: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncStatus {
+    pub enabled: bool,
+    pub last: Option<SyncSummary>,
+    pub last_error: Option<String>,
+}
+
+/// Cuts the wait before the next background pass short
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+/// One pass at a time
+static RUNNING: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+static STATUS: Lazy<std::sync::Mutex<SyncStatus>> = Lazy::new(Default::default);
+
+fn state_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("sync_state.json");
+    Some(path)
+}
+
+fn load_state() -> LocalState {
+    state_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &LocalState) -> anyhow::Result<()> {
+    let path = state_path().context("Could not find config directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Sign a request for the sync object with AWS Signature Version 4
+fn sign_s3(settings: &SyncSettings, method: &reqwest::Method, url: &reqwest::Url, payload_hash: &str) -> Vec<(&'static str, String)> {
+    let now = Utc::now();
+    let date = now.format("%Y%m%d").to_string();
+    let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        method, url.path(), host, payload_hash, timestamp, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, settings.s3_region);
+    let to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{:x}", timestamp, scope, Sha256::digest(canonical.as_bytes()));
+
+    let key = [settings.s3_region.as_str(), "s3", "aws4_request"]
+        .iter()
+        .fold(hmac_sha256(format!("AWS4{}", settings.s3_secret_access_key).as_bytes(), &date), |key, part| hmac_sha256(&key, part));
+    let signature: String = hmac_sha256(&key, &to_sign).iter().map(|b| format!("{:02x}", b)).collect();
+    vec![
+        ("x-amz-date", timestamp),
+        ("x-amz-content-sha256", payload_hash.to_string()),
+        (
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                settings.s3_access_key_id, scope, signed_headers, signature
+            ),
+        ),
+    ]
+}
+
+/// A request for the sync file on the WebDAV server or in the bucket
+fn server_request(settings: &SyncSettings, method: reqwest::Method, body: Vec<u8>) -> AstralResult<reqwest::RequestBuilder> {
+    let url = reqwest::Url::parse(&settings.file_url())
+        .map_err(|e| AstralError::invalid_input(format!("Invalid {} address: {}", settings.backend.label(), e)))?;
+    // Credentials and routines don't travel in the clear, even if the store was edited by hand
+    if url.scheme() != "https" {
+        return Err(AstralError::invalid_input(format!("The {} has to be reached over https://", settings.backend.label())));
+    }
+    let mut request = crate::proxy::client().request(method.clone(), url.clone()).timeout(Duration::from_secs(30));
+    match settings.backend {
+        SyncBackend::S3 => {
+            let payload_hash = format!("{:x}", Sha256::digest(&body));
+            for (name, value) in sign_s3(settings, &method, &url, &payload_hash) {
+                request = request.header(name, value);
+            }
+        }
+        _ if !settings.webdav_username.is_empty() => {
+            request = request.basic_auth(&settings.webdav_username, Some(&settings.webdav_password));
+        }
+        _ => {}
+    }
+    Ok(if body.is_empty() { request } else { request.body(body) })
+}
+
+/// The sync file and, from a server, its ETag
+async fn fetch(settings: &SyncSettings) -> AstralResult<(Option<SyncDocument>, Option<String>)> {
+    let content = match settings.backend {
+        SyncBackend::Folder => {
+            let path = PathBuf::from(&settings.folder).join(SYNC_FILE);
+            match tokio::fs::read_to_string(&path).await {
+                Ok(content) => (Some(content), None),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => (None, None),
+                Err(e) => return Err(AstralError::from(format!("Couldn't read {:?}: {}", path, e))),
+            }
+        }
+        SyncBackend::WebDav | SyncBackend::S3 => {
+            let label = settings.backend.label();
+            let response = crate::privacy::send(server_request(settings, reqwest::Method::GET, Vec::new())?)
+                .await
+                .map_err(|e| AstralError::from(e.context(format!("Couldn't reach the {}", label))))?;
+            match response.status() {
+                reqwest::StatusCode::NOT_FOUND => (None, None),
+                status if status.is_success() => {
+                    let etag = response
+                        .headers()
+                        .get(reqwest::header::ETAG)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string);
+                    (Some(response.text().await?), etag)
+                }
+                reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                    return Err(AstralError::new(ErrorCode::Unauthorized, format!("The {} refused the credentials", label)));
+                }
+                status => return Err(AstralError::new(ErrorCode::ServiceUnavailable, format!("The {} answered {}", label, status))),
+            }
+        }
+    };
+
+    match content {
+        (Some(content), etag) => {
+            let document: SyncDocument = serde_json::from_str(&content)
+                .map_err(|e| AstralError::from(format!("The sync file is damaged: {}", e)))?;
+            if document.format > FORMAT_VERSION {
+                return Err(AstralError::new(
+                    ErrorCode::NotConfigured,
+                    "The sync file was written by a newer ASTRAL; update this machine first",
+                ));
+            }
+            Ok((Some(document), etag))
+        }
+        (None, _) => Ok((None, None)),
+    }
+}
+
+/// Write the sync file. On a server the write only goes through if nobody
+/// replaced the file since `etag` was read; returns false if someone did.
+async fn store(settings: &SyncSettings, document: &SyncDocument, etag: Option<&str>) -> AstralResult<bool> {
+    let content = serde_json::to_vec_pretty(document).map_err(|e| AstralError::from(e.to_string()))?;
+    match settings.backend {
+        SyncBackend::Folder => {
+            let path = PathBuf::from(&settings.folder).join(SYNC_FILE);
+            // Write beside it and rename, so the other machine never reads half a file
+            let partial = path.with_extension("json.partial");
+            tokio::fs::write(&partial, content)
+                .await
+                .and(tokio::fs::rename(&partial, &path).await)
+                .map_err(|e| AstralError::from(format!("Couldn't write {:?}: {}", path, e)))?;
+            Ok(true)
+        }
+        SyncBackend::WebDav | SyncBackend::S3 => {
+            let label = settings.backend.label();
+            let request = server_request(settings, reqwest::Method::PUT, content)?
+                .header(reqwest::header::CONTENT_TYPE, "application/json");
+            let request = match etag {
+                Some(etag) => request.header(reqwest::header::IF_MATCH, etag),
+                None => request.header(reqwest::header::IF_NONE_MATCH, "*"),
+            };
+            let response = crate::privacy::send(request)
+                .await
+                .map_err(|e| AstralError::from(e.context(format!("Couldn't reach the {}", label))))?;
+            match response.status() {
+                // S3 answers a lost race on a new object with 409
+                reqwest::StatusCode::PRECONDITION_FAILED | reqwest::StatusCode::CONFLICT => Ok(false),
+                status if status.is_success() => Ok(true),
+                status => Err(AstralError::new(ErrorCode::ServiceUnavailable, format!("The {} answered {}", label, status))),
+            }
+        }
+    }
+}
+
+/// Fold this machine's routines into `document`, then take the newer copy of
+/// each. Returns the routines to change and to delete here, and how many of
+/// this machine's routines went into the document.
+fn merge_routines(
+    document: &mut SyncDocument,
+    local: &[AutomationRoutine],
+    previous: &BTreeSet<String>,
+    device: &str,
+    now: DateTime<Utc>,
+) -> (Vec<AutomationRoutine>, Vec<String>, usize) {
+    let mut sent = 0;
+    for routine in local {
+        let newer = document
+            .routines
+            .get(&routine.id)
+            .map_or(true, |record| routine.modified_at() > record.updated_at);
+        if newer {
+            document.routines.insert(
+                routine.id.clone(),
+                RoutineRecord { updated_at: routine.modified_at(), device: device.to_string(), routine: Some(routine.clone()) },
+            );
+            sent += 1;
+        }
+    }
+
+    // Synced last time and gone now: deleted here
+    for id in previous {
+        if local.iter().any(|r| &r.id == id) {
+            continue;
+        }
+        if let Some(record) = document.routines.get_mut(id).filter(|r| r.routine.is_some()) {
+            *record = RoutineRecord { updated_at: now, device: device.to_string(), routine: None };
+            sent += 1;
+        }
+    }
+
+    let mut changed = Vec::new();
+    let mut removed = Vec::new();
+    for (id, record) in &document.routines {
+        let here = local.iter().find(|r| &r.id == id);
+        match (&record.routine, here) {
+            (Some(routine), Some(here)) if routine.modified_at() > here.modified_at() => changed.push(hold(routine, Some(here))),
+            // New on another device; ones deleted here were turned into deletions above
+            (Some(routine), None) => changed.push(hold(routine, None)),
+            (None, Some(here)) if record.updated_at > here.modified_at() => removed.push(id.clone()),
+            _ => {}
+        }
+    }
+    (changed, removed, sent)
+}
+
+/// Whether a routine does anything that needs a permission, or would open
+/// the house up through Home Assistant
+fn is_gated(routine: &AutomationRoutine) -> bool {
+    routine.actions.iter().any(|action| {
+        action.capability().is_some()
+            || matches!(action, crate::automation::AutomationAction::HomeAssistant { service, .. }
+                if crate::home_assistant::is_sensitive(service))
+    })
+}
+
+/// What a routine does and when, leaving out its name and state
+fn behaviour(routine: &AutomationRoutine) -> Option<serde_json::Value> {
+    serde_json::to_value((&routine.trigger, &routine.actions, &routine.conditions)).ok()
+}
+
+/// `incoming`, turned off unless it's harmless or the user already runs
+/// exactly this here. Its timestamp is kept, so turning it back off doesn't
+/// travel to the other machines; turning it on here does, as a change.
+fn hold(incoming: &AutomationRoutine, here: Option<&AutomationRoutine>) -> AutomationRoutine {
+    let mut routine = incoming.clone();
+    let confirmed = here.is_some_and(|here| here.enabled && behaviour(here) == behaviour(incoming));
+    if routine.enabled && is_gated(&routine) && !confirmed {
+        routine.enabled = false;
+    }
+    routine
+}
+
+/// Fold this machine's learned app names into `document`: ones learned or
+/// changed since the last pass go in with the time of this pass, ones
+/// forgotten go in as deletions. Returns the merged names and how many went
+/// out and came in.
+fn merge_aliases(
+    document: &mut SyncDocument,
+    local: &HashMap<String, String>,
+    previous: &BTreeMap<String, String>,
+    device: &str,
+    now: DateTime<Utc>,
+) -> (BTreeMap<String, String>, usize, usize) {
+    let mut sent = 0;
+    for (alias, app) in local {
+        if previous.get(alias) != Some(app) {
+            document
+                .aliases
+                .insert(alias.clone(), AliasRecord { updated_at: now, device: device.to_string(), app: Some(app.clone()) });
+            sent += 1;
+        }
+    }
+    for alias in previous.keys().filter(|alias| !local.contains_key(*alias)) {
+        if let Some(record) = document.aliases.get_mut(alias).filter(|r| r.app.is_some()) {
+            *record = AliasRecord { updated_at: now, device: device.to_string(), app: None };
+            sent += 1;
+        }
+    }
+
+    let merged: BTreeMap<String, String> = document
+        .aliases
+        .iter()
+        .filter_map(|(alias, record)| Some((alias.clone(), record.app.clone()?)))
+        .collect();
+    let received = merged.iter().filter(|(alias, app)| local.get(*alias) != Some(*app)).count()
+        + local.keys().filter(|alias| !merged.contains_key(*alias)).count();
+    (merged, sent, received)
+}
+
+async fn run_pass(app: &AppHandle, settings: &SyncSettings) -> AstralResult<SyncSummary> {
+    let device = settings.device();
+    let state = app.state::<AppState>();
+    let previous = load_state();
+
+    // Over WebDAV another machine may write between our read and write; merge again if so
+    for _ in 0..3 {
+        let now = Utc::now();
+        let (document, etag) = fetch(settings).await?;
+        let mut document = document.unwrap_or_default();
+        document.format = FORMAT_VERSION;
+        document.devices.insert(device.clone(), now);
+
+        let mut summary = SyncSummary {
+            synced_at: now.to_rfc3339(),
+            routines_received: 0,
+            routines_sent: 0,
+            conversation_received: false,
+            conversation_sent: false,
+            routines_held: Vec::new(),
+            aliases_received: 0,
+            aliases_sent: 0,
+            notes_received: false,
+            notes_sent: false,
+            devices: document.devices.keys().filter(|d| **d != device).cloned().collect(),
+        };
+
+        let mut routine_changes = None;
+        if settings.routines {
+            let local = state.inner().automation.lock().await.get_all_routines();
+            let (changed, removed, sent) = merge_routines(&mut document, &local, &previous.routine_ids, &device, now);
+            summary.routines_received = changed.len() + removed.len();
+            summary.routines_sent = sent;
+            summary.routines_held = changed
+                .iter()
+                .filter(|r| !r.enabled && document.routines.get(&r.id).and_then(|rec| rec.routine.as_ref()).is_some_and(|rec| rec.enabled))
+                .map(|r| r.name.clone())
+                .collect();
+            routine_changes = Some((changed, removed));
+        }
+
+        let mut alias_update = None;
+        let mut notes_update = None;
+        if settings.memories {
+            let local = crate::app_launcher::learned_aliases();
+            let (merged, sent, received) = merge_aliases(&mut document, &local, &previous.aliases, &device, now);
+            summary.aliases_sent = sent;
+            summary.aliases_received = received;
+            alias_update = Some((merged, received > 0));
+
+            let notes = crate::notes::snapshot();
+            let remote_at = document.notes.as_ref().map(|n| n.updated_at);
+            if remote_at > notes.updated_at {
+                notes_update = document.notes.as_ref().map(|n| n.store.clone());
+                summary.notes_received = true;
+            } else if let Some(at) = notes.updated_at.filter(|at| Some(*at) > remote_at) {
+                document.notes = Some(NotesRecord { updated_at: at, device: device.clone(), store: notes });
+                summary.notes_sent = true;
+            }
+        }
+
+        let mut conversation_update = None;
+        if settings.conversations {
+            let llm = state.inner().llm.lock().await;
+            let local_at = llm.as_ref().and_then(|m| m.history_updated_at());
+            let remote_at = document.conversation.as_ref().map(|c| c.updated_at);
+            if remote_at > local_at {
+                conversation_update = document.conversation.as_ref().map(|c| (c.messages.clone(), c.updated_at));
+                summary.conversation_received = true;
+            } else if let (Some(at), Some(manager)) = (local_at.filter(|at| Some(*at) > remote_at), llm.as_ref()) {
+                let messages = manager.get_history().to_vec();
+                document.conversation = Some(ConversationRecord { updated_at: at, device: device.clone(), messages });
+                summary.conversation_sent = true;
+            }
+        }
+
+        if !store(settings, &document, etag.as_deref()).await? {
+            info!("The sync file changed while merging, trying again");
+            continue;
+        }
+
+        // Only apply once the merged file is written, so a failed pass changes nothing here
+        if let Some((changed, removed)) = routine_changes {
+            if !changed.is_empty() || !removed.is_empty() {
+                state.inner().automation.lock().await.apply_sync(changed, &removed).map_err(AstralError::from)?;
+                crate::tray::refresh_routines(app).await;
+            }
+        }
+        if let Some((messages, updated_at)) = conversation_update {
+            let mut llm = state.inner().llm.lock().await;
+            if let Some(manager) = llm.as_mut() {
+                manager.restore_history(messages, updated_at);
+            }
+        }
+        let mut aliases = previous.aliases.clone();
+        if let Some((merged, received)) = alias_update {
+            if received {
+                crate::app_launcher::replace_aliases(merged.clone().into_iter().collect()).map_err(AstralError::from)?;
+            }
+            aliases = merged;
+        }
+        if let Some(store) = notes_update {
+            crate::notes::replace(app, store).map_err(AstralError::from)?;
+        }
+        if !summary.routines_held.is_empty() {
+            warn!("Synced routines turned off until enabled here: {:?}", summary.routines_held);
+        }
+        let routine_ids = document
+            .routines
+            .iter()
+            .filter(|(_, record)| record.routine.is_some())
+            .map(|(id, _)| id.clone())
+            .collect();
+        if let Err(e) = save_state(&LocalState { routine_ids, aliases }) {
+            warn!("Couldn't save the sync state: {}", e);
+        }
+        return Ok(summary);
+    }
+    Err(AstralError::new(ErrorCode::Timeout, "Another device kept changing the sync file; try again in a moment"))
+}
+
+/// Run one pass now, recording the outcome for `get_sync_status`
+async fn sync(app: &AppHandle) -> AstralResult<SyncSummary> {
+    let settings = crate::settings::load_settings(app.clone()).await?.sync;
+    if !settings.enabled {
+        return Err(AstralError::new(ErrorCode::NotConfigured, "Sync is off"));
+    }
+
+    let _running = RUNNING.lock().await;
+    let result = run_pass(app, &settings).await;
+    let mut status = STATUS.lock().unwrap_or_else(|e| e.into_inner());
+    match &result {
+        Ok(summary) => {
+            info!(
+                "Synced with {:?}: {} routines in, {} out, conversation in: {}, out: {}, {} app names in, {} out, notes in: {}, out: {}",
+                summary.devices,
+                summary.routines_received,
+                summary.routines_sent,
+                summary.conversation_received,
+                summary.conversation_sent,
+                summary.aliases_received,
+                summary.aliases_sent,
+                summary.notes_received,
+                summary.notes_sent
+            );
+            status.last = Some(summary.clone());
+            status.last_error = None;
+            if let Err(e) = app.emit(SYNC_COMPLETED_EVENT, summary) {
+                warn!("Failed to emit sync result: {}", e);
+            }
+        }
+        Err(e) => {
+            warn!("Sync failed: {}", e);
+            status.last_error = Some(e.to_string());
+        }
+    }
+    result
+}
+
+/// Sync on the next tick; called when the sync settings change
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Start the background sync loop; called from setup
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) => {
+                    if settings.sync.enabled {
+                        let _ = sync(&app).await;
+                    }
+                    settings.sync.interval_minutes.max(1)
+                }
+                Err(e) => {
+                    warn!("Couldn't load sync settings: {}", e);
+                    15
+                }
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval * 60)) => {}
+                _ = WAKE.notified() => {}
+            }
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn sync_now(app: AppHandle) -> AstralResult<SyncSummary> {
+    sync(&app).await
+}
+
+#[tauri::command]
+pub async fn get_sync_status(app: AppHandle) -> AstralResult<SyncStatus> {
+    let enabled = crate::settings::load_settings(app).await?.sync.enabled;
+    let status = STATUS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    Ok(SyncStatus { enabled, ..status })
+}
@@ -0,0 +1,472 @@
+// System Events Module
+// Device plug/unplug and session lock/unlock/sleep/resume notifications,
+// surfaced to the UI (`device-changed`, `session-changed`) and to routines as
+// `SystemEvent` triggers ("controller_connected" starts Gaming Mode).
+// Windows listens on a hidden window; other platforms poll.
+
+use tracing::{info, warn};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+pub const DEVICE_CHANGED_EVENT: &str = "device-changed";
+pub const SESSION_CHANGED_EVENT: &str = "session-changed";
+
+/// One physical device shows up under several interfaces; repeats inside this window are dropped
+const DEDUP_WINDOW: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceKind {
+    Controller,
+    Storage,
+    Usb,
+}
+
+impl DeviceKind {
+    fn event_prefix(&self) -> &'static str {
+        match self {
+            Self::Controller => "controller",
+            Self::Storage => "storage",
+            Self::Usb => "usb",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceEvent {
+    pub kind: DeviceKind,
+    /// Hardware id, drive letter or product name, whatever the platform gives us
+    pub name: String,
+    pub connected: bool,
+}
+
+impl DeviceEvent {
+    /// `SystemEvent` types this fires: "controller_connected", "storage_disconnected", ...
+    /// USB devices also fire "device_connected" / "device_disconnected"; a controller
+    /// or drive plugged in over USB arrives as its own USB event too.
+    pub fn event_types(&self) -> Vec<String> {
+        let state = if self.connected { "connected" } else { "disconnected" };
+        let mut types = vec![format!("{}_{}", self.kind.event_prefix(), state)];
+        if self.kind == DeviceKind::Usb {
+            types.push(format!("device_{}", state));
+        }
+        types
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEvent {
+    Locked,
+    Unlocked,
+    Suspended,
+    Resumed,
+}
+
+impl SessionEvent {
+    /// `SystemEvent` type: "session_locked", "system_resumed", ...
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::Locked => "session_locked",
+            Self::Unlocked => "session_unlocked",
+            Self::Suspended => "system_suspended",
+            Self::Resumed => "system_resumed",
+        }
+    }
+}
+
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+static LAST_EVENT: Lazy<Mutex<Option<(DeviceEvent, Instant)>>> = Lazy::new(|| Mutex::new(None));
+static LAST_SESSION_EVENT: Lazy<Mutex<Option<(SessionEvent, Instant)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Start listening for device and session changes
+pub fn start(app: AppHandle) {
+    if APP_HANDLE.set(app).is_err() {
+        warn!("System event listener already started");
+        return;
+    }
+
+    if let Err(e) = std::thread::Builder::new()
+        .name("astral-system-events".to_string())
+        .spawn(platform::listen)
+    {
+        warn!("Failed to start system event listener: {}", e);
+    }
+}
+
+/// Hand a device event to the UI and to routines
+fn dispatch(event: DeviceEvent) {
+    {
+        let mut last = LAST_EVENT.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((previous, at)) = last.as_ref() {
+            if *previous == event && at.elapsed() < DEDUP_WINDOW {
+                return;
+            }
+        }
+        *last = Some((event.clone(), Instant::now()));
+    }
+
+    info!("Device {}: {:?} {}", if event.connected { "connected" } else { "disconnected" }, event.kind, event.name);
+
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    if let Err(e) = app.emit(DEVICE_CHANGED_EVENT, &event) {
+        warn!("Failed to emit {}: {}", DEVICE_CHANGED_EVENT, e);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        for event_type in event.event_types() {
+            crate::commands::fire_system_event(app, &event_type).await;
+        }
+    });
+}
+
+/// Hand a session event to the UI and to routines
+fn dispatch_session(event: SessionEvent) {
+    {
+        // Windows reports resume twice (automatic, then user-initiated)
+        let mut last = LAST_SESSION_EVENT.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((previous, at)) = last.as_ref() {
+            if *previous == event && at.elapsed() < DEDUP_WINDOW {
+                return;
+            }
+        }
+        *last = Some((event, Instant::now()));
+    }
+
+    info!("Session event: {}", event.event_type());
+
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    if let Err(e) = app.emit(SESSION_CHANGED_EVENT, event) {
+        warn!("Failed to emit {}: {}", SESSION_CHANGED_EVENT, e);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        crate::commands::fire_system_event(app, event.event_type()).await;
+    });
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{dispatch, dispatch_session, DeviceEvent, DeviceKind, SessionEvent};
+    use tracing::warn;
+    use std::ffi::c_void;
+    use windows::core::{w, GUID};
+    use windows::Win32::Foundation::{HANDLE, HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::System::RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+        RegisterDeviceNotificationW, TranslateMessage, DBT_DEVICEARRIVAL, DBT_DEVICEREMOVECOMPLETE,
+        DBT_DEVTYP_DEVICEINTERFACE, DBT_DEVTYP_VOLUME, DEVICE_NOTIFY_ALL_INTERFACE_CLASSES,
+        DEVICE_NOTIFY_WINDOW_HANDLE, DEV_BROADCAST_DEVICEINTERFACE_W, DEV_BROADCAST_HDR, DEV_BROADCAST_VOLUME,
+        MSG, WINDOW_EX_STYLE, WINDOW_STYLE, WM_DEVICECHANGE, WM_POWERBROADCAST, WM_WTSSESSION_CHANGE, WNDCLASSW,
+    };
+
+    const GUID_DEVINTERFACE_USB_DEVICE: GUID = GUID::from_u128(0xa5dcbf10_6530_11d2_901f_00c04fb951ed);
+    const GUID_DEVINTERFACE_HID: GUID = GUID::from_u128(0x4d1e55b2_f16f_11cf_88cb_001111000030);
+
+    // WM_WTSSESSION_CHANGE / WM_POWERBROADCAST codes
+    const WTS_SESSION_LOCK: usize = 0x7;
+    const WTS_SESSION_UNLOCK: usize = 0x8;
+    const PBT_APMSUSPEND: usize = 0x4;
+    const PBT_APMRESUMESUSPEND: usize = 0x7;
+    const PBT_APMRESUMEAUTOMATIC: usize = 0x12;
+
+    /// Vendor ids of Sony, Nintendo and Valve; XInput pads carry "IG_" in their HID path
+    const CONTROLLER_VENDORS: &[&str] = &["VID_054C", "VID_057E", "VID_28DE"];
+
+    fn is_controller(path: &str) -> bool {
+        let upper = path.to_uppercase();
+        upper.contains("IG_") || CONTROLLER_VENDORS.iter().any(|v| upper.contains(v))
+    }
+
+    /// "\\?\USB#VID_046D&PID_C52B#5&2a..." -> "VID_046D&PID_C52B"
+    fn hardware_id(path: &str) -> String {
+        path.trim_start_matches(r"\\?\").split('#').nth(1).unwrap_or(path).to_string()
+    }
+
+    unsafe fn device_event(header: *const DEV_BROADCAST_HDR, connected: bool) -> Option<DeviceEvent> {
+        match (*header).dbch_devicetype {
+            DBT_DEVTYP_VOLUME => {
+                let volume = &*(header as *const DEV_BROADCAST_VOLUME);
+                let letter = (0..26u8).find(|bit| volume.dbcv_unitmask & (1 << bit) != 0)?;
+                Some(DeviceEvent {
+                    kind: DeviceKind::Storage,
+                    name: format!("{}:", (b'A' + letter) as char),
+                    connected,
+                })
+            }
+            DBT_DEVTYP_DEVICEINTERFACE => {
+                let interface = &*(header as *const DEV_BROADCAST_DEVICEINTERFACE_W);
+                // dbcc_name is a NUL-terminated string running past the struct
+                let name_ptr = interface.dbcc_name.as_ptr();
+                let len = (0..).take_while(|&i| *name_ptr.add(i) != 0).count();
+                let path = String::from_utf16_lossy(std::slice::from_raw_parts(name_ptr, len));
+
+                let kind = if interface.dbcc_classguid == GUID_DEVINTERFACE_HID && is_controller(&path) {
+                    DeviceKind::Controller
+                } else if interface.dbcc_classguid == GUID_DEVINTERFACE_USB_DEVICE {
+                    DeviceKind::Usb
+                } else {
+                    return None;
+                };
+                Some(DeviceEvent { kind, name: hardware_id(&path), connected })
+            }
+            _ => None,
+        }
+    }
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if msg == WM_DEVICECHANGE && lparam.0 != 0 {
+            let connected = match wparam.0 as u32 {
+                DBT_DEVICEARRIVAL => Some(true),
+                DBT_DEVICEREMOVECOMPLETE => Some(false),
+                _ => None,
+            };
+            if let Some(connected) = connected {
+                if let Some(event) = device_event(lparam.0 as *const DEV_BROADCAST_HDR, connected) {
+                    dispatch(event);
+                }
+            }
+            return LRESULT(1);
+        }
+
+        let session = match (msg, wparam.0) {
+            (WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK) => Some(SessionEvent::Locked),
+            (WM_WTSSESSION_CHANGE, WTS_SESSION_UNLOCK) => Some(SessionEvent::Unlocked),
+            (WM_POWERBROADCAST, PBT_APMSUSPEND) => Some(SessionEvent::Suspended),
+            (WM_POWERBROADCAST, PBT_APMRESUMESUSPEND | PBT_APMRESUMEAUTOMATIC) => Some(SessionEvent::Resumed),
+            _ => None,
+        };
+        if let Some(event) = session {
+            dispatch_session(event);
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    /// Run a hidden window and pump its messages forever. It has to be a
+    /// top-level window: message-only windows miss broadcasts like volume arrival.
+    pub fn listen() {
+        unsafe {
+            let instance = match GetModuleHandleW(None) {
+                Ok(module) => module.into(),
+                Err(e) => {
+                    warn!("System events: no module handle: {}", e);
+                    return;
+                }
+            };
+
+            let class_name = w!("AstralSystemEvents");
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                hInstance: instance,
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            if RegisterClassW(&class) == 0 {
+                warn!("System events: failed to register window class");
+                return;
+            }
+
+            let hwnd = CreateWindowExW(
+                WINDOW_EX_STYLE(0),
+                class_name,
+                w!("ASTRAL system events"),
+                WINDOW_STYLE(0),
+                0,
+                0,
+                0,
+                0,
+                None,
+                None,
+                instance,
+                None,
+            );
+            if hwnd.0 == 0 {
+                warn!("System events: failed to create window");
+                return;
+            }
+
+            // Interface arrivals aren't broadcast; volumes are
+            let filter = DEV_BROADCAST_DEVICEINTERFACE_W {
+                dbcc_size: std::mem::size_of::<DEV_BROADCAST_DEVICEINTERFACE_W>() as u32,
+                dbcc_devicetype: DBT_DEVTYP_DEVICEINTERFACE.0,
+                ..Default::default()
+            };
+            if let Err(e) = RegisterDeviceNotificationW(
+                HANDLE(hwnd.0),
+                &filter as *const _ as *const c_void,
+                DEVICE_NOTIFY_WINDOW_HANDLE | DEVICE_NOTIFY_ALL_INTERFACE_CLASSES,
+            ) {
+                warn!("System events: device notifications unavailable: {}", e);
+            }
+            if let Err(e) = WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) {
+                warn!("System events: session notifications unavailable: {}", e);
+            }
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, HWND(0), 0, 0).as_bool() {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::{dispatch, dispatch_session, DeviceEvent, DeviceKind, SessionEvent};
+    use std::collections::HashSet;
+    use std::time::{Duration, SystemTime};
+
+    const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+    /// A wall-clock jump this much longer than the poll interval means we were asleep
+    const SLEEP_GAP: Duration = Duration::from_secs(30);
+
+    /// Whether the screen is locked; None when we can't tell
+    #[cfg(target_os = "macos")]
+    fn session_locked() -> Option<bool> {
+        // ioreg exposes the console session's lock flag
+        let output = std::process::Command::new("ioreg").args(["-n", "Root", "-d1", "-a"]).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        // The key is only present while locked
+        let Some(key) = text.find("CGSSessionScreenIsLocked") else {
+            return output.status.success().then_some(false);
+        };
+        Some(text[key..].lines().nth(1).is_some_and(|l| l.contains("true")))
+    }
+
+    /// Whether the screen is locked; None when we can't tell
+    #[cfg(not(target_os = "macos"))]
+    fn session_locked() -> Option<bool> {
+        let session = std::env::var("XDG_SESSION_ID").ok()?;
+        let output = std::process::Command::new("loginctl")
+            .args(["show-session", &session, "-p", "LockedHint", "--value"])
+            .output()
+            .ok()?;
+        match String::from_utf8_lossy(&output.stdout).trim() {
+            "yes" => Some(true),
+            "no" => Some(false),
+            _ => None,
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn snapshot() -> HashSet<(DeviceKind, String)> {
+        let mut devices = HashSet::new();
+
+        // "  +-o USB Receiver@14200000  <class IOUSBHostDevice, ...>"
+        if let Ok(output) = std::process::Command::new("ioreg").args(["-p", "IOUSB", "-w0"]).output() {
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                let Some((_, rest)) = line.split_once("+-o ") else {
+                    continue;
+                };
+                let name = rest.split('@').next().unwrap_or(rest).trim().to_string();
+                if name.is_empty() || name.contains("Root Hub") {
+                    continue;
+                }
+                let lower = name.to_lowercase();
+                let kind = if lower.contains("controller") || lower.contains("gamepad") {
+                    DeviceKind::Controller
+                } else {
+                    DeviceKind::Usb
+                };
+                devices.insert((kind, name));
+            }
+        }
+
+        if let Ok(entries) = std::fs::read_dir("/Volumes") {
+            for entry in entries.filter_map(|e| e.ok()) {
+                // The boot volume is a symlink to /
+                if entry.file_type().map(|t| t.is_symlink()).unwrap_or(false) {
+                    continue;
+                }
+                devices.insert((DeviceKind::Storage, entry.file_name().to_string_lossy().to_string()));
+            }
+        }
+
+        devices
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn snapshot() -> HashSet<(DeviceKind, String)> {
+        let mut devices = HashSet::new();
+
+        // Only entries with a product string are devices; the rest are interfaces and hubs
+        if let Ok(entries) = std::fs::read_dir("/sys/bus/usb/devices") {
+            for entry in entries.filter_map(|e| e.ok()) {
+                if let Ok(product) = std::fs::read_to_string(entry.path().join("product")) {
+                    let product = product.trim();
+                    if !product.is_empty() && !product.contains("Host Controller") {
+                        devices.insert((DeviceKind::Usb, product.to_string()));
+                    }
+                }
+            }
+        }
+
+        if let Ok(entries) = std::fs::read_dir("/dev/input") {
+            for entry in entries.filter_map(|e| e.ok()) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with("js") {
+                    devices.insert((DeviceKind::Controller, name));
+                }
+            }
+        }
+
+        let user = std::env::var("USER").unwrap_or_default();
+        for root in [format!("/media/{}", user), format!("/run/media/{}", user)] {
+            if let Ok(entries) = std::fs::read_dir(root) {
+                for entry in entries.filter_map(|e| e.ok()) {
+                    devices.insert((DeviceKind::Storage, entry.file_name().to_string_lossy().to_string()));
+                }
+            }
+        }
+
+        devices
+    }
+
+    /// Diff device snapshots and the lock state; what's attached at startup isn't reported.
+    /// Suspend can't be seen from here, so both events fire on resume.
+    pub fn listen() {
+        let mut known = snapshot();
+        let mut locked = session_locked();
+        let mut last_tick = SystemTime::now();
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let now = SystemTime::now();
+            if now.duration_since(last_tick).is_ok_and(|gap| gap > POLL_INTERVAL + SLEEP_GAP) {
+                dispatch_session(SessionEvent::Suspended);
+                dispatch_session(SessionEvent::Resumed);
+            }
+            last_tick = now;
+
+            let now_locked = session_locked();
+            if let (Some(was), Some(is)) = (locked, now_locked) {
+                if was != is {
+                    dispatch_session(if is { SessionEvent::Locked } else { SessionEvent::Unlocked });
+                }
+            }
+            locked = now_locked.or(locked);
+
+            let current = snapshot();
+
+            for (kind, name) in current.difference(&known) {
+                dispatch(DeviceEvent { kind: *kind, name: name.clone(), connected: true });
+            }
+            for (kind, name) in known.difference(&current) {
+                dispatch(DeviceEvent { kind: *kind, name: name.clone(), connected: false });
+            }
+            known = current;
+        }
+    }
+}
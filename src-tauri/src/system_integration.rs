@@ -1,57 +1,407 @@
 // System Integration Module
 // Handles Windows API interactions and system-level operations
 
-use log::info;
+use tracing::info;
 use anyhow::Result;
 
-#[cfg(target_os = "windows")]
-use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+#[allow(dead_code)]
+pub async fn launch_application(app_name: &str) -> Result<()> {
+    info!("Launching application: {}", app_name);
+    // TODO: Implement application launching
+    Ok(())
+}
 
-use crate::commands::SystemInfo;
+/// File types that run code when opened. `open_path` refuses these unless the
+/// caller explicitly allows it, so a misheard search result can't start a program.
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "com", "bat", "cmd", "msi", "msc", "scr", "pif", "cpl", "ps1", "psm1", "vbs", "vbe", "js", "jse",
+    "wsf", "wsh", "hta", "reg", "lnk", "jar", "sh", "bash", "command", "app", "appimage", "run", "desktop",
+];
 
-#[cfg(target_os = "windows")]
-pub fn get_windows_system_info() -> Result<SystemInfo> {
-    unsafe {
-        let mut mem_status = MEMORYSTATUSEX {
-            dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
-            ..Default::default()
-        };
+/// URL schemes `open_path` hands to the default browser/mail client
+const ALLOWED_URL_SCHEMES: &[&str] = &["http://", "https://", "mailto:"];
 
-        GlobalMemoryStatusEx(&mut mem_status)?;
+/// What `open_path` ended up opening
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum OpenedKind {
+    File,
+    Folder,
+    Url,
+}
 
-        Ok(SystemInfo {
-            cpu_usage: get_cpu_usage(),
-            memory_used: mem_status.ullTotalPhys - mem_status.ullAvailPhys,
-            memory_total: mem_status.ullTotalPhys,
-            gpu_usage: None, // TODO: Implement GPU monitoring
-        })
-    }
+pub fn is_executable_path(path: &std::path::Path) -> bool {
+    let by_extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| EXECUTABLE_EXTENSIONS.contains(&e.as_str()));
+
+    #[cfg(unix)]
+    let by_mode = {
+        use std::os::unix::fs::PermissionsExt;
+        path.is_file()
+            && std::fs::metadata(path)
+                .map(|m| m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+    };
+    #[cfg(not(unix))]
+    let by_mode = false;
+
+    by_extension || by_mode
+}
+
+/// Open a file, folder or URL with its default handler. URLs are limited to
+/// http(s)/mailto, and executables are refused unless `allow_executables` is set.
+pub fn open_path(target: &str, allow_executables: bool) -> Result<OpenedKind> {
+    let target = target.trim().trim_matches('"');
+    let lower = target.to_lowercase();
+
+    let kind = if ALLOWED_URL_SCHEMES.iter().any(|scheme| lower.starts_with(scheme)) {
+        OpenedKind::Url
+    } else if lower.contains("://") || lower.starts_with("shell:") {
+        anyhow::bail!("Refusing to open unsupported link: {}", target);
+    } else {
+        let path = std::path::Path::new(target);
+        if !path.exists() {
+            anyhow::bail!("{} doesn't exist", target);
+        }
+        if path.is_dir() {
+            OpenedKind::Folder
+        } else {
+            if !allow_executables && is_executable_path(path) {
+                anyhow::bail!("{} is a program; not opening it without confirmation", target);
+            }
+            OpenedKind::File
+        }
+    };
+
+    info!("Opening {:?}: {}", kind, target);
+    shell_open(target)?;
+    Ok(kind)
 }
 
 #[cfg(target_os = "windows")]
-fn get_cpu_usage() -> f32 {
-    // TODO: Implement proper CPU usage monitoring
-    // For now, return placeholder
-    0.0
+fn shell_open(target: &str) -> Result<()> {
+    // explorer.exe hands the target to its registered handler without a console window
+    std::process::Command::new("explorer").arg(target).spawn()?;
+    Ok(())
 }
 
-#[allow(dead_code)]
-pub async fn launch_application(app_name: &str) -> Result<()> {
-    info!("Launching application: {}", app_name);
-    // TODO: Implement application launching
+#[cfg(target_os = "macos")]
+fn shell_open(target: &str) -> Result<()> {
+    std::process::Command::new("open").arg(target).spawn()?;
     Ok(())
 }
 
-#[allow(dead_code)]
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn shell_open(target: &str) -> Result<()> {
+    std::process::Command::new("xdg-open").arg(target).spawn()?;
+    Ok(())
+}
+
+/// Media playback actions, sent as virtual media keys so they reach whichever
+/// player currently owns media focus (Spotify, browsers, etc.)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaAction {
+    PlayPause,
+    Next,
+    Previous,
+    Stop,
+}
+
+impl MediaAction {
+    /// Parse an automation action name ("play", "pause", "next", ...)
+    pub fn parse(action: &str) -> Option<Self> {
+        match action.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+            // Media keys only have a toggle; play and pause both map to it
+            "play" | "pause" | "play_pause" | "toggle" | "resume" => Some(Self::PlayPause),
+            "next" | "next_track" | "skip" => Some(Self::Next),
+            "previous" | "prev" | "previous_track" | "back" => Some(Self::Previous),
+            "stop" => Some(Self::Stop),
+            _ => None,
+        }
+    }
+
+    /// Recognize spoken media commands like "pause the music" or "next song".
+    /// The phrase has to be the whole instruction, so "what was the last
+    /// song?" and "stop playing games" don't press anything.
+    pub fn from_command(command: &str) -> Option<Self> {
+        let text = crate::intents::imperative(command);
+
+        const NEXT: &[&str] = &[
+            "next track", "next song", "skip song", "skip track", "skip this song", "skip this track",
+            "play the next song", "play the next track",
+        ];
+        const PREVIOUS: &[&str] = &[
+            "previous track", "previous song", "last song", "last track", "go back a song",
+            "play the previous song", "play the previous track",
+        ];
+        const STOP: &[&str] = &["stop the music", "stop music", "stop playback", "stop playing"];
+        const TOGGLE: &[&str] = &[
+            "pause", "resume", "unpause", "pause the music", "pause music", "pause the song", "pause the video",
+            "pause playback", "resume the music", "resume music", "resume playback", "play the music",
+        ];
+
+        if NEXT.contains(&text.as_str()) {
+            Some(Self::Next)
+        } else if PREVIOUS.contains(&text.as_str()) {
+            Some(Self::Previous)
+        } else if STOP.contains(&text.as_str()) {
+            Some(Self::Stop)
+        } else if TOGGLE.contains(&text.as_str()) {
+            Some(Self::PlayPause)
+        } else {
+            None
+        }
+    }
+
+    /// Short confirmation to speak back
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::PlayPause => "Toggled playback",
+            Self::Next => "Skipping to the next track",
+            Self::Previous => "Going back to the previous track",
+            Self::Stop => "Stopped playback",
+        }
+    }
+}
+
 pub async fn control_media(action: &str) -> Result<()> {
     info!("Media control: {}", action);
-    // TODO: Implement media control (play/pause/next/prev)
+
+    let action = MediaAction::parse(action)
+        .ok_or_else(|| anyhow::anyhow!("Unknown media action: {}", action))?;
+
+    send_media_key(action)
+}
+
+/// Press the media key for `action`
+#[cfg(target_os = "windows")]
+pub fn send_media_key(action: MediaAction) -> Result<()> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+        KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, VIRTUAL_KEY, VK_MEDIA_NEXT_TRACK,
+        VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK, VK_MEDIA_STOP,
+    };
+
+    let vk = match action {
+        MediaAction::PlayPause => VK_MEDIA_PLAY_PAUSE,
+        MediaAction::Next => VK_MEDIA_NEXT_TRACK,
+        MediaAction::Previous => VK_MEDIA_PREV_TRACK,
+        MediaAction::Stop => VK_MEDIA_STOP,
+    };
+
+    fn key(vk: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: vk,
+                    wScan: 0,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        }
+    }
+
+    // Key down + key up
+    let inputs = [
+        key(vk, KEYEVENTF_EXTENDEDKEY),
+        key(vk, KEYEVENTF_EXTENDEDKEY | KEYEVENTF_KEYUP),
+    ];
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        return Err(anyhow::anyhow!("SendInput sent {} of {} media key events", sent, inputs.len()));
+    }
+
     Ok(())
 }
 
-#[allow(dead_code)]
-pub async fn search_files(query: &str) -> Result<Vec<String>> {
-    info!("Searching files: {}", query);
-    // TODO: Implement file search
-    Ok(vec![])
+#[cfg(not(target_os = "windows"))]
+pub fn send_media_key(_action: MediaAction) -> Result<()> {
+    Err(anyhow::anyhow!("Media control is only supported on Windows"))
+}
+
+/// Spoken volume commands: "set volume to 40", "volume up by 10", "mute"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeCommand {
+    Set(u8),
+    Adjust(i32),
+    Mute(bool),
+}
+
+/// Step used for "louder" / "volume up" without an explicit amount
+const DEFAULT_VOLUME_STEP: i32 = 10;
+
+impl VolumeCommand {
+    /// Only instructions count: "is the volume 50?" or "mute microsoft teams"
+    /// aren't volume commands, and "mute the mic" is the assistant's own mute
+    pub fn from_command(command: &str) -> Option<Self> {
+        let text = crate::intents::imperative(command);
+        let text = text.as_str();
+
+        const TARGETS: &[&str] = &["", " the sound", " sound", " the volume", " volume", " audio", " the audio", " the speakers"];
+        if let Some(rest) = text.strip_prefix("unmute") {
+            return TARGETS.contains(&rest).then_some(Self::Mute(false));
+        }
+        if let Some(rest) = text.strip_prefix("mute") {
+            return TARGETS.contains(&rest).then_some(Self::Mute(true));
+        }
+
+        const UP: &[&str] = &["volume up", "turn it up", "turn the volume up", "turn up the volume", "louder", "increase the volume", "increase volume", "raise the volume"];
+        const DOWN: &[&str] = &["volume down", "turn it down", "turn the volume down", "turn down the volume", "quieter", "decrease the volume", "decrease volume", "lower the volume"];
+        for (phrases, sign) in [(UP, 1), (DOWN, -1)] {
+            if let Some(rest) = phrases.iter().find_map(|p| text.strip_prefix(p)) {
+                let amount = match rest.trim() {
+                    "" => DEFAULT_VOLUME_STEP,
+                    rest => parse_amount(rest.strip_prefix("by ").unwrap_or(rest))?,
+                };
+                return Some(Self::Adjust(sign * amount));
+            }
+        }
+
+        const SET: &[&str] = &["set the volume to ", "set volume to ", "change the volume to ", "turn the volume to ", "volume to ", "volume "];
+        let level = parse_amount(SET.iter().find_map(|p| text.strip_prefix(p))?)?;
+        Some(Self::Set(level as u8))
+    }
+}
+
+/// "40", "40%" or "40 percent", capped to 0-100
+fn parse_amount(text: &str) -> Option<i32> {
+    let text = text.trim();
+    let text = text.strip_suffix(" percent").or_else(|| text.strip_suffix('%')).unwrap_or(text);
+    text.trim().parse::<i64>().ok().map(|amount| amount.clamp(0, 100) as i32)
+}
+
+/// Run a parsed volume command, returning a confirmation to speak back
+pub fn apply_volume_command(command: VolumeCommand) -> Result<String> {
+    match command {
+        VolumeCommand::Set(level) => {
+            set_master_volume(level)?;
+            Ok(format!("Volume set to {}%", level))
+        }
+        VolumeCommand::Adjust(delta) => {
+            let level = adjust_master_volume(delta)?;
+            Ok(format!("Volume is now {}%", level))
+        }
+        VolumeCommand::Mute(muted) => {
+            set_master_mute(muted)?;
+            Ok(if muted { "Sound muted".to_string() } else { "Sound unmuted".to_string() })
+        }
+    }
+}
+
+/// Change the master volume by `delta` percentage points, returning the new level
+pub fn adjust_master_volume(delta: i32) -> Result<u8> {
+    let current = get_master_volume()? as i32;
+    let level = current.saturating_add(delta).clamp(0, 100) as u8;
+    set_master_volume(level)?;
+    Ok(level)
+}
+
+#[cfg(target_os = "windows")]
+mod volume {
+    use anyhow::Result;
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::Media::Audio::{eConsole, eRender, IMMDeviceEnumerator, MMDeviceEnumerator};
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+
+    /// Run `f` against the default render endpoint's volume control
+    fn with_endpoint_volume<T>(f: impl FnOnce(&IAudioEndpointVolume) -> windows::core::Result<T>) -> Result<T> {
+        unsafe {
+            // Already-initialized (or initialized in another mode) is fine
+            let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+
+            let enumerator: IMMDeviceEnumerator = CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+            let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
+            let endpoint: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None)?;
+
+            Ok(f(&endpoint)?)
+        }
+    }
+
+    pub fn get_level() -> Result<u8> {
+        let scalar = with_endpoint_volume(|v| unsafe { v.GetMasterVolumeLevelScalar() })?;
+        Ok((scalar * 100.0).round().clamp(0.0, 100.0) as u8)
+    }
+
+    pub fn set_level(level: u8) -> Result<()> {
+        let scalar = level.min(100) as f32 / 100.0;
+        with_endpoint_volume(|v| unsafe { v.SetMasterVolumeLevelScalar(scalar, std::ptr::null()) })
+    }
+
+    pub fn get_mute() -> Result<bool> {
+        let muted = with_endpoint_volume(|v| unsafe { v.GetMute() })?;
+        Ok(muted.as_bool())
+    }
+
+    pub fn set_mute(muted: bool) -> Result<()> {
+        with_endpoint_volume(|v| unsafe { v.SetMute(BOOL::from(muted), std::ptr::null()) })
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod volume {
+    use anyhow::{bail, Result};
+
+    pub fn get_level() -> Result<u8> {
+        bail!("Volume control is only supported on Windows")
+    }
+
+    pub fn set_level(_level: u8) -> Result<()> {
+        bail!("Volume control is only supported on Windows")
+    }
+
+    pub fn get_mute() -> Result<bool> {
+        bail!("Volume control is only supported on Windows")
+    }
+
+    pub fn set_mute(_muted: bool) -> Result<()> {
+        bail!("Volume control is only supported on Windows")
+    }
+}
+
+/// Master output volume, 0-100
+pub fn get_master_volume() -> Result<u8> {
+    volume::get_level()
+}
+
+pub fn set_master_volume(level: u8) -> Result<()> {
+    info!("Setting volume to {}%", level);
+    volume::set_level(level)
+}
+
+pub fn get_master_mute() -> Result<bool> {
+    volume::get_mute()
+}
+
+pub fn set_master_mute(muted: bool) -> Result<()> {
+    info!("Setting speaker mute: {}", muted);
+    volume::set_mute(muted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn media_instructions() {
+        assert_eq!(MediaAction::from_command("Pause the music."), Some(MediaAction::PlayPause));
+        assert_eq!(MediaAction::from_command("could you skip this song please"), Some(MediaAction::Next));
+        assert_eq!(MediaAction::from_command("Last song"), Some(MediaAction::Previous));
+        assert_eq!(MediaAction::from_command("stop playing"), Some(MediaAction::Stop));
+        assert_eq!(MediaAction::from_command("resume"), Some(MediaAction::PlayPause));
+    }
+
+    #[test]
+    fn media_questions_and_remarks_are_ignored() {
+        assert_eq!(MediaAction::from_command("What was the last song?"), None);
+        assert_eq!(MediaAction::from_command("what's the next track"), None);
+        assert_eq!(MediaAction::from_command("stop playing games"), None);
+        assert_eq!(MediaAction::from_command("is the music paused?"), None);
+        assert_eq!(MediaAction::from_command("why did you pause the music"), None);
+    }
 }
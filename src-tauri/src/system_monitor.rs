@@ -1,177 +1,398 @@
+// System Monitor Module
+// CPU, memory and process stats from the sysinfo crate. One `System` is kept
+// for the life of the app so CPU usage is measured between refreshes instead
+// of spawning a process per query. A background loop samples it every few
+// seconds into a ring buffer the dashboard charts from, and a second one
+// pushes live `system-stats` events while the window is on screen.
+
+use crate::alerts::AlertTracker;
+use tracing::warn;
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime};
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const SYSTEM_STATS_EVENT: &str = "system-stats";
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// Floor for the `system.stats_stream_interval_ms` setting
+const MIN_STREAM_INTERVAL: Duration = Duration::from_millis(500);
+/// How often a hidden window is checked for coming back
+const HIDDEN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// 24 hours of samples
+const HISTORY_CAPACITY: usize = (24 * 60 * 60 / SAMPLE_INTERVAL.as_secs()) as usize;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoreStats {
+    /// 0-based logical core number
+    pub index: usize,
+    pub usage: f32,
+    pub frequency_mhz: u64,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemStats {
     pub cpu_usage: f32,
+    /// Per logical core, in core order
+    pub cores: Vec<CoreStats>,
     pub memory_usage: f32,
     pub memory_total: u64,
     pub memory_used: u64,
     pub gpu_usage: Option<f32>,
+    pub process_count: usize,
     pub timestamp: u64,
 }
 
-#[cfg(target_os = "windows")]
-mod windows_monitor {
-    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
-    
-    pub fn get_cpu_usage() -> Result<f32, String> {
-        // Windows CPU usage requires sampling over time
-        // For now, return a placeholder that we'll improve with proper monitoring
-        Ok(0.0)
-    }
-    
-    pub fn get_memory_usage() -> Result<(f32, u64, u64), String> {
-        unsafe {
-            let mut mem_status = MEMORYSTATUSEX {
-                dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
-                ..Default::default()
-            };
-            
-            if GlobalMemoryStatusEx(&mut mem_status).is_ok() {
-                let total = mem_status.ullTotalPhys;
-                let available = mem_status.ullAvailPhys;
-                let used = total - available;
-                let usage_percent = (used as f64 / total as f64 * 100.0) as f32;
-                
-                Ok((usage_percent, total, used))
-            } else {
-                Err("Failed to get memory status".to_string())
+/// One point in the stats history; per-core and process data aren't kept
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StatsSample {
+    pub timestamp: u64,
+    pub cpu_usage: f32,
+    pub memory_usage: f32,
+    pub memory_used: u64,
+}
+
+static HISTORY: Lazy<Mutex<VecDeque<StatsSample>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(HISTORY_CAPACITY)));
+
+struct Monitor {
+    system: System,
+    /// When CPU usage was last sampled; sysinfo needs a gap between samples
+    last_cpu_refresh: Option<Instant>,
+}
+
+static MONITOR: Lazy<Mutex<Monitor>> = Lazy::new(|| {
+    Mutex::new(Monitor {
+        system: System::new_with_specifics(
+            RefreshKind::new()
+                .with_cpu(CpuRefreshKind::everything())
+                .with_memory(MemoryRefreshKind::new().with_ram()),
+        ),
+        last_cpu_refresh: None,
+    })
+});
+
+impl Monitor {
+    fn refresh(&mut self) {
+        match self.last_cpu_refresh {
+            // The first sample has no baseline, so take two
+            None => {
+                self.system.refresh_cpu();
+                std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+                self.system.refresh_cpu();
+                self.last_cpu_refresh = Some(Instant::now());
+            }
+            // Refreshing sooner than this returns garbage, so keep the last sample
+            Some(last) if last.elapsed() >= sysinfo::MINIMUM_CPU_UPDATE_INTERVAL => {
+                self.system.refresh_cpu();
+                self.last_cpu_refresh = Some(Instant::now());
             }
+            Some(_) => {}
         }
+        self.system.refresh_memory();
+        self.system.refresh_processes();
     }
-    
-    pub fn get_gpu_usage() -> Result<Option<f32>, String> {
-        // GPU monitoring requires vendor-specific APIs (NVML for NVIDIA, etc.)
-        // Return None for now - can be implemented later with GPU libraries
-        Ok(None)
-    }
 }
 
-#[cfg(not(target_os = "windows"))]
-mod windows_monitor {
-    pub fn get_cpu_usage() -> Result<f32, String> {
-        Err("CPU monitoring only available on Windows".to_string())
+fn refreshed_monitor() -> MutexGuard<'static, Monitor> {
+    let mut monitor = MONITOR.lock().unwrap_or_else(|e| e.into_inner());
+    monitor.refresh();
+    monitor
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Current CPU, memory and process stats. Blocking on the first call only.
+pub fn get_system_stats() -> Result<SystemStats, String> {
+    let monitor = refreshed_monitor();
+    let system = &monitor.system;
+
+    let memory_total = system.total_memory();
+    let memory_used = system.used_memory();
+    let memory_usage = if memory_total > 0 {
+        (memory_used as f64 / memory_total as f64 * 100.0) as f32
+    } else {
+        0.0
+    };
+
+    Ok(SystemStats {
+        cpu_usage: system.global_cpu_info().cpu_usage(),
+        cores: system
+            .cpus()
+            .iter()
+            .enumerate()
+            .map(|(index, cpu)| CoreStats {
+                index,
+                usage: cpu.cpu_usage(),
+                frequency_mhz: cpu.frequency(),
+            })
+            .collect(),
+        memory_usage,
+        memory_total,
+        memory_used,
+        // Needs vendor APIs (NVML, ADLX); not wired up yet
+        gpu_usage: None,
+        process_count: system.processes().len(),
+        timestamp: unix_timestamp(),
+    })
+}
+
+fn record(stats: &SystemStats) {
+    let mut history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    if history.len() == HISTORY_CAPACITY {
+        history.pop_front();
     }
-    
-    pub fn get_memory_usage() -> Result<(f32, u64, u64), String> {
-        Err("Memory monitoring only available on Windows".to_string())
+    history.push_back(StatsSample {
+        timestamp: stats.timestamp,
+        cpu_usage: stats.cpu_usage,
+        memory_usage: stats.memory_usage,
+        memory_used: stats.memory_used,
+    });
+}
+
+/// Samples from the last `range` seconds, averaged into buckets of
+/// `resolution` seconds (0 returns the raw samples)
+pub fn stats_history(range: u64, resolution: u64) -> Vec<StatsSample> {
+    let since = unix_timestamp().saturating_sub(range);
+    let history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let samples = history.iter().filter(|s| s.timestamp >= since);
+
+    if resolution <= SAMPLE_INTERVAL.as_secs() {
+        return samples.copied().collect();
     }
-    
-    pub fn get_gpu_usage() -> Result<Option<f32>, String> {
-        Ok(None)
+
+    let mut buckets: Vec<(StatsSample, u64)> = Vec::new();
+    for sample in samples {
+        let bucket_start = sample.timestamp - sample.timestamp % resolution;
+        match buckets.last_mut() {
+            Some((bucket, count)) if bucket.timestamp == bucket_start => {
+                bucket.cpu_usage += sample.cpu_usage;
+                bucket.memory_usage += sample.memory_usage;
+                bucket.memory_used += sample.memory_used;
+                *count += 1;
+            }
+            _ => buckets.push((StatsSample { timestamp: bucket_start, ..*sample }, 1)),
+        }
     }
+
+    buckets
+        .into_iter()
+        .map(|(bucket, count)| StatsSample {
+            timestamp: bucket.timestamp,
+            cpu_usage: bucket.cpu_usage / count as f32,
+            memory_usage: bucket.memory_usage / count as f32,
+            memory_used: bucket.memory_used / count,
+        })
+        .collect()
 }
 
-// CPU usage tracker with proper sampling
-lazy_static::lazy_static! {
-    static ref CPU_TRACKER: Arc<Mutex<CpuTracker>> = Arc::new(Mutex::new(CpuTracker::new()));
+/// Sample stats into the history every few seconds and check them against
+/// the alert rules in settings
+pub fn start_monitor_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tracker = AlertTracker::default();
+
+        loop {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+            let Ok(Ok(stats)) = tokio::task::spawn_blocking(get_system_stats).await else { continue };
+            record(&stats);
+
+            let rules = match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) => settings.system.alert_rules,
+                Err(e) => {
+                    warn!("Couldn't load alert rules: {}", e);
+                    continue;
+                }
+            };
+            let checked = {
+                let rules = rules.clone();
+                tokio::task::spawn_blocking(move || {
+                    let alerts = tracker.check(&rules, &stats);
+                    (tracker, alerts)
+                })
+                .await
+            };
+            let Ok((returned, alerts)) = checked else {
+                tracker = AlertTracker::default();
+                continue;
+            };
+            tracker = returned;
+            for alert in alerts {
+                crate::alerts::raise(&app, &rules, alert).await;
+            }
+        }
+    });
 }
 
-struct CpuTracker {
-    last_measurement: Option<SystemTime>,
-    last_cpu_usage: f32,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootInfo {
+    pub uptime_secs: u64,
+    /// Unix seconds
+    pub boot_time: u64,
+    /// An installed update is waiting on a restart; None where that can't be told
+    pub pending_reboot: Option<bool>,
 }
 
-impl CpuTracker {
-    fn new() -> Self {
-        Self {
-            last_measurement: None,
-            last_cpu_usage: 0.0,
+/// Uptime past which the spoken answer suggests a restart anyway
+const LONG_UPTIME: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+impl BootInfo {
+    /// Short sentence to speak back, with a nudge to restart when it's due
+    pub fn describe(&self) -> String {
+        let days = self.uptime_secs / 86_400;
+        let hours = self.uptime_secs % 86_400 / 3600;
+        let minutes = self.uptime_secs % 3600 / 60;
+        let plural = |n: u64, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+        let uptime = match (days, hours) {
+            (0, 0) => plural(minutes, "minute"),
+            (0, _) => format!("{} and {}", plural(hours, "hour"), plural(minutes, "minute")),
+            _ => format!("{} and {}", plural(days, "day"), plural(hours, "hour")),
+        };
+
+        let mut reply = format!("Your computer has been on for {}.", uptime);
+        if self.pending_reboot == Some(true) {
+            reply.push_str(" An update is waiting on a restart, so you should reboot when you get a chance.");
+        } else if self.uptime_secs >= LONG_UPTIME.as_secs() {
+            reply.push_str(" It might be worth a restart soon.");
         }
+        reply
     }
-    
-    fn get_usage(&mut self) -> f32 {
-        let now = SystemTime::now();
-        
-        // Update measurement if enough time has passed (1 second)
-        if let Some(last) = self.last_measurement {
-            if now.duration_since(last).unwrap_or(Duration::from_secs(0)) < Duration::from_secs(1) {
-                return self.last_cpu_usage;
-            }
+}
+
+pub fn boot_info() -> BootInfo {
+    BootInfo {
+        uptime_secs: System::uptime(),
+        boot_time: System::boot_time(),
+        pending_reboot: platform::pending_reboot(),
+    }
+}
+
+/// "how long has my PC been on", "what's my uptime", "when did I last reboot"
+pub fn is_uptime_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    lower.contains("uptime")
+        || (lower.contains("how long") && (lower.contains("been on") || lower.contains("been running") || lower.contains("been up")))
+        || lower.contains("last reboot")
+        || lower.contains("last restart")
+        || lower.contains("last shut down")
+        || lower.contains("last boot")
+        || lower.contains("need to restart")
+        || lower.contains("need to reboot")
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    /// The keys Windows Update, servicing and installers set while a restart is pending
+    pub fn pending_reboot() -> Option<bool> {
+        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+        let key_exists = |path: &str| hklm.open_subkey(path).is_ok();
+
+        let pending = key_exists(r"SOFTWARE\Microsoft\Windows\CurrentVersion\Component Based Servicing\RebootPending")
+            || key_exists(r"SOFTWARE\Microsoft\Windows\CurrentVersion\WindowsUpdate\Auto Update\RebootRequired")
+            || hklm
+                .open_subkey(r"SYSTEM\CurrentControlSet\Control\Session Manager")
+                .and_then(|k| k.get_raw_value("PendingFileRenameOperations"))
+                .is_ok();
+        Some(pending)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    // softwareupdate can say so, but takes a network round trip to answer
+    pub fn pending_reboot() -> Option<bool> {
+        None
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use std::path::Path;
+    use sysinfo::System;
+
+    /// Debian and Ubuntu drop a flag file; elsewhere an upgraded kernel no
+    /// longer has modules for the running one
+    pub fn pending_reboot() -> Option<bool> {
+        if Path::new("/var/run/reboot-required").exists() {
+            return Some(true);
         }
-        
-        // Get new measurement
-        if let Ok(usage) = sysinfo::get_cpu_usage() {
-            self.last_cpu_usage = usage;
-            self.last_measurement = Some(now);
+        let modules = Path::new("/lib/modules");
+        if !modules.exists() {
+            return None;
         }
-        
-        self.last_cpu_usage
+        Some(!modules.join(System::kernel_version()?).exists())
     }
 }
 
-// Sysinfo-based CPU monitoring for cross-platform support
-mod sysinfo {
-    #[allow(dead_code)]
-    pub fn get_cpu_usage() -> Result<f32, String> {
-        // Use system command to get CPU usage
-        #[cfg(target_os = "windows")]
-        {
-            use std::process::Command;
-            
-            // Use WMIC to get CPU load percentage
-            let output = Command::new("wmic")
-                .args(&["cpu", "get", "loadpercentage"])
-                .output();
-                
-            if let Ok(output) = output {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                // Parse the output (second line contains the percentage)
-                if let Some(line) = stdout.lines().nth(1) {
-                    if let Ok(usage) = line.trim().parse::<f32>() {
-                        return Ok(usage);
-                    }
+fn window_visible(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .is_some_and(|w| w.is_visible().unwrap_or(false) && !w.is_minimized().unwrap_or(false))
+}
+
+/// Emit `system-stats` every `system.stats_stream_interval_ms` (0 turns it off) so
+/// the UI doesn't have to poll. Nothing is sampled while the window is hidden
+/// or minimized.
+pub fn start_stats_stream(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) => settings.system.stats_stream_interval_ms,
+                Err(e) => {
+                    warn!("Couldn't load the stats stream interval: {}", e);
+                    2000
                 }
+            };
+            if interval == 0 || !window_visible(&app) {
+                tokio::time::sleep(HIDDEN_POLL_INTERVAL).await;
+                continue;
             }
-        }
-        
-        Ok(0.0)
-    }
-}
 
-pub fn get_system_stats() -> Result<SystemStats, String> {
-    let cpu_usage = {
-        let mut tracker = CPU_TRACKER.lock().map_err(|e| e.to_string())?;
-        tracker.get_usage()
-    };
-    
-    let (memory_usage, memory_total, memory_used) = windows_monitor::get_memory_usage()?;
-    let gpu_usage = windows_monitor::get_gpu_usage()?;
-    
-    let timestamp = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    
-    Ok(SystemStats {
-        cpu_usage,
-        memory_usage,
-        memory_total,
-        memory_used,
-        gpu_usage,
-        timestamp,
-    })
+            if let Ok(Ok(stats)) = tokio::task::spawn_blocking(get_system_stats).await {
+                if let Err(e) = app.emit(SYSTEM_STATS_EVENT, &stats) {
+                    warn!("Failed to emit system stats: {}", e);
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(interval).max(MIN_STREAM_INTERVAL)).await;
+        }
+    });
 }
 
 #[tauri::command]
 pub async fn get_system_stats_command() -> Result<SystemStats, String> {
-    get_system_stats()
+    tokio::task::spawn_blocking(get_system_stats)
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
 pub async fn get_cpu_usage_command() -> Result<f32, String> {
-    let mut tracker = CPU_TRACKER.lock().map_err(|e| e.to_string())?;
-    Ok(tracker.get_usage())
+    Ok(get_system_stats_command().await?.cpu_usage)
 }
 
 #[tauri::command]
 pub async fn get_memory_usage_command() -> Result<(f32, u64, u64), String> {
-    windows_monitor::get_memory_usage()
+    let stats = get_system_stats_command().await?;
+    Ok((stats.memory_usage, stats.memory_total, stats.memory_used))
 }
 
 #[tauri::command]
 pub async fn get_gpu_usage_command() -> Result<Option<f32>, String> {
-    windows_monitor::get_gpu_usage()
+    Ok(None)
+}
+
+/// `range` and `resolution` in seconds, e.g. (3600, 60) for the last hour per minute
+#[tauri::command]
+pub async fn get_stats_history(range: u64, resolution: u64) -> Result<Vec<StatsSample>, String> {
+    Ok(stats_history(range, resolution))
 }
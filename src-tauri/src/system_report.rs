@@ -0,0 +1,121 @@
+// System Report Module
+// "How's my system doing?": gathers the monitor's numbers, asks the LLM to
+// turn them into one spoken paragraph, and falls back to reading the numbers
+// when no LLM is reachable. Also runs daily at `system.system_report_time`.
+
+use crate::processes::ProcessSort;
+use crate::state::AppState;
+use tracing::{info, warn};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// One line per fact, plain enough for the LLM and the fallback alike. Blocking.
+fn gather_facts() -> Vec<String> {
+    let mut facts = Vec::new();
+
+    if let Ok(stats) = crate::system_monitor::get_system_stats() {
+        facts.push(format!("CPU usage is {:.0}%", stats.cpu_usage));
+        facts.push(format!(
+            "memory usage is {:.0}% ({:.1} of {:.1} GB)",
+            stats.memory_usage,
+            stats.memory_used as f64 / 1024f64.powi(3),
+            stats.memory_total as f64 / 1024f64.powi(3)
+        ));
+    }
+
+    let readings = crate::sensors::temperatures();
+    for reading in crate::sensors::hottest(&readings) {
+        facts.push(format!("{} temperature is {:.0}°C", reading.label, reading.celsius));
+    }
+
+    for disk in crate::disks::disk_stats().iter().filter(|d| !d.removable) {
+        facts.push(format!("drive {} has {} GB free of {} GB", disk.name, disk.free / 1024u64.pow(3), disk.total / 1024u64.pow(3)));
+    }
+
+    if let Ok(health) = crate::power::battery_health() {
+        facts.push(health.describe());
+    }
+
+    facts.push(crate::system_monitor::boot_info().describe());
+
+    let busy: Vec<String> = crate::processes::top_processes(ProcessSort::Cpu, 3)
+        .iter()
+        .map(|p| format!("{} ({:.0}% of one core)", p.name, p.cpu_usage))
+        .collect();
+    if !busy.is_empty() {
+        facts.push(format!("the busiest processes are {}", busy.join(", ")));
+    }
+
+    facts
+}
+
+fn build_report_prompt(facts: &[String]) -> String {
+    format!(
+        "Here is the current state of the user's computer:\n- {}\n\n\
+         In one short, friendly paragraph meant to be read aloud, tell the user how their system is doing. \
+         Lead with anything that needs attention (heat, low space, a pending restart, a runaway process); \
+         if everything is fine, say so briefly. No lists, headings or markdown.",
+        facts.join("\n- ")
+    )
+}
+
+/// The spoken report text
+pub async fn system_report(app: &AppHandle) -> Result<String, String> {
+    let facts = tokio::task::spawn_blocking(gather_facts).await.map_err(|e| e.to_string())?;
+
+    match app.state::<AppState>().send_llm_message(&build_report_prompt(&facts)).await {
+        Ok(response) => Ok(response.content),
+        Err(e) => {
+            info!("LLM unavailable for the system report ({}), reading the numbers instead", e);
+            Ok(format!("{}.", facts.join(". ")))
+        }
+    }
+}
+
+/// "how's my system doing", "give me a system report"
+pub fn is_report_query(command: &str) -> bool {
+    let lower = command.to_lowercase();
+    ["system doing", "computer doing", "pc doing", "system health", "health report", "system report", "health check"]
+        .iter()
+        .any(|p| lower.contains(p))
+}
+
+/// Speak the report once a day at the `system.system_report_time` setting ("HH:MM"; empty = off)
+pub fn start_report_schedule(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_run: Option<chrono::NaiveDate> = None;
+
+        loop {
+            tokio::time::sleep(SCHEDULE_CHECK_INTERVAL).await;
+            let time = match crate::settings::load_settings(app.clone()).await {
+                Ok(settings) => settings.system.system_report_time,
+                Err(e) => {
+                    warn!("Couldn't load the system report time: {}", e);
+                    continue;
+                }
+            };
+            let now = chrono::Local::now();
+            if time.trim().is_empty() || now.format("%H:%M").to_string() != time.trim() || last_run == Some(now.date_naive()) {
+                continue;
+            }
+
+            last_run = Some(now.date_naive());
+            match system_report(&app).await {
+                Ok(report) => crate::voice::speak(&report),
+                Err(e) => warn!("Scheduled system report failed: {}", e),
+            }
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+/// Build the report and speak it, returning the text for the UI
+#[tauri::command]
+pub async fn speak_system_report(app: AppHandle) -> Result<String, String> {
+    let report = system_report(&app).await?;
+    crate::voice::speak(&report);
+    Ok(report)
+}
@@ -0,0 +1,292 @@
+// System Tray Module
+// Native tray icon; the icon greys out with a red slash while the mic is muted.
+// The menu shows the voice pipeline's state and offers mute, pause, stop
+// speaking, privacy mode, the user's favorite routines and a profile switcher.
+
+use crate::state::AppState;
+use crate::voice::VoiceState;
+use tracing::{info, warn};
+use once_cell::sync::OnceCell;
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+/// Menu ids for routine items are this prefix plus the routine id
+const ROUTINE_ITEM_PREFIX: &str = "routine:";
+/// Same for profile items
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+
+struct TrayIcons {
+    normal: Image<'static>,
+    muted: Image<'static>,
+}
+
+static ICONS: OnceCell<TrayIcons> = OnceCell::new();
+static MUTE_ITEM: OnceCell<CheckMenuItem<Wry>> = OnceCell::new();
+static PAUSE_ITEM: OnceCell<CheckMenuItem<Wry>> = OnceCell::new();
+static PRIVACY_ITEM: OnceCell<CheckMenuItem<Wry>> = OnceCell::new();
+static STATUS_ITEM: OnceCell<MenuItem<Wry>> = OnceCell::new();
+static ROUTINES_MENU: OnceCell<Submenu<Wry>> = OnceCell::new();
+static PROFILES_MENU: OnceCell<Submenu<Wry>> = OnceCell::new();
+
+fn status_label(state: VoiceState) -> &'static str {
+    match state {
+        VoiceState::Idle => "Status: Idle",
+        VoiceState::WakeListening => "Status: Waiting for wake word",
+        VoiceState::Recording => "Status: Listening",
+        VoiceState::Processing => "Status: Thinking",
+        VoiceState::Speaking => "Status: Speaking",
+    }
+}
+
+/// Create the tray icon and its menu
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let normal = app
+        .default_window_icon()
+        .cloned()
+        .map(|icon| icon.to_owned())
+        .ok_or_else(|| tauri::Error::AssetNotFound("default window icon".to_string()))?;
+    let muted = muted_icon(&normal);
+
+    // The status line is informational only
+    let status_item = MenuItem::with_id(app, "status", status_label(crate::voice::state()), false, None::<&str>)?;
+    let show_item = MenuItem::with_id(app, "show", "Show AKI", true, None::<&str>)?;
+    let mute_item = CheckMenuItem::with_id(app, "mute", "Mute microphone", true, crate::voice::is_muted(), None::<&str>)?;
+    let pause_item = CheckMenuItem::with_id(app, "pause", "Pause assistant", true, crate::voice::is_paused(), None::<&str>)?;
+    let stop_item = MenuItem::with_id(app, "stop_speaking", "Stop speaking", true, None::<&str>)?;
+    let privacy_item = CheckMenuItem::with_id(app, "privacy", "Privacy mode (offline)", true, crate::privacy::is_enabled(), None::<&str>)?;
+    // Filled in once settings and routines are available
+    let routines_menu = Submenu::with_id(app, "routines", "Favorite routines", true)?;
+    let profiles_menu = Submenu::with_id(app, "profiles", "Profile", true)?;
+    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status_item,
+            &PredefinedMenuItem::separator(app)?,
+            &show_item,
+            &mute_item,
+            &pause_item,
+            &stop_item,
+            &privacy_item,
+            &routines_menu,
+            &profiles_menu,
+            &PredefinedMenuItem::separator(app)?,
+            &quit_item,
+        ],
+    )?;
+
+    let icon = if crate::voice::is_muted() { muted.clone() } else { normal.clone() };
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .icon(icon)
+        .tooltip("AKI")
+        .menu(&menu)
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "mute" => {
+                crate::voice::toggle_muted();
+            }
+            "pause" => crate::voice::set_paused(!crate::voice::is_paused()),
+            "stop_speaking" => crate::voice::stop_speaking(),
+            "privacy" => toggle_privacy_mode(app),
+            "show" => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            "quit" => app.exit(0),
+            id => {
+                if let Some(routine_id) = id.strip_prefix(ROUTINE_ITEM_PREFIX) {
+                    run_routine(app, routine_id.to_string());
+                } else if let Some(profile_id) = id.strip_prefix(PROFILE_ITEM_PREFIX) {
+                    switch_profile(app, profile_id.to_string());
+                }
+            }
+        })
+        .build(app)?;
+
+    let _ = ICONS.set(TrayIcons { normal, muted });
+    let _ = MUTE_ITEM.set(mute_item);
+    let _ = PAUSE_ITEM.set(pause_item);
+    let _ = PRIVACY_ITEM.set(privacy_item);
+    let _ = STATUS_ITEM.set(status_item);
+    let _ = ROUTINES_MENU.set(routines_menu);
+    let _ = PROFILES_MENU.set(profiles_menu);
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        refresh_routines(&handle).await;
+        refresh_profiles(&handle).await;
+    });
+
+    info!("Tray icon initialized");
+    Ok(())
+}
+
+/// Swap the tray icon and tooltip to reflect the mic mute state
+pub fn set_muted_indicator(app: &AppHandle, muted: bool) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+
+    if let Some(icons) = ICONS.get() {
+        let icon = if muted { icons.muted.clone() } else { icons.normal.clone() };
+        if let Err(e) = tray.set_icon(Some(icon)) {
+            warn!("Failed to update tray icon: {}", e);
+        }
+    }
+
+    if let Some(item) = MUTE_ITEM.get() {
+        let _ = item.set_checked(muted);
+    }
+    set_voice_status(app, crate::voice::state());
+}
+
+/// Show the voice pipeline's state in the status line
+pub fn set_voice_status(app: &AppHandle, state: VoiceState) {
+    if let Some(item) = STATUS_ITEM.get() {
+        let label = if crate::voice::is_paused() { "Status: Paused" } else { status_label(state) };
+        let _ = item.set_text(label);
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let mut tooltip = if crate::voice::is_muted() {
+            "AKI (microphone muted)".to_string()
+        } else {
+            format!("AKI - {}", status_label(state).trim_start_matches("Status: "))
+        };
+        if crate::privacy::is_enabled() {
+            tooltip.push_str(" [privacy mode]");
+        }
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+pub fn set_paused_indicator(app: &AppHandle, paused: bool) {
+    if let Some(item) = PAUSE_ITEM.get() {
+        let _ = item.set_checked(paused);
+    }
+    set_voice_status(app, crate::voice::state());
+}
+
+pub fn set_privacy_indicator(app: &AppHandle, enabled: bool) {
+    if let Some(item) = PRIVACY_ITEM.get() {
+        let _ = item.set_checked(enabled);
+    }
+    set_voice_status(app, crate::voice::state());
+}
+
+/// Flip `general.privacy_mode`; saving the setting applies it
+fn toggle_privacy_mode(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let enabled = serde_json::Value::Bool(!crate::privacy::is_enabled());
+        if let Err(e) = crate::settings::update_setting(app.clone(), "general.privacy_mode".to_string(), enabled).await {
+            warn!("Toggling privacy mode from the tray failed: {}", e);
+            set_privacy_indicator(&app, crate::privacy::is_enabled());
+        }
+    });
+}
+
+fn run_routine(app: &AppHandle, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut automation = app.state::<AppState>().inner().automation.lock().await;
+        if let Err(e) = automation.execute_routine(&app, &id).await {
+            warn!("Routine {} from the tray failed: {}", id, e);
+        }
+    });
+}
+
+/// Rebuild the Favorite routines submenu from the `general.favorite_routines` setting
+pub async fn refresh_routines(app: &AppHandle) {
+    let Some(submenu) = ROUTINES_MENU.get() else {
+        return;
+    };
+    let favorites = crate::settings::load_settings(app.clone())
+        .await
+        .map(|s| s.general.favorite_routines)
+        .unwrap_or_default();
+    let routines = app.state::<AppState>().inner().automation.lock().await.get_all_routines();
+
+    if let Ok(items) = submenu.items() {
+        for item in items {
+            let _ = submenu.remove(&item);
+        }
+    }
+    for routine in routines.iter().filter(|r| favorites.is_empty() || favorites.contains(&r.id)) {
+        let id = format!("{}{}", ROUTINE_ITEM_PREFIX, routine.id);
+        match MenuItem::with_id(app, id, &routine.name, true, None::<&str>) {
+            Ok(item) => {
+                let _ = submenu.append(&item);
+            }
+            Err(e) => warn!("Failed to add {} to the tray menu: {}", routine.name, e),
+        }
+    }
+}
+
+fn switch_profile(app: &AppHandle, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::profiles::switch_profile(app.clone(), id.clone()).await {
+            warn!("Switching to profile {} from the tray failed: {}", id, e);
+            // Put the check mark back on the profile still in use
+            refresh_profiles(&app).await;
+        }
+    });
+}
+
+/// Rebuild the Profile submenu with a check on the active profile
+pub async fn refresh_profiles(app: &AppHandle) {
+    let Some(submenu) = PROFILES_MENU.get() else {
+        return;
+    };
+    let Ok(profiles) = crate::settings::load_settings(app.clone()).await.map(|s| s.profiles) else {
+        return;
+    };
+
+    if let Ok(items) = submenu.items() {
+        for item in items {
+            let _ = submenu.remove(&item);
+        }
+    }
+    let _ = submenu.set_enabled(!profiles.list.is_empty());
+    for profile in &profiles.list {
+        let id = format!("{}{}", PROFILE_ITEM_PREFIX, profile.id);
+        let active = profile.id == profiles.active;
+        match CheckMenuItem::with_id(app, id, &profile.name, true, active, None::<&str>) {
+            Ok(item) => {
+                let _ = submenu.append(&item);
+            }
+            Err(e) => warn!("Failed to add profile {} to the tray menu: {}", profile.name, e),
+        }
+    }
+}
+
+/// Dimmed greyscale copy of the icon with a red slash across it
+fn muted_icon(base: &Image<'_>) -> Image<'static> {
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+    let thickness = (width.max(height) / 12).max(1) as i64;
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = ((y * width + x) * 4) as usize;
+
+            let on_slash = (x as i64 * height as i64 / width as i64 - y as i64).abs() <= thickness;
+            if on_slash {
+                rgba[i..i + 4].copy_from_slice(&[230, 40, 40, 255]);
+                continue;
+            }
+
+            let grey = (rgba[i] as u32 * 30 + rgba[i + 1] as u32 * 59 + rgba[i + 2] as u32 * 11) / 200;
+            rgba[i] = grey as u8;
+            rgba[i + 1] = grey as u8;
+            rgba[i + 2] = grey as u8;
+        }
+    }
+
+    Image::new_owned(rgba, width, height)
+}
@@ -0,0 +1,164 @@
+// Updater Module
+// Checks GitHub releases for a newer signed build on the channel picked in
+// settings (stable or beta), tells the UI with an `update-available` event,
+// and downloads and installs it when the user asks. Checks run shortly after
+// launch and then every few hours while `general.auto_check_updates` is on.
+// A build without an update signing key in tauri.conf.json can't verify
+// what it downloads, so it never checks.
+
+use tracing::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+use tokio::sync::Mutex;
+
+pub const UPDATE_AVAILABLE_EVENT: &str = "update-available";
+pub const UPDATE_PROGRESS_EVENT: &str = "update-progress";
+
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+const STABLE_ENDPOINT: &str = "https://github.com/precie21/astral-assistant/releases/latest/download/latest.json";
+/// Beta builds publish their manifest to a rolling `beta` release
+const BETA_ENDPOINT: &str = "https://github.com/precie21/astral-assistant/releases/download/beta/latest.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    fn endpoint(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => STABLE_ENDPOINT,
+            UpdateChannel::Beta => BETA_ENDPOINT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub version: String,
+    pub current_version: String,
+    pub channel: UpdateChannel,
+    pub date: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// The last update found, kept so installing doesn't need a second check
+static PENDING: Lazy<Mutex<Option<Update>>> = Lazy::new(|| Mutex::new(None));
+
+/// Whether this build has the public key updates are verified against
+fn has_signing_key(app: &AppHandle) -> bool {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|key| key.as_str())
+        .is_some_and(|key| !key.trim().is_empty())
+}
+
+async fn find_update(app: &AppHandle, channel: UpdateChannel) -> Result<Option<Update>, String> {
+    if !has_signing_key(app) {
+        return Err("Updates are turned off in this build because it has no update signing key".to_string());
+    }
+    crate::privacy::check_url(channel.endpoint())?;
+    let endpoint = Url::parse(channel.endpoint()).map_err(|e| e.to_string())?;
+    let mut builder = app.updater_builder();
+    // The updater has its own HTTP client, so it's told about the proxy here
+    if let Some(proxy) = crate::proxy::url()? {
+        builder = builder.proxy(proxy);
+    }
+    let updater = builder
+        .endpoints(vec![endpoint])
+        .map_err(|e| e.to_string())?
+        .build()
+        .map_err(|e| e.to_string())?;
+    updater.check().await.map_err(|e| format!("Update check failed: {}", e))
+}
+
+/// Check the configured channel, remembering and announcing a newer build
+async fn check(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let channel = crate::settings::load_settings(app.clone()).await?.general.update_channel;
+    let Some(update) = find_update(app, channel).await? else {
+        *PENDING.lock().await = None;
+        return Ok(None);
+    };
+
+    let info = UpdateInfo {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        channel,
+        date: update.date.map(|d| d.to_string()),
+        notes: update.body.clone(),
+    };
+    info!("Update available on the {:?} channel: {} -> {}", channel, info.current_version, info.version);
+    *PENDING.lock().await = Some(update);
+    if let Err(e) = app.emit(UPDATE_AVAILABLE_EVENT, &info) {
+        warn!("Failed to emit update event: {}", e);
+    }
+    Ok(Some(info))
+}
+
+pub fn start_background_checks(app: AppHandle) {
+    if !has_signing_key(&app) {
+        info!("No update signing key in this build, not checking for updates");
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            let enabled = crate::settings::load_settings(app.clone())
+                .await
+                .map(|s| s.general.auto_check_updates)
+                .unwrap_or(true);
+            if enabled {
+                if let Err(e) = check(&app).await {
+                    warn!("{}", e);
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn check_for_update(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    check(&app).await
+}
+
+/// Download and install the update found by the last check, then restart
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    let update = match PENDING.lock().await.take() {
+        Some(update) => update,
+        None => {
+            let channel = crate::settings::load_settings(app.clone()).await?.general.update_channel;
+            find_update(&app, channel).await?.ok_or_else(|| "ASTRAL is already up to date".to_string())?
+        }
+    };
+
+    info!("Installing update {}", update.version);
+    let progress_app = app.clone();
+    let mut downloaded: u64 = 0;
+    update
+        .download_and_install(
+            move |chunk, total| {
+                downloaded += chunk as u64;
+                let _ = progress_app.emit(UPDATE_PROGRESS_EVENT, serde_json::json!({ "downloaded": downloaded, "total": total }));
+            },
+            || info!("Update downloaded, installing"),
+        )
+        .await
+        .map_err(|e| format!("Update failed: {}", e))?;
+
+    app.restart()
+}
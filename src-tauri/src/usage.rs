@@ -0,0 +1,340 @@
+// Usage Module
+// What the LLM costs. Every request's tokens are added to a ledger on disk,
+// one line per day, provider and model, and priced from a table of list
+// prices per million tokens (`usage.prices` overrides or extends it; Ollama
+// and the built-in model are free). The `usage` settings section can also set
+// a monthly spending cap: once this month's spend reaches it, requests to the
+// paid providers are refused until the month turns over or the cap is
+// raised, while local models keep working.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use crate::llm_provider::LLMProvider;
+use chrono::{Datelike, Local, NaiveDate};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tracing::{info, warn};
+
+/// USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// List prices by model name prefix; the longest matching prefix wins, so
+/// "gpt-4o-mini" isn't priced as "gpt-4o"
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-5", 1.25, 10.0),
+    ("gpt-5-mini", 0.25, 2.0),
+    ("gpt-5-nano", 0.05, 0.4),
+    ("gpt-4.1", 2.0, 8.0),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1-nano", 0.1, 0.4),
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4-turbo", 10.0, 30.0),
+    ("gpt-4", 30.0, 60.0),
+    ("gpt-3.5-turbo", 0.5, 1.5),
+    ("o1", 15.0, 60.0),
+    ("o1-mini", 1.1, 4.4),
+    ("o3", 2.0, 8.0),
+    ("o3-mini", 1.1, 4.4),
+    ("o4-mini", 1.1, 4.4),
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("text-embedding-ada-002", 0.1, 0.0),
+    ("claude-opus-4-5", 5.0, 25.0),
+    ("claude-opus-4", 15.0, 75.0),
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-haiku-4", 1.0, 5.0),
+    ("claude-3-7-sonnet", 3.0, 15.0),
+    ("claude-3-5-sonnet", 3.0, 15.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-3-opus", 15.0, 75.0),
+    ("claude-3-haiku", 0.25, 1.25),
+];
+
+/// The `usage` settings section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageSettings {
+    /// Most to spend on cloud models in a calendar month, in USD; 0 for no cap
+    pub monthly_cap_usd: f64,
+    /// Prices by model name, for models the built-in table doesn't know or
+    /// that the user pays a different rate for
+    pub prices: HashMap<String, ModelPrice>,
+}
+
+impl UsageSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        if !self.monthly_cap_usd.is_finite() || self.monthly_cap_usd < 0.0 {
+            return Err("The monthly spending cap can't be negative".to_string());
+        }
+        self.prices = std::mem::take(&mut self.prices)
+            .into_iter()
+            .map(|(model, price)| (model.trim().to_lowercase(), price))
+            .filter(|(model, _)| !model.is_empty())
+            .collect();
+        if self.prices.values().any(|p| !(p.input >= 0.0 && p.output >= 0.0)) {
+            return Err("Model prices can't be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// One day's requests to one model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LedgerEntry {
+    date: NaiveDate,
+    provider: LLMProvider,
+    model: String,
+    requests: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    cost_usd: f64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Ledger {
+    entries: Vec<LedgerEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelUsage {
+    pub provider: LLMProvider,
+    pub model: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Totals for a day ("2026-10-15") or a month ("2026-10")
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageTotals {
+    pub period: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// Most expensive first
+    pub models: Vec<ModelUsage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpendStatus {
+    pub month_cost_usd: f64,
+    /// 0 when there's no cap
+    pub monthly_cap_usd: f64,
+    /// Cloud requests are being refused
+    pub cap_reached: bool,
+}
+
+static LEDGER: Lazy<RwLock<Ledger>> = Lazy::new(|| RwLock::new(load()));
+static SETTINGS: Lazy<RwLock<UsageSettings>> = Lazy::new(|| RwLock::new(UsageSettings::default()));
+
+fn ledger_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("usage_ledger.json");
+    Some(path)
+}
+
+fn load() -> Ledger {
+    let Some(content) = ledger_path().and_then(|path| std::fs::read_to_string(path).ok()) else {
+        return Ledger::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        warn!("Ignoring unreadable usage_ledger.json: {}", e);
+        Ledger::default()
+    })
+}
+
+fn save(ledger: &Ledger) -> Result<(), String> {
+    let path = ledger_path().ok_or_else(|| "Could not find config directory".to_string())?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    }
+    let content = serde_json::to_string(ledger).map_err(|e| format!("Failed to serialize the usage ledger: {}", e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write the usage ledger: {}", e))
+}
+
+/// Use `settings` from now on; called when settings load or change
+pub fn set(settings: UsageSettings) {
+    *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = settings;
+}
+
+/// Providers that bill per token
+pub fn is_paid(provider: LLMProvider) -> bool {
+    !matches!(provider, LLMProvider::Ollama | LLMProvider::Embedded)
+}
+
+/// What `model` costs on `provider`, if it's known. OpenRouter names models
+/// "<vendor>/<model>" and charges about the vendor's own price.
+fn price(provider: LLMProvider, model: &str) -> Option<ModelPrice> {
+    if !is_paid(provider) {
+        return Some(ModelPrice { input: 0.0, output: 0.0 });
+    }
+    let model = model.trim().to_lowercase();
+    let settings = SETTINGS.read().unwrap_or_else(|e| e.into_inner());
+    if let Some(price) = settings.prices.get(&model) {
+        return Some(*price);
+    }
+    let name = model.rsplit('/').next().unwrap_or(&model);
+    PRICES
+        .iter()
+        .filter(|(prefix, _, _)| name.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+        .map(|&(_, input, output)| ModelPrice { input, output })
+}
+
+fn month_of(date: NaiveDate) -> String {
+    format!("{:04}-{:02}", date.year(), date.month())
+}
+
+/// Add a request's tokens to today's line for the model
+pub fn record(provider: LLMProvider, model: &str, input_tokens: u64, output_tokens: u64) {
+    let price = price(provider, model);
+    if price.is_none() {
+        info!("No price known for {}; recording its tokens without a cost", model);
+    }
+    let cost = price.map_or(0.0, |p| (input_tokens as f64 * p.input + output_tokens as f64 * p.output) / 1_000_000.0);
+
+    let today = Local::now().date_naive();
+    let mut ledger = LEDGER.write().unwrap_or_else(|e| e.into_inner());
+    let index = ledger
+        .entries
+        .iter()
+        .position(|e| e.date == today && e.provider == provider && e.model == model)
+        .unwrap_or_else(|| {
+            ledger.entries.push(LedgerEntry {
+                date: today,
+                provider,
+                model: model.to_string(),
+                requests: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+                cost_usd: 0.0,
+            });
+            ledger.entries.len() - 1
+        });
+    let entry = &mut ledger.entries[index];
+    entry.requests += 1;
+    entry.input_tokens += input_tokens;
+    entry.output_tokens += output_tokens;
+    entry.cost_usd += cost;
+    if let Err(e) = save(&ledger) {
+        warn!("Couldn't save the usage ledger: {}", e);
+    }
+}
+
+fn month_cost(month: &str) -> f64 {
+    let ledger = LEDGER.read().unwrap_or_else(|e| e.into_inner());
+    ledger.entries.iter().filter(|e| month_of(e.date) == month).map(|e| e.cost_usd).sum()
+}
+
+fn spend_status() -> SpendStatus {
+    let month_cost_usd = month_cost(&month_of(Local::now().date_naive()));
+    let monthly_cap_usd = SETTINGS.read().unwrap_or_else(|e| e.into_inner()).monthly_cap_usd;
+    SpendStatus {
+        month_cost_usd,
+        monthly_cap_usd,
+        cap_reached: monthly_cap_usd > 0.0 && month_cost_usd >= monthly_cap_usd,
+    }
+}
+
+/// Refuse a request to a paid provider once this month's cap is spent
+pub fn check_cap(provider: LLMProvider) -> AstralResult<()> {
+    if !is_paid(provider) {
+        return Ok(());
+    }
+    let status = spend_status();
+    if !status.cap_reached {
+        return Ok(());
+    }
+    warn!("Monthly spending cap reached, refusing a {:?} request", provider);
+    Err(AstralError::new(
+        ErrorCode::SpendingCap,
+        format!("This month's ${:.2} spending cap for cloud models is used up", status.monthly_cap_usd),
+    )
+    .with_detail(format!("${:.2} spent so far this month", status.month_cost_usd)))
+}
+
+/// Totals for each of `periods`, newest first; `period_of` names an entry's period
+fn totals(period_of: impl Fn(NaiveDate) -> String, periods: Vec<String>) -> Vec<UsageTotals> {
+    let ledger = LEDGER.read().unwrap_or_else(|e| e.into_inner());
+    let mut by_period: BTreeMap<String, Vec<&LedgerEntry>> = periods.iter().map(|p| (p.clone(), Vec::new())).collect();
+    for entry in &ledger.entries {
+        if let Some(entries) = by_period.get_mut(&period_of(entry.date)) {
+            entries.push(entry);
+        }
+    }
+
+    by_period
+        .into_iter()
+        .rev()
+        .map(|(period, entries)| {
+            let mut models: Vec<ModelUsage> = Vec::new();
+            for entry in entries {
+                match models.iter_mut().find(|m| m.provider == entry.provider && m.model == entry.model) {
+                    Some(model) => {
+                        model.requests += entry.requests;
+                        model.input_tokens += entry.input_tokens;
+                        model.output_tokens += entry.output_tokens;
+                        model.cost_usd += entry.cost_usd;
+                    }
+                    None => models.push(ModelUsage {
+                        provider: entry.provider,
+                        model: entry.model.clone(),
+                        requests: entry.requests,
+                        input_tokens: entry.input_tokens,
+                        output_tokens: entry.output_tokens,
+                        cost_usd: entry.cost_usd,
+                    }),
+                }
+            }
+            models.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd).then(b.requests.cmp(&a.requests)));
+            UsageTotals {
+                period,
+                requests: models.iter().map(|m| m.requests).sum(),
+                input_tokens: models.iter().map(|m| m.input_tokens).sum(),
+                output_tokens: models.iter().map(|m| m.output_tokens).sum(),
+                cost_usd: models.iter().map(|m| m.cost_usd).sum(),
+                models,
+            }
+        })
+        .collect()
+}
+
+// ========== Tauri Commands ==========
+
+/// Usage for each of the last `days` days (default 30), today first
+#[tauri::command]
+pub async fn get_daily_usage(days: Option<u32>) -> Vec<UsageTotals> {
+    let today = Local::now().date_naive();
+    let days: Vec<String> = (0..days.unwrap_or(30).clamp(1, 366))
+        .filter_map(|back| today.checked_sub_days(chrono::Days::new(back as u64)))
+        .map(|date| date.to_string())
+        .collect();
+    totals(|date| date.to_string(), days)
+}
+
+/// Usage for each of the last `months` months (default 12), this month first
+#[tauri::command]
+pub async fn get_monthly_usage(months: Option<u32>) -> Vec<UsageTotals> {
+    let today = Local::now().date_naive();
+    let months: Vec<String> = (0..months.unwrap_or(12).clamp(1, 120))
+        .filter_map(|back| today.with_day(1)?.checked_sub_months(chrono::Months::new(back)))
+        .map(month_of)
+        .collect();
+    totals(month_of, months)
+}
+
+/// This month's spend against the cap
+#[tauri::command]
+pub async fn get_spend_status() -> SpendStatus {
+    spend_status()
+}
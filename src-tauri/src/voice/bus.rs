@@ -0,0 +1,163 @@
+// Voice Pipeline Bus
+// The stages of a voice interaction (wake word, speech-to-text, intent
+// routing, the LLM and speech synthesis) report what they did as
+// `PipelineEvent`s on one broadcast channel instead of each poking the UI,
+// tray and state machine on its own. Anything in the backend can `subscribe`
+// to follow along; the forwarder started by `init` keeps the tray and the
+// per-purpose UI events (`voice-state-changed`, `wake-word-detected`,
+// `assistant-speak`) in step and re-emits every event as `voice-pipeline` so
+// the UI can draw the pipeline as it runs. The broadcast drops events for a
+// subscriber that falls behind, so speech also goes to the UI on a queue of
+// its own: a lost status update is redrawn by the next, a lost reply is never heard.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, warn};
+
+use super::{VoiceState, VoiceStateChange};
+
+/// Every pipeline event, payload is a `PipelineMessage`
+pub const PIPELINE_EVENT: &str = "voice-pipeline";
+
+/// Events a subscriber can fall behind by before it starts missing some
+const CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    WakeWord,
+    /// Recording until the user stops talking
+    Capture,
+    Stt,
+    Intent,
+    Llm,
+    Tts,
+}
+
+/// Which handler a command ended up with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Route {
+    /// The answer to a question the assistant asked back
+    Clarification,
+    /// Built-in command matched by the intent router's rule patterns
+    Intent,
+    Skill,
+    /// Built-in command picked by LLM classification
+    ClassifiedIntent,
+    /// Question about the screen, sent to the LLM with the OCR'd text
+    Screen,
+    /// Question about the selected file, sent to the LLM with its text
+    Document,
+    /// Question about current events, sent to the LLM with web search results
+    Web,
+    /// Question about the user's own documents, sent to the LLM with matching passages
+    Knowledge,
+    Llm,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PipelineEvent {
+    StateChanged { from: VoiceState, to: VoiceState },
+    WakeWordDetected,
+    /// The user stopped talking after `duration_ms` of audio
+    SpeechCaptured { duration_ms: u64 },
+    Transcribed { text: String, confidence: Option<f32> },
+    IntentRouted { route: Route, command: String },
+    LlmResponded { model: String, chars: usize, tokens_used: Option<u32> },
+    /// The LLM ran one of ASTRAL's tools while answering
+    ToolCalled { name: String, ok: bool },
+    /// The backend wants something said; the UI does the speaking
+    SpeechRequested { text: String },
+    SpeechSynthesized { bytes: usize },
+    StageFailed { stage: Stage, error: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PipelineMessage {
+    /// Goes up by one each time recording starts, grouping the events of one interaction
+    pub interaction: u64,
+    /// Unix time in milliseconds
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub event: PipelineEvent,
+}
+
+static BUS: Lazy<broadcast::Sender<PipelineMessage>> = Lazy::new(|| broadcast::channel(CAPACITY).0);
+static INTERACTION: AtomicU64 = AtomicU64::new(0);
+/// Text to say, in order; the receiver is taken by `init`
+static SPEECH: Lazy<(mpsc::UnboundedSender<String>, Mutex<Option<mpsc::UnboundedReceiver<String>>>)> = Lazy::new(|| {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (sender, Mutex::new(Some(receiver)))
+});
+
+/// Put `event` on the bus
+pub fn publish(event: PipelineEvent) {
+    let interaction = match event {
+        PipelineEvent::StateChanged { to: VoiceState::Recording, .. } => INTERACTION.fetch_add(1, Ordering::AcqRel) + 1,
+        _ => INTERACTION.load(Ordering::Acquire),
+    };
+    debug!("Pipeline: {:?}", event);
+    if let PipelineEvent::SpeechRequested { text } = &event {
+        let _ = SPEECH.0.send(text.clone());
+    }
+
+    // No subscribers (before `init`) is not an error
+    let _ = BUS.send(PipelineMessage {
+        interaction,
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        event,
+    });
+}
+
+/// Receive every event published from now on
+pub fn subscribe() -> broadcast::Receiver<PipelineMessage> {
+    BUS.subscribe()
+}
+
+/// Start forwarding the bus to the tray and UI
+pub(super) fn init(app: AppHandle) {
+    if let Some(mut speech) = SPEECH.1.lock().unwrap_or_else(|e| e.into_inner()).take() {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Some(text) = speech.recv().await {
+                if let Err(e) = app.emit(super::SPEAK_EVENT, text) {
+                    warn!("Failed to emit speech: {}", e);
+                }
+            }
+        });
+    }
+
+    let mut events = subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(message) => forward(&app, &message),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    warn!("Pipeline forwarder missed {} events", missed);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+fn forward(app: &AppHandle, message: &PipelineMessage) {
+    let result = match &message.event {
+        PipelineEvent::StateChanged { from, to } => {
+            crate::tray::set_voice_status(app, *to);
+            app.emit(super::STATE_CHANGED_EVENT, VoiceStateChange { from: *from, to: *to })
+        }
+        PipelineEvent::WakeWordDetected => app.emit(super::wake_word::WAKE_WORD_EVENT, ()),
+        _ => Ok(()),
+    };
+
+    if let Err(e) = result.and_then(|_| app.emit(PIPELINE_EVENT, message)) {
+        warn!("Failed to emit pipeline event: {}", e);
+    }
+}
@@ -0,0 +1,248 @@
+// Voice Capture
+// Runs microphone capture on a dedicated thread that feeds a lock-free ring buffer.
+// The cpal callback never blocks or allocates; async consumers only pull whole frames.
+
+use anyhow::{anyhow, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SizedSample};
+use tracing::{error, info, warn};
+use rtrb::{Consumer, Producer, RingBuffer};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Seconds of audio the ring buffer can hold before the producer starts dropping
+const RING_BUFFER_SECONDS: usize = 2;
+
+/// Global mic mute. While set the device stream is paused and anything the
+/// callback still receives is thrown away.
+static MIC_MUTED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_muted(muted: bool) {
+    MIC_MUTED.store(muted, Ordering::Release);
+}
+
+pub fn is_muted() -> bool {
+    MIC_MUTED.load(Ordering::Acquire)
+}
+
+/// Audio capture configuration
+pub struct AudioConfig {
+    #[allow(dead_code)]
+    pub sample_rate: u32,
+    #[allow(dead_code)]
+    pub channels: u16,
+    pub buffer_size: usize,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: 16000, // 16kHz for speech recognition
+            channels: 1,         // Mono
+            buffer_size: 512,
+        }
+    }
+}
+
+/// Handle to the running capture thread. Dropping it stops capture.
+pub struct AudioCapture {
+    running: Arc<AtomicBool>,
+    dropped_samples: Arc<AtomicUsize>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Consumer side of the ring buffer, yielding fixed-size mono frames
+pub struct FrameReader {
+    consumer: Consumer<f32>,
+    frame_size: usize,
+    sample_rate: u32,
+}
+
+impl AudioCapture {
+    /// Open the default input device on a dedicated thread and start capturing.
+    /// Returns once the stream is running (or failed to start).
+    pub fn start(config: &AudioConfig) -> Result<(Self, FrameReader)> {
+        let running = Arc::new(AtomicBool::new(true));
+        let dropped_samples = Arc::new(AtomicUsize::new(0));
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<(Consumer<f32>, u32)>>();
+
+        let thread_running = running.clone();
+        let thread_dropped = dropped_samples.clone();
+
+        // cpal streams aren't Send, so the stream lives and dies on this thread
+        let thread = std::thread::Builder::new()
+            .name("astral-audio".to_string())
+            .spawn(move || {
+                let stream = match open_input_stream(thread_dropped) {
+                    Ok((stream, consumer, sample_rate)) => {
+                        let _ = ready_tx.send(Ok((consumer, sample_rate)));
+                        stream
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+
+                let mut paused = false;
+                while thread_running.load(Ordering::Acquire) {
+                    let muted = is_muted();
+                    if muted != paused {
+                        let result = if muted { stream.pause() } else { stream.play() };
+                        match result {
+                            Ok(_) => info!("Audio capture {}", if muted { "paused (muted)" } else { "resumed" }),
+                            Err(e) => warn!("Failed to {} input stream: {}", if muted { "pause" } else { "resume" }, e),
+                        }
+                        paused = muted;
+                    }
+                    std::thread::park_timeout(Duration::from_millis(100));
+                }
+
+                drop(stream);
+                info!("Audio capture thread stopped");
+            })
+            .context("Failed to spawn audio capture thread")?;
+
+        let (consumer, sample_rate) = ready_rx
+            .recv()
+            .map_err(|_| anyhow!("Audio capture thread exited before starting"))??;
+
+        info!("Audio capture started at {} Hz", sample_rate);
+
+        let capture = Self {
+            running,
+            dropped_samples,
+            thread: Some(thread),
+        };
+
+        let reader = FrameReader {
+            consumer,
+            frame_size: config.buffer_size,
+            sample_rate,
+        };
+
+        Ok((capture, reader))
+    }
+
+    /// Stop capture and wait for the audio thread to release the device
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+
+    /// Samples dropped because the consumer fell behind
+    pub fn dropped_samples(&self) -> usize {
+        self.dropped_samples.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for AudioCapture {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl FrameReader {
+    /// Pop the next complete frame, if one is buffered
+    pub fn next_frame(&mut self) -> Option<Vec<f32>> {
+        let chunk = self.consumer.read_chunk(self.frame_size).ok()?;
+        let (first, second) = chunk.as_slices();
+
+        let mut frame = Vec::with_capacity(self.frame_size);
+        frame.extend_from_slice(first);
+        frame.extend_from_slice(second);
+        chunk.commit_all();
+
+        Some(frame)
+    }
+
+    /// Time it takes the device to fill one frame
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frame_size as f64 / self.sample_rate as f64)
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+fn open_input_stream(dropped: Arc<AtomicUsize>) -> Result<(cpal::Stream, Consumer<f32>, u32)> {
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .context("No input device available")?;
+    let supported = device
+        .default_input_config()
+        .context("Failed to query input device config")?;
+
+    let sample_rate = supported.sample_rate().0;
+    let channels = supported.channels() as usize;
+    let (producer, consumer) = RingBuffer::<f32>::new(sample_rate as usize * RING_BUFFER_SECONDS);
+
+    info!(
+        "Opening input device '{}' ({} Hz, {} channels, {:?})",
+        device.name().unwrap_or_else(|_| "unknown".to_string()),
+        sample_rate,
+        channels,
+        supported.sample_format()
+    );
+
+    let stream_config: cpal::StreamConfig = supported.config();
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, channels, producer, dropped)?,
+        cpal::SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, channels, producer, dropped)?,
+        cpal::SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, channels, producer, dropped)?,
+        format => return Err(anyhow!("Unsupported sample format: {:?}", format)),
+    };
+
+    stream.play().context("Failed to start input stream")?;
+    Ok((stream, consumer, sample_rate))
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    channels: usize,
+    mut producer: Producer<f32>,
+    dropped: Arc<AtomicUsize>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            if is_muted() {
+                return;
+            }
+
+            // Downmix to mono and push without blocking; if the consumer is
+            // behind we drop samples rather than stall the device
+            for frame in data.chunks(channels) {
+                let mono = frame.iter().map(|s| f32::from_sample(*s)).sum::<f32>() / channels as f32;
+                if producer.push(mono).is_err() {
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        },
+        |e| error!("Audio input stream error: {}", e),
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+/// Log a warning if capture has been dropping audio since the last check
+pub fn report_dropouts(capture: &AudioCapture, last_reported: &mut usize) {
+    let dropped = capture.dropped_samples();
+    if dropped > *last_reported {
+        warn!("Audio capture dropped {} samples (consumer too slow)", dropped - *last_reported);
+        *last_reported = dropped;
+    }
+}
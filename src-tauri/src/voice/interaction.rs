@@ -0,0 +1,337 @@
+// Voice Interaction
+// One spoken exchange run end to end in the backend: record from the
+// microphone until the user stops talking (a simple energy-based VAD),
+// transcribe with Whisper, hand the text to the command router (intents,
+// skills, the LLM), then synthesize the reply with ElevenLabs. Each stage
+// has its own timeout from the `voice.interaction` settings section, and
+// `cancel_voice_interaction` (or the stop-speaking hotkey) abandons the
+// exchange at whatever stage it's in. Playback stays in the UI, which gets
+// the reply and its audio back and settles the pipeline when it's done.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::Cursor;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use super::bus::{self, PipelineEvent, Stage};
+use super::capture::{AudioCapture, AudioConfig};
+use super::whisper::WhisperEngine;
+use super::VoiceState;
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use crate::state::AppState;
+
+/// Rate the Whisper server expects
+const STT_SAMPLE_RATE: u32 = 16000;
+
+/// Audio kept from just before speech starts, so the first syllable isn't clipped
+const PRE_ROLL: Duration = Duration::from_millis(300);
+
+/// The `voice.interaction` settings section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InteractionConfig {
+    /// How long to wait for the user to start talking
+    pub listen_timeout_secs: u64,
+    /// Recording stops after this much speech even if the user keeps going
+    pub max_utterance_secs: u64,
+    /// Silence that ends the utterance
+    pub end_silence_ms: u64,
+    /// Frame loudness (RMS, 0.0 - 1.0) that counts as speech
+    pub vad_threshold: f32,
+    pub stt_timeout_secs: u64,
+    /// Command handling, including any LLM call
+    pub response_timeout_secs: u64,
+    pub tts_timeout_secs: u64,
+}
+
+impl Default for InteractionConfig {
+    fn default() -> Self {
+        Self {
+            listen_timeout_secs: 8,
+            max_utterance_secs: 15,
+            end_silence_ms: 800,
+            vad_threshold: 0.02,
+            stt_timeout_secs: 20,
+            response_timeout_secs: 60,
+            tts_timeout_secs: 20,
+        }
+    }
+}
+
+impl InteractionConfig {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !(self.vad_threshold > 0.0 && self.vad_threshold <= 1.0) {
+            return Err("The voice activity threshold must be between 0 and 1".to_string());
+        }
+        let timeouts = [
+            self.listen_timeout_secs,
+            self.max_utterance_secs,
+            self.end_silence_ms,
+            self.stt_timeout_secs,
+            self.response_timeout_secs,
+            self.tts_timeout_secs,
+        ];
+        if timeouts.contains(&0) {
+            return Err("Voice interaction timeouts must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InteractionResult {
+    pub transcript: String,
+    pub confidence: Option<f32>,
+    pub reply: String,
+    /// ElevenLabs audio (MP3) of `reply`; `None` when ElevenLabs is off, in
+    /// which case the UI speaks the reply with the browser voice
+    pub audio: Option<Vec<u8>>,
+}
+
+/// Cancels the interaction in progress, if any
+static CANCEL: Lazy<Mutex<Option<watch::Sender<bool>>>> = Lazy::new(|| Mutex::new(None));
+
+fn cancelled() -> AstralError {
+    AstralError::new(ErrorCode::Cancelled, "Voice interaction cancelled")
+}
+
+/// Abandon the running interaction; false if none was running
+pub fn cancel() -> bool {
+    match CANCEL.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(sender) => {
+            info!("Cancelling voice interaction");
+            let _ = sender.send(true);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Run one stage under its timeout
+async fn timed<T>(stage: Stage, limit: Duration, name: &str, future: impl Future<Output = AstralResult<T>>) -> AstralResult<T> {
+    match tokio::time::timeout(limit, future).await {
+        Ok(result) => result,
+        Err(_) => {
+            let error = AstralError::new(
+                ErrorCode::Timeout,
+                format!("{} took longer than {} seconds", name, limit.as_secs()),
+            );
+            bus::publish(PipelineEvent::StageFailed { stage, error: error.message.clone() });
+            Err(error)
+        }
+    }
+}
+
+fn rms(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return 0.0;
+    }
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+}
+
+/// Record from the default input device until the user stops talking.
+/// Blocking; checks `cancel` between frames.
+fn record_utterance(config: &InteractionConfig, cancel: &watch::Receiver<bool>) -> AstralResult<(Vec<f32>, u32)> {
+    let (_capture, mut frames) = AudioCapture::start(&AudioConfig::default())
+        .map_err(|e| AstralError::new(ErrorCode::ServiceUnavailable, "Couldn't open the microphone").with_detail(format!("{:#}", e)))?;
+    let frame_duration = frames.frame_duration();
+    let pre_roll_frames = (PRE_ROLL.as_secs_f64() / frame_duration.as_secs_f64()).ceil() as usize;
+    let listen_timeout = Duration::from_secs(config.listen_timeout_secs);
+    let max_utterance = Duration::from_secs(config.max_utterance_secs);
+    let end_silence = Duration::from_millis(config.end_silence_ms);
+
+    let waiting_since = Instant::now();
+    let mut pre_roll: VecDeque<Vec<f32>> = VecDeque::with_capacity(pre_roll_frames + 1);
+    let mut speech: Vec<f32> = Vec::new();
+    let mut speech_started: Option<Instant> = None;
+    let mut silence = Duration::ZERO;
+
+    loop {
+        if *cancel.borrow() {
+            return Err(cancelled());
+        }
+
+        while let Some(frame) = frames.next_frame() {
+            let loud = rms(&frame) >= config.vad_threshold;
+            match speech_started {
+                None if loud => {
+                    speech_started = Some(Instant::now());
+                    speech.extend(pre_roll.drain(..).flatten());
+                    speech.extend_from_slice(&frame);
+                }
+                None => {
+                    pre_roll.push_back(frame);
+                    if pre_roll.len() > pre_roll_frames {
+                        pre_roll.pop_front();
+                    }
+                }
+                Some(started) => {
+                    speech.extend_from_slice(&frame);
+                    silence = if loud { Duration::ZERO } else { silence + frame_duration };
+                    if silence >= end_silence || started.elapsed() >= max_utterance {
+                        return Ok((speech, frames.sample_rate()));
+                    }
+                }
+            }
+        }
+
+        if speech_started.is_none() && waiting_since.elapsed() >= listen_timeout {
+            return Err(AstralError::new(ErrorCode::Timeout, "I didn't hear anything"));
+        }
+        std::thread::sleep(frame_duration);
+    }
+}
+
+/// Linear-interpolation resample; good enough for speech going to Whisper
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from as f64 / to as f64;
+    let length = (samples.len() as f64 / ratio) as usize;
+    (0..length)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let index = position as usize;
+            let next = samples.get(index + 1).copied().unwrap_or(samples[index]);
+            let fraction = (position - index as f64) as f32;
+            samples[index] + (next - samples[index]) * fraction
+        })
+        .collect()
+}
+
+/// 16 kHz mono 16-bit WAV, what the Whisper server wants
+fn encode_wav(samples: &[f32], sample_rate: u32) -> AstralResult<Vec<u8>> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: STT_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut wav = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut wav, spec).map_err(|e| AstralError::from(e.to_string()))?;
+    for sample in resample(samples, sample_rate, STT_SAMPLE_RATE) {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .map_err(|e| AstralError::from(e.to_string()))?;
+    }
+    writer.finalize().map_err(|e| AstralError::from(e.to_string()))?;
+    Ok(wav.into_inner())
+}
+
+async fn run(app: &AppHandle, cancel: watch::Receiver<bool>) -> AstralResult<InteractionResult> {
+    let settings = crate::settings::load_settings(app.clone()).await?;
+    let config = settings.voice.interaction;
+    super::transition(VoiceState::Recording)?;
+
+    // Capture + VAD, bounded by the listen timeout and maximum utterance length
+    let recorder_config = config.clone();
+    let (samples, sample_rate) = tokio::task::spawn_blocking(move || record_utterance(&recorder_config, &cancel))
+        .await?
+        .inspect_err(|e| {
+            if e.code != ErrorCode::Cancelled {
+                bus::publish(PipelineEvent::StageFailed { stage: Stage::Capture, error: e.message.clone() });
+            }
+        })?;
+    let duration_ms = samples.len() as u64 * 1000 / sample_rate.max(1) as u64;
+    bus::publish(PipelineEvent::SpeechCaptured { duration_ms });
+    super::transition(VoiceState::Processing)?;
+
+    let wav = tokio::task::spawn_blocking(move || encode_wav(&samples, sample_rate)).await??;
+    let engine = WhisperEngine::new(settings.voice.whisper);
+    let transcript = timed(
+        Stage::Stt,
+        Duration::from_secs(config.stt_timeout_secs),
+        "Speech-to-text",
+        async { engine.transcribe_bytes_detailed(wav).await.map_err(AstralError::from) },
+    )
+    .await?;
+
+    if transcript.text.is_empty() {
+        super::settle();
+        return Ok(InteractionResult {
+            transcript: String::new(),
+            confidence: transcript.confidence,
+            reply: String::new(),
+            audio: None,
+        });
+    }
+
+    let reply = timed(
+        Stage::Intent,
+        Duration::from_secs(config.response_timeout_secs),
+        "Answering",
+        async {
+            crate::commands::execute_command(app.clone(), transcript.text.clone(), transcript.confidence)
+                .await
+                .map_err(AstralError::from)
+        },
+    )
+    .await?;
+
+    let audio = if settings.tts.enabled && !reply.trim().is_empty() {
+        let state = app.state::<AppState>();
+        let engine = state.inner().tts.lock().await;
+        Some(timed(Stage::Tts, Duration::from_secs(config.tts_timeout_secs), "Speech synthesis", engine.generate_speech(&reply)).await?)
+    } else {
+        None
+    };
+
+    if reply.trim().is_empty() {
+        super::settle();
+    } else {
+        super::transition(VoiceState::Speaking)?;
+    }
+
+    Ok(InteractionResult {
+        transcript: transcript.text,
+        confidence: transcript.confidence,
+        reply,
+        audio,
+    })
+}
+
+// ========== Tauri Commands ==========
+
+/// Listen for one command, answer it and return the reply (plus its audio
+/// when ElevenLabs is on). The UI plays the reply and then calls `voice_settle`.
+#[tauri::command]
+pub async fn voice_interaction(app: AppHandle) -> AstralResult<InteractionResult> {
+    super::ensure_unmuted()?;
+    if super::is_paused() {
+        return Err(AstralError::invalid_input("The assistant is paused"));
+    }
+
+    let (sender, mut cancel) = watch::channel(false);
+    {
+        let mut running = CANCEL.lock().unwrap_or_else(|e| e.into_inner());
+        if running.is_some() {
+            return Err(AstralError::invalid_input("A voice interaction is already running"));
+        }
+        *running = Some(sender);
+    }
+
+    let stages = cancel.clone();
+    let result = tokio::select! {
+        result = run(&app, stages) => result,
+        _ = cancel.wait_for(|cancelled| *cancelled) => Err(cancelled()),
+    };
+    *CANCEL.lock().unwrap_or_else(|e| e.into_inner()) = None;
+
+    if let Err(e) = &result {
+        warn!("Voice interaction ended early: {}", e);
+        super::settle();
+    }
+    result
+}
+
+#[tauri::command]
+pub async fn cancel_voice_interaction() -> AstralResult<bool> {
+    Ok(cancel())
+}
@@ -0,0 +1,275 @@
+// Voice Subsystem
+// Single home for capture, wake word, STT and keyword commands, driven by one
+// state machine: Idle -> WakeListening -> Recording -> Processing -> Speaking.
+// Stages report progress over the pipeline bus (`bus`).
+
+pub mod bus;
+pub mod capture;
+pub mod interaction;
+pub mod vosk;
+pub mod wake_word;
+pub mod whisper;
+
+use tracing::{debug, info, warn};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted on every state change, payload is `VoiceStateChange`
+pub const STATE_CHANGED_EVENT: &str = "voice-state-changed";
+
+/// Event emitted when the mic is muted or unmuted, payload is the new `bool`
+pub const MUTE_CHANGED_EVENT: &str = "mic-mute-changed";
+
+/// Event asking the UI to say something the backend came up with (alerts,
+/// routine output); payload is the text
+pub const SPEAK_EVENT: &str = "assistant-speak";
+
+/// Push-to-talk hotkey went down (`true`) or up (`false`); the UI records in between
+pub const PUSH_TO_TALK_EVENT: &str = "push-to-talk";
+
+/// Event asking the UI to cut off whatever it's saying
+pub const STOP_SPEAKING_EVENT: &str = "stop-speaking";
+
+/// Event emitted when the assistant is paused or resumed, payload is the new `bool`
+pub const PAUSE_CHANGED_EVENT: &str = "assistant-paused";
+
+/// Where the voice pipeline currently is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VoiceState {
+    Idle,
+    WakeListening,
+    Recording,
+    Processing,
+    Speaking,
+}
+
+impl VoiceState {
+    /// Whether the pipeline may move from `self` to `next`.
+    /// Any state can drop back to Idle; otherwise the flow only moves forward,
+    /// except for returning to WakeListening once an interaction is over.
+    pub fn can_transition_to(self, next: VoiceState) -> bool {
+        use VoiceState::*;
+
+        matches!(
+            (self, next),
+            (_, Idle)
+                | (Idle, WakeListening)
+                | (Idle, Recording)
+                | (Idle, Speaking)
+                | (WakeListening, Recording)
+                | (WakeListening, Speaking)
+                | (Recording, Processing)
+                | (Recording, WakeListening)
+                | (Processing, Speaking)
+                | (Processing, Recording)
+                | (Processing, WakeListening)
+                | (Speaking, Recording)
+                | (Speaking, WakeListening)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VoiceStateChange {
+    pub from: VoiceState,
+    pub to: VoiceState,
+}
+
+static STATE: Lazy<Mutex<VoiceState>> = Lazy::new(|| Mutex::new(VoiceState::Idle));
+static APP_HANDLE: OnceCell<AppHandle> = OnceCell::new();
+/// Paused: the wake word and push-to-talk are ignored and unprompted speech is dropped
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Hook the subsystem up to the app so state changes reach the UI
+pub fn init(app: AppHandle) {
+    if APP_HANDLE.set(app.clone()).is_err() {
+        warn!("Voice subsystem already initialized");
+        return;
+    }
+    bus::init(app);
+    info!("Voice subsystem initialized");
+}
+
+/// Current voice pipeline state
+pub fn state() -> VoiceState {
+    *STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Move the pipeline to `next`, publishing the change on the bus if it changed
+pub fn transition(next: VoiceState) -> Result<VoiceState, String> {
+    let from = {
+        let mut state = STATE.lock().map_err(|e| e.to_string())?;
+        let from = *state;
+
+        if from == next {
+            return Ok(next);
+        }
+
+        if !from.can_transition_to(next) {
+            return Err(format!("Invalid voice state transition: {:?} -> {:?}", from, next));
+        }
+
+        *state = next;
+        from
+    };
+
+    info!("Voice state: {:?} -> {:?}", from, next);
+    crate::metrics::voice_state_changed(next);
+    bus::publish(bus::PipelineEvent::StateChanged { from, to: next });
+
+    Ok(next)
+}
+
+/// Return to the resting state once an interaction is over: keep listening for
+/// the wake word if detection is on, otherwise go idle
+pub fn settle() -> VoiceState {
+    let resting = if wake_word::is_active() {
+        VoiceState::WakeListening
+    } else {
+        VoiceState::Idle
+    };
+
+    transition(resting).unwrap_or_else(|e| {
+        warn!("{}", e);
+        state()
+    })
+}
+
+/// Whether the microphone is muted
+pub fn is_muted() -> bool {
+    capture::is_muted()
+}
+
+/// Mute or unmute the microphone. Capture is paused at the device, audio sent
+/// in for transcription is refused, and the tray icon reflects the state.
+pub fn set_muted(muted: bool) {
+    if capture::is_muted() == muted {
+        return;
+    }
+
+    capture::set_muted(muted);
+    info!("Microphone {}", if muted { "muted" } else { "unmuted" });
+
+    if let Some(app) = APP_HANDLE.get() {
+        crate::tray::set_muted_indicator(app, muted);
+        if let Err(e) = app.emit(MUTE_CHANGED_EVENT, muted) {
+            warn!("Failed to emit mute change: {}", e);
+        }
+    }
+}
+
+/// Whether the assistant is paused from the tray
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Acquire)
+}
+
+/// Pause or resume the assistant
+pub fn set_paused(paused: bool) {
+    if PAUSED.swap(paused, Ordering::AcqRel) == paused {
+        return;
+    }
+    info!("Assistant {}", if paused { "paused" } else { "resumed" });
+
+    if let Some(app) = APP_HANDLE.get() {
+        crate::tray::set_paused_indicator(app, paused);
+        if let Err(e) = app.emit(PAUSE_CHANGED_EVENT, paused) {
+            warn!("Failed to emit pause change: {}", e);
+        }
+    }
+}
+
+/// Have the assistant say `text` unprompted. Speech itself (ElevenLabs or the
+/// browser voice) runs in the UI.
+pub fn speak(text: &str) {
+    if is_paused() {
+        debug!("Assistant paused, not saying: {}", text);
+        return;
+    }
+    if APP_HANDLE.get().is_none() {
+        warn!("Voice subsystem not initialized, dropping speech: {}", text);
+        return;
+    }
+    bus::publish(bus::PipelineEvent::SpeechRequested { text: text.to_string() });
+}
+
+/// Start (`true`) or finish (`false`) a push-to-talk recording
+pub fn push_to_talk(pressed: bool) {
+    if pressed && (is_muted() || is_paused()) {
+        info!("Push-to-talk ignored, microphone is muted or the assistant is paused");
+        return;
+    }
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = app.emit(PUSH_TO_TALK_EVENT, pressed) {
+            warn!("Failed to emit push-to-talk: {}", e);
+        }
+    }
+}
+
+/// Stop the current speech, abandon any interaction or LLM request in
+/// progress and settle the pipeline
+pub fn stop_speaking() {
+    interaction::cancel();
+    crate::llm_provider::cancel_requests();
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = app.emit(STOP_SPEAKING_EVENT, ()) {
+            warn!("Failed to emit stop speaking: {}", e);
+        }
+    }
+    if state() == VoiceState::Speaking {
+        settle();
+    }
+}
+
+/// Flip the mute state, returning the new value
+pub fn toggle_muted() -> bool {
+    let muted = !is_muted();
+    set_muted(muted);
+    muted
+}
+
+/// Error out if the mic is muted; used to gate audio coming in from the frontend
+pub fn ensure_unmuted() -> crate::error::AstralResult<()> {
+    if is_muted() {
+        Err(crate::error::AstralError::new(crate::error::ErrorCode::Muted, "Microphone is muted"))
+    } else {
+        Ok(())
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn voice_get_state() -> Result<VoiceState, String> {
+    Ok(state())
+}
+
+/// Report pipeline progress from the frontend (it owns recording and playback)
+#[tauri::command]
+pub async fn voice_set_state(state: VoiceState) -> Result<VoiceState, String> {
+    transition(state)
+}
+
+/// Finish an interaction and return to Idle / WakeListening
+#[tauri::command]
+pub async fn voice_settle() -> Result<VoiceState, String> {
+    Ok(settle())
+}
+
+#[tauri::command]
+pub async fn voice_is_muted() -> Result<bool, String> {
+    Ok(is_muted())
+}
+
+#[tauri::command]
+pub async fn voice_set_muted(muted: bool) -> Result<bool, String> {
+    set_muted(muted);
+    Ok(muted)
+}
+
+#[tauri::command]
+pub async fn voice_toggle_mute() -> Result<bool, String> {
+    Ok(toggle_muted())
+}
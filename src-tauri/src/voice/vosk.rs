@@ -0,0 +1,222 @@
+// Voice Keyword Commands (Vosk)
+// Offline recognizer for a small fixed command grammar ("stop", "louder", "next track")
+// that answers in tens of milliseconds without going through Whisper + LLM
+
+use anyhow::{anyhow, Result};
+use tracing::debug;
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use tauri::AppHandle;
+
+/// The `voice.vosk` settings section
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct VoskConfig {
+    pub enabled: bool,
+    pub model_path: String,
+}
+
+impl Default for VoskConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            // e.g. vosk-model-small-en-us-0.15 unpacked somewhere on disk
+            model_path: String::new(),
+        }
+    }
+}
+
+/// Commands the offline recognizer understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeywordCommand {
+    Stop,
+    Pause,
+    Play,
+    NextTrack,
+    PreviousTrack,
+    Louder,
+    Quieter,
+    Mute,
+    Unmute,
+}
+
+/// Spoken phrases mapped to their command. Every phrase is part of the grammar.
+const COMMAND_PHRASES: &[(&str, KeywordCommand)] = &[
+    ("stop", KeywordCommand::Stop),
+    ("pause", KeywordCommand::Pause),
+    ("play", KeywordCommand::Play),
+    ("resume", KeywordCommand::Play),
+    ("next", KeywordCommand::NextTrack),
+    ("next track", KeywordCommand::NextTrack),
+    ("skip", KeywordCommand::NextTrack),
+    ("previous", KeywordCommand::PreviousTrack),
+    ("previous track", KeywordCommand::PreviousTrack),
+    ("go back", KeywordCommand::PreviousTrack),
+    ("louder", KeywordCommand::Louder),
+    ("volume up", KeywordCommand::Louder),
+    ("quieter", KeywordCommand::Quieter),
+    ("volume down", KeywordCommand::Quieter),
+    ("mute", KeywordCommand::Mute),
+    ("unmute", KeywordCommand::Unmute),
+];
+
+impl KeywordCommand {
+    pub fn from_phrase(phrase: &str) -> Option<Self> {
+        let phrase = phrase.trim().to_lowercase();
+        COMMAND_PHRASES
+            .iter()
+            .find(|(p, _)| *p == phrase)
+            .map(|(_, cmd)| *cmd)
+    }
+}
+
+/// Result of running the keyword recognizer over a clip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordMatch {
+    pub command: KeywordCommand,
+    pub phrase: String,
+    pub confidence: f32,
+}
+
+/// Build the JSON grammar list passed to Vosk. "[unk]" lets the recognizer
+/// reject speech that isn't a command instead of forcing the closest match.
+#[cfg_attr(not(feature = "vosk"), allow(dead_code))]
+fn grammar() -> Vec<&'static str> {
+    let mut phrases: Vec<&str> = COMMAND_PHRASES.iter().map(|(p, _)| *p).collect();
+    phrases.push("[unk]");
+    phrases
+}
+
+/// Decode a WAV clip into mono 16-bit samples plus its sample rate
+fn decode_wav(audio_bytes: &[u8]) -> Result<(Vec<i16>, f32)> {
+    let mut reader = hound::WavReader::new(Cursor::new(audio_bytes))
+        .map_err(|e| anyhow!("Failed to read WAV data: {}", e))?;
+    let spec = reader.spec();
+
+    if spec.bits_per_sample != 16 || spec.sample_format != hound::SampleFormat::Int {
+        return Err(anyhow!("Expected 16-bit PCM WAV, got {}-bit {:?}", spec.bits_per_sample, spec.sample_format));
+    }
+
+    let channels = spec.channels.max(1) as usize;
+    let samples: Vec<i16> = reader
+        .samples::<i16>()
+        .step_by(channels) // keep the first channel only
+        .collect::<Result<_, _>>()
+        .map_err(|e| anyhow!("Failed to decode WAV samples: {}", e))?;
+
+    Ok((samples, spec.sample_rate as f32))
+}
+
+#[cfg(feature = "vosk")]
+mod backend {
+    use super::*;
+    use once_cell::sync::Lazy;
+    use std::sync::Mutex;
+    use tracing::info;
+    use ::vosk::{Model, Recognizer};
+
+    // Loading a model takes a few hundred ms, so keep it around between clips
+    static MODEL: Lazy<Mutex<Option<(String, Model)>>> = Lazy::new(|| Mutex::new(None));
+
+    pub fn recognize(model_path: &str, samples: &[i16], sample_rate: f32) -> Result<Option<(String, f32)>> {
+        let mut cached = MODEL.lock().map_err(|e| anyhow!("{}", e))?;
+
+        let reload = !matches!(cached.as_ref(), Some((path, _)) if path == model_path);
+        if reload {
+            info!("Loading Vosk model from {}", model_path);
+            let model = Model::new(model_path)
+                .ok_or_else(|| anyhow!("Failed to load Vosk model at '{}'", model_path))?;
+            *cached = Some((model_path.to_string(), model));
+        }
+
+        let (_, model) = cached.as_ref().unwrap();
+        let mut recognizer = Recognizer::new_with_grammar(model, sample_rate, &grammar())
+            .ok_or_else(|| anyhow!("Failed to create Vosk recognizer"))?;
+        recognizer.set_words(true);
+
+        recognizer
+            .accept_waveform(samples)
+            .map_err(|e| anyhow!("Vosk rejected audio: {:?}", e))?;
+
+        let result = match recognizer.final_result().single() {
+            Some(result) => result,
+            None => return Ok(None),
+        };
+
+        if result.text.is_empty() {
+            return Ok(None);
+        }
+
+        // Average per-word confidence as the clip confidence
+        let confidence = if result.result.is_empty() {
+            1.0
+        } else {
+            result.result.iter().map(|w| w.conf).sum::<f32>() / result.result.len() as f32
+        };
+
+        Ok(Some((result.text.to_string(), confidence)))
+    }
+}
+
+#[cfg(not(feature = "vosk"))]
+mod backend {
+    use super::*;
+
+    pub fn recognize(_model_path: &str, _samples: &[i16], _sample_rate: f32) -> Result<Option<(String, f32)>> {
+        Err(anyhow!("ASTRAL was built without Vosk support (enable the `vosk` feature)"))
+    }
+}
+
+pub struct VoskEngine {
+    config: VoskConfig,
+}
+
+impl VoskEngine {
+    pub fn new(config: VoskConfig) -> Self {
+        Self { config }
+    }
+
+    /// Recognize a keyword command in a WAV clip. Returns None when the clip
+    /// contains no known command.
+    pub fn recognize_bytes(&self, audio_bytes: &[u8]) -> Result<Option<KeywordMatch>> {
+        if !self.config.enabled {
+            return Err(anyhow!("Vosk is not enabled"));
+        }
+
+        if self.config.model_path.is_empty() {
+            return Err(anyhow!("Vosk model path not set. Download one at: https://alphacephei.com/vosk/models"));
+        }
+
+        let (samples, sample_rate) = decode_wav(audio_bytes)?;
+        debug!("Running keyword recognizer over {} samples at {} Hz", samples.len(), sample_rate);
+
+        let recognized = backend::recognize(&self.config.model_path, &samples, sample_rate)?;
+
+        Ok(recognized.and_then(|(phrase, confidence)| {
+            let command = KeywordCommand::from_phrase(&phrase)?;
+            debug!("Keyword command: {:?} ('{}', confidence {:.2})", command, phrase, confidence);
+            Some(KeywordMatch { command, phrase, confidence })
+        }))
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn vosk_recognize_command(app: AppHandle, audio_bytes: Vec<u8>) -> Result<Option<KeywordMatch>, String> {
+    super::ensure_unmuted()?;
+
+    let config = crate::settings::load_settings(app).await?.voice.vosk;
+    let engine = VoskEngine::new(config);
+
+    // Recognition is CPU-bound; keep it off the async runtime threads
+    tokio::task::spawn_blocking(move || engine.recognize_bytes(&audio_bytes))
+        .await
+        .map_err(|e| format!("Recognizer task failed: {}", e))?
+        .map_err(|e| format!("Keyword recognition failed: {}", e))
+}
+
+#[tauri::command]
+pub async fn vosk_get_command_phrases() -> Result<Vec<String>, String> {
+    Ok(COMMAND_PHRASES.iter().map(|(p, _)| p.to_string()).collect())
+}
@@ -0,0 +1,260 @@
+use anyhow::{anyhow, Context, Result};
+use tracing::{debug, error, info, warn};
+use reqwest;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::error::{AstralError, AstralResult};
+use super::bus::{self, PipelineEvent};
+
+/// Also the `voice.whisper` settings section
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WhisperConfig {
+    pub enabled: bool,
+    pub server_url: String,
+    pub model: String,
+}
+
+impl Default for WhisperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server_url: "http://localhost:9881".to_string(),
+            model: "base.en".to_string(),
+        }
+    }
+}
+
+pub struct WhisperEngine {
+    config: WhisperConfig,
+    client: reqwest::Client,
+}
+
+impl WhisperEngine {
+    pub fn new(config: WhisperConfig) -> Self {
+        Self {
+            config,
+            client: crate::proxy::client(),
+        }
+    }
+
+    /// Check if Whisper server is running and healthy
+    pub async fn health_check(&self) -> Result<bool> {
+        let url = format!("{}/health", self.config.server_url);
+        
+        match crate::privacy::send(self.client.get(&url)).await {
+            Ok(response) => {
+                if response.status().is_success() {
+                    info!("Whisper server health check passed");
+                    Ok(true)
+                } else {
+                    error!("Whisper server returned non-success status");
+                    Ok(false)
+                }
+            }
+            Err(e) => {
+                error!("Whisper server health check failed: {}", e);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Post audio to the Whisper server; the round trip is the STT latency metric.
+    /// The server is told to expect the current locale's language, and the
+    /// result goes out on the pipeline bus.
+    async fn send_for_transcription(&self, form: reqwest::multipart::Form) -> Result<TranscribeResponse> {
+        let language = crate::locale::current_language();
+        if language != "en" && self.config.model.ends_with(".en") {
+            warn!("Whisper model {} only understands English; use a multilingual model for {}", self.config.model, language);
+        }
+        let form = form.text("language", language);
+
+        let result = crate::metrics::time(crate::metrics::Stage::Stt, async {
+            let url = format!("{}/transcribe", self.config.server_url);
+            let response = crate::privacy::send(self.client.post(&url).multipart(form))
+                .await
+                .context("Couldn't reach the Whisper server")?;
+
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_default();
+                return Err(anyhow::Error::from(AstralError::from_status("The Whisper server", status, &error_text)));
+            }
+
+            response.json::<TranscribeResponse>().await
+                .map_err(|e| anyhow!("Failed to parse Whisper response: {}", e))
+        })
+        .await;
+
+        bus::publish(match &result {
+            Ok(response) => PipelineEvent::Transcribed {
+                text: response.text.trim().to_string(),
+                confidence: response.confidence(),
+            },
+            Err(e) => PipelineEvent::StageFailed { stage: bus::Stage::Stt, error: e.to_string() },
+        });
+        result
+    }
+
+    /// Transcribe audio file to text
+    pub async fn transcribe_file(&self, audio_path: PathBuf) -> Result<String> {
+        if !self.config.enabled {
+            return Err(AstralError::not_configured("Whisper is not enabled").into());
+        }
+
+        // Read audio file
+        let audio_bytes = fs::read(&audio_path)
+            .map_err(|e| anyhow!("Failed to read audio file: {}", e))?;
+
+        debug!("Sending {} bytes to Whisper server", audio_bytes.len());
+
+        // Create multipart form
+        let form = reqwest::multipart::Form::new()
+            .part(
+                "file",
+                reqwest::multipart::Part::bytes(audio_bytes)
+                    .file_name("audio.wav")
+                    .mime_str("audio/wav")?,
+            );
+
+        let result = self.send_for_transcription(form).await?;
+
+        debug!("Transcription: {}", result.text);
+        Ok(result.text.trim().to_string())
+    }
+
+    /// Transcribe raw audio bytes (WAV format)
+    pub async fn transcribe_bytes(&self, audio_bytes: Vec<u8>) -> Result<String> {
+        Ok(self.transcribe_bytes_detailed(audio_bytes).await?.text)
+    }
+
+    /// Transcribe raw audio bytes (WAV format), keeping the confidence score
+    /// when the server reports one
+    pub async fn transcribe_bytes_detailed(&self, audio_bytes: Vec<u8>) -> Result<Transcript> {
+        if !self.config.enabled {
+            return Err(AstralError::not_configured("Whisper is not enabled").into());
+        }
+
+        debug!("Transcribing {} bytes", audio_bytes.len());
+
+        // Create multipart form
+        let form = reqwest::multipart::Form::new()
+            .part(
+                "file",
+                reqwest::multipart::Part::bytes(audio_bytes)
+                    .file_name("audio.wav")
+                    .mime_str("audio/wav")?,
+            );
+
+        let result = self.send_for_transcription(form).await?;
+
+        let confidence = result.confidence();
+        debug!("Transcription: {} (confidence: {:?})", result.text, confidence);
+        Ok(Transcript {
+            text: result.text.trim().to_string(),
+            confidence,
+        })
+    }
+}
+
+/// Transcription text plus the recognizer's confidence (0.0 - 1.0), if known
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Transcript {
+    pub text: String,
+    pub confidence: Option<f32>,
+}
+
+/// Whisper server response. Servers that only return `text` are treated as
+/// confident; richer servers can send `confidence` directly or the usual
+/// whisper `segments` with `avg_logprob` / `no_speech_prob`.
+#[derive(Deserialize)]
+struct TranscribeResponse {
+    text: String,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    segments: Vec<TranscribeSegment>,
+}
+
+#[derive(Deserialize)]
+struct TranscribeSegment {
+    avg_logprob: f32,
+    #[serde(default)]
+    no_speech_prob: f32,
+}
+
+impl TranscribeResponse {
+    fn confidence(&self) -> Option<f32> {
+        if let Some(confidence) = self.confidence {
+            return Some(confidence.clamp(0.0, 1.0));
+        }
+
+        if self.segments.is_empty() {
+            return None;
+        }
+
+        let count = self.segments.len() as f32;
+        let avg_logprob = self.segments.iter().map(|s| s.avg_logprob).sum::<f32>() / count;
+        let no_speech = self.segments.iter().map(|s| s.no_speech_prob).sum::<f32>() / count;
+
+        Some((avg_logprob.exp() * (1.0 - no_speech)).clamp(0.0, 1.0))
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn whisper_get_config(app: AppHandle) -> AstralResult<WhisperConfig> {
+    Ok(crate::settings::load_settings(app).await?.voice.whisper)
+}
+
+#[tauri::command]
+pub async fn whisper_update_config(app: AppHandle, config: WhisperConfig) -> AstralResult<()> {
+    info!("Whisper config updated: enabled={}, url={}", config.enabled, config.server_url);
+    crate::settings::modify_settings(&app, |settings| {
+        settings.voice.whisper = config;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn whisper_health_check(app: AppHandle) -> AstralResult<bool> {
+    let config = whisper_get_config(app).await?;
+    let engine = WhisperEngine::new(config);
+    
+    Ok(engine.health_check().await?)
+}
+
+#[tauri::command]
+pub async fn whisper_transcribe(app: AppHandle, audio_path: String) -> AstralResult<String> {
+    super::ensure_unmuted()?;
+    let config = whisper_get_config(app).await?;
+    let engine = WhisperEngine::new(config);
+    
+    engine.transcribe_file(PathBuf::from(audio_path)).await
+        .map_err(AstralError::from)
+}
+
+#[tauri::command]
+pub async fn whisper_transcribe_bytes(app: AppHandle, audio_bytes: Vec<u8>) -> AstralResult<String> {
+    super::ensure_unmuted()?;
+    let config = whisper_get_config(app).await?;
+    let engine = WhisperEngine::new(config);
+    
+    engine.transcribe_bytes(audio_bytes).await
+        .map_err(AstralError::from)
+}
+
+#[tauri::command]
+pub async fn whisper_transcribe_detailed(app: AppHandle, audio_bytes: Vec<u8>) -> AstralResult<Transcript> {
+    super::ensure_unmuted()?;
+    let config = whisper_get_config(app).await?;
+    let engine = WhisperEngine::new(config);
+    
+    engine.transcribe_bytes_detailed(audio_bytes).await
+        .map_err(AstralError::from)
+}
@@ -0,0 +1,288 @@
+// Web Actions Module
+// Spoken shortcuts into websites: "search YouTube for lofi and play the
+// first result", "open Gmail", "google this". Each action is a set of
+// phrase templates and a URL template, filled in here rather than left to
+// the LLM: `{query}` is the words the phrase captured and `{clipboard}` the
+// text on the clipboard, both URL-encoded. An action can also follow the
+// results page to its first link (`first_result`), which is how "play the
+// first result" works without a YouTube API key. The built-in actions can
+// be changed or added to in `web_actions.json` in the config dir; an entry
+// with the same name replaces a built-in one, and `"disabled": true` turns
+// it off. The file is reread when it changes.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tracing::{info, warn};
+
+/// Pulls the first link out of a results page: the text between `after`
+/// and `until`, put into `url` as `{match}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FirstResult {
+    pub after: String,
+    pub until: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebAction {
+    pub name: String,
+    /// What to call the site in replies, e.g. "YouTube"
+    #[serde(default)]
+    pub label: String,
+    /// "search youtube for {query}"; matched case-insensitively
+    pub phrases: Vec<String>,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_result: Option<FirstResult>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub disabled: bool,
+}
+
+impl WebAction {
+    fn label(&self) -> &str {
+        if self.label.is_empty() {
+            &self.name
+        } else {
+            &self.label
+        }
+    }
+}
+
+fn action(name: &str, label: &str, phrases: &[&str], url: &str) -> WebAction {
+    WebAction {
+        name: name.to_string(),
+        label: label.to_string(),
+        phrases: phrases.iter().map(|p| p.to_string()).collect(),
+        url: url.to_string(),
+        first_result: None,
+        disabled: false,
+    }
+}
+
+fn builtin_actions() -> Vec<WebAction> {
+    vec![
+        WebAction {
+            first_result: Some(FirstResult {
+                after: "\"videoId\":\"".to_string(),
+                until: "\"".to_string(),
+                url: "https://www.youtube.com/watch?v={match}".to_string(),
+            }),
+            ..action(
+                "youtube_play",
+                "YouTube",
+                &["search youtube for {query} and play the first result", "play {query} on youtube", "youtube play {query}"],
+                "https://www.youtube.com/results?search_query={query}",
+            )
+        },
+        action(
+            "youtube_search",
+            "YouTube",
+            &["search youtube for {query}", "search on youtube for {query}", "find {query} on youtube"],
+            "https://www.youtube.com/results?search_query={query}",
+        ),
+        action("youtube", "YouTube", &["open youtube", "go to youtube"], "https://www.youtube.com"),
+        action("google_this", "Google", &["google this", "google that", "search for this", "look this up"], "https://www.google.com/search?q={clipboard}"),
+        action("google", "Google", &["search google for {query}", "open google search for {query}"], "https://www.google.com/search?q={query}"),
+        action("gmail", "Gmail", &["open gmail", "open my gmail", "go to gmail"], "https://mail.google.com"),
+        action("google_calendar", "Google Calendar", &["open google calendar"], "https://calendar.google.com"),
+        action("google_drive", "Google Drive", &["open google drive", "open my drive"], "https://drive.google.com"),
+        action(
+            "directions",
+            "Google Maps",
+            &["directions to {query}", "get directions to {query}"],
+            "https://www.google.com/maps/dir/?api=1&destination={query}",
+        ),
+        action("maps", "Google Maps", &["show {query} on the map", "find {query} on google maps"], "https://www.google.com/maps/search/{query}"),
+        action("maps_home", "Google Maps", &["open google maps", "open maps"], "https://www.google.com/maps"),
+        action("wikipedia", "Wikipedia", &["search wikipedia for {query}", "look up {query} on wikipedia"], "https://en.wikipedia.org/wiki/Special:Search?search={query}"),
+        action("amazon", "Amazon", &["search amazon for {query}", "find {query} on amazon"], "https://www.amazon.com/s?k={query}"),
+        action("github", "GitHub", &["search github for {query}"], "https://github.com/search?q={query}"),
+        action("github_home", "GitHub", &["open github", "go to github"], "https://github.com"),
+        action("reddit", "Reddit", &["search reddit for {query}"], "https://www.reddit.com/search/?q={query}"),
+        action("translate", "Google Translate", &["translate this"], "https://translate.google.com/?text={clipboard}"),
+    ]
+}
+
+/// Loaded actions and the modification time of the file they came from
+static ACTIONS: Lazy<Mutex<(Option<SystemTime>, Vec<WebAction>)>> = Lazy::new(|| Mutex::new((None, load())));
+
+fn actions_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("ASTRAL");
+    path.push("web_actions.json");
+    Some(path)
+}
+
+fn modified() -> Option<SystemTime> {
+    std::fs::metadata(actions_path()?).and_then(|m| m.modified()).ok()
+}
+
+/// Built-in actions merged with the user's file
+fn load() -> Vec<WebAction> {
+    let mut actions = builtin_actions();
+    let Some(content) = actions_path().and_then(|path| std::fs::read_to_string(path).ok()) else {
+        return actions;
+    };
+    let custom: Vec<WebAction> = match serde_json::from_str(&content) {
+        Ok(custom) => custom,
+        Err(e) => {
+            warn!("Ignoring unreadable web_actions.json: {}", e);
+            return actions;
+        }
+    };
+    for entry in custom {
+        actions.retain(|a| a.name != entry.name);
+        actions.push(entry);
+    }
+    actions.retain(|a| !a.disabled);
+    info!("Loaded {} web actions", actions.len());
+    actions
+}
+
+/// The current actions, rereading the file if it changed
+pub fn actions() -> Vec<WebAction> {
+    let mut cached = ACTIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let modified = modified();
+    if cached.0 != modified {
+        *cached = (modified, load());
+    }
+    cached.1.clone()
+}
+
+// ========== Matching ==========
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebActionCommand {
+    pub action: WebAction,
+    /// What `{query}` captured
+    pub query: Option<String>,
+}
+
+/// Match `command` against one phrase template
+fn match_phrase(phrase: &str, command: &str) -> Option<Option<String>> {
+    let phrase = phrase.to_lowercase();
+    let Some((prefix, suffix)) = phrase.split_once("{query}") else {
+        return (phrase.trim() == command).then_some(None);
+    };
+    let query = command.strip_prefix(prefix)?.strip_suffix(suffix)?.trim();
+    (!query.is_empty()).then(|| Some(query.to_string()))
+}
+
+impl WebActionCommand {
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.trim().to_lowercase();
+        let lower = lower.trim_end_matches(['?', '.', '!']).trim();
+        let lower = lower.strip_suffix(" please").unwrap_or(lower);
+        // Longer phrases first, so "search youtube for x and play the first result"
+        // isn't taken as a search for "x and play the first result"
+        let mut candidates: Vec<(usize, WebAction, Option<String>)> = actions()
+            .into_iter()
+            .flat_map(|action| {
+                action
+                    .phrases
+                    .iter()
+                    .filter_map(|phrase| match_phrase(phrase, lower).map(|query| (phrase.len(), query)))
+                    .max_by_key(|(len, _)| *len)
+                    .map(|(len, query)| (len, action.clone(), query))
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.0.cmp(&a.0));
+        candidates
+            .into_iter()
+            .next()
+            .map(|(_, action, query)| WebActionCommand { action, query })
+    }
+}
+
+// ========== Running ==========
+
+/// Percent-encode for a URL query or path segment
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// The first result on the page at `url`, if the page has one
+async fn first_result(url: &str, rule: &FirstResult) -> Option<String> {
+    let request = crate::proxy::client()
+        .get(url)
+        .header("Accept-Language", "en")
+        .timeout(Duration::from_secs(10));
+    let page = crate::privacy::send(request).await.ok()?.text().await.ok()?;
+    let start = page.find(&rule.after)? + rule.after.len();
+    let end = page[start..].find(&rule.until)? + start;
+    let found = &page[start..end];
+    (!found.is_empty() && found.len() < 200).then(|| rule.url.replace("{match}", &encode(found)))
+}
+
+/// Fill in the action's URL and open it, saying what happened
+pub async fn run(app: &AppHandle, command: WebActionCommand) -> String {
+    let action = &command.action;
+    let mut url = action.url.clone();
+    let mut subject = command.query.clone();
+
+    if url.contains("{clipboard}") {
+        let copied = app.clipboard().read_text().unwrap_or_default();
+        let copied = copied.trim();
+        if copied.is_empty() {
+            return "Copy the text first, then ask me again.".to_string();
+        }
+        url = url.replace("{clipboard}", &encode(copied));
+        subject = Some(copied.chars().take(60).collect());
+    }
+    if let Some(query) = &command.query {
+        url = url.replace("{query}", &encode(query));
+    }
+
+    let mut reply = match &subject {
+        Some(subject) => format!("Searching {} for {}.", action.label(), subject),
+        None => format!("Opening {}.", action.label()),
+    };
+    if let Some(rule) = &action.first_result {
+        match first_result(&url, rule).await {
+            Some(result) => {
+                url = result;
+                reply = format!("Playing the first {} result for {}.", action.label(), subject.as_deref().unwrap_or("that"));
+            }
+            None => info!("No first result found for {}, opening the results instead", action.name),
+        }
+    }
+
+    match crate::system_integration::open_path(&url, false) {
+        Ok(_) => reply,
+        Err(e) => format!("I couldn't open {}: {}", action.label(), e),
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_web_actions() -> Vec<WebAction> {
+    actions()
+}
+
+/// Open `web_actions.json` for editing, creating it with the built-in actions first
+#[tauri::command]
+pub async fn open_web_actions_file() -> Result<String, String> {
+    let path = actions_path().ok_or_else(|| "Could not find config directory".to_string())?;
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create config dir: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&builtin_actions())
+            .map_err(|e| format!("Failed to serialize web actions: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write web actions: {}", e))?;
+    }
+    let path = path.to_string_lossy().to_string();
+    crate::system_integration::open_path(&path, false).map_err(|e| e.to_string())?;
+    Ok(path)
+}
@@ -0,0 +1,235 @@
+// Web Search Module
+// Grounds answers about current events in real search results instead of
+// whatever the model remembers. Questions that ask for fresh information
+// ("what's the latest on the Mars mission", "look up the Lakers score") are
+// searched with the Brave Search API or a self-hosted SearxNG instance, and
+// the results go to the LLM with instructions to cite them by number; the
+// cited links are listed under the answer. Also offered to LLM clients as the
+// `web_search` MCP tool. Off unless `search.enabled`.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use tauri::AppHandle;
+use tracing::{debug, info};
+
+const BRAVE_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+
+/// Longest snippet kept per result, so a handful fit a small model's context
+const MAX_SNIPPET_CHARS: usize = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchProvider {
+    Brave,
+    Searxng,
+}
+
+/// The `search` settings section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchSettings {
+    pub enabled: bool,
+    pub provider: SearchProvider,
+    pub brave_api_key: String,
+    /// Base address of the SearxNG instance, which needs the JSON format enabled
+    pub searxng_url: String,
+    pub max_results: usize,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: SearchProvider::Brave,
+            brave_api_key: String::new(),
+            searxng_url: "http://localhost:8888".to_string(),
+            max_results: 5,
+        }
+    }
+}
+
+impl SearchSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        self.searxng_url = self.searxng_url.trim().trim_end_matches('/').to_string();
+        if !(1..=20).contains(&self.max_results) {
+            return Err("Web search can return between 1 and 20 results".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+/// Whether a command asks for something only a search can answer well: an
+/// explicit "search the web for ..." / "look up ...", or a question about
+/// the news ("what's the latest on the launch?", "who won last night?").
+/// Mentioning "today" or "right now" isn't enough; those are usually about
+/// this machine or the user's own day.
+pub fn is_web_query(command: &str) -> bool {
+    const EXPLICIT: &[&str] = &["search the web", "search online", "search the internet", "look up ", "google ", "find online "];
+    const QUESTION: &[&str] = &["what", "what's", "whats", "who", "who's", "when", "how", "did", "is", "are", "tell"];
+    let lower = crate::intents::imperative(command);
+    if EXPLICIT.iter().any(|p| lower.starts_with(p)) {
+        return true;
+    }
+
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .collect();
+    let asks = words.first().is_some_and(|w| QUESTION.contains(w));
+    let current = words.iter().any(|w| ["latest", "news", "headlines"].contains(w))
+        || ["who won", "what happened", "the score"].iter().any(|p| lower.contains(p));
+    asks && current
+}
+
+/// The part worth searching for: "search the web for rust 1.80" -> "rust 1.80"
+fn search_terms(command: &str) -> String {
+    let trimmed = command.trim().trim_end_matches(['?', '.', '!']);
+    let lower = trimmed.to_lowercase();
+    ["search the web for ", "search online for ", "search the internet for ", "look up ", "google ", "find online "]
+        .iter()
+        .find_map(|p| lower.find(p).and_then(|i| trimmed.get(i + p.len()..)).map(|rest| rest.trim().to_string()))
+        .filter(|terms| !terms.is_empty())
+        .unwrap_or_else(|| trimmed.to_string())
+}
+
+fn snippet(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// `results`' entries as `SearchResult`s, reading the snippet from `snippet_key`
+fn parse_results(results: Option<&Value>, snippet_key: &str, limit: usize) -> Vec<SearchResult> {
+    results
+        .and_then(Value::as_array)
+        .map(|results| {
+            results
+                .iter()
+                .filter_map(|r| {
+                    Some(SearchResult {
+                        title: r.get("title")?.as_str()?.trim().to_string(),
+                        url: r.get("url")?.as_str()?.to_string(),
+                        snippet: snippet(r.get(snippet_key).and_then(Value::as_str).unwrap_or_default()),
+                    })
+                })
+                .take(limit)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn get_json(request: reqwest::RequestBuilder, service: &str) -> AstralResult<Value> {
+    let response = crate::privacy::send(request.timeout(Duration::from_secs(15)))
+        .await
+        .map_err(|e| AstralError::from(e.context(format!("Couldn't reach {}", service))))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status(service, status, &body));
+    }
+    Ok(response.json().await?)
+}
+
+/// Search with the configured provider
+pub async fn search(settings: &SearchSettings, query: &str) -> AstralResult<Vec<SearchResult>> {
+    if !settings.enabled {
+        return Err(AstralError::not_configured("Web search is off; turn it on in Settings"));
+    }
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(AstralError::invalid_input("Nothing to search for"));
+    }
+    debug!("Searching the web for {:?} with {:?}", query, settings.provider);
+
+    let client = crate::proxy::client();
+    let count = settings.max_results.to_string();
+    let results = match settings.provider {
+        SearchProvider::Brave => {
+            if settings.brave_api_key.is_empty() {
+                return Err(AstralError::not_configured("Add a Brave Search API key in Settings"));
+            }
+            let request = client
+                .get(BRAVE_ENDPOINT)
+                .query(&[("q", query), ("count", count.as_str())])
+                .header("Accept", "application/json")
+                .header("X-Subscription-Token", &settings.brave_api_key);
+            let body = get_json(request, "Brave Search").await?;
+            parse_results(body.pointer("/web/results"), "description", settings.max_results)
+        }
+        SearchProvider::Searxng => {
+            if settings.searxng_url.is_empty() {
+                return Err(AstralError::not_configured("Add the address of your SearxNG instance in Settings"));
+            }
+            let request = client
+                .get(format!("{}/search", settings.searxng_url))
+                .query(&[("q", query), ("format", "json")]);
+            let body = get_json(request, "SearxNG").await.map_err(|e| match e.code {
+                ErrorCode::Unauthorized => AstralError::new(
+                    ErrorCode::NotConfigured,
+                    "SearxNG refused the request; enable the json format under search.formats in its settings.yml",
+                ),
+                _ => e,
+            })?;
+            parse_results(body.get("results"), "content", settings.max_results)
+        }
+    };
+    info!("Web search found {} results", results.len());
+    Ok(results)
+}
+
+/// Search for what `command` asks about, using the saved settings
+pub async fn search_for_command(app: &AppHandle, command: &str) -> AstralResult<Vec<SearchResult>> {
+    let settings = crate::settings::load_settings(app.clone()).await?.search;
+    search(&settings, &search_terms(command)).await
+}
+
+pub async fn is_enabled(app: &AppHandle) -> bool {
+    crate::settings::load_settings(app.clone()).await.is_ok_and(|s| s.search.enabled)
+}
+
+pub fn build_search_prompt(request: &str, results: &[SearchResult]) -> String {
+    let listing: Vec<String> = results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| format!("[{}] {}\n{}\n{}", i + 1, r.title, r.url, r.snippet))
+        .collect();
+    format!(
+        "The user asked: \"{}\"\n\nToday is {}. These are web search results:\n---\n{}\n---\nAnswer from the results only, citing them by number like [1]. If they don't answer the question, say so. Keep it short enough to read aloud and don't read out the links.",
+        request,
+        chrono::Local::now().format("%A %-d %B %Y"),
+        listing.join("\n\n")
+    )
+}
+
+/// The links behind the `[n]` citations in `answer`, to show under it
+pub fn cited_sources(answer: &str, results: &[SearchResult]) -> String {
+    let cited: Vec<String> = results
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| answer.contains(&format!("[{}]", i + 1)))
+        .map(|(i, r)| format!("[{}] {} - {}", i + 1, r.title, r.url))
+        .collect();
+    if cited.is_empty() {
+        return String::new();
+    }
+    format!("\n\nSources:\n{}", cited.join("\n"))
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn web_search(app: AppHandle, query: String) -> AstralResult<Vec<SearchResult>> {
+    let settings = crate::settings::load_settings(app).await?.search;
+    search(&settings, &query).await
+}
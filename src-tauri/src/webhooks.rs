@@ -0,0 +1,168 @@
+// Webhooks Module
+// Pushes ASTRAL's notifications out to services that reach your phone when
+// you're away from the PC: ntfy topics, Slack incoming webhooks, IFTTT Maker
+// webhooks, or any URL that accepts a JSON POST. Targets live in the
+// `webhooks` settings section; routines send to them with the
+// `PushNotification` action and alert rules with `push: true`. A message
+// goes to every enabled target unless the caller names some.
+
+use crate::error::{AstralError, AstralResult, ErrorCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashSet;
+use std::time::Duration;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    /// https://ntfy.sh/<topic> or a self-hosted server; `token` for protected topics
+    Ntfy,
+    /// An incoming webhook URL from a Slack app
+    Slack,
+    /// https://maker.ifttt.com/trigger/<event>/with/key/<key>; the title and
+    /// message arrive as value1 and value2
+    Ifttt,
+    /// `{"title", "message", "source"}` as JSON, with `token` as a bearer token
+    Generic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookTarget {
+    pub name: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    pub kind: WebhookKind,
+    pub url: String,
+    #[serde(default)]
+    pub token: String,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// The `webhooks` settings section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub targets: Vec<WebhookTarget>,
+}
+
+impl WebhookSettings {
+    pub(crate) fn validate(&mut self) -> Result<(), String> {
+        let mut names = HashSet::new();
+        for target in &mut self.targets {
+            target.name = target.name.trim().to_string();
+            target.url = target.url.trim().to_string();
+            target.token = target.token.trim().to_string();
+            if target.name.is_empty() {
+                return Err("Every webhook needs a name".to_string());
+            }
+            if !names.insert(target.name.to_lowercase()) {
+                return Err(format!("More than one webhook is named {}", target.name));
+            }
+            if target.enabled && !(target.url.starts_with("https://") || target.url.starts_with("http://")) {
+                return Err(format!("The {} webhook needs an http:// or https:// URL", target.name));
+            }
+            if target.enabled && target.kind == WebhookKind::Ntfy && ntfy_endpoint(&target.url).is_none() {
+                return Err(format!("The {} webhook URL needs a topic, like https://ntfy.sh/<topic>", target.name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The server root and topic of an ntfy URL. ntfy takes JSON at the root
+/// with the topic inside, which keeps titles intact that a Title header
+/// couldn't carry without RFC 2047 encoding.
+fn ntfy_endpoint(url: &str) -> Option<(String, String)> {
+    let mut url = reqwest::Url::parse(url).ok()?;
+    let topic = url.path_segments()?.filter(|s| !s.is_empty()).last()?.to_string();
+    url.path_segments_mut().ok()?.pop_if_empty().pop();
+    url.set_query(None);
+    Some((url.to_string(), topic))
+}
+
+fn build_request(target: &WebhookTarget, title: &str, message: &str) -> AstralResult<reqwest::RequestBuilder> {
+    let client = crate::proxy::client();
+    let request = match target.kind {
+        WebhookKind::Ntfy => {
+            let (server, topic) = ntfy_endpoint(&target.url)
+                .ok_or_else(|| AstralError::invalid_input(format!("The {} webhook URL has no ntfy topic", target.name)))?;
+            client.post(server).json(&json!({ "topic": topic, "title": title, "message": message }))
+        }
+        WebhookKind::Slack => client.post(&target.url).json(&json!({ "text": format!("*{}*\n{}", title, message) })),
+        WebhookKind::Ifttt => client
+            .post(&target.url)
+            .json(&json!({ "value1": title, "value2": message, "value3": "ASTRAL" })),
+        WebhookKind::Generic => client
+            .post(&target.url)
+            .json(&json!({ "title": title, "message": message, "source": "astral" })),
+    };
+    let request = if target.token.is_empty() { request } else { request.bearer_auth(&target.token) };
+    Ok(request.timeout(Duration::from_secs(10)))
+}
+
+async fn deliver(target: &WebhookTarget, title: &str, message: &str) -> AstralResult<()> {
+    let response = crate::privacy::send(build_request(target, title, message)?)
+        .await
+        .map_err(|e| AstralError::from(e.context(format!("Couldn't reach the {} webhook", target.name))))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AstralError::from_status(&target.name, status, &body));
+    }
+    Ok(())
+}
+
+/// Send a notification to the named targets, or every enabled one when
+/// `targets` is empty. Succeeds if at least one delivery did, and returns how many.
+pub async fn send(app: &AppHandle, title: &str, message: &str, targets: &[String]) -> AstralResult<usize> {
+    let settings = crate::settings::load_settings(app.clone()).await?.webhooks;
+    let chosen: Vec<WebhookTarget> = if targets.is_empty() {
+        settings.targets.into_iter().filter(|t| t.enabled).collect()
+    } else {
+        let mut chosen = Vec::new();
+        for name in targets {
+            let target = settings
+                .targets
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| AstralError::new(ErrorCode::NotFound, format!("There's no webhook named {}", name)))?;
+            if target.enabled {
+                chosen.push(target.clone());
+            }
+        }
+        chosen
+    };
+    if chosen.is_empty() {
+        return Err(AstralError::not_configured("No webhooks are turned on; add one in Settings"));
+    }
+
+    let results = futures::future::join_all(chosen.iter().map(|target| deliver(target, title, message))).await;
+    let mut delivered = 0;
+    let mut first_error = None;
+    for (target, result) in chosen.iter().zip(results) {
+        match result {
+            Ok(()) => delivered += 1,
+            Err(e) => {
+                warn!("Webhook {} failed: {}", target.name, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    info!("Sent \"{}\" to {} of {} webhooks", title, delivered, chosen.len());
+    match first_error {
+        Some(e) if delivered == 0 => Err(e),
+        _ => Ok(delivered),
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn test_webhook(app: AppHandle, name: String) -> AstralResult<()> {
+    send(&app, "ASTRAL", "Test notification from ASTRAL", &[name]).await.map(|_| ())
+}
@@ -0,0 +1,539 @@
+// Window Layout Module
+// Monitor enumeration and moving app windows between monitors or snapping
+// them to half the screen ("put the browser on my second monitor")
+
+use anyhow::{bail, Result};
+use tracing::info;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Monitor {
+    /// 1-based, primary first, then left to right
+    pub index: usize,
+    pub name: String,
+    pub primary: bool,
+    pub bounds: Rect,
+    /// Bounds minus the taskbar / dock / panels
+    pub work_area: Rect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowPosition {
+    Left,
+    Right,
+    Maximized,
+    Centered,
+}
+
+impl WindowPosition {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "left" | "left_half" => Some(Self::Left),
+            "right" | "right_half" => Some(Self::Right),
+            "max" | "maximize" | "maximized" | "full" | "fullscreen" => Some(Self::Maximized),
+            "center" | "centre" | "centered" | "middle" => Some(Self::Centered),
+            _ => None,
+        }
+    }
+
+    /// Target rectangle inside a monitor's work area
+    fn within(&self, area: &Rect) -> Rect {
+        match self {
+            Self::Left => Rect { width: area.width / 2, ..area.clone() },
+            Self::Right => Rect {
+                x: area.x + area.width / 2,
+                width: area.width - area.width / 2,
+                ..area.clone()
+            },
+            Self::Maximized => area.clone(),
+            Self::Centered => Rect {
+                x: area.x + area.width / 6,
+                y: area.y + area.height / 6,
+                width: area.width * 2 / 3,
+                height: area.height * 2 / 3,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorTarget {
+    /// 1-based monitor index
+    Index(usize),
+    /// "the other monitor": the one after the window's current monitor
+    Next,
+    /// Stay on the current monitor
+    Current,
+}
+
+/// A spoken placement request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacementCommand {
+    pub app_name: String,
+    pub monitor: MonitorTarget,
+    pub position: WindowPosition,
+}
+
+impl PlacementCommand {
+    /// "put the browser on my second monitor", "snap chrome to the left",
+    /// "move spotify to the other screen"
+    pub fn from_command(command: &str) -> Option<Self> {
+        let lower = command.to_lowercase();
+        let lower = lower.trim().trim_end_matches(|c: char| c.is_ascii_punctuation());
+
+        let rest = ["put ", "move ", "snap ", "send ", "throw "]
+            .iter()
+            .find_map(|verb| lower.strip_prefix(verb))?;
+        let (app, target) = [" on ", " to ", " onto "]
+            .iter()
+            .find_map(|sep| rest.split_once(sep))?;
+
+        let mentions_monitor = ["monitor", "screen", "display"].iter().any(|w| target.contains(w));
+        let monitor = if !mentions_monitor {
+            MonitorTarget::Current
+        } else if target.contains("other") || target.contains("next") {
+            MonitorTarget::Next
+        } else if target.contains("primary") || target.contains("main") {
+            MonitorTarget::Index(1)
+        } else {
+            let ordinals = [("first", 1), ("second", 2), ("third", 3), ("fourth", 4), ("1", 1), ("2", 2), ("3", 3), ("4", 4)];
+            let index = target
+                .split(|c: char| !c.is_alphanumeric())
+                .find_map(|word| ordinals.iter().find(|(w, _)| *w == word).map(|(_, i)| *i))?;
+            MonitorTarget::Index(index)
+        };
+
+        let position = if target.contains("left") {
+            WindowPosition::Left
+        } else if target.contains("right") {
+            WindowPosition::Right
+        } else if target.contains("center") || target.contains("middle") {
+            WindowPosition::Centered
+        } else if mentions_monitor || target.contains("full") || target.contains("max") {
+            WindowPosition::Maximized
+        } else {
+            return None;
+        };
+
+        let app_name = app
+            .trim_start_matches("the ")
+            .trim_start_matches("my ")
+            .trim_end_matches(" window")
+            .trim()
+            .to_string();
+        if app_name.is_empty() || ["it", "this", "that"].contains(&app_name.as_str()) {
+            return None;
+        }
+
+        Some(Self { app_name, monitor, position })
+    }
+
+    pub fn describe(&self) -> String {
+        let position = match self.position {
+            WindowPosition::Left => "the left half",
+            WindowPosition::Right => "the right half",
+            WindowPosition::Maximized => "full screen",
+            WindowPosition::Centered => "the center",
+        };
+        match self.monitor {
+            MonitorTarget::Index(index) => format!("Moved {} to {} of monitor {}", self.app_name, position, index),
+            MonitorTarget::Next => format!("Moved {} to {} of the next monitor", self.app_name, position),
+            MonitorTarget::Current => format!("Moved {} to {}", self.app_name, position),
+        }
+    }
+}
+
+/// Primary first, then left to right, numbered from 1
+fn ordered(mut monitors: Vec<Monitor>) -> Vec<Monitor> {
+    monitors.sort_by_key(|m| (!m.primary, m.bounds.x, m.bounds.y));
+    for (i, monitor) in monitors.iter_mut().enumerate() {
+        monitor.index = i + 1;
+    }
+    monitors
+}
+
+/// Blocking.
+pub fn list_monitors() -> Result<Vec<Monitor>> {
+    Ok(ordered(platform::monitors()?))
+}
+
+fn center_of(rect: &Rect) -> (i32, i32) {
+    (rect.x + rect.width / 2, rect.y + rect.height / 2)
+}
+
+fn contains(rect: &Rect, (x, y): (i32, i32)) -> bool {
+    x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
+}
+
+/// Move the main window of `app_name`. Blocking.
+pub fn place_window(app_name: &str, monitor: MonitorTarget, position: WindowPosition) -> Result<()> {
+    let pids: Vec<u32> = crate::processes::find_by_name(app_name).iter().map(|p| p.pid).collect();
+    if pids.is_empty() {
+        bail!("{} isn't running", app_name);
+    }
+    let window = platform::find_window(&pids)?;
+
+    let monitors = list_monitors()?;
+    if monitors.is_empty() {
+        bail!("No monitors found");
+    }
+    let current = platform::window_rect(&window)
+        .ok()
+        .and_then(|rect| monitors.iter().position(|m| contains(&m.bounds, center_of(&rect))))
+        .unwrap_or(0);
+
+    let target = match monitor {
+        MonitorTarget::Index(index) => monitors
+            .get(index.saturating_sub(1))
+            .ok_or_else(|| anyhow::anyhow!("There's no monitor {} (found {})", index, monitors.len()))?,
+        MonitorTarget::Next => &monitors[(current + 1) % monitors.len()],
+        MonitorTarget::Current => &monitors[current],
+    };
+
+    info!("Moving {} to {:?} on monitor {}", app_name, position, target.index);
+    platform::move_window(&window, &position.within(&target.work_area), position == WindowPosition::Maximized)
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{Monitor, Rect};
+    use anyhow::{bail, Result};
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindow, GetWindowRect, GetWindowTextLengthW, GetWindowThreadProcessId, IsWindowVisible,
+        SetForegroundWindow, SetWindowPos, ShowWindow, GW_OWNER, MONITORINFOF_PRIMARY, SWP_NOZORDER, SWP_SHOWWINDOW,
+        SW_MAXIMIZE, SW_RESTORE,
+    };
+
+    pub type Window = HWND;
+
+    fn to_rect(rect: &RECT) -> Rect {
+        Rect {
+            x: rect.left,
+            y: rect.top,
+            width: rect.right - rect.left,
+            height: rect.bottom - rect.top,
+        }
+    }
+
+    unsafe extern "system" fn collect_monitor(monitor: HMONITOR, _: HDC, _: *mut RECT, data: LPARAM) -> BOOL {
+        let monitors = &mut *(data.0 as *mut Vec<HMONITOR>);
+        monitors.push(monitor);
+        true.into()
+    }
+
+    pub fn monitors() -> Result<Vec<Monitor>> {
+        let mut handles: Vec<HMONITOR> = Vec::new();
+        let mut monitors = Vec::new();
+
+        unsafe {
+            let _ = EnumDisplayMonitors(HDC(0), None, Some(collect_monitor), LPARAM(&mut handles as *mut _ as isize));
+
+            for handle in handles {
+                let mut info = MONITORINFOEXW::default();
+                info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+                if !GetMonitorInfoW(handle, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).as_bool() {
+                    continue;
+                }
+
+                let device = info.szDevice;
+                let len = device.iter().position(|c| *c == 0).unwrap_or(device.len());
+                monitors.push(Monitor {
+                    index: 0,
+                    // "\\.\DISPLAY1" -> "DISPLAY1"
+                    name: String::from_utf16_lossy(&device[..len]).trim_start_matches(r"\\.\").to_string(),
+                    primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+                    bounds: to_rect(&info.monitorInfo.rcMonitor),
+                    work_area: to_rect(&info.monitorInfo.rcWork),
+                });
+            }
+        }
+
+        Ok(monitors)
+    }
+
+    struct Search<'a> {
+        pids: &'a [u32],
+        found: Option<HWND>,
+    }
+
+    unsafe extern "system" fn match_window(hwnd: HWND, data: LPARAM) -> BOOL {
+        let search = &mut *(data.0 as *mut Search);
+
+        // Top-level, visible, titled windows that aren't dialogs owned by another window
+        if !IsWindowVisible(hwnd).as_bool() || GetWindowTextLengthW(hwnd) == 0 || GetWindow(hwnd, GW_OWNER).0 != 0 {
+            return true.into();
+        }
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if search.pids.contains(&pid) {
+            search.found = Some(hwnd);
+            return false.into();
+        }
+        true.into()
+    }
+
+    pub fn find_window(pids: &[u32]) -> Result<Window> {
+        let mut search = Search { pids, found: None };
+        unsafe {
+            // Stopping the enumeration early is reported as an error
+            let _ = EnumWindows(Some(match_window), LPARAM(&mut search as *mut _ as isize));
+        }
+        match search.found {
+            Some(hwnd) => Ok(hwnd),
+            None => bail!("That app has no open window"),
+        }
+    }
+
+    pub fn window_rect(window: &Window) -> Result<Rect> {
+        let mut rect = RECT::default();
+        unsafe { GetWindowRect(*window, &mut rect)? };
+        Ok(to_rect(&rect))
+    }
+
+    pub fn move_window(window: &Window, rect: &Rect, maximize: bool) -> Result<()> {
+        unsafe {
+            // A maximized window ignores SetWindowPos until it's restored
+            ShowWindow(*window, SW_RESTORE);
+            SetWindowPos(*window, HWND(0), rect.x, rect.y, rect.width, rect.height, SWP_NOZORDER | SWP_SHOWWINDOW)?;
+            if maximize {
+                ShowWindow(*window, SW_MAXIMIZE);
+            }
+            SetForegroundWindow(*window);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{Monitor, Rect};
+    use anyhow::{bail, Context, Result};
+    use std::process::Command;
+
+    /// Process name, as System Events knows it
+    pub type Window = String;
+
+    // NSScreen through JXA; Cocoa frames have a bottom-left origin, flipped
+    // here to the top-left coordinates System Events uses
+    const SCREENS_SCRIPT: &str = r#"ObjC.import('AppKit');
+var screens = $.NSScreen.screens.js;
+var top = screens[0].frame.size.height;
+JSON.stringify(screens.map(function (s, i) {
+  var f = s.frame, v = s.visibleFrame;
+  return {
+    name: ObjC.unwrap(s.localizedName) || ('Display ' + (i + 1)),
+    primary: i === 0,
+    bounds: [f.origin.x, top - f.origin.y - f.size.height, f.size.width, f.size.height],
+    work: [v.origin.x, top - v.origin.y - v.size.height, v.size.width, v.size.height]
+  };
+}));"#;
+
+    #[derive(serde::Deserialize)]
+    struct Screen {
+        name: String,
+        primary: bool,
+        bounds: [f64; 4],
+        work: [f64; 4],
+    }
+
+    fn rect(values: [f64; 4]) -> Rect {
+        Rect {
+            x: values[0] as i32,
+            y: values[1] as i32,
+            width: values[2] as i32,
+            height: values[3] as i32,
+        }
+    }
+
+    fn osascript(args: &[&str]) -> Result<String> {
+        let output = Command::new("osascript").args(args).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    pub fn monitors() -> Result<Vec<Monitor>> {
+        let json = osascript(&["-l", "JavaScript", "-e", SCREENS_SCRIPT])?;
+        let screens: Vec<Screen> = serde_json::from_str(&json).context("Unexpected screen list")?;
+        Ok(screens
+            .into_iter()
+            .map(|s| Monitor {
+                index: 0,
+                name: s.name,
+                primary: s.primary,
+                bounds: rect(s.bounds),
+                work_area: rect(s.work),
+            })
+            .collect())
+    }
+
+    pub fn find_window(pids: &[u32]) -> Result<Window> {
+        let pid = pids.first().context("That app isn't running")?;
+        osascript(&[
+            "-e",
+            &format!("tell application \"System Events\" to get name of first process whose unix id is {}", pid),
+        ])
+    }
+
+    pub fn window_rect(window: &Window) -> Result<Rect> {
+        let text = osascript(&[
+            "-e",
+            &format!(
+                "tell application \"System Events\" to tell process \"{}\" to get {{position, size}} of window 1",
+                window
+            ),
+        ])?;
+        let values: Vec<f64> = text.split(',').filter_map(|v| v.trim().parse().ok()).collect();
+        if values.len() != 4 {
+            bail!("Unexpected window bounds: {}", text);
+        }
+        Ok(rect([values[0], values[1], values[2], values[3]]))
+    }
+
+    // Moving other apps' windows needs the Accessibility permission
+    pub fn move_window(window: &Window, rect: &Rect, _maximize: bool) -> Result<()> {
+        osascript(&[
+            "-e",
+            &format!(
+                "tell application \"System Events\" to tell process \"{}\"\nset frontmost to true\nset position of window 1 to {{{}, {}}}\nset size of window 1 to {{{}, {}}}\nend tell",
+                window, rect.x, rect.y, rect.width, rect.height
+            ),
+        ])
+        .map(|_| ())
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::{Monitor, Rect};
+    use anyhow::{bail, Context, Result};
+    use std::process::Command;
+
+    /// X11 window id, as wmctrl prints it
+    pub type Window = String;
+
+    // X11 only: xrandr for monitors and wmctrl for windows. Wayland compositors
+    // don't let clients move other apps' windows.
+    fn run(program: &str, args: &[&str]) -> Result<String> {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .with_context(|| format!("{} is required for window placement", program))?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// "1920/344x1080/193+0+0" -> Rect
+    fn parse_geometry(geometry: &str) -> Option<Rect> {
+        let (size, offset) = geometry.split_once('+')?;
+        let (width, height) = size.split_once('x')?;
+        let (x, y) = offset.split_once('+')?;
+        Some(Rect {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+            width: width.split('/').next()?.parse().ok()?,
+            height: height.split('/').next()?.parse().ok()?,
+        })
+    }
+
+    pub fn monitors() -> Result<Vec<Monitor>> {
+        // " 0: +*eDP-1 1920/344x1080/193+0+0  eDP-1"
+        Ok(run("xrandr", &["--listmonitors"])?
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let bounds = parse_geometry(fields.get(2)?)?;
+                Some(Monitor {
+                    index: 0,
+                    name: fields.last()?.to_string(),
+                    primary: fields.get(1)?.contains('*'),
+                    work_area: bounds.clone(),
+                    bounds,
+                })
+            })
+            .collect())
+    }
+
+    pub fn find_window(pids: &[u32]) -> Result<Window> {
+        // "0x03e00003  0 12345  host Title"
+        run("wmctrl", &["-lp"])?
+            .lines()
+            .find_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let pid: u32 = fields.get(2)?.parse().ok()?;
+                pids.contains(&pid).then(|| fields[0].to_string())
+            })
+            .context("That app has no open window")
+    }
+
+    pub fn window_rect(window: &Window) -> Result<Rect> {
+        // "0x03e00003  0 100 200 800 600 host Title"
+        run("wmctrl", &["-lG"])?
+            .lines()
+            .find_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.first() != Some(&window.as_str()) {
+                    return None;
+                }
+                Some(Rect {
+                    x: fields.get(2)?.parse().ok()?,
+                    y: fields.get(3)?.parse().ok()?,
+                    width: fields.get(4)?.parse().ok()?,
+                    height: fields.get(5)?.parse().ok()?,
+                })
+            })
+            .context("Window not found")
+    }
+
+    pub fn move_window(window: &Window, rect: &Rect, maximize: bool) -> Result<()> {
+        run("wmctrl", &["-i", "-r", window, "-b", "remove,maximized_vert,maximized_horz"])?;
+        let geometry = format!("0,{},{},{},{}", rect.x, rect.y, rect.width, rect.height);
+        run("wmctrl", &["-i", "-r", window, "-e", &geometry])?;
+        if maximize {
+            run("wmctrl", &["-i", "-r", window, "-b", "add,maximized_vert,maximized_horz"])?;
+        }
+        run("wmctrl", &["-i", "-a", window]).map(|_| ())
+    }
+}
+
+// ========== Tauri Commands ==========
+
+#[tauri::command]
+pub async fn get_monitors() -> Result<Vec<Monitor>, String> {
+    tokio::task::spawn_blocking(list_monitors)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Move an app's window. `monitor` is 1-based and defaults to the window's current
+/// monitor; `position` is "left", "right", "maximized" (default) or "centered".
+#[tauri::command]
+pub async fn move_app_window(app_name: String, monitor: Option<usize>, position: Option<String>) -> Result<(), String> {
+    let position = match position {
+        Some(p) => WindowPosition::parse(&p).ok_or_else(|| format!("Unknown window position: {}", p))?,
+        None => WindowPosition::Maximized,
+    };
+    let monitor = monitor.map(MonitorTarget::Index).unwrap_or(MonitorTarget::Current);
+
+    tokio::task::spawn_blocking(move || place_window(&app_name, monitor, position))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
@@ -3,6 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import HolographicNode from "./components/HolographicNode";
 import Dashboard from "./components/Dashboard";
 import SystemTray from "./components/SystemTray";
+import { describeError, errorCode } from "./errors";
 
 function App() {
     const [isListening, setIsListening] = useState(false);
@@ -12,8 +13,22 @@ function App() {
     const [commandHistory, setCommandHistory] = useState<string[]>([]);
     const recognitionRef = useRef<any>(null);
     const synthRef = useRef<SpeechSynthesis | null>(null);
+    const currentAudioRef = useRef<HTMLAudioElement | null>(null);
     const mediaRecorderRef = useRef<MediaRecorder | null>(null);
     const audioChunksRef = useRef<Blob[]>([]);
+    // Set when the backend asked a yes/no question, so the next reply goes back to it
+    const awaitingAnswerRef = useRef(false);
+    // Mirrors the backend dictation toggle; the session flag records whether the
+    // current recording started in dictation, so stopping it still types the text
+    const dictationRef = useRef(false);
+    const dictationSessionRef = useRef(false);
+    const listeningControlsRef = useRef<{ start: () => void; stop: () => void } | null>(null);
+    // `general.locale`, for the browser's speech recognition and synthesis
+    const localeRef = useRef('en-US');
+    // `general.privacy_mode`: no cloud services, including the browser's online recognizer and voices
+    const privacyRef = useRef(false);
+    // The active persona's browser voice, by name
+    const browserVoiceRef = useRef('');
     const [useWhisper, setUseWhisper] = useState(false);
     const [audioLevel, setAudioLevel] = useState(0);
     const [wakeWordActive, setWakeWordActive] = useState(false);
@@ -23,42 +38,134 @@ function App() {
     const wakeWordStreamRef = useRef<MediaStream | null>(null);
     const wakeWordRecorderRef = useRef<MediaRecorder | null>(null);
     const wakeWordIntervalRef = useRef<number | null>(null);
+    // Set while the backend runs a whole voice interaction (`voice_interaction`)
+    const interactionRef = useRef(false);
+
+    // Global dictation hotkey: record while on, type each transcript into the focused app
+    useEffect(() => {
+        let unlisten: (() => void) | null = null;
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            unlisten = await listen<boolean>('dictation-toggled', (event) => {
+                dictationRef.current = event.payload;
+                if (event.payload) {
+                    listeningControlsRef.current?.start();
+                } else {
+                    listeningControlsRef.current?.stop();
+                }
+            });
+        });
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    // Push-to-talk hotkey: record while it's held
+    useEffect(() => {
+        let unlisten: (() => void) | null = null;
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            unlisten = await listen<boolean>('push-to-talk', (event) => {
+                if (event.payload) {
+                    listeningControlsRef.current?.start();
+                } else {
+                    listeningControlsRef.current?.stop();
+                }
+            });
+        });
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    // "Stop speaking" hotkey and tray item
+    useEffect(() => {
+        let unlisten: (() => void) | null = null;
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            unlisten = await listen('stop-speaking', () => {
+                currentAudioRef.current?.pause();
+                currentAudioRef.current = null;
+                synthRef.current?.cancel();
+                setAssistantState('idle');
+            });
+        });
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    // Theme to draw with; "auto" in settings is resolved against the OS by the backend
+    useEffect(() => {
+        const applyTheme = (theme: string) => { document.documentElement.dataset.theme = theme; };
+        invoke<string>("get_effective_theme").then(applyTheme).catch(() => applyTheme('dark'));
+
+        let unlisten: (() => void) | null = null;
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            unlisten = await listen<string>('theme-changed', (event) => applyTheme(event.payload));
+        });
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    // Speech the backend asks for on its own (alerts, routines)
+    useEffect(() => {
+        let unlisten: (() => void) | null = null;
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            unlisten = await listen<string>('assistant-speak', (event) => speak(event.payload));
+        });
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    // Files dropped onto the window get summarized
+    useEffect(() => {
+        let unlisten: (() => void) | null = null;
+        import('@tauri-apps/api/webview').then(async ({ getCurrentWebview }) => {
+            unlisten = await getCurrentWebview().onDragDropEvent(async (event) => {
+                if (event.payload.type !== 'drop' || event.payload.paths.length === 0) return;
+
+                const path = event.payload.paths[0];
+                const name = path.split(/[\\/]/).pop() ?? path;
+                setTranscript(`Summarize ${name}`);
+                setAssistantState('thinking');
+                try {
+                    const summary: string = await invoke("summarize_file", { path });
+                    speak(summary);
+                } catch (error) {
+                    speak(`I couldn't read ${name}. ${describeError(error)}`);
+                }
+            });
+        });
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    // Mirror the UI state into the backend voice state machine
+    useEffect(() => {
+        if (assistantState === 'idle') {
+            invoke('voice_settle').catch(console.error);
+            return;
+        }
+        const voiceState = { listening: 'Recording', thinking: 'Processing', speaking: 'Speaking' }[assistantState];
+        invoke('voice_set_state', { state: voiceState }).catch(console.error);
+    }, [assistantState]);
 
     // Load settings on startup
     useEffect(() => {
         const loadSettings = async () => {
             try {
+                // The backend configures its engines from these itself
                 const settings: any = await invoke('load_settings');
                 console.log('Loaded settings:', settings);
-                setUseWhisper(settings.whisper_enabled);
-                
-                // Apply other settings (TTS, LLM, etc.)
-                if (settings.elevenlabs_enabled && settings.elevenlabs_api_key) {
-                    await invoke('elevenlabs_update_config', {
-                        config: {
-                            api_key: settings.elevenlabs_api_key,
-                            voice_id: settings.elevenlabs_voice_id,
-                            model_id: settings.elevenlabs_model_id,
-                            enabled: settings.elevenlabs_enabled
-                        }
-                    });
-                }
-                
-                if (settings.whisper_enabled) {
-                    await invoke('whisper_update_config', {
-                        config: {
-                            server_url: settings.whisper_server_url,
-                            model: settings.whisper_model,
-                            enabled: settings.whisper_enabled
-                        }
-                    });
-                }
+                setUseWhisper(settings.voice.whisper.enabled);
+                localeRef.current = settings.general.locale;
+                privacyRef.current = settings.general.privacy_mode;
+                const persona = settings.personas?.list?.find((p: any) => p.id === settings.personas.active);
+                browserVoiceRef.current = persona?.browser_voice ?? '';
             } catch (error) {
                 console.error('Failed to load settings:', error);
             }
         };
         
         loadSettings();
+
+        // Pick up changes made elsewhere (dashboard, tray, API)
+        let unlisten: (() => void) | null = null;
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            unlisten = await listen<string[]>('settings-changed', (event) => {
+                if (['voice', 'general', 'personas'].some(section => event.payload.includes(section))) loadSettings();
+            });
+        });
+        return () => { if (unlisten) unlisten(); };
     }, []);
 
     // Wake word continuous audio capture
@@ -252,7 +359,7 @@ function App() {
             recognitionRef.current = new SpeechRecognition();
             recognitionRef.current.continuous = false;
             recognitionRef.current.interimResults = true;
-            recognitionRef.current.lang = 'en-US';
+            recognitionRef.current.lang = localeRef.current;
 
             recognitionRef.current.onstart = () => {
                 setAssistantState('listening');
@@ -272,7 +379,7 @@ function App() {
                 if (event.results[event.results.length - 1].isFinal) {
                     console.log('Final transcript, processing:', currentTranscript);
                     setIsListening(false);
-                    processCommand(currentTranscript);
+                    handleTranscript(currentTranscript);
                 }
             };
 
@@ -330,7 +437,48 @@ function App() {
         setShowDashboard(!showDashboard);
     };
 
+    // Whisper path outside dictation: the backend records until the user stops
+    // talking, transcribes, answers and synthesizes the reply in one command
+    const runVoiceInteraction = async () => {
+        interactionRef.current = true;
+        setAssistantState('listening');
+        setIsListening(true);
+        try {
+            const result: { transcript: string; reply: string; audio: number[] | null } = await invoke('voice_interaction');
+            setIsListening(false);
+            if (!result.transcript) {
+                setAssistantState('idle');
+                return;
+            }
+            setTranscript(result.transcript);
+            setCommandHistory(prev => [result.transcript, ...prev.slice(0, 4)]);
+            awaitingAnswerRef.current = result.reply.trim().endsWith('?');
+            if (result.audio) {
+                playAudio(result.audio, result.reply);
+            } else if (result.reply) {
+                speakWithBrowser(result.reply);
+            } else {
+                setAssistantState('idle');
+            }
+        } catch (error) {
+            setIsListening(false);
+            if (errorCode(error) === 'cancelled' || errorCode(error) === 'timeout') {
+                setAssistantState('idle');
+            } else {
+                speak(describeError(error, localeRef.current.split('-')[0]));
+            }
+        } finally {
+            interactionRef.current = false;
+        }
+    };
+
     const startListening = async () => {
+        // Pressing again during a backend interaction calls it off
+        if (interactionRef.current) {
+            invoke('cancel_voice_interaction').catch(console.error);
+            return;
+        }
+
         // If already listening with Whisper, stop recording
         if (isListening && useWhisper && mediaRecorderRef.current) {
             console.log("Stopping Whisper recording...");
@@ -350,6 +498,12 @@ function App() {
         if (isListening) return;
         
         setTranscript("");
+        dictationSessionRef.current = dictationRef.current;
+        
+        if (useWhisper && !dictationSessionRef.current) {
+            runVoiceInteraction();
+            return;
+        }
         
         // Use Whisper if available
         if (useWhisper) {
@@ -418,7 +572,7 @@ function App() {
                         }
                         
                         setTranscript(transcript);
-                        processCommand(transcript);
+                        handleTranscript(transcript);
                     } catch (error) {
                         console.error("Whisper transcription failed:", error);
                         setAssistantState('idle');
@@ -435,10 +589,14 @@ function App() {
                 console.error('Error starting Whisper recording:', error);
                 setAssistantState('idle');
             }
+        } else if (privacyRef.current) {
+            // The browser recognizer sends audio to the cloud
+            speak("Privacy mode is on, so I can only listen through local Whisper. Turn Whisper on in Settings.");
         } else {
             // Use browser speech recognition
             if (recognitionRef.current && !isListening) {
                 try {
+                    recognitionRef.current.lang = localeRef.current;
                     recognitionRef.current.start();
                 } catch (error) {
                     console.error('Error starting recognition:', error);
@@ -448,6 +606,10 @@ function App() {
     };
 
     const stopListening = () => {
+        if (interactionRef.current) {
+            // The backend stops recording on its own once the user goes quiet
+            return;
+        }
         if (useWhisper && mediaRecorderRef.current && isListening) {
             console.log("Stopping Whisper recording...");
             mediaRecorderRef.current.stop();
@@ -457,6 +619,26 @@ function App() {
         }
     };
 
+    listeningControlsRef.current = { start: startListening, stop: stopListening };
+
+    const handleTranscript = async (text: string) => {
+        if (!dictationSessionRef.current) {
+            processCommand(text);
+            return;
+        }
+
+        try {
+            await invoke("type_dictation", { text });
+        } catch (error) {
+            console.error("Dictation typing failed:", error);
+        }
+        setAssistantState('idle');
+        // Keep dictating until the hotkey is pressed again
+        if (dictationRef.current) {
+            listeningControlsRef.current?.start();
+        }
+    };
+
     // Helper function to convert AudioBuffer to WAV
     const audioBufferToWav = async (audioBuffer: AudioBuffer): Promise<Blob> => {
         const numberOfChannels = audioBuffer.numberOfChannels;
@@ -512,29 +694,11 @@ function App() {
             // Try ElevenLabs first
             const config: any = await invoke("elevenlabs_get_config");
             
-            if (config.enabled) {
+            if (config.enabled && !privacyRef.current) {
                 console.log('Using ElevenLabs TTS');
                 const audioBytes: number[] = await invoke("elevenlabs_speak", { text });
                 console.log('ElevenLabs generated audio:', audioBytes.length, 'bytes');
-                
-                // Convert bytes to Blob and create URL
-                const audioBlob = new Blob([new Uint8Array(audioBytes)], { type: 'audio/mpeg' });
-                const audioUrl = URL.createObjectURL(audioBlob);
-                
-                // Play the audio
-                const audio = new Audio(audioUrl);
-                audio.onended = () => {
-                    console.log('ElevenLabs speech ended');
-                    URL.revokeObjectURL(audioUrl); // Clean up
-                    setAssistantState('idle');
-                };
-                audio.onerror = (e) => {
-                    console.error('ElevenLabs audio playback error:', e);
-                    URL.revokeObjectURL(audioUrl); // Clean up
-                    console.log('Falling back to browser TTS');
-                    speakWithBrowser(text);
-                };
-                await audio.play();
+                await playAudio(audioBytes, text);
                 return;
             }
         } catch (error) {
@@ -545,9 +709,39 @@ function App() {
         speakWithBrowser(text);
     };
     
+    // Play ElevenLabs audio, falling back to the browser voice if playback fails
+    const playAudio = async (audioBytes: number[], text: string) => {
+        setAssistantState('speaking');
+        const audioBlob = new Blob([new Uint8Array(audioBytes)], { type: 'audio/mpeg' });
+        const audioUrl = URL.createObjectURL(audioBlob);
+        
+        const audio = new Audio(audioUrl);
+        currentAudioRef.current = audio;
+        audio.onended = () => {
+            console.log('ElevenLabs speech ended');
+            URL.revokeObjectURL(audioUrl); // Clean up
+            setAssistantState('idle');
+        };
+        audio.onerror = (e) => {
+            console.error('ElevenLabs audio playback error:', e);
+            URL.revokeObjectURL(audioUrl); // Clean up
+            console.log('Falling back to browser TTS');
+            speakWithBrowser(text);
+        };
+        await audio.play();
+    };
+    
     const speakWithBrowser = (text: string) => {
         if (synthRef.current) {
             const utterance = new SpeechSynthesisUtterance(text);
+            utterance.lang = localeRef.current;
+            const language = localeRef.current.split('-')[0];
+            // Online voices are out in privacy mode
+            const voices = synthRef.current.getVoices().filter(v => !privacyRef.current || v.localService);
+            const voice = voices.find(v => browserVoiceRef.current !== '' && v.name === browserVoiceRef.current)
+                ?? voices.find(v => v.lang === localeRef.current)
+                ?? voices.find(v => v.lang.split('-')[0] === language);
+            if (voice) utterance.voice = voice;
             utterance.rate = 1.0;
             utterance.pitch = 1.0;
             utterance.volume = 1.0;
@@ -579,9 +773,25 @@ function App() {
         const lowerCommand = command.toLowerCase();
         let response = "";
 
+        // Commands handled by the native pipeline (execute_command)
+        const runNative = async (): Promise<string> => {
+            const reply: string = await invoke("execute_command", { command });
+            awaitingAnswerRef.current = reply.trim().endsWith('?');
+            return reply;
+        };
+
         try {
+            // Answer to a question the backend asked ("Are you sure you want to shut down?")
+            if (awaitingAnswerRef.current) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    awaitingAnswerRef.current = false;
+                    response = `Something went wrong. ${error}`;
+                }
+            }
             // Greetings
-            if (lowerCommand === 'hello' || lowerCommand === 'hi' || lowerCommand === 'hey' || lowerCommand.startsWith('hello.') || lowerCommand.startsWith('hi.')) {
+            else if (lowerCommand === 'hello' || lowerCommand === 'hi' || lowerCommand === 'hey' || lowerCommand.startsWith('hello.') || lowerCommand.startsWith('hi.')) {
                 const greetings = [
                     "Hello! How can I assist you today?",
                     "Hi there! What can I do for you?",
@@ -637,6 +847,195 @@ function App() {
             else if (lowerCommand.includes('weather')) {
                 response = "Weather integration coming soon. I'll be able to tell you the current weather conditions.";
             }
+            // App name corrections ("when I say browser I mean Firefox")
+            else if (/^when i say .+ i mean .+/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't remember that. ${error}`;
+                }
+            }
+            // Now playing
+            else if (/\b(what('s| is) (this song|playing)|what song is (this|playing)|who('s| is)? sing(s|ing) this|name of this song|what am i listening to)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't tell what's playing. ${error}`;
+                }
+            }
+            // System services: "restart the print spooler service"
+            else if (/^(start|stop|restart) (the )?.+ service\.?$|^(start|stop|restart) service /.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't control that service. ${error}`;
+                }
+            }
+            // Media playback (handled natively via media keys)
+            else if (/\b(pause|resume|unpause|next (track|song)|previous (track|song)|skip (this )?(song|track)|stop (the )?music)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't control playback. ${error}`;
+                }
+            }
+            // Opening an earlier file search result
+            else if (/^open (it|that|that file|this file|the file|(the )?(first|second|third|fourth|fifth) (one|result|file|match)|(result|number) \d+)\.?$/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't open that. ${error}`;
+                }
+            }
+            // File search
+            else if (/^(find (the file|file|my|the)|search for|look for|where is my|where's my) /.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't search for files. ${error}`;
+                }
+            }
+            // Questions about what's on screen (OCR + LLM)
+            else if (/\bon (the |my )?screen\b/.test(lowerCommand) && /\b(read|what|explain|says|error|summarize)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't read the screen. ${error}`;
+                }
+            }
+            // "Look at my screen": a screenshot for a vision model (GPT-4o, Claude, llava)
+            else if (/\b(look at|see) (the |my )?screen\b/.test(lowerCommand)) {
+                try {
+                    const llmResponse: any = await invoke("send_llm_message_with_images", { message: command, imagePaths: [], screenshot: true });
+                    response = llmResponse.content;
+                } catch (error) {
+                    if (errorCode(error) === 'cancelled') {
+                        setAssistantState('idle');
+                        return;
+                    }
+                    response = `I couldn't look at the screen. ${describeError(error)}`;
+                }
+            }
+            // Questions about the file selected in Explorer/Finder (text extraction + LLM)
+            else if (/\b(this|selected) (document|file|pdf)\b|\bthe selection\b/.test(lowerCommand) && /\b(what|summarize|summary|explain|read|tell me)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't read that file. ${error}`;
+                }
+            }
+            // Power: lock, sleep, shut down, restart (and cancelling a scheduled one)
+            else if (/\b(lock (my|the) (computer|pc|screen)|go to sleep|sleep (mode|in \d+)|put (the|my) computer to sleep|shut ?down|turn off (the|my) computer|power off|restart (the|my) (computer|pc)|reboot)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't do that. ${error}`;
+                }
+            }
+            // Health report: "how's my system doing?"
+            else if (/\b(system|computer|pc) doing\b|\b(system health|health report|system report|health check)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't put together a system report. ${error}`;
+                }
+            }
+            // Uptime: "how long has my PC been on?", "do I need to restart?"
+            else if (/\buptime\b|\bhow long\b.*\bbeen (on|running|up)\b|\blast (reboot|restart)\b|\bneed to (restart|reboot)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't check the uptime. ${error}`;
+                }
+            }
+            // Heavy processes: "what's slowing down my PC?", "what's using my memory"
+            else if (/\b(slowing (down )?my|so slow|running slow|(top|heaviest) processes|what('s| is) using|hogging)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't check the running processes. ${error}`;
+                }
+            }
+            // Closing apps ("kill Chrome")
+            else if (/^(force (quit|close)|kill|end task|close|quit) (?!(it|this|that|the window|the dashboard|settings|dashboard)$).+/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't close that. ${error}`;
+                }
+            }
+            // Wi-Fi profiles: "switch to my hotspot", "connect to HomeNet wifi"
+            else if (/\b(connect( me)? to|switch to|join|use)\b.*\b(wi-?fi|network|hotspot)\b|\b(list|known|saved|which) .*networks?\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't change the Wi-Fi network. ${error}`;
+                }
+            }
+            // Network status
+            else if (/\b(vpn|my ip|ip address|wi-?fi|internet connection|am i (online|connected)|network status)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't check the network. ${error}`;
+                }
+            }
+            // Battery and power plans
+            else if (/\bbattery\b/.test(lowerCommand) || /\b(power (plan|mode)|high performance|performance mode|power saver|balanced mode)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't check that. ${error}`;
+                }
+            }
+            // Window placement
+            else if (/^(put|move|snap|send|throw) .+ (on|to|onto) .*(monitor|screen|display|left|right|center|middle|full)/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't move that window. ${error}`;
+                }
+            }
+            // Recycle bin and disk cleanup
+            else if (/\b(recycle bin|trash|free up|disk cleanup|clean up|temp files|cache)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't clean up. ${error}`;
+                }
+            }
+            // Focus Assist / Do Not Disturb
+            else if (/\b(focus assist|do not disturb|focus mode)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't change Focus Assist. ${error}`;
+                }
+            }
+            // Screen brightness
+            else if (/\b(brightness|brighter|dimmer|dim (the|my) screen)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't change the brightness. ${error}`;
+                }
+            }
+            // Dark mode and night light
+            else if (/\b(dark|light) (mode|theme)\b|\bnight (light|shift)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't change the theme. ${error}`;
+                }
+            }
+            // Speaker volume and microphone mute
+            else if (/\b(volume|louder|quieter|unmute|mute)\b/.test(lowerCommand)) {
+                try {
+                    response = await runNative();
+                } catch (error) {
+                    response = `I couldn't change the volume. ${error}`;
+                }
+            }
             // Open/Launch applications
             else if (lowerCommand.includes('open') || lowerCommand.includes('launch') || lowerCommand.includes('start')) {
                 // Extract app name
@@ -680,7 +1079,13 @@ function App() {
                     const llmResponse: any = await invoke("send_llm_message", { message: command });
                     response = llmResponse.content;
                 } catch (error) {
-                    response = "I'm having trouble connecting to my AI brain right now. Make sure Ollama is running with 'ollama serve' or configure an API key in settings.";
+                    if (errorCode(error) === 'cancelled') {
+                        setAssistantState('idle');
+                        return;
+                    }
+                    response = errorCode(error) === 'internal'
+                        ? "I'm having trouble connecting to my AI brain right now. Make sure Ollama is running with 'ollama serve' or configure an API key in settings."
+                        : describeError(error);
                 }
             }
             // Default - route everything else to AI for creative interpretation
@@ -689,6 +1094,10 @@ function App() {
                     const llmResponse: any = await invoke("send_llm_message", { message: command });
                     response = llmResponse.content;
                 } catch (error) {
+                    if (errorCode(error) === 'cancelled') {
+                        setAssistantState('idle');
+                        return;
+                    }
                     // Only if AI completely fails, give a fallback
                     response = "Hmm, I'm having a bit of trouble processing that. Could you rephrase, or perhaps try asking about the time, system stats, or say 'help' for options?";
                 }
@@ -741,6 +1150,14 @@ function App() {
                                     'bg-cyber-pink animate-pulse'
                         }`} />
                     <span className="text-sm font-medium capitalize">{assistantState}</span>
+                    {assistantState === 'thinking' && (
+                        <button
+                            className="text-xs text-white/60 hover:text-white border border-white/20 rounded px-2 py-0.5"
+                            onClick={() => invoke('cancel_llm_request').catch(console.error)}
+                        >
+                            Stop
+                        </button>
+                    )}
                     {wakeWordActive && (
                         <div className="ml-auto flex items-center gap-2 text-xs text-cyber-cyan">
                             <div className="w-1.5 h-1.5 rounded-full bg-cyber-cyan animate-pulse" />
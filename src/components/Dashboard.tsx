@@ -1,35 +1,159 @@
 import { motion, AnimatePresence } from 'framer-motion';
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useRef } from 'react';
+import { describeError } from '../errors';
 
 interface DashboardProps {
     onClose: () => void;
 }
 
+interface CoreStats {
+    index: number;
+    usage: number;
+    frequency_mhz: number;
+}
+
+interface DiskStats {
+    name: string;
+    total: number;
+    free: number;
+    removable: boolean;
+}
+
+interface TemperatureReading {
+    label: string;
+    kind: 'cpu' | 'gpu' | 'disk' | 'other';
+    celsius: number;
+    critical: number | null;
+}
+
+interface FanReading {
+    label: string;
+    rpm: number;
+}
+
+interface StatsSample {
+    timestamp: number;
+    cpu_usage: number;
+    memory_usage: number;
+}
+
+interface BatteryHealth {
+    health_percent: number | null;
+    discharge_rate_mw: number | null;
+    cycle_count: number | null;
+    seconds_remaining: number | null;
+}
+
+interface OverviewStats {
+    cpu: number;
+    memory: number;
+    memoryUsed: number;
+    memoryTotal: number;
+    gpu: number;
+    cores: CoreStats[];
+    disks: DiskStats[];
+    temperatures: TemperatureReading[];
+    fans: FanReading[];
+    history: StatsSample[];
+    battery: BatteryHealth | null;
+}
+
 export default function Dashboard({ onClose }: DashboardProps) {
-    const [activeTab, setActiveTab] = useState<'overview' | 'automation' | 'settings'>('overview');
-    const [systemStats, setSystemStats] = useState({ cpu: 0, memory: 0, memoryUsed: 0, memoryTotal: 0, gpu: 0 });
+    const [activeTab, setActiveTab] = useState<'overview' | 'automation' | 'notes' | 'settings'>('overview');
+    const [systemStats, setSystemStats] = useState<OverviewStats>({ cpu: 0, memory: 0, memoryUsed: 0, memoryTotal: 0, gpu: 0, cores: [], disks: [], temperatures: [], fans: [], history: [], battery: null });
 
     useEffect(() => {
-        // Update system stats every 2 seconds
+        const applyStats = (stats: any) => {
+            setSystemStats(prev => ({
+                ...prev,
+                cpu: Math.round(stats.cpu_usage),
+                memory: Math.round(stats.memory_usage),
+                memoryUsed: Math.round(stats.memory_used / (1024 * 1024 * 1024)), // Convert to GB
+                memoryTotal: Math.round(stats.memory_total / (1024 * 1024 * 1024)), // Convert to GB
+                gpu: stats.gpu_usage ? Math.round(stats.gpu_usage) : 0,
+                cores: stats.cores ?? []
+            }));
+        };
+
+        // One fetch so the panel isn't empty until the first push
         const updateStats = async () => {
             try {
                 const { invoke } = await import('@tauri-apps/api/core');
-                const stats: any = await invoke('get_system_stats_command');
-                setSystemStats({
-                    cpu: Math.round(stats.cpu_usage),
-                    memory: Math.round(stats.memory_usage),
-                    memoryUsed: Math.round(stats.memory_used / (1024 * 1024 * 1024)), // Convert to GB
-                    memoryTotal: Math.round(stats.memory_total / (1024 * 1024 * 1024)), // Convert to GB
-                    gpu: stats.gpu_usage ? Math.round(stats.gpu_usage) : 0
-                });
+                applyStats(await invoke('get_system_stats_command'));
             } catch (error) {
                 console.error('Failed to get system stats:', error);
             }
         };
 
+        // After that the backend pushes stats while the window is visible
+        let unlisten: (() => void) | null = null;
+        import('@tauri-apps/api/event').then(async ({ listen }) => {
+            unlisten = await listen('system-stats', (event) => applyStats(event.payload));
+        });
+
+        // Free space changes slowly; no need to poll it with the CPU
+        const updateDisks = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const disks: DiskStats[] = await invoke('get_disk_stats');
+                setSystemStats(prev => ({ ...prev, disks }));
+            } catch (error) {
+                console.error('Failed to get disk stats:', error);
+            }
+        };
+
+        // Reading sensors can spawn helper tools, so poll them less often too
+        const updateTemperatures = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const [temperatures, fans] = await Promise.all([
+                    invoke<TemperatureReading[]>('get_temperatures'),
+                    invoke<FanReading[]>('get_fan_speeds'),
+                ]);
+                setSystemStats(prev => ({ ...prev, temperatures, fans }));
+            } catch (error) {
+                console.error('Failed to get temperatures and fans:', error);
+            }
+        };
+
+        // The backend samples every 5 seconds; chart the last 10 minutes at 15s resolution
+        const updateHistory = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const history: StatsSample[] = await invoke('get_stats_history', { range: 600, resolution: 15 });
+                setSystemStats(prev => ({ ...prev, history }));
+            } catch (error) {
+                console.error('Failed to get stats history:', error);
+            }
+        };
+
+        // Desktops have no battery; the command errors and the card stays hidden
+        const updateBattery = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const battery: BatteryHealth = await invoke('get_battery_health');
+                setSystemStats(prev => ({ ...prev, battery }));
+            } catch {
+                setSystemStats(prev => ({ ...prev, battery: null }));
+            }
+        };
+
         updateStats(); // Initial load
-        const interval = setInterval(updateStats, 2000);
-        return () => clearInterval(interval);
+        updateDisks();
+        updateTemperatures();
+        updateHistory();
+        updateBattery();
+        const diskInterval = setInterval(updateDisks, 30000);
+        const temperatureInterval = setInterval(updateTemperatures, 10000);
+        const historyInterval = setInterval(updateHistory, 15000);
+        const batteryInterval = setInterval(updateBattery, 30000);
+        return () => {
+            clearInterval(batteryInterval);
+            if (unlisten) unlisten();
+            clearInterval(historyInterval);
+            clearInterval(diskInterval);
+            clearInterval(temperatureInterval);
+        };
     }, []);
 
     return (
@@ -55,7 +179,7 @@ export default function Dashboard({ onClose }: DashboardProps) {
 
                     {/* Tabs */}
                     <div className="flex gap-2 mt-4">
-                        {(['overview', 'automation', 'settings'] as const).map((tab) => (
+                        {(['overview', 'automation', 'notes', 'settings'] as const).map((tab) => (
                             <button
                                 key={tab}
                                 onClick={() => setActiveTab(tab)}
@@ -74,6 +198,7 @@ export default function Dashboard({ onClose }: DashboardProps) {
             <div className="p-6 h-[calc(100%-140px)] overflow-y-auto custom-scrollbar">
                 {activeTab === 'overview' && <OverviewTab stats={systemStats} />}
                 {activeTab === 'automation' && <AutomationTab />}
+                {activeTab === 'notes' && <NotesTab />}
                 {activeTab === 'settings' && <SettingsTab />}
             </div>
             </motion.div>
@@ -81,7 +206,24 @@ export default function Dashboard({ onClose }: DashboardProps) {
     );
 }
 
-function OverviewTab({ stats }: { stats: { cpu: number; memory: number; memoryUsed: number; memoryTotal: number; gpu: number } }) {
+function Sparkline({ label, values, stroke }: { label: string; values: number[]; stroke: string }) {
+    const points = values
+        .map((value, i) => `${(i / (values.length - 1)) * 100},${30 - (Math.min(value, 100) / 100) * 30}`)
+        .join(' ');
+    return (
+        <div className="mb-2">
+            <div className="flex justify-between text-xs text-white/50 mb-1">
+                <span>{label}</span>
+                <span>peak {Math.round(Math.max(...values))}%</span>
+            </div>
+            <svg viewBox="0 0 100 30" preserveAspectRatio="none" className="w-full h-8">
+                <polyline points={points} fill="none" stroke={stroke} strokeWidth="1.5" vectorEffect="non-scaling-stroke" />
+            </svg>
+        </div>
+    );
+}
+
+function OverviewTab({ stats }: { stats: OverviewStats }) {
     return (
         <div className="space-y-4">
             <div className="glass p-4 rounded-lg">
@@ -93,6 +235,100 @@ function OverviewTab({ stats }: { stats: { cpu: number; memory: number; memoryUs
                 </div>
             </div>
 
+            {stats.history.length > 1 && (
+                <div className="glass p-4 rounded-lg">
+                    <h3 className="text-sm font-semibold text-white/60 mb-3">Last 10 Minutes</h3>
+                    <Sparkline label="CPU" values={stats.history.map(s => s.cpu_usage)} stroke="#00f0ff" />
+                    <Sparkline label="Memory" values={stats.history.map(s => s.memory_usage)} stroke="#b24bf3" />
+                </div>
+            )}
+
+            {stats.cores.length > 0 && (
+                <div className="glass p-4 rounded-lg">
+                    <h3 className="text-sm font-semibold text-white/60 mb-3">CPU Cores</h3>
+                    <div className="grid grid-cols-4 gap-2">
+                        {stats.cores.map(core => (
+                            <div
+                                key={core.index}
+                                className="bg-white/5 rounded p-2 text-center"
+                                title={`Core ${core.index}: ${Math.round(core.usage)}% at ${core.frequency_mhz} MHz`}
+                            >
+                                <div className="text-[10px] text-white/50">#{core.index}</div>
+                                <div className={`text-sm font-semibold ${core.usage >= 95 ? 'text-red-400' : 'text-cyber-cyan'}`}>
+                                    {Math.round(core.usage)}%
+                                </div>
+                                <div className="text-[10px] text-white/40">{(core.frequency_mhz / 1000).toFixed(1)} GHz</div>
+                            </div>
+                        ))}
+                    </div>
+                </div>
+            )}
+
+            {stats.disks.length > 0 && (
+                <div className="glass p-4 rounded-lg">
+                    <h3 className="text-sm font-semibold text-white/60 mb-3">Drives</h3>
+                    <div className="space-y-2">
+                        {stats.disks.map(disk => {
+                            const gb = (bytes: number) => Math.round(bytes / (1024 * 1024 * 1024));
+                            const usedPercent = disk.total > 0 ? Math.round((disk.total - disk.free) / disk.total * 100) : 0;
+                            return (
+                                <StatusItem
+                                    key={disk.name}
+                                    label={disk.name}
+                                    value={`${gb(disk.free)} GB free of ${gb(disk.total)} GB`}
+                                    color="cyan"
+                                    percent={usedPercent}
+                                />
+                            );
+                        })}
+                    </div>
+                </div>
+            )}
+
+            {(stats.temperatures.length > 0 || stats.fans.length > 0) && (
+                <div className="glass p-4 rounded-lg">
+                    <h3 className="text-sm font-semibold text-white/60 mb-3">Cooling</h3>
+                    <div className="space-y-1 text-sm">
+                        {stats.temperatures
+                            .filter(t => t.kind === 'cpu' || t.kind === 'gpu')
+                            .map(t => (
+                                <div key={`${t.kind}-${t.label}`} className="flex justify-between">
+                                    <span className="text-white/80 truncate mr-2">{t.label}</span>
+                                    <span className={t.celsius >= (t.critical ?? 90) ? 'text-red-400' : 'text-cyber-cyan'}>
+                                        {Math.round(t.celsius)}°C
+                                    </span>
+                                </div>
+                            ))}
+                        {stats.fans.map(fan => (
+                            <div key={`fan-${fan.label}`} className="flex justify-between">
+                                <span className="text-white/80 truncate mr-2">{fan.label}</span>
+                                <span className={fan.rpm === 0 ? 'text-white/40' : 'text-cyber-purple'}>
+                                    {fan.rpm === 0 ? 'stopped' : `${fan.rpm} RPM`}
+                                </span>
+                            </div>
+                        ))}
+                    </div>
+                </div>
+            )}
+
+            {stats.battery && (
+                <div className="glass p-4 rounded-lg">
+                    <h3 className="text-sm font-semibold text-white/60 mb-3">Battery</h3>
+                    <div className="space-y-2">
+                        {stats.battery.health_percent !== null && (
+                            <StatusItem label="Health" value={`${Math.round(stats.battery.health_percent)}%`} color="cyan" percent={stats.battery.health_percent} />
+                        )}
+                        <div className="flex justify-between text-sm text-white/80">
+                            <span>{stats.battery.cycle_count !== null ? `${stats.battery.cycle_count} cycles` : ''}</span>
+                            <span>
+                                {stats.battery.discharge_rate_mw !== null && `${(stats.battery.discharge_rate_mw / 1000).toFixed(1)} W`}
+                                {stats.battery.seconds_remaining !== null && ` · ${Math.floor(stats.battery.seconds_remaining / 3600)}h ${Math.floor((stats.battery.seconds_remaining % 3600) / 60)}m left`}
+                            </span>
+                        </div>
+                    </div>
+                </div>
+            )}
+
             <div className="glass p-4 rounded-lg">
                 <h3 className="text-sm font-semibold text-white/60 mb-3">Recent Commands</h3>
                 <div className="space-y-2 text-sm">
@@ -205,24 +441,231 @@ function AutomationTab() {
     );
 }
 
+interface ListItem {
+    id: string;
+    text: string;
+    done: boolean;
+    added_at: string;
+}
+
+interface NotesStore {
+    lists: Record<string, ListItem[]>;
+    notes: { id: string; text: string; created_at: string }[];
+}
+
+function NotesTab() {
+    const [store, setStore] = useState<NotesStore>({ lists: {}, notes: [] });
+    const [drafts, setDrafts] = useState<Record<string, string>>({});
+    const [newList, setNewList] = useState('');
+    const [noteDraft, setNoteDraft] = useState('');
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const { listen } = await import('@tauri-apps/api/event');
+                setStore(await invoke('get_notes'));
+                // Voice commands and MCP clients change the notes too
+                unlisten = await listen<NotesStore>('notes-changed', (event) => setStore(event.payload));
+            } catch (error) {
+                console.error('Failed to load notes:', error);
+            }
+        };
+        load();
+        return () => unlisten?.();
+    }, []);
+
+    const call = async (command: string, args: Record<string, unknown>) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            return await invoke(command, args);
+        } catch (error) {
+            setMessage(String(error));
+        }
+    };
+
+    const addItem = async (list: string) => {
+        const text = (drafts[list] ?? '').trim();
+        if (!text) return;
+        await call('add_list_item', { list, text });
+        setDrafts({ ...drafts, [list]: '' });
+    };
+
+    const exportNotes = async () => {
+        const path = await call('export_notes', {});
+        if (path) setMessage(`Exported to ${path}`);
+    };
+
+    const lists = Object.keys(store.lists);
+
+    return (
+        <div className="space-y-4">
+            {lists.map(list => (
+                <div key={list} className="glass p-4 rounded-lg">
+                    <div className="flex items-center justify-between mb-3">
+                        <h3 className="text-sm font-semibold text-white/60 capitalize">{list === 'todo' ? 'To-do' : list} list</h3>
+                        <button className="text-xs text-white/40 hover:text-white/70" onClick={() => call('delete_list', { list })}>
+                            Delete list
+                        </button>
+                    </div>
+                    <div className="space-y-1">
+                        {store.lists[list].map(item => (
+                            <div key={item.id} className="flex items-center gap-2 text-sm">
+                                <input
+                                    type="checkbox"
+                                    checked={item.done}
+                                    onChange={() => call('set_list_item_done', { list, id: item.id, done: !item.done })}
+                                />
+                                <span className={`flex-1 ${item.done ? 'line-through text-white/40' : ''}`}>{item.text}</span>
+                                <button className="text-xs text-white/40 hover:text-white/70" onClick={() => call('remove_list_item', { list, id: item.id })}>
+                                    ✕
+                                </button>
+                            </div>
+                        ))}
+                    </div>
+                    <div className="flex gap-2 mt-2">
+                        <input
+                            type="text"
+                            className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                            value={drafts[list] ?? ''}
+                            onChange={(e) => setDrafts({ ...drafts, [list]: e.target.value })}
+                            onKeyDown={(e) => e.key === 'Enter' && addItem(list)}
+                            placeholder="Add an item"
+                        />
+                    </div>
+                </div>
+            ))}
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">New List</h3>
+                <div className="flex gap-2">
+                    <input
+                        type="text"
+                        className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                        value={newList}
+                        onChange={(e) => setNewList(e.target.value)}
+                        placeholder="List name, e.g. shopping"
+                    />
+                    <input
+                        type="text"
+                        className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                        value={drafts[''] ?? ''}
+                        onChange={(e) => setDrafts({ ...drafts, '': e.target.value })}
+                        placeholder="First item"
+                    />
+                    <button
+                        className="cyber-button text-xs"
+                        disabled={!newList.trim() || !(drafts[''] ?? '').trim()}
+                        onClick={async () => {
+                            await call('add_list_item', { list: newList, text: drafts[''] });
+                            setNewList('');
+                            setDrafts({ ...drafts, '': '' });
+                        }}
+                    >
+                        Create
+                    </button>
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Notes</h3>
+                <div className="space-y-2">
+                    {store.notes.slice().reverse().map(note => (
+                        <div key={note.id} className="flex items-start gap-2 text-sm bg-white/5 rounded p-2">
+                            <div className="flex-1">
+                                <div>{note.text}</div>
+                                <div className="text-xs text-white/40">{new Date(note.created_at).toLocaleString()}</div>
+                            </div>
+                            <button className="text-xs text-white/40 hover:text-white/70" onClick={() => call('remove_note', { id: note.id })}>
+                                ✕
+                            </button>
+                        </div>
+                    ))}
+                    <input
+                        type="text"
+                        className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                        value={noteDraft}
+                        onChange={(e) => setNoteDraft(e.target.value)}
+                        onKeyDown={async (e) => {
+                            if (e.key === 'Enter' && noteDraft.trim()) {
+                                await call('create_note', { text: noteDraft });
+                                setNoteDraft('');
+                            }
+                        }}
+                        placeholder="Write a note and press Enter"
+                    />
+                </div>
+            </div>
+
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <button className="cyber-button text-xs" onClick={exportNotes}>
+                Export as Markdown
+            </button>
+
+            <div className="text-xs text-white/40 text-center">
+                Say "add milk to my shopping list", "what's on my list?" or "take a note, call the dentist"
+            </div>
+        </div>
+    );
+}
+
 function SettingsTab() {
     const [settings, setSettings] = useState({
-        localProcessing: true,
         cloudBackup: false,
         usageAnalytics: false
     });
-    const [llmProvider, setLlmProvider] = useState('Ollama');
-    const [showApiKey, setShowApiKey] = useState(false);
     const [ttsEnabled, setTtsEnabled] = useState(false);
     const [ttsApiKey, setTtsApiKey] = useState('');
     const [ttsVoice, setTtsVoice] = useState('21m00Tcm4TlvDq8ikWAM');
     const [voices, setVoices] = useState<any[]>([]);
+    const [languageVoices, setLanguageVoices] = useState<Record<string, string>>({});
+    const [locale, setLocale] = useState('en-US');
+    const [languages, setLanguages] = useState<{ code: string; name: string }[]>([]);
 
     useEffect(() => {
         loadTTSConfig();
         loadVoices();
+        loadLanguages();
     }, []);
 
+    const loadLanguages = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setLanguages(await invoke('get_supported_languages'));
+            const settings: any = await invoke('load_settings');
+            setLocale(settings.general.locale);
+        } catch (error) {
+            console.error('Failed to load languages:', error);
+        }
+    };
+
+    const changeLocale = async (newLocale: string) => {
+        setLocale(newLocale);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: 'general.locale', value: newLocale });
+        } catch (error) {
+            console.error('Failed to update language:', error);
+        }
+    };
+
+    const changeLanguageVoice = async (voiceId: string) => {
+        const next = { ...languageVoices };
+        if (voiceId) next[language] = voiceId; else delete next[language];
+        setLanguageVoices(next);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: 'tts.voices_by_language', value: next });
+        } catch (error) {
+            console.error('Failed to update voice for language:', error);
+        }
+    };
+
+    const language = locale.split('-')[0];
+    const languageName = languages.find(l => l.code === language)?.name ?? language;
+
     const loadTTSConfig = async () => {
         try {
             const { invoke } = await import('@tauri-apps/api/core');
@@ -230,6 +673,7 @@ function SettingsTab() {
             setTtsEnabled(config.enabled);
             setTtsApiKey(config.api_key);
             setTtsVoice(config.voice_id);
+            setLanguageVoices(config.voices_by_language ?? {});
         } catch (error) {
             console.error('Failed to load TTS config:', error);
         }
@@ -249,144 +693,211 @@ function SettingsTab() {
         setSettings(prev => ({ ...prev, [key]: !prev[key] }));
     };
 
-    const testLlmConnection = async () => {
-        try {
-            const { invoke } = await import('@tauri-apps/api/core');
-            const config = {
-                provider: llmProvider,
-                api_key: null,
-                model: llmProvider === 'OpenAI' ? 'gpt-4' : llmProvider === 'Claude' ? 'claude-3-sonnet-20240229' : 'llama2',
-                temperature: 0.7,
-                max_tokens: 500,
-                ollama_url: 'http://localhost:11434'
-            };
-            const result = await invoke('test_llm_connection', { config });
-            alert(result ? 'Connection successful!' : 'Connection failed - check your settings');
-        } catch (error) {
-            alert('Connection test failed: ' + error);
-        }
-    };
-
     return (
         <div className="space-y-4">
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Profiles</h3>
+                <div className="space-y-3">
+                    <ProfileManager onSwitch={loadTTSConfig} />
+                </div>
+            </div>
+
             <div className="glass p-4 rounded-lg">
                 <h3 className="text-sm font-semibold text-white/60 mb-3">AI Provider</h3>
                 <div className="space-y-3">
-                    <div>
-                        <label className="text-xs text-white/50 block mb-1">LLM Provider</label>
-                        <select 
-                            className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
-                            value={llmProvider}
-                            onChange={(e) => setLlmProvider(e.target.value)}
-                        >
-                            <option value="Ollama">Ollama (Local)</option>
-                            <option value="OpenAI">OpenAI GPT-4</option>
-                            <option value="Claude">Anthropic Claude</option>
-                        </select>
-                    </div>
-                    {llmProvider !== 'Ollama' && (
-                        <div>
-                            <label className="text-xs text-white/50 block mb-1">API Key</label>
-                            <input 
-                                type={showApiKey ? 'text' : 'password'}
-                                className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
-                                placeholder="sk-..."
-                            />
-                            <button 
-                                className="text-xs text-cyber-cyan mt-1"
-                                onClick={() => setShowApiKey(!showApiKey)}
-                            >
-                                {showApiKey ? 'Hide' : 'Show'}
-                            </button>
-                        </div>
-                    )}
-                    {llmProvider === 'Ollama' && (
-                        <div className="text-xs text-white/50 bg-cyber-cyan/10 border border-cyber-cyan/30 rounded p-2">
-                            Make sure Ollama is running: <code className="text-cyber-cyan">ollama serve</code>
-                        </div>
-                    )}
-                    <button className="w-full cyber-button text-sm" onClick={testLlmConnection}>
-                        Test Connection
-                    </button>
+                    <LlmSettings />
                 </div>
             </div>
 
             <div className="glass p-4 rounded-lg">
-                <h3 className="text-sm font-semibold text-white/60 mb-3">Speech-to-Text (Whisper)</h3>
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Personas</h3>
                 <div className="space-y-3">
-                    <WhisperSettings />
+                    <PersonaSettings />
                 </div>
             </div>
 
             <div className="glass p-4 rounded-lg">
-                <h3 className="text-sm font-semibold text-white/60 mb-3">Text-to-Speech (ElevenLabs)</h3>
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Usage &amp; Spending</h3>
                 <div className="space-y-3">
-                    <ToggleItem 
-                        label="Use ElevenLabs (Natural Voice)" 
-                        enabled={ttsEnabled}
-                        onToggle={async () => {
-                            const newState = !ttsEnabled;
-                            setTtsEnabled(newState);
-                            try {
-                                const { invoke } = await import('@tauri-apps/api/core');
-                                await invoke('elevenlabs_update_config', {
-                                    config: {
-                                        api_key: ttsApiKey,
-                                        voice_id: ttsVoice,
-                                        model_id: 'eleven_turbo_v2_5',
-                                        enabled: newState
-                                    }
-                                });
-                                console.log('ElevenLabs', newState ? 'enabled' : 'disabled');
-                            } catch (error) {
-                                console.error('Failed to update TTS config:', error);
-                            }
-                        }}
-                    />
-                    
-                    <div>
-                        <label className="text-xs text-white/50 block mb-1">API Key</label>
-                        <input 
-                            type="password"
-                            className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
-                            placeholder="sk_..."
-                            value={ttsApiKey}
-                            onChange={async (e) => {
-                                const newKey = e.target.value;
-                                setTtsApiKey(newKey);
-                                try {
-                                    const { invoke } = await import('@tauri-apps/api/core');
-                                    await invoke('elevenlabs_update_config', {
-                                        config: {
-                                            api_key: newKey,
-                                            voice_id: ttsVoice,
-                                            model_id: 'eleven_turbo_v2_5',
-                                            enabled: ttsEnabled
-                                        }
-                                    });
-                                } catch (error) {
-                                    console.error('Failed to update API key:', error);
-                                }
-                            }}
-                        />
+                    <UsageSettings />
+                    <ResponseCacheSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Language</h3>
+                <div className="space-y-3">
+                    <select
+                        className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                        value={language}
+                        onChange={(e) => changeLocale(e.target.value)}
+                    >
+                        {languages.map(l => (
+                            <option key={l.code} value={l.code}>{l.name}</option>
+                        ))}
+                    </select>
+                    <div className="text-xs text-white/50">
+                        ASTRAL listens, answers and speaks in this language. Whisper needs a multilingual model (not *.en) for languages other than English.
                     </div>
-                    
-                    <div>
-                        <label className="text-xs text-white/50 block mb-1">Voice</label>
-                        <select 
-                            className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
-                            value={ttsVoice}
-                            onChange={async (e) => {
-                                const newVoice = e.target.value;
-                                setTtsVoice(newVoice);
-                                try {
-                                    const { invoke } = await import('@tauri-apps/api/core');
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Web Search</h3>
+                <div className="space-y-3">
+                    <WebSearchSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Calendar</h3>
+                <div className="space-y-3">
+                    <CalendarSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Email</h3>
+                <div className="space-y-3">
+                    <EmailSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Home Assistant</h3>
+                <div className="space-y-3">
+                    <HomeAssistantSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">MQTT</h3>
+                <div className="space-y-3">
+                    <MqttSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Proxy</h3>
+                <div className="space-y-3">
+                    <ProxySettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Philips Hue</h3>
+                <div className="space-y-3">
+                    <HueSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Chat Bridge</h3>
+                <div className="space-y-3">
+                    <ChatBridgeSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Knowledge Base</h3>
+                <div className="space-y-3">
+                    <KnowledgeBaseSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Web Actions</h3>
+                <div className="space-y-3">
+                    <WebActionsSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Push Notifications</h3>
+                <div className="space-y-3">
+                    <WebhookSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Speech-to-Text (Whisper)</h3>
+                <div className="space-y-3">
+                    <WhisperSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Text-to-Speech (ElevenLabs)</h3>
+                <div className="space-y-3">
+                    <ToggleItem 
+                        label="Use ElevenLabs (Natural Voice)" 
+                        enabled={ttsEnabled}
+                        onToggle={async () => {
+                            const newState = !ttsEnabled;
+                            setTtsEnabled(newState);
+                            try {
+                                const { invoke } = await import('@tauri-apps/api/core');
+                                await invoke('elevenlabs_update_config', {
+                                    config: {
+                                        api_key: ttsApiKey,
+                                        voice_id: ttsVoice,
+                                        model_id: 'eleven_turbo_v2_5',
+                                        enabled: newState,
+                                        voices_by_language: languageVoices
+                                    }
+                                });
+                                console.log('ElevenLabs', newState ? 'enabled' : 'disabled');
+                            } catch (error) {
+                                console.error('Failed to update TTS config:', error);
+                            }
+                        }}
+                    />
+                    
+                    <div>
+                        <label className="text-xs text-white/50 block mb-1">API Key</label>
+                        <input 
+                            type="password"
+                            className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                            placeholder="sk_..."
+                            value={ttsApiKey}
+                            onChange={async (e) => {
+                                const newKey = e.target.value;
+                                setTtsApiKey(newKey);
+                                try {
+                                    const { invoke } = await import('@tauri-apps/api/core');
+                                    await invoke('elevenlabs_update_config', {
+                                        config: {
+                                            api_key: newKey,
+                                            voice_id: ttsVoice,
+                                            model_id: 'eleven_turbo_v2_5',
+                                            enabled: ttsEnabled,
+                                            voices_by_language: languageVoices
+                                        }
+                                    });
+                                } catch (error) {
+                                    console.error('Failed to update API key:', error);
+                                }
+                            }}
+                        />
+                    </div>
+                    
+                    <div>
+                        <label className="text-xs text-white/50 block mb-1">Voice</label>
+                        <select 
+                            className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                            value={ttsVoice}
+                            onChange={async (e) => {
+                                const newVoice = e.target.value;
+                                setTtsVoice(newVoice);
+                                try {
+                                    const { invoke } = await import('@tauri-apps/api/core');
                                     await invoke('elevenlabs_update_config', {
                                         config: {
                                             api_key: ttsApiKey,
                                             voice_id: newVoice,
                                             model_id: 'eleven_turbo_v2_5',
-                                            enabled: ttsEnabled
+                                            enabled: ttsEnabled,
+                                            voices_by_language: languageVoices
                                         }
                                     });
                                 } catch (error) {
@@ -399,6 +910,22 @@ function SettingsTab() {
                             ))}
                         </select>
                     </div>
+
+                    {language !== 'en' && (
+                        <div>
+                            <label className="text-xs text-white/50 block mb-1">Voice for {languageName}</label>
+                            <select
+                                className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                                value={languageVoices[language] ?? ''}
+                                onChange={(e) => changeLanguageVoice(e.target.value)}
+                            >
+                                <option value="">Same as above</option>
+                                {voices.map(voice => (
+                                    <option key={voice.id} value={voice.id}>{voice.name}</option>
+                                ))}
+                            </select>
+                        </div>
+                    )}
                     
                     <button 
                         className="w-full cyber-button text-sm"
@@ -408,7 +935,7 @@ function SettingsTab() {
                                 const result: string = await invoke('elevenlabs_test');
                                 alert(result);
                             } catch (error) {
-                                alert('Test failed: ' + error);
+                                alert('Test failed: ' + describeError(error));
                             }
                         }}
                     >
@@ -428,14 +955,54 @@ function SettingsTab() {
                 </div>
             </div>
 
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Keyboard Shortcuts</h3>
+                <div className="space-y-3">
+                    <HotkeySettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Updates</h3>
+                <div className="space-y-3">
+                    <UpdateSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Backup</h3>
+                <div className="space-y-3">
+                    <BackupSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Sync</h3>
+                <div className="space-y-3">
+                    <SyncSettings />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Diagnostics</h3>
+                <div className="space-y-3">
+                    <SetupCheck />
+                    <PipelineView />
+                    <PerformanceMetrics />
+                    <LogViewer />
+                    <CrashReports />
+                </div>
+            </div>
+
+            <div className="glass p-4 rounded-lg">
+                <h3 className="text-sm font-semibold text-white/60 mb-3">Permissions</h3>
+                <PermissionSettings />
+            </div>
+
             <div className="glass p-4 rounded-lg">
                 <h3 className="text-sm font-semibold text-white/60 mb-3">Privacy</h3>
                 <div className="space-y-3">
-                    <ToggleItem 
-                        label="Local Processing" 
-                        enabled={settings.localProcessing}
-                        onToggle={() => toggleSetting('localProcessing')}
-                    />
+                    <PrivacyModeToggle />
                     <ToggleItem 
                         label="Cloud Backup" 
                         enabled={settings.cloudBackup}
@@ -666,6 +1233,2969 @@ function WakeWordSettings() {
     );
 }
 
+const HOTKEY_LABELS: Record<string, string> = {
+    toggle_window: 'Show / hide dashboard',
+    push_to_talk: 'Push to talk (hold)',
+    stop_speaking: 'Stop speaking',
+    favorite_routine: 'Run favorite routine',
+    toggle_mute: 'Mute microphone',
+    toggle_dictation: 'Dictation',
+};
+
+interface HotkeyStatus {
+    action: string;
+    shortcut: string;
+    registered: boolean;
+    error: string | null;
+}
+
+function HotkeySettings() {
+    const [bindings, setBindings] = useState<Record<string, string>>({});
+    const [status, setStatus] = useState<HotkeyStatus[]>([]);
+    const [routines, setRoutines] = useState<any[]>([]);
+    const [favorite, setFavorite] = useState('');
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setBindings(settings.general.hotkeys);
+                setFavorite(settings.general.favorite_routine);
+                setStatus(await invoke('get_hotkeys'));
+                setRoutines(await invoke('get_automation_routines'));
+            } catch (error) {
+                console.error('Failed to load hotkeys:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const save = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setStatus(await invoke('set_hotkeys', { bindings }));
+            setError(null);
+        } catch (error) {
+            setError(String(error));
+        }
+    };
+
+    const saveFavorite = async (routineId: string) => {
+        setFavorite(routineId);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: 'general.favorite_routine', value: routineId });
+        } catch (error) {
+            console.error('Failed to save favorite routine:', error);
+        }
+    };
+
+    return (
+        <>
+            {Object.entries(HOTKEY_LABELS).map(([action, label]) => {
+                const current = status.find(s => s.action === action);
+                return (
+                    <div key={action}>
+                        <div className="flex items-center justify-between gap-3">
+                            <span className="text-sm text-white/70">{label}</span>
+                            <input
+                                type="text"
+                                className="w-48 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm font-mono"
+                                value={bindings[action] ?? ''}
+                                onChange={(e) => setBindings(prev => ({ ...prev, [action]: e.target.value }))}
+                                placeholder="Unbound"
+                            />
+                        </div>
+                        {current && !current.registered && (
+                            <div className="text-xs text-red-400 mt-1">{current.error}</div>
+                        )}
+                    </div>
+                );
+            })}
+
+            <div className="flex items-center justify-between gap-3">
+                <span className="text-sm text-white/70">Favorite routine</span>
+                <select
+                    className="w-48 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={favorite}
+                    onChange={(e) => saveFavorite(e.target.value)}
+                >
+                    <option value="">None</option>
+                    {routines.map(routine => (
+                        <option key={routine.id} value={routine.id}>{routine.name}</option>
+                    ))}
+                </select>
+            </div>
+
+            {error && (
+                <div className="text-xs p-2 rounded bg-red-500/10 border border-red-500/30 text-red-400">{error}</div>
+            )}
+
+            <button className="w-full cyber-button text-sm" onClick={save}>
+                Save Shortcuts
+            </button>
+
+            <div className="text-xs text-white/50 bg-cyber-cyan/10 border border-cyber-cyan/30 rounded p-2">
+                ℹ️ Use names like CmdOrCtrl+Shift+M or Alt+F9. Leave a field empty to unbind it.
+            </div>
+        </>
+    );
+}
+
+interface UpdateInfo {
+    version: string;
+    current_version: string;
+    channel: string;
+    date: string | null;
+    notes: string | null;
+}
+
+function UpdateSettings() {
+    const [channel, setChannel] = useState('stable');
+    const [autoCheck, setAutoCheck] = useState(true);
+    const [update, setUpdate] = useState<UpdateInfo | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+    const [busy, setBusy] = useState(false);
+
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const { listen } = await import('@tauri-apps/api/event');
+                const settings: any = await invoke('load_settings');
+                setChannel(settings.general.update_channel);
+                setAutoCheck(settings.general.auto_check_updates);
+                unlisten = await listen<UpdateInfo>('update-available', (event) => setUpdate(event.payload));
+            } catch (error) {
+                console.error('Failed to load update settings:', error);
+            }
+        };
+        load();
+        return () => unlisten?.();
+    }, []);
+
+    const saveSetting = async (key: string, value: any) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key, value });
+        } catch (error) {
+            console.error(`Failed to save ${key}:`, error);
+        }
+    };
+
+    const check = async () => {
+        setBusy(true);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const found: UpdateInfo | null = await invoke('check_for_update');
+            setUpdate(found);
+            setMessage(found ? null : 'ASTRAL is up to date');
+        } catch (error) {
+            setMessage(String(error));
+        }
+        setBusy(false);
+    };
+
+    const install = async () => {
+        setBusy(true);
+        setMessage('Downloading update…');
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('install_update');
+        } catch (error) {
+            setMessage(String(error));
+            setBusy(false);
+        }
+    };
+
+    return (
+        <>
+            <div className="flex items-center justify-between gap-3">
+                <span className="text-sm text-white/70">Release channel</span>
+                <select
+                    className="w-48 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={channel}
+                    onChange={(e) => {
+                        setChannel(e.target.value);
+                        setUpdate(null);
+                        saveSetting('general.update_channel', e.target.value);
+                    }}
+                >
+                    <option value="stable">Stable</option>
+                    <option value="beta">Beta</option>
+                </select>
+            </div>
+
+            <ToggleItem
+                label="Check for updates automatically"
+                enabled={autoCheck}
+                onToggle={() => {
+                    setAutoCheck(!autoCheck);
+                    saveSetting('general.auto_check_updates', !autoCheck);
+                }}
+            />
+
+            {update && (
+                <div className="text-xs text-white/70 bg-cyber-cyan/10 border border-cyber-cyan/30 rounded p-2">
+                    Version {update.version} is available (you have {update.current_version}).
+                    {update.notes && <div className="mt-1 text-white/50 whitespace-pre-line">{update.notes}</div>}
+                </div>
+            )}
+
+            {message && <div className="text-xs text-white/50">{message}</div>}
+
+            <button className="w-full cyber-button text-sm" onClick={update ? install : check} disabled={busy}>
+                {update ? 'Install and Restart' : 'Check for Updates'}
+            </button>
+        </>
+    );
+}
+
+type PipelineStage = 'wake_word' | 'capture' | 'stt' | 'intent' | 'llm' | 'tts';
+
+interface PipelineMessage {
+    interaction: number;
+    timestamp: number;
+    kind: string;
+    text?: string;
+    confidence?: number | null;
+    duration_ms?: number;
+    route?: string;
+    command?: string;
+    model?: string;
+    name?: string;
+    ok?: boolean;
+    stage?: PipelineStage;
+    error?: string;
+}
+
+const PIPELINE_STAGES: { stage: PipelineStage; label: string }[] = [
+    { stage: 'wake_word', label: 'Wake word' },
+    { stage: 'capture', label: 'Listening' },
+    { stage: 'stt', label: 'Speech to text' },
+    { stage: 'intent', label: 'Routing' },
+    { stage: 'llm', label: 'LLM' },
+    { stage: 'tts', label: 'Speech' },
+];
+
+/** Which pipeline stage a bus event belongs to; state changes don't belong to one */
+function stageOf(message: PipelineMessage): PipelineStage | null {
+    switch (message.kind) {
+        case 'wake_word_detected': return 'wake_word';
+        case 'speech_captured': return 'capture';
+        case 'transcribed': return 'stt';
+        case 'intent_routed': return 'intent';
+        case 'llm_responded':
+        case 'tool_called': return 'llm';
+        case 'speech_requested':
+        case 'speech_synthesized': return 'tts';
+        case 'stage_failed': return message.stage ?? null;
+        default: return null;
+    }
+}
+
+function describePipeline(message: PipelineMessage): string {
+    switch (message.kind) {
+        case 'transcribed':
+            return message.confidence != null
+                ? `"${message.text}" (${Math.round(message.confidence * 100)}%)`
+                : `"${message.text}"`;
+        case 'speech_captured': return `${((message.duration_ms ?? 0) / 1000).toFixed(1)} s`;
+        case 'intent_routed': return `${message.route?.replace('_', ' ')}`;
+        case 'llm_responded': return message.model ?? '';
+        case 'tool_called': return `${message.name}${message.ok ? '' : ' (failed)'}`;
+        case 'speech_requested': return `"${message.text}"`;
+        case 'stage_failed': return message.error ?? 'failed';
+        default: return '';
+    }
+}
+
+/** Live view of the current voice interaction, fed by the backend's pipeline bus */
+function PipelineView() {
+    const [interaction, setInteraction] = useState(0);
+    const [stages, setStages] = useState<Partial<Record<PipelineStage, PipelineMessage>>>({});
+    const [voiceState, setVoiceState] = useState('Idle');
+    const interactionRef = useRef(0);
+
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+        const setup = async () => {
+            const { listen } = await import('@tauri-apps/api/event');
+            unlisten = await listen<PipelineMessage & { to?: string }>('voice-pipeline', (event) => {
+                const message = event.payload;
+                if (message.kind === 'state_changed' && message.to) {
+                    setVoiceState(message.to);
+                }
+                const stage = stageOf(message);
+                if (message.interaction !== interactionRef.current) {
+                    // A new interaction starts with an empty pipeline
+                    interactionRef.current = message.interaction;
+                    setInteraction(message.interaction);
+                    setStages(stage ? { [stage]: message } : {});
+                } else if (stage) {
+                    setStages(current => ({ ...current, [stage]: message }));
+                }
+            });
+        };
+        setup();
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    return (
+        <div className="space-y-2">
+            <div className="flex items-center justify-between text-xs text-white/40">
+                <span>Voice pipeline{interaction > 0 ? ` · interaction ${interaction}` : ''}</span>
+                <span className="font-mono">{voiceState}</span>
+            </div>
+            <div className="grid grid-cols-6 gap-1">
+                {PIPELINE_STAGES.map(({ stage, label }) => {
+                    const message = stages[stage];
+                    const color = !message
+                        ? 'bg-white/5 text-white/30'
+                        : message.kind === 'stage_failed'
+                            ? 'bg-red-500/20 text-red-300'
+                            : 'bg-cyber-cyan/20 text-cyber-cyan';
+                    return (
+                        <div key={stage} className={`p-2 rounded text-xs ${color}`} title={message ? describePipeline(message) : undefined}>
+                            <div className="font-semibold">{label}</div>
+                            <div className="truncate text-white/60">{message ? describePipeline(message) : '—'}</div>
+                        </div>
+                    );
+                })}
+            </div>
+        </div>
+    );
+}
+
+interface StageMetrics {
+    stage: string;
+    count: number;
+    last_ms: number | null;
+    avg_ms: number | null;
+    p50_ms: number | null;
+    p95_ms: number | null;
+    max_ms: number | null;
+}
+
+const STAGE_LABELS: Record<string, string> = {
+    stt: 'Speech to text',
+    llm_response: 'LLM response',
+    tts: 'Speech synthesis',
+    command: 'Command handling',
+    voice_round_trip: 'Voice round trip',
+};
+
+function PerformanceMetrics() {
+    const [metrics, setMetrics] = useState<StageMetrics[]>([]);
+
+    const refresh = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setMetrics(await invoke('get_performance_metrics'));
+        } catch (error) {
+            console.error('Failed to load performance metrics:', error);
+        }
+    };
+
+    useEffect(() => {
+        refresh();
+        const interval = setInterval(refresh, 5000);
+        return () => clearInterval(interval);
+    }, []);
+
+    const reset = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('reset_performance_metrics');
+            refresh();
+        } catch (error) {
+            console.error('Failed to reset performance metrics:', error);
+        }
+    };
+
+    const ms = (value: number | null) => (value === null ? '—' : `${value} ms`);
+
+    return (
+        <>
+            <table className="w-full text-xs">
+                <thead>
+                    <tr className="text-white/40 text-left">
+                        <th className="font-normal">Step</th>
+                        <th className="font-normal text-right">Last</th>
+                        <th className="font-normal text-right">Median</th>
+                        <th className="font-normal text-right">p95</th>
+                        <th className="font-normal text-right">Samples</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {metrics.map(m => (
+                        <tr key={m.stage} className="text-white/70">
+                            <td>{STAGE_LABELS[m.stage] ?? m.stage}</td>
+                            <td className="text-right font-mono">{ms(m.last_ms)}</td>
+                            <td className="text-right font-mono">{ms(m.p50_ms)}</td>
+                            <td className="text-right font-mono">{ms(m.p95_ms)}</td>
+                            <td className="text-right font-mono">{m.count}</td>
+                        </tr>
+                    ))}
+                </tbody>
+            </table>
+            <button className="cyber-button text-xs" onClick={reset}>Reset Timings</button>
+        </>
+    );
+}
+
+interface LogEntry {
+    timestamp: string;
+    level: string;
+    target: string;
+    message: string;
+}
+
+const LOG_LEVEL_COLORS: Record<string, string> = {
+    ERROR: 'text-red-400',
+    WARN: 'text-yellow-400',
+    INFO: 'text-white/70',
+    DEBUG: 'text-white/40',
+    TRACE: 'text-white/30',
+};
+
+function LogViewer() {
+    const [level, setLevel] = useState('info');
+    const [entries, setEntries] = useState<LogEntry[]>([]);
+    const [logDir, setLogDir] = useState('');
+
+    const refresh = async (selected: string) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setEntries(await invoke('get_recent_logs', { level: selected, count: 200 }));
+        } catch (error) {
+            console.error('Failed to load logs:', error);
+        }
+    };
+
+    useEffect(() => {
+        refresh(level);
+        import('@tauri-apps/api/core')
+            .then(({ invoke }) => invoke<string>('get_log_directory'))
+            .then(setLogDir)
+            .catch(() => {});
+    }, [level]);
+
+    const copy = () => {
+        const text = entries.map(e => `${e.timestamp} ${e.level} ${e.target}: ${e.message}`).join('\n');
+        navigator.clipboard.writeText(text).catch(error => console.error('Failed to copy logs:', error));
+    };
+
+    return (
+        <>
+            <div className="flex items-center justify-between gap-3">
+                <select
+                    className="bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={level}
+                    onChange={(e) => setLevel(e.target.value)}
+                >
+                    <option value="error">Errors</option>
+                    <option value="warn">Warnings</option>
+                    <option value="info">Info</option>
+                    <option value="debug">Debug</option>
+                </select>
+                <div className="flex gap-2">
+                    <button className="cyber-button text-xs" onClick={() => refresh(level)}>Refresh</button>
+                    <button className="cyber-button text-xs" onClick={copy}>Copy</button>
+                </div>
+            </div>
+
+            <div className="h-48 overflow-y-auto bg-black/30 rounded p-2 font-mono text-xs space-y-0.5">
+                {entries.length === 0 && <div className="text-white/40">No log entries</div>}
+                {entries.map((entry, i) => (
+                    <div key={i} className={LOG_LEVEL_COLORS[entry.level] ?? 'text-white/70'}>
+                        <span className="text-white/30">{entry.timestamp.slice(11, 19)}</span> {entry.level} {entry.message}
+                    </div>
+                ))}
+            </div>
+
+            {logDir && <div className="text-xs text-white/40 break-all">Log files: {logDir}</div>}
+        </>
+    );
+}
+
+interface CrashReportSummary {
+    id: string;
+    kind: 'panic' | 'manual';
+    timestamp: string;
+    message: string;
+    submitted: boolean;
+}
+
+function CrashReports() {
+    const [reports, setReports] = useState<CrashReportSummary[]>([]);
+    const [consent, setConsent] = useState(false);
+    const [note, setNote] = useState('');
+    const [message, setMessage] = useState<string | null>(null);
+
+    const refresh = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setReports(await invoke('get_crash_reports'));
+        } catch (error) {
+            console.error('Failed to load crash reports:', error);
+        }
+    };
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setConsent(settings.diagnostics.crash_reports_consent);
+            } catch (error) {
+                console.error('Failed to load crash report settings:', error);
+            }
+            refresh();
+        };
+        load();
+    }, []);
+
+    const toggleConsent = async () => {
+        const next = !consent;
+        setConsent(next);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: 'diagnostics.crash_reports_consent', value: next });
+        } catch (error) {
+            console.error('Failed to save crash report consent:', error);
+        }
+    };
+
+    const run = async (command: string, args: Record<string, unknown>, done: string) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke(command, args);
+            setMessage(done);
+        } catch (error) {
+            setMessage(String(error));
+        }
+        refresh();
+    };
+
+    return (
+        <>
+            <ToggleItem label="Allow sharing crash reports I submit" enabled={consent} onToggle={toggleConsent} />
+
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={note}
+                    onChange={(e) => setNote(e.target.value)}
+                    placeholder="What went wrong?"
+                />
+                <button
+                    className="cyber-button text-xs"
+                    onClick={() => {
+                        run('create_error_report', { note }, 'Error report saved');
+                        setNote('');
+                    }}
+                >
+                    Save Report
+                </button>
+            </div>
+
+            {reports.map(report => (
+                <div key={report.id} className="flex items-center justify-between gap-2 text-xs">
+                    <div className="min-w-0">
+                        <div className={`truncate ${report.kind === 'panic' ? 'text-red-400' : 'text-white/70'}`}>{report.message}</div>
+                        <div className="text-white/40">{new Date(report.timestamp).toLocaleString()}{report.submitted && ' · sent'}</div>
+                    </div>
+                    <div className="flex gap-2 shrink-0">
+                        {consent && !report.submitted && (
+                            <button className="cyber-button text-xs" onClick={() => run('submit_crash_report', { id: report.id }, 'Report sent, thank you')}>
+                                Send
+                            </button>
+                        )}
+                        <button className="cyber-button text-xs" onClick={() => run('delete_crash_report', { id: report.id }, 'Report deleted')}>
+                            Delete
+                        </button>
+                    </div>
+                </div>
+            ))}
+
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+type FixAction =
+    | { kind: 'open_url'; url: string }
+    | { kind: 'pull_ollama_model'; model: string }
+    | { kind: 'open_settings'; section: string };
+
+interface DiagnosticCheck {
+    id: string;
+    title: string;
+    status: 'ok' | 'warning' | 'error' | 'skipped';
+    detail: string;
+    suggestion: string | null;
+    fix: FixAction | null;
+}
+
+const CHECK_STATUS_COLORS: Record<DiagnosticCheck['status'], string> = {
+    ok: 'text-green-400',
+    warning: 'text-yellow-400',
+    error: 'text-red-400',
+    skipped: 'text-white/40',
+};
+
+function SetupCheck() {
+    const [checks, setChecks] = useState<DiagnosticCheck[]>([]);
+    const [running, setRunning] = useState(false);
+    const [pulling, setPulling] = useState<string | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    const run = async () => {
+        setRunning(true);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setChecks(await invoke('run_diagnostics'));
+        } catch (error) {
+            setMessage(String(error));
+        }
+        setRunning(false);
+    };
+
+    useEffect(() => {
+        run();
+    }, []);
+
+    const pull = async (model: string) => {
+        setPulling(model);
+        setMessage(`Downloading ${model}, this can take a while...`);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('pull_ollama_model', { model });
+            setMessage(`${model} installed`);
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+        setPulling(null);
+        run();
+    };
+
+    return (
+        <>
+            <div className="flex items-center justify-between">
+                <span className="text-sm text-white/70">Setup check</span>
+                <button className="cyber-button text-xs" disabled={running} onClick={run}>
+                    {running ? 'Checking...' : 'Run Again'}
+                </button>
+            </div>
+
+            {checks.map(check => (
+                <div key={check.id} className="text-xs">
+                    <div className="flex justify-between gap-2">
+                        <span className="text-white/70">{check.title}</span>
+                        <span className={CHECK_STATUS_COLORS[check.status]}>{check.status}</span>
+                    </div>
+                    <div className="text-white/50">{check.detail}</div>
+                    {check.suggestion && (
+                        <div className="flex items-center justify-between gap-2 text-white/60">
+                            <span>{check.suggestion}</span>
+                            {check.fix?.kind === 'open_url' && (
+                                <a href={check.fix.url} target="_blank" className="text-cyber-cyan underline shrink-0">Open</a>
+                            )}
+                            {check.fix?.kind === 'pull_ollama_model' && (
+                                <button className="cyber-button text-xs shrink-0" disabled={pulling !== null} onClick={() => pull((check.fix as { model: string }).model)}>
+                                    Download
+                                </button>
+                            )}
+                        </div>
+                    )}
+                </div>
+            ))}
+
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+interface Profile {
+    id: string;
+    name: string;
+    voice_id: string;
+    enabled_routines: string[];
+}
+
+function ProfileManager({ onSwitch }: { onSwitch: () => void }) {
+    const [profiles, setProfiles] = useState<Profile[]>([]);
+    const [active, setActive] = useState('');
+    const [newName, setNewName] = useState('');
+    const [message, setMessage] = useState<string | null>(null);
+
+    const refresh = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const result: { active: string; list: Profile[] } = await invoke('get_profiles');
+            setProfiles(result.list);
+            setActive(result.active);
+        } catch (error) {
+            console.error('Failed to load profiles:', error);
+        }
+    };
+
+    useEffect(() => {
+        refresh();
+        let unlisten: (() => void) | undefined;
+        const setup = async () => {
+            const { listen } = await import('@tauri-apps/api/event');
+            unlisten = await listen('profile-changed', () => {
+                refresh();
+                onSwitch();
+            });
+        };
+        setup();
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    const run = async (command: string, args: Record<string, unknown>) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke(command, args);
+            setMessage(null);
+        } catch (error) {
+            setMessage(String(error));
+        }
+        refresh();
+    };
+
+    return (
+        <>
+            {profiles.map(profile => (
+                <div key={profile.id} className="flex items-center justify-between gap-2 text-sm">
+                    <span className={profile.id === active ? 'text-cyber-cyan' : 'text-white/70'}>
+                        {profile.name}{profile.id === active && ' (active)'}
+                    </span>
+                    <div className="flex gap-2 shrink-0">
+                        {profile.id !== active && (
+                            <>
+                                <button className="cyber-button text-xs" onClick={() => run('switch_profile', { id: profile.id })}>
+                                    Switch
+                                </button>
+                                <button className="cyber-button text-xs" onClick={() => run('delete_profile', { id: profile.id })}>
+                                    Delete
+                                </button>
+                            </>
+                        )}
+                    </div>
+                </div>
+            ))}
+
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={newName}
+                    onChange={(e) => setNewName(e.target.value)}
+                    placeholder="New profile from current setup, e.g. Work"
+                />
+                <button
+                    className="cyber-button text-xs"
+                    disabled={!newName.trim()}
+                    onClick={() => {
+                        run('create_profile', { name: newName });
+                        setNewName('');
+                    }}
+                >
+                    Add
+                </button>
+            </div>
+
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+interface ImportSummary {
+    created_at: string;
+    routines: number;
+    aliases: number;
+    secrets_imported: boolean;
+}
+
+function BackupSettings() {
+    const [includeSecrets, setIncludeSecrets] = useState(false);
+    const [importPath, setImportPath] = useState('');
+    const [message, setMessage] = useState<string | null>(null);
+
+    const exportBackup = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const path: string = await invoke('export_settings', { includeSecrets });
+            setMessage(`Backup saved to ${path}`);
+        } catch (error) {
+            setMessage(String(error));
+        }
+    };
+
+    const importBackup = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const summary: ImportSummary = await invoke('import_settings', { path: importPath });
+            setMessage(
+                `Restored backup from ${new Date(summary.created_at).toLocaleString()}: ` +
+                `${summary.routines} routines, ${summary.aliases} aliases` +
+                (summary.secrets_imported ? ', API keys' : '')
+            );
+            setImportPath('');
+        } catch (error) {
+            setMessage(String(error));
+        }
+    };
+
+    return (
+        <>
+            <ToggleItem label="Include API keys (stored unencrypted in the backup)" enabled={includeSecrets} onToggle={() => setIncludeSecrets(!includeSecrets)} />
+            <button className="cyber-button text-xs" onClick={exportBackup}>
+                Export Settings
+            </button>
+
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={importPath}
+                    onChange={(e) => setImportPath(e.target.value)}
+                    placeholder="Path to astral-backup-*.zip"
+                />
+                <button className="cyber-button text-xs" disabled={!importPath.trim()} onClick={importBackup}>
+                    Import
+                </button>
+            </div>
+
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+/** The `search` settings section */
+function WebSearchSettings() {
+    const [search, setSearch] = useState<any>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setSearch(settings.search);
+            } catch (error) {
+                console.error('Failed to load web search settings:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = search;
+        setSearch({ ...search, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `search.${field}`, value });
+        } catch (error) {
+            setSearch(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const test = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const results: { title: string }[] = await invoke('web_search', { query: 'weather today' });
+            setMessage(results.length ? `Working: ${results.length} results, first "${results[0].title}"` : 'Working, but no results came back');
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    if (!search) return null;
+
+    return (
+        <>
+            <ToggleItem label="Answer current-events questions from the web" enabled={search.enabled} onToggle={() => save('enabled', !search.enabled)} />
+            <div className="flex items-center justify-between gap-3">
+                <span className="text-sm text-white/70">Search with</span>
+                <select
+                    className="w-48 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={search.provider}
+                    onChange={(e) => save('provider', e.target.value)}
+                >
+                    <option value="brave">Brave Search API</option>
+                    <option value="searxng">SearxNG (self-hosted)</option>
+                </select>
+            </div>
+            {search.provider === 'brave' ? (
+                <input
+                    type="password"
+                    className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={search.brave_api_key}
+                    onBlur={(e) => e.target.value !== search.brave_api_key && save('brave_api_key', e.target.value)}
+                    placeholder="Brave Search API key"
+                />
+            ) : (
+                <input
+                    type="text"
+                    className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={search.searxng_url}
+                    onBlur={(e) => e.target.value !== search.searxng_url && save('searxng_url', e.target.value)}
+                    placeholder="http://localhost:8888"
+                />
+            )}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <button className="cyber-button text-xs" disabled={!search.enabled} onClick={test}>
+                Test Search
+            </button>
+        </>
+    );
+}
+
+interface CalendarStatus {
+    enabled: boolean;
+    google_connected: boolean;
+    fetched_at: string | null;
+    errors: string[];
+    events: { title: string; start: string; all_day: boolean }[];
+}
+
+function CalendarSettings() {
+    const [calendar, setCalendar] = useState<any>(null);
+    const [status, setStatus] = useState<CalendarStatus | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+    const [connecting, setConnecting] = useState(false);
+
+    const loadStatus = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setStatus(await invoke<CalendarStatus>('get_calendar_status'));
+        } catch (error) {
+            console.error('Failed to load calendar status:', error);
+        }
+    };
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setCalendar(settings.calendar);
+            } catch (error) {
+                console.error('Failed to load calendar settings:', error);
+            }
+        };
+        load();
+        loadStatus();
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = calendar;
+        setCalendar({ ...calendar, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `calendar.${field}`, value });
+        } catch (error) {
+            setCalendar(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const refresh = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setStatus(await invoke<CalendarStatus>('refresh_calendar'));
+            setMessage(null);
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    const toggleGoogle = async () => {
+        setConnecting(true);
+        setMessage(status?.google_connected ? null : 'Finish signing in to Google in your browser...');
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke(status?.google_connected ? 'disconnect_google_calendar' : 'connect_google_calendar');
+            setMessage(null);
+        } catch (error) {
+            setMessage(describeError(error));
+        } finally {
+            setConnecting(false);
+            loadStatus();
+        }
+    };
+
+    if (!calendar) return null;
+
+    return (
+        <>
+            <ToggleItem label="Read my calendar" enabled={calendar.enabled} onToggle={() => save('enabled', !calendar.enabled)} />
+            <textarea
+                className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                rows={2}
+                defaultValue={calendar.ics_urls.join('\n')}
+                onBlur={(e) => {
+                    const urls = e.target.value.split('\n').map((u) => u.trim()).filter(Boolean);
+                    if (urls.join('\n') !== calendar.ics_urls.join('\n')) save('ics_urls', urls);
+                }}
+                placeholder="ICS subscription addresses, one per line"
+            />
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={calendar.google_client_id}
+                    onBlur={(e) => e.target.value !== calendar.google_client_id && save('google_client_id', e.target.value)}
+                    placeholder="Google OAuth client ID"
+                />
+                <input
+                    type="password"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={calendar.google_client_secret}
+                    onBlur={(e) => e.target.value !== calendar.google_client_secret && save('google_client_secret', e.target.value)}
+                    placeholder="Client secret"
+                />
+            </div>
+            <div className="flex items-center justify-between gap-3">
+                <span className="text-sm text-white/70">Announce meetings (minutes before, 0 = off)</span>
+                <input
+                    type="number"
+                    min={0}
+                    max={120}
+                    className="w-20 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={calendar.announce_minutes}
+                    onChange={(e) => save('announce_minutes', Number(e.target.value))}
+                />
+            </div>
+            {status?.fetched_at && (
+                <div className="text-xs text-white/50">
+                    {status.events.length} events, updated {new Date(status.fetched_at).toLocaleTimeString()}
+                </div>
+            )}
+            {status?.errors.map((error) => (
+                <div key={error} className="text-xs text-red-400">{error}</div>
+            ))}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <div className="flex gap-2">
+                <button className="cyber-button text-xs" disabled={connecting} onClick={toggleGoogle}>
+                    {status?.google_connected ? 'Disconnect Google' : 'Connect Google Calendar'}
+                </button>
+                <button className="cyber-button text-xs" disabled={!calendar.enabled} onClick={refresh}>
+                    Refresh
+                </button>
+            </div>
+        </>
+    );
+}
+
+function EmailSettings() {
+    const [email, setEmail] = useState<any>(null);
+    const [summary, setSummary] = useState<string | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+    const [busy, setBusy] = useState(false);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setEmail(settings.email);
+            } catch (error) {
+                console.error('Failed to load email settings:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = email;
+        setEmail({ ...email, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `email.${field}`, value });
+        } catch (error) {
+            setEmail(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const summarize = async () => {
+        setBusy(true);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setSummary(await invoke<string>('summarize_email'));
+            setMessage(null);
+        } catch (error) {
+            setMessage(describeError(error));
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    const toggleGmail = async () => {
+        const connected = !!email.google_refresh_token;
+        setBusy(true);
+        setMessage(connected ? null : 'Finish signing in to Google in your browser...');
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke(connected ? 'disconnect_google_email' : 'connect_google_email');
+            const settings: any = await invoke('load_settings');
+            setEmail(settings.email);
+            setMessage(null);
+        } catch (error) {
+            setMessage(describeError(error));
+        } finally {
+            setBusy(false);
+        }
+    };
+
+    if (!email) return null;
+
+    return (
+        <>
+            <ToggleItem label="Summarize unread email" enabled={email.enabled} onToggle={() => save('enabled', !email.enabled)} />
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={email.server}
+                    onBlur={(e) => e.target.value !== email.server && save('server', e.target.value)}
+                    placeholder="IMAP server (imap.gmail.com)"
+                />
+                <input
+                    type="number"
+                    className="w-20 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={email.port}
+                    onChange={(e) => save('port', Number(e.target.value))}
+                />
+            </div>
+            <input
+                type="text"
+                className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                defaultValue={email.username}
+                onBlur={(e) => e.target.value !== email.username && save('username', e.target.value)}
+                placeholder="Email address"
+            />
+            <div className="flex items-center justify-between gap-3">
+                <span className="text-sm text-white/70">Sign in with</span>
+                <select
+                    className="w-48 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={email.auth}
+                    onChange={(e) => save('auth', e.target.value)}
+                >
+                    <option value="password">App password</option>
+                    <option value="google">Google (Gmail)</option>
+                </select>
+            </div>
+            {email.auth === 'password' ? (
+                <input
+                    type="password"
+                    className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={email.password}
+                    onBlur={(e) => e.target.value !== email.password && save('password', e.target.value)}
+                    placeholder="App password"
+                />
+            ) : (
+                <div className="flex items-center justify-between gap-3">
+                    <span className="text-xs text-white/50">Uses the Google OAuth client from the Calendar section</span>
+                    <button className="cyber-button text-xs" disabled={busy} onClick={toggleGmail}>
+                        {email.google_refresh_token ? 'Disconnect Gmail' : 'Connect Gmail'}
+                    </button>
+                </div>
+            )}
+            <ToggleItem label="Let the LLM write the summary" enabled={email.summarize_with_llm} onToggle={() => save('summarize_with_llm', !email.summarize_with_llm)} />
+            <div className="text-xs text-white/50">
+                Read-only: checking never marks messages as read. Allow "Mark email as read" under Permissions to change that.
+            </div>
+            {summary && <div className="text-sm text-white/80">{summary}</div>}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <button className="cyber-button text-xs" disabled={!email.enabled || busy} onClick={summarize}>
+                Summarize Inbox
+            </button>
+        </>
+    );
+}
+
+function HomeAssistantSettings() {
+    const [ha, setHa] = useState<any>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setHa(settings.home_assistant);
+            } catch (error) {
+                console.error('Failed to load Home Assistant settings:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = ha;
+        setHa({ ...ha, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `home_assistant.${field}`, value });
+        } catch (error) {
+            setHa(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const test = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const entities: number = await invoke('test_home_assistant');
+            setMessage(`Connected: ${entities} entities`);
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    if (!ha) return null;
+
+    return (
+        <>
+            <ToggleItem label="Control my smart home" enabled={ha.enabled} onToggle={() => save('enabled', !ha.enabled)} />
+            <input
+                type="text"
+                className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                defaultValue={ha.url}
+                onBlur={(e) => e.target.value !== ha.url && save('url', e.target.value)}
+                placeholder="http://homeassistant.local:8123"
+            />
+            <input
+                type="password"
+                className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                defaultValue={ha.token}
+                onBlur={(e) => e.target.value !== ha.token && save('token', e.target.value)}
+                placeholder="Long-lived access token"
+            />
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <button className="cyber-button text-xs" disabled={!ha.enabled} onClick={test}>
+                Test Connection
+            </button>
+        </>
+    );
+}
+
+interface MqttStatus {
+    enabled: boolean;
+    connected: boolean;
+    subscriptions: string[];
+    last_error: string | null;
+}
+
+function MqttSettings() {
+    const [mqtt, setMqtt] = useState<any>(null);
+    const [status, setStatus] = useState<MqttStatus | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setMqtt(settings.mqtt);
+                setStatus(await invoke<MqttStatus>('get_mqtt_status'));
+            } catch (error) {
+                console.error('Failed to load MQTT settings:', error);
+            }
+        };
+        load();
+        const timer = setInterval(async () => {
+            const { invoke } = await import('@tauri-apps/api/core');
+            invoke<MqttStatus>('get_mqtt_status').then(setStatus).catch(() => {});
+        }, 5000);
+        return () => clearInterval(timer);
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = mqtt;
+        setMqtt({ ...mqtt, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `mqtt.${field}`, value });
+        } catch (error) {
+            setMqtt(previous);
+            setMessage(String(error));
+        }
+    };
+
+    if (!mqtt) return null;
+
+    return (
+        <>
+            <ToggleItem label="Connect to an MQTT broker" enabled={mqtt.enabled} onToggle={() => save('enabled', !mqtt.enabled)} />
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={mqtt.host}
+                    onBlur={(e) => e.target.value !== mqtt.host && save('host', e.target.value)}
+                    placeholder="Broker address"
+                />
+                <input
+                    type="number"
+                    className="w-20 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={mqtt.port}
+                    onChange={(e) => save('port', Number(e.target.value))}
+                />
+            </div>
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={mqtt.username}
+                    onBlur={(e) => e.target.value !== mqtt.username && save('username', e.target.value)}
+                    placeholder="User name (optional)"
+                />
+                <input
+                    type="password"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={mqtt.password}
+                    onBlur={(e) => e.target.value !== mqtt.password && save('password', e.target.value)}
+                    placeholder="Password"
+                />
+            </div>
+            {status?.enabled && (
+                <div className="text-xs text-white/50">
+                    {status.connected
+                        ? `Connected, listening on ${status.subscriptions.length ? status.subscriptions.join(', ') : 'no topics yet'}`
+                        : `Not connected${status.last_error ? `: ${status.last_error}` : ''}`}
+                </div>
+            )}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+function ProxySettings() {
+    const [proxy, setProxy] = useState<any>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setProxy(settings.proxy);
+            } catch (error) {
+                console.error('Failed to load proxy settings:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = proxy;
+        setProxy({ ...proxy, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `proxy.${field}`, value });
+            setMessage(null);
+        } catch (error) {
+            setProxy(previous);
+            setMessage(describeError(error));
+        }
+    };
+
+    if (!proxy) return null;
+
+    const noProxy = proxy.no_proxy.join(', ');
+
+    return (
+        <>
+            <ToggleItem label="Send requests through a proxy" enabled={proxy.enabled} onToggle={() => save('enabled', !proxy.enabled)} />
+            <input
+                type="text"
+                className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                defaultValue={proxy.url}
+                onBlur={(e) => e.target.value !== proxy.url && save('url', e.target.value)}
+                placeholder="http://proxy.example:8080 or socks5h://127.0.0.1:1080"
+            />
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={proxy.username}
+                    onBlur={(e) => e.target.value !== proxy.username && save('username', e.target.value)}
+                    placeholder="User name (optional)"
+                />
+                <input
+                    type="password"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={proxy.password}
+                    onBlur={(e) => e.target.value !== proxy.password && save('password', e.target.value)}
+                    placeholder="Password"
+                />
+            </div>
+            <div>
+                <label className="text-xs text-white/50 block mb-1">Reach directly (hosts, .domains or IP ranges, comma separated)</label>
+                <input
+                    type="text"
+                    className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={noProxy}
+                    onBlur={(e) => e.target.value !== noProxy
+                        && save('no_proxy', e.target.value.split(',').map(h => h.trim()).filter(Boolean))}
+                    placeholder="localhost, 127.0.0.1, .corp.example"
+                />
+            </div>
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+function HueSettings() {
+    const [hue, setHue] = useState<any>(null);
+    const [bridgeIp, setBridgeIp] = useState('');
+    const [pairing, setPairing] = useState(false);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setHue(settings.hue);
+                setBridgeIp(settings.hue.bridge_ip);
+            } catch (error) {
+                console.error('Failed to load Hue settings:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = hue;
+        setHue({ ...hue, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `hue.${field}`, value });
+        } catch (error) {
+            setHue(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const discover = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const bridges: string[] = await invoke('discover_hue_bridges');
+            if (bridges.length === 0) {
+                setMessage('No bridge found; enter its address instead');
+            } else {
+                setBridgeIp(bridges[0]);
+                setMessage(`Found ${bridges.length === 1 ? 'a bridge' : `${bridges.length} bridges`}`);
+            }
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    const pair = async () => {
+        setPairing(true);
+        setMessage('Press the link button on your Hue bridge…');
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('pair_hue_bridge', { bridgeIp });
+            const settings: any = await invoke('load_settings');
+            setHue(settings.hue);
+            setMessage('Paired');
+        } catch (error) {
+            setMessage(describeError(error));
+        } finally {
+            setPairing(false);
+        }
+    };
+
+    if (!hue) return null;
+
+    return (
+        <>
+            <ToggleItem label="Control Hue lights" enabled={hue.enabled} onToggle={() => save('enabled', !hue.enabled)} />
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={bridgeIp}
+                    onChange={(e) => setBridgeIp(e.target.value)}
+                    placeholder="Bridge address, e.g. 192.168.1.20"
+                />
+                <button className="cyber-button text-xs" disabled={!hue.enabled} onClick={discover}>
+                    Search
+                </button>
+            </div>
+            <div className="text-xs text-white/50">
+                {hue.username && hue.bridge_ip ? `Paired with ${hue.bridge_ip}` : 'Not paired'}
+            </div>
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <button className="cyber-button text-xs" disabled={!hue.enabled || pairing || !bridgeIp} onClick={pair}>
+                {pairing ? 'Waiting for link button…' : 'Pair Bridge'}
+            </button>
+        </>
+    );
+}
+
+interface BotStatus {
+    enabled: boolean;
+    connected: boolean;
+    bot_name: string | null;
+    last_error: string | null;
+}
+
+interface ChatBridgeStatus {
+    telegram: BotStatus;
+    discord: BotStatus;
+}
+
+function ChatBridgeSettings() {
+    const [bridge, setBridge] = useState<any>(null);
+    const [status, setStatus] = useState<ChatBridgeStatus | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setBridge(settings.chat_bridge);
+                setStatus(await invoke<ChatBridgeStatus>('get_chat_bridge_status'));
+            } catch (error) {
+                console.error('Failed to load chat bridge settings:', error);
+            }
+        };
+        load();
+        const timer = setInterval(async () => {
+            const { invoke } = await import('@tauri-apps/api/core');
+            invoke<ChatBridgeStatus>('get_chat_bridge_status').then(setStatus).catch(() => {});
+        }, 5000);
+        return () => clearInterval(timer);
+    }, []);
+
+    const save = async (service: 'telegram' | 'discord', field: string, value: any) => {
+        const previous = bridge;
+        setBridge({ ...bridge, [service]: { ...bridge[service], [field]: value } });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `chat_bridge.${service}.${field}`, value });
+            setMessage(null);
+        } catch (error) {
+            setBridge(previous);
+            setMessage(String(error));
+        }
+    };
+
+    if (!bridge) return null;
+
+    const describeStatus = (bot: BotStatus | undefined) => {
+        if (!bot?.enabled) return 'Off';
+        if (bot.connected) return `Connected${bot.bot_name ? ` as ${bot.bot_name}` : ''}`;
+        return bot.last_error ?? 'Connecting…';
+    };
+
+    const bot = (service: 'telegram' | 'discord', label: string) => {
+        const settings = bridge[service];
+        const users = settings.allowed_users.join(', ');
+        return (
+            <div className="space-y-2">
+                <ToggleItem
+                    label={`Chat with me on ${label}`}
+                    enabled={settings.enabled}
+                    onToggle={() => save(service, 'enabled', !settings.enabled)}
+                />
+                <input
+                    type="password"
+                    className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={settings.token}
+                    onBlur={(e) => e.target.value !== settings.token && save(service, 'token', e.target.value)}
+                    placeholder={`${label} bot token`}
+                />
+                <input
+                    type="text"
+                    className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    defaultValue={users}
+                    onBlur={(e) =>
+                        e.target.value !== users &&
+                        save(
+                            service,
+                            'allowed_users',
+                            e.target.value.split(',').map((u) => u.trim()).filter(Boolean),
+                        )
+                    }
+                    placeholder="Allowed user IDs, comma separated"
+                />
+                <div className="text-xs text-white/50">{describeStatus(status?.[service])}</div>
+            </div>
+        );
+    };
+
+    return (
+        <>
+            {bot('telegram', 'Telegram')}
+            {bot('discord', 'Discord')}
+            <div className="text-xs text-white/40">
+                Message the bot once and it replies with your user ID to add here.
+            </div>
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+interface KnowledgeStatus {
+    enabled: boolean;
+    documents: number;
+    chunks: number;
+    indexing: boolean;
+    last_sync: number | null;
+    last_error: string | null;
+}
+
+interface KnowledgeDocument {
+    path: string;
+    name: string;
+    title: string;
+    kind: string;
+    size: number;
+    modified: number | null;
+    indexed_at: number;
+    chunks: number;
+    added: boolean;
+}
+
+interface KnowledgePassage {
+    path: string;
+    name: string;
+    text: string;
+    score: number;
+}
+
+function KnowledgeBaseSettings() {
+    const [knowledge, setKnowledge] = useState<any>(null);
+    const [status, setStatus] = useState<KnowledgeStatus | null>(null);
+    const [documents, setDocuments] = useState<KnowledgeDocument[]>([]);
+    const [showDocuments, setShowDocuments] = useState(false);
+    const [newFiles, setNewFiles] = useState('');
+    const [adding, setAdding] = useState(false);
+    const [question, setQuestion] = useState('');
+    const [answer, setAnswer] = useState<{ answer: string; sources: KnowledgePassage[] } | null>(null);
+    const [asking, setAsking] = useState(false);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setKnowledge(settings.knowledge);
+                setStatus(await invoke<KnowledgeStatus>('get_knowledge_status'));
+            } catch (error) {
+                console.error('Failed to load knowledge base settings:', error);
+            }
+        };
+        load();
+        const timer = setInterval(async () => {
+            const { invoke } = await import('@tauri-apps/api/core');
+            invoke<KnowledgeStatus>('get_knowledge_status').then(setStatus).catch(() => {});
+        }, 5000);
+        return () => clearInterval(timer);
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = knowledge;
+        setKnowledge({ ...knowledge, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `knowledge.${field}`, value });
+        } catch (error) {
+            setKnowledge(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const rebuild = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('rebuild_knowledge_base');
+            setMessage('Reindexing your documents');
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    const loadDocuments = async () => {
+        const { invoke } = await import('@tauri-apps/api/core');
+        setDocuments(await invoke<KnowledgeDocument[]>('list_knowledge_documents'));
+    };
+
+    const toggleDocuments = async () => {
+        if (!showDocuments) await loadDocuments().catch(() => {});
+        setShowDocuments(!showDocuments);
+    };
+
+    const addFiles = async () => {
+        const paths = newFiles.split('\n').map((f) => f.trim()).filter(Boolean);
+        if (paths.length === 0) return;
+        setAdding(true);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const added = await invoke<KnowledgeDocument[]>('ingest_documents', { paths });
+            setNewFiles('');
+            setMessage(`Learned ${added.length} ${added.length === 1 ? 'file' : 'files'}`);
+            if (showDocuments) await loadDocuments();
+        } catch (error) {
+            setMessage(describeError(error));
+        } finally {
+            setAdding(false);
+        }
+    };
+
+    const removeDocument = async (path: string) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('remove_knowledge_document', { path });
+            setDocuments(documents.filter((d) => d.path !== path));
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    const ask = async () => {
+        if (!question.trim()) return;
+        setAsking(true);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setAnswer(await invoke('ask_documents', { question }));
+            setMessage(null);
+        } catch (error) {
+            setMessage(describeError(error));
+        } finally {
+            setAsking(false);
+        }
+    };
+
+    if (!knowledge) return null;
+
+    const folders = knowledge.folders.join('\n');
+    const cited = answer?.sources.filter((_, i) => answer.answer.includes(`[${i + 1}]`)) ?? [];
+
+    return (
+        <>
+            <ToggleItem
+                label="Answer questions from my documents"
+                enabled={knowledge.enabled}
+                onToggle={() => save('enabled', !knowledge.enabled)}
+            />
+            <ToggleItem
+                label="Bring up relevant documents in any conversation"
+                enabled={knowledge.auto_context}
+                onToggle={() => save('auto_context', !knowledge.auto_context)}
+            />
+            <textarea
+                className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                rows={3}
+                defaultValue={folders}
+                onBlur={(e) =>
+                    e.target.value !== folders &&
+                    save('folders', e.target.value.split('\n').map((f) => f.trim()).filter(Boolean))
+                }
+                placeholder="Folders to learn from, one per line"
+            />
+            <input
+                type="text"
+                className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                defaultValue={knowledge.embedding_model}
+                onBlur={(e) => e.target.value !== knowledge.embedding_model && save('embedding_model', e.target.value)}
+                placeholder="Embedding model (default: nomic-embed-text, or text-embedding-3-small with OpenAI)"
+            />
+            {status && knowledge.enabled && (
+                <div className="text-xs text-white/50">
+                    {status.indexing ? 'Indexing… ' : ''}
+                    {status.documents} documents, {status.chunks} passages
+                    {status.last_error && <span className="text-red-400"> — {status.last_error}</span>}
+                    {' · '}
+                    <button className="underline" onClick={toggleDocuments}>
+                        {showDocuments ? 'Hide' : 'Show'} documents
+                    </button>
+                </div>
+            )}
+            {showDocuments && knowledge.enabled && (
+                <div className="max-h-48 overflow-y-auto space-y-1">
+                    {documents.length === 0 && <div className="text-xs text-white/40">Nothing indexed yet</div>}
+                    {documents.map((doc) => (
+                        <div key={doc.path} className="flex items-center gap-2 text-xs" title={doc.path}>
+                            <span className="flex-1 truncate text-white/70">
+                                {doc.title || doc.name}
+                                <span className="text-white/40">
+                                    {' '}
+                                    — {doc.name}, {doc.chunks ? `${doc.chunks} passages` : 'no readable text'}
+                                    {doc.added ? ', added by hand' : ''}
+                                </span>
+                            </span>
+                            <button className="text-red-400 hover:text-red-300" onClick={() => removeDocument(doc.path)}>
+                                Remove
+                            </button>
+                        </div>
+                    ))}
+                </div>
+            )}
+            <div className="flex gap-2">
+                <textarea
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    rows={2}
+                    value={newFiles}
+                    onChange={(e) => setNewFiles(e.target.value)}
+                    placeholder="Files to teach ASTRAL (PDF, Markdown, text), one path per line"
+                    disabled={!knowledge.enabled}
+                />
+                <button className="cyber-button text-xs" disabled={!knowledge.enabled || adding} onClick={addFiles}>
+                    {adding ? 'Learning…' : 'Add'}
+                </button>
+            </div>
+            <div className="flex gap-2">
+                <input
+                    type="text"
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={question}
+                    onChange={(e) => setQuestion(e.target.value)}
+                    onKeyDown={(e) => e.key === 'Enter' && ask()}
+                    placeholder="Ask your documents…"
+                    disabled={!knowledge.enabled}
+                />
+                <button className="cyber-button text-xs" disabled={!knowledge.enabled || asking} onClick={ask}>
+                    {asking ? 'Thinking…' : 'Ask'}
+                </button>
+            </div>
+            {answer && (
+                <div className="text-sm text-white/80 space-y-1">
+                    <div>{answer.answer}</div>
+                    {cited.map((source) => (
+                        <div key={source.path + source.text.slice(0, 20)} className="text-xs text-white/40 truncate" title={source.path}>
+                            {source.name}
+                        </div>
+                    ))}
+                </div>
+            )}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <button className="cyber-button text-xs" disabled={!knowledge.enabled} onClick={rebuild}>
+                Rebuild Index
+            </button>
+        </>
+    );
+}
+
+const MODEL_PLACEHOLDERS: Record<string, string> = {
+    Ollama: 'mistral:latest',
+    OpenAI: 'gpt-4o-mini',
+    AzureOpenAI: 'gpt-4o-mini',
+    OpenRouter: 'anthropic/claude-sonnet-4.5',
+    Claude: 'claude-sonnet-4-5',
+};
+
+function LlmSettings() {
+    const [llm, setLlm] = useState<any>(null);
+    const [showApiKey, setShowApiKey] = useState(false);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setLlm(settings.llm);
+            } catch (error) {
+                console.error('Failed to load LLM settings:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = llm;
+        setLlm({ ...llm, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `llm.${field}`, value });
+            setMessage(null);
+        } catch (error) {
+            setLlm(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const testConnection = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const ok = await invoke<boolean>('test_llm_connection', { config: llm });
+            setMessage(ok ? 'Connection successful' : 'Connection failed - check your settings');
+        } catch (error) {
+            setMessage('Connection test failed: ' + describeError(error));
+        }
+    };
+
+    if (!llm) return null;
+
+    const input = (field: string, placeholder: string, type = 'text') => (
+        <input
+            key={`${llm.provider}-${field}`}
+            type={type}
+            className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+            defaultValue={llm[field] ?? ''}
+            onBlur={(e) => e.target.value !== (llm[field] ?? '') && save(field, e.target.value)}
+            placeholder={placeholder}
+        />
+    );
+
+    return (
+        <>
+            <div>
+                <label className="text-xs text-white/50 block mb-1">LLM Provider</label>
+                <select
+                    className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                    value={llm.provider}
+                    onChange={(e) => save('provider', e.target.value)}
+                >
+                    <option value="Ollama">Ollama (Local)</option>
+                    <option value="OpenAI">OpenAI</option>
+                    <option value="AzureOpenAI">Azure OpenAI</option>
+                    <option value="OpenRouter">OpenRouter</option>
+                    <option value="Claude">Anthropic Claude</option>
+                    <option value="Embedded">Built-in (llama.cpp)</option>
+                </select>
+            </div>
+            {llm.provider !== 'Ollama' && llm.provider !== 'Embedded' && (
+                <div>
+                    <label className="text-xs text-white/50 block mb-1">API Key</label>
+                    {input('api_key', llm.provider === 'AzureOpenAI' ? 'Key 1 from Keys and Endpoint' : 'sk-...', showApiKey ? 'text' : 'password')}
+                    <button className="text-xs text-cyber-cyan mt-1" onClick={() => setShowApiKey(!showApiKey)}>
+                        {showApiKey ? 'Hide' : 'Show'}
+                    </button>
+                </div>
+            )}
+            {llm.provider === 'AzureOpenAI' && (
+                <>
+                    {input('azure_endpoint', 'https://your-resource.openai.azure.com')}
+                    {input('azure_deployment', 'Deployment name')}
+                    {input('azure_api_version', 'API version, e.g. 2024-10-21')}
+                </>
+            )}
+            {llm.provider === 'Embedded' ? (
+                <div>
+                    <label className="text-xs text-white/50 block mb-1">Model File</label>
+                    {input('embedded_model_path', 'C:\\Models\\llama-3.2-3b-instruct-q4_k_m.gguf')}
+                    <div className="text-xs text-white/50 mt-1">
+                        Any GGUF chat model. It loads on the first question, which can take a few seconds.
+                    </div>
+                </div>
+            ) : (
+                <div>
+                    <label className="text-xs text-white/50 block mb-1">Model</label>
+                    {input('model', MODEL_PLACEHOLDERS[llm.provider] ?? '')}
+                </div>
+            )}
+            {llm.provider === 'Ollama' && (
+                <>
+                    {input('ollama_url', 'http://localhost:11434')}
+                    <div className="text-xs text-white/50 bg-cyber-cyan/10 border border-cyber-cyan/30 rounded p-2">
+                        Make sure Ollama is running: <code className="text-cyber-cyan">ollama serve</code>
+                    </div>
+                    <OllamaModels current={llm.model} onUse={(name) => save('model', name)} />
+                </>
+            )}
+            <ToggleItem
+                label="Let the AI use ASTRAL's tools"
+                enabled={llm.tool_calling}
+                onToggle={() => save('tool_calling', !llm.tool_calling)}
+            />
+            {llm.tool_calling && (
+                <div className="text-xs text-white/50">
+                    Looking things up runs straight away. Opening apps, running routines, smart home changes and skills wait for your yes.
+                </div>
+            )}
+            <SystemPromptEditor />
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <button className="w-full cyber-button text-sm" onClick={testConnection}>
+                Test Connection
+            </button>
+        </>
+    );
+}
+
+interface SystemPrompt {
+    prompt: string;
+    custom: boolean;
+    default_prompt: string;
+}
+
+function SystemPromptEditor() {
+    const [saved, setSaved] = useState<SystemPrompt | null>(null);
+    const [draft, setDraft] = useState('');
+    const [message, setMessage] = useState<string | null>(null);
+
+    const apply = (prompt: SystemPrompt) => {
+        setSaved(prompt);
+        setDraft(prompt.prompt);
+    };
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                apply(await invoke<SystemPrompt>('get_system_prompt'));
+            } catch (error) {
+                console.error('Failed to load the system prompt:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const run = async (command: string, args: Record<string, unknown> = {}) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            apply(await invoke<SystemPrompt>(command, args));
+            setMessage('Saved');
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    if (!saved) return null;
+
+    return (
+        <div>
+            <label className="text-xs text-white/50 block mb-1">
+                System Prompt {saved.custom ? '(custom)' : '(default)'}
+            </label>
+            <textarea
+                className="w-full h-40 bg-white/10 border border-white/20 rounded px-3 py-2 text-xs font-mono"
+                value={draft}
+                onChange={(e) => { setDraft(e.target.value); setMessage(null); }}
+            />
+            <div className="text-xs text-white/40 mt-1">
+                {'{{language}}, {{now_playing}}, {{active_window}} and {{upcoming_events}} are filled in for each request.'}
+            </div>
+            <div className="flex gap-2 mt-2">
+                <button className="cyber-button text-xs" disabled={draft === saved.prompt} onClick={() => run('set_system_prompt', { prompt: draft })}>
+                    Save Prompt
+                </button>
+                <button className="cyber-button text-xs" disabled={!saved.custom} onClick={() => run('reset_system_prompt')}>
+                    Reset to Default
+                </button>
+            </div>
+            {message && <div className="text-xs text-white/50 mt-1">{message}</div>}
+        </div>
+    );
+}
+
+interface Persona {
+    id: string;
+    name: string;
+    description: string;
+    system_prompt: string;
+    model: string;
+    temperature: number | null;
+    voice_id: string;
+    browser_voice: string;
+}
+
+const EMPTY_PERSONA: Persona = {
+    id: '',
+    name: '',
+    description: '',
+    system_prompt: '',
+    model: '',
+    temperature: null,
+    voice_id: '',
+    browser_voice: '',
+};
+
+interface UsageTotals {
+    period: string;
+    requests: number;
+    input_tokens: number;
+    output_tokens: number;
+    cost_usd: number;
+    models: { provider: string; model: string; requests: number; input_tokens: number; output_tokens: number; cost_usd: number }[];
+}
+
+interface SpendStatus {
+    month_cost_usd: number;
+    monthly_cap_usd: number;
+    cap_reached: boolean;
+}
+
+const formatUsd = (usd: number) => `$${usd < 0.01 && usd > 0 ? usd.toFixed(4) : usd.toFixed(2)}`;
+
+function UsageSettings() {
+    const [usage, setUsage] = useState<any>(null);
+    const [today, setToday] = useState<UsageTotals | null>(null);
+    const [months, setMonths] = useState<UsageTotals[]>([]);
+    const [spend, setSpend] = useState<SpendStatus | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    const refresh = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const [days, monthly, status] = await Promise.all([
+                invoke<UsageTotals[]>('get_daily_usage', { days: 1 }),
+                invoke<UsageTotals[]>('get_monthly_usage', { months: 3 }),
+                invoke<SpendStatus>('get_spend_status'),
+            ]);
+            setToday(days[0] ?? null);
+            setMonths(monthly);
+            setSpend(status);
+        } catch (error) {
+            console.error('Failed to load usage:', error);
+        }
+    };
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setUsage(settings.usage);
+            } catch (error) {
+                console.error('Failed to load usage settings:', error);
+            }
+        };
+        load();
+        refresh();
+    }, []);
+
+    const saveCap = async (value: string) => {
+        const cap = value.trim() === '' ? 0 : Number(value);
+        if (Number.isNaN(cap)) {
+            setMessage('Enter the cap in dollars, or leave it empty for no cap');
+            return;
+        }
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: 'usage.monthly_cap_usd', value: cap });
+            setUsage({ ...usage, monthly_cap_usd: cap });
+            setMessage(null);
+            await refresh();
+        } catch (error) {
+            setMessage(String(error));
+        }
+    };
+
+    if (!usage) return null;
+
+    const tokens = (totals: UsageTotals) => (totals.input_tokens + totals.output_tokens).toLocaleString();
+
+    return (
+        <>
+            {today && (
+                <div className="text-xs text-white/60">
+                    Today: {today.requests} requests, {tokens(today)} tokens, {formatUsd(today.cost_usd)}
+                </div>
+            )}
+            {months.map((month) => (
+                <div key={month.period} className="text-xs text-white/60">
+                    <div>
+                        {month.period}: {month.requests} requests, {tokens(month)} tokens, {formatUsd(month.cost_usd)}
+                    </div>
+                    {month.models.slice(0, 3).map((m) => (
+                        <div key={m.provider + m.model} className="pl-3 text-white/40 truncate">
+                            {m.model} ({m.provider}): {formatUsd(m.cost_usd)}
+                        </div>
+                    ))}
+                </div>
+            ))}
+            <div>
+                <label className="text-xs text-white/50 block mb-1">Monthly spending cap for cloud models (USD)</label>
+                <input
+                    type="number"
+                    min="0"
+                    step="0.5"
+                    className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                    defaultValue={usage.monthly_cap_usd || ''}
+                    onBlur={(e) => Number(e.target.value || 0) !== usage.monthly_cap_usd && saveCap(e.target.value)}
+                    placeholder="No cap"
+                />
+            </div>
+            {spend && spend.monthly_cap_usd > 0 && (
+                <div className={`text-xs ${spend.cap_reached ? 'text-red-400' : 'text-white/50'}`}>
+                    {formatUsd(spend.month_cost_usd)} of {formatUsd(spend.monthly_cap_usd)} spent this month
+                    {spend.cap_reached && ' — cloud models are paused until next month'}
+                </div>
+            )}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+function ResponseCacheSettings() {
+    const [cache, setCache] = useState<any>(null);
+    const [stats, setStats] = useState<{ entries: number; hits: number; misses: number } | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    const refresh = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setStats(await invoke('get_response_cache_stats'));
+        } catch (error) {
+            console.error('Failed to load response cache stats:', error);
+        }
+    };
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setCache(settings.response_cache);
+            } catch (error) {
+                console.error('Failed to load response cache settings:', error);
+            }
+        };
+        load();
+        refresh();
+    }, []);
+
+    const save = async (key: string, value: unknown) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `response_cache.${key}`, value });
+            setCache({ ...cache, [key]: value });
+            setMessage(null);
+            await refresh();
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    const clear = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('clear_response_cache');
+            await refresh();
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    if (!cache) return null;
+
+    return (
+        <>
+            <label className="flex items-center gap-2 text-sm">
+                <input type="checkbox" checked={cache.enabled} onChange={(e) => save('enabled', e.target.checked)} />
+                Reuse replies to short repeated questions
+            </label>
+            {cache.enabled && (
+                <>
+                    <div>
+                        <label className="text-xs text-white/50 block mb-1">Keep replies for (seconds)</label>
+                        <input
+                            type="number"
+                            min="1"
+                            className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                            defaultValue={cache.ttl_secs}
+                            onBlur={(e) => Number(e.target.value) !== cache.ttl_secs && save('ttl_secs', Number(e.target.value))}
+                        />
+                    </div>
+                    {stats && (
+                        <div className="flex items-center justify-between text-xs text-white/60">
+                            <span>{stats.entries} cached, {stats.hits} hits, {stats.misses} misses</span>
+                            <button className="px-2 py-1 bg-white/10 hover:bg-white/20 rounded" onClick={clear}>Clear</button>
+                        </div>
+                    )}
+                </>
+            )}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+function PersonaSettings() {
+    const [personas, setPersonas] = useState<{ active: string; list: Persona[] }>({ active: '', list: [] });
+    const [draft, setDraft] = useState<Persona | null>(null);
+    const [importPath, setImportPath] = useState('');
+    const [message, setMessage] = useState<string | null>(null);
+    const browserVoices = typeof window !== 'undefined' && window.speechSynthesis ? window.speechSynthesis.getVoices() : [];
+
+    const refresh = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setPersonas(await invoke('get_personas'));
+        } catch (error) {
+            console.error('Failed to load personas:', error);
+        }
+    };
+
+    useEffect(() => {
+        refresh();
+    }, []);
+
+    const run = async (command: string, args: Record<string, unknown>, done?: (result: any) => string | null) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const result = await invoke(command, args);
+            setMessage(done ? done(result) : null);
+            await refresh();
+            return true;
+        } catch (error) {
+            setMessage(describeError(error));
+            return false;
+        }
+    };
+
+    const saveDraft = async () => {
+        if (!draft) return;
+        const existing = personas.list.some(p => p.id === draft.id && draft.id !== '');
+        if (await run(existing ? 'update_persona' : 'create_persona', { persona: draft })) setDraft(null);
+    };
+
+    const field = (name: keyof Persona, placeholder: string) => draft && (
+        <input
+            className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+            value={String(draft[name] ?? '')}
+            onChange={(e) => setDraft({ ...draft, [name]: e.target.value })}
+            placeholder={placeholder}
+        />
+    );
+
+    return (
+        <>
+            <select
+                className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                value={personas.active}
+                onChange={(e) => run('switch_persona', { id: e.target.value || null })}
+            >
+                <option value="">No persona (use the AI Provider settings)</option>
+                {personas.list.map(persona => (
+                    <option key={persona.id} value={persona.id}>{persona.name}</option>
+                ))}
+            </select>
+            {personas.list.map(persona => (
+                <div key={persona.id} className="flex items-center justify-between text-xs bg-white/5 rounded px-2 py-1">
+                    <div className="truncate" title={persona.description}>
+                        {persona.name}
+                        {persona.model && <span className="text-white/40"> · {persona.model}</span>}
+                    </div>
+                    <div className="flex gap-2 shrink-0">
+                        <button className="cyber-button text-xs" onClick={() => setDraft({ ...persona })}>Edit</button>
+                        <button className="cyber-button text-xs" onClick={() => run('export_personas', { ids: [persona.id] }, (path) => `Saved to ${path}`)}>
+                            Export
+                        </button>
+                        <button className="cyber-button text-xs" onClick={() => run('delete_persona', { id: persona.id })}>Delete</button>
+                    </div>
+                </div>
+            ))}
+            {draft ? (
+                <div className="space-y-2 border border-white/10 rounded p-2">
+                    {field('name', 'Name, e.g. Pirate')}
+                    {field('description', 'What this persona is like')}
+                    <textarea
+                        className="w-full h-28 bg-white/10 border border-white/20 rounded px-3 py-2 text-xs font-mono"
+                        value={draft.system_prompt}
+                        onChange={(e) => setDraft({ ...draft, system_prompt: e.target.value })}
+                        placeholder="System prompt; leave empty to keep the current one"
+                    />
+                    {field('model', 'Model; leave empty to keep the current one')}
+                    <input
+                        type="number"
+                        min={0}
+                        max={2}
+                        step={0.1}
+                        className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                        value={draft.temperature ?? ''}
+                        onChange={(e) => setDraft({ ...draft, temperature: e.target.value === '' ? null : Number(e.target.value) })}
+                        placeholder="Temperature; leave empty to keep the current one"
+                    />
+                    {field('voice_id', 'ElevenLabs voice ID')}
+                    <select
+                        className="w-full bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                        value={draft.browser_voice}
+                        onChange={(e) => setDraft({ ...draft, browser_voice: e.target.value })}
+                    >
+                        <option value="">Default system voice</option>
+                        {browserVoices.map(voice => (
+                            <option key={voice.name} value={voice.name}>{voice.name} ({voice.lang})</option>
+                        ))}
+                    </select>
+                    <div className="flex gap-2">
+                        <button className="cyber-button text-xs" disabled={!draft.name.trim()} onClick={saveDraft}>Save Persona</button>
+                        <button className="cyber-button text-xs" onClick={() => setDraft(null)}>Cancel</button>
+                    </div>
+                </div>
+            ) : (
+                <button className="w-full cyber-button text-sm" onClick={() => setDraft({ ...EMPTY_PERSONA })}>
+                    New Persona
+                </button>
+            )}
+            <div className="flex gap-2">
+                <input
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                    value={importPath}
+                    onChange={(e) => setImportPath(e.target.value)}
+                    placeholder="Path to a persona file"
+                />
+                <button
+                    className="cyber-button text-sm"
+                    disabled={!importPath.trim()}
+                    onClick={() => run('import_personas', { path: importPath }, (imported) => {
+                        setImportPath('');
+                        return `Imported ${imported.length} persona(s)`;
+                    })}
+                >
+                    Import
+                </button>
+            </div>
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </>
+    );
+}
+
+interface OllamaModel {
+    name: string;
+    size: number;
+    parameter_size: string;
+    quantization: string;
+}
+
+interface PullProgress {
+    model: string;
+    status: string;
+    completed?: number;
+    total?: number;
+}
+
+function OllamaModels({ current, onUse }: { current: string; onUse: (name: string) => void }) {
+    const [models, setModels] = useState<OllamaModel[] | null>(null);
+    const [pullName, setPullName] = useState('');
+    const [progress, setProgress] = useState<PullProgress | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    const refresh = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setModels(await invoke<OllamaModel[]>('list_ollama_models'));
+            setMessage(null);
+        } catch (error) {
+            setModels(null);
+            setMessage(describeError(error));
+        }
+    };
+
+    useEffect(() => {
+        refresh();
+        let unlisten: (() => void) | undefined;
+        const setup = async () => {
+            const { listen } = await import('@tauri-apps/api/event');
+            unlisten = await listen<PullProgress>('ollama-pull-progress', (event) => setProgress(event.payload));
+        };
+        setup();
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    const pull = async () => {
+        const model = pullName.trim();
+        if (!model) return;
+        setProgress({ model, status: 'starting' });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('pull_ollama_model', { model });
+            setPullName('');
+            await refresh();
+        } catch (error) {
+            setMessage(describeError(error));
+        } finally {
+            setProgress(null);
+        }
+    };
+
+    const remove = async (model: string) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('delete_ollama_model', { model });
+            await refresh();
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    const gb = (bytes: number) => (bytes / (1024 * 1024 * 1024)).toFixed(1);
+    const percent = progress?.total ? Math.round(((progress.completed ?? 0) / progress.total) * 100) : null;
+
+    return (
+        <div className="space-y-2">
+            <label className="text-xs text-white/50 block">Installed Models</label>
+            {models && models.length === 0 && <div className="text-xs text-white/50">No models installed yet</div>}
+            {models?.map((model) => (
+                <div key={model.name} className="flex items-center justify-between text-xs bg-white/5 rounded px-2 py-1">
+                    <div className="truncate">
+                        {model.name}
+                        <span className="text-white/40"> {[model.parameter_size, model.quantization, `${gb(model.size)} GB`].filter(Boolean).join(' · ')}</span>
+                    </div>
+                    <div className="flex gap-2 shrink-0">
+                        {model.name === current ? (
+                            <span className="text-cyber-cyan">In use</span>
+                        ) : (
+                            <button className="cyber-button text-xs" onClick={() => onUse(model.name)}>Use</button>
+                        )}
+                        <button className="cyber-button text-xs" onClick={() => remove(model.name)}>Delete</button>
+                    </div>
+                </div>
+            ))}
+            <div className="flex gap-2">
+                <input
+                    className="flex-1 bg-white/10 border border-white/20 rounded px-3 py-2 text-sm"
+                    value={pullName}
+                    onChange={(e) => setPullName(e.target.value)}
+                    onKeyDown={(e) => e.key === 'Enter' && pull()}
+                    placeholder="Model to download, e.g. llama3.2"
+                    disabled={!!progress}
+                />
+                <button className="cyber-button text-sm" onClick={pull} disabled={!!progress || !pullName.trim()}>
+                    Pull
+                </button>
+            </div>
+            {progress && (
+                <div className="text-xs text-white/50">
+                    {progress.model}: {progress.status}{percent !== null && ` (${percent}%)`}
+                    {percent !== null && (
+                        <div className="h-1 bg-white/10 rounded mt-1">
+                            <div className="h-1 bg-cyber-cyan rounded" style={{ width: `${percent}%` }} />
+                        </div>
+                    )}
+                </div>
+            )}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+        </div>
+    );
+}
+
+interface WebAction {
+    name: string;
+    label: string;
+    phrases: string[];
+    url: string;
+}
+
+function WebActionsSettings() {
+    const [actions, setActions] = useState<WebAction[]>([]);
+    const [message, setMessage] = useState<string | null>(null);
+
+    const load = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            setActions(await invoke<WebAction[]>('get_web_actions'));
+        } catch (error) {
+            console.error('Failed to load web actions:', error);
+        }
+    };
+
+    useEffect(() => {
+        load();
+    }, []);
+
+    const edit = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const path = await invoke<string>('open_web_actions_file');
+            setMessage(`Editing ${path}. Changes apply the next time you ask.`);
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    return (
+        <>
+            <div className="text-xs text-white/50 space-y-1 max-h-40 overflow-y-auto">
+                {actions.map((action) => (
+                    <div key={action.name} className="truncate" title={action.url}>
+                        "{action.phrases[0]}" — {action.label || action.name}
+                    </div>
+                ))}
+            </div>
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <div className="flex gap-2">
+                <button className="cyber-button text-xs" onClick={edit}>
+                    Edit Actions
+                </button>
+                <button className="cyber-button text-xs" onClick={load}>
+                    Reload
+                </button>
+            </div>
+        </>
+    );
+}
+
+interface WebhookTarget {
+    name: string;
+    enabled: boolean;
+    kind: 'ntfy' | 'slack' | 'ifttt' | 'generic';
+    url: string;
+    token: string;
+}
+
+function WebhookSettings() {
+    const [targets, setTargets] = useState<WebhookTarget[] | null>(null);
+    const [message, setMessage] = useState<string | null>(null);
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setTargets(settings.webhooks.targets);
+            } catch (error) {
+                console.error('Failed to load webhook settings:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const save = async (next: WebhookTarget[]) => {
+        const previous = targets;
+        setTargets(next);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: 'webhooks.targets', value: next });
+            setMessage(null);
+        } catch (error) {
+            setTargets(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const update = (index: number, field: keyof WebhookTarget, value: any) =>
+        targets && save(targets.map((t, i) => (i === index ? { ...t, [field]: value } : t)));
+
+    const add = () => {
+        if (!targets) return;
+        const name = targets.some((t) => t.name === 'Phone') ? `Phone ${targets.length + 1}` : 'Phone';
+        // Starts off until it has a URL, so saving it passes validation
+        save([...targets, { name, enabled: false, kind: 'ntfy', url: '', token: '' }]);
+    };
+
+    const test = async (name: string) => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('test_webhook', { name });
+            setMessage(`Sent a test notification to ${name}`);
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+    };
+
+    if (!targets) return null;
+
+    return (
+        <>
+            {targets.map((target, index) => (
+                <div key={target.name} className="space-y-2 border-b border-white/10 pb-3">
+                    <div className="flex gap-2">
+                        <input
+                            type="text"
+                            className="flex-1 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                            defaultValue={target.name}
+                            onBlur={(e) => e.target.value !== target.name && update(index, 'name', e.target.value)}
+                            placeholder="Name"
+                        />
+                        <select
+                            className="bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                            value={target.kind}
+                            onChange={(e) => update(index, 'kind', e.target.value)}
+                        >
+                            <option value="ntfy">ntfy</option>
+                            <option value="slack">Slack</option>
+                            <option value="ifttt">IFTTT</option>
+                            <option value="generic">JSON webhook</option>
+                        </select>
+                        <button className="text-xs text-white/40 hover:text-white/70" onClick={() => save(targets.filter((_, i) => i !== index))}>
+                            ✕
+                        </button>
+                    </div>
+                    <input
+                        type="password"
+                        className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                        defaultValue={target.url}
+                        onBlur={(e) => e.target.value !== target.url && update(index, 'url', e.target.value)}
+                        placeholder={target.kind === 'ntfy' ? 'https://ntfy.sh/your-topic' : 'Webhook URL'}
+                    />
+                    {(target.kind === 'ntfy' || target.kind === 'generic') && (
+                        <input
+                            type="password"
+                            className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                            defaultValue={target.token}
+                            onBlur={(e) => e.target.value !== target.token && update(index, 'token', e.target.value)}
+                            placeholder="Access token (optional)"
+                        />
+                    )}
+                    <div className="flex items-center gap-2">
+                        <div className="flex-1">
+                            <ToggleItem label="Send notifications here" enabled={target.enabled} onToggle={() => update(index, 'enabled', !target.enabled)} />
+                        </div>
+                        <button className="cyber-button text-xs" disabled={!target.enabled} onClick={() => test(target.name)}>
+                            Test
+                        </button>
+                    </div>
+                </div>
+            ))}
+            {message && <div className="text-xs text-white/50">{message}</div>}
+            <button className="cyber-button text-xs" onClick={add}>
+                Add Webhook
+            </button>
+        </>
+    );
+}
+
+interface SyncSummary {
+    synced_at: string;
+    routines_received: number;
+    routines_sent: number;
+    conversation_received: boolean;
+    conversation_sent: boolean;
+    routines_held: string[];
+    aliases_received: number;
+    aliases_sent: number;
+    notes_received: boolean;
+    notes_sent: boolean;
+    devices: string[];
+}
+
+interface SyncStatus {
+    enabled: boolean;
+    last: SyncSummary | null;
+    last_error: string | null;
+}
+
+function describeSync(summary: SyncSummary): string {
+    const parts = [`${summary.routines_received} routines in`, `${summary.routines_sent} out`];
+    if (summary.conversation_received) parts.push('conversation in');
+    if (summary.conversation_sent) parts.push('conversation out');
+    if (summary.aliases_received) parts.push(`${summary.aliases_received} app names in`);
+    if (summary.aliases_sent) parts.push(`${summary.aliases_sent} app names out`);
+    if (summary.notes_received) parts.push('notes in');
+    if (summary.notes_sent) parts.push('notes out');
+    const devices = summary.devices.length ? ` with ${summary.devices.join(', ')}` : '';
+    const held = summary.routines_held.length
+        ? `. Turned off until you turn them on here, since they need a permission: ${summary.routines_held.join(', ')}`
+        : '';
+    return `Synced${devices} at ${new Date(summary.synced_at).toLocaleTimeString()}: ${parts.join(', ')}${held}`;
+}
+
+/** The `sync` settings section */
+function SyncSettings() {
+    const [sync, setSync] = useState<any>(null);
+    const [message, setMessage] = useState<string | null>(null);
+    const [busy, setBusy] = useState(false);
+
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const { listen } = await import('@tauri-apps/api/event');
+                const settings: any = await invoke('load_settings');
+                setSync(settings.sync);
+                const status: SyncStatus = await invoke('get_sync_status');
+                setMessage(status.last_error ?? (status.last ? describeSync(status.last) : null));
+                unlisten = await listen<SyncSummary>('sync-completed', (event) => setMessage(describeSync(event.payload)));
+            } catch (error) {
+                console.error('Failed to load sync settings:', error);
+            }
+        };
+        load();
+        return () => unlisten?.();
+    }, []);
+
+    const save = async (field: string, value: any) => {
+        const previous = sync;
+        setSync({ ...sync, [field]: value });
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `sync.${field}`, value });
+        } catch (error) {
+            setSync(previous);
+            setMessage(String(error));
+        }
+    };
+
+    const syncNow = async () => {
+        setBusy(true);
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            const summary: SyncSummary = await invoke('sync_now');
+            setMessage(describeSync(summary));
+        } catch (error) {
+            setMessage(describeError(error));
+        }
+        setBusy(false);
+    };
+
+    if (!sync) return null;
+
+    const input = (field: string, placeholder: string, type = 'text') => (
+        <input
+            type={type}
+            className="w-full bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+            defaultValue={sync[field]}
+            onBlur={(e) => e.target.value !== sync[field] && save(field, e.target.value)}
+            placeholder={placeholder}
+        />
+    );
+
+    return (
+        <>
+            <div className="flex items-center justify-between gap-3">
+                <span className="text-sm text-white/70">Sync through</span>
+                <select
+                    className="w-48 bg-white/10 border border-white/20 rounded px-2 py-1 text-sm"
+                    value={sync.backend}
+                    onChange={(e) => save('backend', e.target.value)}
+                >
+                    <option value="folder">Shared folder</option>
+                    <option value="webdav">WebDAV server</option>
+                    <option value="s3">S3 bucket</option>
+                </select>
+            </div>
+
+            {sync.backend === 'folder' && input('folder', 'Folder both machines can reach (network drive, Syncthing, Dropbox)')}
+            {sync.backend === 'webdav' && (
+                <>
+                    {input('webdav_url', 'https://nas.local/dav/astral/')}
+                    <div className="flex gap-2">
+                        {input('webdav_username', 'Username')}
+                        {input('webdav_password', 'Password', 'password')}
+                    </div>
+                </>
+            )}
+            {sync.backend === 's3' && (
+                <>
+                    {input('s3_endpoint', 'https://s3.eu-central-1.amazonaws.com')}
+                    <div className="flex gap-2">
+                        {input('s3_bucket', 'Bucket')}
+                        {input('s3_region', 'Region')}
+                    </div>
+                    <div className="flex gap-2">
+                        {input('s3_access_key_id', 'Access key ID')}
+                        {input('s3_secret_access_key', 'Secret access key', 'password')}
+                    </div>
+                </>
+            )}
+
+            {input('device_name', 'Name for this machine (defaults to the host name)')}
+            <ToggleItem label="Sync the conversation" enabled={sync.conversations} onToggle={() => save('conversations', !sync.conversations)} />
+            <ToggleItem label="Sync routines" enabled={sync.routines} onToggle={() => save('routines', !sync.routines)} />
+            <ToggleItem label="Sync notes, lists and learned app names" enabled={sync.memories} onToggle={() => save('memories', !sync.memories)} />
+            <ToggleItem label="Enable sync" enabled={sync.enabled} onToggle={() => save('enabled', !sync.enabled)} />
+
+            {message && <div className="text-xs text-white/50">{message}</div>}
+
+            <button className="cyber-button text-xs" disabled={!sync.enabled || busy} onClick={syncNow}>
+                Sync Now
+            </button>
+        </>
+    );
+}
+
+/** `general.privacy_mode`, also switchable from the tray */
+function PrivacyModeToggle() {
+    const [enabled, setEnabled] = useState(false);
+
+    useEffect(() => {
+        let unlisten: (() => void) | undefined;
+        const setup = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setEnabled(settings.general.privacy_mode);
+                const { listen } = await import('@tauri-apps/api/event');
+                unlisten = await listen<boolean>('privacy-mode-changed', (event) => setEnabled(event.payload));
+            } catch (error) {
+                console.error('Failed to load privacy mode:', error);
+            }
+        };
+        setup();
+        return () => { if (unlisten) unlisten(); };
+    }, []);
+
+    const toggle = async () => {
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: 'general.privacy_mode', value: !enabled });
+            setEnabled(!enabled);
+        } catch (error) {
+            console.error('Failed to change privacy mode:', error);
+        }
+    };
+
+    return (
+        <>
+            <ToggleItem label="Privacy Mode (offline only)" enabled={enabled} onToggle={toggle} />
+            <div className="text-xs text-white/50">
+                Blocks OpenAI, Claude, ElevenLabs and any non-local Whisper server. Use Ollama and a local Whisper server while it's on.
+            </div>
+        </>
+    );
+}
+
+const CAPABILITIES: { key: string; label: string }[] = [
+    { key: 'system_command', label: 'Run system commands from routines' },
+    { key: 'file_deletion', label: 'Delete files (recycle bin, cleanup)' },
+    { key: 'keyboard_injection', label: 'Type into other apps (dictation)' },
+    { key: 'power', label: 'Lock, sleep, shut down and restart' },
+    { key: 'email_changes', label: 'Mark email as read' },
+    { key: 'close_apps', label: 'Close apps and end processes' },
+    { key: 'skills', label: 'Run installed skills' },
+];
+
+function PermissionSettings() {
+    const [granted, setGranted] = useState<Record<string, boolean>>({});
+
+    useEffect(() => {
+        const load = async () => {
+            try {
+                const { invoke } = await import('@tauri-apps/api/core');
+                const settings: any = await invoke('load_settings');
+                setGranted(settings.permissions);
+            } catch (error) {
+                console.error('Failed to load permissions:', error);
+            }
+        };
+        load();
+    }, []);
+
+    const toggle = async (key: string) => {
+        const next = !granted[key];
+        try {
+            const { invoke } = await import('@tauri-apps/api/core');
+            await invoke('update_setting', { key: `permissions.${key}`, value: next });
+            setGranted(current => ({ ...current, [key]: next }));
+        } catch (error) {
+            console.error('Failed to update permission:', error);
+        }
+    };
+
+    return (
+        <div className="space-y-3">
+            {CAPABILITIES.map(({ key, label }) => (
+                <ToggleItem key={key} label={label} enabled={!!granted[key]} onToggle={() => toggle(key)} />
+            ))}
+            <div className="text-xs text-white/50">
+                These are off until you allow them. Voice commands and routines that need one are refused.
+            </div>
+        </div>
+    );
+}
+
 function ToggleItem({ label, enabled, onToggle }: { label: string; enabled: boolean; onToggle: () => void }) {
     return (
         <div className="flex items-center justify-between cursor-pointer hover:bg-white/5 p-2 rounded transition-colors" onClick={onToggle}>
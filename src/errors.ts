@@ -0,0 +1,62 @@
+// Errors from backend commands. Commands that return an AstralError send
+// `{ code, message, detail }` (and `retry_after_secs` when a provider said
+// how long to wait); older ones still reject with a plain string.
+
+export type ErrorCode =
+    | 'not_configured'
+    | 'service_unavailable'
+    | 'network'
+    | 'timeout'
+    | 'unauthorized'
+    | 'rate_limited'
+    | 'provider_error'
+    | 'invalid_input'
+    | 'not_found'
+    | 'permission_denied'
+    | 'muted'
+    | 'cancelled'
+    | 'privacy_mode'
+    | 'spending_cap'
+    | 'internal';
+
+export interface AstralError {
+    code: ErrorCode;
+    message: string;
+    detail?: string;
+    retry_after_secs?: number;
+}
+
+// What the user can do about each kind of error, per language
+const HINTS: Record<string, Partial<Record<ErrorCode, string>>> = {
+    en: {
+        not_configured: 'You can set this up in Settings.',
+        service_unavailable: 'Check that Ollama or the Whisper server is running.',
+        network: 'Check your internet connection.',
+        timeout: 'The service took too long to answer; try again.',
+        unauthorized: 'Check the API key in Settings.',
+        rate_limited: 'Wait a moment before trying again.',
+        muted: 'Unmute the microphone to talk to me.',
+        privacy_mode: 'Switch to Ollama and local Whisper, or turn privacy mode off.',
+        spending_cap: 'Raise the monthly cap in Settings, or switch to Ollama until next month.',
+    },
+};
+
+export function isAstralError(error: unknown): error is AstralError {
+    return typeof error === 'object' && error !== null && 'code' in error && 'message' in error;
+}
+
+export function errorCode(error: unknown): ErrorCode {
+    return isAstralError(error) ? error.code : 'internal';
+}
+
+// A message fit to show or speak, with a hint on what to do when there is one
+export function describeError(error: unknown, language = 'en'): string {
+    if (!isAstralError(error)) {
+        return String(error);
+    }
+    const hint = (HINTS[language] ?? HINTS.en)[error.code] ?? HINTS.en[error.code];
+    if (error.code === 'rate_limited' && error.retry_after_secs && language === 'en') {
+        return `${error.message.replace(/[.!]$/, '')}. Try again in ${error.retry_after_secs} s.`;
+    }
+    return hint ? `${error.message.replace(/[.!]$/, '')}. ${hint}` : error.message;
+}
@@ -2,7 +2,7 @@ import os
 import sys
 import wave
 import subprocess
-from fastapi import FastAPI, File, UploadFile, HTTPException
+from fastapi import FastAPI, File, Form, UploadFile, HTTPException
 from fastapi.responses import JSONResponse
 import uvicorn
 
@@ -12,7 +12,7 @@ WHISPER_EXECUTABLE = "./main.exe" if os.name == "nt" else "./main"
 MODEL_PATH = "models/ggml-small.en.bin"  # Changed from base.en
 
 @app.post("/transcribe")
-async def transcribe_audio(file: UploadFile = File(...)):
+async def transcribe_audio(file: UploadFile = File(...), language: str = Form(None)):
     try:
         # Save uploaded file
         audio_path = "temp_audio.wav"
@@ -23,8 +23,12 @@ async def transcribe_audio(file: UploadFile = File(...)):
         print(f"[DEBUG] Received {len(content)} bytes")
         
         # Run Whisper
+        args = [WHISPER_EXECUTABLE, "-m", MODEL_PATH, "-f", audio_path, "-nt"]
+        # ASTRAL sends the user's language; .en models only do English
+        if language and not MODEL_PATH.endswith(".en.bin"):
+            args += ["-l", language]
         result = subprocess.run(
-            [WHISPER_EXECUTABLE, "-m", MODEL_PATH, "-f", audio_path, "-nt"],
+            args,
             capture_output=True,
             text=True
         )